@@ -0,0 +1,59 @@
+use criterion::{
+    black_box,
+    criterion_group,
+    criterion_main,
+    Criterion,
+};
+use naumachia::scripts::{
+    cache::{
+        CachedBlueprint,
+        ScriptCache,
+    },
+    plutus_validator::PlutusValidator,
+    raw_script::BlueprintFile,
+    ScriptError,
+    ScriptResult,
+};
+
+const BLUEPRINT_JSON: &str =
+    include_str!("../sample-dApps/checking_account/checking/plutus.json");
+const VALIDATOR_NAME: &str = "checking_account_validator.spend";
+
+static BLUEPRINT: CachedBlueprint = CachedBlueprint::new(BLUEPRINT_JSON);
+static VALIDATOR: ScriptCache<PlutusValidator<(), ()>> = ScriptCache::new();
+
+// What the sample endpoints did before caching: parse the whole blueprint on every call
+fn validator_per_call() -> ScriptResult<PlutusValidator<(), ()>> {
+    let blueprint: BlueprintFile = serde_json::from_str(BLUEPRINT_JSON)
+        .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))?;
+    let validator_blueprint =
+        blueprint
+            .get_validator(VALIDATOR_NAME)
+            .ok_or(ScriptError::FailedToConstruct(format!(
+                "Validator not listed in Blueprint: {:?}",
+                VALIDATOR_NAME
+            )))?;
+    PlutusValidator::from_blueprint(validator_blueprint)
+        .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))
+}
+
+fn validator_cached() -> ScriptResult<PlutusValidator<(), ()>> {
+    VALIDATOR.get_or_try_init(|| {
+        PlutusValidator::from_blueprint(BLUEPRINT.validator(VALIDATOR_NAME)?)
+            .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))
+    })
+}
+
+fn construct_validator(c: &mut Criterion) {
+    let mut group = c.benchmark_group("construct_validator");
+    group.bench_function("parse_per_call", |b| {
+        b.iter(|| black_box(validator_per_call().unwrap()))
+    });
+    group.bench_function("cached", |b| {
+        b.iter(|| black_box(validator_cached().unwrap()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, construct_validator);
+criterion_main!(benches);
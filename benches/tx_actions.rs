@@ -0,0 +1,202 @@
+use criterion::{
+    black_box,
+    criterion_group,
+    criterion_main,
+    BatchSize,
+    Criterion,
+};
+use naumachia::{
+    output::{
+        Output,
+        UnbuiltOutput,
+    },
+    policy_id::PolicyId,
+    scripts::{
+        context::TxContext,
+        plutus_validator::plutus_data::{
+            Constr,
+            PlutusData,
+        },
+        ExecutionCost,
+        MintingPolicy,
+        ScriptResult,
+        Validator,
+    },
+    transaction::TxActions,
+    values::Values,
+    Address,
+    Network,
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+};
+
+const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+const BOB: &str = "addr_test1qpuy2q9xel76qxdw8r29skldzc876cdgg9cugfg7mwh0zvpg3292mxuf3kq7nysjumlxjrlsfn9tp85r0l54l29x3qcs7nvyfm";
+
+struct NoopScript;
+
+impl Validator<PlutusData, ()> for NoopScript {
+    fn execute(&self, _: PlutusData, _: (), _: TxContext) -> ScriptResult<ExecutionCost> {
+        Ok(ExecutionCost::default())
+    }
+
+    fn address(&self, _network: Network) -> ScriptResult<Address> {
+        Ok(Address::from_bech32(BOB).unwrap())
+    }
+
+    fn script_hex(&self) -> ScriptResult<String> {
+        Ok(String::new())
+    }
+}
+
+impl MintingPolicy<()> for NoopScript {
+    fn execute(&self, _: (), _: TxContext) -> ScriptResult<ExecutionCost> {
+        Ok(ExecutionCost::default())
+    }
+
+    fn id(&self) -> ScriptResult<String> {
+        Ok(hex::encode([1, 2, 3, 4, 5]))
+    }
+
+    fn script_hex(&self) -> ScriptResult<String> {
+        Ok(String::new())
+    }
+}
+
+// Roughly the size of a datum holding a few keys and amounts
+fn datum() -> PlutusData {
+    let entries = (0..16)
+        .map(|i| {
+            PlutusData::Constr(Constr {
+                constr: 0,
+                fields: vec![
+                    PlutusData::BoundedBytes(vec![i; 28]),
+                    PlutusData::BigInt((i as i64 * 1_000_000).into()),
+                ],
+            })
+        })
+        .collect();
+    PlutusData::Array(entries)
+}
+
+fn values() -> Values {
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, 10_000_000);
+    for i in 0..8 {
+        values.add_one_value(&PolicyId::native_token(&hex::encode([i; 28]), &None), 1);
+    }
+    values
+}
+
+// Mint a token, redeem a script output, and lock the result back at the script
+fn mint_redeem_init(
+    output: Output<PlutusData>,
+    datum: PlutusData,
+    values: Values,
+) -> TxActions<PlutusData, ()> {
+    let script_address = Address::from_bech32(BOB).unwrap();
+    let alice = Address::from_bech32(ALICE).unwrap();
+    TxActions::v2()
+        .with_mint(1, Some(b"token".to_vec()), (), Box::new(NoopScript))
+        .with_script_redeem(output, (), Box::new(NoopScript))
+        .with_script_init(datum, values, script_address)
+        .with_transfer(2_000_000, alice.clone(), PolicyId::Lovelace)
+        .with_transfer(3_000_000, alice, PolicyId::Lovelace)
+}
+
+fn build_unbuilt_tx(c: &mut Criterion) {
+    let script_address = Address::from_bech32(BOB).unwrap();
+    let output = Output::new_validator(vec![0; 32], 0, script_address, values(), datum());
+    let (datum, values) = (datum(), values());
+    c.bench_function("mint_redeem_init_to_unbuilt_tx", |b| {
+        b.iter_batched(
+            || (output.clone(), datum.clone(), values.clone()),
+            |(output, datum, values)| {
+                black_box(
+                    mint_redeem_init(output, datum, values)
+                        .to_unbuilt_tx()
+                        .unwrap(),
+                )
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+// Transfers of lovelace and a few tokens, spread over a handful of recipients
+fn transfers() -> Vec<(u64, Address, PolicyId)> {
+    let recipients = [ALICE, BOB].map(|address| Address::from_bech32(address).unwrap());
+    (0..64u8)
+        .map(|i| {
+            let policy_id = match i % 4 {
+                0 => PolicyId::Lovelace,
+                token => PolicyId::native_token(&hex::encode([token; 28]), &None),
+            };
+            (1_000_000, recipients[i as usize % 2].clone(), policy_id)
+        })
+        .collect()
+}
+
+// How `to_unbuilt_tx` grouped transfers before: keyed by bech32, with the values of each
+// output copied out and its address parsed back
+fn outputs_grouped_by_bech32(
+    transfers: Vec<(u64, Address, PolicyId)>,
+) -> Vec<UnbuiltOutput<PlutusData>> {
+    let mut by_owner: HashMap<String, RefCell<Values>> = HashMap::new();
+    for (amount, owner, policy_id) in transfers {
+        let owner = owner.to_bech32().unwrap();
+        by_owner
+            .entry(owner)
+            .or_default()
+            .borrow_mut()
+            .add_one_value(&policy_id, amount);
+    }
+    by_owner
+        .into_iter()
+        .map(|(owner, values)| {
+            let values = values.borrow().clone().vec().iter().fold(
+                Values::default(),
+                |mut acc, (policy_id, amount)| {
+                    acc.add_one_value(policy_id, *amount);
+                    acc
+                },
+            );
+            UnbuiltOutput::new_wallet(Address::from_bech32(&owner).unwrap(), values)
+        })
+        .collect()
+}
+
+// Before and after grouping transfers by address bytes. `after` also pays for building
+// the `TxActions`, so it can only understate the gain
+fn group_transfers(c: &mut Criterion) {
+    let transfers = transfers();
+    let mut group = c.benchmark_group("group_transfers");
+    group.bench_function("before", |b| {
+        b.iter_batched(
+            || transfers.clone(),
+            |transfers| black_box(outputs_grouped_by_bech32(transfers)),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("after", |b| {
+        b.iter_batched(
+            || transfers.clone(),
+            |transfers| {
+                let actions = transfers.into_iter().fold(
+                    TxActions::<PlutusData, ()>::v2(),
+                    |actions, (amount, recipient, policy_id)| {
+                        actions.with_transfer(amount, recipient, policy_id)
+                    },
+                );
+                black_box(actions.to_unbuilt_tx().unwrap())
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, build_unbuilt_tx, group_transfers);
+criterion_main!(benches);
@@ -1 +0,0 @@
-pub mod one_shot;
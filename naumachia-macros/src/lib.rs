@@ -0,0 +1,209 @@
+#![warn(missing_docs)]
+
+//! Procedural macros for the Naumachia Smart-Contract Framework
+//!
+//! Use them through the re-exports in `naumachia`, e.g. `naumachia::cli::EndpointsCli`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{
+    format_ident,
+    quote,
+};
+use syn::{
+    parse_macro_input,
+    Attribute,
+    Data,
+    DeriveInput,
+    Field,
+    Fields,
+    GenericArgument,
+    Path,
+    PathArguments,
+    Type,
+};
+
+/// Derive a [clap](https://docs.rs/clap) subcommand for an `Endpoints` enum
+///
+/// For `enum MyEndpoints` this generates `enum MyEndpointsCli`, deriving
+/// `clap::Subcommand`, with one subcommand per variant and one `--flag` per field, plus
+/// `From<MyEndpointsCli> for MyEndpoints`. Doc comments become the help text.
+///
+/// Fields are parsed with the parsers in `naumachia::cli`:
+///
+/// - `OutputId` from `<tx hash>#<index>`
+/// - `Address` from bech32
+/// - `PubKeyHash` from hex
+/// - `PolicyId` from `lovelace` or `<policy id>[.<asset name>]`, both in hex
+/// - `u64` fields named `*lovelace*` or marked `#[cli(lovelace)]` from lovelace or
+///   amounts like `2.5ADA`
+///
+/// Any other parser can be chosen with `#[cli(parser = path::to::parser)]`. Everything
+/// else uses clap's default parsing. Only unit and struct variants are supported, and the
+/// crate using the derive must depend on `clap` 4.
+#[proc_macro_derive(EndpointsCli, attributes(cli))]
+pub fn derive_endpoints_cli(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_endpoints_cli(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_endpoints_cli(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "EndpointsCli can only be derived for enums",
+            ))
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "EndpointsCli doesn't support generic enums",
+        ))
+    }
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let cli_name = format_ident!("{}Cli", name);
+    let cli_doc = format!("Command line subcommands for [`{name}`]");
+
+    let mut cli_variants = Vec::new();
+    let mut conversions = Vec::new();
+    for variant in data.variants.iter() {
+        let variant_name = &variant.ident;
+        let docs = doc_attrs(&variant.attrs);
+        match &variant.fields {
+            Fields::Unit => {
+                cli_variants.push(quote! {
+                    #(#docs)*
+                    #variant_name
+                });
+                conversions.push(quote! {
+                    #cli_name::#variant_name => #name::#variant_name
+                });
+            }
+            Fields::Named(fields) => {
+                let mut cli_fields = Vec::new();
+                let mut field_names = Vec::new();
+                for field in fields.named.iter() {
+                    let field_name = &field.ident;
+                    let ty = &field.ty;
+                    let field_docs = doc_attrs(&field.attrs);
+                    let arg = match value_parser(field)? {
+                        Some(parser) => quote! { #[arg(long, value_parser = #parser)] },
+                        None => quote! { #[arg(long)] },
+                    };
+                    cli_fields.push(quote! {
+                        #(#field_docs)*
+                        #arg
+                        #field_name: #ty
+                    });
+                    field_names.push(field_name);
+                }
+                cli_variants.push(quote! {
+                    #(#docs)*
+                    #variant_name { #(#cli_fields),* }
+                });
+                conversions.push(quote! {
+                    #cli_name::#variant_name { #(#field_names),* } =>
+                        #name::#variant_name { #(#field_names),* }
+                });
+            }
+            Fields::Unnamed(_) => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "EndpointsCli only supports unit and struct variants",
+                ))
+            }
+        }
+    }
+
+    Ok(quote! {
+        #[doc = #cli_doc]
+        #[derive(::clap::Subcommand, Debug)]
+        #vis enum #cli_name {
+            #(#cli_variants),*
+        }
+
+        impl ::core::convert::From<#cli_name> for #name {
+            fn from(cli: #cli_name) -> Self {
+                match cli {
+                    #(#conversions),*
+                }
+            }
+        }
+    })
+}
+
+fn doc_attrs(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .collect()
+}
+
+// The parser for `field` chosen by `#[cli(...)]`, or else by its type and name
+fn value_parser(field: &Field) -> syn::Result<Option<TokenStream2>> {
+    let mut parser = None;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cli"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("lovelace") {
+                parser = Some(quote! { ::naumachia::cli::parse_lovelace });
+                Ok(())
+            } else if meta.path.is_ident("parser") {
+                let path: Path = meta.value()?.parse()?;
+                parser = Some(quote! { #path });
+                Ok(())
+            } else {
+                Err(meta.error("expected `lovelace` or `parser = ...`"))
+            }
+        })?;
+    }
+    if parser.is_some() {
+        return Ok(parser)
+    }
+
+    let type_name = match inner_type_name(&field.ty) {
+        Some(type_name) => type_name,
+        None => return Ok(None),
+    };
+    let is_lovelace = field
+        .ident
+        .as_ref()
+        .map(|ident| ident.to_string().contains("lovelace"))
+        .unwrap_or(false);
+    let parser = match type_name.as_str() {
+        "OutputId" => Some(quote! { ::naumachia::cli::parse_output_id }),
+        "Address" => Some(quote! { ::naumachia::cli::parse_address }),
+        "PubKeyHash" => Some(quote! { ::naumachia::cli::parse_pub_key_hash }),
+        "PolicyId" => Some(quote! { ::naumachia::cli::parse_policy_id }),
+        "u64" if is_lovelace => Some(quote! { ::naumachia::cli::parse_lovelace }),
+        _ => None,
+    };
+    Ok(parser)
+}
+
+// Name of the type, looking through `Option` since clap parses the inner value
+fn inner_type_name(ty: &Type) -> Option<String> {
+    let segment = match ty {
+        Type::Path(type_path) => type_path.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident == "Option" {
+        if let PathArguments::AngleBracketed(args) = &segment.arguments {
+            if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                return inner_type_name(inner)
+            }
+        }
+        return None
+    }
+    Some(segment.ident.to_string())
+}
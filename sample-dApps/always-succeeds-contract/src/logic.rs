@@ -1,6 +1,7 @@
 use crate::logic::script::get_script;
 use async_trait::async_trait;
 use naumachia::{
+    cli::EndpointsCli,
     ledger_client::LedgerClient,
     logic::{
         error::{
@@ -27,9 +28,15 @@ mod tests;
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct AlwaysSucceedsLogic;
 
-#[derive(Debug)]
+#[derive(Debug, EndpointsCli)]
 pub enum AlwaysSucceedsEndpoints {
-    Lock { amount: u64 },
+    /// Lock amount at script address
+    Lock {
+        /// Lovelace, or ADA like `2.5ADA`
+        #[cli(lovelace)]
+        amount: u64,
+    },
+    /// Claim locked Output, given as `<tx hash>#<index>`, at script address
     Claim { output_id: OutputId },
 }
 
@@ -1,12 +1,11 @@
 use always_succeeds_contract::logic::{
-    AlwaysSucceedsEndpoints,
+    AlwaysSucceedsEndpointsCli,
     AlwaysSucceedsLogic,
     AlwaysSucceedsLookupResponses,
     AlwaysSucceedsLookups,
 };
 use clap::Parser;
 use naumachia::{
-    output::OutputId,
     smart_contract::{
         SmartContract,
         SmartContractTrait,
@@ -23,10 +22,8 @@ struct Args {
 
 #[derive(clap::Subcommand, Debug)]
 enum ActionParams {
-    /// Lock amount at script address
-    Lock { amount: f64 },
-    /// Claim locked Output at script address
-    Claim { tx_hash: String, index: u64 },
+    #[clap(flatten)]
+    Endpoint(AlwaysSucceedsEndpointsCli),
     /// List all outputs locked at script address
     List { count: usize },
 }
@@ -40,20 +37,8 @@ async fn main() {
     let contract = SmartContract::new(logic, ledger_client);
 
     match args.action {
-        ActionParams::Lock { amount } => {
-            let tx_id = contract
-                .hit_endpoint(AlwaysSucceedsEndpoints::Lock {
-                    amount: (amount * 1_000_000.) as u64,
-                })
-                .await
-                .unwrap();
-            println!("TxId: {:?}", tx_id);
-        }
-        ActionParams::Claim { tx_hash, index } => {
-            let tx_hash_bytes = hex::decode(tx_hash).unwrap();
-            let output_id = OutputId::new(tx_hash_bytes, index);
-            let endpoint = AlwaysSucceedsEndpoints::Claim { output_id };
-            let tx_id = contract.hit_endpoint(endpoint).await.unwrap();
+        ActionParams::Endpoint(endpoint) => {
+            let tx_id = contract.hit_endpoint(endpoint.into()).await.unwrap();
             println!("TxId: {:?}", tx_id);
         }
         ActionParams::List { count } => {
@@ -66,10 +51,7 @@ async fn main() {
                     println!("Active contracts:");
                     for output in outputs {
                         println!("-------------------------------------");
-                        let id = output.id();
-                        let tx_hash = hex::encode(id.tx_hash());
-                        let index = id.index();
-                        println!("tx: {:?}, index: {:?}", tx_hash, index);
+                        println!("output: {}", output.id());
                         println!("{:?}", output.values());
                         println!("{:?}", output.datum());
                     }
@@ -0,0 +1,131 @@
+use naumachia::scripts::{
+    context::PubKeyHash,
+    plutus_validator::plutus_data::{
+        Constr,
+        PlutusData,
+    },
+};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Bid {
+    pub bidder: PubKeyHash,
+    pub amount: u64,
+}
+
+/// Auction of the native tokens held by its output, taking bids until `deadline_secs`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AuctionDatum {
+    pub seller: PubKeyHash,
+    pub min_bid: u64,
+    pub deadline_secs: i64,
+    pub highest_bid: Option<Bid>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AuctionRedeemer {
+    PlaceBid(Bid),
+    Close,
+}
+
+impl From<Bid> for PlutusData {
+    fn from(bid: Bid) -> Self {
+        PlutusData::Constr(Constr {
+            constr: 0,
+            fields: vec![bid.bidder.into(), bid.amount.into()],
+        })
+    }
+}
+
+impl TryFrom<PlutusData> for Bid {
+    type Error = ();
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        let PlutusData::Constr(Constr { constr: 0, fields }) = data else {
+            return Err(());
+        };
+        match fields.as_slice() {
+            [PlutusData::BoundedBytes(bidder), amount] => Ok(Bid {
+                bidder: PubKeyHash::new(bidder),
+                amount: u64::try_from(amount.clone()).map_err(|_| ())?,
+            }),
+            _ => Err(()),
+        }
+    }
+}
+
+// Aiken's `Option` is `Some` as constructor 0 and `None` as constructor 1
+impl From<AuctionDatum> for PlutusData {
+    fn from(datum: AuctionDatum) -> Self {
+        let highest_bid = match datum.highest_bid {
+            Some(bid) => PlutusData::Constr(Constr {
+                constr: 0,
+                fields: vec![bid.into()],
+            }),
+            None => PlutusData::Constr(Constr {
+                constr: 1,
+                fields: vec![],
+            }),
+        };
+        // The validator compares against the validity range, which is in milliseconds
+        PlutusData::Constr(Constr {
+            constr: 0,
+            fields: vec![
+                datum.seller.into(),
+                datum.min_bid.into(),
+                (datum.deadline_secs * 1000).into(),
+                highest_bid,
+            ],
+        })
+    }
+}
+
+impl TryFrom<PlutusData> for AuctionDatum {
+    type Error = ();
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        let PlutusData::Constr(Constr { constr: 0, fields }) = data else {
+            return Err(());
+        };
+        let [PlutusData::BoundedBytes(seller), min_bid, deadline, PlutusData::Constr(highest_bid)] =
+            fields.as_slice()
+        else {
+            return Err(());
+        };
+        let highest_bid = match (highest_bid.constr, highest_bid.fields.as_slice()) {
+            (0, [bid]) => Some(Bid::try_from(bid.clone())?),
+            (1, []) => None,
+            _ => return Err(()),
+        };
+        Ok(AuctionDatum {
+            seller: PubKeyHash::new(seller),
+            min_bid: u64::try_from(min_bid.clone()).map_err(|_| ())?,
+            deadline_secs: i64::try_from(deadline.clone()).map_err(|_| ())? / 1000,
+            highest_bid,
+        })
+    }
+}
+
+impl From<AuctionRedeemer> for PlutusData {
+    fn from(redeemer: AuctionRedeemer) -> Self {
+        let (constr, fields) = match redeemer {
+            AuctionRedeemer::PlaceBid(bid) => (0, vec![bid.into()]),
+            AuctionRedeemer::Close => (1, vec![]),
+        };
+        PlutusData::Constr(Constr { constr, fields })
+    }
+}
+
+impl TryFrom<PlutusData> for AuctionRedeemer {
+    type Error = ();
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        let PlutusData::Constr(Constr { constr, fields }) = data else {
+            return Err(());
+        };
+        match (constr, fields.as_slice()) {
+            (0, [bid]) => Ok(AuctionRedeemer::PlaceBid(Bid::try_from(bid.clone())?)),
+            (1, []) => Ok(AuctionRedeemer::Close),
+            _ => Err(()),
+        }
+    }
+}
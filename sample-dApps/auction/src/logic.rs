@@ -0,0 +1,527 @@
+use crate::{
+    datum::{
+        AuctionDatum,
+        AuctionRedeemer,
+        Bid,
+    },
+    scripts::auction_validator,
+};
+use async_trait::async_trait;
+use naumachia::{
+    address::enterprise_address,
+    cli::EndpointsCli,
+    ledger_client::LedgerClient,
+    logic::{
+        error::{
+            as_endpoint_err,
+            SCLogicError,
+            SCLogicResult,
+        },
+        SCLogic,
+    },
+    output::{
+        Output,
+        OutputId,
+    },
+    policy_id::PolicyId,
+    scripts::{
+        context::{
+            pub_key_hash_from_address_if_available,
+            PubKeyHash,
+        },
+        Validator,
+    },
+    transaction::TxActions,
+    values::Values,
+    Address,
+    Network,
+};
+use thiserror::Error;
+
+/// Lovelace the seller locks with the lot, returned to them by the first bid
+pub const DEPOSIT_LOVELACE: u64 = 2_000_000;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AuctionLogic;
+
+#[derive(Debug, EndpointsCli)]
+pub enum AuctionEndpoints {
+    /// Auction `amount` of `lot`, taking bids of at least `min_bid` lovelace for `duration_secs`
+    Open {
+        /// Token, as `<policy id>.<asset name>` in hex
+        lot: PolicyId,
+        amount: u64,
+        /// Lovelace, or ADA like `2.5ADA`
+        #[cli(lovelace)]
+        min_bid: u64,
+        duration_secs: i64,
+    },
+    /// Outbid the current highest bid, refunding its bidder
+    Bid {
+        output_id: OutputId,
+        /// Lovelace, or ADA like `2.5ADA`
+        #[cli(lovelace)]
+        amount: u64,
+    },
+    /// Pay the seller and give the lot to the highest bidder, once the deadline passed
+    Close { output_id: OutputId },
+}
+
+#[derive(Debug)]
+pub enum AuctionLookups {
+    ListAuctions { count: usize },
+}
+
+#[derive(Debug)]
+pub enum AuctionLookupResponses {
+    Auctions(Vec<Output<AuctionDatum>>),
+}
+
+#[derive(Debug, Error)]
+pub enum AuctionError {
+    #[error("Could not find an auction with id: {0:?}")]
+    OutputNotFound(OutputId),
+    #[error("Could not find an auction datum in output: {0:?}")]
+    DatumUnreadable(OutputId),
+    #[error("Signer address doesn't have a public key hash")]
+    InvalidSigner,
+    #[error("Bid of {bid} doesn't beat the minimum of {minimum}")]
+    BidTooLow { bid: u64, minimum: u64 },
+    #[error("Auction already ended at {0}")]
+    AuctionEnded(i64),
+    #[error("Auction is still taking bids until {0}")]
+    AuctionStillOpen(i64),
+}
+
+#[async_trait]
+impl SCLogic for AuctionLogic {
+    type Endpoints = AuctionEndpoints;
+    type Lookups = AuctionLookups;
+    type LookupResponses = AuctionLookupResponses;
+    type Datums = AuctionDatum;
+    type Redeemers = AuctionRedeemer;
+
+    async fn handle_endpoint<LC: LedgerClient<Self::Datums, Self::Redeemers>>(
+        endpoint: Self::Endpoints,
+        ledger_client: &LC,
+    ) -> SCLogicResult<TxActions<Self::Datums, Self::Redeemers>> {
+        match endpoint {
+            AuctionEndpoints::Open {
+                lot,
+                amount,
+                min_bid,
+                duration_secs,
+            } => impl_open(ledger_client, lot, amount, min_bid, duration_secs).await,
+            AuctionEndpoints::Bid { output_id, amount } => {
+                impl_bid(ledger_client, output_id, amount).await
+            }
+            AuctionEndpoints::Close { output_id } => {
+                impl_close(ledger_client, output_id).await
+            }
+        }
+    }
+
+    async fn lookup<LC: LedgerClient<Self::Datums, Self::Redeemers>>(
+        query: Self::Lookups,
+        ledger_client: &LC,
+    ) -> SCLogicResult<Self::LookupResponses> {
+        match query {
+            AuctionLookups::ListAuctions { count } => {
+                let network = ledger_client.network().await?;
+                let address = auction_validator()?.address(network)?;
+                let outputs = ledger_client.outputs_at_address(&address, count).await?;
+                Ok(AuctionLookupResponses::Auctions(outputs))
+            }
+        }
+    }
+}
+
+async fn impl_open<LC: LedgerClient<AuctionDatum, AuctionRedeemer>>(
+    ledger_client: &LC,
+    lot: PolicyId,
+    amount: u64,
+    min_bid: u64,
+    duration_secs: i64,
+) -> SCLogicResult<TxActions<AuctionDatum, AuctionRedeemer>> {
+    let network = ledger_client.network().await?;
+    let address = auction_validator()?.address(network)?;
+    let seller = signer_pkh(ledger_client).await?;
+    let now = ledger_client.current_time_secs().await?;
+    let datum = AuctionDatum {
+        seller,
+        min_bid,
+        deadline_secs: now + duration_secs,
+        highest_bid: None,
+    };
+    let mut values = Values::default();
+    values.add_one_value(&lot, amount);
+    values.add_one_value(&PolicyId::Lovelace, DEPOSIT_LOVELACE);
+    Ok(TxActions::v2().with_script_init_inline_datum(datum, values, address))
+}
+
+async fn impl_bid<LC: LedgerClient<AuctionDatum, AuctionRedeemer>>(
+    ledger_client: &LC,
+    output_id: OutputId,
+    amount: u64,
+) -> SCLogicResult<TxActions<AuctionDatum, AuctionRedeemer>> {
+    let network = ledger_client.network().await?;
+    let (output, datum) = find_auction(ledger_client, network, output_id).await?;
+    let now = ledger_client.current_time_secs().await?;
+    if now >= datum.deadline_secs {
+        return Err(as_endpoint_err(AuctionError::AuctionEnded(
+            datum.deadline_secs,
+        )))
+    }
+    let (previous_holder, outbids) = match &datum.highest_bid {
+        Some(previous) => (previous.bidder.clone(), amount > previous.amount),
+        None => (datum.seller.clone(), amount >= datum.min_bid),
+    };
+    if !outbids {
+        let minimum = datum
+            .highest_bid
+            .as_ref()
+            .map_or(datum.min_bid, |previous| previous.amount + 1);
+        return Err(as_endpoint_err(AuctionError::BidTooLow {
+            bid: amount,
+            minimum,
+        }))
+    }
+
+    let bid = Bid {
+        bidder: signer_pkh(ledger_client).await?,
+        amount,
+    };
+    let held = output.values().get(&PolicyId::Lovelace).unwrap_or(0);
+    let mut values = lot(&output);
+    values.add_one_value(&PolicyId::Lovelace, amount);
+    let new_datum = AuctionDatum {
+        highest_bid: Some(bid.clone()),
+        ..datum.clone()
+    };
+    let address = output.owner();
+    let refund_address = key_address(network, &previous_holder)?;
+    let script = Box::new(auction_validator()?);
+    let actions = TxActions::v2()
+        .with_script_redeem(output, AuctionRedeemer::PlaceBid(bid), script)
+        .with_script_init_inline_datum(new_datum, values, address)
+        .with_transfer(held, refund_address, PolicyId::Lovelace)
+        .with_valid_range_secs(None, Some(datum.deadline_secs));
+    Ok(actions)
+}
+
+async fn impl_close<LC: LedgerClient<AuctionDatum, AuctionRedeemer>>(
+    ledger_client: &LC,
+    output_id: OutputId,
+) -> SCLogicResult<TxActions<AuctionDatum, AuctionRedeemer>> {
+    let network = ledger_client.network().await?;
+    let (output, datum) = find_auction(ledger_client, network, output_id).await?;
+    let now = ledger_client.current_time_secs().await?;
+    if now < datum.deadline_secs {
+        return Err(as_endpoint_err(AuctionError::AuctionStillOpen(
+            datum.deadline_secs,
+        )))
+    }
+
+    let held = output.values().get(&PolicyId::Lovelace).unwrap_or(0);
+    let lot = lot(&output);
+    let script = Box::new(auction_validator()?);
+    let mut actions = TxActions::v2()
+        .with_script_redeem(output, AuctionRedeemer::Close, script)
+        .with_valid_range_secs(Some(datum.deadline_secs), None);
+    // Without a winner the lot goes back to the seller, who has to be the signer
+    if let Some(winner) = datum.highest_bid {
+        let seller_address = key_address(network, &datum.seller)?;
+        let winner_address = key_address(network, &winner.bidder)?;
+        actions = actions.with_transfer(held, seller_address, PolicyId::Lovelace);
+        for (policy_id, amount) in lot.vec() {
+            actions = actions.with_transfer(amount, winner_address.clone(), policy_id);
+        }
+    }
+    Ok(actions)
+}
+
+async fn find_auction<LC: LedgerClient<AuctionDatum, AuctionRedeemer>>(
+    ledger_client: &LC,
+    network: Network,
+    output_id: OutputId,
+) -> SCLogicResult<(Output<AuctionDatum>, AuctionDatum)> {
+    let address = auction_validator()?.address(network)?;
+    let output = ledger_client
+        .all_outputs_at_address(&address)
+        .await?
+        .into_iter()
+        .find(|o| o.id() == &output_id)
+        .ok_or(AuctionError::OutputNotFound(output_id.clone()))
+        .map_err(as_endpoint_err)?;
+    let datum = output
+        .typed_datum()
+        .ok_or(AuctionError::DatumUnreadable(output_id))
+        .map_err(as_endpoint_err)?;
+    Ok((output, datum))
+}
+
+/// Everything the auction output holds but lovelace
+fn lot(output: &Output<AuctionDatum>) -> Values {
+    let mut lot = Values::default();
+    for (policy_id, amount) in output.values().as_iter() {
+        if policy_id != &PolicyId::Lovelace {
+            lot.add_one_value(policy_id, *amount);
+        }
+    }
+    lot
+}
+
+/// The datum only knows payment key hashes, so payouts go to their enterprise addresses
+pub fn key_address(network: Network, pkh: &PubKeyHash) -> SCLogicResult<Address> {
+    enterprise_address(network, &pkh.clone().into()).map_err(as_endpoint_err)
+}
+
+async fn signer_pkh<LC: LedgerClient<AuctionDatum, AuctionRedeemer>>(
+    ledger_client: &LC,
+) -> SCLogicResult<PubKeyHash> {
+    let me = ledger_client.signer_base_address().await?;
+    pub_key_hash_from_address_if_available(&me)
+        .ok_or(AuctionError::InvalidSigner)
+        .map_err(|e| SCLogicError::Endpoint(Box::new(e)))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use naumachia::{
+        ledger_client::test_ledger_client::{
+            in_memory_storage::InMemoryStorage,
+            TestLedgerClient,
+            TestLedgerClientBuilder,
+        },
+        smart_contract::{
+            SmartContract,
+            SmartContractTrait,
+        },
+    };
+
+    const SELLER: &str = "addr_test1qpmtp5t0t5y6cqkaz7rfsyrx7mld77kpvksgkwm0p7en7qum7a589n30e80tclzrrnj8qr4qvzj6al0vpgtnmrkkksnqd8upj0";
+    const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+    const BOB: &str = "addr_test1qzvrhz9v6lwcr26a52y8mmk2nzq37lky68359keq3dgth4lkzpnnjv8vf98m20lhqdzl60mcftq7r2lc4xtcsv0w6xjstag0ua";
+    const DURATION: i64 = 1_000;
+
+    fn lot_policy() -> PolicyId {
+        PolicyId::native_token("deadbeef", &Some(b"Painting".to_vec()))
+    }
+
+    type AuctionLedger =
+        TestLedgerClient<AuctionDatum, AuctionRedeemer, InMemoryStorage<AuctionDatum>>;
+
+    async fn opened_auction() -> (AuctionLedger, OutputId) {
+        let seller = Address::from_bech32(SELLER).unwrap();
+        let alice = Address::from_bech32(ALICE).unwrap();
+        let bob = Address::from_bech32(BOB).unwrap();
+        let ledger_client = TestLedgerClientBuilder::new(&seller)
+            .with_starting_time(0)
+            .start_output(&seller)
+            .with_value(PolicyId::Lovelace, 10_000_000)
+            .with_value(lot_policy(), 1)
+            .finish_output()
+            .fund_lovelace(&alice, 100_000_000)
+            .fund_lovelace(&bob, 100_000_000)
+            .build_in_memory();
+        let contract = SmartContract::new(AuctionLogic, ledger_client.clone());
+        contract
+            .hit_endpoint(AuctionEndpoints::Open {
+                lot: lot_policy(),
+                amount: 1,
+                min_bid: 5_000_000,
+                duration_secs: DURATION,
+            })
+            .await
+            .unwrap();
+        let output_id = auction_id(&ledger_client).await;
+        (ledger_client, output_id)
+    }
+
+    async fn auction_id<LC: LedgerClient<AuctionDatum, AuctionRedeemer>>(
+        ledger_client: &LC,
+    ) -> OutputId {
+        let network = ledger_client.network().await.unwrap();
+        let address = auction_validator().unwrap().address(network).unwrap();
+        let outputs = ledger_client
+            .all_outputs_at_address(&address)
+            .await
+            .unwrap();
+        outputs.first().unwrap().id().clone()
+    }
+
+    async fn lovelace_of_key(
+        ledger_client: &impl LedgerClient<AuctionDatum, AuctionRedeemer>,
+        address: &str,
+    ) -> u64 {
+        let address = Address::from_bech32(address).unwrap();
+        let pkh = pub_key_hash_from_address_if_available(&address).unwrap();
+        let enterprise = key_address(Network::Testnet, &pkh).unwrap();
+        ledger_client
+            .balance_at_address(&enterprise, &PolicyId::Lovelace)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn bid__refunds_the_outbid_bidder() {
+        // given
+        let (ledger_client, output_id) = opened_auction().await;
+        let alice = SmartContract::new(
+            AuctionLogic,
+            ledger_client.with_signer(&Address::from_bech32(ALICE).unwrap()),
+        );
+        let bob = SmartContract::new(
+            AuctionLogic,
+            ledger_client.with_signer(&Address::from_bech32(BOB).unwrap()),
+        );
+        alice
+            .hit_endpoint(AuctionEndpoints::Bid {
+                output_id,
+                amount: 10_000_000,
+            })
+            .await
+            .unwrap();
+        let output_id = auction_id(&ledger_client).await;
+
+        // when
+        bob.hit_endpoint(AuctionEndpoints::Bid {
+            output_id,
+            amount: 15_000_000,
+        })
+        .await
+        .unwrap();
+
+        // then
+        assert_eq!(
+            lovelace_of_key(&ledger_client, SELLER).await,
+            DEPOSIT_LOVELACE
+        );
+        assert_eq!(lovelace_of_key(&ledger_client, ALICE).await, 10_000_000);
+        let network = ledger_client.network().await.unwrap();
+        let address = auction_validator().unwrap().address(network).unwrap();
+        let auction = ledger_client
+            .all_outputs_at_address(&address)
+            .await
+            .unwrap()
+            .pop()
+            .unwrap();
+        assert_eq!(auction.values().get(&PolicyId::Lovelace), Some(15_000_000));
+        assert_eq!(auction.values().get(&lot_policy()), Some(1));
+        let bob_pkh =
+            pub_key_hash_from_address_if_available(&Address::from_bech32(BOB).unwrap())
+                .unwrap();
+        assert_eq!(
+            auction.typed_datum().unwrap().highest_bid,
+            Some(Bid {
+                bidder: bob_pkh,
+                amount: 15_000_000
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn bid__cannot_match_the_highest_bid() {
+        // given
+        let (ledger_client, output_id) = opened_auction().await;
+        let alice = SmartContract::new(
+            AuctionLogic,
+            ledger_client.with_signer(&Address::from_bech32(ALICE).unwrap()),
+        );
+        let bob = SmartContract::new(
+            AuctionLogic,
+            ledger_client.with_signer(&Address::from_bech32(BOB).unwrap()),
+        );
+        alice
+            .hit_endpoint(AuctionEndpoints::Bid {
+                output_id,
+                amount: 10_000_000,
+            })
+            .await
+            .unwrap();
+        let output_id = auction_id(&ledger_client).await;
+
+        // when
+        let res = bob
+            .hit_endpoint(AuctionEndpoints::Bid {
+                output_id,
+                amount: 10_000_000,
+            })
+            .await;
+
+        // then
+        assert!(res.is_err());
+    }
+
+    #[tokio::test]
+    async fn close__pays_the_seller_and_gives_the_lot_to_the_winner() {
+        // given
+        let (ledger_client, output_id) = opened_auction().await;
+        let alice = SmartContract::new(
+            AuctionLogic,
+            ledger_client.with_signer(&Address::from_bech32(ALICE).unwrap()),
+        );
+        alice
+            .hit_endpoint(AuctionEndpoints::Bid {
+                output_id,
+                amount: 10_000_000,
+            })
+            .await
+            .unwrap();
+        let output_id = auction_id(&ledger_client).await;
+        ledger_client
+            .set_current_time_secs(DURATION + 1)
+            .await
+            .unwrap();
+
+        // when
+        let seller = SmartContract::new(AuctionLogic, ledger_client.clone());
+        seller
+            .hit_endpoint(AuctionEndpoints::Close { output_id })
+            .await
+            .unwrap();
+
+        // then
+        assert_eq!(
+            lovelace_of_key(&ledger_client, SELLER).await,
+            DEPOSIT_LOVELACE + 10_000_000
+        );
+        let alice_pkh =
+            pub_key_hash_from_address_if_available(&Address::from_bech32(ALICE).unwrap())
+                .unwrap();
+        let winner = key_address(Network::Testnet, &alice_pkh).unwrap();
+        let lot = ledger_client
+            .balance_at_address(&winner, &lot_policy())
+            .await
+            .unwrap();
+        assert_eq!(lot, 1);
+    }
+
+    #[tokio::test]
+    async fn bid__fails_after_the_deadline() {
+        // given
+        let (ledger_client, output_id) = opened_auction().await;
+        let alice = SmartContract::new(
+            AuctionLogic,
+            ledger_client.with_signer(&Address::from_bech32(ALICE).unwrap()),
+        );
+        ledger_client
+            .set_current_time_secs(DURATION + 1)
+            .await
+            .unwrap();
+
+        // when
+        let res = alice
+            .hit_endpoint(AuctionEndpoints::Bid {
+                output_id,
+                amount: 10_000_000,
+            })
+            .await;
+
+        // then
+        assert!(res.is_err());
+    }
+}
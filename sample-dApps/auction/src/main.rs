@@ -0,0 +1,60 @@
+use auction::logic::{
+    AuctionEndpointsCli,
+    AuctionLogic,
+    AuctionLookupResponses,
+    AuctionLookups,
+};
+use clap::Parser;
+use naumachia::{
+    smart_contract::{
+        SmartContract,
+        SmartContractTrait,
+    },
+    trireme_ledger_client::get_trireme_ledger_client_from_file,
+};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    #[clap(subcommand)]
+    action: ActionParams,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum ActionParams {
+    #[clap(flatten)]
+    Endpoint(AuctionEndpointsCli),
+    /// List open auctions
+    List { count: usize },
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    let logic = AuctionLogic;
+    let ledger_client = get_trireme_ledger_client_from_file().await.unwrap();
+    let contract = SmartContract::new(logic, ledger_client);
+
+    let endpoint = match args.action {
+        ActionParams::Endpoint(endpoint) => endpoint.into(),
+        ActionParams::List { count } => {
+            let AuctionLookupResponses::Auctions(outputs) = contract
+                .lookup(AuctionLookups::ListAuctions { count })
+                .await
+                .unwrap();
+            println!("Open auctions:");
+            for output in outputs {
+                println!("-------------------------------------");
+                println!("output: {}", output.id());
+                println!("{:?}", output.values());
+                println!("{:?}", output.datum());
+            }
+            return
+        }
+    };
+    match contract.hit_endpoint(endpoint).await {
+        Ok(tx_id) => println!("TxId: {:?}", tx_id),
+        Err(e) => println!("Error: {:?}", e),
+    }
+}
@@ -0,0 +1,29 @@
+use crate::datum::{
+    AuctionDatum,
+    AuctionRedeemer,
+};
+use naumachia::scripts::{
+    cache::{
+        CachedBlueprint,
+        ScriptCache,
+    },
+    plutus_validator::PlutusValidator,
+    ScriptError,
+    ScriptResult,
+};
+
+const VALIDATOR_NAME: &str = "auction.spend";
+
+static BLUEPRINT: CachedBlueprint =
+    CachedBlueprint::new(include_str!("../english_auction/plutus.json"));
+
+pub fn auction_validator() -> ScriptResult<PlutusValidator<AuctionDatum, AuctionRedeemer>>
+{
+    static VALIDATOR: ScriptCache<PlutusValidator<AuctionDatum, AuctionRedeemer>> =
+        ScriptCache::new();
+    VALIDATOR.get_or_try_init(|| {
+        let validator_blueprint = BLUEPRINT.validator(VALIDATOR_NAME)?;
+        PlutusValidator::from_blueprint(validator_blueprint)
+            .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))
+    })
+}
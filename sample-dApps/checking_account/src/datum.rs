@@ -66,7 +66,7 @@ impl From<CheckingAccountDatums> for PlutusData {
             }) => {
                 let owner = owner.into();
                 let puller = puller.into();
-                let amount_lovelace = PlutusData::BigInt((amount_lovelace as i64).into()); // TODO
+                let amount_lovelace = PlutusData::BigInt(amount_lovelace.into());
                 let next_pull = PlutusData::BigInt(next_pull.into());
                 let period = PlutusData::BigInt(period.into());
                 let spending_token = PlutusData::BoundedBytes(spending_token);
@@ -88,6 +88,11 @@ impl From<CheckingAccountDatums> for PlutusData {
     }
 }
 
+/// Whether `data` has the shape of an [`AllowedPuller`] datum, without decoding its fields
+pub fn is_allowed_puller(data: &PlutusData) -> bool {
+    matches!(data, PlutusData::Constr(constr) if constr.fields.len() == 7)
+}
+
 impl TryFrom<PlutusData> for CheckingAccountDatums {
     type Error = ();
 
@@ -134,15 +139,15 @@ fn allowed_puller(fields: &[PlutusData]) -> Result<CheckingAccountDatums, ()> {
     let PlutusData::BigInt(amount_lovelace) = fields.get(2).ok_or(())? else {
         return Err(());
     };
-    let amount_lovelace: i64 = amount_lovelace.into();
+    let amount_lovelace = u64::try_from(amount_lovelace).map_err(|_| ())?;
     let PlutusData::BigInt(next_pull) = fields.get(3).ok_or(())? else {
         return Err(());
     };
-    let next_pull: i64 = next_pull.into();
+    let next_pull = i64::try_from(next_pull).map_err(|_| ())?;
     let PlutusData::BigInt(period) = fields.get(4).ok_or(())? else {
         return Err(());
     };
-    let period: i64 = period.into();
+    let period = i64::try_from(period).map_err(|_| ())?;
     let PlutusData::BoundedBytes(spending_token) = fields.get(5).ok_or(())? else {
         return Err(());
     };
@@ -154,7 +159,7 @@ fn allowed_puller(fields: &[PlutusData]) -> Result<CheckingAccountDatums, ()> {
     let datum = CheckingAccountDatums::AllowedPuller(AllowedPuller {
         owner,
         puller,
-        amount_lovelace: amount_lovelace as u64, // TODO
+        amount_lovelace,
         next_pull,
         period,
         spending_token,
@@ -3,6 +3,7 @@ pub mod fund_account;
 pub mod init_account;
 pub mod pull;
 pub mod remove_puller;
+pub mod rotate_spend_token;
 
 #[allow(non_snake_case)]
 #[cfg(test)]
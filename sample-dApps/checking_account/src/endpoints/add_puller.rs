@@ -1,6 +1,10 @@
 use crate::{
+    checking_account_validator,
     pull_validator,
-    spend_token_policy,
+    scripts::spend_token_policy::{
+        spend_token_generation,
+        spend_token_policy_for,
+    },
     AllowedPuller,
     CheckingAccountDatums,
     CheckingAccountError,
@@ -49,12 +53,10 @@ pub async fn add_puller<LC: LedgerClient<CheckingAccountDatums, ()>>(
     let nft_id_bytes = hex::decode(checking_account_nft_id).unwrap();
     let my_pubkey = pub_key_hash_from_address_if_available(&me).unwrap();
 
-    let parameterized_spending_token_policy = spend_token_policy().unwrap();
-    let policy = parameterized_spending_token_policy
-        .apply(nft_id_bytes.clone().into())
-        .unwrap()
-        .apply(my_pubkey.into())
-        .unwrap();
+    let generation =
+        current_spend_token_generation(ledger_client, &nft_id_bytes, &my_pubkey).await?;
+    let policy = spend_token_policy_for(&nft_id_bytes, &my_pubkey, generation)
+        .map_err(SCLogicError::PolicyScript)?;
 
     let id = policy.id().unwrap();
     let boxed_policy = Box::new(policy);
@@ -66,7 +68,10 @@ pub async fn add_puller<LC: LedgerClient<CheckingAccountDatums, ()>>(
 
     let mut values = Values::default();
     values.add_one_value(
-        &PolicyId::NativeToken(id.clone(), Some(SPEND_TOKEN_ASSET_NAME.to_string())),
+        &PolicyId::NativeToken(
+            id.clone(),
+            Some(SPEND_TOKEN_ASSET_NAME.as_bytes().to_vec()),
+        ),
         1,
     );
     let datum = AllowedPuller {
@@ -82,10 +87,47 @@ pub async fn add_puller<LC: LedgerClient<CheckingAccountDatums, ()>>(
     let actions = TxActions::v2()
         .with_mint(
             1,
-            Some(SPEND_TOKEN_ASSET_NAME.to_string()),
+            Some(SPEND_TOKEN_ASSET_NAME.as_bytes().to_vec()),
             (),
             boxed_policy,
         )
-        .with_script_init(datum, values, address);
+        .with_script_init_inline_datum(datum, values, address);
     Ok(actions)
 }
+
+// Accounts start with generation 0, so pullers can be added before the account is found
+async fn current_spend_token_generation<LC: LedgerClient<CheckingAccountDatums, ()>>(
+    ledger_client: &LC,
+    checking_account_nft: &[u8],
+    owner: &PubKeyHash,
+) -> SCLogicResult<u32> {
+    let network = ledger_client
+        .network()
+        .await
+        .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
+    let address = checking_account_validator()
+        .map_err(SCLogicError::ValidatorScript)?
+        .address(network)
+        .map_err(SCLogicError::ValidatorScript)?;
+    let nft_policy = hex::encode(checking_account_nft);
+    let account = ledger_client
+        .all_outputs_at_address(&address)
+        .await
+        .map_err(|e| SCLogicError::Lookup(Box::new(e)))?
+        .into_iter()
+        .filter(|output| {
+            output
+                .values()
+                .as_iter()
+                .any(|(policy_id, amt)| policy_id.id() == nft_policy && *amt == 1)
+        })
+        .find_map(|output| match output.typed_datum() {
+            Some(CheckingAccountDatums::CheckingAccount(account)) => Some(account),
+            _ => None,
+        });
+    let Some(account) = account else { return Ok(0) };
+    spend_token_generation(checking_account_nft, owner, &account.spend_token_policy)
+        .map_err(SCLogicError::PolicyScript)?
+        .ok_or(CheckingAccountError::UnknownSpendTokenPolicy)
+        .map_err(|e| SCLogicError::Endpoint(Box::new(e)))
+}
@@ -49,6 +49,6 @@ pub async fn fund_account<LC: LedgerClient<CheckingAccountDatums, ()>>(
     values.add_one_value(&PolicyId::Lovelace, amount);
     let actions = TxActions::v2()
         .with_script_redeem(output, redeemer, script)
-        .with_script_init(new_datum, values, checking_account_address);
+        .with_script_init_inline_datum(new_datum, values, checking_account_address);
     Ok(actions)
 }
@@ -6,22 +6,22 @@ use crate::{
     CheckingAccountError,
     CHECKING_ACCOUNT_NFT_ASSET_NAME,
 };
-use nau_scripts::{
-    one_shot,
-    one_shot::OutputReference,
-};
 use naumachia::{
     ledger_client::LedgerClient,
     logic::error::{
         SCLogicError,
         SCLogicResult,
     },
-    output::Output,
+    output::{
+        Output,
+        UnbuiltOutput,
+    },
     policy_id::PolicyId,
+    protocol_params::ProtocolParameters,
     scripts::{
         context::pub_key_hash_from_address_if_available,
+        stdlib::one_shot::one_shot_nft_policy_for,
         MintingPolicy,
-        ScriptError,
         Validator,
     },
     transaction::TxActions,
@@ -42,11 +42,8 @@ pub async fn init_account<LC: LedgerClient<CheckingAccountDatums, ()>>(
         .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
 
     let my_input = select_any_above_min(ledger_client).await?;
-    let nft = one_shot::get_parameterized_script().map_err(SCLogicError::PolicyScript)?;
-    let nft_policy = nft
-        .apply(OutputReference::from(&my_input))
-        .map_err(|e| ScriptError::FailedToConstruct(format!("{:?}", e)))
-        .map_err(SCLogicError::PolicyScript)?;
+    let nft_policy =
+        one_shot_nft_policy_for(&my_input).map_err(SCLogicError::PolicyScript)?;
     let spending_token_policy_parameterized =
         spend_token_policy().map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
     let nft_script_id = nft_policy.id().unwrap();
@@ -65,28 +62,34 @@ pub async fn init_account<LC: LedgerClient<CheckingAccountDatums, ()>>(
         .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
 
     let spend_token_id = hex::decode(&spend_token_policy).unwrap();
-    let datum = CheckingAccount {
+    let datum: CheckingAccountDatums = CheckingAccount {
         owner: owner_pubkey,
         spend_token_policy: spend_token_id,
     }
     .into();
     let boxed_nft_policy = Box::new(nft_policy);
     let mut values = Values::default();
-    values.add_one_value(&PolicyId::Lovelace, starting_lovelace);
     values.add_one_value(
         &PolicyId::NativeToken(
             nft_script_id,
-            Some(CHECKING_ACCOUNT_NFT_ASSET_NAME.to_string()),
+            Some(CHECKING_ACCOUNT_NFT_ASSET_NAME.as_bytes().to_vec()),
         ),
         1,
     );
+    let min_ada = UnbuiltOutput::new_validator_with_inline_datum(
+        address.clone(),
+        values.clone(),
+        datum.clone(),
+    )
+    .min_ada_required(&ProtocolParameters::default());
+    values.add_one_value(&PolicyId::Lovelace, starting_lovelace.max(min_ada));
 
     let actions = TxActions::v2()
-        .with_script_init(datum, values, address)
+        .with_script_init_inline_datum(datum, values, address)
         .with_specific_input(my_input)
         .with_mint(
             1,
-            Some(CHECKING_ACCOUNT_NFT_ASSET_NAME.to_string()),
+            Some(CHECKING_ACCOUNT_NFT_ASSET_NAME.as_bytes().to_vec()),
             (),
             boxed_nft_policy,
         );
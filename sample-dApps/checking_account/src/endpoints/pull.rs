@@ -96,7 +96,7 @@ pub async fn pull_from_account<LC: LedgerClient<CheckingAccountDatums, ()>>(
     let new_checking_account_datum = checking_account_output
         .typed_datum()
         .ok_or(CheckingAccountError::DatumNotFoundForOutput(
-            checking_account_output_id.clone(),
+            checking_account_output_id,
         ))
         .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?
         .clone();
@@ -108,21 +108,21 @@ pub async fn pull_from_account<LC: LedgerClient<CheckingAccountDatums, ()>>(
     sub_values.add_one_value(&PolicyId::Lovelace, amount);
     let new_account_value = old_values
         .try_subtract(&sub_values)
-        .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?
-        .ok_or(CheckingAccountError::OutputNotFound(
-            checking_account_output_id.clone(),
-        ))
         .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
 
     let actions = TxActions::v2()
         .with_script_redeem(allow_pull_output, allow_pull_redeemer, allow_pull_script)
-        .with_script_init(new_allow_pull_datum, allow_pull_value, allow_pull_address)
+        .with_script_init_inline_datum(
+            new_allow_pull_datum,
+            allow_pull_value,
+            allow_pull_address,
+        )
         .with_script_redeem(
             checking_account_output,
             checking_account_redeemer,
             checking_account_script,
         )
-        .with_script_init(
+        .with_script_init_inline_datum(
             new_checking_account_datum,
             new_account_value,
             checking_account_address,
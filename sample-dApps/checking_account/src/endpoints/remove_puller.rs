@@ -1,7 +1,13 @@
 use crate::{
+    datum::is_allowed_puller,
     pull_validator,
+    scripts::spend_token_policy::{
+        spend_token_generation,
+        spend_token_policy_for,
+    },
     CheckingAccountDatums,
     CheckingAccountError,
+    SPEND_TOKEN_ASSET_NAME,
 };
 use naumachia::{
     ledger_client::LedgerClient,
@@ -10,7 +16,11 @@ use naumachia::{
         SCLogicResult,
     },
     output::OutputId,
-    scripts::Validator,
+    policy_id::PolicyId,
+    scripts::{
+        MintingPolicy,
+        Validator,
+    },
     transaction::TxActions,
 };
 
@@ -27,15 +37,47 @@ pub async fn remove_puller<LC: LedgerClient<CheckingAccountDatums, ()>>(
         .address(network)
         .map_err(SCLogicError::ValidatorScript)?;
     let output = ledger_client
-        .all_outputs_at_address(&address)
+        .outputs_at_address_matching(&address, is_allowed_puller)
         .await
         .map_err(|e| SCLogicError::Lookup(Box::new(e)))?
         .into_iter()
         .find(|o| o.id() == &output_id)
-        .ok_or(CheckingAccountError::OutputNotFound(output_id))
+        .ok_or(CheckingAccountError::OutputNotFound(output_id.clone()))
         .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
+    let Some(CheckingAccountDatums::AllowedPuller(puller)) = output.typed_datum() else {
+        return Err(SCLogicError::Endpoint(Box::new(
+            CheckingAccountError::DatumNotFoundForOutput(output_id),
+        )))
+    };
+
+    // Burn the spend token along with the puller so it can't be reused
+    let generation = spend_token_generation(
+        &puller.checking_account_nft,
+        &puller.owner,
+        &puller.spending_token,
+    )
+    .map_err(SCLogicError::PolicyScript)?
+    .ok_or(CheckingAccountError::UnknownSpendTokenPolicy)
+    .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
+    let policy =
+        spend_token_policy_for(&puller.checking_account_nft, &puller.owner, generation)
+            .map_err(SCLogicError::PolicyScript)?;
+    let spend_token = PolicyId::NativeToken(
+        policy.id().map_err(SCLogicError::PolicyScript)?,
+        Some(SPEND_TOKEN_ASSET_NAME.as_bytes().to_vec()),
+    );
+    let spend_tokens = output.values().get(&spend_token).unwrap_or_default();
+
     let redeemer = ();
     let script = Box::new(validator);
-    let actions = TxActions::v2().with_script_redeem(output, redeemer, script);
+    let mut actions = TxActions::v2().with_script_redeem(output, redeemer, script);
+    if spend_tokens > 0 {
+        actions = actions.with_burn(
+            spend_tokens,
+            Some(SPEND_TOKEN_ASSET_NAME.as_bytes().to_vec()),
+            (),
+            Box::new(policy),
+        );
+    }
     Ok(actions)
 }
@@ -0,0 +1,104 @@
+use crate::{
+    checking_account_validator,
+    scripts::spend_token_policy::{
+        spend_token_generation,
+        spend_token_policy_for,
+        MAX_SPEND_TOKEN_ROTATIONS,
+    },
+    CheckingAccount,
+    CheckingAccountDatums,
+    CheckingAccountError,
+    CHECKING_ACCOUNT_NFT_ASSET_NAME,
+};
+use naumachia::{
+    ledger_client::LedgerClient,
+    logic::error::{
+        SCLogicError,
+        SCLogicResult,
+    },
+    output::{
+        Output,
+        OutputId,
+    },
+    scripts::{
+        MintingPolicy,
+        Validator,
+    },
+    transaction::TxActions,
+};
+
+pub async fn rotate_spend_token_policy<LC: LedgerClient<CheckingAccountDatums, ()>>(
+    ledger_client: &LC,
+    output_id: OutputId,
+) -> SCLogicResult<TxActions<CheckingAccountDatums, ()>> {
+    let network = ledger_client
+        .network()
+        .await
+        .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
+    let checking_account_validator =
+        checking_account_validator().map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
+    let checking_account_address = checking_account_validator
+        .address(network)
+        .map_err(SCLogicError::ValidatorScript)?;
+    let output = ledger_client
+        .all_outputs_at_address(&checking_account_address)
+        .await
+        .map_err(|e| SCLogicError::Lookup(Box::new(e)))?
+        .into_iter()
+        .find(|o| o.id() == &output_id)
+        .ok_or(CheckingAccountError::OutputNotFound(output_id.clone()))
+        .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
+    let Some(CheckingAccountDatums::CheckingAccount(account)) = output.typed_datum()
+    else {
+        return Err(SCLogicError::Endpoint(Box::new(
+            CheckingAccountError::DatumNotFoundForOutput(output_id),
+        )))
+    };
+    let nft = checking_account_nft(&output)?;
+
+    let generation =
+        spend_token_generation(&nft, &account.owner, &account.spend_token_policy)
+            .map_err(SCLogicError::PolicyScript)?
+            .ok_or(CheckingAccountError::UnknownSpendTokenPolicy)
+            .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
+    // Past the cap, the account's policy couldn't be recognized as a rotation anymore
+    if generation >= MAX_SPEND_TOKEN_ROTATIONS {
+        return Err(SCLogicError::Endpoint(Box::new(
+            CheckingAccountError::SpendTokenRotationsExhausted(MAX_SPEND_TOKEN_ROTATIONS),
+        )))
+    }
+    let next_generation = generation + 1;
+    let new_policy = spend_token_policy_for(&nft, &account.owner, next_generation)
+        .map_err(SCLogicError::PolicyScript)?
+        .id()
+        .map_err(SCLogicError::PolicyScript)?;
+    let new_datum = CheckingAccount {
+        owner: account.owner,
+        spend_token_policy: hex::decode(new_policy).unwrap(),
+    }
+    .into();
+
+    let redeemer = ();
+    let script = Box::new(checking_account_validator);
+    let values = output.values().to_owned();
+    let actions = TxActions::v2()
+        .with_script_redeem(output, redeemer, script)
+        .with_script_init_inline_datum(new_datum, values, checking_account_address);
+    Ok(actions)
+}
+
+fn checking_account_nft(
+    output: &Output<CheckingAccountDatums>,
+) -> SCLogicResult<Vec<u8>> {
+    output
+        .values()
+        .as_iter()
+        .find(|(policy_id, amt)| {
+            policy_id.asset_name()
+                == Some(CHECKING_ACCOUNT_NFT_ASSET_NAME.as_bytes().to_vec())
+                && **amt == 1
+        })
+        .and_then(|(policy_id, _)| hex::decode(policy_id.id()).ok())
+        .ok_or(CheckingAccountError::NftNotFound(output.id().clone()))
+        .map_err(|e| SCLogicError::Endpoint(Box::new(e)))
+}
@@ -1,11 +1,18 @@
 use crate::{
     checking_account_validator,
     datum::CheckingAccountDatums,
-    scripts::pull_validator::pull_validator,
+    scripts::{
+        pull_validator::pull_validator,
+        spend_token_policy::{
+            spend_token_policy_for,
+            MAX_SPEND_TOKEN_ROTATIONS,
+        },
+    },
     spend_token_policy,
     AllowedPuller,
     CheckingAccount,
     CheckingAccountEndpoints,
+    CheckingAccountError,
     CheckingAccountLogic,
     CHECKING_ACCOUNT_NFT_ASSET_NAME,
     SPEND_TOKEN_ASSET_NAME,
@@ -64,7 +71,8 @@ async fn init_creates_instance_with_correct_balance() {
     let value = script_output.values().get(&PolicyId::Lovelace).unwrap();
     assert_eq!(value, account_amount);
     let nft = script_output.values().as_iter().find(|(policy_id, amt)| {
-        policy_id.asset_name() == Some(CHECKING_ACCOUNT_NFT_ASSET_NAME.to_string())
+        policy_id.asset_name()
+            == Some(CHECKING_ACCOUNT_NFT_ASSET_NAME.as_bytes().to_vec())
             && **amt == 1
     });
     assert!(nft.is_some());
@@ -114,7 +122,7 @@ async fn add_puller_creates_new_datum_for_puller() {
         .values()
         .get(&PolicyId::NativeToken(
             id,
-            Some(SPEND_TOKEN_ASSET_NAME.to_string()),
+            Some(SPEND_TOKEN_ASSET_NAME.as_bytes().to_vec()),
         ))
         .unwrap();
     assert_eq!(value, 1);
@@ -455,3 +463,203 @@ async fn pull_from_account__fails_if_time_not_past_next_pull_time() {
 
     assert!(matches!(err, Error::SCLogic(SCLogicError::Endpoint(_))));
 }
+
+#[tokio::test]
+async fn remove_puller__burns_the_spend_token() {
+    let me = Address::from_bech32("addr_test1qpmtp5t0t5y6cqkaz7rfsyrx7mld77kpvksgkwm0p7en7qum7a589n30e80tclzrrnj8qr4qvzj6al0vpgtnmrkkksnqd8upj0").unwrap();
+    let nft_id = vec![1, 2, 3, 4, 5];
+    let backend = TestLedgerClientBuilder::new(&me)
+        .start_output(&me)
+        .with_value(PolicyId::Lovelace, 100_000_000)
+        .finish_output()
+        .build_in_memory();
+    let puller = Address::from_bech32("addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr").unwrap();
+    let add_endpoint = CheckingAccountEndpoints::AddPuller {
+        checking_account_nft: hex::encode(&nft_id),
+        puller: pub_key_hash_from_address_if_available(&puller).unwrap(),
+        amount_lovelace: 15_000_000,
+        period: 1000,
+        next_pull: 0,
+    };
+    let contract = SmartContract::new(CheckingAccountLogic, backend);
+    contract.hit_endpoint(add_endpoint).await.unwrap();
+    let address = pull_validator().unwrap().address(Network::Testnet).unwrap();
+    let output_id = contract
+        .ledger_client()
+        .all_outputs_at_address(&address)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap()
+        .id()
+        .to_owned();
+
+    let remove_endpoint = CheckingAccountEndpoints::RemovePuller { output_id };
+    contract.hit_endpoint(remove_endpoint).await.unwrap();
+
+    let my_pubkey_hash = pub_key_hash_from_address_if_available(&me).unwrap();
+    let policy = spend_token_policy()
+        .unwrap()
+        .apply(nft_id.into())
+        .unwrap()
+        .apply(my_pubkey_hash.into())
+        .unwrap();
+    let spend_token = PolicyId::NativeToken(
+        policy.id().unwrap(),
+        Some(SPEND_TOKEN_ASSET_NAME.as_bytes().to_vec()),
+    );
+    let spend_tokens_left = contract
+        .ledger_client()
+        .balance_at_address(&me, &spend_token)
+        .await
+        .unwrap();
+    assert_eq!(spend_tokens_left, 0);
+}
+
+#[tokio::test]
+async fn rotate_spend_token_policy__new_pullers_get_tokens_of_the_new_policy() {
+    let me = Address::from_bech32("addr_test1qpmtp5t0t5y6cqkaz7rfsyrx7mld77kpvksgkwm0p7en7qum7a589n30e80tclzrrnj8qr4qvzj6al0vpgtnmrkkksnqd8upj0").unwrap();
+    let backend = TestLedgerClientBuilder::new(&me)
+        .start_output(&me)
+        .with_value(PolicyId::Lovelace, 100_000_000)
+        .finish_output()
+        .build_in_memory();
+    let contract = SmartContract::new(CheckingAccountLogic, backend);
+    let init_endpoint = CheckingAccountEndpoints::InitAccount {
+        starting_lovelace: 10_000_000,
+    };
+    contract.hit_endpoint(init_endpoint).await.unwrap();
+    let account_address = checking_account_validator()
+        .unwrap()
+        .address(Network::Testnet)
+        .unwrap();
+    let account = contract
+        .ledger_client()
+        .all_outputs_at_address(&account_address)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let Some(CheckingAccountDatums::CheckingAccount(CheckingAccount {
+        spend_token_policy: original_policy,
+        ..
+    })) = account.typed_datum()
+    else {
+        panic!("Expected a checking account datum");
+    };
+    let nft = account
+        .values()
+        .as_iter()
+        .find(|(policy_id, _)| {
+            policy_id.asset_name()
+                == Some(CHECKING_ACCOUNT_NFT_ASSET_NAME.as_bytes().to_vec())
+        })
+        .map(|(policy_id, _)| policy_id.id())
+        .unwrap();
+
+    let rotate_endpoint = CheckingAccountEndpoints::RotateSpendTokenPolicy {
+        output_id: account.id().to_owned(),
+    };
+    contract.hit_endpoint(rotate_endpoint).await.unwrap();
+    let puller = Address::from_bech32("addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr").unwrap();
+    let add_endpoint = CheckingAccountEndpoints::AddPuller {
+        checking_account_nft: nft,
+        puller: pub_key_hash_from_address_if_available(&puller).unwrap(),
+        amount_lovelace: 15_000_000,
+        period: 1000,
+        next_pull: 0,
+    };
+    contract.hit_endpoint(add_endpoint).await.unwrap();
+
+    let rotated = contract
+        .ledger_client()
+        .all_outputs_at_address(&account_address)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let Some(CheckingAccountDatums::CheckingAccount(CheckingAccount {
+        spend_token_policy: rotated_policy,
+        ..
+    })) = rotated.typed_datum()
+    else {
+        panic!("Expected a checking account datum");
+    };
+    assert_ne!(rotated_policy, original_policy);
+    assert_eq!(rotated.values(), account.values());
+    let pull_address = pull_validator().unwrap().address(Network::Testnet).unwrap();
+    let pull_output = contract
+        .ledger_client()
+        .all_outputs_at_address(&pull_address)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let Some(CheckingAccountDatums::AllowedPuller(allowed_puller)) =
+        pull_output.typed_datum()
+    else {
+        panic!("Expected an allowed puller datum");
+    };
+    assert_eq!(allowed_puller.spending_token, rotated_policy);
+    let spend_token = PolicyId::NativeToken(
+        hex::encode(&rotated_policy),
+        Some(SPEND_TOKEN_ASSET_NAME.as_bytes().to_vec()),
+    );
+    assert_eq!(pull_output.values().get(&spend_token), Some(1));
+}
+
+#[tokio::test]
+async fn rotate_spend_token_policy__fails_once_the_rotations_run_out() {
+    // given
+    let me = Address::from_bech32("addr_test1qpmtp5t0t5y6cqkaz7rfsyrx7mld77kpvksgkwm0p7en7qum7a589n30e80tclzrrnj8qr4qvzj6al0vpgtnmrkkksnqd8upj0").unwrap();
+    let owner = pub_key_hash_from_address_if_available(&me).unwrap();
+    let nft = vec![7; 28];
+    let last_policy =
+        spend_token_policy_for(&nft, &owner, MAX_SPEND_TOKEN_ROTATIONS).unwrap();
+    let datum = CheckingAccount {
+        owner,
+        spend_token_policy: hex::decode(last_policy.id().unwrap()).unwrap(),
+    };
+    let account_address = checking_account_validator()
+        .unwrap()
+        .address(Network::Testnet)
+        .unwrap();
+    let nft_policy = PolicyId::NativeToken(
+        hex::encode(&nft),
+        Some(CHECKING_ACCOUNT_NFT_ASSET_NAME.as_bytes().to_vec()),
+    );
+    let backend = TestLedgerClientBuilder::new(&me)
+        .start_output(&me)
+        .with_value(PolicyId::Lovelace, 100_000_000)
+        .finish_output()
+        .start_output(&account_address)
+        .with_value(PolicyId::Lovelace, 10_000_000)
+        .with_value(nft_policy, 1)
+        .with_datum(datum.into())
+        .finish_output()
+        .build_in_memory();
+    let account = backend
+        .all_outputs_at_address(&account_address)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let contract = SmartContract::new(CheckingAccountLogic, backend);
+
+    // when
+    let rotate_endpoint = CheckingAccountEndpoints::RotateSpendTokenPolicy {
+        output_id: account.id().to_owned(),
+    };
+    let err = contract.hit_endpoint(rotate_endpoint).await.unwrap_err();
+
+    // then
+    let Error::SCLogic(SCLogicError::Endpoint(err)) = err else {
+        panic!("Expected an endpoint error, got {err:?}");
+    };
+    assert!(matches!(
+        err.downcast_ref::<CheckingAccountError>(),
+        Some(CheckingAccountError::SpendTokenRotationsExhausted(
+            MAX_SPEND_TOKEN_ROTATIONS
+        ))
+    ));
+}
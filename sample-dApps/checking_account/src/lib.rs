@@ -19,8 +19,10 @@ use endpoints::{
     init_account::init_account,
     pull::pull_from_account,
     remove_puller::remove_puller,
+    rotate_spend_token::rotate_spend_token_policy,
 };
 use naumachia::{
+    cli::EndpointsCli,
     ledger_client::LedgerClient,
     logic::{
         error::SCLogicResult,
@@ -45,7 +47,7 @@ pub const SPEND_TOKEN_ASSET_NAME: &str = "SPEND TOKEN";
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct TimeLockedLogic;
 
-#[derive(Debug)]
+#[derive(Debug, EndpointsCli)]
 pub enum CheckingAccountEndpoints {
     // Owner Endpoints
     /// Create a new checking account
@@ -59,16 +61,21 @@ pub enum CheckingAccountEndpoints {
         period: i64,
         next_pull: i64,
     },
-    /// Disallow puller from accessing account account
+    /// Disallow puller from accessing account account, burning their spend token
     RemovePuller { output_id: OutputId },
+    /// Replace the account's spend token policy, revoking every puller's spend token at
+    /// once
+    RotateSpendTokenPolicy { output_id: OutputId },
     /// Add funds to checking account
     FundAccount {
         output_id: OutputId,
+        #[cli(lovelace)]
         fund_amount: u64,
     },
     /// Remove funds from a checking account
     WithdrawFromAccount {
         output_id: OutputId,
+        #[cli(lovelace)]
         withdraw_amount: u64,
     },
     // Puller Endpoints
@@ -76,6 +83,7 @@ pub enum CheckingAccountEndpoints {
     PullFromCheckingAccount {
         allow_pull_output_id: OutputId,
         checking_account_output_id: OutputId,
+        #[cli(lovelace)]
         amount: u64,
     },
 }
@@ -122,6 +130,12 @@ pub enum CheckingAccountError {
     InvalidAddress(Address),
     #[error("Too early to pull: next_pull: {next_pull}, current_time: {current_time}")]
     TooEarlyToPull { next_pull: i64, current_time: i64 },
+    #[error("No checking account NFT on output with id: {0:?}")]
+    NftNotFound(OutputId),
+    #[error("Spend token policy isn't a rotation of the account's policy")]
+    UnknownSpendTokenPolicy,
+    #[error("Spend token policy was already rotated the most times allowed: {0}")]
+    SpendTokenRotationsExhausted(u32),
 }
 
 #[async_trait]
@@ -160,6 +174,9 @@ impl SCLogic for CheckingAccountLogic {
             CheckingAccountEndpoints::RemovePuller { output_id } => {
                 remove_puller(ledger_client, output_id).await
             }
+            CheckingAccountEndpoints::RotateSpendTokenPolicy { output_id } => {
+                rotate_spend_token_policy(ledger_client, output_id).await
+            }
             CheckingAccountEndpoints::FundAccount {
                 output_id,
                 fund_amount,
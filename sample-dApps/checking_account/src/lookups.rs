@@ -68,7 +68,8 @@ pub async fn get_my_accounts<LC: LedgerClient<CheckingAccountDatums, ()>>(
                 .as_iter()
                 .find(|(policy_id, amt)| {
                     if let Some(asset_name) = policy_id.asset_name() {
-                        amt == &&1 && asset_name == CHECKING_ACCOUNT_NFT_ASSET_NAME
+                        amt == &&1
+                            && asset_name == CHECKING_ACCOUNT_NFT_ASSET_NAME.as_bytes()
                     // TODO: This is kinda fragile since in theory you could have
                     //   other assets with the same name
                     } else {
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use checking::{
     CheckingAccountEndpoints,
+    CheckingAccountEndpointsCli,
     CheckingAccountLogic,
     CheckingAccountLookupResponses,
     CheckingAccountLookups,
@@ -34,6 +35,9 @@ enum ActionParams {
     MyAccounts,
     /// Starts a dialogue to add a puller for a checking account
     AddPuller,
+    /// Hit any checking account endpoint directly
+    #[clap(subcommand)]
+    Endpoint(CheckingAccountEndpointsCli),
 }
 
 #[tokio::main]
@@ -46,6 +50,10 @@ async fn main() -> Result<()> {
         }
         ActionParams::MyAccounts => my_account_impl().await?,
         ActionParams::AddPuller => add_puller_impl().await?,
+        ActionParams::Endpoint(endpoint) => {
+            let tx_id = hit_endpoint(endpoint.into()).await?;
+            println!("TxId: {:?}", tx_id);
+        }
     }
     Ok(())
 }
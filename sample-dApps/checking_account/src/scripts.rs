@@ -1,3 +1,9 @@
+use naumachia::scripts::cache::CachedBlueprint;
+
 pub mod checking_account_validtor;
 pub mod pull_validator;
 pub mod spend_token_policy;
+
+/// Blueprint of every script in the checking account dApp, parsed once
+pub(crate) static BLUEPRINT: CachedBlueprint =
+    CachedBlueprint::new(include_str!("../checking/plutus.json"));
@@ -1,15 +1,17 @@
-use crate::datum::CheckingAccountDatums;
+use crate::{
+    datum::CheckingAccountDatums,
+    scripts::BLUEPRINT,
+};
 use naumachia::scripts::{
+    cache::ScriptCache,
     plutus_validator::{
         plutus_data::PlutusData,
         PlutusValidator,
     },
-    raw_script::BlueprintFile,
     ScriptError,
     ScriptResult,
 };
 
-const BLUEPRINT: &str = include_str!("../../checking/plutus.json");
 const VALIDATOR_NAME: &str = "checking_account_validator.spend";
 
 pub struct SpendingTokenPolicy {
@@ -30,18 +32,13 @@ impl From<SpendingTokenPolicy> for PlutusData {
 
 pub fn checking_account_validator(
 ) -> ScriptResult<PlutusValidator<CheckingAccountDatums, ()>> {
-    let blueprint: BlueprintFile = serde_json::from_str(BLUEPRINT)
-        .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))?;
-    let validator_blueprint =
-        blueprint
-            .get_validator(VALIDATOR_NAME)
-            .ok_or(ScriptError::FailedToConstruct(format!(
-                "Validator not listed in Blueprint: {:?}",
-                VALIDATOR_NAME
-            )))?;
-    let raw_script_validator = PlutusValidator::from_blueprint(validator_blueprint)
-        .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))?;
-    Ok(raw_script_validator)
+    static VALIDATOR: ScriptCache<PlutusValidator<CheckingAccountDatums, ()>> =
+        ScriptCache::new();
+    VALIDATOR.get_or_try_init(|| {
+        let validator_blueprint = BLUEPRINT.validator(VALIDATOR_NAME)?;
+        PlutusValidator::from_blueprint(validator_blueprint)
+            .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))
+    })
 }
 
 #[cfg(test)]
@@ -1,27 +1,24 @@
-use crate::datum::CheckingAccountDatums;
+use crate::{
+    datum::CheckingAccountDatums,
+    scripts::BLUEPRINT,
+};
 use naumachia::scripts::{
+    cache::ScriptCache,
     plutus_validator::PlutusValidator,
-    raw_script::BlueprintFile,
     ScriptError,
     ScriptResult,
 };
 
-const SCRIPT_RAW: &str = include_str!("../../checking/plutus.json");
 const VALIDATOR_NAME: &str = "pull_validator.spend";
 
 pub fn pull_validator() -> ScriptResult<PlutusValidator<CheckingAccountDatums, ()>> {
-    let blueprint: BlueprintFile = serde_json::from_str(SCRIPT_RAW)
-        .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))?;
-    let validator_blueprint =
-        blueprint
-            .get_validator(VALIDATOR_NAME)
-            .ok_or(ScriptError::FailedToConstruct(format!(
-                "Validator not listed in Blueprint: {:?}",
-                VALIDATOR_NAME
-            )))?;
-    let raw_script_validator = PlutusValidator::from_blueprint(validator_blueprint)
-        .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))?;
-    Ok(raw_script_validator)
+    static VALIDATOR: ScriptCache<PlutusValidator<CheckingAccountDatums, ()>> =
+        ScriptCache::new();
+    VALIDATOR.get_or_try_init(|| {
+        let validator_blueprint = BLUEPRINT.validator(VALIDATOR_NAME)?;
+        PlutusValidator::from_blueprint(validator_blueprint)
+            .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))
+    })
 }
 
 #[allow(non_snake_case)]
@@ -1,113 +1,46 @@
 use naumachia::scripts::{
     context::PubKeyHash,
-    plutus_minting_policy::TwoParamMintingPolicy,
-    plutus_validator::plutus_data::PlutusData,
-    raw_script::BlueprintFile,
-    ScriptError,
+    plutus_minting_policy::PlutusMintingPolicy,
+    stdlib::spend_token,
+    MintingPolicy,
     ScriptResult,
 };
 
-const BLUEPRINT: &str = include_str!("../../checking/plutus.json");
-const VALIDATOR_NAME: &str = "spend_token_policy.mint";
-
-pub struct CheckingAccountNFT {
-    inner: Vec<u8>,
-}
-
-impl From<Vec<u8>> for CheckingAccountNFT {
-    fn from(value: Vec<u8>) -> Self {
-        CheckingAccountNFT { inner: value }
-    }
-}
-
-impl From<CheckingAccountNFT> for PlutusData {
-    fn from(value: CheckingAccountNFT) -> Self {
-        PlutusData::BoundedBytes(value.inner)
-    }
-}
-
-pub struct Owner {
-    inner: Vec<u8>,
-}
-
-impl From<PubKeyHash> for Owner {
-    fn from(value: PubKeyHash) -> Self {
-        let inner = value.bytes();
-        Owner { inner }
-    }
-}
-
-impl From<Owner> for PlutusData {
-    fn from(value: Owner) -> Self {
-        PlutusData::BoundedBytes(value.inner)
-    }
-}
-
-pub fn spend_token_policy(
-) -> ScriptResult<TwoParamMintingPolicy<CheckingAccountNFT, Owner, ()>> {
-    let script_file: BlueprintFile = serde_json::from_str(BLUEPRINT)
-        .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))?;
-    let validator_blueprint = script_file.get_validator(VALIDATOR_NAME).ok_or(
-        ScriptError::FailedToConstruct(format!(
-            "Validator not listed in Blueprint: {:?}",
-            VALIDATOR_NAME
-        )),
-    )?;
-    let raw_script_validator = TwoParamMintingPolicy::from_blueprint(validator_blueprint)
-        .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))?;
-    Ok(raw_script_validator)
-}
-
-#[allow(non_snake_case)]
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use naumachia::{
-        scripts::{
-            context::{
-                pub_key_hash_from_address_if_available,
-                ContextBuilder,
-            },
-            MintingPolicy,
-        },
-        Address,
-    };
-
-    #[test]
-    fn execute__correct_signer_can_mint() {
-        let signer = Address::from_bech32("addr_test1qpmtp5t0t5y6cqkaz7rfsyrx7mld77kpvksgkwm0p7en7qum7a589n30e80tclzrrnj8qr4qvzj6al0vpgtnmrkkksnqd8upj0").unwrap();
-        let param_script = spend_token_policy().unwrap();
-        let nft = CheckingAccountNFT {
-            inner: vec![1, 2, 3],
-        };
-        let signer_pkh = pub_key_hash_from_address_if_available(&signer).unwrap();
-        let owner = Owner {
-            inner: signer_pkh.bytes(),
-        };
-        let script = param_script.apply(nft).unwrap().apply(owner).unwrap();
-
-        let ctx = ContextBuilder::new(signer_pkh).build_mint(&[]);
-
-        script.execute((), ctx).unwrap();
+pub use naumachia::scripts::stdlib::spend_token::spend_token_policy;
+
+/// Most times an account's spend token policy can be rotated
+pub const MAX_SPEND_TOKEN_ROTATIONS: u32 = 32;
+
+/// Spend token policy of the account with `checking_account_nft`, after it was rotated
+/// `generation` times
+///
+/// The policy only checks the owner's signature, so each generation is parameterized with
+/// the NFT followed by the generation to get a new policy id. Generation 0 is just the NFT,
+/// which is what accounts start with.
+pub fn spend_token_policy_for(
+    checking_account_nft: &[u8],
+    owner: &PubKeyHash,
+    generation: u32,
+) -> ScriptResult<PlutusMintingPolicy<()>> {
+    let mut seed = checking_account_nft.to_vec();
+    if generation > 0 {
+        seed.extend(generation.to_be_bytes());
     }
-
-    #[test]
-    fn execute__incorrect_signer_cannot_mint() {
-        let correct_signer = Address::from_bech32("addr_test1qrksjmprvgcedgdt6rhg40590vr6exdzdc2hm5wc6pyl9ymkyskmqs55usm57gflrumk9kd63f3ty6r0l2tdfwfm28qs0rurdr").unwrap();
-        let incorrect_signer = Address::from_bech32("addr_test1qqddk5xnz08mxsqw6jdaenvhdah835lhvm62tt5lydk2as7kfjf77qy57hqhnefcqyy7hmhsygj9j38rj984hn9r57fs066hcl").unwrap();
-        let param_script = spend_token_policy().unwrap();
-        let nft = CheckingAccountNFT {
-            inner: vec![1, 2, 3],
-        };
-        let signer_pkh = pub_key_hash_from_address_if_available(&correct_signer).unwrap();
-        let owner = Owner {
-            inner: signer_pkh.bytes(),
-        };
-        let script = param_script.apply(nft).unwrap().apply(owner).unwrap();
-        let incorrect_signer_pkh =
-            pub_key_hash_from_address_if_available(&incorrect_signer).unwrap();
-        let ctx = ContextBuilder::new(incorrect_signer_pkh).build_mint(&[]);
-
-        script.execute((), ctx).unwrap_err();
+    spend_token::spend_token_policy_for(&seed, owner)
+}
+
+/// Which rotation of the account with `checking_account_nft` has the policy id `policy_id`
+pub fn spend_token_generation(
+    checking_account_nft: &[u8],
+    owner: &PubKeyHash,
+    policy_id: &[u8],
+) -> ScriptResult<Option<u32>> {
+    let policy_id = hex::encode(policy_id);
+    for generation in 0..=MAX_SPEND_TOKEN_ROTATIONS {
+        let policy = spend_token_policy_for(checking_account_nft, owner, generation)?;
+        if policy.id()? == policy_id {
+            return Ok(Some(generation))
+        }
     }
+    Ok(None)
 }
@@ -42,9 +42,7 @@ pub async fn withdraw_from_account<LC: LedgerClient<CheckingAccountDatums, ()>>(
 
     let new_datum = output
         .typed_datum()
-        .ok_or(CheckingAccountError::DatumNotFoundForOutput(
-            output_id.clone(),
-        ))
+        .ok_or(CheckingAccountError::DatumNotFoundForOutput(output_id))
         .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?
         .clone();
     let redeemer = ();
@@ -54,11 +52,9 @@ pub async fn withdraw_from_account<LC: LedgerClient<CheckingAccountDatums, ()>>(
     sub_values.add_one_value(&PolicyId::Lovelace, amount);
     let new_value = old_values
         .try_subtract(&sub_values)
-        .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?
-        .ok_or(CheckingAccountError::OutputNotFound(output_id))
         .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
     let actions = TxActions::v2()
         .with_script_redeem(output, redeemer, script)
-        .with_script_init(new_datum, new_value, address);
+        .with_script_init_inline_datum(new_datum, new_value, address);
     Ok(actions)
 }
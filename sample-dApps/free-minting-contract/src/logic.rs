@@ -1,6 +1,7 @@
 use crate::logic::script::get_policy;
 use async_trait::async_trait;
 use naumachia::{
+    cli::EndpointsCli,
     ledger_client::LedgerClient,
     logic::{
         error::{
@@ -17,8 +18,9 @@ pub mod script;
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct FreeMintingLogic;
 
-#[derive(Debug)]
+#[derive(Debug, EndpointsCli)]
 pub enum FreeMintingEndpoints {
+    /// Mint amount
     Mint { amount: u64 },
 }
 
@@ -40,7 +42,7 @@ impl SCLogic for FreeMintingLogic {
                 let policy = Box::new(inner_policy);
                 let actions = TxActions::v1().with_mint(
                     amount,
-                    Some("FREEEEEE".to_string()),
+                    Some(b"FREEEEEE".to_vec()),
                     (),
                     policy,
                 );
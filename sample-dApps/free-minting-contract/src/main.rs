@@ -1,6 +1,6 @@
 use clap::Parser;
 use free_minting_contract::logic::{
-    FreeMintingEndpoints,
+    FreeMintingEndpointsCli,
     FreeMintingLogic,
 };
 use naumachia::{
@@ -15,13 +15,7 @@ use naumachia::{
 #[clap(author, version, about, long_about = None)]
 struct Args {
     #[clap(subcommand)]
-    action: ActionParams,
-}
-
-#[derive(clap::Subcommand, Debug)]
-enum ActionParams {
-    /// Mint amount
-    Mint { amount: u64 },
+    action: FreeMintingEndpointsCli,
 }
 
 #[tokio::main]
@@ -31,11 +25,6 @@ async fn main() {
     let ledger_client = get_trireme_ledger_client_from_file().await.unwrap();
     let contract = SmartContract::new(logic, ledger_client);
 
-    let tx_id = match args.action {
-        ActionParams::Mint { amount } => contract
-            .hit_endpoint(FreeMintingEndpoints::Mint { amount })
-            .await
-            .unwrap(),
-    };
+    let tx_id = contract.hit_endpoint(args.action.into()).await.unwrap();
     println!("TxId: {:?}", tx_id);
 }
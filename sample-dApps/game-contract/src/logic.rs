@@ -5,6 +5,7 @@ use crate::logic::script::{
 };
 use async_trait::async_trait;
 use naumachia::{
+    cli::EndpointsCli,
     ledger_client::LedgerClient,
     logic::{
         error::{
@@ -31,9 +32,16 @@ mod tests;
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct GameLogic;
 
-#[derive(Debug)]
+#[derive(Debug, EndpointsCli)]
 pub enum GameEndpoints {
-    Lock { amount: u64, secret: String },
+    /// Lock amount at script address
+    Lock {
+        /// Lovelace, or ADA like `2.5ADA`
+        #[cli(lovelace)]
+        amount: u64,
+        secret: String,
+    },
+    /// Claim locked Output, given as `<tx hash>#<index>`, at script address
     Guess { output_id: OutputId, guess: String },
 }
 
@@ -1,12 +1,11 @@
 use clap::Parser;
 use game_contract::logic::{
-    GameEndpoints,
+    GameEndpointsCli,
     GameLogic,
     GameLookupResponses,
     GameLookups,
 };
 use naumachia::{
-    output::OutputId,
     smart_contract::{
         SmartContract,
         SmartContractTrait,
@@ -23,14 +22,8 @@ struct Args {
 
 #[derive(clap::Subcommand, Debug)]
 enum ActionParams {
-    /// Lock amount at script address
-    Lock { amount: f64, secret: String },
-    /// Claim locked Output at script address
-    Guess {
-        tx_hash: String,
-        index: u64,
-        guess: String,
-    },
+    #[clap(flatten)]
+    Endpoint(GameEndpointsCli),
     /// List all outputs locked at script address
     List { count: usize },
 }
@@ -44,25 +37,8 @@ async fn main() {
     let contract = SmartContract::new(logic, ledger_client);
 
     match args.action {
-        ActionParams::Lock { amount, secret } => {
-            let tx_id = contract
-                .hit_endpoint(GameEndpoints::Lock {
-                    amount: (amount * 1_000_000.) as u64,
-                    secret,
-                })
-                .await
-                .unwrap();
-            println!("tx: {:?}", tx_id);
-        }
-        ActionParams::Guess {
-            tx_hash,
-            index,
-            guess,
-        } => {
-            let tx_hash_bytes = hex::decode(tx_hash).unwrap();
-            let output_id = OutputId::new(tx_hash_bytes, index);
-            let endpoint = GameEndpoints::Guess { output_id, guess };
-            let tx_id = contract.hit_endpoint(endpoint).await.unwrap();
+        ActionParams::Endpoint(endpoint) => {
+            let tx_id = contract.hit_endpoint(endpoint.into()).await.unwrap();
             println!("tx: {:?}", tx_id);
         }
         ActionParams::List { count } => {
@@ -75,7 +51,7 @@ async fn main() {
                     println!("Active contracts:");
                     for output in outputs {
                         println!("-------------------------------------");
-                        println!("{:?}", output.id());
+                        println!("output: {}", output.id());
                         println!("{:?}", output.values());
                         println!("{:?}", output.datum());
                     }
@@ -1 +1,2 @@
 pub mod logic;
+pub mod metadata;
@@ -1,19 +1,26 @@
-// use crate::logic::script::{get_parameterized_script, OutputReference};
+use crate::metadata::{
+    cip25_metadata,
+    cip27_metadata,
+    NftMetadata,
+    Royalty,
+};
 use async_trait::async_trait;
 use naumachia::{
     ledger_client::LedgerClient,
     logic::SCLogic,
     output::Output,
     policy_id::PolicyId,
-    scripts::ScriptError,
-    transaction::TxActions,
+    scripts::{
+        stdlib::one_shot::one_shot_nft_policy_for,
+        MintingPolicy,
+    },
+    transaction::{
+        metadata::MAX_METADATUM_LEN,
+        TxActions,
+    },
 };
 use thiserror::Error;
 
-use nau_scripts::one_shot::{
-    get_parameterized_script,
-    OutputReference,
-};
 use naumachia::logic::error::{
     SCLogicError,
     SCLogicResult,
@@ -22,15 +29,24 @@ use naumachia::logic::error::{
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct MintNFTLogic;
 
+pub const NFT_ASSET_NAME: &str = "OneShot";
+
 #[derive(Debug)]
 pub enum MintNFTEndpoints {
-    Mint,
+    Mint {
+        metadata: NftMetadata,
+        royalty: Option<Royalty>,
+    },
 }
 
 #[derive(Debug, Error)]
 pub enum MintNFTError {
     #[error("Could not find any UTxO to use as the input for NFT policy")]
     InputNotFound,
+    #[error("Royalty rate must be between 0 and 1, got {0}")]
+    InvalidRoyaltyRate(f64),
+    #[error("The NFT's {0} must be at most {MAX_METADATUM_LEN} bytes")]
+    FieldTooLong(&'static str),
 }
 
 #[async_trait]
@@ -46,7 +62,9 @@ impl SCLogic for MintNFTLogic {
         ledger_client: &LC,
     ) -> SCLogicResult<TxActions<Self::Datums, Self::Redeemers>> {
         match endpoint {
-            MintNFTEndpoints::Mint => impl_mint(ledger_client).await,
+            MintNFTEndpoints::Mint { metadata, royalty } => {
+                impl_mint(ledger_client, metadata, royalty).await
+            }
         }
     }
 
@@ -60,17 +78,43 @@ impl SCLogic for MintNFTLogic {
 
 async fn impl_mint<LC: LedgerClient<(), ()>>(
     ledger_client: &LC,
+    metadata: NftMetadata,
+    royalty: Option<Royalty>,
 ) -> SCLogicResult<TxActions<(), ()>> {
+    // CIP-25 only allows the image and description to be split into chunks
+    let single_strings = [
+        ("name", Some(&metadata.name)),
+        ("media type", metadata.media_type.as_ref()),
+    ];
+    for (field, value) in single_strings {
+        if value.map_or(false, |value| value.len() > MAX_METADATUM_LEN) {
+            return Err(SCLogicError::Endpoint(Box::new(
+                MintNFTError::FieldTooLong(field),
+            )))
+        }
+    }
     let my_input = select_any_above_min(ledger_client).await?;
-    let param_script = get_parameterized_script().map_err(SCLogicError::PolicyScript)?;
-    let script = param_script
-        .apply(OutputReference::from(&my_input))
-        .map_err(|e| ScriptError::FailedToConstruct(format!("{:?}", e)))
-        .map_err(SCLogicError::PolicyScript)?;
-    let policy = Box::new(script);
-    let actions = TxActions::v2()
-        .with_mint(1, Some("OneShot".to_string()), (), policy)
+    let script =
+        one_shot_nft_policy_for(&my_input).map_err(SCLogicError::PolicyScript)?;
+    let policy_id = script.id().map_err(SCLogicError::PolicyScript)?;
+    let policy_id = hex::decode(policy_id).expect("Policy ids are hex");
+    let asset_name = NFT_ASSET_NAME.as_bytes().to_vec();
+    let mut actions = TxActions::v2()
+        .with_mint(1, Some(asset_name.clone()), (), Box::new(script.clone()))
+        .with_metadata(cip25_metadata(&policy_id, &asset_name, &metadata))
         .with_specific_input(my_input);
+    if let Some(royalty) = royalty {
+        if !(0.0..=1.0).contains(&royalty.rate) {
+            return Err(SCLogicError::Endpoint(Box::new(
+                MintNFTError::InvalidRoyaltyRate(royalty.rate),
+            )))
+        }
+        // CIP-27 royalty token: nameless, under the same policy, minted no later than the
+        // NFT. The one-shot policy can only mint once, so both go in this transaction.
+        actions = actions
+            .with_mint(1, None, (), Box::new(script))
+            .with_metadata(cip27_metadata(&royalty));
+    }
     Ok(actions)
 }
 
@@ -110,3 +154,117 @@ async fn select_any_above_min<LC: LedgerClient<(), ()>>(
         .to_owned();
     Ok(selected)
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::metadata::{
+        CIP25_LABEL,
+        CIP27_LABEL,
+    };
+    use naumachia::{
+        ledger_client::test_ledger_client::TestLedgerClientBuilder,
+        smart_contract::{
+            SmartContract,
+            SmartContractTrait,
+        },
+        transaction::metadata::Metadatum,
+        Address,
+    };
+
+    #[tokio::test]
+    async fn mint__attaches_cip25_metadata_and_mints_royalty_token() {
+        // given
+        let me = Address::from_bech32("addr_test1qpmtp5t0t5y6cqkaz7rfsyrx7mld77kpvksgkwm0p7en7qum7a589n30e80tclzrrnj8qr4qvzj6al0vpgtnmrkkksnqd8upj0").unwrap();
+        let backend = TestLedgerClientBuilder::new(&me)
+            .start_output(&me)
+            .with_value(PolicyId::Lovelace, 100_000_000)
+            .finish_output()
+            .build_in_memory();
+        let contract = SmartContract::new(MintNFTLogic, backend);
+        let image = format!("ipfs://{}", "Qm".repeat(40));
+        let endpoint = MintNFTEndpoints::Mint {
+            metadata: NftMetadata {
+                name: "Trireme #1".to_string(),
+                image: image.clone(),
+                media_type: Some("image/png".to_string()),
+                description: None,
+            },
+            royalty: Some(Royalty {
+                rate: 0.05,
+                address: me.to_bech32().unwrap(),
+            }),
+        };
+
+        // when
+        contract.hit_endpoint(endpoint).await.unwrap();
+
+        // then
+        let tx = contract.ledger_client().last_tx().unwrap();
+        let (policy, _) = tx
+            .minted()
+            .as_iter()
+            .find(|(policy, _)| {
+                policy.asset_name() == Some(NFT_ASSET_NAME.as_bytes().to_vec())
+            })
+            .unwrap();
+        tx.assert_minted(&policy.id(), Some(NFT_ASSET_NAME.as_bytes()), 1);
+        tx.assert_minted(&policy.id(), None, 1);
+        let Some(Metadatum::Map(policies)) = tx.metadata().get(CIP25_LABEL) else {
+            panic!("Expected CIP-25 metadata");
+        };
+        let (policy_key, Metadatum::Map(assets)) = &policies[0] else {
+            panic!("Expected assets under the policy");
+        };
+        assert_eq!(
+            policy_key,
+            &Metadatum::Bytes(hex::decode(policy.id()).unwrap())
+        );
+        let (_, Metadatum::Map(details)) = &assets[0] else {
+            panic!("Expected the NFT's details");
+        };
+        assert_eq!(details[0].1, Metadatum::Text("Trireme #1".to_string()));
+        let (_, Metadatum::List(image_chunks)) = &details[1] else {
+            panic!("Expected the long image URI to be split");
+        };
+        let joined: String = image_chunks
+            .iter()
+            .map(|chunk| match chunk {
+                Metadatum::Text(text) => text.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(joined, image);
+        assert!(tx.metadata().get(CIP27_LABEL).is_some());
+    }
+
+    #[tokio::test]
+    async fn mint__rejects_names_too_long_for_a_single_metadata_string() {
+        // given
+        let me = Address::from_bech32("addr_test1qpmtp5t0t5y6cqkaz7rfsyrx7mld77kpvksgkwm0p7en7qum7a589n30e80tclzrrnj8qr4qvzj6al0vpgtnmrkkksnqd8upj0").unwrap();
+        let backend = TestLedgerClientBuilder::new(&me)
+            .start_output(&me)
+            .with_value(PolicyId::Lovelace, 100_000_000)
+            .finish_output()
+            .build_in_memory();
+        let contract = SmartContract::new(MintNFTLogic, backend);
+        let endpoint = MintNFTEndpoints::Mint {
+            metadata: NftMetadata {
+                name: "T".repeat(MAX_METADATUM_LEN + 1),
+                image: "ipfs://Qm".to_string(),
+                media_type: None,
+                description: Some("D".repeat(MAX_METADATUM_LEN + 1)),
+            },
+            royalty: None,
+        };
+
+        // when
+        let res = contract.hit_endpoint(endpoint).await;
+
+        // then
+        assert!(res.is_err());
+        assert!(contract.ledger_client().last_tx().is_none());
+    }
+}
@@ -1,7 +1,13 @@
 use clap::Parser;
-use mint_nft::logic::{
-    MintNFTEndpoints,
-    MintNFTLogic,
+use mint_nft::{
+    logic::{
+        MintNFTEndpoints,
+        MintNFTLogic,
+    },
+    metadata::{
+        NftMetadata,
+        Royalty,
+    },
 };
 use naumachia::{
     smart_contract::{
@@ -18,10 +24,28 @@ struct Args {
     action: ActionParams,
 }
 
+// Written by hand rather than derived with `EndpointsCli`, since minting takes the
+// metadata as a whole struct
 #[derive(clap::Subcommand, Debug)]
 enum ActionParams {
-    /// Mint single NFT
-    Mint,
+    /// Mint single NFT with CIP-25 metadata
+    Mint {
+        /// Display name of the NFT
+        name: String,
+        /// URI of the NFT's image, e.g. `ipfs://...`
+        image: String,
+        /// Media type of the image, e.g. `image/png`
+        #[clap(long)]
+        media_type: Option<String>,
+        #[clap(long)]
+        description: Option<String>,
+        /// Share of secondary sales paid as royalties, between 0 and 1
+        #[clap(long, requires = "royalty_address")]
+        royalty_rate: Option<f64>,
+        /// Address receiving royalties
+        #[clap(long, requires = "royalty_rate")]
+        royalty_address: Option<String>,
+    },
 }
 
 #[tokio::main]
@@ -33,8 +57,25 @@ async fn main() {
     let contract = SmartContract::new(logic, ledger_client);
 
     let tx_id = match args.action {
-        ActionParams::Mint => {
-            contract.hit_endpoint(MintNFTEndpoints::Mint).await.unwrap()
+        ActionParams::Mint {
+            name,
+            image,
+            media_type,
+            description,
+            royalty_rate,
+            royalty_address,
+        } => {
+            let metadata = NftMetadata {
+                name,
+                image,
+                media_type,
+                description,
+            };
+            let royalty = royalty_rate
+                .zip(royalty_address)
+                .map(|(rate, address)| Royalty { rate, address });
+            let endpoint = MintNFTEndpoints::Mint { metadata, royalty };
+            contract.hit_endpoint(endpoint).await.unwrap()
         }
     };
     println!("TxId: {:?}", tx_id);
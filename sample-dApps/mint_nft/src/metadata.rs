@@ -0,0 +1,83 @@
+use naumachia::transaction::metadata::{
+    Metadatum,
+    TxMetadata,
+    MAX_METADATUM_LEN,
+};
+
+/// CIP-25 label for NFT metadata
+pub const CIP25_LABEL: u64 = 721;
+/// CIP-27 label for the royalty token's metadata
+pub const CIP27_LABEL: u64 = 777;
+
+/// Display details of an NFT, as shown by wallets and marketplaces
+#[derive(Clone, Debug, PartialEq)]
+pub struct NftMetadata {
+    pub name: String,
+    pub image: String,
+    pub media_type: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Royalty marketplaces should pay on secondary sales, following CIP-27
+#[derive(Clone, Debug, PartialEq)]
+pub struct Royalty {
+    /// Share of each sale, between 0 and 1
+    pub rate: f64,
+    /// Bech32 address receiving the royalties
+    pub address: String,
+}
+
+/// CIP-25 version 2 metadata for `asset_name` under `policy_id`
+///
+/// Only `image` and `description` may be split into chunks, so `name` and `media_type` must
+/// fit in a single metadata string, see [`MAX_METADATUM_LEN`].
+pub fn cip25_metadata(
+    policy_id: &[u8],
+    asset_name: &[u8],
+    nft: &NftMetadata,
+) -> TxMetadata {
+    let mut details = vec![
+        ("name".into(), nft.name.as_str().into()),
+        ("image".into(), chunked(&nft.image)),
+    ];
+    if let Some(media_type) = &nft.media_type {
+        details.push(("mediaType".into(), media_type.as_str().into()));
+    }
+    if let Some(description) = &nft.description {
+        details.push(("description".into(), chunked(description)));
+    }
+    let assets =
+        Metadatum::Map(vec![(asset_name.to_vec().into(), Metadatum::Map(details))]);
+    let policies = Metadatum::Map(vec![
+        (policy_id.to_vec().into(), assets),
+        ("version".into(), 2u64.into()),
+    ]);
+    TxMetadata::default().with_entry(CIP25_LABEL, policies)
+}
+
+/// CIP-27 metadata for the royalty token of a policy
+pub fn cip27_metadata(royalty: &Royalty) -> TxMetadata {
+    let royalty = Metadatum::Map(vec![
+        ("rate".into(), chunked(&royalty.rate.to_string())),
+        ("addr".into(), chunked(&royalty.address)),
+    ]);
+    TxMetadata::default().with_entry(CIP27_LABEL, royalty)
+}
+
+// Metadata strings are limited in length, so longer ones, like most image URLs, are split
+// into a list of strings that readers join back together
+fn chunked(text: &str) -> Metadatum {
+    if text.len() <= MAX_METADATUM_LEN {
+        return text.into()
+    }
+    let mut chunks = Vec::new();
+    let mut chunk = String::new();
+    for c in text.chars() {
+        if chunk.len() + c.len_utf8() > MAX_METADATUM_LEN {
+            chunks.push(Metadatum::Text(std::mem::take(&mut chunk)));
+        }
+        chunk.push(c);
+    }
+    chunks.push(Metadatum::Text(chunk));
+    Metadatum::List(chunks)
+}
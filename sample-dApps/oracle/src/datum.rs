@@ -0,0 +1,86 @@
+use naumachia::scripts::{
+    context::PubKeyHash,
+    plutus_validator::plutus_data::{
+        Constr,
+        PlutusData,
+    },
+};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum OracleDatums {
+    PriceFeed(PriceFeed),
+    Vault(Vault),
+}
+
+/// Price posted by the oracle's operator, in lovelace
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PriceFeed {
+    pub operator: PubKeyHash,
+    pub price: u64,
+    pub updated_at: i64,
+}
+
+impl From<PriceFeed> for OracleDatums {
+    fn from(value: PriceFeed) -> Self {
+        OracleDatums::PriceFeed(value)
+    }
+}
+
+/// Funds the owner can only claim once the feed's price reaches `strike_price`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Vault {
+    pub owner: PubKeyHash,
+    pub strike_price: u64,
+}
+
+impl From<Vault> for OracleDatums {
+    fn from(value: Vault) -> Self {
+        OracleDatums::Vault(value)
+    }
+}
+
+impl From<OracleDatums> for PlutusData {
+    fn from(value: OracleDatums) -> Self {
+        let fields = match value {
+            OracleDatums::PriceFeed(PriceFeed {
+                operator,
+                price,
+                updated_at,
+            }) => vec![operator.into(), price.into(), updated_at.into()],
+            OracleDatums::Vault(Vault {
+                owner,
+                strike_price,
+            }) => vec![owner.into(), strike_price.into()],
+        };
+        PlutusData::Constr(Constr { constr: 0, fields })
+    }
+}
+
+impl TryFrom<PlutusData> for OracleDatums {
+    type Error = ();
+
+    fn try_from(value: PlutusData) -> Result<Self, Self::Error> {
+        let PlutusData::Constr(constr) = value else {
+            return Err(());
+        };
+
+        match constr.fields.as_slice() {
+            [PlutusData::BoundedBytes(operator), price, updated_at] => {
+                let feed = PriceFeed {
+                    operator: PubKeyHash::new(operator),
+                    price: u64::try_from(price.clone()).map_err(|_| ())?,
+                    updated_at: i64::try_from(updated_at.clone()).map_err(|_| ())?,
+                };
+                Ok(feed.into())
+            }
+            [PlutusData::BoundedBytes(owner), strike_price] => {
+                let vault = Vault {
+                    owner: PubKeyHash::new(owner),
+                    strike_price: u64::try_from(strike_price.clone()).map_err(|_| ())?,
+                };
+                Ok(vault.into())
+            }
+            _ => Err(()),
+        }
+    }
+}
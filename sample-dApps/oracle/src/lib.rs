@@ -0,0 +1,3 @@
+pub mod datum;
+pub mod logic;
+pub mod scripts;
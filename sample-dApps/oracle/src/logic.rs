@@ -0,0 +1,389 @@
+use crate::{
+    datum::{
+        OracleDatums,
+        PriceFeed,
+        Vault,
+    },
+    scripts::{
+        price_feed_validator,
+        vault_validator,
+    },
+};
+use async_trait::async_trait;
+use naumachia::{
+    cli::EndpointsCli,
+    ledger_client::LedgerClient,
+    logic::{
+        error::{
+            SCLogicError,
+            SCLogicResult,
+        },
+        SCLogic,
+    },
+    output::{
+        Output,
+        OutputId,
+    },
+    policy_id::PolicyId,
+    scripts::{
+        context::{
+            pub_key_hash_from_address_if_available,
+            script_hash_from_address,
+            PubKeyHash,
+        },
+        ScriptError,
+        Validator,
+    },
+    transaction::TxActions,
+    values::Values,
+    Address,
+    Network,
+};
+use thiserror::Error;
+
+/// Lovelace kept with the posted price, so the feed output meets the minimum UTxO value
+pub const FEED_LOVELACE: u64 = 2_000_000;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OracleLogic;
+
+#[derive(Debug, EndpointsCli)]
+pub enum OracleEndpoints {
+    /// Post `price`, replacing the one the signer posted before
+    PostPrice { price: u64 },
+    /// Lock `amount` lovelace, claimable once the feed's price reaches `strike_price`
+    OpenVault {
+        strike_price: u64,
+        /// Lovelace, or ADA like `2.5ADA`
+        #[cli(lovelace)]
+        amount: u64,
+    },
+    /// Claim a vault, reading the feed's price through a reference input
+    ClaimVault { output_id: OutputId },
+}
+
+#[derive(Debug)]
+pub enum OracleLookups {
+    CurrentPrice,
+    ListVaults { count: usize },
+}
+
+#[derive(Debug)]
+pub enum OracleLookupResponses {
+    CurrentPrice(Option<PriceFeed>),
+    Vaults(Vec<Output<OracleDatums>>),
+}
+
+#[derive(Debug, Error)]
+pub enum OracleError {
+    #[error("Could not find an output with id: {0:?}")]
+    OutputNotFound(OutputId),
+    #[error("Could not find a vault datum in output: {0:?}")]
+    DatumUnreadable(OutputId),
+    #[error("No price has been posted to the feed")]
+    NoPriceFeed,
+    #[error("Signer address doesn't have a public key hash")]
+    InvalidSigner,
+    #[error("Price {price} hasn't reached the vault's strike price {strike_price}")]
+    StrikePriceNotReached { price: u64, strike_price: u64 },
+}
+
+#[async_trait]
+impl SCLogic for OracleLogic {
+    type Endpoints = OracleEndpoints;
+    type Lookups = OracleLookups;
+    type LookupResponses = OracleLookupResponses;
+    type Datums = OracleDatums;
+    type Redeemers = ();
+
+    async fn handle_endpoint<LC: LedgerClient<Self::Datums, Self::Redeemers>>(
+        endpoint: Self::Endpoints,
+        ledger_client: &LC,
+    ) -> SCLogicResult<TxActions<Self::Datums, Self::Redeemers>> {
+        match endpoint {
+            OracleEndpoints::PostPrice { price } => {
+                impl_post_price(ledger_client, price).await
+            }
+            OracleEndpoints::OpenVault {
+                strike_price,
+                amount,
+            } => impl_open_vault(ledger_client, strike_price, amount).await,
+            OracleEndpoints::ClaimVault { output_id } => {
+                impl_claim_vault(ledger_client, output_id).await
+            }
+        }
+    }
+
+    async fn lookup<LC: LedgerClient<Self::Datums, Self::Redeemers>>(
+        query: Self::Lookups,
+        ledger_client: &LC,
+    ) -> SCLogicResult<Self::LookupResponses> {
+        match query {
+            OracleLookups::CurrentPrice => {
+                let network = ledger_client.network().await?;
+                let feed = latest_feed(ledger_client, network)
+                    .await?
+                    .map(|(_, feed)| feed);
+                Ok(OracleLookupResponses::CurrentPrice(feed))
+            }
+            OracleLookups::ListVaults { count } => {
+                let network = ledger_client.network().await?;
+                let address = vault_address(network)?;
+                let outputs = ledger_client.outputs_at_address(&address, count).await?;
+                Ok(OracleLookupResponses::Vaults(outputs))
+            }
+        }
+    }
+}
+
+async fn impl_post_price<LC: LedgerClient<OracleDatums, ()>>(
+    ledger_client: &LC,
+    price: u64,
+) -> SCLogicResult<TxActions<OracleDatums, ()>> {
+    let network = ledger_client.network().await?;
+    let operator = signer_pkh(ledger_client).await?;
+    let script = price_feed_validator()?;
+    let address = script.address(network)?;
+    let updated_at = ledger_client.current_time_secs().await?;
+    let datum = PriceFeed {
+        operator: operator.clone(),
+        price,
+        updated_at,
+    }
+    .into();
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, FEED_LOVELACE);
+
+    let mut actions =
+        TxActions::v2().with_script_init_inline_datum(datum, values, address.clone());
+    // Older prices from the same operator are spent, so readers only ever find one
+    let previous = ledger_client.all_outputs_at_address(&address).await?;
+    for output in previous {
+        if matches!(
+            output.typed_datum(),
+            Some(OracleDatums::PriceFeed(feed)) if feed.operator == operator
+        ) {
+            actions = actions.with_script_redeem(output, (), Box::new(script.clone()));
+        }
+    }
+    Ok(actions)
+}
+
+async fn impl_open_vault<LC: LedgerClient<OracleDatums, ()>>(
+    ledger_client: &LC,
+    strike_price: u64,
+    amount: u64,
+) -> SCLogicResult<TxActions<OracleDatums, ()>> {
+    let network = ledger_client.network().await?;
+    let owner = signer_pkh(ledger_client).await?;
+    let address = vault_address(network)?;
+    let datum = Vault {
+        owner,
+        strike_price,
+    }
+    .into();
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, amount);
+    Ok(TxActions::v2().with_script_init_inline_datum(datum, values, address))
+}
+
+async fn impl_claim_vault<LC: LedgerClient<OracleDatums, ()>>(
+    ledger_client: &LC,
+    output_id: OutputId,
+) -> SCLogicResult<TxActions<OracleDatums, ()>> {
+    let network = ledger_client.network().await?;
+    let script = vault_validator(feed_hash(network)?)?;
+    let address = script.address(network)?;
+    let output = ledger_client
+        .all_outputs_at_address(&address)
+        .await?
+        .into_iter()
+        .find(|o| o.id() == &output_id)
+        .ok_or(OracleError::OutputNotFound(output_id.clone()))
+        .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
+    let Some(OracleDatums::Vault(vault)) = output.typed_datum() else {
+        return Err(SCLogicError::Endpoint(Box::new(
+            OracleError::DatumUnreadable(output_id),
+        )))
+    };
+    let (feed_output, feed) = latest_feed(ledger_client, network)
+        .await?
+        .ok_or(OracleError::NoPriceFeed)
+        .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
+    // Checked here too, so an early claim fails with a reason instead of a script failure
+    if feed.price < vault.strike_price {
+        return Err(SCLogicError::Endpoint(Box::new(
+            OracleError::StrikePriceNotReached {
+                price: feed.price,
+                strike_price: vault.strike_price,
+            },
+        )))
+    }
+    let actions = TxActions::v2()
+        .with_reference_input(feed_output)
+        .with_script_redeem(output, (), Box::new(script));
+    Ok(actions)
+}
+
+/// Most recently updated price at the feed, with the output holding it
+async fn latest_feed<LC: LedgerClient<OracleDatums, ()>>(
+    ledger_client: &LC,
+    network: Network,
+) -> SCLogicResult<Option<(Output<OracleDatums>, PriceFeed)>> {
+    let address = price_feed_validator()?.address(network)?;
+    let latest = ledger_client
+        .all_outputs_at_address(&address)
+        .await?
+        .into_iter()
+        .filter_map(|output| match output.typed_datum() {
+            Some(OracleDatums::PriceFeed(feed)) => Some((output, feed)),
+            _ => None,
+        })
+        .max_by_key(|(_, feed)| feed.updated_at);
+    Ok(latest)
+}
+
+fn feed_hash(network: Network) -> SCLogicResult<Vec<u8>> {
+    let address = price_feed_validator()?.address(network)?;
+    let hash =
+        script_hash_from_address(&address).ok_or(ScriptError::FailedToConstruct(
+            "Price feed address isn't a script address".to_string(),
+        ))?;
+    Ok(hash)
+}
+
+/// Address of the vaults trusting this dApp's price feed
+pub fn vault_address(network: Network) -> SCLogicResult<Address> {
+    let address = vault_validator(feed_hash(network)?)?.address(network)?;
+    Ok(address)
+}
+
+async fn signer_pkh<LC: LedgerClient<OracleDatums, ()>>(
+    ledger_client: &LC,
+) -> SCLogicResult<PubKeyHash> {
+    let me = ledger_client.signer_base_address().await?;
+    pub_key_hash_from_address_if_available(&me)
+        .ok_or(OracleError::InvalidSigner)
+        .map_err(|e| SCLogicError::Endpoint(Box::new(e)))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use naumachia::{
+        ledger_client::test_ledger_client::TestLedgerClientBuilder,
+        smart_contract::{
+            SmartContract,
+            SmartContractTrait,
+        },
+    };
+
+    const ME: &str = "addr_test1qpmtp5t0t5y6cqkaz7rfsyrx7mld77kpvksgkwm0p7en7qum7a589n30e80tclzrrnj8qr4qvzj6al0vpgtnmrkkksnqd8upj0";
+
+    async fn vault_id<LC: LedgerClient<OracleDatums, ()> + Send + Sync>(
+        contract: &SmartContract<OracleLogic, LC>,
+    ) -> OutputId {
+        let OracleLookupResponses::Vaults(vaults) = contract
+            .lookup(OracleLookups::ListVaults { count: 1 })
+            .await
+            .unwrap()
+        else {
+            panic!("Expected vaults")
+        };
+        vaults.first().unwrap().id().clone()
+    }
+
+    #[tokio::test]
+    async fn claim_vault__reads_the_posted_price_without_spending_it() {
+        // given
+        let me = Address::from_bech32(ME).unwrap();
+        let start_amount = 100_000_000;
+        let locked_amount = 10_000_000;
+        let backend = TestLedgerClientBuilder::new(&me)
+            .with_starting_time(1_000)
+            .start_output(&me)
+            .with_value(PolicyId::Lovelace, start_amount)
+            .finish_output()
+            .build_in_memory();
+        let contract = SmartContract::new(OracleLogic, backend);
+        contract
+            .hit_endpoint(OracleEndpoints::OpenVault {
+                strike_price: 500,
+                amount: locked_amount,
+            })
+            .await
+            .unwrap();
+        contract
+            .hit_endpoint(OracleEndpoints::PostPrice { price: 600 })
+            .await
+            .unwrap();
+        let output_id = vault_id(&contract).await;
+
+        // when
+        contract
+            .hit_endpoint(OracleEndpoints::ClaimVault { output_id })
+            .await
+            .unwrap();
+
+        // then
+        let network = contract.ledger_client().network().await.unwrap();
+        let vaults = contract
+            .ledger_client()
+            .all_outputs_at_address(&vault_address(network).unwrap())
+            .await
+            .unwrap();
+        assert!(vaults.is_empty());
+        let price = contract.lookup(OracleLookups::CurrentPrice).await.unwrap();
+        assert!(matches!(
+            price,
+            OracleLookupResponses::CurrentPrice(Some(PriceFeed { price: 600, .. }))
+        ));
+        let my_balance = contract
+            .ledger_client()
+            .balance_at_address(&me, &PolicyId::Lovelace)
+            .await
+            .unwrap();
+        assert_eq!(my_balance, start_amount - FEED_LOVELACE);
+    }
+
+    #[tokio::test]
+    async fn claim_vault__fails_below_the_strike_price() {
+        // given
+        let me = Address::from_bech32(ME).unwrap();
+        let backend = TestLedgerClientBuilder::new(&me)
+            .start_output(&me)
+            .with_value(PolicyId::Lovelace, 100_000_000)
+            .finish_output()
+            .build_in_memory();
+        let contract = SmartContract::new(OracleLogic, backend);
+        contract
+            .hit_endpoint(OracleEndpoints::OpenVault {
+                strike_price: 500,
+                amount: 10_000_000,
+            })
+            .await
+            .unwrap();
+        contract
+            .hit_endpoint(OracleEndpoints::PostPrice { price: 400 })
+            .await
+            .unwrap();
+        let output_id = vault_id(&contract).await;
+
+        // when
+        let res = contract
+            .hit_endpoint(OracleEndpoints::ClaimVault { output_id })
+            .await;
+
+        // then
+        assert!(res.is_err());
+        let network = contract.ledger_client().network().await.unwrap();
+        let vaults = contract
+            .ledger_client()
+            .all_outputs_at_address(&vault_address(network).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(vaults.len(), 1);
+    }
+}
@@ -0,0 +1,73 @@
+use clap::Parser;
+use naumachia::{
+    smart_contract::{
+        SmartContract,
+        SmartContractTrait,
+    },
+    trireme_ledger_client::get_trireme_ledger_client_from_file,
+};
+use oracle::logic::{
+    OracleEndpointsCli,
+    OracleLogic,
+    OracleLookupResponses,
+    OracleLookups,
+};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    #[clap(subcommand)]
+    action: ActionParams,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum ActionParams {
+    #[clap(flatten)]
+    Endpoint(OracleEndpointsCli),
+    /// Show the latest posted price
+    Price,
+    /// List open vaults
+    List { count: usize },
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    let logic = OracleLogic;
+    let ledger_client = get_trireme_ledger_client_from_file().await.unwrap();
+    let contract = SmartContract::new(logic, ledger_client);
+
+    match args.action {
+        ActionParams::Endpoint(endpoint) => {
+            match contract.hit_endpoint(endpoint.into()).await {
+                Ok(tx_id) => println!("TxId: {:?}", tx_id),
+                Err(e) => println!("Error: {:?}", e),
+            }
+        }
+        ActionParams::Price => {
+            let res = contract.lookup(OracleLookups::CurrentPrice).await.unwrap();
+            match res {
+                OracleLookupResponses::CurrentPrice(Some(feed)) => {
+                    println!("Price: {} (posted at {})", feed.price, feed.updated_at)
+                }
+                _ => println!("No price posted yet"),
+            }
+        }
+        ActionParams::List { count } => {
+            let res = contract
+                .lookup(OracleLookups::ListVaults { count })
+                .await
+                .unwrap();
+            if let OracleLookupResponses::Vaults(outputs) = res {
+                println!("Open vaults:");
+                for output in outputs {
+                    println!("-------------------------------------");
+                    println!("output: {}", output.id());
+                    println!("{:?}", output.values());
+                    println!("{:?}", output.datum());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::datum::OracleDatums;
+use naumachia::scripts::{
+    cache::{
+        CachedBlueprint,
+        ScriptCache,
+    },
+    plutus_validator::{
+        plutus_data::PlutusData,
+        OneParamRawValidator,
+        PlutusValidator,
+    },
+    ScriptError,
+    ScriptResult,
+};
+
+const PRICE_FEED_VALIDATOR_NAME: &str = "price_feed.spend";
+const VAULT_VALIDATOR_NAME: &str = "vault.spend";
+
+/// Blueprint of every script in the oracle dApp, parsed once
+static BLUEPRINT: CachedBlueprint =
+    CachedBlueprint::new(include_str!("../price_oracle/plutus.json"));
+
+/// Hash of the script holding the price feed, which vaults read their price from
+pub struct FeedHash {
+    inner: Vec<u8>,
+}
+
+impl From<Vec<u8>> for FeedHash {
+    fn from(value: Vec<u8>) -> Self {
+        FeedHash { inner: value }
+    }
+}
+
+impl From<FeedHash> for PlutusData {
+    fn from(value: FeedHash) -> Self {
+        PlutusData::BoundedBytes(value.inner)
+    }
+}
+
+pub fn price_feed_validator() -> ScriptResult<PlutusValidator<OracleDatums, ()>> {
+    static VALIDATOR: ScriptCache<PlutusValidator<OracleDatums, ()>> = ScriptCache::new();
+    VALIDATOR.get_or_try_init(|| {
+        let validator_blueprint = BLUEPRINT.validator(PRICE_FEED_VALIDATOR_NAME)?;
+        PlutusValidator::from_blueprint(validator_blueprint)
+            .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))
+    })
+}
+
+fn raw_vault_validator() -> ScriptResult<OneParamRawValidator<FeedHash, OracleDatums, ()>>
+{
+    static VALIDATOR: ScriptCache<OneParamRawValidator<FeedHash, OracleDatums, ()>> =
+        ScriptCache::new();
+    VALIDATOR.get_or_try_init(|| {
+        let validator_blueprint = BLUEPRINT.validator(VAULT_VALIDATOR_NAME)?;
+        OneParamRawValidator::from_blueprint(validator_blueprint)
+            .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))
+    })
+}
+
+/// Vault validator trusting the feed at the script with `feed_hash`
+pub fn vault_validator(
+    feed_hash: Vec<u8>,
+) -> ScriptResult<PlutusValidator<OracleDatums, ()>> {
+    raw_vault_validator()?
+        .apply(feed_hash.into())
+        .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datum::{
+        PriceFeed,
+        Vault,
+    };
+    use naumachia::{
+        output::Output,
+        policy_id::PolicyId,
+        scripts::{
+            context::{
+                pub_key_hash_from_address_if_available,
+                script_hash_from_address,
+                ContextBuilder,
+            },
+            Validator,
+        },
+        values::Values,
+        Address,
+        Network,
+    };
+
+    const OWNER: &str = "addr_test1qpmtp5t0t5y6cqkaz7rfsyrx7mld77kpvksgkwm0p7en7qum7a589n30e80tclzrrnj8qr4qvzj6al0vpgtnmrkkksnqd8upj0";
+
+    fn feed_output(price: u64) -> Output<OracleDatums> {
+        let owner = Address::from_bech32(OWNER).unwrap();
+        let operator = pub_key_hash_from_address_if_available(&owner).unwrap();
+        let feed_address = price_feed_validator()
+            .unwrap()
+            .address(Network::Testnet)
+            .unwrap();
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, 2_000_000);
+        let datum = PriceFeed {
+            operator,
+            price,
+            updated_at: 0,
+        }
+        .into();
+        Output::new_validator(vec![1; 32], 0, feed_address, values, datum)
+    }
+
+    fn execute_vault(reference_inputs: &[Output<OracleDatums>]) -> ScriptResult<()> {
+        let owner = Address::from_bech32(OWNER).unwrap();
+        let owner_pkh = pub_key_hash_from_address_if_available(&owner).unwrap();
+        let feed_address = price_feed_validator()
+            .unwrap()
+            .address(Network::Testnet)
+            .unwrap();
+        let script =
+            vault_validator(script_hash_from_address(&feed_address).unwrap()).unwrap();
+        let ctx = reference_inputs
+            .iter()
+            .fold(ContextBuilder::new(owner_pkh.clone()), |builder, output| {
+                builder.add_reference_input(output)
+            })
+            .build_spend(&vec![], 0);
+        let datum = Vault {
+            owner: owner_pkh,
+            strike_price: 500,
+        }
+        .into();
+        script.execute(datum, (), ctx).map(|_| ())
+    }
+
+    #[test]
+    fn vault_succeeds_if_referenced_price_reaches_strike_price() {
+        execute_vault(&[feed_output(600)]).unwrap();
+    }
+
+    #[test]
+    fn vault_fails_if_referenced_price_is_below_strike_price() {
+        assert!(execute_vault(&[feed_output(400)]).is_err());
+    }
+
+    #[test]
+    fn vault_fails_without_the_feed_as_reference_input() {
+        assert!(execute_vault(&[]).is_err());
+    }
+}
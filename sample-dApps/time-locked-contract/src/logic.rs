@@ -1,6 +1,7 @@
 use crate::logic::script::get_script;
 use async_trait::async_trait;
 use naumachia::{
+    cli::EndpointsCli,
     ledger_client::LedgerClient,
     logic::{
         error::{
@@ -26,9 +27,16 @@ pub mod script;
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct TimeLockedLogic;
 
-#[derive(Debug)]
+#[derive(Debug, EndpointsCli)]
 pub enum TimeLockedEndpoints {
-    Lock { amount: u64, after_secs: i64 },
+    /// Lock amount at script address
+    Lock {
+        /// Lovelace, or ADA like `2.5ADA`
+        #[cli(lovelace)]
+        amount: u64,
+        after_secs: i64,
+    },
+    /// Claim locked Output, given as `<tx hash>#<index>`, at script address
     Claim { output_id: OutputId },
 }
 
@@ -117,7 +125,7 @@ async fn impl_claim<LC: LedgerClient<i64, ()>>(
         .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
     let redeemer = ();
     let script_box = Box::new(script);
-    let lower_bound = if let DatumKind::Typed(inner) = output.datum().clone() {
+    let lower_bound = if let Some(DatumKind::Typed(inner)) = output.datum().value() {
         inner / 1000
     } else {
         return Err(SCLogicError::Endpoint(Box::new(
@@ -52,7 +52,8 @@ impl TryFrom<PlutusData> for Timestamp {
                     .ok_or(format!("Data constr fields empty"))?;
                 match field {
                     PlutusData::BigInt(big_int) => {
-                        let milliseconds = big_int.into();
+                        let milliseconds =
+                            i64::try_from(big_int).map_err(|e| e.to_string())?;
                         let timestamp = Timestamp { milliseconds };
                         Ok(timestamp)
                     }
@@ -124,6 +125,29 @@ mod tests {
         let datum = 69_i64;
         let error = script.execute(datum, (), ctx);
 
-        assert!(error.is_err());
+        match error {
+            Err(ScriptError::Evaluation(failure)) => {
+                assert_eq!(failure.name(), Some(VALIDATOR_NAME));
+                assert_eq!(failure.datum(), Some(&PlutusData::from(datum)));
+                assert!(failure.cost().cpu() > 0);
+            }
+            other => panic!("Expected an evaluation failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn blueprint_schema_catches_wrapped_timestamp() {
+        let script_file: BlueprintFile = serde_json::from_str(BLUEPRINT).unwrap();
+
+        let plain: PlutusData = 69_i64.into();
+        let wrapped: PlutusData = Timestamp::new(69).into();
+
+        assert_eq!(
+            script_file
+                .decode_datum_as::<i64>(VALIDATOR_NAME, &plain)
+                .unwrap(),
+            69
+        );
+        assert!(script_file.decode_datum(VALIDATOR_NAME, &wrapped).is_err());
     }
 }
@@ -0,0 +1,269 @@
+use crate::{
+    error::ErrorClass,
+    scripts::context::PubKeyHash,
+};
+use pallas_addresses::{
+    Address,
+    Network,
+    ShelleyDelegationPart,
+    ShelleyPaymentPart,
+};
+use thiserror::Error;
+
+// Size of key and script hashes used as credentials
+const CREDENTIAL_HASH_LENGTH: usize = 28;
+
+// Shelley address header types, see CIP-19
+const BASE_KEY_KEY: u8 = 0b0000;
+const BASE_SCRIPT_KEY: u8 = 0b0001;
+const BASE_KEY_SCRIPT: u8 = 0b0010;
+const BASE_SCRIPT_SCRIPT: u8 = 0b0011;
+const ENTERPRISE_KEY: u8 = 0b0110;
+const ENTERPRISE_SCRIPT: u8 = 0b0111;
+const STAKE_KEY: u8 = 0b1110;
+const STAKE_SCRIPT: u8 = 0b1111;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AddressError {
+    #[error("Credential hashes are 28 bytes, got {0}")]
+    InvalidHashLength(usize),
+    #[error("Expected a Shelley payment address")]
+    NotShelley,
+    #[error("Address {address} isn't on the {expected:?} network")]
+    WrongNetwork { address: String, expected: Network },
+    #[error("Failed to build address: {0}")]
+    Pallas(String),
+}
+
+impl AddressError {
+    /// Stable code identifying the failure
+    pub fn code(&self) -> &'static str {
+        match self {
+            AddressError::InvalidHashLength(_) => "address.invalid_hash_length",
+            AddressError::NotShelley => "address.not_shelley",
+            AddressError::WrongNetwork { .. } => "address.wrong_network",
+            AddressError::Pallas(_) => "address.pallas",
+        }
+    }
+
+    /// Broad class of the failure
+    pub fn class(&self) -> ErrorClass {
+        ErrorClass::InvalidInput
+    }
+}
+
+/// Specialized result type for building addresses
+pub type AddressResult<T, E = AddressError> = Result<T, E>;
+
+/// Payment or staking credential: the hash of a verification key or of a script
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Credential {
+    /// Hash of a verification key
+    Key(Vec<u8>),
+    /// Hash of a script
+    Script(Vec<u8>),
+}
+
+impl Credential {
+    /// Getter for the key or script hash
+    pub fn hash(&self) -> &[u8] {
+        match self {
+            Credential::Key(hash) | Credential::Script(hash) => hash,
+        }
+    }
+
+    /// Whether the credential is a script hash
+    pub fn is_script(&self) -> bool {
+        matches!(self, Credential::Script(_))
+    }
+
+    fn checked_hash(&self) -> AddressResult<&[u8]> {
+        let hash = self.hash();
+        if hash.len() == CREDENTIAL_HASH_LENGTH {
+            Ok(hash)
+        } else {
+            Err(AddressError::InvalidHashLength(hash.len()))
+        }
+    }
+}
+
+impl From<PubKeyHash> for Credential {
+    fn from(pub_key_hash: PubKeyHash) -> Self {
+        Credential::Key(pub_key_hash.bytes())
+    }
+}
+
+impl From<&ShelleyPaymentPart> for Credential {
+    fn from(payment: &ShelleyPaymentPart) -> Self {
+        match payment {
+            ShelleyPaymentPart::Key(hash) => Credential::Key(hash.to_vec()),
+            ShelleyPaymentPart::Script(hash) => Credential::Script(hash.to_vec()),
+        }
+    }
+}
+
+/// Address with only a payment part, which can't be delegated
+pub fn enterprise_address(
+    network: Network,
+    payment: &Credential,
+) -> AddressResult<Address> {
+    let header_type = if payment.is_script() {
+        ENTERPRISE_SCRIPT
+    } else {
+        ENTERPRISE_KEY
+    };
+    from_parts(header_type, network, &[payment.checked_hash()?])
+}
+
+/// Address with a payment part and a staking part
+pub fn base_address(
+    network: Network,
+    payment: &Credential,
+    stake: &Credential,
+) -> AddressResult<Address> {
+    let header_type = match (payment.is_script(), stake.is_script()) {
+        (false, false) => BASE_KEY_KEY,
+        (true, false) => BASE_SCRIPT_KEY,
+        (false, true) => BASE_KEY_SCRIPT,
+        (true, true) => BASE_SCRIPT_SCRIPT,
+    };
+    from_parts(
+        header_type,
+        network,
+        &[payment.checked_hash()?, stake.checked_hash()?],
+    )
+}
+
+/// Reward address for the `stake` credential, e.g. `stake_test1...`
+pub fn stake_address(network: Network, stake: &Credential) -> AddressResult<Address> {
+    let header_type = if stake.is_script() {
+        STAKE_SCRIPT
+    } else {
+        STAKE_KEY
+    };
+    from_parts(header_type, network, &[stake.checked_hash()?])
+}
+
+/// Same payment part and network as `address`, delegated with `stake` instead
+pub fn with_stake_credential(
+    address: &Address,
+    stake: &Credential,
+) -> AddressResult<Address> {
+    match address {
+        Address::Shelley(shelley) => {
+            let payment = Credential::from(shelley.payment());
+            base_address(shelley.network(), &payment, stake)
+        }
+        _ => Err(AddressError::NotShelley),
+    }
+}
+
+/// Same payment part and network as `address`, without any staking part
+pub fn without_stake_credential(address: &Address) -> AddressResult<Address> {
+    match address {
+        Address::Shelley(shelley) => match shelley.delegation() {
+            ShelleyDelegationPart::Null => Ok(address.clone()),
+            _ => {
+                let payment = Credential::from(shelley.payment());
+                enterprise_address(shelley.network(), &payment)
+            }
+        },
+        _ => Err(AddressError::NotShelley),
+    }
+}
+
+/// Check that `address` belongs to `network`, judging by its header
+///
+/// Byron addresses don't carry the network in their header and are always accepted.
+pub fn check_network(address: &Address, network: Network) -> AddressResult<()> {
+    let address_network = match address {
+        Address::Shelley(shelley) => shelley.network(),
+        Address::Stake(stake) => stake.network(),
+        Address::Byron(_) => return Ok(()),
+    };
+    if address_network == network {
+        Ok(())
+    } else {
+        Err(AddressError::WrongNetwork {
+            address: address.to_bech32().unwrap_or_else(|_| address.to_hex()),
+            expected: network,
+        })
+    }
+}
+
+fn from_parts(
+    header_type: u8,
+    network: Network,
+    hashes: &[&[u8]],
+) -> AddressResult<Address> {
+    let network_id = match network {
+        Network::Testnet => 0,
+        Network::Mainnet => 1,
+        Network::Other(inner) => inner,
+    };
+    let mut bytes = vec![(header_type << 4) | (network_id & 0x0f)];
+    for hash in hashes {
+        bytes.extend_from_slice(hash);
+    }
+    Address::from_bytes(&bytes).map_err(|e| AddressError::Pallas(format!("{e:?}")))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    const BASE_ADDR: &str = "addr_test1qpuy2q9xel76qxdw8r29skldzc876cdgg9cugfg7mwh0zvpg3292mxuf3kq7nysjumlxjrlsfn9tp85r0l54l29x3qcs7nvyfm";
+
+    #[test]
+    fn without_stake_credential__then_with_stake_credential__round_trips() {
+        // given
+        let base = Address::from_bech32(BASE_ADDR).unwrap();
+        let stake = match &base {
+            Address::Shelley(shelley) => match shelley.delegation() {
+                ShelleyDelegationPart::Key(hash) => Credential::Key(hash.to_vec()),
+                _ => panic!("expected a key staking part"),
+            },
+            _ => panic!("expected a Shelley address"),
+        };
+
+        // when
+        let enterprise = without_stake_credential(&base).unwrap();
+        let rebuilt = with_stake_credential(&enterprise, &stake).unwrap();
+
+        // then
+        assert!(enterprise.to_bech32().unwrap().starts_with("addr_test1v"));
+        assert_eq!(rebuilt, base);
+    }
+
+    #[test]
+    fn stake_address__uses_reward_prefix() {
+        let stake = Credential::Script(vec![3; 28]);
+
+        let address = stake_address(Network::Testnet, &stake).unwrap();
+
+        assert!(address.to_bech32().unwrap().starts_with("stake_test1"));
+    }
+
+    #[test]
+    fn check_network__rejects_other_network() {
+        let address = Address::from_bech32(BASE_ADDR).unwrap();
+
+        assert!(check_network(&address, Network::Testnet).is_ok());
+        assert!(matches!(
+            check_network(&address, Network::Mainnet),
+            Err(AddressError::WrongNetwork { .. })
+        ));
+    }
+
+    #[test]
+    fn enterprise_address__rejects_short_hashes() {
+        let payment = Credential::Key(vec![1; 20]);
+
+        let res = enterprise_address(Network::Mainnet, &payment);
+
+        assert_eq!(res, Err(AddressError::InvalidHashLength(20)));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{
+    output::{
+        OutputId,
+        OutputIdParseError,
+    },
+    scripts::context::PubKeyHash,
+    PolicyId,
+};
+use pallas_addresses::Address;
+
+pub use naumachia_macros::EndpointsCli;
+
+const LOVELACE_PER_ADA: u64 = 1_000_000;
+const ADA_DECIMALS: usize = 6;
+const POLICY_ID_LEN: usize = 28;
+
+/// Parse an [`OutputId`] written as `<tx hash hex>#<index>`
+pub fn parse_output_id(s: &str) -> Result<OutputId, String> {
+    s.parse().map_err(|e: OutputIdParseError| e.to_string())
+}
+
+/// Parse a bech32 [`Address`]
+pub fn parse_address(s: &str) -> Result<Address, String> {
+    Address::from_bech32(s).map_err(|e| format!("Invalid address: {e:?}"))
+}
+
+/// Parse a hex encoded [`PubKeyHash`]
+pub fn parse_pub_key_hash(s: &str) -> Result<PubKeyHash, String> {
+    let bytes = hex::decode(s).map_err(|e| format!("Invalid pub key hash: {e}"))?;
+    Ok(PubKeyHash::new(&bytes))
+}
+
+/// Parse a [`PolicyId`], given as `lovelace` or `<policy id>[.<asset name>]`, both in hex
+pub fn parse_policy_id(s: &str) -> Result<PolicyId, String> {
+    if s.eq_ignore_ascii_case("lovelace") {
+        return Ok(PolicyId::Lovelace)
+    }
+    let (id, asset_name) = match s.split_once('.') {
+        Some((id, asset_name)) => {
+            let asset_name = hex::decode(asset_name)
+                .map_err(|e| format!("Invalid asset name: {e}"))?;
+            (id, Some(asset_name))
+        }
+        None => (s, None),
+    };
+    let id_bytes = hex::decode(id).map_err(|e| format!("Invalid policy id: {e}"))?;
+    if id_bytes.len() != POLICY_ID_LEN {
+        return Err(format!(
+            "Expected a {POLICY_ID_LEN} byte policy id, got {} bytes",
+            id_bytes.len()
+        ))
+    }
+    Ok(PolicyId::native_token(id, &asset_name))
+}
+
+/// Parse an amount of lovelace, given either as lovelace or as ADA with an `ADA` suffix
+///
+/// E.g. `2500000`, `2.5ADA` and `2.5 ada` are all 2,500,000 lovelace.
+pub fn parse_lovelace(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let lower = s.to_lowercase();
+    let ada = match lower.strip_suffix("ada") {
+        Some(ada) => ada.trim_end(),
+        None => {
+            return s
+                .parse()
+                .map_err(|e| format!("Invalid lovelace amount {s:?}: {e}"))
+        }
+    };
+    let invalid = || format!("Invalid ADA amount {s:?}");
+    let (whole, fraction) = ada.split_once('.').unwrap_or((ada, ""));
+    if fraction.len() > ADA_DECIMALS || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid())
+    }
+    let whole: u64 = whole.parse().map_err(|_| invalid())?;
+    let fraction: u64 = format!("{fraction:0<ADA_DECIMALS$}")
+        .parse()
+        .map_err(|_| invalid())?;
+    whole
+        .checked_mul(LOVELACE_PER_ADA)
+        .and_then(|lovelace| lovelace.checked_add(fraction))
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn parse_lovelace__accepts_lovelace_and_ada() {
+        assert_eq!(parse_lovelace("2500000"), Ok(2_500_000));
+        assert_eq!(parse_lovelace("2.5ADA"), Ok(2_500_000));
+        assert_eq!(parse_lovelace("2.5 ada"), Ok(2_500_000));
+        assert_eq!(parse_lovelace("3ADA"), Ok(3_000_000));
+        assert_eq!(parse_lovelace("0.000001ADA"), Ok(1));
+    }
+
+    #[test]
+    fn parse_lovelace__rejects_sub_lovelace_amounts() {
+        assert!(parse_lovelace("0.0000001ADA").is_err());
+        assert!(parse_lovelace("1.5").is_err());
+        assert!(parse_lovelace("-1ADA").is_err());
+    }
+
+    #[test]
+    fn parse_policy_id__reads_lovelace_and_tokens_with_or_without_names() {
+        let policy = "57fca08abbaddee36da742a839f7d83a7e1d2419f1507fcbf3916522";
+        assert_eq!(parse_policy_id("lovelace"), Ok(PolicyId::Lovelace));
+        assert_eq!(
+            parse_policy_id(policy),
+            Ok(PolicyId::native_token(policy, &None))
+        );
+        assert_eq!(
+            parse_policy_id(&format!("{policy}.4e4654")),
+            Ok(PolicyId::native_token(policy, &Some(b"NFT".to_vec())))
+        );
+        assert!(parse_policy_id("abcd").is_err());
+        assert!(parse_policy_id(&format!("{policy}.NFT")).is_err());
+    }
+
+    #[test]
+    fn parse_output_id__reads_hash_and_index() {
+        // when
+        let output_id = parse_output_id("abcd#3").unwrap();
+
+        // then
+        assert_eq!(output_id.tx_hash(), &[0xab, 0xcd]);
+        assert_eq!(output_id.index(), 3);
+    }
+}
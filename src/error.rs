@@ -7,10 +7,33 @@ use crate::{
     logic::error::SCLogicError,
     policy_id::PolicyId,
     scripts::ScriptError,
+    transaction::TxId,
+    values::ValuesError,
 };
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// Broad class of a failure, for handling errors without matching on every variant
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    /// Not enough funds to build or balance the transaction
+    InsufficientFunds,
+    /// A validator or minting policy failed or couldn't be run
+    ScriptFailure,
+    /// The ledger provider failed or couldn't be reached
+    ProviderFailure,
+    /// The provider received the transaction and rejected it, e.g. as invalid
+    Rejected,
+    /// Malformed or inconsistent input, like a bad address or datum
+    InvalidInput,
+    /// Misconfigured scripts, wallets, providers or project files
+    Configuration,
+    /// Refused on purpose, by a safety interlock or hook
+    Refused,
+    /// Raised by the contract's own endpoint or lookup logic
+    Application,
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Error with Address")]
@@ -23,6 +46,8 @@ pub enum Error {
     SCLogic(#[from] SCLogicError),
     #[error("Error: Insufficient amount of {0:?}.")]
     InsufficientAmountOf(PolicyId),
+    #[error("Values Error: {0}")]
+    Values(#[from] ValuesError),
     #[error("Error: Failed to retrieve policy for {0:?}.")]
     FailedToRetrievePolicyFor(PolicyId),
     #[error("Error: Failed to retrieve script for {0:?}.")]
@@ -31,8 +56,181 @@ pub enum Error {
     FailedToRetrieveRedeemerFor(Address),
     #[error("Unable to mint ADA/Lovelace")]
     ImpossibleToMintADA,
+    #[error("Unable to combine transaction actions: {0}")]
+    IncompatibleTxActions(String),
+    #[error("No endpoints given to hit")]
+    NoEndpoints,
+    #[error("Transaction {0:?} wasn't confirmed in time")]
+    TxNotConfirmed(TxId),
+    #[error("Failed after submitting {submitted:?}: {error}")]
+    IncompleteSteps {
+        submitted: Vec<TxId>,
+        error: Box<Error>,
+    },
+    #[error("Refused by mainnet safety interlock: {0}")]
+    MainnetSafety(String),
+    #[error("Endpoint rejected by hook: {0}")]
+    RejectedByHook(String),
+    #[error("Error serving JSON-RPC: {0}")]
+    Rpc(String),
     #[error("Error with Trireme integration: {0:?}")]
     Trireme(String),
     #[error("Error dealing with TOML files: {0:?}")]
-    TOML(Box<dyn std::error::Error + Send + Sync>),
+    TOML(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl Error {
+    /// Stable code identifying the failure, e.g. `ledger_client.failed_to_issue_tx`
+    ///
+    /// Wrapped errors report the code of the underlying error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Address(_) => "naumachia.address",
+            Error::LedgerClient(error) => error.code(),
+            Error::Script(error) => error.code(),
+            Error::SCLogic(error) => error.code(),
+            Error::Values(error) => error.code(),
+            Error::InsufficientAmountOf(_) => "naumachia.insufficient_amount",
+            Error::FailedToRetrievePolicyFor(_) => "naumachia.missing_policy",
+            Error::FailedToRetrieveScriptFor(_) => "naumachia.missing_script",
+            Error::FailedToRetrieveRedeemerFor(_) => "naumachia.missing_redeemer",
+            Error::ImpossibleToMintADA => "naumachia.mint_ada",
+            Error::IncompatibleTxActions(_) => "naumachia.incompatible_tx_actions",
+            Error::NoEndpoints => "naumachia.no_endpoints",
+            Error::TxNotConfirmed(_) => "naumachia.tx_not_confirmed",
+            Error::IncompleteSteps { error, .. } => error.code(),
+            Error::MainnetSafety(_) => "naumachia.mainnet_safety",
+            Error::RejectedByHook(_) => "naumachia.rejected_by_hook",
+            Error::Rpc(_) => "naumachia.rpc",
+            Error::Trireme(_) => "naumachia.trireme",
+            Error::TOML(_) => "naumachia.toml",
+        }
+    }
+
+    /// Broad class of the failure
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            Error::LedgerClient(error) => error.class(),
+            Error::Script(error) => error.class(),
+            Error::SCLogic(error) => error.class(),
+            Error::Values(error) => error.class(),
+            Error::IncompleteSteps { error, .. } => error.class(),
+            Error::TxNotConfirmed(_) => ErrorClass::ProviderFailure,
+            Error::InsufficientAmountOf(_) => ErrorClass::InsufficientFunds,
+            Error::FailedToRetrievePolicyFor(_)
+            | Error::FailedToRetrieveScriptFor(_)
+            | Error::FailedToRetrieveRedeemerFor(_)
+            | Error::Trireme(_)
+            | Error::TOML(_) => ErrorClass::Configuration,
+            Error::Address(_)
+            | Error::ImpossibleToMintADA
+            | Error::IncompatibleTxActions(_)
+            | Error::NoEndpoints
+            | Error::Rpc(_) => ErrorClass::InvalidInput,
+            Error::MainnetSafety(_) | Error::RejectedByHook(_) => ErrorClass::Refused,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::scripts::{
+        context::CtxScriptPurpose,
+        plutus_validator::plutus_data::PlutusData,
+        ExecutionCost,
+        ScriptFailure,
+    };
+    use std::error::Error as _;
+
+    #[derive(Debug, Error)]
+    #[error("connection reset")]
+    struct ProviderError;
+
+    #[test]
+    fn class__sees_through_wrapped_errors() {
+        // given
+        let ledger_error = LedgerClientError::FailedToIssueTx(Box::new(ProviderError));
+        let error = Error::SCLogic(SCLogicError::LedgerClient(ledger_error));
+
+        // when
+        let class = error.class();
+        let code = error.code();
+
+        // then
+        assert_eq!(class, ErrorClass::ProviderFailure);
+        assert_eq!(code, "ledger_client.failed_to_issue_tx");
+    }
+
+    #[test]
+    fn source__chains_to_the_root_cause() {
+        let ledger_error = LedgerClientError::FailedToIssueTx(Box::new(ProviderError));
+        let error = Error::SCLogic(SCLogicError::LedgerClient(ledger_error));
+
+        let mut root: &dyn std::error::Error = &error;
+        while let Some(source) = root.source() {
+            root = source;
+        }
+
+        assert_eq!(root.to_string(), "connection reset");
+    }
+
+    #[test]
+    fn script_failure__is_found_behind_rejected_submission() {
+        // given
+        let failure = ScriptFailure::new(
+            CtxScriptPurpose::Mint(vec![1, 2, 3]),
+            PlutusData::BigInt(0_i64.into()),
+            ExecutionCost::new(10, 20),
+            "EvaluationFailure".to_string(),
+        )
+        .with_traces(vec!["not signed by owner".to_string()]);
+        let script_error = ScriptError::Evaluation(Box::new(failure.clone()));
+
+        // when
+        let error = LedgerClientError::FailedToIssueTx(Box::new(script_error));
+
+        // then
+        assert_eq!(error.script_failure(), Some(&failure));
+        assert_eq!(error.class(), ErrorClass::ScriptFailure);
+    }
+
+    #[test]
+    fn class__separates_rejected_submissions_from_provider_failures() {
+        // given
+        let rejected = crate::ledger_client::provider_error::ProviderError::new(
+            "Blockfrost",
+            "POST /tx/submit",
+        )
+        .with_status(400)
+        .with_body("BadInputsUTxO");
+        let unavailable = crate::ledger_client::provider_error::ProviderError::new(
+            "Blockfrost",
+            "POST /tx/submit",
+        )
+        .with_status(503);
+
+        // when
+        let rejected = LedgerClientError::FailedToIssueTx(Box::new(rejected));
+        let unavailable = LedgerClientError::FailedToIssueTx(Box::new(unavailable));
+
+        // then
+        assert_eq!(rejected.class(), ErrorClass::Rejected);
+        assert_eq!(unavailable.class(), ErrorClass::ProviderFailure);
+    }
+
+    #[test]
+    fn class__separates_insufficient_funds_from_script_failures() {
+        let underflow = Error::Values(ValuesError::Underflow {
+            policy: PolicyId::Lovelace,
+            available: 1,
+            required: 2,
+        });
+        let script = Error::Script(ScriptError::FailedToExecute("boom".to_string()));
+
+        assert_eq!(underflow.class(), ErrorClass::InsufficientFunds);
+        assert_eq!(script.class(), ErrorClass::ScriptFailure);
+    }
 }
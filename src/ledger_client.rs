@@ -1,27 +1,60 @@
 use thiserror::Error;
 
+/// Sources of the current time
+pub mod clock;
+/// Faucets for funding wallets in tests and on local networks
+pub mod faucet;
+/// Context of failed requests to remote providers
+pub mod provider_error;
+/// Retrying failed submissions
+pub mod retry;
 /// Test ledger client module
 pub mod test_ledger_client;
 
 use async_trait::async_trait;
 
 use crate::{
+    address::AddressError,
+    error::ErrorClass,
+    ledger_client::{
+        provider_error::ProviderError,
+        retry::RetryPolicy,
+    },
     output::{
         Output,
         OutputId,
     },
+    scripts::{
+        plutus_validator::plutus_data::PlutusData,
+        ScriptError,
+        ScriptFailure,
+    },
     transaction::{
         TxId,
+        TxPreview,
         UnbuiltTransaction,
     },
+    values::Values,
     PolicyId,
 };
+use futures::{
+    future,
+    stream::{
+        self,
+        BoxStream,
+    },
+    StreamExt,
+    TryStreamExt,
+};
 use pallas_addresses::{
     Address,
     Network,
 };
 use std::error;
 
+/// Most address queries [`LedgerClient::all_outputs_at_addresses`] has in flight at once
+pub const MAX_CONCURRENT_ADDRESS_QUERIES: usize = 8;
+
 /// Interface defining interactions with your specific ledger--AKA the Cardano blockchain. The
 /// abstraction allows the concept of fake and mock ledgers to be used in tests and simulations.
 // TODO: Having this bound to a specific Datum/Redeemer doesn't really make sense at this scope.
@@ -47,24 +80,142 @@ pub trait LedgerClient<Datum, Redeemer>: Send + Sync {
         address: &Address,
     ) -> LedgerClientResult<Vec<Output<Datum>>>;
 
+    /// Get complete lists of UTxOs at several addresses, e.g. one per validator, querying
+    /// them concurrently
+    ///
+    /// At most [`MAX_CONCURRENT_ADDRESS_QUERIES`] queries are in flight at once. Repeated
+    /// addresses are queried once, and outputs come back in the order of `addresses`.
+    async fn all_outputs_at_addresses(
+        &self,
+        addresses: &[Address],
+    ) -> LedgerClientResult<Vec<Output<Datum>>>
+    where
+        Datum: Send,
+    {
+        let mut unique: Vec<&Address> = Vec::new();
+        for address in addresses {
+            if !unique.contains(&address) {
+                unique.push(address);
+            }
+        }
+        let per_address: Vec<Vec<Output<Datum>>> = stream::iter(unique)
+            .map(|address| self.all_outputs_at_address(address))
+            .buffered(MAX_CONCURRENT_ADDRESS_QUERIES)
+            .try_collect()
+            .await?;
+        Ok(per_address.into_iter().flatten().collect())
+    }
+
+    /// Stream the UTxOs owned by `address` one at a time
+    ///
+    /// Lets bots scanning busy script addresses work through outputs without holding all of
+    /// them. The default fetches them with
+    /// [`all_outputs_at_address`](Self::all_outputs_at_address); clients that can page or
+    /// convert outputs lazily should override it.
+    fn stream_outputs_at_address<'a>(
+        &'a self,
+        address: &'a Address,
+    ) -> BoxStream<'a, LedgerClientResult<Output<Datum>>>
+    where
+        Datum: Send + 'a,
+    {
+        stream::once(self.all_outputs_at_address(address))
+            .map_ok(|outputs| stream::iter(outputs.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed()
+    }
+
+    /// Get the first UTxO at `address` matching `predicate`, without collecting the rest
+    async fn find_output_at_address<P>(
+        &self,
+        address: &Address,
+        predicate: P,
+    ) -> LedgerClientResult<Option<Output<Datum>>>
+    where
+        Datum: Send,
+        P: Fn(&Output<Datum>) -> bool + Send + Sync,
+    {
+        let found = self
+            .stream_outputs_at_address(address)
+            .try_filter(|output| future::ready(predicate(output)))
+            .try_next()
+            .await?;
+        Ok(found)
+    }
+
+    /// Get every UTxO at `address` whose datum matches `predicate`
+    ///
+    /// `predicate` sees the datum as [`PlutusData`], so clients can check it before converting
+    /// the output to `Datum`. Outputs without a known datum never match. The default filters
+    /// [`stream_outputs_at_address`](Self::stream_outputs_at_address); clients that can filter
+    /// datums server side should override it.
+    async fn outputs_at_address_matching<P>(
+        &self,
+        address: &Address,
+        predicate: P,
+    ) -> LedgerClientResult<Vec<Output<Datum>>>
+    where
+        Datum: Clone + Into<PlutusData> + Send,
+        P: Fn(&PlutusData) -> bool + Send + Sync,
+    {
+        self.stream_outputs_at_address(address)
+            .try_filter(|output| {
+                let matches = output
+                    .datum_plutus_data()
+                    .map_or(false, |data| predicate(&data));
+                future::ready(matches)
+            })
+            .try_collect()
+            .await
+    }
+
     /// Get the balance for a specific policy at a given address
+    ///
+    /// Use [`LedgerClient::balances_at_address`] when more than one policy is needed, so the
+    /// outputs are only fetched once.
     async fn balance_at_address(
         &self,
         address: &Address,
         policy: &PolicyId,
     ) -> LedgerClientResult<u64> {
-        let bal = self
+        let bal = self.balances_at_address(address).await?.get(policy);
+        Ok(bal.unwrap_or(0))
+    }
+
+    /// Get the balance of every policy at a given address
+    async fn balances_at_address(&self, address: &Address) -> LedgerClientResult<Values> {
+        let outputs = self.all_outputs_at_address(address).await?;
+        Ok(Values::from_outputs(&outputs))
+    }
+
+    /// Get the output at `address` holding the `nft`, if any
+    ///
+    /// Thread-token contracts can use this to find the output carrying their state.
+    async fn output_holding_nft(
+        &self,
+        address: &Address,
+        nft: &PolicyId,
+    ) -> LedgerClientResult<Option<Output<Datum>>> {
+        let output = self
             .all_outputs_at_address(address)
             .await?
-            .iter()
-            .fold(0, |acc, o| {
-                if let Some(val) = o.values().get(policy) {
-                    acc + val
-                } else {
-                    acc
-                }
-            });
-        Ok(bal)
+            .into_iter()
+            .find(|o| matches!(o.values().get(nft), Some(amt) if amt > 0));
+        Ok(output)
+    }
+
+    /// Get the native tokens at `address` with a total quantity of exactly one
+    async fn nfts_at_address(
+        &self,
+        address: &Address,
+    ) -> LedgerClientResult<Vec<PolicyId>> {
+        let outputs = self.all_outputs_at_address(address).await?;
+        let nfts = Values::from_outputs(&outputs)
+            .as_iter()
+            .filter(|(policy, amt)| **policy != PolicyId::Lovelace && **amt == 1)
+            .map(|(policy, _)| policy.clone())
+            .collect();
+        Ok(nfts)
     }
 
     /// Issue a transaction to the ledger signed by the signer key owned by the instance of `LedgerClient`
@@ -73,6 +224,55 @@ pub trait LedgerClient<Datum, Redeemer>: Send + Sync {
         tx: UnbuiltTransaction<Datum, Redeemer>,
     ) -> LedgerClientResult<TxId>;
 
+    /// Issue a transaction like [`issue`](Self::issue), resubmitting the same signed
+    /// transaction when submission fails in a way `policy` considers transient
+    ///
+    /// If the inputs were spent by another transaction instead, it's built again from `tx`,
+    /// so new inputs are selected.
+    ///
+    /// Clients that can't resubmit a transaction issue it once.
+    async fn issue_with_retry(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+        _policy: &RetryPolicy,
+    ) -> LedgerClientResult<TxId> {
+        self.issue(tx).await
+    }
+
+    /// Build a transaction the way [`issue`](Self::issue) would, without signing or
+    /// submitting it
+    ///
+    /// Clients that can't build a transaction without issuing it return
+    /// [`LedgerClientError::Unsupported`].
+    async fn preview(
+        &self,
+        _tx: UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<TxPreview<Datum>> {
+        Err(LedgerClientError::Unsupported(
+            "previewing transactions".to_string(),
+        ))
+    }
+
+    /// Issue a transaction like [`issue`](Self::issue), also describing what was built, e.g.
+    /// to record the fee and execution units of submitted transactions
+    ///
+    /// Clients that can't describe the built transaction, or fail to once it is submitted,
+    /// return `None` for it.
+    async fn issue_with_preview(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<(TxId, Option<TxPreview<Datum>>)> {
+        Ok((self.issue(tx).await?, None))
+    }
+
+    /// Whether the transaction `tx_id` made it on chain
+    ///
+    /// Clients whose transactions take effect as soon as they are issued, like the
+    /// [`TestLedgerClient`](test_ledger_client::TestLedgerClient), always say it did.
+    async fn is_confirmed(&self, _tx_id: &TxId) -> LedgerClientResult<bool> {
+        Ok(true)
+    }
+
     /// Get the network identifier for the ledger
     async fn network(&self) -> LedgerClientResult<Network>;
 
@@ -80,6 +280,9 @@ pub trait LedgerClient<Datum, Redeemer>: Send + Sync {
     async fn last_block_time_secs(&self) -> LedgerClientResult<i64>;
 
     /// Get the current time in seconds since the UNIX epoch.
+    ///
+    /// Endpoint handlers should use this, rather than the system time, for anything
+    /// time-dependent so tests and the chain agree with them.
     async fn current_time_secs(&self) -> LedgerClientResult<i64>;
 }
 
@@ -87,29 +290,133 @@ pub trait LedgerClient<Datum, Redeemer>: Send + Sync {
 #[derive(Debug, Error)]
 pub enum LedgerClientError {
     #[error("Couldn't retrieve base address")]
-    BaseAddress(Box<dyn error::Error + Send + Sync>),
+    BaseAddress(#[source] Box<dyn error::Error + Send + Sync>),
+    #[error("Couldn't retrieve payment public key")]
+    PaymentPublicKey(#[source] Box<dyn error::Error + Send + Sync>),
     #[error("Bad address: {0:?}")]
-    BadAddress(Box<dyn error::Error + Send + Sync>),
+    BadAddress(#[source] Box<dyn error::Error + Send + Sync>),
     #[error("Couldn't convert TxId")]
-    BadTxId(Box<dyn error::Error + Send + Sync>),
+    BadTxId(#[source] Box<dyn error::Error + Send + Sync>),
     #[error("Failed to retrieve outputs at {0:?}: {1:?}.")]
-    FailedToRetrieveOutputsAt(Address, Box<dyn error::Error + Send + Sync>),
+    FailedToRetrieveOutputsAt(Address, #[source] Box<dyn error::Error + Send + Sync>),
     #[error("Failed to retrieve UTXO with ID {0:?}.")]
-    FailedToRetrieveOutputWithId(OutputId, Box<dyn error::Error + Send + Sync>),
+    FailedToRetrieveOutputWithId(OutputId, #[source] Box<dyn error::Error + Send + Sync>),
+    #[error("Failed to retrieve transaction {0:?}: {1:?}")]
+    FailedToRetrieveTx(TxId, #[source] Box<dyn error::Error + Send + Sync>),
     #[error("Failed to issue transaction: {0:?}")]
-    FailedToIssueTx(Box<dyn error::Error + Send + Sync>),
+    FailedToIssueTx(#[source] Box<dyn error::Error + Send + Sync>),
     #[error("There isn't a single utxo big enough for collateral")]
     NoBigEnoughCollateralUTxO,
     #[error("The script input you're trying to spend doesn't have a datum")]
     NoDatumOnScriptInput,
-    #[error("The script input you're trying to spend doesn't have a datum")]
+    #[error("Configuration error: {0}")]
     ConfigError(String),
     #[error("While getting current time: {0:?}")]
-    CurrentTime(Box<dyn error::Error + Send + Sync>),
+    CurrentTime(#[source] Box<dyn error::Error + Send + Sync>),
     #[error("While setting validity range: {0:?}")]
     ValidityRange(String),
     #[error("While getting last block time: {0:?}")]
-    FailedToGetBlockTime(Box<dyn error::Error + Send + Sync>),
+    FailedToGetBlockTime(#[source] Box<dyn error::Error + Send + Sync>),
+    #[error("Faucet failed to fund address: {0:?}")]
+    Faucet(#[source] Box<dyn error::Error + Send + Sync>),
+    #[error("Transaction uses an address on the wrong network: {0}")]
+    WrongNetwork(#[source] AddressError),
+    #[error("Not supported by this ledger client: {0}")]
+    Unsupported(String),
+}
+
+impl LedgerClientError {
+    /// Stable code identifying the failure
+    pub fn code(&self) -> &'static str {
+        match self {
+            LedgerClientError::BaseAddress(_) => "ledger_client.base_address",
+            LedgerClientError::PaymentPublicKey(_) => "ledger_client.payment_public_key",
+            LedgerClientError::BadAddress(_) => "ledger_client.bad_address",
+            LedgerClientError::BadTxId(_) => "ledger_client.bad_tx_id",
+            LedgerClientError::FailedToRetrieveOutputsAt(..) => {
+                "ledger_client.failed_to_retrieve_outputs"
+            }
+            LedgerClientError::FailedToRetrieveOutputWithId(..) => {
+                "ledger_client.failed_to_retrieve_output"
+            }
+            LedgerClientError::FailedToRetrieveTx(..) => {
+                "ledger_client.failed_to_retrieve_tx"
+            }
+            LedgerClientError::FailedToIssueTx(_) => "ledger_client.failed_to_issue_tx",
+            LedgerClientError::NoBigEnoughCollateralUTxO => "ledger_client.no_collateral",
+            LedgerClientError::NoDatumOnScriptInput => {
+                "ledger_client.no_datum_on_script_input"
+            }
+            LedgerClientError::ConfigError(_) => "ledger_client.config",
+            LedgerClientError::CurrentTime(_) => "ledger_client.current_time",
+            LedgerClientError::ValidityRange(_) => "ledger_client.validity_range",
+            LedgerClientError::FailedToGetBlockTime(_) => "ledger_client.block_time",
+            LedgerClientError::Faucet(_) => "ledger_client.faucet",
+            LedgerClientError::WrongNetwork(_) => "ledger_client.wrong_network",
+            LedgerClientError::Unsupported(_) => "ledger_client.unsupported",
+        }
+    }
+
+    /// Report of the script that failed evaluation, if that's what caused this error
+    pub fn script_failure(&self) -> Option<&ScriptFailure> {
+        match self.find_source::<ScriptError>()? {
+            ScriptError::Evaluation(failure) => Some(failure.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Status, body and request of the failed provider call, if that's what caused this error
+    pub fn provider_error(&self) -> Option<&ProviderError> {
+        self.find_source::<ProviderError>()
+    }
+
+    fn find_source<T: error::Error + 'static>(&self) -> Option<&T> {
+        let mut source = error::Error::source(self);
+        while let Some(inner) = source {
+            if let Some(found) = inner.downcast_ref::<T>() {
+                return Some(found)
+            }
+            source = error::Error::source(inner);
+        }
+        None
+    }
+
+    /// Broad class of the failure
+    ///
+    /// Submissions refused by a script failing pre-flight evaluation count as script
+    /// failures, and ones the provider answered with a client error, other than rate
+    /// limiting, count as rejected. Other failures to submit count as provider failures.
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            LedgerClientError::FailedToIssueTx(inner) => {
+                if let Some(script_error) = inner.downcast_ref::<ScriptError>() {
+                    return script_error.class()
+                }
+                match self.provider_error() {
+                    Some(provider_error) if provider_error.is_client_error() => {
+                        ErrorClass::Rejected
+                    }
+                    _ => ErrorClass::ProviderFailure,
+                }
+            }
+            LedgerClientError::FailedToRetrieveOutputsAt(..)
+            | LedgerClientError::FailedToRetrieveOutputWithId(..)
+            | LedgerClientError::FailedToRetrieveTx(..)
+            | LedgerClientError::CurrentTime(_)
+            | LedgerClientError::FailedToGetBlockTime(_)
+            | LedgerClientError::Faucet(_) => ErrorClass::ProviderFailure,
+            LedgerClientError::NoBigEnoughCollateralUTxO => ErrorClass::InsufficientFunds,
+            LedgerClientError::BaseAddress(_)
+            | LedgerClientError::PaymentPublicKey(_)
+            | LedgerClientError::ConfigError(_)
+            | LedgerClientError::Unsupported(_) => ErrorClass::Configuration,
+            LedgerClientError::BadAddress(_)
+            | LedgerClientError::BadTxId(_)
+            | LedgerClientError::NoDatumOnScriptInput
+            | LedgerClientError::ValidityRange(_)
+            | LedgerClientError::WrongNetwork(_) => ErrorClass::InvalidInput,
+        }
+    }
 }
 
 #[allow(missing_docs)]
@@ -0,0 +1,146 @@
+use crate::ledger_client::{
+    LedgerClient,
+    LedgerClientError,
+    LedgerClientResult,
+};
+use async_trait::async_trait;
+use std::{
+    marker::PhantomData,
+    sync::{
+        atomic::{
+            AtomicI64,
+            Ordering,
+        },
+        Arc,
+    },
+};
+
+/// Source of the current time for building transactions, e.g. validity ranges or the next
+/// time a pull is allowed
+///
+/// Ledger clients answer [`LedgerClient::current_time_secs`] from their clock, so endpoint
+/// handlers asking the ledger client get the same time whatever runs them.
+#[async_trait]
+pub trait Clock: Send + Sync {
+    /// Current posix time in seconds
+    async fn now_secs(&self) -> LedgerClientResult<i64>;
+}
+
+#[async_trait]
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    async fn now_secs(&self) -> LedgerClientResult<i64> {
+        self.as_ref().now_secs().await
+    }
+}
+
+/// [`Clock`] reading the system time
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    async fn now_secs(&self) -> LedgerClientResult<i64> {
+        crate::runtime::unix_time_secs().map_err(LedgerClientError::CurrentTime)
+    }
+}
+
+/// [`Clock`] only moving when told to, for tests
+///
+/// Clones share the same time, so a test can keep one to move the time of a clock it gave
+/// away.
+#[derive(Clone, Debug, Default)]
+pub struct TestClock {
+    now: Arc<AtomicI64>,
+}
+
+impl TestClock {
+    /// Constructor for a [`TestClock`] starting at `posix_time` seconds
+    pub fn new(posix_time: i64) -> Self {
+        TestClock {
+            now: Arc::new(AtomicI64::new(posix_time)),
+        }
+    }
+
+    /// Set the time to `posix_time` seconds
+    pub fn set(&self, posix_time: i64) {
+        self.now.store(posix_time, Ordering::SeqCst);
+    }
+
+    /// Move the time forward by `secs`
+    pub fn advance(&self, secs: i64) {
+        self.now.fetch_add(secs, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl Clock for TestClock {
+    async fn now_secs(&self) -> LedgerClientResult<i64> {
+        Ok(self.now.load(Ordering::SeqCst))
+    }
+}
+
+/// [`Clock`] reading the time of the latest block seen by a ledger client
+///
+/// Validity ranges computed from it are consistent with the chain even if the local clock
+/// is off, at the cost of lagging behind by up to a block.
+pub struct ChainTipClock<LC, Datum, Redeemer> {
+    ledger_client: LC,
+    _datum: PhantomData<fn() -> (Datum, Redeemer)>,
+}
+
+impl<LC, Datum, Redeemer> ChainTipClock<LC, Datum, Redeemer>
+where
+    LC: LedgerClient<Datum, Redeemer>,
+{
+    /// Constructor for a [`ChainTipClock`] asking `ledger_client` for the latest block
+    pub fn new(ledger_client: LC) -> Self {
+        ChainTipClock {
+            ledger_client,
+            _datum: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<LC, Datum, Redeemer> Clock for ChainTipClock<LC, Datum, Redeemer>
+where
+    LC: LedgerClient<Datum, Redeemer>,
+{
+    async fn now_secs(&self) -> LedgerClientResult<i64> {
+        self.ledger_client.last_block_time_secs().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::ledger_client::test_ledger_client::TestLedgerClientBuilder;
+    use pallas_addresses::Address;
+
+    const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+
+    #[tokio::test]
+    async fn now_secs__follows_the_time_it_is_given() {
+        // given
+        let clock = TestClock::new(1_000);
+        let shared = clock.clone();
+        let alice = Address::from_bech32(ALICE).unwrap();
+        let ledger_client =
+            TestLedgerClientBuilder::<(), ()>::new(&alice).build_in_memory();
+        ledger_client.set_current_time_secs(5_000).await.unwrap();
+        let chain_tip = ChainTipClock::new(ledger_client);
+
+        // when
+        shared.advance(20);
+        let advanced = clock.now_secs().await.unwrap();
+        shared.set(3_000);
+        let set = clock.now_secs().await.unwrap();
+
+        // then
+        assert_eq!(advanced, 1_020);
+        assert_eq!(set, 3_000);
+        assert_eq!(chain_tip.now_secs().await.unwrap(), 5_000);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{
+    ledger_client::{
+        LedgerClientError,
+        LedgerClientResult,
+    },
+    values::Values,
+    PolicyId,
+};
+use async_trait::async_trait;
+use pallas_addresses::Address;
+use serde::Serialize;
+use thiserror::Error;
+
+/// Source of funds for bootstrapping wallets in tests and on local development networks
+#[async_trait]
+pub trait Faucet: Send + Sync {
+    /// Send the given `values` to `address`
+    async fn fund(&self, address: &Address, values: &Values) -> LedgerClientResult<()>;
+
+    /// Send `lovelace` to `address`
+    async fn fund_lovelace(
+        &self,
+        address: &Address,
+        lovelace: u64,
+    ) -> LedgerClientResult<()> {
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, lovelace);
+        self.fund(address, &values).await
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum FaucetError {
+    #[error("Faucet can only send ADA, not {0:?}")]
+    UnsupportedAsset(PolicyId),
+    #[error("Faucet responded with {status}: {body}")]
+    BadResponse { status: u16, body: String },
+    #[error("Request to faucet failed: {0}")]
+    Request(String),
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TopUpRequest {
+    address: String,
+    ada_amount: u64,
+}
+
+/// [`Faucet`] backed by the top-up endpoint of a local devnet, e.g.
+/// `http://localhost:10000/local-cluster/api/addresses/topup` for Yaci DevKit.
+///
+/// Devnet faucets only send ADA, in whole ADA increments, so amounts are rounded up.
+#[derive(Clone, Debug)]
+pub struct DevnetFaucet {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl DevnetFaucet {
+    /// Constructor for [`DevnetFaucet`] pointing at the top-up endpoint `url`
+    pub fn new(url: &str) -> Self {
+        DevnetFaucet {
+            url: url.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl Faucet for DevnetFaucet {
+    async fn fund(&self, address: &Address, values: &Values) -> LedgerClientResult<()> {
+        if let Some((policy, _)) = values
+            .as_iter()
+            .find(|(policy, _)| **policy != PolicyId::Lovelace)
+        {
+            let err = FaucetError::UnsupportedAsset(policy.clone());
+            return Err(LedgerClientError::Faucet(Box::new(err)));
+        }
+        let lovelace = values.get(&PolicyId::Lovelace).unwrap_or_default();
+        let address = address
+            .to_bech32()
+            .map_err(|e| LedgerClientError::BadAddress(Box::new(e)))?;
+        let request = TopUpRequest {
+            address,
+            ada_amount: (lovelace + 999_999) / 1_000_000,
+        };
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| FaucetError::Request(e.to_string()))
+            .map_err(|e| LedgerClientError::Faucet(Box::new(e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let err = FaucetError::BadResponse {
+                status: status.as_u16(),
+                body,
+            };
+            return Err(LedgerClientError::Faucet(Box::new(err)));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    error::Error,
+    fmt,
+};
+
+const TOO_MANY_REQUESTS: u16 = 429;
+
+/// Failed request to a remote provider, e.g. Blockfrost or Ogmios, with what was asked and
+/// how the provider answered
+#[derive(Debug)]
+pub struct ProviderError {
+    provider: String,
+    request: String,
+    status: Option<u16>,
+    body: Option<String>,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl ProviderError {
+    /// Constructor for a [`ProviderError`] for `request` to `provider`, e.g.
+    /// `("Blockfrost", "POST /tx/submit")`
+    pub fn new(provider: &str, request: &str) -> Self {
+        ProviderError {
+            provider: provider.to_string(),
+            request: request.to_string(),
+            status: None,
+            body: None,
+            source: None,
+        }
+    }
+
+    /// Add the HTTP status code of the response
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Add the error body of the response
+    pub fn with_body(mut self, body: &str) -> Self {
+        self.body = Some(body.to_string());
+        self
+    }
+
+    /// Add the client error that caused the failure
+    pub fn with_source<E: Error + Send + Sync + 'static>(mut self, source: E) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    /// Build from a response with an unsuccessful status, reading its body
+    pub async fn from_response(
+        provider: &str,
+        request: &str,
+        response: reqwest::Response,
+    ) -> Self {
+        let error =
+            ProviderError::new(provider, request).with_status(response.status().as_u16());
+        match response.text().await {
+            Ok(body) => error.with_body(&body),
+            Err(e) => error.with_source(e),
+        }
+    }
+
+    /// Getter for the provider name
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    /// Getter for the request that failed
+    pub fn request(&self) -> &str {
+        &self.request
+    }
+
+    /// Getter for the HTTP status code, if the provider responded at all
+    pub fn status(&self) -> Option<u16> {
+        self.status
+    }
+
+    /// Getter for the error body of the response
+    pub fn body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    /// Whether the provider refused the request for exceeding its rate limit
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == Some(TOO_MANY_REQUESTS)
+    }
+
+    /// Whether the provider blamed the request, e.g. for a malformed transaction
+    pub fn is_client_error(&self) -> bool {
+        matches!(self.status, Some(400..=499)) && !self.is_rate_limited()
+    }
+
+    /// Whether the provider itself failed
+    pub fn is_server_error(&self) -> bool {
+        matches!(self.status, Some(500..=599))
+    }
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} request {} failed", self.provider, self.request)?;
+        if let Some(status) = self.status {
+            write!(f, " with status {status}")?;
+        }
+        if let Some(body) = &self.body {
+            write!(f, ": {body}")?;
+        } else if let Some(source) = &self.source {
+            write!(f, ": {source}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ProviderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn Error + 'static))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::ledger_client::LedgerClientError;
+
+    #[test]
+    fn provider_error__is_found_behind_ledger_client_error() {
+        // given
+        let provider_error = ProviderError::new("Blockfrost", "POST /tx/submit")
+            .with_status(429)
+            .with_body("Project over limit");
+
+        // when
+        let error = LedgerClientError::FailedToIssueTx(Box::new(provider_error));
+
+        // then
+        let found = error.provider_error().unwrap();
+        assert!(found.is_rate_limited());
+        assert!(!found.is_client_error());
+        assert_eq!(
+            found.to_string(),
+            "Blockfrost request POST /tx/submit failed with status 429: Project over limit"
+        );
+    }
+}
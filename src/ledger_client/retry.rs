@@ -0,0 +1,290 @@
+use crate::ledger_client::{
+    LedgerClientError,
+    LedgerClientResult,
+};
+use std::{
+    error::Error,
+    fmt,
+    future::Future,
+    time::Duration,
+};
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How to retry issuing a transaction after a failed submission
+///
+/// Attempts resubmit the same signed transaction, after checking the previous attempt
+/// didn't land anyway, so it can never be paid twice. If the previous attempt didn't land
+/// but its inputs were spent by another transaction, the transaction is built again with
+/// freshly selected inputs instead. Waits between attempts grow exponentially from the
+/// initial backoff up to the max backoff.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    is_retryable: fn(&LedgerClientError) -> bool,
+    reselect_spent_inputs: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            is_retryable: is_transient_submission_error,
+            reselect_spent_inputs: true,
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("reselect_spent_inputs", &self.reselect_spent_inputs)
+            .finish()
+    }
+}
+
+impl RetryPolicy {
+    /// Make at most `max_attempts` attempts, including the first one
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Wait `initial` after the first failure, doubling up to `max` after later ones
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Decide with `is_retryable` which errors are worth another attempt
+    pub fn with_retryable(
+        mut self,
+        is_retryable: fn(&LedgerClientError) -> bool,
+    ) -> Self {
+        self.is_retryable = is_retryable;
+        self
+    }
+
+    /// Whether to build the transaction again with new inputs when its inputs were already
+    /// spent, rather than giving up
+    pub fn with_reselect_spent_inputs(mut self, reselect_spent_inputs: bool) -> Self {
+        self.reselect_spent_inputs = reselect_spent_inputs;
+        self
+    }
+
+    /// Getter for whether spent inputs are selected again
+    pub fn reselects_spent_inputs(&self) -> bool {
+        self.reselect_spent_inputs
+    }
+
+    /// Getter for the max number of attempts
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Time to wait after the failed `attempt`, starting at 1
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Whether `error` is worth another attempt
+    pub fn is_retryable(&self, error: &LedgerClientError) -> bool {
+        (self.is_retryable)(error)
+            || (self.reselect_spent_inputs && is_spent_inputs_error(error))
+    }
+}
+
+/// Default check for [`RetryPolicy`]: failed submissions the provider answered with a 5xx
+/// or rate limit, or that didn't reach it
+///
+/// Rejections of the transaction itself, like already spent inputs, aren't transient.
+pub fn is_transient_submission_error(error: &LedgerClientError) -> bool {
+    if !matches!(error, LedgerClientError::FailedToIssueTx(_)) {
+        return false
+    }
+    match error.provider_error() {
+        Some(provider_error) => match provider_error.status() {
+            Some(_) => {
+                provider_error.is_rate_limited() || provider_error.is_server_error()
+            }
+            None => provider_error
+                .source()
+                .and_then(|source| source.downcast_ref::<reqwest::Error>())
+                .map_or(false, |e| e.is_request() || e.is_timeout()),
+        },
+        None => false,
+    }
+}
+
+/// Whether the provider rejected the submission because some of its inputs were already
+/// spent
+///
+/// Resubmitting the same transaction can't get past this, so it's only retried with new
+/// inputs, see [`RetryPolicy::with_reselect_spent_inputs`].
+pub fn is_spent_inputs_error(error: &LedgerClientError) -> bool {
+    if !matches!(error, LedgerClientError::FailedToIssueTx(_)) {
+        return false
+    }
+    // Blockfrost and cardano-submit-api report `BadInputsUTxO`, Ogmios `badInputs`
+    error
+        .provider_error()
+        .and_then(|provider_error| provider_error.body())
+        .map_or(false, |body| body.to_lowercase().contains("badinputs"))
+}
+
+/// Run `attempt` until it succeeds, fails with an error `policy` doesn't retry, or runs
+/// out of attempts
+///
+/// Attempts following a failure [`is_spent_inputs_error`] reports have to select new inputs.
+pub async fn with_retry<T, F, Fut>(
+    policy: &RetryPolicy,
+    mut attempt: F,
+) -> LedgerClientResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = LedgerClientResult<T>>,
+{
+    let mut attempts = 1;
+    loop {
+        match attempt().await {
+            Err(error)
+                if attempts < policy.max_attempts() && policy.is_retryable(&error) =>
+            {
+                let backoff = policy.backoff(attempts);
+                tracing::warn!(
+                    "Attempt {attempts} failed, retrying in {backoff:?}: {error:?}"
+                );
+                crate::runtime::sleep(backoff).await;
+                attempts += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::ledger_client::provider_error::ProviderError;
+    use std::sync::atomic::{
+        AtomicU32,
+        Ordering,
+    };
+
+    fn failed_to_issue(status: u16, body: &str) -> LedgerClientError {
+        let provider_error = ProviderError::new("Blockfrost", "POST /tx/submit")
+            .with_status(status)
+            .with_body(body);
+        LedgerClientError::FailedToIssueTx(Box::new(provider_error))
+    }
+
+    fn instant_policy() -> RetryPolicy {
+        RetryPolicy::default().with_backoff(Duration::ZERO, Duration::ZERO)
+    }
+
+    #[tokio::test]
+    async fn with_retry__retries_transient_failures_until_success() {
+        // given
+        let policy = instant_policy();
+        let calls = &AtomicU32::new(0);
+
+        // when
+        let res = with_retry(&policy, || async move {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(failed_to_issue(503, "Service Unavailable")),
+                _ => Ok(()),
+            }
+        })
+        .await;
+
+        // then
+        assert!(res.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn with_retry__gives_up_on_permanent_failures() {
+        // given
+        let policy = instant_policy();
+        let calls = &AtomicU32::new(0);
+
+        // when
+        let res: LedgerClientResult<()> = with_retry(&policy, || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(failed_to_issue(400, "ValueNotConservedUTxO"))
+        })
+        .await;
+
+        // then
+        assert!(res.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn with_retry__stops_after_max_attempts() {
+        // given
+        let policy = instant_policy().with_max_attempts(4);
+        let calls = &AtomicU32::new(0);
+
+        // when
+        let res: LedgerClientResult<()> = with_retry(&policy, || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(failed_to_issue(500, "mempool is full"))
+        })
+        .await;
+
+        // then
+        assert!(res.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn is_transient_submission_error__never_retries_spent_inputs() {
+        let error = failed_to_issue(400, "BadInputsUTxO timeout 500");
+
+        assert!(!is_transient_submission_error(&error));
+    }
+
+    #[test]
+    fn is_retryable__reselects_spent_inputs_unless_disabled() {
+        // given
+        let error = failed_to_issue(400, "BadInputsUTxO (fromList [TxIn ...])");
+
+        // when
+        let by_default = RetryPolicy::default().is_retryable(&error);
+        let disabled = RetryPolicy::default()
+            .with_reselect_spent_inputs(false)
+            .is_retryable(&error);
+
+        // then
+        assert!(is_spent_inputs_error(&error));
+        assert!(by_default);
+        assert!(!disabled);
+    }
+
+    #[test]
+    fn backoff__doubles_up_to_max() {
+        let policy = RetryPolicy::default()
+            .with_backoff(Duration::from_secs(1), Duration::from_secs(5));
+
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(4), Duration::from_secs(5));
+    }
+}
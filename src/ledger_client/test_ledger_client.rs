@@ -4,6 +4,10 @@ use std::{
     marker::PhantomData,
     path::Path,
     sync::{
+        atomic::{
+            AtomicU64,
+            Ordering,
+        },
         Arc,
         Mutex,
     },
@@ -11,14 +15,17 @@ use std::{
 
 use crate::{
     ledger_client::{
+        faucet::Faucet,
         test_ledger_client::in_memory_storage::InMemoryStorage,
         LedgerClient,
         LedgerClientError,
         LedgerClientResult,
     },
     output::{
+        DatumAttachment,
         DatumKind,
         Output,
+        ReferenceScript,
         UnbuiltOutput,
     },
     scripts::{
@@ -34,8 +41,14 @@ use crate::{
             ValidRange,
         },
         plutus_validator::plutus_data::PlutusData,
+        ExecutionCost,
+        Validator,
+    },
+    slot_config::SlotConfig,
+    transaction::{
+        TxId,
+        TxPreview,
     },
-    transaction::TxId,
     values::Values,
     PolicyId,
     UnbuiltTransaction,
@@ -46,13 +59,20 @@ use pallas_addresses::{
     Address,
     Network,
 };
-use rand::Rng;
+use rand::{
+    rngs::StdRng,
+    Rng,
+    SeedableRng,
+};
 use thiserror::Error;
+use tx_log::IssuedTx;
 
 /// In-memory storage module
 pub mod in_memory_storage;
 /// Local persisted storage module
 pub mod local_persisted_storage;
+/// Log of transactions issued against the test ledger
+pub mod tx_log;
 
 #[cfg(test)]
 mod tests;
@@ -78,6 +98,10 @@ pub struct TestLedgerClientBuilder<Datum, Redeemer> {
     outputs: Vec<(Address, Output<Datum>)>,
     starting_time: i64,
     block_length: i64,
+    min_collateral: Option<u64>,
+    slot_config: SlotConfig,
+    faucet_count: u64,
+    seed: Option<u64>,
     _redeemer: PhantomData<Redeemer>,
 }
 
@@ -93,6 +117,10 @@ where
             outputs: Vec::new(),
             starting_time: 0,
             block_length: 20,
+            min_collateral: None,
+            slot_config: SlotConfig::default(),
+            faucet_count: 0,
+            seed: None,
             _redeemer: PhantomData,
         }
     }
@@ -106,7 +134,35 @@ where
             owner: owner.clone(),
             values: Values::default(),
             datum: None,
+            datum_as_hash: false,
+            reference_script: None,
+        }
+    }
+
+    /// Fund `address` with the given `values` in a single output. Unlike [`Self::start_output`],
+    /// the output's id is deterministic.
+    pub fn fund(mut self, address: &Address, values: Values) -> Self {
+        let tx_hash = faucet_tx_hash(self.faucet_count);
+        self.faucet_count += 1;
+        let output = Output::new_wallet(tx_hash, 0, address.clone(), values);
+        self.add_output(address, output);
+        self
+    }
+
+    /// Fund `address` with `lovelace` in a single output. See [`Self::fund`].
+    pub fn fund_lovelace(self, address: &Address, lovelace: u64) -> Self {
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, lovelace);
+        self.fund(address, values)
+    }
+
+    /// Add `outputs` as they are, keeping their ids, e.g. outputs read from a
+    /// [`ContractSnapshot`](crate::smart_contract::snapshot::ContractSnapshot)
+    pub fn with_outputs(mut self, outputs: Vec<Output<Datum>>) -> Self {
+        for output in outputs {
+            self.add_output(&output.owner(), output);
         }
+        self
     }
 
     /// Circumvents the [`OutputBuilder`] and adds an output directly to the ledger client builder.
@@ -126,16 +182,46 @@ where
         self
     }
 
+    /// Require a pure-ADA collateral output of at least `min_amount` lovelace for txs that run
+    /// scripts. See [`TestLedgerClient::with_collateral`].
+    pub fn with_collateral(mut self, min_amount: u64) -> Self {
+        self.min_collateral = Some(min_amount);
+        self
+    }
+
+    /// Convert validity ranges to slots with `slot_config`. See
+    /// [`TestLedgerClient::with_slot_config`].
+    pub fn with_slot_config(mut self, slot_config: SlotConfig) -> Self {
+        self.slot_config = slot_config;
+        self
+    }
+
+    /// Seed the ledger's randomness, so tx ids are the same every run. See
+    /// [`TestLedgerClient::with_seed`].
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
     /// Build the [`TestLedgerClient`] with an _ephemeral_ [`InMemoryStorage`] for [`TestLedgerStorage`]
     pub fn build_in_memory(
         &self,
     ) -> TestLedgerClient<Datum, Redeemer, InMemoryStorage<Datum>> {
-        TestLedgerClient::new_in_memory(
+        let mut ledger_client = TestLedgerClient::new_in_memory(
             self.signer.clone(),
             self.outputs.clone(),
             self.block_length,
             self.starting_time,
-        )
+        );
+        ledger_client.faucet_count = Arc::new(AtomicU64::new(self.faucet_count));
+        let mut ledger_client = ledger_client.with_slot_config(self.slot_config);
+        if let Some(seed) = self.seed {
+            ledger_client = ledger_client.with_seed(seed);
+        }
+        match self.min_collateral {
+            Some(min_amount) => ledger_client.with_collateral(min_amount),
+            None => ledger_client,
+        }
     }
 }
 
@@ -149,6 +235,8 @@ pub struct OutputBuilder<
     owner: Address,
     values: Values,
     datum: Option<Datum>,
+    datum_as_hash: bool,
+    reference_script: Option<ReferenceScript>,
 }
 
 impl<Datum, Redeemer> OutputBuilder<Datum, Redeemer>
@@ -173,6 +261,34 @@ where
     /// Specify the datum for the current output. This will override any previous datum specified.
     pub fn with_datum(mut self, datum: Datum) -> OutputBuilder<Datum, Redeemer> {
         self.datum = Some(datum);
+        self.datum_as_hash = false;
+        self
+    }
+
+    /// Specify the datum for the current output, attached by its hash rather than inline. This
+    /// will override any previous datum specified.
+    pub fn with_datum_hash(mut self, datum: Datum) -> OutputBuilder<Datum, Redeemer> {
+        self.datum = Some(datum);
+        self.datum_as_hash = true;
+        self
+    }
+
+    /// Store the given script on the current output so it can be used as a reference script.
+    pub fn with_reference_script(
+        mut self,
+        script: &dyn Validator<Datum, Redeemer>,
+    ) -> OutputBuilder<Datum, Redeemer> {
+        let script_address = script
+            .address(Network::Testnet)
+            .expect("Script should have an address");
+        let hash = match script_address {
+            Address::Shelley(shelley_address) => {
+                shelley_address.payment().as_hash().to_vec()
+            }
+            _ => panic!("Script address should be a Shelley address"),
+        };
+        let script_hex = script.script_hex().expect("Script should have a CBOR hex");
+        self.reference_script = Some(ReferenceScript::new(hash, &script_hex));
         self
     }
 
@@ -183,15 +299,26 @@ where
             owner,
             values,
             datum,
+            datum_as_hash,
+            reference_script,
         } = self;
         let address = owner.clone();
-        let tx_hash = arbitrary_tx_id().to_vec();
+        let tx_hash = starting_tx_hash(inner.outputs.len() as u64);
         let index = 0;
-        let output = if let Some(datum) = datum {
-            Output::new_validator(tx_hash, index, address, values, datum)
+        let mut output = if let Some(datum) = datum {
+            let data: PlutusData = datum.clone().into();
+            let output = Output::new_validator(tx_hash, index, address, values, datum);
+            if datum_as_hash {
+                output.with_datum_hash(data.hash())
+            } else {
+                output
+            }
         } else {
             Output::new_wallet(tx_hash, index, address, values)
         };
+        if let Some(reference_script) = reference_script {
+            output = output.with_reference_script(reference_script);
+        }
         inner.add_output(&owner, output);
         inner
     }
@@ -232,6 +359,23 @@ pub trait TestLedgerStorage<Datum> {
         &self,
         address: &Address,
     ) -> LedgerClientResult<Vec<Output<Datum>>>;
+    /// Get all UTxOs holding any amount of the given policy
+    ///
+    /// Defaults to filtering the [`all_outputs`](Self::all_outputs) of the signer, for
+    /// storages that can't search every address.
+    async fn outputs_with_policy(
+        &self,
+        policy: &PolicyId,
+    ) -> LedgerClientResult<Vec<Output<Datum>>> {
+        let signer = self.signer().await?;
+        let outputs = self
+            .all_outputs(&signer)
+            .await?
+            .into_iter()
+            .filter(|output| output.values().get(policy).is_some())
+            .collect();
+        Ok(outputs)
+    }
     /// Remove the given output from the storage
     async fn remove_output(&self, output: &Output<Datum>) -> LedgerClientResult<()>;
     /// Add the given output to the storage
@@ -248,13 +392,73 @@ pub trait TestLedgerStorage<Datum> {
 
 /// Implementation of the [`LedgerClient`] trait that mocks the ledger. Typically, the best way to
 /// construct is using the [`TestLedgerClientBuilder`].
+///
+/// If the `Storage` is [`Clone`] (e.g. [`InMemoryStorage`]), clones of the client share the same
+/// ledger state. Transactions are issued one at a time, so concurrent endpoint calls against
+/// clones of the same client are safe.
 #[derive(Debug)]
 pub struct TestLedgerClient<Datum, Redeemer, Storage: TestLedgerStorage<Datum>> {
     storage: Storage,
+    issue_lock: Arc<tokio::sync::Mutex<()>>,
+    tx_log: Arc<Mutex<Vec<IssuedTx<Datum>>>>,
+    min_collateral: Option<u64>,
+    slot_config: SlotConfig,
+    faucet_count: Arc<AtomicU64>,
+    rng: LedgerRng,
     _datum: PhantomData<Datum>,
     _redeemer: PhantomData<Redeemer>,
 }
 
+/// Randomness of a [`TestLedgerClient`], shared by its clones
+#[derive(Clone, Debug)]
+struct LedgerRng {
+    seed: u64,
+    rng: Arc<Mutex<StdRng>>,
+}
+
+impl LedgerRng {
+    fn new(seed: u64) -> Self {
+        LedgerRng {
+            seed,
+            rng: Arc::new(Mutex::new(StdRng::seed_from_u64(seed))),
+        }
+    }
+
+    fn tx_hash(&self) -> Vec<u8> {
+        let mut tx_hash = [0; 32];
+        self.rng
+            .lock()
+            .expect("Rng lock poisoned")
+            .fill(&mut tx_hash);
+        tx_hash.to_vec()
+    }
+}
+
+impl Default for LedgerRng {
+    fn default() -> Self {
+        LedgerRng::new(rand::thread_rng().gen())
+    }
+}
+
+impl<Datum, Redeemer, Storage> Clone for TestLedgerClient<Datum, Redeemer, Storage>
+where
+    Storage: TestLedgerStorage<Datum> + Clone,
+{
+    fn clone(&self) -> Self {
+        TestLedgerClient {
+            storage: self.storage.clone(),
+            issue_lock: self.issue_lock.clone(),
+            tx_log: self.tx_log.clone(),
+            min_collateral: self.min_collateral,
+            slot_config: self.slot_config,
+            faucet_count: self.faucet_count.clone(),
+            rng: self.rng.clone(),
+            _datum: PhantomData,
+            _redeemer: PhantomData,
+        }
+    }
+}
+
 impl<Datum, Redeemer> TestLedgerClient<Datum, Redeemer, InMemoryStorage<Datum>>
 where
     Datum: Clone + Send + Sync + PartialEq,
@@ -268,17 +472,44 @@ where
     ) -> Self {
         let storage = InMemoryStorage {
             signer,
-            outputs: Arc::new(Mutex::new(outputs)),
+            outputs: Arc::new(Mutex::new(outputs.into_iter().collect())),
             current_posix_time: Arc::new(Mutex::new(starting_time)),
             block_length,
         };
         TestLedgerClient {
             storage,
+            issue_lock: Default::default(),
+            tx_log: Default::default(),
+            min_collateral: None,
+            slot_config: SlotConfig::default(),
+            faucet_count: Default::default(),
+            rng: Default::default(),
             _datum: Default::default(),
             _redeemer: Default::default(),
         }
     }
+
+    /// Clone of this client signing as `signer`, sharing the same ledger state
+    ///
+    /// Lets a test have several parties, e.g. a seller and competing bidders, act on one ledger.
+    pub fn with_signer(&self, signer: &Address) -> Self {
+        let mut client = self.clone();
+        client.storage.signer = signer.clone();
+        client
+    }
+
+    /// Snapshot of every output currently on the ledger, with its owner
+    pub fn ledger_outputs(&self) -> Vec<(Address, Output<Datum>)> {
+        self.storage
+            .outputs
+            .lock()
+            .expect("Outputs lock poisoned")
+            .iter()
+            .cloned()
+            .collect()
+    }
 }
+
 impl<T, Datum, Redeemer>
     TestLedgerClient<Datum, Redeemer, LocalPersistedStorage<T, Datum>>
 where
@@ -301,6 +532,12 @@ where
         let _ = storage.get_data();
         TestLedgerClient {
             storage,
+            issue_lock: Default::default(),
+            tx_log: Default::default(),
+            min_collateral: None,
+            slot_config: SlotConfig::default(),
+            faucet_count: Default::default(),
+            rng: Default::default(),
             _datum: Default::default(),
             _redeemer: Default::default(),
         }
@@ -312,6 +549,12 @@ where
         let _ = storage.get_data();
         TestLedgerClient {
             storage,
+            issue_lock: Default::default(),
+            tx_log: Default::default(),
+            min_collateral: None,
+            slot_config: SlotConfig::default(),
+            faucet_count: Default::default(),
+            rng: Default::default(),
             _datum: Default::default(),
             _redeemer: Default::default(),
         }
@@ -346,43 +589,161 @@ where
         let new_time = advanced_time + current_time;
         self.storage.set_current_time(new_time).await
     }
-}
 
-#[async_trait]
-impl<Datum, Redeemer, Storage> LedgerClient<Datum, Redeemer>
-    for TestLedgerClient<Datum, Redeemer, Storage>
-where
-    Datum: Clone + PartialEq + Debug + Send + Sync + Into<PlutusData>,
-    Redeemer: Clone + Eq + PartialEq + Debug + Hash + Send + Sync,
-    Storage: TestLedgerStorage<Datum> + Send + Sync,
-{
-    async fn signer_base_address(&self) -> LedgerClientResult<Address> {
-        self.storage.signer().await
+    /// Put `output` on the ledger without a transaction, e.g. when replaying chain history
+    pub async fn insert_output(&self, output: &Output<Datum>) -> LedgerClientResult<()> {
+        self.storage.add_output(output).await
     }
 
-    async fn outputs_at_address(
+    /// Take `output` off the ledger without a transaction, e.g. when replaying chain history
+    pub async fn delete_output(&self, output: &Output<Datum>) -> LedgerClientResult<()> {
+        self.storage.remove_output(output).await
+    }
+
+    /// All transactions issued against this ledger, in the order they were issued
+    pub fn issued_txs(&self) -> Vec<IssuedTx<Datum>> {
+        self.tx_log.lock().expect("Tx log lock poisoned").clone()
+    }
+
+    /// All outputs on the ledger holding any amount of `policy`, e.g. to find where a
+    /// thread token ended up
+    pub async fn outputs_with_policy(
         &self,
-        address: &Address,
-        count: usize,
+        policy: &PolicyId,
     ) -> LedgerClientResult<Vec<Output<Datum>>> {
-        self.storage.outputs_by_count(address, count).await
+        self.storage.outputs_with_policy(policy).await
     }
 
-    async fn all_outputs_at_address(
+    /// The most recently issued transaction, if any
+    pub fn last_tx(&self) -> Option<IssuedTx<Datum>> {
+        self.tx_log
+            .lock()
+            .expect("Tx log lock poisoned")
+            .last()
+            .cloned()
+    }
+
+    /// Panics unless exactly `amount` of the given asset was minted across all issued transactions
+    pub fn assert_minted(&self, policy_id: &str, asset_name: Option<&[u8]>, amount: u64) {
+        let policy = PolicyId::native_token(policy_id, &asset_name.map(<[u8]>::to_vec));
+        let minted: u64 = self
+            .issued_txs()
+            .iter()
+            .filter_map(|tx| tx.minted().get(&policy))
+            .sum();
+        assert_eq!(
+            minted,
+            amount,
+            "Expected {amount} of {policy:?} to be minted, but found {minted} (ledger seed: {})",
+            self.seed()
+        );
+    }
+
+    /// Require txs that run scripts to provide a pure-ADA collateral output of at least
+    /// `min_amount` lovelace. If any script fails, the collateral is consumed, like on-chain.
+    pub fn with_collateral(mut self, min_amount: u64) -> Self {
+        self.min_collateral = Some(min_amount);
+        self
+    }
+
+    /// Convert validity ranges to slots with `slot_config`, like the ledger does, before
+    /// checking them and handing them to scripts. Defaults to one second slots from the
+    /// Unix epoch.
+    pub fn with_slot_config(mut self, slot_config: SlotConfig) -> Self {
+        self.slot_config = slot_config;
+        self
+    }
+
+    /// Seed the randomness behind tx ids, so failures reproduce exactly. Clones made
+    /// afterwards share the seeded randomness. Random by default, see
+    /// [`TestLedgerClient::seed`].
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = LedgerRng::new(seed);
+        self
+    }
+
+    /// Seed of the ledger's randomness, to reproduce a failing test with
+    /// [`TestLedgerClient::with_seed`]
+    pub fn seed(&self) -> u64 {
+        self.rng.seed
+    }
+
+    async fn forfeit_collateral(
         &self,
-        address: &Address,
-    ) -> LedgerClientResult<Vec<Output<Datum>>> {
-        self.storage.all_outputs(address).await
+        collateral: Option<&Output<Datum>>,
+    ) -> LedgerClientResult<()> {
+        if let Some(collateral) = collateral {
+            self.storage.remove_output(collateral).await?;
+            self.advance_time_one_block().await?;
+        }
+        Ok(())
     }
 
-    async fn issue(
+    fn record_tx(&self, tx: IssuedTx<Datum>) -> LedgerClientResult<()> {
+        self.tx_log
+            .lock()
+            .map_err(|e| TestLCError::Mutex(format! {"{e:?}"}))
+            .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?
+            .push(tx);
+        Ok(())
+    }
+}
+
+/// Transaction selected and validated by the [`TestLedgerClient`], not yet applied to storage
+struct BuiltTx<Datum> {
+    signer: Address,
+    inputs: Vec<Output<Datum>>,
+    outputs: Vec<Output<Datum>>,
+    minted: Values,
+    burned: Values,
+    collateral: Option<Output<Datum>>,
+    costs: Vec<ExecutionCost>,
+    tx_hash: Vec<u8>,
+}
+
+fn preview_of<Datum: Clone>(built: &BuiltTx<Datum>) -> TxPreview<Datum> {
+    let inputs = built
+        .inputs
+        .iter()
+        .map(|input| input.id().clone())
+        .collect();
+    let minted = built
+        .minted
+        .as_iter()
+        .map(|(policy_id, amount)| (policy_id.clone(), *amount as i64));
+    let burned = built
+        .burned
+        .as_iter()
+        .map(|(policy_id, amount)| (policy_id.clone(), -(*amount as i64)));
+    let mint = minted.chain(burned).collect();
+    // The test ledger doesn't charge fees
+    TxPreview::new(inputs, built.outputs.clone(), 0, mint, built.costs.clone())
+}
+
+/// A script rejected the transaction, which costs the collateral when issuing
+struct ScriptFailure<Datum> {
+    collateral: Option<Output<Datum>>,
+    error: LedgerClientError,
+}
+
+impl<Datum, Redeemer, Storage> TestLedgerClient<Datum, Redeemer, Storage>
+where
+    Datum: Clone + PartialEq + Debug + Send + Sync + Into<PlutusData>,
+    Redeemer: Clone + Eq + PartialEq + Debug + Hash + Send + Sync,
+    Storage: TestLedgerStorage<Datum> + Send + Sync,
+{
+    #[tracing::instrument(skip_all)]
+    async fn build_tx(
         &self,
-        tx: UnbuiltTransaction<Datum, Redeemer>,
-    ) -> LedgerClientResult<TxId> {
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<Result<BuiltTx<Datum>, ScriptFailure<Datum>>> {
         // Setup
+        let network = self.storage.network().await?;
+        tx.check_network(network)
+            .map_err(LedgerClientError::WrongNetwork)?;
         let valid_range = tx.valid_range;
         let current_time = self.current_time_secs().await?;
-        check_time_valid(valid_range, current_time)
+        check_time_valid(valid_range, current_time, &self.slot_config)
             .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
 
         let signer = self.signer_base_address().await?;
@@ -390,16 +751,40 @@ where
         // TODO: Optimize selection
         let mut combined_inputs = self.all_outputs_at_address(&signer).await?;
 
+        let runs_scripts = !tx.script_inputs.is_empty() || !tx.minting.is_empty();
+        let collateral = match self.min_collateral {
+            Some(min_amount) if runs_scripts => {
+                let collateral = select_collateral(&combined_inputs, min_amount)
+                    .ok_or(LedgerClientError::NoBigEnoughCollateralUTxO)?;
+                Some(collateral)
+            }
+            _ => None,
+        };
+
+        let mut costs = Vec::new();
         let mut spending_outputs: Vec<Output<_>> = Vec::new();
         for (input, redeemer, script) in tx.script_inputs().iter() {
-            if let DatumKind::Typed(datum) = input.datum() {
+            if let Some(DatumKind::Typed(datum)) = input.datum().value() {
                 if !spending_outputs.contains(input) {
-                    let ctx = spend_tx_context(&tx, &signer, input)?;
+                    let ctx = spend_tx_context(tx, &signer, input, &self.slot_config)?;
                     // TODO: Check that the output is at the script address
                     //  https://github.com/MitchTurner/naumachia/issues/86
-                    script
-                        .execute(datum.to_owned(), redeemer.to_owned(), ctx)
-                        .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
+                    match script.execute(datum.to_owned(), redeemer.to_owned(), ctx) {
+                        Ok(cost) => {
+                            tracing::debug!(
+                                mem = cost.mem(),
+                                cpu = cost.cpu(),
+                                "Validator succeeded"
+                            );
+                            costs.push(cost)
+                        }
+                        Err(e) => {
+                            return Ok(Err(ScriptFailure {
+                                collateral,
+                                error: LedgerClientError::FailedToIssueTx(Box::new(e)),
+                            }))
+                        }
+                    }
                     combined_inputs.push(input.clone());
                     spending_outputs.push(input.clone());
                 } else {
@@ -422,23 +807,45 @@ where
                     acc
                 });
 
-        let mut construction_ctx = TxIdConstructionCtx::new();
+        let mut construction_ctx = TxIdConstructionCtx::new(self.rng.tx_hash());
 
         let mut minted_value = Values::default();
+        let mut burned_value = Values::default();
 
         for (amount, asset_name, redeemer, policy) in tx.minting.iter() {
             let id = policy
                 .id()
                 .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
             let policy_id = PolicyId::native_token(&id, asset_name);
-            let ctx = mint_tx_context(&tx, &signer, &id)?;
-            policy
-                .execute(redeemer.to_owned(), ctx)
-                .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
-            minted_value.add_one_value(&policy_id, *amount);
+            let ctx = mint_tx_context(tx, &signer, &id, &self.slot_config)?;
+            match policy.execute(redeemer.to_owned(), ctx) {
+                Ok(cost) => {
+                    tracing::debug!(
+                        policy = %id,
+                        mem = cost.mem(),
+                        cpu = cost.cpu(),
+                        "Minting policy succeeded"
+                    );
+                    costs.push(cost)
+                }
+                Err(e) => {
+                    return Ok(Err(ScriptFailure {
+                        collateral,
+                        error: LedgerClientError::FailedToIssueTx(Box::new(e)),
+                    }))
+                }
+            }
+            match u64::try_from(*amount) {
+                Ok(minted) => minted_value.add_one_value(&policy_id, minted),
+                Err(_) => burned_value.add_one_value(&policy_id, amount.unsigned_abs()),
+            }
         }
 
         total_input_value.add_values(&minted_value);
+        let total_input_value = total_input_value
+            .try_subtract(&burned_value)
+            .map_err(|_| TestLCError::NotEnoughInputs)
+            .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
 
         let total_output_value =
             tx.unbuilt_outputs()
@@ -448,17 +855,13 @@ where
                     acc
                 });
 
-        let maybe_remainder = total_input_value
+        let remainder = total_input_value
             .try_subtract(&total_output_value)
             .map_err(|_| TestLCError::NotEnoughInputs)
             .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
 
-        for input in combined_inputs {
-            self.storage.remove_output(&input).await?;
-        }
-
         let mut combined_outputs = Vec::new();
-        if let Some(remainder) = maybe_remainder {
+        if !remainder.is_empty() {
             combined_outputs.push(new_wallet_output(
                 &signer,
                 &remainder,
@@ -466,17 +869,109 @@ where
             ));
         }
 
-        let built_outputs = build_outputs(tx.unbuilt_outputs, &mut construction_ctx);
+        let built_outputs =
+            build_outputs(tx.unbuilt_outputs.clone(), &mut construction_ctx);
 
         combined_outputs.extend(built_outputs);
 
-        for output in combined_outputs {
-            self.storage.add_output(&output).await?;
+        Ok(Ok(BuiltTx {
+            signer,
+            inputs: combined_inputs,
+            outputs: combined_outputs,
+            minted: minted_value,
+            burned: burned_value,
+            collateral,
+            costs,
+            tx_hash: construction_ctx.tx_hash(),
+        }))
+    }
+}
+
+#[async_trait]
+impl<Datum, Redeemer, Storage> LedgerClient<Datum, Redeemer>
+    for TestLedgerClient<Datum, Redeemer, Storage>
+where
+    Datum: Clone + PartialEq + Debug + Send + Sync + Into<PlutusData>,
+    Redeemer: Clone + Eq + PartialEq + Debug + Hash + Send + Sync,
+    Storage: TestLedgerStorage<Datum> + Send + Sync,
+{
+    async fn signer_base_address(&self) -> LedgerClientResult<Address> {
+        self.storage.signer().await
+    }
+
+    async fn outputs_at_address(
+        &self,
+        address: &Address,
+        count: usize,
+    ) -> LedgerClientResult<Vec<Output<Datum>>> {
+        self.storage.outputs_by_count(address, count).await
+    }
+
+    async fn all_outputs_at_address(
+        &self,
+        address: &Address,
+    ) -> LedgerClientResult<Vec<Output<Datum>>> {
+        self.storage.all_outputs(address).await
+    }
+
+    async fn issue(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<TxId> {
+        let (tx_id, _) = self.issue_with_preview(tx).await?;
+        Ok(tx_id)
+    }
+
+    #[tracing::instrument(skip_all, fields(tx_id = tracing::field::Empty))]
+    async fn issue_with_preview(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<(TxId, Option<TxPreview<Datum>>)> {
+        // Only one tx may select and spend inputs at a time
+        let _guard = self.issue_lock.lock().await;
+
+        let built = match self.build_tx(&tx).await? {
+            Ok(built) => built,
+            Err(ScriptFailure { collateral, error }) => {
+                self.forfeit_collateral(collateral.as_ref()).await?;
+                return Err(error)
+            }
+        };
+
+        for input in built.inputs.iter() {
+            self.storage.remove_output(input).await?;
+        }
+
+        for output in built.outputs.iter() {
+            self.storage.add_output(output).await?;
         }
 
         self.advance_time_one_block().await?;
 
-        Ok(TxId::new(&hex::encode(construction_ctx.tx_hash())))
+        let tx_id = TxId::new(&hex::encode(&built.tx_hash));
+        tracing::Span::current().record("tx_id", tx_id.as_str().as_str());
+        let preview = preview_of(&built);
+        self.record_tx(IssuedTx {
+            tx_id: tx_id.clone(),
+            inputs: built.inputs,
+            outputs: built.outputs,
+            minted: built.minted,
+            burned: built.burned,
+            valid_range: tx.valid_range,
+            signers: vec![built.signer],
+            collateral: built.collateral,
+            metadata: tx.metadata,
+        })?;
+
+        Ok((tx_id, Some(preview)))
+    }
+
+    async fn preview(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<TxPreview<Datum>> {
+        let built = self.build_tx(&tx).await?.map_err(|failure| failure.error)?;
+        Ok(preview_of(&built))
     }
 
     async fn network(&self) -> LedgerClientResult<Network> {
@@ -492,30 +987,85 @@ where
     }
 }
 
+#[async_trait]
+impl<Datum, Redeemer, Storage> Faucet for TestLedgerClient<Datum, Redeemer, Storage>
+where
+    Datum: Clone + Send + Sync + PartialEq,
+    Redeemer: Send + Sync,
+    Storage: TestLedgerStorage<Datum> + Send + Sync,
+{
+    async fn fund(&self, address: &Address, values: &Values) -> LedgerClientResult<()> {
+        let count = self.faucet_count.fetch_add(1, Ordering::SeqCst);
+        let tx_hash = faucet_tx_hash(count);
+        let output = Output::new_wallet(tx_hash, 0, address.clone(), values.clone());
+        self.storage.add_output(&output).await
+    }
+}
+
+// Outputs from the builder get ids from their position, so they don't depend on the seed
+fn starting_tx_hash(count: u64) -> Vec<u8> {
+    let mut tx_hash = [0; 32];
+    tx_hash[..5].copy_from_slice(b"start");
+    tx_hash[24..].copy_from_slice(&count.to_be_bytes());
+    tx_hash.to_vec()
+}
+
+fn faucet_tx_hash(count: u64) -> Vec<u8> {
+    let mut tx_hash = [0; 32];
+    tx_hash[..6].copy_from_slice(b"faucet");
+    tx_hash[24..].copy_from_slice(&count.to_be_bytes());
+    tx_hash.to_vec()
+}
+
+fn select_collateral<Datum: Clone>(
+    wallet_outputs: &[Output<Datum>],
+    min_amount: u64,
+) -> Option<Output<Datum>> {
+    wallet_outputs
+        .iter()
+        .find(|output| {
+            let values = output.values();
+            values.len() == 1
+                && values
+                    .get(&PolicyId::Lovelace)
+                    .map_or(false, |amount| amount >= min_amount)
+        })
+        .cloned()
+}
+
 fn check_time_valid(
     valid_range: (Option<i64>, Option<i64>),
     current_time: i64,
+    slot_config: &SlotConfig,
 ) -> Result<(), TestLCError> {
+    let current_slot = slot_at(slot_config, current_time);
     if let Some(lower) = valid_range.0 {
-        if current_time < lower * 1000 {
+        if current_slot < slot_at(slot_config, lower) {
             return Err(TestLCError::TxTooEarly);
         }
-    } else if let Some(upper) = valid_range.1 {
-        if current_time >= upper * 1000 {
+    }
+    if let Some(upper) = valid_range.1 {
+        if current_slot >= slot_at(slot_config, upper) {
             return Err(TestLCError::TxTooLate);
         }
     }
     Ok(())
 }
 
+// Times before the start of the slot config are clamped to its first slot
+fn slot_at(slot_config: &SlotConfig, posix_secs: i64) -> u64 {
+    slot_config
+        .slot_from_posix_secs(posix_secs)
+        .unwrap_or(slot_config.zero_slot())
+}
+
 struct TxIdConstructionCtx {
     tx_hash: Vec<u8>,
     next_index: u64,
 }
 
 impl TxIdConstructionCtx {
-    pub fn new() -> Self {
-        let tx_hash = arbitrary_tx_id().to_vec();
+    pub fn new(tx_hash: Vec<u8>) -> Self {
         TxIdConstructionCtx {
             tx_hash,
             next_index: 0,
@@ -554,7 +1104,7 @@ fn new_validator_output<Datum>(
     Output::new_validator(tx_hash, index, addr.clone(), vals.clone(), datum)
 }
 
-fn build_outputs<Datum>(
+fn build_outputs<Datum: Clone + Into<PlutusData>>(
     unbuilt_outputs: Vec<UnbuiltOutput<Datum>>,
     construction_ctx: &mut TxIdConstructionCtx,
 ) -> Vec<Output<Datum>> {
@@ -569,9 +1119,16 @@ fn build_outputs<Datum>(
                 script_address: owner,
                 values,
                 datum,
+                datum_attachment,
             } => {
                 let addr = Address::from_bech32(&owner).expect("Already validated");
-                new_validator_output(&addr, &values, datum, construction_ctx)
+                let datum_hash = Into::<PlutusData>::into(datum.clone()).hash();
+                let output =
+                    new_validator_output(&addr, &values, datum, construction_ctx);
+                match datum_attachment {
+                    DatumAttachment::Inline => output,
+                    DatumAttachment::Hash => output.with_datum_hash(datum_hash),
+                }
             }
         })
         .collect()
@@ -581,47 +1138,60 @@ fn spend_tx_context<Datum: Into<PlutusData> + Clone, Redeemer>(
     tx: &UnbuiltTransaction<Datum, Redeemer>,
     signer_address: &Address,
     output: &Output<Datum>,
+    slot_config: &SlotConfig,
 ) -> LedgerClientResult<TxContext> {
     let id = output.id();
     let out_ref = CtxOutputReference::new(id.tx_hash().to_vec(), id.index());
     let purpose = CtxScriptPurpose::Spend(out_ref);
-    tx_context(tx, signer_address, purpose)
+    tx_context(tx, signer_address, purpose, slot_config)
 }
 
 fn mint_tx_context<Datum: Into<PlutusData> + Clone, Redeemer>(
     tx: &UnbuiltTransaction<Datum, Redeemer>,
     signer_address: &Address,
     policy_id: &str,
+    slot_config: &SlotConfig,
 ) -> LedgerClientResult<TxContext> {
     let id = hex::decode(policy_id)
         .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
     let purpose = CtxScriptPurpose::Mint(id);
-    tx_context(tx, signer_address, purpose)
+    tx_context(tx, signer_address, purpose, slot_config)
 }
 
 fn tx_context<Datum: Into<PlutusData> + Clone, Redeemer>(
     tx: &UnbuiltTransaction<Datum, Redeemer>,
     signer_address: &Address,
     purpose: CtxScriptPurpose,
+    slot_config: &SlotConfig,
 ) -> LedgerClientResult<TxContext> {
-    let lower = tx.valid_range.0.map(|n| (n * 1000, true));
-    let upper = tx.valid_range.1.map(|n| (n * 1000, false));
+    // Scripts see the bounds of the validity slots, not the times they were set at
+    let to_posix_ms = |secs| slot_config.posix_ms_from_slot(slot_at(slot_config, secs));
+    let lower = tx.valid_range.0.map(|n| (to_posix_ms(n), true));
+    let upper = tx.valid_range.1.map(|n| (to_posix_ms(n), false));
 
     let mut inputs = Vec::new();
     let mut outputs = Vec::new();
+    let mut datums = Vec::new();
     for (utxo, _, _) in tx.script_inputs.iter() {
         let id = utxo.id();
         let value = CtxValue::from(utxo.values().to_owned());
-        let datum = utxo.typed_datum().into();
+        let datum = match (utxo.datum_hash(), utxo.typed_datum()) {
+            (Some(hash), Some(datum)) => {
+                datums.push((hash.to_vec(), datum.into()));
+                CtxDatum::DatumHash(hash.to_vec())
+            }
+            (_, datum) => datum.into(),
+        };
         let address = utxo.owner();
         let transaction_id = id.tx_hash().to_vec();
+        let reference_script = utxo.reference_script().map(|s| s.hash().to_vec());
         let input = Input {
             transaction_id,
             output_index: id.index(),
             address,
             value,
             datum,
-            reference_script: None,
+            reference_script,
         };
         inputs.push(input);
     }
@@ -632,6 +1202,7 @@ fn tx_context<Datum: Into<PlutusData> + Clone, Redeemer>(
         let output_index = id.index();
         let address = input.owner();
         let value = CtxValue::from(input.values().to_owned());
+        let reference_script = input.reference_script().map(|s| s.hash().to_vec());
 
         let new_input = Input {
             transaction_id,
@@ -639,11 +1210,31 @@ fn tx_context<Datum: Into<PlutusData> + Clone, Redeemer>(
             address,
             value,
             datum: CtxDatum::NoDatum,
-            reference_script: None,
+            reference_script,
         };
         inputs.push(new_input)
     }
 
+    let mut reference_inputs = Vec::new();
+    for output in &tx.reference_inputs {
+        let id = output.id();
+        let datum = match (output.datum_hash(), output.typed_datum()) {
+            (Some(hash), Some(datum)) => {
+                datums.push((hash.to_vec(), datum.into()));
+                CtxDatum::DatumHash(hash.to_vec())
+            }
+            (_, datum) => datum.into(),
+        };
+        reference_inputs.push(Input {
+            transaction_id: id.tx_hash().to_vec(),
+            output_index: id.index(),
+            address: output.owner(),
+            value: CtxValue::from(output.values().to_owned()),
+            datum,
+            reference_script: output.reference_script().map(|s| s.hash().to_vec()),
+        });
+    }
+
     for output in tx.unbuilt_outputs.iter() {
         let new_output = match output {
             UnbuiltOutput::Wallet { owner, values } => {
@@ -661,11 +1252,20 @@ fn tx_context<Datum: Into<PlutusData> + Clone, Redeemer>(
                 script_address,
                 values,
                 datum,
+                datum_attachment,
             } => {
                 let address = Address::from_bech32(script_address)
                     .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
                 let value = CtxValue::from(values.to_owned());
-                let datum = CtxDatum::InlineDatum(datum.to_owned().into());
+                let data: PlutusData = datum.to_owned().into();
+                let datum = match datum_attachment {
+                    DatumAttachment::Inline => CtxDatum::InlineDatum(data),
+                    DatumAttachment::Hash => {
+                        let hash = data.hash();
+                        datums.push((hash.clone(), data));
+                        CtxDatum::DatumHash(hash)
+                    }
+                };
                 CtxOutput {
                     address,
                     value,
@@ -682,15 +1282,16 @@ fn tx_context<Datum: Into<PlutusData> + Clone, Redeemer>(
     )?;
     let range = ValidRange { lower, upper };
 
-    // TODO: Extra Signatories, and Datums (they are already included in CTX Builder)
+    // TODO: Extra Signatories (they are already included in CTX Builder)
     let ctx = TxContext {
         purpose,
         signer,
         range,
         inputs,
+        reference_inputs,
         outputs,
         extra_signatories: vec![],
-        datums: vec![],
+        datums,
     };
     Ok(ctx)
 }
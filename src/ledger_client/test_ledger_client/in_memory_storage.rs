@@ -8,28 +8,177 @@ use crate::{
         LedgerClientError::FailedToIssueTx,
         LedgerClientResult,
     },
-    output::Output,
+    output::{
+        Output,
+        OutputId,
+    },
+    PolicyId,
 };
 use pallas_addresses::{
     Address,
     Network,
 };
-use std::sync::{
-    Arc,
-    Mutex,
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+        HashMap,
+    },
+    sync::{
+        Arc,
+        Mutex,
+    },
 };
 
-/// A mutable, shared reference to a vector of `Output`s.
-type MutableData<Datum> = Arc<Mutex<Vec<(Address, Output<Datum>)>>>;
+/// A mutable, shared reference to the indexed `Output`s.
+type MutableData<Datum> = Arc<Mutex<IndexedOutputs<Datum>>>;
+
+/// Outputs of the in-memory ledger, indexed by address, policy id and output id so queries
+/// don't scan the whole ledger
+///
+/// Outputs are kept in the order they were added.
+#[derive(Debug)]
+pub struct IndexedOutputs<Datum> {
+    next_key: u64,
+    outputs: BTreeMap<u64, (Address, Output<Datum>)>,
+    by_address: HashMap<Vec<u8>, BTreeSet<u64>>,
+    by_policy: HashMap<PolicyId, BTreeSet<u64>>,
+    by_id: HashMap<OutputId, BTreeSet<u64>>,
+}
+
+impl<Datum> Default for IndexedOutputs<Datum> {
+    fn default() -> Self {
+        IndexedOutputs {
+            next_key: 0,
+            outputs: BTreeMap::new(),
+            by_address: HashMap::new(),
+            by_policy: HashMap::new(),
+            by_id: HashMap::new(),
+        }
+    }
+}
+
+impl<Datum> IndexedOutputs<Datum> {
+    /// Add `output`, held at `address`
+    pub fn insert(&mut self, address: Address, output: Output<Datum>) {
+        let key = self.next_key;
+        self.next_key += 1;
+        self.by_address
+            .entry(address.to_vec())
+            .or_default()
+            .insert(key);
+        for (policy, _) in output.values().as_iter() {
+            self.by_policy
+                .entry(policy.clone())
+                .or_default()
+                .insert(key);
+        }
+        self.by_id
+            .entry(output.id().clone())
+            .or_default()
+            .insert(key);
+        self.outputs.insert(key, (address, output));
+    }
+
+    /// Remove the first output equal to `output`, returning whether there was one
+    pub fn remove(&mut self, output: &Output<Datum>) -> bool
+    where
+        Datum: PartialEq,
+    {
+        let key = self.by_id.get(output.id()).and_then(|keys| {
+            keys.iter()
+                .find(|key| matches!(self.outputs.get(*key), Some((_, o)) if o == output))
+                .copied()
+        });
+        let (key, address, removed) = match key
+            .and_then(|key| self.outputs.remove(&key).map(|(a, o)| (key, a, o)))
+        {
+            Some(entry) => entry,
+            None => return false,
+        };
+        remove_from_index(&mut self.by_address, &address.to_vec(), key);
+        for (policy, _) in removed.values().as_iter() {
+            remove_from_index(&mut self.by_policy, policy, key);
+        }
+        remove_from_index(&mut self.by_id, removed.id(), key);
+        true
+    }
+
+    /// Outputs held at `address`
+    pub fn at_address<'a>(
+        &'a self,
+        address: &Address,
+    ) -> impl Iterator<Item = &'a Output<Datum>> + 'a {
+        self.indexed(self.by_address.get(&address.to_vec()))
+    }
+
+    /// Outputs holding any amount of `policy`
+    pub fn with_policy<'a>(
+        &'a self,
+        policy: &PolicyId,
+    ) -> impl Iterator<Item = &'a Output<Datum>> + 'a {
+        self.indexed(self.by_policy.get(policy))
+    }
+
+    /// All outputs with the addresses holding them
+    pub fn iter(&self) -> impl Iterator<Item = &(Address, Output<Datum>)> {
+        self.outputs.values()
+    }
+
+    /// Number of outputs
+    pub fn len(&self) -> usize {
+        self.outputs.len()
+    }
+
+    /// Whether there are no outputs
+    pub fn is_empty(&self) -> bool {
+        self.outputs.is_empty()
+    }
+
+    fn indexed<'a>(
+        &'a self,
+        keys: Option<&'a BTreeSet<u64>>,
+    ) -> impl Iterator<Item = &'a Output<Datum>> + 'a {
+        keys.into_iter()
+            .flatten()
+            .filter_map(|key| self.outputs.get(key))
+            .map(|(_, output)| output)
+    }
+}
+
+impl<Datum> FromIterator<(Address, Output<Datum>)> for IndexedOutputs<Datum> {
+    fn from_iter<I: IntoIterator<Item = (Address, Output<Datum>)>>(iter: I) -> Self {
+        let mut indexed = IndexedOutputs::default();
+        for (address, output) in iter {
+            indexed.insert(address, output);
+        }
+        indexed
+    }
+}
+
+fn remove_from_index<K, Q>(index: &mut HashMap<K, BTreeSet<u64>>, entry: &Q, key: u64)
+where
+    K: std::borrow::Borrow<Q> + std::hash::Hash + Eq,
+    Q: std::hash::Hash + Eq + ?Sized,
+{
+    if let Some(keys) = index.get_mut(entry) {
+        keys.remove(&key);
+        if keys.is_empty() {
+            index.remove(entry);
+        }
+    }
+}
 
 /// Storage adapter for [`TestLedgerClient`] that is ephemeral and in-memory. This is useful
 /// for Unit Tests and Integration Tests.
+///
+/// Cloning is cheap and the clone shares the same underlying ledger state.
 #[derive(Debug)]
 pub struct InMemoryStorage<Datum> {
     /// The address of the signer key owned by this instance of the [`LedgerClient`]. This is a
     /// simplification of how Cardano works, but it's sufficient for testing.
     pub signer: Address,
-    /// A list of all the outputs on this fake ledger.
+    /// All the outputs on this fake ledger.
     pub outputs: MutableData<Datum>,
     /// The current time on the ledger, or the time of the last "block" committed to the ledger.
     pub current_posix_time: Arc<Mutex<i64>>,
@@ -37,6 +186,17 @@ pub struct InMemoryStorage<Datum> {
     pub block_length: i64,
 }
 
+impl<Datum> Clone for InMemoryStorage<Datum> {
+    fn clone(&self) -> Self {
+        InMemoryStorage {
+            signer: self.signer.clone(),
+            outputs: self.outputs.clone(),
+            current_posix_time: self.current_posix_time.clone(),
+            block_length: self.block_length,
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl<Datum: Clone + Send + Sync + PartialEq> TestLedgerStorage<Datum>
     for InMemoryStorage<Datum>
@@ -57,10 +217,9 @@ impl<Datum: Clone + Send + Sync + PartialEq> TestLedgerStorage<Datum>
             .map_err(|e| {
                 LedgerClientError::FailedToRetrieveOutputsAt(address.clone(), Box::new(e))
             })?
-            .iter()
-            .cloned()
-            .filter_map(|(a, o)| if &a == address { Some(o) } else { None })
+            .at_address(address)
             .take(count)
+            .cloned()
             .collect();
         Ok(outputs)
     }
@@ -76,9 +235,23 @@ impl<Datum: Clone + Send + Sync + PartialEq> TestLedgerStorage<Datum>
             .map_err(|e| {
                 LedgerClientError::FailedToRetrieveOutputsAt(address.clone(), Box::new(e))
             })?
-            .iter()
+            .at_address(address)
+            .cloned()
+            .collect();
+        Ok(outputs)
+    }
+
+    async fn outputs_with_policy(
+        &self,
+        policy: &PolicyId,
+    ) -> LedgerClientResult<Vec<Output<Datum>>> {
+        let outputs = self
+            .outputs
+            .lock()
+            .map_err(|e| TestLCError::Mutex(format! {"{e:?}"}))
+            .map_err(|e| FailedToIssueTx(Box::new(e)))?
+            .with_policy(policy)
             .cloned()
-            .filter_map(|(a, o)| if &a == address { Some(o) } else { None })
             .collect();
         Ok(outputs)
     }
@@ -89,17 +262,14 @@ impl<Datum: Clone + Send + Sync + PartialEq> TestLedgerStorage<Datum>
             .lock()
             .map_err(|e| TestLCError::Mutex(format! {"{e:?}"}))
             .map_err(|e| FailedToIssueTx(Box::new(e)))?;
-        let index = ledger_utxos
-            .iter()
-            .position(|(_, x)| x == output)
-            .ok_or_else(|| {
-                LedgerClientError::FailedToRetrieveOutputWithId(
-                    output.id().clone(),
-                    Box::new(TestLCError::DuplicateInput),
-                )
-            })?;
-        ledger_utxos.remove(index);
-        Ok(())
+        if ledger_utxos.remove(output) {
+            Ok(())
+        } else {
+            Err(LedgerClientError::FailedToRetrieveOutputWithId(
+                output.id().clone(),
+                Box::new(TestLCError::DuplicateInput),
+            ))
+        }
     }
 
     async fn add_output(&self, output: &Output<Datum>) -> LedgerClientResult<()> {
@@ -108,7 +278,7 @@ impl<Datum: Clone + Send + Sync + PartialEq> TestLedgerStorage<Datum>
             .lock()
             .map_err(|e| TestLCError::Mutex(format! {"{e:?}"}))
             .map_err(|e| FailedToIssueTx(Box::new(e)))?;
-        ledger_utxos.push((output.owner(), output.clone()));
+        ledger_utxos.insert(output.owner(), output.clone());
         Ok(())
     }
 
@@ -139,3 +309,54 @@ impl<Datum: Clone + Send + Sync + PartialEq> TestLedgerStorage<Datum>
         Ok(Network::Testnet)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::values::Values;
+
+    const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+    const BOB: &str = "addr_test1qzvrhz9v6lwcr26a52y8mmk2nzq37lky68359keq3dgth4lkzpnnjv8vf98m20lhqdzl60mcftq7r2lc4xtcsv0w6xjstag0ua";
+
+    fn output(owner: &Address, index: u64, policy: &PolicyId) -> Output<()> {
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, 2_000_000);
+        values.add_one_value(policy, 1);
+        Output::new_wallet(vec![1; 32], index, owner.clone(), values)
+    }
+
+    #[test]
+    fn indexed_outputs__finds_outputs_by_address_and_policy_after_removal() {
+        // given
+        let alice = Address::from_bech32(ALICE).unwrap();
+        let bob = Address::from_bech32(BOB).unwrap();
+        let token = PolicyId::native_token("abcd", &None);
+        let other = PolicyId::native_token("ef01", &None);
+        let first = output(&alice, 0, &token);
+        let second = output(&bob, 1, &token);
+        let third = output(&alice, 2, &other);
+        let mut indexed: IndexedOutputs<()> = [
+            (alice.clone(), first.clone()),
+            (bob.clone(), second.clone()),
+            (alice.clone(), third.clone()),
+        ]
+        .into_iter()
+        .collect();
+
+        // when
+        let removed = indexed.remove(&first);
+
+        // then
+        assert!(removed);
+        assert!(!indexed.remove(&first));
+        assert_eq!(indexed.len(), 2);
+        assert_eq!(indexed.at_address(&alice).collect::<Vec<_>>(), vec![&third]);
+        assert_eq!(
+            indexed.with_policy(&token).collect::<Vec<_>>(),
+            vec![&second]
+        );
+        assert_eq!(indexed.with_policy(&PolicyId::Lovelace).count(), 2);
+    }
+}
@@ -31,14 +31,20 @@ use crate::{
     output::{
         Output,
         OutputId,
+        ReferenceScript,
     },
     scripts::plutus_validator::plutus_data::PlutusData,
     values::Values,
     PolicyId,
 };
 
+// Version 1 stores asset names as hex. Files without a version store them as text
+const DATA_VERSION: u32 = 1;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub(crate) struct LedgerData {
+    #[serde(default)]
+    version: u32,
     active_signer_name: String,
     active_signer: String,
     signers: HashMap<String, String>,
@@ -53,6 +59,10 @@ struct LDOutput {
     owner: String,
     values: Values,
     datum: Option<PlutusData>,
+    #[serde(default)]
+    datum_hash: Option<Vec<u8>>,
+    #[serde(default)]
+    reference_script: Option<ReferenceScript>,
 }
 
 impl<Datum: Clone + Into<PlutusData>> From<Output<Datum>> for LDOutput {
@@ -62,6 +72,8 @@ impl<Datum: Clone + Into<PlutusData>> From<Output<Datum>> for LDOutput {
             owner: output.owner().to_bech32().expect("Already validated"),
             values: output.values().clone(),
             datum: output.datum_plutus_data(),
+            datum_hash: output.datum_hash().map(|hash| hash.to_vec()),
+            reference_script: output.reference_script().cloned(),
         }
     }
 }
@@ -73,18 +85,69 @@ impl<Datum> From<LDOutput> for Output<Datum> {
             owner,
             values,
             datum,
+            datum_hash,
+            reference_script,
         } = value;
         let tx_hash = id.tx_hash().to_owned();
         let index = id.index();
         let owner = Address::from_bech32(&owner).unwrap(); // TODO: Unwrap
-        if let Some(datum) = datum {
+        let mut output = if let Some(datum) = datum {
             Output::new_untyped_validator(tx_hash, index, owner, values, datum)
         } else {
             Output::new_wallet(tx_hash, index, owner, values)
+        };
+        if let Some(datum_hash) = datum_hash {
+            output = output.with_datum_hash(datum_hash);
         }
+        if let Some(reference_script) = reference_script {
+            output = output.with_reference_script(reference_script);
+        }
+        output
     }
 }
 
+// Bring data written by older versions up to `DATA_VERSION`. Only the asset names changed,
+// so the rest of the file is left as it is
+fn migrate_ledger_data(data: &mut serde_json::Value) {
+    let version = data.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if version == 0 {
+        let outputs = data
+            .get_mut("outputs")
+            .and_then(|outputs| outputs.as_array_mut())
+            .into_iter()
+            .flatten();
+        for output in outputs {
+            if let Some(values) = output.pointer_mut("/values/values") {
+                hex_encode_asset_names(values);
+            }
+        }
+    }
+    data["version"] = DATA_VERSION.into();
+}
+
+// Values are keyed by the JSON of their `PolicyId`, e.g. `{"NativeToken":["<id>","<name>"]}`
+fn hex_encode_asset_names(values: &mut serde_json::Value) {
+    let Some(values) = values.as_object_mut() else {
+        return
+    };
+    let migrated = std::mem::take(values)
+        .into_iter()
+        .map(|(policy, amount)| {
+            let mut key: serde_json::Value = match serde_json::from_str(&policy) {
+                Ok(key) => key,
+                Err(_) => return (policy, amount),
+            };
+            if let Some(name) = key.pointer_mut("/NativeToken/1") {
+                if let Some(text) = name.as_str() {
+                    *name = hex::encode(text.as_bytes()).into();
+                }
+            }
+            (key.to_string(), amount)
+        })
+        .collect();
+    *values = migrated;
+}
+
 #[derive(Debug, Error)]
 enum LocalPersistedLCError {
     // #[error("Not enough input value available for outputs")]
@@ -100,6 +163,7 @@ impl LedgerData {
         let mut signers = HashMap::new();
         signers.insert(signer_name.to_string(), address_bech_32.clone());
         LedgerData {
+            version: DATA_VERSION,
             active_signer_name: signer_name.to_string(),
             active_signer: address_bech_32,
             signers,
@@ -199,7 +263,10 @@ where
         let mut file = File::open(path).unwrap();
         let mut contents = String::new();
         file.read_to_string(&mut contents).expect("Could not read");
-        serde_json::from_str(&contents).unwrap()
+        let mut data: serde_json::Value =
+            serde_json::from_str(&contents).expect("Ledger data isn't valid JSON");
+        migrate_ledger_data(&mut data);
+        serde_json::from_value(data).expect("Ledger data has an unknown format")
     }
 
     fn update_outputs(&self, new_outputs: Vec<LDOutput>) {
@@ -304,6 +371,21 @@ where
         Ok(outputs)
     }
 
+    async fn outputs_with_policy(
+        &self,
+        policy: &PolicyId,
+    ) -> LedgerClientResult<Vec<Output<Datum>>> {
+        let data = self.get_data();
+        let outputs = data
+            .outputs
+            .into_iter()
+            .map(Into::<Output<Datum>>::into)
+            .filter(|o| o.values().get(policy).is_some())
+            .map(|output| output.with_typed_datum_if_possible())
+            .collect();
+        Ok(outputs)
+    }
+
     async fn remove_output(&self, output: &Output<Datum>) -> LedgerClientResult<()> {
         let mut ledger_utxos = self.get_data().outputs;
         let sanitized_output = output.clone().into();
@@ -430,4 +512,34 @@ mod tests {
         let signer = storage.signer().await.unwrap();
         assert_eq!(signer, bob_address);
     }
+
+    #[tokio::test]
+    async fn get_data__migrates_files_with_text_asset_names() {
+        // given
+        let signer = Address::from_bech32("addr_test1qrksjmprvgcedgdt6rhg40590vr6exdzdc2hm5wc6pyl9ymkyskmqs55usm57gflrumk9kd63f3ty6r0l2tdfwfm28qs0rurdr").unwrap();
+        let tmp_dir = TempDir::new().unwrap();
+        let policy = PolicyId::native_token("abcd", &Some(b"nft".to_vec()));
+        let mut values = Values::default();
+        values.add_one_value(&policy, 1);
+        let mut data = LedgerData::new("Alice", &signer, BLOCK_LENGTH);
+        data.add_output(Output::<()>::new_wallet(
+            vec![1; 32],
+            0,
+            signer.clone(),
+            values,
+        ));
+        let old_format = serde_json::to_string(&data)
+            .unwrap()
+            .replace(&hex::encode(b"nft"), "nft")
+            .replace(&format!("\"version\":{DATA_VERSION},"), "");
+        std::fs::write(tmp_dir.path().join(DATA), old_format).unwrap();
+
+        // when
+        let storage = LocalPersistedStorage::<_, ()>::load(tmp_dir.path());
+        let outputs = storage.all_outputs(&signer).await.unwrap();
+
+        // then
+        assert_eq!(outputs[0].values().get(&policy), Some(1));
+        assert_eq!(storage.get_data().version, DATA_VERSION);
+    }
 }
@@ -2,6 +2,7 @@
 
 use super::*;
 use crate::{
+    error::Error,
     ledger_client::{
         test_ledger_client::{
             local_persisted_storage::starting_output,
@@ -9,18 +10,28 @@ use crate::{
         },
         LedgerClient,
     },
-    output::UnbuiltOutput,
+    output::{
+        OutputId,
+        UnbuiltOutput,
+    },
     scripts::{
+        deployments::DeploymentRegistry,
         ExecutionCost,
         MintingPolicy,
         ScriptError,
         ScriptResult,
         Validator,
     },
-    transaction::TransactionVersion,
+    slot_config::SlotConfig,
+    transaction::{
+        TransactionVersion,
+        TxActions,
+    },
+    trireme_ledger_client::Network as TriremeNetwork,
     PolicyId,
     UnbuiltTransaction,
 };
+use futures::TryStreamExt;
 
 const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
 const BOB: &str = "addr_test1qzvrhz9v6lwcr26a52y8mmk2nzq37lky68359keq3dgth4lkzpnnjv8vf98m20lhqdzl60mcftq7r2lc4xtcsv0w6xjstag0ua";
@@ -59,6 +70,160 @@ async fn balance_at_address() {
     assert_eq!(expected, actual);
 }
 
+#[tokio::test]
+async fn balances_at_address__sums_every_policy_across_outputs() {
+    // given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let token = PolicyId::native_token("abcd", &Some(b"Coin".to_vec()));
+    let record = TestLedgerClientBuilder::<(), ()>::new(&signer)
+        .start_output(&signer)
+        .with_value(PolicyId::Lovelace, 2_000_000)
+        .with_value(token.clone(), 100)
+        .finish_output()
+        .start_output(&signer)
+        .with_value(PolicyId::Lovelace, 3_000_000)
+        .with_value(token.clone(), 50)
+        .finish_output()
+        .build_in_memory();
+
+    // when
+    let balances = record.balances_at_address(&signer).await.unwrap();
+
+    // then
+    assert_eq!(balances.get(&PolicyId::Lovelace), Some(5_000_000));
+    assert_eq!(balances.get(&token), Some(150));
+    let lovelace = record
+        .balance_at_address(&signer, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(lovelace, 5_000_000);
+}
+
+#[tokio::test]
+async fn output_holding_nft__finds_output_with_token() {
+    // given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let nft = PolicyId::native_token("abcd", &Some(b"Thread".to_vec()));
+    let fungible = PolicyId::native_token("abcd", &Some(b"Coin".to_vec()));
+    let record = TestLedgerClientBuilder::<(), ()>::new(&signer)
+        .start_output(&signer)
+        .with_value(PolicyId::Lovelace, 2_000_000)
+        .with_value(fungible, 100)
+        .finish_output()
+        .start_output(&signer)
+        .with_value(PolicyId::Lovelace, 2_000_000)
+        .with_value(nft.clone(), 1)
+        .finish_output()
+        .build_in_memory();
+
+    // when
+    let holder = record.output_holding_nft(&signer, &nft).await.unwrap();
+    let nfts = record.nfts_at_address(&signer).await.unwrap();
+
+    // then
+    assert_eq!(holder.unwrap().values().get(&nft), Some(1));
+    assert_eq!(nfts, vec![nft]);
+}
+
+#[tokio::test]
+async fn all_outputs_at_addresses__merges_outputs_of_each_address_once() {
+    // given
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let bob = Address::from_bech32(BOB).unwrap();
+    let record = TestLedgerClientBuilder::<(), ()>::new(&alice)
+        .start_output(&alice)
+        .with_value(PolicyId::Lovelace, 1_000_000)
+        .finish_output()
+        .start_output(&bob)
+        .with_value(PolicyId::Lovelace, 2_000_000)
+        .finish_output()
+        .start_output(&bob)
+        .with_value(PolicyId::Lovelace, 3_000_000)
+        .finish_output()
+        .build_in_memory();
+
+    // when
+    let outputs = record
+        .all_outputs_at_addresses(&[bob.clone(), alice.clone(), bob.clone()])
+        .await
+        .unwrap();
+
+    // then
+    let owners: Vec<_> = outputs.iter().map(|o| o.owner()).collect();
+    assert_eq!(owners, vec![bob.clone(), bob, alice]);
+}
+
+#[tokio::test]
+async fn stream_outputs_at_address__yields_each_output_and_finds_first_match() {
+    // given
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let record = TestLedgerClientBuilder::<(), ()>::new(&alice)
+        .start_output(&alice)
+        .with_value(PolicyId::Lovelace, 1_000_000)
+        .finish_output()
+        .start_output(&alice)
+        .with_value(PolicyId::Lovelace, 5_000_000)
+        .finish_output()
+        .build_in_memory();
+
+    // when
+    let streamed: Vec<_> = record
+        .stream_outputs_at_address(&alice)
+        .try_collect()
+        .await
+        .unwrap();
+    let big = record
+        .find_output_at_address(&alice, |o| {
+            o.values().get(&PolicyId::Lovelace) > Some(2_000_000)
+        })
+        .await
+        .unwrap();
+
+    // then
+    assert_eq!(
+        streamed,
+        record.all_outputs_at_address(&alice).await.unwrap()
+    );
+    assert_eq!(
+        big.unwrap().values().get(&PolicyId::Lovelace),
+        Some(5_000_000)
+    );
+}
+
+#[tokio::test]
+async fn outputs_at_address_matching__only_returns_outputs_with_matching_datums() {
+    // given
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let record = TestLedgerClientBuilder::<u64, ()>::new(&alice)
+        .start_output(&alice)
+        .with_value(PolicyId::Lovelace, 1_000_000)
+        .with_datum(1)
+        .finish_output()
+        .start_output(&alice)
+        .with_value(PolicyId::Lovelace, 2_000_000)
+        .with_datum(2)
+        .finish_output()
+        .start_output(&alice)
+        .with_value(PolicyId::Lovelace, 3_000_000)
+        .finish_output()
+        .build_in_memory();
+
+    // when
+    let wanted = PlutusData::from(2_u64);
+    let matching = record
+        .outputs_at_address_matching(&alice, |data| data == &wanted)
+        .await
+        .unwrap();
+
+    // then
+    assert_eq!(matching.len(), 1);
+    assert_eq!(matching[0].typed_datum(), Some(2));
+    assert_eq!(
+        matching[0].values().get(&PolicyId::Lovelace),
+        Some(2_000_000)
+    );
+}
+
 #[tokio::test]
 async fn issue_transfer() {
     let sender = Address::from_bech32(ALICE).unwrap();
@@ -79,7 +244,9 @@ async fn issue_transfer() {
         unbuilt_outputs: vec![new_output],
         minting: Default::default(),
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     };
     record.issue(tx).await.unwrap();
     let actual_bob = record
@@ -119,7 +286,9 @@ async fn issuing_tx_advances_time_by_block_length() {
         unbuilt_outputs: vec![],
         minting: Default::default(),
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     };
     record.issue(tx).await.unwrap();
     let expected = starting_time + BLOCK_LENGTH;
@@ -145,7 +314,9 @@ async fn errors_if_spending_more_than_you_own() {
         unbuilt_outputs: vec![new_output],
         minting: Default::default(),
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     };
     let error = record.issue(tx).await.unwrap_err();
 
@@ -177,7 +348,9 @@ async fn cannot_transfer_before_valid_range() {
         unbuilt_outputs: vec![new_output],
         minting: Default::default(),
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (Some(valid_time), None),
+        metadata: Default::default(),
     };
     let error = record.issue(tx).await.unwrap_err();
 
@@ -209,13 +382,53 @@ async fn cannot_transfer_after_valid_range() {
         unbuilt_outputs: vec![new_output],
         minting: Default::default(),
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, Some(valid_time)),
+        metadata: Default::default(),
     };
     let error = record.issue(tx).await.unwrap_err();
 
     assert!(matches!(error, LedgerClientError::FailedToIssueTx(_),));
 }
 
+#[tokio::test]
+async fn issue__checks_valid_range_by_slot() {
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let output = starting_output::<()>(&sender, 10_000_000);
+    let slot_length_ms = 20_000;
+    let record: TestLedgerClient<(), (), _> = TestLedgerClient::new_in_memory(
+        sender.clone(),
+        vec![(sender, output)],
+        BLOCK_LENGTH,
+        0,
+    )
+    .with_slot_config(SlotConfig::new(0, 0, slot_length_ms));
+    record.set_current_time_secs(30).await.unwrap();
+    let recipient = Address::from_bech32(BOB).unwrap();
+    let transfer = |valid_range| {
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, 1_000_000);
+        UnbuiltTransaction::<(), ()> {
+            script_version: TransactionVersion::V2,
+            script_inputs: vec![],
+            unbuilt_outputs: vec![UnbuiltOutput::new_wallet(recipient.clone(), values)],
+            minting: Default::default(),
+            specific_wallet_inputs: vec![],
+            valid_range,
+            metadata: Default::default(),
+        }
+    };
+
+    // 35s is in the same 20s slot as 30s, so the tx is already valid
+    record.issue(transfer((Some(35), None))).await.unwrap();
+    let error = record
+        .issue(transfer((Some(0), Some(40))))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, LedgerClientError::FailedToIssueTx(_)));
+}
+
 #[derive(Clone, Copy)]
 struct AlwaysTrueFakeValidator;
 
@@ -266,7 +479,9 @@ async fn redeeming_datum() {
         unbuilt_outputs: vec![new_output],
         minting: Default::default(),
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     };
     record.issue(tx).await.unwrap();
 
@@ -297,7 +512,9 @@ async fn redeeming_datum() {
         unbuilt_outputs: vec![],
         minting: Default::default(),
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     };
 
     record.issue(tx).await.unwrap();
@@ -365,7 +582,9 @@ async fn failing_script_will_not_redeem() {
         unbuilt_outputs: vec![new_output],
         minting: Default::default(),
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     };
     record.issue(tx).await.unwrap();
 
@@ -396,7 +615,9 @@ async fn failing_script_will_not_redeem() {
         unbuilt_outputs: vec![],
         minting: Default::default(),
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     };
 
     record.issue(tx).await.unwrap_err();
@@ -427,7 +648,9 @@ async fn cannot_redeem_datum_twice() {
         unbuilt_outputs: vec![new_output],
         minting: Default::default(),
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     };
     record.issue(tx).await.unwrap();
 
@@ -461,7 +684,9 @@ async fn cannot_redeem_datum_twice() {
         unbuilt_outputs: vec![],
         minting: Default::default(),
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     };
 
     // Then should error
@@ -503,9 +728,11 @@ async fn mint_always_true() {
         script_version: TransactionVersion::V2,
         script_inputs: vec![],
         unbuilt_outputs: vec![],
-        minting: vec![(minting_amount, None, (), script_box)],
+        minting: vec![(minting_amount as i64, None, (), script_box)],
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     };
     record.issue(tx).await.unwrap();
 
@@ -551,9 +778,11 @@ async fn mint_always_fails_errors() {
         script_version: TransactionVersion::V2,
         script_inputs: vec![],
         unbuilt_outputs: vec![],
-        minting: vec![(minting_amount, None, (), script_box)],
+        minting: vec![(minting_amount as i64, None, (), script_box)],
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     };
     record.issue(tx).await.unwrap_err();
 
@@ -625,9 +854,11 @@ async fn spends_specific_script_value() {
         script_version: TransactionVersion::V2,
         script_inputs: vec![redeem_info],
         unbuilt_outputs: vec![],
-        minting: vec![(minting_amount, asset_name.clone(), (), script_box)],
+        minting: vec![(minting_amount as i64, asset_name.clone(), (), script_box)],
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     };
     record.issue(tx).await.unwrap();
 
@@ -637,3 +868,795 @@ async fn spends_specific_script_value() {
         .unwrap();
     assert_eq!(alice_balance, minting_amount);
 }
+
+fn transfer_tx(recipient: &Address, amount: u64) -> UnbuiltTransaction<(), ()> {
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, amount);
+    let new_output = UnbuiltOutput::new_wallet(recipient.clone(), values);
+    UnbuiltTransaction {
+        script_version: TransactionVersion::V2,
+        script_inputs: vec![],
+        unbuilt_outputs: vec![new_output],
+        minting: Default::default(),
+        specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
+        valid_range: (None, None),
+        metadata: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn ledger_outputs__lists_every_output_left_after_issuing() {
+    // given
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let bob = Address::from_bech32(BOB).unwrap();
+    let output = starting_output::<()>(&alice, 10_000_000);
+    let record: TestLedgerClient<(), (), _> = TestLedgerClient::new_in_memory(
+        alice.clone(),
+        vec![(alice.clone(), output)],
+        BLOCK_LENGTH,
+        0,
+    );
+
+    // when
+    record.issue(transfer_tx(&bob, 2_000_000)).await.unwrap();
+    record.issue(transfer_tx(&bob, 3_000_000)).await.unwrap();
+    let outputs = record.ledger_outputs();
+
+    // then
+    let held_by = |owner: &Address| {
+        outputs
+            .iter()
+            .filter(|(address, _)| address == owner)
+            .map(|(_, output)| output.values().get(&PolicyId::Lovelace).unwrap())
+            .collect::<Vec<_>>()
+    };
+    let mut bobs = held_by(&bob);
+    bobs.sort();
+    assert_eq!(bobs, vec![2_000_000, 3_000_000]);
+    assert_eq!(held_by(&alice), vec![5_000_000]);
+}
+
+#[tokio::test]
+async fn clones_share_ledger_state() {
+    // given
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let recipient = Address::from_bech32(BOB).unwrap();
+    let starting_amount = 10_000_000;
+    let transfer_amount = 3_000_000;
+    let output = starting_output::<()>(&sender, starting_amount);
+    let outputs = vec![(sender.clone(), output)];
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::new_in_memory(sender.clone(), outputs, BLOCK_LENGTH, 0);
+    let cloned = record.clone();
+
+    // when
+    cloned
+        .issue(transfer_tx(&recipient, transfer_amount))
+        .await
+        .unwrap();
+
+    // then
+    let bob_balance = record
+        .balance_at_address(&recipient, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(bob_balance, transfer_amount);
+    assert_eq!(record.current_time_secs().await.unwrap(), BLOCK_LENGTH);
+}
+
+#[tokio::test]
+async fn with_signer__spends_from_the_other_signer_on_the_same_ledger() {
+    // given
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let bob = Address::from_bech32(BOB).unwrap();
+    let starting_amount = 10_000_000;
+    let transfer_amount = 3_000_000;
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, starting_amount);
+    let outputs = vec![
+        (
+            alice.clone(),
+            Output::new_wallet(vec![1; 32], 0, alice.clone(), values.clone()),
+        ),
+        (
+            bob.clone(),
+            Output::new_wallet(vec![2; 32], 0, bob.clone(), values),
+        ),
+    ];
+    let alice_client: TestLedgerClient<(), (), _> =
+        TestLedgerClient::new_in_memory(alice.clone(), outputs, BLOCK_LENGTH, 0);
+    let bob_client = alice_client.with_signer(&bob);
+
+    // when
+    bob_client
+        .issue(transfer_tx(&alice, transfer_amount))
+        .await
+        .unwrap();
+
+    // then
+    let alice_balance = alice_client
+        .balance_at_address(&alice, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    let bob_balance = alice_client
+        .balance_at_address(&bob, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(alice_balance, starting_amount + transfer_amount);
+    assert_eq!(bob_balance, starting_amount - transfer_amount);
+    assert_eq!(bob_client.signer_base_address().await.unwrap(), bob);
+}
+
+#[tokio::test]
+async fn concurrent_issues_against_shared_ledger_are_serialized() {
+    // given
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let recipient = Address::from_bech32(BOB).unwrap();
+    let starting_amount = 10_000_000;
+    let transfer_amount = 3_000_000;
+    let output = starting_output::<()>(&sender, starting_amount);
+    let outputs = vec![(sender.clone(), output)];
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::new_in_memory(sender.clone(), outputs, BLOCK_LENGTH, 0);
+    let first = record.clone();
+    let second = record.clone();
+
+    // when
+    let (first_res, second_res) = tokio::join!(
+        first.issue(transfer_tx(&recipient, transfer_amount)),
+        second.issue(transfer_tx(&recipient, transfer_amount)),
+    );
+
+    // then
+    first_res.unwrap();
+    second_res.unwrap();
+    let alice_balance = record
+        .balance_at_address(&sender, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(alice_balance, starting_amount - 2 * transfer_amount);
+    let bob_balance = record
+        .balance_at_address(&recipient, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(bob_balance, 2 * transfer_amount);
+    assert_eq!(record.current_time_secs().await.unwrap(), 2 * BLOCK_LENGTH);
+}
+
+#[derive(Clone, Copy)]
+struct RequiresDatumHashValidator;
+
+impl Validator<(), ()> for RequiresDatumHashValidator {
+    fn execute(
+        &self,
+        _datum: (),
+        _redeemer: (),
+        ctx: TxContext,
+    ) -> ScriptResult<ExecutionCost> {
+        let spends_by_hash = ctx.inputs.iter().any(|input| match &input.datum {
+            CtxDatum::DatumHash(hash) => ctx.datums.iter().any(|(h, _)| h == hash),
+            _ => false,
+        });
+        if spends_by_hash {
+            Ok(ExecutionCost::default())
+        } else {
+            Err(ScriptError::FailedToExecute(
+                "Expected input with datum hash".to_string(),
+            ))
+        }
+    }
+
+    fn address(&self, _network: Network) -> ScriptResult<Address> {
+        Ok(Address::from_bech32(
+            "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu",
+        )
+        .unwrap())
+    }
+
+    fn script_hex(&self) -> ScriptResult<String> {
+        Ok("4e4d01000033222220051200120011".to_string())
+    }
+}
+
+#[tokio::test]
+async fn builder_can_add_datum_hash_and_reference_script_outputs() {
+    // given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let validator = RequiresDatumHashValidator;
+    let script_address = validator.address(Network::Testnet).unwrap();
+    let locked_amount = 3_000_000;
+
+    // when
+    let record = TestLedgerClientBuilder::<(), ()>::new(&signer)
+        .start_output(&script_address)
+        .with_value(PolicyId::Lovelace, locked_amount)
+        .with_datum_hash(())
+        .with_reference_script(&validator)
+        .finish_output()
+        .build_in_memory();
+
+    // then
+    let output = record
+        .all_outputs_at_address(&script_address)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let expected_hash = PlutusData::from(()).hash();
+    assert_eq!(output.datum_hash(), Some(expected_hash.as_slice()));
+    assert_eq!(output.typed_datum(), Some(()));
+    let reference_script = output.reference_script().unwrap();
+    assert_eq!(
+        reference_script.script_hex(),
+        validator.script_hex().unwrap()
+    );
+
+    let script_box: Box<dyn Validator<(), ()>> = Box::new(validator);
+    let tx: UnbuiltTransaction<(), ()> = UnbuiltTransaction {
+        script_version: TransactionVersion::V2,
+        script_inputs: vec![(output, (), script_box)],
+        unbuilt_outputs: vec![],
+        minting: Default::default(),
+        specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
+        valid_range: (None, None),
+        metadata: Default::default(),
+    };
+    record.issue(tx).await.unwrap();
+
+    let signer_balance = record
+        .balance_at_address(&signer, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(signer_balance, locked_amount);
+}
+
+#[tokio::test]
+async fn to_unbuilt_tx__finds_reference_scripts_by_hash() {
+    // given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let validator = RequiresDatumHashValidator;
+    let script_address = validator.address(Network::Testnet).unwrap();
+    let record = TestLedgerClientBuilder::<(), ()>::new(&signer)
+        .start_output(&script_address)
+        .with_value(PolicyId::Lovelace, 20_000_000)
+        .with_reference_script(&validator)
+        .finish_output()
+        .build_in_memory();
+    let deployed = record
+        .all_outputs_at_address(&script_address)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let script_hash = deployed.reference_script().unwrap().hash().to_vec();
+
+    // when
+    let v2_tx = TxActions::<(), ()>::v2()
+        .with_reference_script(deployed.clone())
+        .to_unbuilt_tx()
+        .unwrap();
+    let v1_res = TxActions::<(), ()>::v1()
+        .with_reference_script(deployed)
+        .to_unbuilt_tx();
+
+    // then
+    assert!(v2_tx.reference_script_input(&script_hash).is_some());
+    assert!(v2_tx.reference_script_input(&[0; 28]).is_none());
+    assert!(v1_res.is_err());
+}
+
+#[tokio::test]
+async fn with_deployments__uses_registered_reference_scripts() {
+    // given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let validator = RequiresDatumHashValidator;
+    let script_address = validator.address(Network::Testnet).unwrap();
+    let record = TestLedgerClientBuilder::<(), ()>::new(&signer)
+        .start_output(&script_address)
+        .with_value(PolicyId::Lovelace, 20_000_000)
+        .with_reference_script(&validator)
+        .finish_output()
+        .build_in_memory();
+    let deployed = record
+        .all_outputs_at_address(&script_address)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let script_hash = deployed.reference_script().unwrap().hash().to_vec();
+    let dir = tempfile::tempdir().unwrap();
+    let mut registry =
+        DeploymentRegistry::load(dir.path().join("deployments.toml")).unwrap();
+    registry
+        .record(&TriremeNetwork::Preprod, &deployed)
+        .unwrap();
+
+    // when
+    let redeem = |network: &TriremeNetwork| {
+        TxActions::v2()
+            .with_script_redeem(deployed.clone(), (), Box::new(validator))
+            .with_deployments(&registry, network)
+            .to_unbuilt_tx()
+            .unwrap()
+    };
+    let on_preprod = redeem(&TriremeNetwork::Preprod);
+    let on_preview = redeem(&TriremeNetwork::Preview);
+    let lazily = TxActions::v2()
+        .with_script_redeem_lazy(deployed.clone(), |_| Ok(()), Box::new(validator))
+        .with_deployments(&registry, &TriremeNetwork::Preprod)
+        .to_unbuilt_tx()
+        .unwrap();
+
+    // then
+    assert!(on_preprod.reference_script_input(&script_hash).is_some());
+    assert_eq!(on_preprod.reference_inputs.len(), 1);
+    assert!(on_preview.reference_inputs.is_empty());
+    assert!(lazily.reference_script_input(&script_hash).is_some());
+}
+
+#[tokio::test]
+async fn script_init__creates_outputs_spendable_by_datum_hash() {
+    // given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let validator = RequiresDatumHashValidator;
+    let script_address = validator.address(Network::Testnet).unwrap();
+    let locked_amount = 3_000_000;
+    let record = TestLedgerClientBuilder::<(), ()>::new(&signer)
+        .fund_lovelace(&signer, 10_000_000)
+        .build_in_memory();
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, locked_amount);
+
+    // when
+    let lock = TxActions::<(), ()>::v2()
+        .with_script_init((), values, script_address.clone())
+        .to_unbuilt_tx()
+        .unwrap();
+    record.issue(lock).await.unwrap();
+    let locked = record
+        .all_outputs_at_address(&script_address)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let unlock = TxActions::<(), ()>::v2()
+        .with_script_redeem(locked.clone(), (), Box::new(validator))
+        .to_unbuilt_tx()
+        .unwrap();
+    let unlock_res = record.issue(unlock).await;
+
+    // then
+    let expected_hash = PlutusData::from(()).hash();
+    assert_eq!(locked.datum_hash(), Some(expected_hash.as_slice()));
+    assert_eq!(locked.typed_datum(), Some(()));
+    assert!(unlock_res.is_ok());
+}
+
+#[tokio::test]
+async fn script_init_inline_datum__stores_the_datum_on_the_output() {
+    // given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let script_address = RequiresDatumHashValidator
+        .address(Network::Testnet)
+        .unwrap();
+    let record = TestLedgerClientBuilder::<(), ()>::new(&signer)
+        .fund_lovelace(&signer, 10_000_000)
+        .build_in_memory();
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, 3_000_000);
+
+    // when
+    let lock = TxActions::<(), ()>::v2()
+        .with_script_init_inline_datum((), values, script_address.clone())
+        .to_unbuilt_tx()
+        .unwrap();
+    record.issue(lock).await.unwrap();
+    let locked = record
+        .all_outputs_at_address(&script_address)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+
+    // then
+    assert_eq!(locked.datum_hash(), None);
+    assert_eq!(locked.typed_datum(), Some(()));
+}
+
+// Redeemer is the index of the output continuing at the script
+struct ContinuingOutputValidator;
+
+impl Validator<(), u64> for ContinuingOutputValidator {
+    fn execute(
+        &self,
+        _datum: (),
+        redeemer: u64,
+        ctx: TxContext,
+    ) -> ScriptResult<ExecutionCost> {
+        let own_address = self.address(Network::Testnet)?;
+        match ctx.outputs.get(redeemer as usize) {
+            Some(output) if output.address == own_address => Ok(ExecutionCost::default()),
+            _ => Err(ScriptError::FailedToExecute(format!(
+                "No continuing output at index {redeemer}"
+            ))),
+        }
+    }
+
+    fn address(&self, _network: Network) -> ScriptResult<Address> {
+        Ok(Address::from_bech32(
+            "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu",
+        )
+        .unwrap())
+    }
+
+    fn script_hex(&self) -> ScriptResult<String> {
+        todo!()
+    }
+}
+
+#[tokio::test]
+async fn script_redeem_lazy__computes_redeemer_from_final_outputs() {
+    // given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let bob = Address::from_bech32(BOB).unwrap();
+    let validator = ContinuingOutputValidator;
+    let script_address = validator.address(Network::Testnet).unwrap();
+    let record = TestLedgerClientBuilder::<(), u64>::new(&signer)
+        .fund_lovelace(&signer, 10_000_000)
+        .start_output(&script_address)
+        .with_value(PolicyId::Lovelace, 3_000_000)
+        .with_datum(())
+        .finish_output()
+        .build_in_memory();
+    let locked = record
+        .all_outputs_at_address(&script_address)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, 2_000_000);
+
+    // when
+    let script = script_address.clone();
+    let tx = TxActions::<(), u64>::v2()
+        .with_script_redeem_lazy(
+            locked,
+            move |shape| {
+                shape.output_index(&script).ok_or_else(|| {
+                    Error::IncompatibleTxActions("no continuing output".to_string())
+                })
+            },
+            Box::new(ContinuingOutputValidator),
+        )
+        .with_transfer(1_000_000, bob, PolicyId::Lovelace)
+        .with_script_init((), values, script_address.clone())
+        .to_unbuilt_tx()
+        .unwrap();
+
+    // then
+    assert_eq!(tx.script_inputs()[0].1, 1);
+    record.issue(tx).await.unwrap();
+    let script_balance = record
+        .balance_at_address(&script_address, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(script_balance, 2_000_000);
+}
+
+#[tokio::test]
+async fn issued_txs_are_recorded_in_tx_log() {
+    // given
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let recipient = Address::from_bech32(BOB).unwrap();
+    let starting_amount = 10_000_000;
+    let transfer_amount = 3_000_000;
+    let minting_amount = 5;
+    let output = starting_output::<()>(&sender, starting_amount);
+    let starting_id = output.id().clone();
+    let outputs = vec![(sender.clone(), output)];
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::new_in_memory(sender.clone(), outputs, BLOCK_LENGTH, 0);
+    assert!(record.last_tx().is_none());
+
+    let policy = AlwaysTruePolicy;
+    let id = policy.id().unwrap();
+    let script_box: Box<dyn MintingPolicy<()>> = Box::new(policy);
+    let mut tx = transfer_tx(&recipient, transfer_amount);
+    tx.minting = vec![(
+        minting_amount as i64,
+        Some(b"Token".to_vec()),
+        (),
+        script_box,
+    )];
+    tx.valid_range = (None, Some(100));
+
+    // when
+    let tx_id = record.issue(tx).await.unwrap();
+    record
+        .issue(transfer_tx(&recipient, transfer_amount))
+        .await
+        .unwrap();
+
+    // then
+    let txs = record.issued_txs();
+    assert_eq!(txs.len(), 2);
+    let first = txs.first().unwrap();
+    assert_eq!(first.tx_id(), &tx_id);
+    assert_eq!(first.valid_range(), (None, Some(100)));
+    assert_eq!(first.signers(), &[sender.clone()]);
+    first.assert_spent(&starting_id);
+    first.assert_minted(&id, Some(b"Token".as_slice()), minting_amount);
+    first.assert_paid(&recipient, &PolicyId::Lovelace, transfer_amount);
+
+    let last = record.last_tx().unwrap();
+    assert_ne!(last.tx_id(), &tx_id);
+    last.assert_minted(&id, Some(b"Token".as_slice()), 0);
+    record.assert_minted(&id, Some(b"Token".as_slice()), minting_amount);
+}
+
+#[tokio::test]
+async fn burning_removes_tokens_from_the_signer() {
+    // given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let policy = AlwaysTruePolicy;
+    let id = policy.id().unwrap();
+    let token = PolicyId::native_token(&id, &Some(b"Token".to_vec()));
+    let record = TestLedgerClientBuilder::<(), ()>::new(&signer)
+        .fund_lovelace(&signer, 10_000_000)
+        .build_in_memory();
+    let mint = TxActions::<(), ()>::v2()
+        .with_mint(5, Some(b"Token".to_vec()), (), Box::new(AlwaysTruePolicy))
+        .to_unbuilt_tx()
+        .unwrap();
+    record.issue(mint).await.unwrap();
+
+    // when
+    let burn = TxActions::<(), ()>::v2()
+        .with_burn(2, Some(b"Token".to_vec()), (), Box::new(AlwaysTruePolicy))
+        .to_unbuilt_tx()
+        .unwrap();
+    let preview = record.preview(burn).await.unwrap();
+    let burn = TxActions::<(), ()>::v2()
+        .with_burn(2, Some(b"Token".to_vec()), (), Box::new(AlwaysTruePolicy))
+        .to_unbuilt_tx()
+        .unwrap();
+    record.issue(burn).await.unwrap();
+    let overburn = TxActions::<(), ()>::v2()
+        .with_burn(4, Some(b"Token".to_vec()), (), Box::new(AlwaysTruePolicy))
+        .to_unbuilt_tx()
+        .unwrap();
+    let overburn_res = record.issue(overburn).await;
+
+    // then
+    assert_eq!(preview.mint(), &[(token.clone(), -2)]);
+    let balance = record.balance_at_address(&signer, &token).await.unwrap();
+    assert_eq!(balance, 3);
+    assert_eq!(record.last_tx().unwrap().burned().get(&token), Some(2));
+    assert!(overburn_res.is_err());
+}
+
+fn mint_tx(policy: Box<dyn MintingPolicy<()>>) -> UnbuiltTransaction<(), ()> {
+    UnbuiltTransaction {
+        script_version: TransactionVersion::V2,
+        script_inputs: vec![],
+        unbuilt_outputs: vec![],
+        minting: vec![(1, None, (), policy)],
+        specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
+        valid_range: (None, None),
+        metadata: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn failing_script_consumes_collateral() {
+    // given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let collateral_amount = 5_000_000;
+    let other_amount = 20_000_000;
+    let record = TestLedgerClientBuilder::<(), ()>::new(&signer)
+        .with_collateral(collateral_amount)
+        .start_output(&signer)
+        .with_value(PolicyId::Lovelace, other_amount)
+        .with_value(PolicyId::NativeToken("abcd".to_string(), None), 1)
+        .finish_output()
+        .start_output(&signer)
+        .with_value(PolicyId::Lovelace, collateral_amount)
+        .finish_output()
+        .build_in_memory();
+
+    // when
+    let error = record.issue(mint_tx(Box::new(AlwaysFailsPolicy))).await;
+
+    // then
+    assert!(matches!(error, Err(LedgerClientError::FailedToIssueTx(_))));
+    let balance = record
+        .balance_at_address(&signer, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(balance, other_amount);
+}
+
+#[tokio::test]
+async fn successful_script_records_collateral() {
+    // given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let collateral_amount = 5_000_000;
+    let record = TestLedgerClientBuilder::<(), ()>::new(&signer)
+        .with_collateral(collateral_amount)
+        .start_output(&signer)
+        .with_value(PolicyId::Lovelace, collateral_amount)
+        .finish_output()
+        .build_in_memory();
+
+    // when
+    record
+        .issue(mint_tx(Box::new(AlwaysTruePolicy)))
+        .await
+        .unwrap();
+
+    // then
+    let collateral = record.last_tx().unwrap().collateral().cloned().unwrap();
+    assert_eq!(
+        collateral.values().get(&PolicyId::Lovelace),
+        Some(collateral_amount)
+    );
+    let balance = record
+        .balance_at_address(&signer, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(balance, collateral_amount);
+}
+
+#[tokio::test]
+async fn errors_if_no_pure_ada_collateral_available() {
+    // given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let record = TestLedgerClientBuilder::<(), ()>::new(&signer)
+        .with_collateral(5_000_000)
+        .start_output(&signer)
+        .with_value(PolicyId::Lovelace, 20_000_000)
+        .with_value(PolicyId::NativeToken("abcd".to_string(), None), 1)
+        .finish_output()
+        .start_output(&signer)
+        .with_value(PolicyId::Lovelace, 1_000_000)
+        .finish_output()
+        .build_in_memory();
+
+    // when
+    let error = record.issue(mint_tx(Box::new(AlwaysTruePolicy))).await;
+
+    // then
+    assert!(matches!(
+        error,
+        Err(LedgerClientError::NoBigEnoughCollateralUTxO)
+    ));
+}
+
+#[tokio::test]
+async fn faucet_funds_addresses_with_deterministic_ids() {
+    // given
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let bob = Address::from_bech32(BOB).unwrap();
+    let token = PolicyId::NativeToken("abcd".to_string(), None);
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, 2_000_000);
+    values.add_one_value(&token, 10);
+    let build = || {
+        TestLedgerClientBuilder::<(), ()>::new(&alice)
+            .fund_lovelace(&alice, 10_000_000)
+            .build_in_memory()
+    };
+    let record = build();
+
+    // when
+    record.fund(&bob, &values).await.unwrap();
+    record.fund_lovelace(&bob, 3_000_000).await.unwrap();
+
+    // then
+    let alice_balance = record
+        .balance_at_address(&alice, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(alice_balance, 10_000_000);
+    let bob_balance = record
+        .balance_at_address(&bob, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(bob_balance, 5_000_000);
+    let bob_tokens = record.balance_at_address(&bob, &token).await.unwrap();
+    assert_eq!(bob_tokens, 10);
+
+    let first_id = first_output_id(&record, &alice).await;
+    let second_id = first_output_id(&build(), &alice).await;
+    assert_eq!(first_id, second_id);
+    let mut bob_ids: Vec<_> = record
+        .all_outputs_at_address(&bob)
+        .await
+        .unwrap()
+        .iter()
+        .map(|output| output.id().clone())
+        .collect();
+    bob_ids.dedup();
+    assert_eq!(bob_ids.len(), 2);
+    assert!(!bob_ids.contains(&first_id));
+}
+
+async fn first_output_id<Storage: TestLedgerStorage<()> + Send + Sync>(
+    ledger: &TestLedgerClient<(), (), Storage>,
+    address: &Address,
+) -> OutputId {
+    ledger
+        .all_outputs_at_address(address)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap()
+        .id()
+        .clone()
+}
+
+#[tokio::test]
+async fn issue__rejects_outputs_on_another_network() {
+    // given
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let output = starting_output::<()>(&sender, 10_000_000);
+    let record: TestLedgerClient<(), (), _> = TestLedgerClient::new_in_memory(
+        sender.clone(),
+        vec![(sender.clone(), output)],
+        BLOCK_LENGTH,
+        0,
+    );
+    let mainnet_recipient = crate::address::enterprise_address(
+        Network::Mainnet,
+        &crate::address::Credential::Key(vec![1; 28]),
+    )
+    .unwrap();
+
+    // when
+    let res = record
+        .issue(transfer_tx(&mainnet_recipient, 3_000_000))
+        .await;
+
+    // then
+    assert!(matches!(res, Err(LedgerClientError::WrongNetwork(_))));
+}
+
+async fn transfer_to_bob_with_seed(seed: u64) -> Vec<OutputId> {
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let bob = Address::from_bech32(BOB).unwrap();
+    let record = TestLedgerClientBuilder::<(), ()>::new(&alice)
+        .with_seed(seed)
+        .start_output(&alice)
+        .with_value(PolicyId::Lovelace, 10_000_000)
+        .finish_output()
+        .build_in_memory();
+    let tx = TxActions::v2()
+        .with_transfer(1_000_000, bob.clone(), PolicyId::Lovelace)
+        .to_unbuilt_tx()
+        .unwrap();
+    record.issue(tx).await.unwrap();
+    assert_eq!(record.seed(), seed);
+    record
+        .all_outputs_at_address(&bob)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|output| output.id().clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn with_seed__reproduces_the_same_tx_ids() {
+    // when
+    let first = transfer_to_bob_with_seed(7).await;
+    let second = transfer_to_bob_with_seed(7).await;
+    let other = transfer_to_bob_with_seed(8).await;
+
+    // then
+    assert_eq!(first, second);
+    assert_ne!(first, other);
+}
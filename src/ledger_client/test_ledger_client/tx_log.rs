@@ -0,0 +1,113 @@
+use crate::{
+    output::{
+        Output,
+        OutputId,
+    },
+    transaction::{
+        metadata::TxMetadata,
+        TxId,
+    },
+    values::Values,
+    PolicyId,
+};
+use pallas_addresses::Address;
+
+/// Record of a transaction issued against the [`TestLedgerClient`](super::TestLedgerClient)
+#[derive(Clone, Debug, PartialEq)]
+pub struct IssuedTx<Datum> {
+    pub(crate) tx_id: TxId,
+    pub(crate) inputs: Vec<Output<Datum>>,
+    pub(crate) outputs: Vec<Output<Datum>>,
+    pub(crate) minted: Values,
+    pub(crate) burned: Values,
+    pub(crate) valid_range: (Option<i64>, Option<i64>),
+    pub(crate) signers: Vec<Address>,
+    pub(crate) collateral: Option<Output<Datum>>,
+    pub(crate) metadata: TxMetadata,
+}
+
+impl<Datum> IssuedTx<Datum> {
+    /// Getter for the id of the transaction
+    pub fn tx_id(&self) -> &TxId {
+        &self.tx_id
+    }
+
+    /// Getter for all the outputs consumed by the transaction
+    pub fn inputs(&self) -> &[Output<Datum>] {
+        &self.inputs
+    }
+
+    /// Getter for all the outputs created by the transaction
+    pub fn outputs(&self) -> &[Output<Datum>] {
+        &self.outputs
+    }
+
+    /// Getter for the values minted by the transaction
+    pub fn minted(&self) -> &Values {
+        &self.minted
+    }
+
+    /// Getter for the values burned by the transaction
+    pub fn burned(&self) -> &Values {
+        &self.burned
+    }
+
+    /// Getter for the validity range of the transaction in seconds
+    pub fn valid_range(&self) -> (Option<i64>, Option<i64>) {
+        self.valid_range
+    }
+
+    /// Getter for the addresses that signed the transaction
+    pub fn signers(&self) -> &[Address] {
+        &self.signers
+    }
+
+    /// Getter for the collateral put up by the transaction, if any was required
+    pub fn collateral(&self) -> Option<&Output<Datum>> {
+        self.collateral.as_ref()
+    }
+
+    /// Getter for the metadata attached to the transaction
+    pub fn metadata(&self) -> &TxMetadata {
+        &self.metadata
+    }
+
+    /// Total amount of `policy` sent to `address` by the transaction
+    pub fn amount_paid_to(&self, address: &Address, policy: &PolicyId) -> u64 {
+        self.outputs
+            .iter()
+            .filter(|output| &output.owner() == address)
+            .filter_map(|output| output.values().get(policy))
+            .sum()
+    }
+
+    /// Panics unless the transaction minted exactly `amount` of the given asset
+    pub fn assert_minted(&self, policy_id: &str, asset_name: Option<&[u8]>, amount: u64) {
+        let policy = PolicyId::native_token(policy_id, &asset_name.map(<[u8]>::to_vec));
+        let minted = self.minted.get(&policy).unwrap_or_default();
+        assert_eq!(
+            minted, amount,
+            "Expected tx {:?} to mint {amount} of {policy:?}, but it minted {minted}",
+            self.tx_id
+        );
+    }
+
+    /// Panics unless the transaction consumed the output with the given id
+    pub fn assert_spent(&self, output_id: &OutputId) {
+        assert!(
+            self.inputs.iter().any(|input| input.id() == output_id),
+            "Expected tx {:?} to spend output {output_id:?}",
+            self.tx_id
+        );
+    }
+
+    /// Panics unless the transaction sent exactly `amount` of `policy` to `address`
+    pub fn assert_paid(&self, address: &Address, policy: &PolicyId, amount: u64) {
+        let paid = self.amount_paid_to(address, policy);
+        assert_eq!(
+            paid, amount,
+            "Expected tx {:?} to pay {amount} of {policy:?} to {address:?}, but it paid {paid}",
+            self.tx_id
+        );
+    }
+}
@@ -15,6 +15,10 @@ pub use pallas_addresses::{
     Network,
 };
 
+/// Helpers for building and taking apart Shelley addresses
+pub mod address;
+/// Parsers and derive for building command line interfaces for smart contracts
+pub mod cli;
 pub mod error;
 
 /// Ledger client module
@@ -25,14 +29,28 @@ pub mod logic;
 pub mod output;
 /// `PolicyId` type module
 pub mod policy_id;
+/// Protocol parameters module
+pub mod protocol_params;
+/// Serving smart contracts over HTTP/JSON-RPC
+#[cfg(feature = "rpc")]
+pub mod rpc;
 /// On-chain script module
 pub mod scripts;
+/// Conversions between POSIX time and slots
+pub mod slot_config;
 /// Smart contract module
 pub mod smart_contract;
 /// Transaction module
 pub mod transaction;
 
+mod runtime;
+
 /// Types and helpers for working with the Trireme CLI
+#[cfg(feature = "trireme")]
+pub mod trireme_ledger_client;
+/// CML ledger client, without the native Trireme environments
+#[cfg(not(feature = "trireme"))]
+#[path = "trireme_ledger_client/cml_only.rs"]
 pub mod trireme_ledger_client;
 /// Values module
 pub mod values;
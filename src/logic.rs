@@ -1,10 +1,14 @@
 use crate::{
     ledger_client::LedgerClient,
+    transaction::TxId,
     TxActions,
 };
 
 use async_trait::async_trait;
-use error::SCLogicResult;
+use error::{
+    SCLogicError,
+    SCLogicResult,
+};
 use std::{
     fmt::Debug,
     hash::Hash,
@@ -12,6 +16,12 @@ use std::{
 
 #[allow(missing_docs)]
 pub mod error;
+/// Property-based harness for testing [`SCLogic`] against the test ledger
+pub mod fuzz;
+/// Moving outputs to a new validator when a contract's datum layout changes
+pub mod migration;
+/// State machines compiled down to [`SCLogic`]
+pub mod state_machine;
 
 /// Interface defining the logic of a smart contract
 #[async_trait]
@@ -98,10 +108,38 @@ pub trait SCLogic: Send + Sync {
     type Redeemers: Clone + PartialEq + Eq + Hash + Send + Sync;
 
     /// Method for handling specific endpoint
+    ///
+    /// Logic whose endpoints need the state of its instance, e.g. its configuration,
+    /// overrides [`SCLogic::handle_instance_endpoint`] instead and can leave this out. It
+    /// then fails wherever no instance is available.
     async fn handle_endpoint<Record: LedgerClient<Self::Datums, Self::Redeemers>>(
+        _endpoint: Self::Endpoints,
+        _ledger_client: &Record,
+    ) -> SCLogicResult<TxActions<Self::Datums, Self::Redeemers>> {
+        Err(SCLogicError::Endpoint(
+            format!(
+                "{} only handles endpoints on an instance",
+                std::any::type_name::<Self>()
+            )
+            .into(),
+        ))
+    }
+
+    /// Handle `endpoint` with this instance of the logic, which is what
+    /// [`SmartContract`](crate::smart_contract::SmartContract) calls
+    ///
+    /// Defaults to [`SCLogic::handle_endpoint`]. For logic implementing
+    /// [`SCLogicWithConfig`], override it to call
+    /// [`SCLogicWithConfig::handle_endpoint_with_config`] with [`SCLogicWithConfig::config`].
+    async fn handle_instance_endpoint<
+        Record: LedgerClient<Self::Datums, Self::Redeemers>,
+    >(
+        &self,
         endpoint: Self::Endpoints,
         ledger_client: &Record,
-    ) -> SCLogicResult<TxActions<Self::Datums, Self::Redeemers>>;
+    ) -> SCLogicResult<TxActions<Self::Datums, Self::Redeemers>> {
+        Self::handle_endpoint(endpoint, ledger_client).await
+    }
 
     /// Method for querying specific data, by lookup
     async fn lookup<Record: LedgerClient<Self::Datums, Self::Redeemers>>(
@@ -109,3 +147,102 @@ pub trait SCLogic: Send + Sync {
         ledger_client: &Record,
     ) -> SCLogicResult<Self::LookupResponses>;
 }
+
+/// Extension of [`SCLogic`] for endpoints that return domain data along with their actions
+///
+/// Lets callers learn what their own transaction did, e.g. the policy id of a minted
+/// token, without re-querying the chain. Use with
+/// [`SmartContract::hit_endpoint_with_output`](crate::smart_contract::SmartContract::hit_endpoint_with_output).
+#[async_trait]
+pub trait SCLogicWithOutput: SCLogic {
+    /// Domain data returned from handling an endpoint
+    type EndpointOutput: Send + Sync;
+
+    /// Like [`SCLogic::handle_endpoint`], also returning the domain data for the endpoint
+    ///
+    /// Logic that needs its instance overrides
+    /// [`SCLogicWithOutput::handle_instance_endpoint_with_output`] instead and can leave
+    /// this out.
+    async fn handle_endpoint_with_output<
+        Record: LedgerClient<Self::Datums, Self::Redeemers>,
+    >(
+        _endpoint: Self::Endpoints,
+        _ledger_client: &Record,
+    ) -> SCLogicResult<(
+        TxActions<Self::Datums, Self::Redeemers>,
+        Self::EndpointOutput,
+    )> {
+        Err(SCLogicError::Endpoint(
+            format!(
+                "{} only handles endpoints on an instance",
+                std::any::type_name::<Self>()
+            )
+            .into(),
+        ))
+    }
+
+    /// Like [`SCLogic::handle_instance_endpoint`], also returning the domain data for the
+    /// endpoint
+    ///
+    /// Defaults to [`SCLogicWithOutput::handle_endpoint_with_output`].
+    async fn handle_instance_endpoint_with_output<
+        Record: LedgerClient<Self::Datums, Self::Redeemers>,
+    >(
+        &self,
+        endpoint: Self::Endpoints,
+        ledger_client: &Record,
+    ) -> SCLogicResult<(
+        TxActions<Self::Datums, Self::Redeemers>,
+        Self::EndpointOutput,
+    )> {
+        Self::handle_endpoint_with_output(endpoint, ledger_client).await
+    }
+}
+
+/// Extension of [`SCLogic`] for endpoints that take several transactions, submitted one after
+/// the other, e.g. deploying a reference script and then initializing state that uses it
+///
+/// Use with
+/// [`SmartContract::hit_endpoint_in_steps`](crate::smart_contract::SmartContract::hit_endpoint_in_steps).
+/// Each step is handled once the transactions of the earlier steps are confirmed, so it can
+/// spend or reference the outputs they created.
+#[async_trait]
+pub trait SCLogicWithSteps: SCLogic {
+    /// Actions for step `step`, counting from 0, of `endpoint`, or `None` once all steps are
+    /// done
+    ///
+    /// `previous` holds the ids of the transactions of the earlier steps, in order.
+    async fn handle_endpoint_step<Record: LedgerClient<Self::Datums, Self::Redeemers>>(
+        endpoint: &Self::Endpoints,
+        step: usize,
+        previous: &[TxId],
+        ledger_client: &Record,
+    ) -> SCLogicResult<Option<TxActions<Self::Datums, Self::Redeemers>>>;
+}
+
+/// Extension of [`SCLogic`] for contracts whose endpoints depend on the configuration of the
+/// instance they're hit on, e.g. the network, script parameters or an admin's key hash
+///
+/// The configuration is held by the logic value given to
+/// [`SmartContract::new`](crate::smart_contract::SmartContract::new), so the same logic can
+/// be deployed with different parameters instead of hardcoding them in the endpoints.
+/// Override [`SCLogic::handle_instance_endpoint`] to call
+/// [`SCLogicWithConfig::handle_endpoint_with_config`], so every way of hitting the contract
+/// uses the configuration, and there's no need for [`SCLogic::handle_endpoint`].
+#[async_trait]
+pub trait SCLogicWithConfig: SCLogic {
+    /// Typed configuration of a contract instance
+    type Config: Send + Sync;
+
+    /// Getter for the configuration of this instance
+    fn config(&self) -> &Self::Config;
+
+    /// Like [`SCLogic::handle_endpoint`], for the instance configured with `config`
+    async fn handle_endpoint_with_config<
+        Record: LedgerClient<Self::Datums, Self::Redeemers>,
+    >(
+        endpoint: Self::Endpoints,
+        config: &Self::Config,
+        ledger_client: &Record,
+    ) -> SCLogicResult<TxActions<Self::Datums, Self::Redeemers>>;
+}
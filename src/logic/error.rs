@@ -1,4 +1,5 @@
 use crate::{
+    error::ErrorClass,
     ledger_client::LedgerClientError,
     scripts::ScriptError,
 };
@@ -8,19 +9,47 @@ use thiserror::Error;
 #[derive(Debug, Error)]
 pub enum SCLogicError {
     #[error("Error handling endpoint: {0:?}")]
-    Endpoint(Box<dyn error::Error + Send + Sync>),
+    Endpoint(#[source] Box<dyn error::Error + Send + Sync>),
     #[error("Error doing lookup: {0:?}")]
-    Lookup(Box<dyn error::Error + Send + Sync>),
+    Lookup(#[source] Box<dyn error::Error + Send + Sync>),
     #[error("Error from Validator Script: {0:?}")]
-    ValidatorScript(ScriptError),
+    ValidatorScript(#[source] ScriptError),
     #[error("Error from Policy Script: {0:?}")]
-    PolicyScript(ScriptError),
+    PolicyScript(#[source] ScriptError),
     #[error("From LedgerClient: {0:?}")]
     LedgerClient(#[from] LedgerClientError),
     #[error("Error from Script: {0:?}")]
     ScriptError(#[from] ScriptError),
 }
 
+impl SCLogicError {
+    /// Stable code identifying the failure, that of the underlying error for wrapped
+    /// ledger client and script errors
+    pub fn code(&self) -> &'static str {
+        match self {
+            SCLogicError::Endpoint(_) => "logic.endpoint",
+            SCLogicError::Lookup(_) => "logic.lookup",
+            SCLogicError::ValidatorScript(error)
+            | SCLogicError::PolicyScript(error)
+            | SCLogicError::ScriptError(error) => error.code(),
+            SCLogicError::LedgerClient(error) => error.code(),
+        }
+    }
+
+    /// Broad class of the failure
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            SCLogicError::Endpoint(_) | SCLogicError::Lookup(_) => {
+                ErrorClass::Application
+            }
+            SCLogicError::ValidatorScript(error)
+            | SCLogicError::PolicyScript(error)
+            | SCLogicError::ScriptError(error) => error.class(),
+            SCLogicError::LedgerClient(error) => error.class(),
+        }
+    }
+}
+
 pub type SCLogicResult<T> = crate::error::Result<T, SCLogicError>;
 
 pub fn as_endpoint_err<E: error::Error + Send + Sync + 'static>(
@@ -0,0 +1,422 @@
+use crate::{
+    ledger_client::{
+        test_ledger_client::{
+            in_memory_storage::InMemoryStorage,
+            TestLedgerClient,
+            TestLedgerClientBuilder,
+        },
+        LedgerClient,
+    },
+    logic::SCLogic,
+    output::Output,
+    scripts::plutus_validator::plutus_data::PlutusData,
+    PolicyId,
+};
+use pallas_addresses::Address;
+use rand::{
+    rngs::StdRng,
+    Rng,
+    SeedableRng,
+};
+use std::fmt::{
+    Debug,
+    Display,
+    Formatter,
+};
+
+type Generator<Logic> = Box<
+    dyn Fn(
+            &mut StdRng,
+            &LedgerState<<Logic as SCLogic>::Datums>,
+        ) -> Option<<Logic as SCLogic>::Endpoints>
+        + Send
+        + Sync,
+>;
+
+type Invariant<Logic> = Box<dyn Fn(&FuzzStep<Logic>) -> bool + Send + Sync>;
+
+/// Snapshot of all the outputs on the test ledger at a point in time
+#[derive(Clone, Debug)]
+pub struct LedgerState<Datum> {
+    outputs: Vec<(Address, Output<Datum>)>,
+}
+
+impl<Datum> LedgerState<Datum> {
+    /// All outputs on the ledger, with their owner
+    pub fn outputs(&self) -> &[(Address, Output<Datum>)] {
+        &self.outputs
+    }
+
+    /// All outputs owned by `address`
+    pub fn outputs_at(&self, address: &Address) -> Vec<&Output<Datum>> {
+        self.outputs
+            .iter()
+            .filter(|(owner, _)| owner == address)
+            .map(|(_, output)| output)
+            .collect()
+    }
+
+    /// Total amount of `policy` owned by `address`
+    pub fn balance_at(&self, address: &Address, policy: &PolicyId) -> u64 {
+        self.outputs_at(address)
+            .iter()
+            .filter_map(|output| output.values().get(policy))
+            .sum()
+    }
+}
+
+/// A single endpoint call made by the [`LogicFuzzer`], handed to each invariant
+pub struct FuzzStep<'a, Logic: SCLogic> {
+    /// The endpoint that was hit
+    pub endpoint: &'a Logic::Endpoints,
+    /// Whether the endpoint produced a transaction that was successfully issued
+    pub succeeded: bool,
+    /// Ledger state before the endpoint was hit
+    pub before: &'a LedgerState<Logic::Datums>,
+    /// Ledger state after the endpoint was hit
+    pub after: &'a LedgerState<Logic::Datums>,
+}
+
+/// Minimal sequence of endpoints found to break an invariant
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzFailure {
+    /// Seed of the run that failed, also used to seed its ledger. Use with
+    /// [`LogicFuzzer::with_seed`] to reproduce
+    pub seed: u64,
+    /// Name of the invariant that was broken
+    pub invariant: String,
+    /// The endpoints, in order, that lead to the invariant being broken
+    pub endpoints: Vec<String>,
+}
+
+impl Display for FuzzFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Invariant {:?} broken (seed: {}) after endpoints:",
+            self.invariant, self.seed
+        )?;
+        for (i, endpoint) in self.endpoints.iter().enumerate() {
+            writeln!(f, "  {i}: {endpoint}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Property-based harness for [`SCLogic`] implementations
+///
+/// Hits random sequences of endpoints against a fresh [`TestLedgerClient`] and checks the
+/// given invariants after every step. Failing sequences are shrunk to a minimal reproduction.
+/// Requires `Endpoints` to be `Clone` so each step can be handed to the invariants after it's hit.
+///
+/// ```ignore
+///     LogicFuzzer::new(CheckingAccountLogic, ledger_builder, |rng, _state| {
+///         Some(CheckingAccountEndpoints::InitAccount {
+///             starting_lovelace: rng.gen_range(2_000_000..10_000_000),
+///         })
+///     })
+///     .with_invariant("signer never gains ada", |step| {
+///         step.after.balance_at(&me, &PolicyId::Lovelace)
+///             <= step.before.balance_at(&me, &PolicyId::Lovelace)
+///     })
+///     .assert_holds()
+///     .await;
+/// ```
+pub struct LogicFuzzer<Logic: SCLogic> {
+    logic: Logic,
+    ledger: TestLedgerClientBuilder<Logic::Datums, Logic::Redeemers>,
+    generator: Generator<Logic>,
+    invariants: Vec<(String, Invariant<Logic>)>,
+    runs: usize,
+    max_steps: usize,
+    seed: u64,
+}
+
+impl<Logic> LogicFuzzer<Logic>
+where
+    Logic: SCLogic,
+    Logic::Endpoints: Clone + Debug,
+    Logic::Datums: Into<PlutusData>,
+    Logic::Redeemers: Debug,
+{
+    /// Constructor for the [`LogicFuzzer`]. `generator` produces the next endpoint to hit given
+    /// the current ledger state, or `None` to skip the step.
+    pub fn new<G>(
+        logic: Logic,
+        ledger: TestLedgerClientBuilder<Logic::Datums, Logic::Redeemers>,
+        generator: G,
+    ) -> Self
+    where
+        G: Fn(&mut StdRng, &LedgerState<Logic::Datums>) -> Option<Logic::Endpoints>
+            + Send
+            + Sync
+            + 'static,
+    {
+        LogicFuzzer {
+            logic,
+            ledger,
+            generator: Box::new(generator),
+            invariants: Vec::new(),
+            runs: 100,
+            max_steps: 10,
+            seed: rand::thread_rng().gen(),
+        }
+    }
+
+    /// Add an invariant that must hold after every step
+    pub fn with_invariant<I>(mut self, name: &str, invariant: I) -> Self
+    where
+        I: Fn(&FuzzStep<Logic>) -> bool + Send + Sync + 'static,
+    {
+        self.invariants
+            .push((name.to_string(), Box::new(invariant)));
+        self
+    }
+
+    /// Specify the number of random sequences to run. Defaults to 100.
+    pub fn with_runs(mut self, runs: usize) -> Self {
+        self.runs = runs;
+        self
+    }
+
+    /// Specify the maximum number of endpoints hit per sequence. Defaults to 10.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Specify the seed of the first run, for reproducing failures. Random by default.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Run all sequences, returning the shrunk failure if any invariant was broken
+    pub async fn run(&self) -> Result<(), FuzzFailure> {
+        for run in 0..self.runs {
+            let seed = self.seed.wrapping_add(run as u64);
+            let steps: Vec<usize> = (0..self.max_steps).collect();
+            if let Some((failing_step, _)) = self.execute(seed, &steps).await {
+                return Err(self.shrink(seed, failing_step).await);
+            }
+        }
+        Ok(())
+    }
+
+    /// Run all sequences, panicking with the shrunk failure if any invariant was broken
+    pub async fn assert_holds(&self) {
+        if let Err(failure) = self.run().await {
+            panic!("{failure}")
+        }
+    }
+
+    async fn shrink(&self, seed: u64, failing_step: usize) -> FuzzFailure {
+        let mut steps: Vec<usize> = (0..=failing_step).collect();
+        let mut invariant = self
+            .execute(seed, &steps)
+            .await
+            .map(|(_, invariant)| invariant)
+            .unwrap_or_default();
+        let mut i = 0;
+        while i < steps.len() {
+            let mut candidate = steps.clone();
+            candidate.remove(i);
+            if let Some((last, name)) = self.execute(seed, &candidate).await {
+                candidate.retain(|step| *step <= last);
+                steps = candidate;
+                invariant = name;
+            } else {
+                i += 1;
+            }
+        }
+        let endpoints = self.endpoints_for(seed, &steps).await;
+        FuzzFailure {
+            seed,
+            invariant,
+            endpoints,
+        }
+    }
+
+    /// Replays the given steps, returning the first step that broke an invariant
+    async fn execute(&self, seed: u64, steps: &[usize]) -> Option<(usize, String)> {
+        let ledger = self.ledger.build_in_memory().with_seed(seed);
+        for step in steps {
+            let before = ledger_state(&ledger);
+            let mut rng = step_rng(seed, *step);
+            let Some(endpoint) = (self.generator)(&mut rng, &before) else {
+                continue;
+            };
+            let succeeded = hit_endpoint(&self.logic, &ledger, endpoint.clone()).await;
+            let after = ledger_state(&ledger);
+            let fuzz_step = FuzzStep {
+                endpoint: &endpoint,
+                succeeded,
+                before: &before,
+                after: &after,
+            };
+            for (name, invariant) in self.invariants.iter() {
+                if !invariant(&fuzz_step) {
+                    return Some((*step, name.clone()));
+                }
+            }
+        }
+        None
+    }
+
+    async fn endpoints_for(&self, seed: u64, steps: &[usize]) -> Vec<String> {
+        let ledger = self.ledger.build_in_memory().with_seed(seed);
+        let mut endpoints = Vec::new();
+        for step in steps {
+            let state = ledger_state(&ledger);
+            let mut rng = step_rng(seed, *step);
+            if let Some(endpoint) = (self.generator)(&mut rng, &state) {
+                endpoints.push(format!("{endpoint:?}"));
+                hit_endpoint(&self.logic, &ledger, endpoint).await;
+            }
+        }
+        endpoints
+    }
+}
+
+fn step_rng(seed: u64, step: usize) -> StdRng {
+    StdRng::seed_from_u64(seed.wrapping_mul(1_000_003).wrapping_add(step as u64))
+}
+
+fn ledger_state<Datum, Redeemer>(
+    ledger: &TestLedgerClient<Datum, Redeemer, InMemoryStorage<Datum>>,
+) -> LedgerState<Datum>
+where
+    Datum: Clone + Send + Sync + PartialEq,
+{
+    LedgerState {
+        outputs: ledger.ledger_outputs(),
+    }
+}
+
+async fn hit_endpoint<Logic>(
+    logic: &Logic,
+    ledger: &TestLedgerClient<
+        Logic::Datums,
+        Logic::Redeemers,
+        InMemoryStorage<Logic::Datums>,
+    >,
+    endpoint: Logic::Endpoints,
+) -> bool
+where
+    Logic: SCLogic,
+    Logic::Datums: Into<PlutusData>,
+    Logic::Redeemers: Debug,
+{
+    let Ok(actions) = logic.handle_instance_endpoint(endpoint, ledger).await else {
+        return false;
+    };
+    let Ok(tx) = actions.to_unbuilt_tx() else {
+        return false;
+    };
+    ledger.issue(tx).await.is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::{
+        logic::error::SCLogicResult,
+        transaction::TxActions,
+    };
+    use async_trait::async_trait;
+
+    const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+    const BOB: &str = "addr_test1qzvrhz9v6lwcr26a52y8mmk2nzq37lky68359keq3dgth4lkzpnnjv8vf98m20lhqdzl60mcftq7r2lc4xtcsv0w6xjstag0ua";
+
+    // Only handles endpoints on an instance, so the fuzzer has to use the one it's given
+    struct TransferLogic {
+        recipient: Address,
+    }
+
+    #[derive(Clone, Debug)]
+    enum TransferEndpoints {
+        Transfer { amount: u64 },
+    }
+
+    #[async_trait]
+    impl SCLogic for TransferLogic {
+        type Endpoints = TransferEndpoints;
+        type Lookups = ();
+        type LookupResponses = ();
+        type Datums = ();
+        type Redeemers = ();
+
+        async fn handle_instance_endpoint<Record: LedgerClient<(), ()>>(
+            &self,
+            endpoint: Self::Endpoints,
+            _ledger_client: &Record,
+        ) -> SCLogicResult<TxActions<(), ()>> {
+            let TransferEndpoints::Transfer { amount } = endpoint;
+            Ok(TxActions::v2().with_transfer(
+                amount,
+                self.recipient.clone(),
+                PolicyId::Lovelace,
+            ))
+        }
+
+        async fn lookup<Record: LedgerClient<(), ()>>(
+            _query: Self::Lookups,
+            _ledger_client: &Record,
+        ) -> SCLogicResult<Self::LookupResponses> {
+            Ok(())
+        }
+    }
+
+    fn fuzzer() -> LogicFuzzer<TransferLogic> {
+        let alice = Address::from_bech32(ALICE).unwrap();
+        let ledger = TestLedgerClientBuilder::new(&alice)
+            .start_output(&alice)
+            .with_value(PolicyId::Lovelace, 100_000_000)
+            .finish_output();
+        let logic = TransferLogic {
+            recipient: Address::from_bech32(BOB).unwrap(),
+        };
+        LogicFuzzer::new(logic, ledger, |rng, _state| {
+            let amount = if rng.gen_bool(0.2) { 4_000_000 } else { 1 };
+            Some(TransferEndpoints::Transfer { amount })
+        })
+        .with_runs(5)
+        .with_seed(42)
+    }
+
+    #[tokio::test]
+    async fn run__passes_when_invariants_hold() {
+        let alice = Address::from_bech32(ALICE).unwrap();
+        let bob = Address::from_bech32(BOB).unwrap();
+        let total = move |state: &LedgerState<()>| {
+            state.balance_at(&alice, &PolicyId::Lovelace)
+                + state.balance_at(&bob, &PolicyId::Lovelace)
+        };
+        fuzzer()
+            .with_invariant("ada is conserved", move |step| {
+                total(step.before) == total(step.after)
+            })
+            .assert_holds()
+            .await;
+    }
+
+    #[tokio::test]
+    async fn run__shrinks_failing_sequence() {
+        let bob = Address::from_bech32(BOB).unwrap();
+        let failure = fuzzer()
+            .with_max_steps(20)
+            .with_invariant("bob holds at most 6 ada", move |step| {
+                step.after.balance_at(&bob, &PolicyId::Lovelace) <= 6_000_000
+            })
+            .run()
+            .await
+            .unwrap_err();
+
+        assert_eq!(failure.invariant, "bob holds at most 6 ada");
+        let expected =
+            vec![format!("{:?}", TransferEndpoints::Transfer { amount: 4_000_000 }); 2];
+        assert_eq!(failure.endpoints, expected);
+    }
+}
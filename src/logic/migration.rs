@@ -0,0 +1,263 @@
+use crate::{
+    ledger_client::LedgerClient,
+    logic::error::{
+        SCLogicError,
+        SCLogicResult,
+    },
+    output::Output,
+    scripts::{
+        SharedValidator,
+        Validator,
+    },
+    TxActions,
+};
+use pallas_addresses::Address;
+use std::sync::Arc;
+
+/// Number of outputs a [`DatumMigration`] moves per transaction by default
+pub const DEFAULT_MIGRATION_BATCH_SIZE: usize = 5;
+
+type Conversion<Datum> = Box<dyn Fn(Datum) -> Option<Datum> + Send + Sync>;
+
+/// Moves the outputs locked at a validator to a new validator, converting their datums to
+/// a new shape on the way
+///
+/// Used when a deployed contract's datum layout changes. The migration is split into
+/// batches of transactions, each sweeping a few of the old outputs and re-creating them,
+/// with the same value, at the new address.
+pub struct DatumMigration<Datum, Redeemer> {
+    old_validator: Arc<dyn Validator<Datum, Redeemer>>,
+    new_address: Address,
+    redeemer: Redeemer,
+    convert: Conversion<Datum>,
+    batch_size: usize,
+}
+
+impl<Datum, Redeemer> DatumMigration<Datum, Redeemer>
+where
+    Datum: Clone + Send + Sync + 'static,
+    Redeemer: Clone + Send + Sync + 'static,
+{
+    /// Constructor for a [`DatumMigration`], spending the outputs of `old_validator` with
+    /// `redeemer` and locking them at `new_address` with their datums passed through
+    /// `convert`
+    ///
+    /// Outputs whose datum isn't an `Old` are left where they are, and reported by
+    /// [`DatumMigration::pending`].
+    pub fn new<Old, New>(
+        old_validator: impl Validator<Datum, Redeemer> + 'static,
+        new_address: Address,
+        redeemer: Redeemer,
+        convert: impl Fn(Old) -> New + Send + Sync + 'static,
+    ) -> Self
+    where
+        Old: TryFrom<Datum>,
+        New: Into<Datum>,
+    {
+        let convert = move |datum: Datum| {
+            let old = Old::try_from(datum).ok()?;
+            Some(convert(old).into())
+        };
+        DatumMigration {
+            old_validator: Arc::new(old_validator),
+            new_address,
+            redeemer,
+            convert: Box::new(convert),
+            batch_size: DEFAULT_MIGRATION_BATCH_SIZE,
+        }
+    }
+
+    /// Move at most `batch_size` outputs per transaction
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Outputs still locked at the old validator, split by whether they can be migrated
+    pub async fn pending<LC: LedgerClient<Datum, Redeemer>>(
+        &self,
+        ledger_client: &LC,
+    ) -> SCLogicResult<PendingMigration<Datum>> {
+        let network = ledger_client.network().await?;
+        let old_address = self
+            .old_validator
+            .address(network)
+            .map_err(SCLogicError::ValidatorScript)?;
+        let mut pending = PendingMigration {
+            migratable: Vec::new(),
+            unconvertible: Vec::new(),
+        };
+        for output in ledger_client.all_outputs_at_address(&old_address).await? {
+            match output.typed_datum().and_then(|datum| (self.convert)(datum)) {
+                Some(new_datum) => pending.migratable.push((output, new_datum)),
+                None => pending.unconvertible.push(output),
+            }
+        }
+        Ok(pending)
+    }
+
+    /// The transaction migrating the next batch of pending outputs, or `None` once they
+    /// are all migrated
+    ///
+    /// Batches pay their fees from the same wallet, so only ask for the next one once the
+    /// previous is confirmed, e.g. as the steps of an
+    /// [`SCLogicWithSteps`](crate::logic::SCLogicWithSteps) endpoint. Outputs that can't be
+    /// migrated are left out, see [`PendingMigration::unconvertible`].
+    pub async fn next_batch<LC: LedgerClient<Datum, Redeemer>>(
+        &self,
+        ledger_client: &LC,
+    ) -> SCLogicResult<Option<TxActions<Datum, Redeemer>>> {
+        let pending = self.pending(ledger_client).await?;
+        if !pending.unconvertible.is_empty() {
+            tracing::warn!(
+                count = pending.unconvertible.len(),
+                "Outputs at the old validator can't be migrated"
+            );
+        }
+        if pending.migratable.is_empty() {
+            return Ok(None)
+        }
+        let batch = pending.migratable.into_iter().take(self.batch_size).fold(
+            TxActions::v2(),
+            |actions, (output, new_datum)| {
+                let validator = Box::new(SharedValidator(self.old_validator.clone()));
+                let values = output.values().clone();
+                actions
+                    .with_script_redeem(output, self.redeemer.clone(), validator)
+                    .with_script_init(new_datum, values, self.new_address.clone())
+            },
+        );
+        Ok(Some(batch))
+    }
+}
+
+/// Outputs still locked at the old validator of a [`DatumMigration`]
+pub struct PendingMigration<Datum> {
+    migratable: Vec<(Output<Datum>, Datum)>,
+    unconvertible: Vec<Output<Datum>>,
+}
+
+impl<Datum> PendingMigration<Datum> {
+    /// Outputs that will be migrated, with their converted datums
+    pub fn migratable(&self) -> &[(Output<Datum>, Datum)] {
+        &self.migratable
+    }
+
+    /// Outputs without a datum that converts, which the migration leaves where they are
+    pub fn unconvertible(&self) -> &[Output<Datum>] {
+        &self.unconvertible
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::{
+        ledger_client::test_ledger_client::TestLedgerClientBuilder,
+        scripts::{
+            context::TxContext,
+            ExecutionCost,
+            ScriptResult,
+        },
+        PolicyId,
+    };
+    use pallas_addresses::Network;
+
+    const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+    const OLD_SCRIPT: &str =
+        "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu";
+    const NEW_SCRIPT: &str =
+        "addr_test1wpe9mt7mkjmkkuqjmevzafm6mle9t0spprr9335q0e6p92cur7fvl";
+
+    struct OldValidator;
+
+    impl Validator<i64, ()> for OldValidator {
+        fn execute(
+            &self,
+            _datum: i64,
+            _redeemer: (),
+            _ctx: TxContext,
+        ) -> ScriptResult<ExecutionCost> {
+            Ok(ExecutionCost::default())
+        }
+
+        fn address(&self, _network: Network) -> ScriptResult<Address> {
+            Ok(Address::from_bech32(OLD_SCRIPT).unwrap())
+        }
+
+        fn script_hex(&self) -> ScriptResult<String> {
+            todo!()
+        }
+    }
+
+    struct Positive(i64);
+
+    impl TryFrom<i64> for Positive {
+        type Error = ();
+
+        fn try_from(value: i64) -> Result<Self, Self::Error> {
+            if value > 0 {
+                Ok(Positive(value))
+            } else {
+                Err(())
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn next_batch__moves_outputs_to_the_new_validator_with_converted_datums() {
+        // given
+        let alice = Address::from_bech32(ALICE).unwrap();
+        let old_script = Address::from_bech32(OLD_SCRIPT).unwrap();
+        let new_script = Address::from_bech32(NEW_SCRIPT).unwrap();
+        let mut builder = TestLedgerClientBuilder::new(&alice)
+            .start_output(&alice)
+            .with_value(PolicyId::Lovelace, 10_000_000)
+            .finish_output();
+        for datum in [1, 2, -1, 3] {
+            builder = builder
+                .start_output(&old_script)
+                .with_datum(datum)
+                .with_value(PolicyId::Lovelace, 2_000_000)
+                .finish_output();
+        }
+        let ledger_client = builder.build_in_memory();
+        let migration =
+            DatumMigration::new(OldValidator, new_script.clone(), (), |old: Positive| {
+                old.0 * 10
+            })
+            .with_batch_size(2);
+
+        // when
+        let mut batch_count = 0;
+        while let Some(batch) = migration.next_batch(&ledger_client).await.unwrap() {
+            ledger_client
+                .issue(batch.to_unbuilt_tx().unwrap())
+                .await
+                .unwrap();
+            batch_count += 1;
+        }
+
+        // then
+        assert_eq!(batch_count, 2);
+        let pending = migration.pending(&ledger_client).await.unwrap();
+        assert!(pending.migratable().is_empty());
+        let unconvertible: Vec<_> = pending
+            .unconvertible()
+            .iter()
+            .filter_map(|output| output.typed_datum())
+            .collect();
+        assert_eq!(unconvertible, vec![-1]);
+        let mut new_datums: Vec<_> = ledger_client
+            .all_outputs_at_address(&new_script)
+            .await
+            .unwrap()
+            .iter()
+            .filter_map(|output| output.typed_datum())
+            .collect();
+        new_datums.sort();
+        assert_eq!(new_datums, vec![10, 20, 30]);
+    }
+}
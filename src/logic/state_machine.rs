@@ -0,0 +1,534 @@
+use crate::{
+    ledger_client::LedgerClient,
+    logic::{
+        error::{
+            as_endpoint_err,
+            as_lookup_err,
+            SCLogicError,
+            SCLogicResult,
+        },
+        SCLogic,
+    },
+    output::Output,
+    scripts::{
+        context::TxContext,
+        plutus_minting_policy::PlutusMintingPolicy,
+        stdlib::one_shot::one_shot_nft_policy_for,
+        ExecutionCost,
+        MintingPolicy,
+        ScriptResult,
+        Validator,
+    },
+    values::Values,
+    PolicyId,
+    TxActions,
+};
+use async_trait::async_trait;
+use pallas_addresses::Address;
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    marker::PhantomData,
+};
+use thiserror::Error;
+
+/// Asset name of the thread tokens minted when instances start
+pub const THREAD_TOKEN_ASSET_NAME: &str = "StateMachineThread";
+
+/// Thread-token style contract, described as states and the transitions between them
+///
+/// Each instance of the machine is a single output at the [`StateMachine::validator`]
+/// address, holding the instance's thread token and its current state as datum. Wrap the
+/// machine in a [`StateMachineLogic`] to use it as [`SCLogic`].
+///
+/// Thread tokens are minted with a one-shot policy when the instance starts, so no other
+/// output can ever hold one. The validator should check that each transition passes the
+/// token on to the next state.
+pub trait StateMachine: Send + Sync + 'static {
+    /// States of the machine, stored as the datum of the instance output
+    type State: Clone + Eq + Debug + Send + Sync;
+    /// Transitions of the machine, used as the redeemer when spending the instance output
+    type Transition: Clone + PartialEq + Eq + Hash + Send + Sync;
+
+    /// Script locking the instance outputs
+    fn validator() -> ScriptResult<Box<dyn Validator<Self::State, Self::Transition>>>;
+
+    /// Whether `transition` can be applied to `state`
+    fn guard(state: &Self::State, transition: &Self::Transition) -> bool;
+
+    /// State after applying `transition` to `state`
+    ///
+    /// `None` finishes the instance, and its value goes back to the signer.
+    fn transform(
+        state: &Self::State,
+        transition: &Self::Transition,
+    ) -> Option<Self::State>;
+
+    /// Redeemer for minting the thread token when an instance starts
+    ///
+    /// The one-shot policy ignores it, but only accepts redeemers encoded as `Void`, i.e.
+    /// `Constr 0 []`.
+    fn thread_token_redeemer() -> Self::Transition;
+}
+
+/// Thread token of the instance started by spending `seed`
+pub fn thread_token_for<Datum>(seed: &Output<Datum>) -> ScriptResult<PolicyId> {
+    let policy_id = one_shot_nft_policy_for(seed)?.id()?;
+    let asset_name = THREAD_TOKEN_ASSET_NAME.as_bytes().to_vec();
+    Ok(PolicyId::native_token(&policy_id, &Some(asset_name)))
+}
+
+/// Endpoints of a [`StateMachineLogic`]
+#[derive(Clone, Debug)]
+pub enum StateMachineEndpoints<State, Transition> {
+    /// Start a new instance in `state`, locking `values` and a newly minted thread token
+    ///
+    /// The thread token is only mintable by the transaction spending `seed`, see
+    /// [`thread_token_for`].
+    Start {
+        /// Initial state
+        state: State,
+        /// Value locked in the instance, besides the thread token
+        values: Values,
+        /// Wallet output spent to make the thread token unique
+        seed: Output<State>,
+    },
+    /// Apply `transition` to the instance holding `thread_token`
+    Apply {
+        /// Thread token of the instance, from [`thread_token_for`]
+        thread_token: PolicyId,
+        /// Transition to apply
+        transition: Transition,
+    },
+}
+
+/// Lookups of a [`StateMachineLogic`]
+#[derive(Clone, Debug)]
+pub enum StateMachineLookups {
+    /// Up to `count` running instances
+    ///
+    /// Outputs are only told apart from instances by holding a single token named
+    /// [`THREAD_TOKEN_ASSET_NAME`]. Look instances up by their thread token to be sure
+    /// they're genuine.
+    Instances {
+        /// Maximum number of instances
+        count: usize,
+    },
+    /// The instance holding `thread_token`
+    Instance {
+        /// Thread token of the instance
+        thread_token: PolicyId,
+    },
+}
+
+/// Responses to [`StateMachineLookups`]
+#[derive(Clone, Debug)]
+pub enum StateMachineLookupResponses<State> {
+    /// Outputs of the running instances
+    Instances(Vec<Output<State>>),
+    /// Output of the instance, if it's running
+    Instance(Option<Output<State>>),
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum StateMachineError {
+    #[error("No running instance holds thread token: {0:?}")]
+    InstanceNotFound(PolicyId),
+    #[error("Instance output doesn't have a state datum")]
+    MissingState,
+    #[error("Transition isn't allowed from the current state")]
+    TransitionNotAllowed,
+    #[error("Not a state machine thread token: {0:?}")]
+    NotAThreadToken(PolicyId),
+}
+
+/// [`SCLogic`] for a [`StateMachine`]
+#[derive(Debug)]
+pub struct StateMachineLogic<Machine> {
+    _machine: PhantomData<Machine>,
+}
+
+impl<Machine> Default for StateMachineLogic<Machine> {
+    fn default() -> Self {
+        StateMachineLogic {
+            _machine: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<Machine: StateMachine> SCLogic for StateMachineLogic<Machine> {
+    type Endpoints = StateMachineEndpoints<Machine::State, Machine::Transition>;
+    type Lookups = StateMachineLookups;
+    type LookupResponses = StateMachineLookupResponses<Machine::State>;
+    type Datums = Machine::State;
+    type Redeemers = Machine::Transition;
+
+    async fn handle_endpoint<Record: LedgerClient<Self::Datums, Self::Redeemers>>(
+        endpoint: Self::Endpoints,
+        ledger_client: &Record,
+    ) -> SCLogicResult<TxActions<Self::Datums, Self::Redeemers>> {
+        match endpoint {
+            StateMachineEndpoints::Start {
+                state,
+                values,
+                seed,
+            } => impl_start::<Machine, _>(ledger_client, state, values, seed).await,
+            StateMachineEndpoints::Apply {
+                thread_token,
+                transition,
+            } => impl_apply::<Machine, _>(ledger_client, thread_token, transition).await,
+        }
+    }
+
+    async fn lookup<Record: LedgerClient<Self::Datums, Self::Redeemers>>(
+        query: Self::Lookups,
+        ledger_client: &Record,
+    ) -> SCLogicResult<Self::LookupResponses> {
+        let address = instance_address::<Machine, _>(ledger_client).await?;
+        match query {
+            StateMachineLookups::Instances { count } => {
+                let outputs = ledger_client
+                    .all_outputs_at_address(&address)
+                    .await
+                    .map_err(as_lookup_err)?
+                    .into_iter()
+                    .filter(holds_a_thread_token)
+                    .take(count)
+                    .collect();
+                Ok(StateMachineLookupResponses::Instances(outputs))
+            }
+            StateMachineLookups::Instance { thread_token } => {
+                check_thread_token(&thread_token).map_err(as_lookup_err)?;
+                let output = find_instance(ledger_client, &address, &thread_token)
+                    .await
+                    .map_err(as_lookup_err)?;
+                Ok(StateMachineLookupResponses::Instance(output))
+            }
+        }
+    }
+}
+
+// The one-shot policy expects a `Void` redeemer, so it runs with `()` whatever the
+// transitions are
+struct ThreadTokenPolicy(PlutusMintingPolicy<()>);
+
+impl<Transition: Send + Sync> MintingPolicy<Transition> for ThreadTokenPolicy {
+    fn execute(
+        &self,
+        _redeemer: Transition,
+        ctx: TxContext,
+    ) -> ScriptResult<ExecutionCost> {
+        self.0.execute((), ctx)
+    }
+
+    fn id(&self) -> ScriptResult<String> {
+        self.0.id()
+    }
+
+    fn script_hex(&self) -> ScriptResult<String> {
+        self.0.script_hex()
+    }
+}
+
+async fn impl_start<Machine: StateMachine, Record>(
+    ledger_client: &Record,
+    state: Machine::State,
+    mut values: Values,
+    seed: Output<Machine::State>,
+) -> SCLogicResult<TxActions<Machine::State, Machine::Transition>>
+where
+    Record: LedgerClient<Machine::State, Machine::Transition>,
+{
+    let address = instance_address::<Machine, _>(ledger_client).await?;
+    let policy = one_shot_nft_policy_for(&seed).map_err(SCLogicError::PolicyScript)?;
+    let thread_token = thread_token_for(&seed).map_err(SCLogicError::PolicyScript)?;
+    values.add_one_value(&thread_token, 1);
+    let actions = TxActions::v2()
+        .with_mint(
+            1,
+            thread_token.asset_name(),
+            Machine::thread_token_redeemer(),
+            Box::new(ThreadTokenPolicy(policy)),
+        )
+        .with_specific_input(seed)
+        .with_script_init(state, values, address);
+    Ok(actions)
+}
+
+async fn impl_apply<Machine: StateMachine, Record>(
+    ledger_client: &Record,
+    thread_token: PolicyId,
+    transition: Machine::Transition,
+) -> SCLogicResult<TxActions<Machine::State, Machine::Transition>>
+where
+    Record: LedgerClient<Machine::State, Machine::Transition>,
+{
+    check_thread_token(&thread_token).map_err(as_endpoint_err)?;
+    let address = instance_address::<Machine, _>(ledger_client).await?;
+    let output = find_instance(ledger_client, &address, &thread_token)
+        .await
+        .map_err(as_endpoint_err)?
+        .ok_or(StateMachineError::InstanceNotFound(thread_token))
+        .map_err(as_endpoint_err)?;
+    let state = output
+        .typed_datum()
+        .ok_or(StateMachineError::MissingState)
+        .map_err(as_endpoint_err)?;
+    if !Machine::guard(&state, &transition) {
+        return Err(as_endpoint_err(StateMachineError::TransitionNotAllowed))
+    }
+    let next_state = Machine::transform(&state, &transition);
+    let values = output.values().clone();
+    let validator = Machine::validator().map_err(SCLogicError::ValidatorScript)?;
+    let mut actions = TxActions::v2().with_script_redeem(output, transition, validator);
+    if let Some(next_state) = next_state {
+        actions = actions.with_script_init(next_state, values, address);
+    }
+    Ok(actions)
+}
+
+async fn instance_address<Machine: StateMachine, Record>(
+    ledger_client: &Record,
+) -> SCLogicResult<Address>
+where
+    Record: LedgerClient<Machine::State, Machine::Transition>,
+{
+    let network = ledger_client.network().await?;
+    let validator = Machine::validator().map_err(SCLogicError::ValidatorScript)?;
+    validator
+        .address(network)
+        .map_err(SCLogicError::ValidatorScript)
+}
+
+async fn find_instance<State, Transition, Record>(
+    ledger_client: &Record,
+    address: &Address,
+    thread_token: &PolicyId,
+) -> crate::ledger_client::LedgerClientResult<Option<Output<State>>>
+where
+    Record: LedgerClient<State, Transition>,
+{
+    let output = ledger_client
+        .all_outputs_at_address(address)
+        .await?
+        .into_iter()
+        .find(|output| output.values().get(thread_token) == Some(1));
+    Ok(output)
+}
+
+fn check_thread_token(thread_token: &PolicyId) -> Result<(), StateMachineError> {
+    let asset_name = thread_token.asset_name();
+    if asset_name.as_deref() != Some(THREAD_TOKEN_ASSET_NAME.as_bytes()) {
+        return Err(StateMachineError::NotAThreadToken(thread_token.clone()))
+    }
+    Ok(())
+}
+
+fn holds_a_thread_token<State>(output: &Output<State>) -> bool {
+    let mut thread_tokens = output
+        .values()
+        .as_iter()
+        .filter(|(policy, _)| check_thread_token(policy).is_ok())
+        .map(|(_, amount)| *amount);
+    matches!(
+        (thread_tokens.next(), thread_tokens.next()),
+        (Some(1), None)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::ledger_client::test_ledger_client::TestLedgerClientBuilder;
+    use pallas_addresses::Network;
+
+    const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+
+    struct Counter;
+
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    enum CounterTransition {
+        Increment,
+        Close,
+    }
+
+    struct CounterValidator;
+
+    impl Validator<i64, CounterTransition> for CounterValidator {
+        fn execute(
+            &self,
+            _datum: i64,
+            _redeemer: CounterTransition,
+            _ctx: TxContext,
+        ) -> ScriptResult<ExecutionCost> {
+            Ok(ExecutionCost::default())
+        }
+
+        fn address(&self, _network: Network) -> ScriptResult<Address> {
+            Ok(Address::from_bech32(
+                "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu",
+            )
+            .unwrap())
+        }
+
+        fn script_hex(&self) -> ScriptResult<String> {
+            todo!()
+        }
+    }
+
+    impl StateMachine for Counter {
+        type State = i64;
+        type Transition = CounterTransition;
+
+        fn validator() -> ScriptResult<Box<dyn Validator<i64, CounterTransition>>> {
+            Ok(Box::new(CounterValidator))
+        }
+
+        fn guard(state: &i64, transition: &CounterTransition) -> bool {
+            match transition {
+                CounterTransition::Increment => *state < 2,
+                CounterTransition::Close => true,
+            }
+        }
+
+        fn transform(state: &i64, transition: &CounterTransition) -> Option<i64> {
+            match transition {
+                CounterTransition::Increment => Some(state + 1),
+                CounterTransition::Close => None,
+            }
+        }
+
+        fn thread_token_redeemer() -> CounterTransition {
+            CounterTransition::Increment
+        }
+    }
+
+    type CounterLogic = StateMachineLogic<Counter>;
+
+    async fn apply<Record: LedgerClient<i64, CounterTransition>>(
+        ledger_client: &Record,
+        thread_token: &PolicyId,
+        transition: CounterTransition,
+    ) -> crate::error::Result<()> {
+        let endpoint = StateMachineEndpoints::Apply {
+            thread_token: thread_token.clone(),
+            transition,
+        };
+        let actions = CounterLogic::handle_endpoint(endpoint, ledger_client).await?;
+        ledger_client.issue(actions.to_unbuilt_tx()?).await?;
+        Ok(())
+    }
+
+    async fn instance_state<Record: LedgerClient<i64, CounterTransition>>(
+        ledger_client: &Record,
+        thread_token: &PolicyId,
+    ) -> Option<i64> {
+        let lookup = StateMachineLookups::Instance {
+            thread_token: thread_token.clone(),
+        };
+        match CounterLogic::lookup(lookup, ledger_client).await.unwrap() {
+            StateMachineLookupResponses::Instance(output) => {
+                output.and_then(|output| output.typed_datum())
+            }
+            StateMachineLookupResponses::Instances(_) => unreachable!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_endpoint__follows_guarded_transitions_until_finished() {
+        // given
+        let alice = Address::from_bech32(ALICE).unwrap();
+        let ledger_client = TestLedgerClientBuilder::new(&alice)
+            .start_output(&alice)
+            .with_value(PolicyId::Lovelace, 10_000_000)
+            .finish_output()
+            .build_in_memory();
+        let seed = ledger_client.all_outputs_at_address(&alice).await.unwrap()[0].clone();
+        let thread_token = thread_token_for(&seed).unwrap();
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, 2_000_000);
+        let start = StateMachineEndpoints::Start {
+            state: 0,
+            values,
+            seed,
+        };
+        let actions = CounterLogic::handle_endpoint(start, &ledger_client)
+            .await
+            .unwrap();
+        ledger_client
+            .issue(actions.to_unbuilt_tx().unwrap())
+            .await
+            .unwrap();
+
+        // when
+        apply(&ledger_client, &thread_token, CounterTransition::Increment)
+            .await
+            .unwrap();
+        apply(&ledger_client, &thread_token, CounterTransition::Increment)
+            .await
+            .unwrap();
+        let guarded =
+            apply(&ledger_client, &thread_token, CounterTransition::Increment).await;
+        let state_before_close = instance_state(&ledger_client, &thread_token).await;
+        apply(&ledger_client, &thread_token, CounterTransition::Close)
+            .await
+            .unwrap();
+
+        // then
+        assert!(matches!(
+            guarded,
+            Err(crate::error::Error::SCLogic(SCLogicError::Endpoint(_)))
+        ));
+        assert_eq!(state_before_close, Some(2));
+        assert_eq!(instance_state(&ledger_client, &thread_token).await, None);
+        let alice_tokens = ledger_client
+            .balance_at_address(&alice, &thread_token)
+            .await
+            .unwrap();
+        assert_eq!(alice_tokens, 1);
+    }
+
+    #[tokio::test]
+    async fn lookup__ignores_instances_without_a_thread_token() {
+        // given
+        let alice = Address::from_bech32(ALICE).unwrap();
+        let script = CounterValidator.address(Network::Testnet).unwrap();
+        let spoofed_token = PolicyId::native_token(
+            "bdaa99eb158414dea0a91d6c727e2268574b23efe6e08ab3b841abe8",
+            &None,
+        );
+        let ledger_client = TestLedgerClientBuilder::new(&alice)
+            .start_output(&script)
+            .with_datum(7)
+            .with_value(PolicyId::Lovelace, 2_000_000)
+            .with_value(spoofed_token.clone(), 1)
+            .finish_output()
+            .build_in_memory();
+
+        // when
+        let instances = CounterLogic::lookup(
+            StateMachineLookups::Instances { count: 10 },
+            &ledger_client,
+        )
+        .await
+        .unwrap();
+        let spoofed = CounterLogic::lookup(
+            StateMachineLookups::Instance {
+                thread_token: spoofed_token,
+            },
+            &ledger_client,
+        )
+        .await;
+
+        // then
+        assert!(matches!(
+            instances,
+            StateMachineLookupResponses::Instances(outputs) if outputs.is_empty()
+        ));
+        assert!(matches!(spoofed, Err(SCLogicError::Lookup(_))));
+    }
+}
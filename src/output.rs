@@ -1,11 +1,24 @@
-use crate::scripts::plutus_validator::plutus_data::PlutusData;
+use crate::{
+    protocol_params::ProtocolParameters,
+    scripts::plutus_validator::plutus_data::PlutusData,
+    transaction::TransactionVersion,
+};
 use pallas_addresses::Address;
 use serde::{
     Deserialize,
     Serialize,
 };
+use std::{
+    fmt,
+    num::ParseIntError,
+    str::FromStr,
+};
+use thiserror::Error;
 
-use crate::values::Values;
+use crate::values::{
+    cbor_bytes_size,
+    Values,
+};
 
 // TODO: Does this need to be separated? We might be able to just have one variant
 /// Representation of an UTxO that does not exist yet. This can be used inside the
@@ -27,9 +40,24 @@ pub enum UnbuiltOutput<Datum> {
         values: Values,
         /// Datum of the output
         datum: Datum,
+        /// How the output carries its datum
+        #[serde(default)]
+        datum_attachment: DatumAttachment,
     },
 }
 
+/// How a new script output carries its datum
+#[derive(Clone, Copy, PartialEq, Debug, Eq, Default, Deserialize, Serialize)]
+pub enum DatumAttachment {
+    /// Store only the datum hash on the output, and the datum in the witnesses
+    ///
+    /// The only option for Plutus V1 scripts, which can't read inline datums.
+    #[default]
+    Hash,
+    /// Store the datum on the output itself
+    Inline,
+}
+
 impl<Datum> UnbuiltOutput<Datum> {
     /// Constructor for wallet output
     pub fn new_wallet(owner: Address, values: Values) -> Self {
@@ -45,6 +73,21 @@ impl<Datum> UnbuiltOutput<Datum> {
             script_address: script_address.to_bech32().expect("Already validated"),
             values,
             datum,
+            datum_attachment: DatumAttachment::default(),
+        }
+    }
+
+    /// Constructor for validator output that stores `datum` inline
+    pub fn new_validator_with_inline_datum(
+        script_address: Address,
+        values: Values,
+        datum: Datum,
+    ) -> Self {
+        UnbuiltOutput::Validator {
+            script_address: script_address.to_bech32().expect("Already validated"),
+            values,
+            datum,
+            datum_attachment: DatumAttachment::Inline,
         }
     }
 
@@ -75,6 +118,49 @@ impl<Datum> UnbuiltOutput<Datum> {
             UnbuiltOutput::Validator { datum, .. } => Some(datum),
         }
     }
+
+    /// Getter for how the output carries its datum, if it has one
+    pub fn datum_attachment(&self) -> Option<DatumAttachment> {
+        match self {
+            UnbuiltOutput::Wallet { .. } => None,
+            UnbuiltOutput::Validator {
+                datum_attachment, ..
+            } => Some(*datum_attachment),
+        }
+    }
+}
+
+impl<Datum: Clone + Into<PlutusData>> UnbuiltOutput<Datum> {
+    /// Lovelace this output needs to meet the min-UTxO rule, counting its address and datum
+    ///
+    /// Any Lovelace already in its values is ignored.
+    pub fn min_ada_required(&self, protocol_params: &ProtocolParameters) -> u64 {
+        let datum_size = match (self.datum(), self.datum_attachment()) {
+            (Some(_), Some(DatumAttachment::Hash)) => Some(DATUM_HASH_FIELD_SIZE),
+            (Some(datum), _) => Some(inline_datum_field_size(&datum.clone().into())),
+            (None, _) => None,
+        };
+        self.values().min_ada_for_output(
+            protocol_params,
+            self.owner().to_vec().len() as u64,
+            &datum_size.into_iter().collect::<Vec<_>>(),
+        )
+    }
+}
+
+// Serialized size of a `[0, datum_hash]` datum option
+const DATUM_HASH_FIELD_SIZE: u64 = 1 + 1 + 2 + 32;
+
+// Serialized size of a `[1, #6.24(bytes .cbor data)]` datum option
+fn inline_datum_field_size(data: &PlutusData) -> u64 {
+    1 + 1 + 2 + cbor_bytes_size(data.bytes().len() as u64)
+}
+
+// Serialized size of a `#6.24(bytes .cbor [version, script])` script reference, where the
+// script hex is already the CBOR of the script's bytes
+fn script_ref_field_size(script: &ReferenceScript) -> u64 {
+    let script_size = script.script_hex().len() as u64 / 2;
+    2 + cbor_bytes_size(1 + 1 + script_size)
 }
 
 /// Representation of an on-chain datum
@@ -115,17 +201,153 @@ impl<Datum> From<DatumKind<Datum>> for Option<Datum> {
     }
 }
 
+/// How a datum is attached to an on-chain output
+///
+/// Outputs can carry their datum inline or only its hash. A hash is resolved when the datum
+/// itself was found, e.g. in the witnesses of the transaction that created the output.
+#[derive(Clone, PartialEq, Debug, Eq, Deserialize, Serialize)]
+pub enum OutputDatum<Datum> {
+    /// No datum
+    None,
+    /// Datum stored inline on the output
+    Inline(DatumKind<Datum>),
+    /// Datum hash on the output, with the datum it resolved to
+    ResolvedHash {
+        /// Hash of the datum
+        hash: Vec<u8>,
+        /// The datum matching the hash
+        datum: DatumKind<Datum>,
+    },
+    /// Datum hash on the output whose datum isn't known
+    UnresolvedHash(Vec<u8>),
+}
+
+impl<Datum> OutputDatum<Datum> {
+    /// The datum, if it is inline or a resolved hash
+    pub fn value(&self) -> Option<&DatumKind<Datum>> {
+        match self {
+            OutputDatum::Inline(datum) | OutputDatum::ResolvedHash { datum, .. } => {
+                Some(datum)
+            }
+            OutputDatum::None | OutputDatum::UnresolvedHash(_) => None,
+        }
+    }
+
+    /// The datum hash, if the datum is attached by hash
+    pub fn hash(&self) -> Option<&[u8]> {
+        match self {
+            OutputDatum::ResolvedHash { hash, .. }
+            | OutputDatum::UnresolvedHash(hash) => Some(hash),
+            OutputDatum::None | OutputDatum::Inline(_) => None,
+        }
+    }
+
+    /// Unwrap the datum if it is known and typed
+    pub fn unwrap_typed(self) -> Datum {
+        match self {
+            OutputDatum::Inline(datum) | OutputDatum::ResolvedHash { datum, .. } => {
+                datum.unwrap_typed()
+            }
+            _ => panic!("Expected Typed Datum"),
+        }
+    }
+
+    /// Unwrap the datum if it is known and untyped
+    pub fn unwrap_untyped(self) -> PlutusData {
+        match self {
+            OutputDatum::Inline(datum) | OutputDatum::ResolvedHash { datum, .. } => {
+                datum.unwrap_untyped()
+            }
+            _ => panic!("Expected Untyped Datum"),
+        }
+    }
+
+    fn map_value(&self, f: impl FnOnce(&DatumKind<Datum>) -> DatumKind<Datum>) -> Self
+    where
+        Datum: Clone,
+    {
+        match self {
+            OutputDatum::None => OutputDatum::None,
+            OutputDatum::Inline(datum) => OutputDatum::Inline(f(datum)),
+            OutputDatum::ResolvedHash { hash, datum } => OutputDatum::ResolvedHash {
+                hash: hash.clone(),
+                datum: f(datum),
+            },
+            OutputDatum::UnresolvedHash(hash) => {
+                OutputDatum::UnresolvedHash(hash.clone())
+            }
+        }
+    }
+}
+
+impl<Datum> From<OutputDatum<Datum>> for Option<Datum> {
+    fn from(value: OutputDatum<Datum>) -> Self {
+        match value {
+            OutputDatum::Inline(datum) | OutputDatum::ResolvedHash { datum, .. } => {
+                datum.into()
+            }
+            OutputDatum::None | OutputDatum::UnresolvedHash(_) => None,
+        }
+    }
+}
+
 /// Domain specific representation of an on-chain UTxO
 #[derive(Clone, PartialEq, Debug, Eq)]
 pub struct Output<Datum> {
     id: OutputId,
     owner: String,
     values: Values,
-    datum: DatumKind<Datum>,
+    datum: OutputDatum<Datum>,
+    reference_script: Option<ReferenceScript>,
 }
 
-/// Unique identifier for specific UTxO
+/// Script stored on an output so that other transactions can use it by reference
 #[derive(Clone, PartialEq, Debug, Eq, Deserialize, Serialize)]
+pub struct ReferenceScript {
+    hash: Vec<u8>,
+    script_hex: String,
+    #[serde(default = "plutus_v2")]
+    version: TransactionVersion,
+}
+
+fn plutus_v2() -> TransactionVersion {
+    TransactionVersion::V2
+}
+
+impl ReferenceScript {
+    /// Constructor for a Plutus V2 `ReferenceScript`
+    pub fn new(hash: Vec<u8>, script_hex: &str) -> Self {
+        ReferenceScript {
+            hash,
+            script_hex: script_hex.to_string(),
+            version: plutus_v2(),
+        }
+    }
+
+    /// Set the Plutus version of the script
+    pub fn with_version(mut self, version: TransactionVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Getter for the script's Plutus version
+    pub fn version(&self) -> &TransactionVersion {
+        &self.version
+    }
+
+    /// Getter for the script's hash
+    pub fn hash(&self) -> &[u8] {
+        &self.hash
+    }
+
+    /// Getter for the script's CBOR hex
+    pub fn script_hex(&self) -> &str {
+        &self.script_hex
+    }
+}
+
+/// Unique identifier for specific UTxO
+#[derive(Clone, PartialEq, Debug, Eq, Hash, Deserialize, Serialize)]
 pub struct OutputId {
     tx_hash: Vec<u8>,
     index: u64,
@@ -148,6 +370,36 @@ impl OutputId {
     }
 }
 
+/// Formats the id as `<tx hash hex>#<index>`
+impl fmt::Display for OutputId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#{}", hex::encode(&self.tx_hash), self.index)
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Error, PartialEq)]
+pub enum OutputIdParseError {
+    #[error("Expected `<tx hash>#<index>`, got {0:?}")]
+    MissingSeparator(String),
+    #[error("Invalid tx hash: {0}")]
+    TxHash(#[from] hex::FromHexError),
+    #[error("Invalid output index: {0}")]
+    Index(#[from] ParseIntError),
+}
+
+/// Parses ids written as `<tx hash hex>#<index>`, the same form they are displayed in
+impl FromStr for OutputId {
+    type Err = OutputIdParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (tx_hash, index) = s
+            .split_once('#')
+            .ok_or_else(|| OutputIdParseError::MissingSeparator(s.to_string()))?;
+        Ok(OutputId::new(hex::decode(tx_hash)?, index.parse()?))
+    }
+}
+
 impl<Datum> Output<Datum> {
     /// Constructor for wallet output
     pub fn new_wallet(
@@ -162,7 +414,8 @@ impl<Datum> Output<Datum> {
             id,
             owner: addr,
             values,
-            datum: DatumKind::None,
+            datum: OutputDatum::None,
+            reference_script: None,
         }
     }
 
@@ -180,7 +433,8 @@ impl<Datum> Output<Datum> {
             id,
             owner: addr,
             values,
-            datum: DatumKind::Typed(datum),
+            datum: OutputDatum::Inline(DatumKind::Typed(datum)),
+            reference_script: None,
         }
     }
 
@@ -198,10 +452,34 @@ impl<Datum> Output<Datum> {
             id,
             owner: addr,
             values,
-            datum: DatumKind::UnTyped(datum),
+            datum: OutputDatum::Inline(DatumKind::UnTyped(datum)),
+            reference_script: None,
         }
     }
 
+    /// Mark the Output's datum as attached by hash, rather than inline. If the Output has no
+    /// datum, the hash is recorded as unresolved
+    pub fn with_datum_hash(mut self, datum_hash: Vec<u8>) -> Self {
+        self.datum = match self.datum {
+            OutputDatum::Inline(datum) | OutputDatum::ResolvedHash { datum, .. } => {
+                OutputDatum::ResolvedHash {
+                    hash: datum_hash,
+                    datum,
+                }
+            }
+            OutputDatum::None | OutputDatum::UnresolvedHash(_) => {
+                OutputDatum::UnresolvedHash(datum_hash)
+            }
+        };
+        self
+    }
+
+    /// Attach a reference script to the Output
+    pub fn with_reference_script(mut self, reference_script: ReferenceScript) -> Self {
+        self.reference_script = Some(reference_script);
+        self
+    }
+
     /// Getter for Output's id
     pub fn id(&self) -> &OutputId {
         &self.id
@@ -218,16 +496,44 @@ impl<Datum> Output<Datum> {
     }
 
     /// Getter for Output's datum
-    pub fn datum(&self) -> &DatumKind<Datum> {
+    pub fn datum(&self) -> &OutputDatum<Datum> {
         &self.datum
     }
+
+    /// Getter for Output's datum hash. Returns `None` if the datum is inline or non-existent
+    pub fn datum_hash(&self) -> Option<&[u8]> {
+        self.datum.hash()
+    }
+
+    /// Getter for Output's reference script, if any
+    pub fn reference_script(&self) -> Option<&ReferenceScript> {
+        self.reference_script.as_ref()
+    }
+}
+
+/// One line summary: id, owner, values, and whether it carries a datum or reference script
+impl<Datum> fmt::Display for Output<Datum> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}: {}", self.id, self.owner, self.values)?;
+        match self.datum {
+            OutputDatum::None => {}
+            OutputDatum::UnresolvedHash(_) => write!(f, ", with unresolved datum hash")?,
+            OutputDatum::Inline(_) | OutputDatum::ResolvedHash { .. } => {
+                write!(f, ", with datum")?
+            }
+        }
+        if self.reference_script.is_some() {
+            write!(f, ", with reference script")?;
+        }
+        Ok(())
+    }
 }
 
 impl<Datum: Clone> Output<Datum> {
     /// Getter for Output's datum, if it is typed. Returns `None` if datum is untyped or non-existent
     pub fn typed_datum(&self) -> Option<Datum> {
-        match &self.datum {
-            DatumKind::Typed(datum) => Some(datum.to_owned()),
+        match self.datum.value() {
+            Some(DatumKind::Typed(datum)) => Some(datum.to_owned()),
             _ => None,
         }
     }
@@ -237,35 +543,60 @@ impl<Datum: Clone + Into<PlutusData>> Output<Datum> {
     /// Converts `Output` to have an untyped datum, if it is typed. Returns the same `Output` if
     /// datum is untyped or non-existent
     pub fn with_untyped_datum(&self) -> Output<Datum> {
-        let new_datum = match &self.datum {
+        let new_datum = self.datum.map_value(|datum| match datum {
             DatumKind::Typed(datum) => DatumKind::UnTyped(datum.to_owned().into()),
             DatumKind::UnTyped(data) => DatumKind::UnTyped(data.clone()),
             DatumKind::None => DatumKind::None,
-        };
+        });
 
         Output {
             id: self.id.clone(),
             owner: self.owner.clone(),
             values: self.values.clone(),
             datum: new_datum,
+            reference_script: self.reference_script.clone(),
         }
     }
 
     /// Getter for `Output`'s datum as `PlutusData`, if it is typed. Returns `None` if datum is non-existent
     pub fn datum_plutus_data(&self) -> Option<PlutusData> {
-        match &self.datum {
-            DatumKind::Typed(datum) => Some(datum.to_owned().into()),
-            DatumKind::UnTyped(data) => Some(data.to_owned()),
-            DatumKind::None => None,
+        match self.datum.value() {
+            Some(DatumKind::Typed(datum)) => Some(datum.to_owned().into()),
+            Some(DatumKind::UnTyped(data)) => Some(data.to_owned()),
+            Some(DatumKind::None) | None => None,
         }
     }
+
+    /// Lovelace this output needs to meet the min-UTxO rule, counting its address, datum and
+    /// reference script
+    ///
+    /// Any Lovelace already in its values is ignored.
+    pub fn min_ada_required(&self, protocol_params: &ProtocolParameters) -> u64 {
+        let datum_size = match &self.datum {
+            OutputDatum::None => None,
+            OutputDatum::Inline(_) => self
+                .datum_plutus_data()
+                .map(|data| inline_datum_field_size(&data)),
+            OutputDatum::ResolvedHash { .. } | OutputDatum::UnresolvedHash(_) => {
+                Some(DATUM_HASH_FIELD_SIZE)
+            }
+        };
+        let script_size = self.reference_script().map(script_ref_field_size);
+        let extra_field_sizes: Vec<_> =
+            datum_size.into_iter().chain(script_size).collect();
+        self.values().min_ada_for_output(
+            protocol_params,
+            self.owner().to_vec().len() as u64,
+            &extra_field_sizes,
+        )
+    }
 }
 
 impl<Datum: Clone + TryFrom<PlutusData>> Output<Datum> {
     /// Converts `Output` to have a typed datum, if it is untyped and can be converted. Returns the
     /// same `Output` if datum is typed, it can't convert, or non-existent
     pub fn with_typed_datum_if_possible(&self) -> Output<Datum> {
-        let new_datum = match &self.datum {
+        let new_datum = self.datum.map_value(|datum| match datum {
             DatumKind::Typed(datum) => DatumKind::Typed(datum.clone()),
             DatumKind::UnTyped(data) => {
                 if let Ok(datum) = Datum::try_from(data.clone()) {
@@ -275,13 +606,128 @@ impl<Datum: Clone + TryFrom<PlutusData>> Output<Datum> {
                 }
             }
             DatumKind::None => DatumKind::None,
-        };
+        });
 
         Output {
             id: self.id.clone(),
             owner: self.owner.clone(),
             values: self.values.clone(),
             datum: new_datum,
+            reference_script: self.reference_script.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::PolicyId;
+
+    #[test]
+    fn min_ada_required__counts_the_datum_and_reference_script() {
+        // given
+        let owner = Address::from_bech32("addr_test1qpuy2q9xel76qxdw8r29skldzc876cdgg9cugfg7mwh0zvpg3292mxuf3kq7nysjumlxjrlsfn9tp85r0l54l29x3qcs7nvyfm").unwrap();
+        let params = ProtocolParameters::default();
+        let datum = PlutusData::BoundedBytes(vec![0; 100]);
+        let per_byte = params.coins_per_utxo_byte();
+
+        // when
+        let wallet =
+            UnbuiltOutput::<PlutusData>::new_wallet(owner.clone(), Values::default());
+        let hashed =
+            UnbuiltOutput::new_validator(owner.clone(), Values::default(), datum.clone());
+        let inline = UnbuiltOutput::new_validator_with_inline_datum(
+            owner.clone(),
+            Values::default(),
+            datum.clone(),
+        );
+        let with_script = Output::new_validator(
+            vec![0; 32],
+            0,
+            owner.clone(),
+            Values::default(),
+            datum,
+        )
+        .with_reference_script(ReferenceScript::new(vec![1; 28], &"00".repeat(200)));
+
+        // then
+        let wallet_min = wallet.min_ada_required(&params);
+        assert_eq!(wallet_min, Values::default().min_ada_required(&params));
+        assert!(hashed.min_ada_required(&params) >= wallet_min + 36 * per_byte);
+        assert!(inline.min_ada_required(&params) >= wallet_min + 100 * per_byte);
+        assert!(with_script.min_ada_required(&params) >= wallet_min + 200 * per_byte);
+    }
+
+    #[test]
+    fn display__summarizes_output() {
+        // given
+        let owner = Address::from_bech32("addr_test1qpuy2q9xel76qxdw8r29skldzc876cdgg9cugfg7mwh0zvpg3292mxuf3kq7nysjumlxjrlsfn9tp85r0l54l29x3qcs7nvyfm").unwrap();
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, 1_000_000);
+        let output = Output::new_validator(vec![0xab; 32], 3, owner.clone(), values, ());
+
+        // when
+        let displayed = output.to_string();
+
+        // then
+        assert_eq!(
+            displayed,
+            format!(
+                "{}#3 at {}: 1 ADA, with datum",
+                "ab".repeat(32),
+                owner.to_bech32().unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn with_datum_hash__distinguishes_resolved_and_unresolved_hashes() {
+        // given
+        let owner = Address::from_bech32("addr_test1qpuy2q9xel76qxdw8r29skldzc876cdgg9cugfg7mwh0zvpg3292mxuf3kq7nysjumlxjrlsfn9tp85r0l54l29x3qcs7nvyfm").unwrap();
+        let hash = vec![1; 32];
+
+        // when
+        let resolved =
+            Output::new_validator(vec![0; 32], 0, owner.clone(), Values::default(), 7)
+                .with_datum_hash(hash.clone());
+        let unresolved =
+            Output::<i64>::new_wallet(vec![0; 32], 1, owner, Values::default())
+                .with_datum_hash(hash.clone());
+
+        // then
+        assert_eq!(
+            resolved.datum(),
+            &OutputDatum::ResolvedHash {
+                hash: hash.clone(),
+                datum: DatumKind::Typed(7),
+            }
+        );
+        assert_eq!(
+            unresolved.datum(),
+            &OutputDatum::UnresolvedHash(hash.clone())
+        );
+        assert_eq!(unresolved.datum_hash(), Some(hash.as_slice()));
+        assert_eq!(unresolved.typed_datum(), None);
+    }
+
+    #[test]
+    fn output_id__round_trips_through_string() {
+        let id = OutputId::new(vec![0xab; 32], 7);
+
+        let parsed: OutputId = id.to_string().parse().unwrap();
+
+        assert_eq!(parsed, id);
+    }
+
+    #[test]
+    fn output_id__rejects_missing_index() {
+        let res = "abcd".parse::<OutputId>();
+
+        assert_eq!(
+            res,
+            Err(OutputIdParseError::MissingSeparator("abcd".to_string()))
+        );
+    }
+}
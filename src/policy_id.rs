@@ -3,13 +3,26 @@ use serde::{
     Serialize,
 };
 
+/// CIP-67 label of CIP-68 reference NFTs, which hold the metadata datum
+pub const REFERENCE_NFT_LABEL: u16 = 100;
+/// CIP-67 label of CIP-68 user NFTs
+pub const NFT_LABEL: u16 = 222;
+/// CIP-67 label of CIP-68 user fungible tokens
+pub const FT_LABEL: u16 = 333;
+/// CIP-67 label of CIP-68 rich fungible tokens
+pub const RFT_LABEL: u16 = 444;
+
 /// Token identity.
+#[serde_with::serde_as]
 #[derive(PartialEq, Eq, Hash, Clone, Debug, Serialize, Deserialize)]
 pub enum PolicyId {
     /// ADA as denominated in Lovelace (1 ADA = 1_000_000 Lovelace)
     Lovelace,
-    /// Native token with policy id and optional asset name
-    NativeToken(String, Option<String>),
+    /// Native token with hex policy id and optional raw asset name bytes
+    NativeToken(
+        String,
+        #[serde_as(as = "Option<serde_with::hex::Hex>")] Option<Vec<u8>>,
+    ),
 }
 
 impl PolicyId {
@@ -19,7 +32,7 @@ impl PolicyId {
     }
 
     /// Constructor for native token policy id
-    pub fn native_token(id: &str, asset: &Option<String>) -> PolicyId {
+    pub fn native_token(id: &str, asset: &Option<Vec<u8>>) -> PolicyId {
         PolicyId::NativeToken(id.to_string(), asset.to_owned())
     }
 
@@ -31,11 +44,151 @@ impl PolicyId {
         }
     }
 
-    /// Getter for asset name
-    pub fn asset_name(&self) -> Option<String> {
+    /// Getter for the raw asset name bytes
+    pub fn asset_name(&self) -> Option<Vec<u8>> {
         match self {
             PolicyId::Lovelace => None,
             PolicyId::NativeToken(_, asset_name) => asset_name.to_owned(),
         }
     }
+
+    /// Asset name as hex, which works for any asset name
+    pub fn asset_name_hex(&self) -> Option<String> {
+        self.asset_name().map(hex::encode)
+    }
+
+    /// Asset name as text, if it is valid UTF-8
+    pub fn asset_name_utf8(&self) -> Option<String> {
+        self.asset_name()
+            .and_then(|name| String::from_utf8(name).ok())
+    }
+
+    /// Constructor for native token policy id with a CIP-67 `label` prefixed to `name`
+    pub fn labeled_token(id: &str, label: u16, name: &[u8]) -> PolicyId {
+        let mut asset_name = cip67_prefix(label).to_vec();
+        asset_name.extend_from_slice(name);
+        PolicyId::NativeToken(id.to_string(), Some(asset_name))
+    }
+
+    /// CIP-67 label of the asset name, if it starts with a valid label prefix
+    pub fn asset_label(&self) -> Option<u16> {
+        let asset_name = self.asset_name()?;
+        parse_cip67_prefix(&asset_name).map(|(label, _)| label)
+    }
+
+    /// Asset name after its CIP-67 label prefix, if it has one
+    pub fn asset_name_without_label(&self) -> Option<Vec<u8>> {
+        let asset_name = self.asset_name()?;
+        parse_cip67_prefix(&asset_name).map(|(_, name)| name.to_vec())
+    }
+
+    /// Same policy and name with its CIP-67 label replaced by `label`, e.g. to find the
+    /// reference NFT of a user token. Unlabeled names get `label` prefixed.
+    pub fn with_asset_label(&self, label: u16) -> PolicyId {
+        match self {
+            PolicyId::Lovelace => PolicyId::Lovelace,
+            PolicyId::NativeToken(id, asset_name) => {
+                let asset_name = asset_name.clone().unwrap_or_default();
+                let name = parse_cip67_prefix(&asset_name)
+                    .map(|(_, name)| name)
+                    .unwrap_or(&asset_name);
+                PolicyId::labeled_token(id, label, name)
+            }
+        }
+    }
+}
+
+/// CIP-67 asset name prefix for `label`: the label and its CRC-8 between zero nibbles
+pub fn cip67_prefix(label: u16) -> [u8; 4] {
+    let crc = crc8(&label.to_be_bytes());
+    [
+        (label >> 12) as u8,
+        (label >> 4) as u8,
+        (((label & 0x0f) as u8) << 4) | (crc >> 4),
+        (crc & 0x0f) << 4,
+    ]
+}
+
+/// Split `asset_name` into its CIP-67 label and the rest of the name
+///
+/// Returns `None` if the name doesn't start with a well-formed prefix with a matching
+/// checksum.
+pub fn parse_cip67_prefix(asset_name: &[u8]) -> Option<(u16, &[u8])> {
+    if asset_name.len() < 4 {
+        return None
+    }
+    let (prefix, name) = asset_name.split_at(4);
+    let label = (u16::from(prefix[0]) << 12)
+        | (u16::from(prefix[1]) << 4)
+        | (u16::from(prefix[2]) >> 4);
+    if prefix == cip67_prefix(label) {
+        Some((label, name))
+    } else {
+        None
+    }
+}
+
+// CRC-8 with polynomial 0x07, as required by CIP-67
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |crc, byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn asset_name__keeps_non_utf8_bytes() {
+        // given
+        let policy = PolicyId::native_token("abcd", &Some(vec![0xff, 0x00, 0x41]));
+
+        // when
+        let json = serde_json::to_string(&policy).unwrap();
+        let round_tripped: PolicyId = serde_json::from_str(&json).unwrap();
+
+        // then
+        assert_eq!(round_tripped, policy);
+        assert_eq!(policy.asset_name_hex(), Some("ff0041".to_string()));
+        assert_eq!(policy.asset_name_utf8(), None);
+    }
+
+    #[test]
+    fn cip67_prefix__matches_spec_examples() {
+        assert_eq!(hex::encode(cip67_prefix(REFERENCE_NFT_LABEL)), "000643b0");
+        assert_eq!(hex::encode(cip67_prefix(NFT_LABEL)), "000de140");
+        assert_eq!(hex::encode(cip67_prefix(FT_LABEL)), "0014df10");
+        assert_eq!(hex::encode(cip67_prefix(RFT_LABEL)), "001bc280");
+    }
+
+    #[test]
+    fn with_asset_label__swaps_user_token_for_reference_token() {
+        // given
+        let user_token = PolicyId::labeled_token("abcd", NFT_LABEL, b"Ship");
+
+        // when
+        let reference = user_token.with_asset_label(REFERENCE_NFT_LABEL);
+
+        // then
+        assert_eq!(user_token.asset_label(), Some(NFT_LABEL));
+        assert_eq!(reference.asset_label(), Some(REFERENCE_NFT_LABEL));
+        assert_eq!(reference.asset_name_without_label(), Some(b"Ship".to_vec()));
+    }
+
+    #[test]
+    fn asset_label__ignores_bad_checksums() {
+        let policy =
+            PolicyId::native_token("abcd", &Some(hex::decode("000de150").unwrap()));
+
+        assert_eq!(policy.asset_label(), None);
+    }
 }
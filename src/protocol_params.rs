@@ -0,0 +1,152 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Lovelace per byte of serialized output, as set on mainnet and the testnets since Babbage
+pub const DEFAULT_COINS_PER_UTXO_BYTE: u64 = 4310;
+
+/// The protocol parameters that offchain logic needs to size outputs and build transactions
+///
+/// Defaults to the values on mainnet and the testnets since Vasil. Prices are kept as
+/// `(numerator, denominator)` pairs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ProtocolParameters {
+    coins_per_utxo_byte: u64,
+    min_fee_a: u64,
+    min_fee_b: u64,
+    key_deposit: u64,
+    pool_deposit: u64,
+    max_tx_size: u32,
+    max_value_size: u32,
+    price_mem: (u64, u64),
+    price_step: (u64, u64),
+    collateral_percentage: u32,
+    max_collateral_inputs: u32,
+}
+
+impl Default for ProtocolParameters {
+    fn default() -> Self {
+        ProtocolParameters {
+            coins_per_utxo_byte: DEFAULT_COINS_PER_UTXO_BYTE,
+            min_fee_a: 44,
+            min_fee_b: 155381,
+            key_deposit: 2000000,
+            pool_deposit: 500000000,
+            max_tx_size: 16384,
+            max_value_size: 5000,
+            price_mem: (577, 10000),
+            price_step: (721, 10000000),
+            collateral_percentage: 150,
+            max_collateral_inputs: 3,
+        }
+    }
+}
+
+impl ProtocolParameters {
+    /// Set the Lovelace charged per byte of serialized output
+    pub fn with_coins_per_utxo_byte(mut self, coins_per_utxo_byte: u64) -> Self {
+        self.coins_per_utxo_byte = coins_per_utxo_byte;
+        self
+    }
+
+    /// Set the linear fee of `min_fee_a` Lovelace per byte plus `min_fee_b` Lovelace
+    pub fn with_min_fee(mut self, min_fee_a: u64, min_fee_b: u64) -> Self {
+        self.min_fee_a = min_fee_a;
+        self.min_fee_b = min_fee_b;
+        self
+    }
+
+    /// Set the Lovelace deposited to register stake keys and pools
+    pub fn with_deposits(mut self, key_deposit: u64, pool_deposit: u64) -> Self {
+        self.key_deposit = key_deposit;
+        self.pool_deposit = pool_deposit;
+        self
+    }
+
+    /// Set the largest transaction and output value, in bytes
+    pub fn with_max_sizes(mut self, max_tx_size: u32, max_value_size: u32) -> Self {
+        self.max_tx_size = max_tx_size;
+        self.max_value_size = max_value_size;
+        self
+    }
+
+    /// Set the Lovelace charged per unit of script memory and per script step
+    pub fn with_ex_unit_prices(
+        mut self,
+        price_mem: (u64, u64),
+        price_step: (u64, u64),
+    ) -> Self {
+        self.price_mem = price_mem;
+        self.price_step = price_step;
+        self
+    }
+
+    /// Set the collateral required, as a percentage of the fee, and how many inputs
+    /// may provide it
+    pub fn with_collateral(
+        mut self,
+        collateral_percentage: u32,
+        max_collateral_inputs: u32,
+    ) -> Self {
+        self.collateral_percentage = collateral_percentage;
+        self.max_collateral_inputs = max_collateral_inputs;
+        self
+    }
+
+    /// Getter for the Lovelace charged per byte of serialized output
+    pub fn coins_per_utxo_byte(&self) -> u64 {
+        self.coins_per_utxo_byte
+    }
+
+    /// Getter for the Lovelace charged per byte of transaction
+    pub fn min_fee_a(&self) -> u64 {
+        self.min_fee_a
+    }
+
+    /// Getter for the Lovelace charged per transaction
+    pub fn min_fee_b(&self) -> u64 {
+        self.min_fee_b
+    }
+
+    /// Getter for the Lovelace deposited to register a stake key
+    pub fn key_deposit(&self) -> u64 {
+        self.key_deposit
+    }
+
+    /// Getter for the Lovelace deposited to register a pool
+    pub fn pool_deposit(&self) -> u64 {
+        self.pool_deposit
+    }
+
+    /// Getter for the largest transaction, in bytes
+    pub fn max_tx_size(&self) -> u32 {
+        self.max_tx_size
+    }
+
+    /// Getter for the largest output value, in bytes
+    pub fn max_value_size(&self) -> u32 {
+        self.max_value_size
+    }
+
+    /// Getter for the Lovelace charged per unit of script memory
+    pub fn price_mem(&self) -> (u64, u64) {
+        self.price_mem
+    }
+
+    /// Getter for the Lovelace charged per script step
+    pub fn price_step(&self) -> (u64, u64) {
+        self.price_step
+    }
+
+    /// Getter for the collateral required, as a percentage of the fee
+    pub fn collateral_percentage(&self) -> u32 {
+        self.collateral_percentage
+    }
+
+    /// Getter for how many inputs may provide collateral
+    pub fn max_collateral_inputs(&self) -> u32 {
+        self.max_collateral_inputs
+    }
+}
@@ -0,0 +1,440 @@
+use crate::{
+    error::{
+        Error,
+        Result,
+    },
+    smart_contract::SmartContractTrait,
+};
+use axum::{
+    extract::State,
+    http::{
+        header::AUTHORIZATION,
+        HeaderMap,
+        StatusCode,
+    },
+    routing::post,
+    Json,
+    Router,
+};
+use secrecy::{
+    ExposeSecret,
+    Secret,
+};
+use serde::{
+    de::DeserializeOwned,
+    Deserialize,
+    Serialize,
+};
+use serde_json::{
+    json,
+    Value,
+};
+use std::{
+    net::SocketAddr,
+    sync::Arc,
+};
+use subtle::ConstantTimeEq;
+
+const JSONRPC_VERSION: &str = "2.0";
+
+/// Method hitting the endpoint given as the params, returning `{"tx_id": "<hex>"}`
+pub const HIT_ENDPOINT_METHOD: &str = "hit_endpoint";
+/// Method running the lookup given as the params, returning the lookup response
+pub const LOOKUP_METHOD: &str = "lookup";
+
+/// The request isn't a valid JSON-RPC 2.0 request
+pub const INVALID_REQUEST: i64 = -32600;
+/// The method isn't [`HIT_ENDPOINT_METHOD`] or [`LOOKUP_METHOD`]
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// The params can't be deserialized into an endpoint or lookup
+pub const INVALID_PARAMS: i64 = -32602;
+/// The lookup response can't be serialized
+pub const INTERNAL_ERROR: i64 = -32603;
+/// Hitting the endpoint or running the lookup failed
+pub const CONTRACT_ERROR: i64 = -32000;
+
+/// JSON-RPC 2.0 request
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RpcRequest {
+    /// Protocol version, must be `"2.0"`
+    pub jsonrpc: String,
+    /// Request id, echoed in the response
+    #[serde(default)]
+    pub id: Value,
+    /// [`HIT_ENDPOINT_METHOD`] or [`LOOKUP_METHOD`]
+    pub method: String,
+    /// The serialized endpoint or lookup
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// JSON-RPC 2.0 response
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct RpcResponse {
+    /// Protocol version, always `"2.0"`
+    pub jsonrpc: String,
+    /// Id of the request being answered
+    pub id: Value,
+    /// Result of a successful request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// Error of a failed request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+/// JSON-RPC 2.0 error object
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RpcError {
+    /// Error code, e.g. [`CONTRACT_ERROR`]
+    pub code: i64,
+    /// Description of the error
+    pub message: String,
+}
+
+impl RpcResponse {
+    fn result(id: Value, result: Value) -> Self {
+        RpcResponse {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    fn error(id: Value, code: i64, message: impl ToString) -> Self {
+        RpcResponse {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            result: None,
+            error: Some(RpcError {
+                code,
+                message: message.to_string(),
+            }),
+        }
+    }
+}
+
+/// Answer a single JSON-RPC `request` with `contract`
+///
+/// Endpoints and lookups are deserialized from the params with serde, so their types
+/// decide the JSON shape, e.g. `{"Transfer": {"amount": 10}}` for a derived enum.
+pub async fn handle_request<SC>(contract: &SC, request: RpcRequest) -> RpcResponse
+where
+    SC: SmartContractTrait + Sync,
+    SC::Endpoint: DeserializeOwned,
+    SC::Lookup: DeserializeOwned,
+    SC::LookupResponse: Serialize,
+{
+    let RpcRequest {
+        jsonrpc,
+        id,
+        method,
+        params,
+    } = request;
+    if jsonrpc != JSONRPC_VERSION {
+        return RpcResponse::error(
+            id,
+            INVALID_REQUEST,
+            format!("Unsupported JSON-RPC version: {jsonrpc:?}"),
+        )
+    }
+    match method.as_str() {
+        HIT_ENDPOINT_METHOD => {
+            let endpoint = match serde_json::from_value(params) {
+                Ok(endpoint) => endpoint,
+                Err(e) => return RpcResponse::error(id, INVALID_PARAMS, e),
+            };
+            match contract.hit_endpoint(endpoint).await {
+                Ok(tx_id) => RpcResponse::result(id, json!({ "tx_id": tx_id.as_str() })),
+                Err(e) => RpcResponse::error(id, CONTRACT_ERROR, e),
+            }
+        }
+        LOOKUP_METHOD => {
+            let lookup = match serde_json::from_value(params) {
+                Ok(lookup) => lookup,
+                Err(e) => return RpcResponse::error(id, INVALID_PARAMS, e),
+            };
+            match contract.lookup(lookup).await {
+                Ok(res) => match serde_json::to_value(res) {
+                    Ok(res) => RpcResponse::result(id, res),
+                    Err(e) => RpcResponse::error(id, INTERNAL_ERROR, e),
+                },
+                Err(e) => RpcResponse::error(id, CONTRACT_ERROR, e),
+            }
+        }
+        _ => RpcResponse::error(
+            id,
+            METHOD_NOT_FOUND,
+            format!("Unknown method: {method:?}"),
+        ),
+    }
+}
+
+struct RpcState<SC> {
+    contract: Arc<SC>,
+    token: Option<Arc<Secret<String>>>,
+}
+
+impl<SC> Clone for RpcState<SC> {
+    fn clone(&self) -> Self {
+        RpcState {
+            contract: self.contract.clone(),
+            token: self.token.clone(),
+        }
+    }
+}
+
+/// [`Router`] answering JSON-RPC requests POSTed to `/` with `contract`, as long as they
+/// carry an `Authorization: Bearer <token>` header
+///
+/// This is the router to nest into an axum app, and the one [`serve`] uses. Hitting an
+/// endpoint spends from the contract's wallet, so other requests are refused with
+/// `401 Unauthorized`.
+pub fn authenticated_router<SC>(contract: Arc<SC>, token: Secret<String>) -> Router
+where
+    SC: SmartContractTrait + Send + Sync + 'static,
+    SC::Endpoint: DeserializeOwned + Send,
+    SC::Lookup: DeserializeOwned + Send,
+    SC::LookupResponse: Serialize + Send,
+{
+    rpc_router(RpcState {
+        contract,
+        token: Some(Arc::new(token)),
+    })
+}
+
+/// [`Router`] like [`authenticated_router`], answering requests without checking who sent
+/// them
+///
+/// Anyone who can reach it can hit endpoints, which spend from the contract's wallet. Only
+/// use it inside an axum app that already authenticates every request.
+pub fn unauthenticated_router<SC>(contract: Arc<SC>) -> Router
+where
+    SC: SmartContractTrait + Send + Sync + 'static,
+    SC::Endpoint: DeserializeOwned + Send,
+    SC::Lookup: DeserializeOwned + Send,
+    SC::LookupResponse: Serialize + Send,
+{
+    rpc_router(RpcState {
+        contract,
+        token: None,
+    })
+}
+
+fn rpc_router<SC>(state: RpcState<SC>) -> Router
+where
+    SC: SmartContractTrait + Send + Sync + 'static,
+    SC::Endpoint: DeserializeOwned + Send,
+    SC::Lookup: DeserializeOwned + Send,
+    SC::LookupResponse: Serialize + Send,
+{
+    Router::new()
+        .route("/", post(handle::<SC>))
+        .with_state(state)
+}
+
+async fn handle<SC>(
+    State(state): State<RpcState<SC>>,
+    headers: HeaderMap,
+    Json(request): Json<RpcRequest>,
+) -> std::result::Result<Json<RpcResponse>, StatusCode>
+where
+    SC: SmartContractTrait + Send + Sync + 'static,
+    SC::Endpoint: DeserializeOwned + Send,
+    SC::Lookup: DeserializeOwned + Send,
+    SC::LookupResponse: Serialize + Send,
+{
+    if let Some(token) = &state.token {
+        if !has_bearer_token(&headers, token) {
+            return Err(StatusCode::UNAUTHORIZED)
+        }
+    }
+    Ok(Json(handle_request(state.contract.as_ref(), request).await))
+}
+
+// Compared in constant time, so response times don't leak how much of the token matched
+fn has_bearer_token(headers: &HeaderMap, token: &Secret<String>) -> bool {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map_or(false, |given| {
+            given
+                .as_bytes()
+                .ct_eq(token.expose_secret().as_bytes())
+                .into()
+        })
+}
+
+/// Serve `contract` over HTTP/JSON-RPC on `addr` until the server fails
+///
+/// Requests must carry `token` as an `Authorization: Bearer <token>` header, since hitting
+/// an endpoint signs and submits a transaction with the contract's keys. Prefer binding to
+/// a loopback `addr` and putting TLS in front of it when serving beyond the local machine.
+pub async fn serve<SC>(
+    contract: SC,
+    addr: &SocketAddr,
+    token: Secret<String>,
+) -> Result<()>
+where
+    SC: SmartContractTrait + Send + Sync + 'static,
+    SC::Endpoint: DeserializeOwned + Send,
+    SC::Lookup: DeserializeOwned + Send,
+    SC::LookupResponse: Serialize + Send,
+{
+    if token.expose_secret().is_empty() {
+        return Err(Error::Rpc("The bearer token can't be empty".to_string()))
+    }
+    if !addr.ip().is_loopback() {
+        tracing::warn!("Serving smart contract over JSON-RPC on non-loopback {addr}");
+    }
+    tracing::info!("Serving smart contract over JSON-RPC on {addr}");
+    axum::Server::bind(addr)
+        .serve(authenticated_router(Arc::new(contract), token).into_make_service())
+        .await
+        .map_err(|e| Error::Rpc(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::{
+        ledger_client::{
+            test_ledger_client::TestLedgerClientBuilder,
+            LedgerClient,
+        },
+        logic::{
+            error::{
+                as_endpoint_err,
+                as_lookup_err,
+                SCLogicResult,
+            },
+            SCLogic,
+        },
+        smart_contract::SmartContract,
+        PolicyId,
+        TxActions,
+    };
+    use async_trait::async_trait;
+    use pallas_addresses::Address;
+
+    const ME: &str = "addr_test1qpuy2q9xel76qxdw8r29skldzc876cdgg9cugfg7mwh0zvpg3292mxuf3kq7nysjumlxjrlsfn9tp85r0l54l29x3qcs7nvyfm";
+    const ALICE: &str = "addr_test1qzvrhz9v6lwcr26a52y8mmk2nzq37lky68359keq3dgth4lkzpnnjv8vf98m20lhqdzl60mcftq7r2lc4xtcsv0w6xjstag0ua";
+
+    #[derive(Debug, Eq, PartialEq)]
+    struct TransferLogic;
+
+    #[derive(Debug, Deserialize)]
+    enum Endpoint {
+        Transfer { amount: u64, recipient: String },
+    }
+
+    #[derive(Debug, Deserialize)]
+    enum Lookup {
+        Balance { address: String },
+    }
+
+    #[async_trait]
+    impl SCLogic for TransferLogic {
+        type Endpoints = Endpoint;
+        type Lookups = Lookup;
+        type LookupResponses = u64;
+        type Datums = ();
+        type Redeemers = ();
+
+        async fn handle_endpoint<Record: LedgerClient<(), ()>>(
+            endpoint: Endpoint,
+            _ledger_client: &Record,
+        ) -> SCLogicResult<TxActions<(), ()>> {
+            let Endpoint::Transfer { amount, recipient } = endpoint;
+            let recipient = Address::from_bech32(&recipient).map_err(as_endpoint_err)?;
+            Ok(TxActions::v2().with_transfer(amount, recipient, PolicyId::Lovelace))
+        }
+
+        async fn lookup<Record: LedgerClient<(), ()>>(
+            lookup: Lookup,
+            ledger_client: &Record,
+        ) -> SCLogicResult<u64> {
+            let Lookup::Balance { address } = lookup;
+            let address = Address::from_bech32(&address).map_err(as_lookup_err)?;
+            let balance = ledger_client
+                .balance_at_address(&address, &PolicyId::Lovelace)
+                .await?;
+            Ok(balance)
+        }
+    }
+
+    fn request(method: &str, params: Value) -> RpcRequest {
+        RpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: json!(1),
+            method: method.to_string(),
+            params,
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_request__hits_endpoint_and_looks_up() {
+        // given
+        let me = Address::from_bech32(ME).unwrap();
+        let ledger_client = TestLedgerClientBuilder::<(), ()>::new(&me)
+            .start_output(&me)
+            .with_value(PolicyId::Lovelace, 100)
+            .finish_output()
+            .build_in_memory();
+        let contract = SmartContract::new(TransferLogic, ledger_client);
+        let transfer = json!({ "Transfer": { "amount": 60, "recipient": ALICE } });
+        let balance = json!({ "Balance": { "address": ALICE } });
+
+        // when
+        let hit = handle_request(&contract, request(HIT_ENDPOINT_METHOD, transfer)).await;
+        let lookup = handle_request(&contract, request(LOOKUP_METHOD, balance)).await;
+
+        // then
+        assert!(hit.result.unwrap()["tx_id"].is_string());
+        assert_eq!(lookup.result, Some(json!(60)));
+    }
+
+    #[test]
+    fn has_bearer_token__requires_the_exact_token() {
+        // given
+        let token = Secret::new("s3cret".to_string());
+        let headers = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(AUTHORIZATION, value.parse().unwrap());
+            headers
+        };
+
+        // when
+        let valid = has_bearer_token(&headers("Bearer s3cret"), &token);
+        let wrong = has_bearer_token(&headers("Bearer s3cre"), &token);
+        let same_length = has_bearer_token(&headers("Bearer s3crex"), &token);
+        let not_bearer = has_bearer_token(&headers("Basic s3cret"), &token);
+        let missing = has_bearer_token(&HeaderMap::new(), &token);
+
+        // then
+        assert!(valid);
+        assert!(!wrong);
+        assert!(!same_length);
+        assert!(!not_bearer);
+        assert!(!missing);
+    }
+
+    #[tokio::test]
+    async fn handle_request__rejects_unknown_methods() {
+        // given
+        let me = Address::from_bech32(ME).unwrap();
+        let ledger_client = TestLedgerClientBuilder::<(), ()>::new(&me).build_in_memory();
+        let contract = SmartContract::new(TransferLogic, ledger_client);
+
+        // when
+        let res = handle_request(&contract, request("withdraw", Value::Null)).await;
+
+        // then
+        assert_eq!(res.id, json!(1));
+        assert_eq!(res.error.unwrap().code, METHOD_NOT_FOUND);
+    }
+}
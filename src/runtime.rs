@@ -0,0 +1,39 @@
+//! Async runtime helpers that work both natively and in the browser
+//!
+//! Natively these use Tokio. On `wasm32` there is no Tokio timer, so the browser's timers
+//! and clock are used instead.
+
+use std::time::Duration;
+
+/// Error getting the current time
+pub(crate) type TimeError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Wait for `duration` without blocking the executor
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+/// Wait for `duration` without blocking the executor
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(duration: Duration) {
+    let millis = duration.as_millis().try_into().unwrap_or(u32::MAX);
+    // Browser timers aren't `Send`, but there is only one thread to run them on
+    send_wrapper::SendWrapper::new(gloo_timers::future::TimeoutFuture::new(millis)).await
+}
+
+/// Seconds since the Unix epoch
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn unix_time_secs() -> Result<i64, TimeError> {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs()
+        .try_into()?;
+    Ok(secs)
+}
+
+/// Seconds since the Unix epoch
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn unix_time_secs() -> Result<i64, TimeError> {
+    Ok((js_sys::Date::now() / 1000.0) as i64)
+}
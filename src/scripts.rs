@@ -1,19 +1,40 @@
-use context::TxContext;
+use crate::error::ErrorClass;
+use context::{
+    CtxScriptPurpose,
+    TxContext,
+};
 use pallas_addresses::{
     Address,
     Network,
 };
-use std::fmt::Debug;
+use plutus_validator::plutus_data::PlutusData;
+use std::{
+    fmt::{
+        self,
+        Debug,
+    },
+    sync::Arc,
+};
 use thiserror::Error;
 
+/// Decoding [`PlutusData`](plutus_validator::plutus_data::PlutusData) with blueprint schemas
+pub mod blueprint_schema;
+/// Parse blueprints and construct scripts once per program
+pub mod cache;
 /// Script context types
 pub mod context;
+/// Registry of where reference scripts were deployed
+pub mod deployments;
 /// Adapter code for [`MintingPolicy`]
 pub mod plutus_minting_policy;
 /// Adapter code for [`Validator`]
 pub mod plutus_validator;
 /// Raw script types
 pub mod raw_script;
+/// Common scripts, so dApps don't need to compile their own
+pub mod stdlib;
+/// Multiple versions of a validator, for upgrading long-lived contracts
+pub mod versioned;
 
 /// Interface for a script locking UTxOs at a script address
 pub trait Validator<D, R>: Send + Sync {
@@ -30,6 +51,28 @@ pub trait Validator<D, R>: Send + Sync {
     fn script_hex(&self) -> ScriptResult<String>;
 }
 
+// Lets several actions share one validator, since they each take a `Box`
+pub(crate) struct SharedValidator<D, R>(pub(crate) Arc<dyn Validator<D, R>>);
+
+impl<D, R> Validator<D, R> for SharedValidator<D, R> {
+    fn execute(
+        &self,
+        datum: D,
+        redeemer: R,
+        ctx: TxContext,
+    ) -> ScriptResult<ExecutionCost> {
+        self.0.execute(datum, redeemer, ctx)
+    }
+
+    fn address(&self, network: Network) -> ScriptResult<Address> {
+        self.0.address(network)
+    }
+
+    fn script_hex(&self) -> ScriptResult<String> {
+        self.0.script_hex()
+    }
+}
+
 /// Interface for a script constraining the minting of tokens
 pub trait MintingPolicy<R>: Send + Sync {
     /// Execute the script with specified redeemer and tx context
@@ -41,7 +84,7 @@ pub trait MintingPolicy<R>: Send + Sync {
 }
 
 /// Cost of executing a script
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ExecutionCost {
     mem: i64,
     cpu: i64,
@@ -85,6 +128,171 @@ pub enum ScriptError {
     IdRetrieval(String),
     #[error("Failed to retrieve script Cbor Hex")]
     ScriptHexRetrieval(String),
+    #[error("Script evaluation failed: {0}")]
+    Evaluation(Box<ScriptFailure>),
+    #[error("No version of the script locks outputs at: {0}")]
+    UnknownScriptVersion(String),
+}
+
+impl ScriptError {
+    /// Stable code identifying the failure
+    pub fn code(&self) -> &'static str {
+        match self {
+            ScriptError::FailedToExecute(_) => "script.failed_to_execute",
+            ScriptError::Evaluation(_) => "script.evaluation",
+            ScriptError::FailedToConstruct(_) => "script.failed_to_construct",
+            ScriptError::DatumDeserialization(_) => "script.datum_deserialization",
+            ScriptError::RedeemerDeserialization(_) => "script.redeemer_deserialization",
+            ScriptError::IdRetrieval(_) => "script.id_retrieval",
+            ScriptError::ScriptHexRetrieval(_) => "script.hex_retrieval",
+            ScriptError::UnknownScriptVersion(_) => "script.unknown_version",
+        }
+    }
+
+    /// Broad class of the failure
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            ScriptError::FailedToExecute(_) | ScriptError::Evaluation(_) => {
+                ErrorClass::ScriptFailure
+            }
+            ScriptError::DatumDeserialization(_)
+            | ScriptError::RedeemerDeserialization(_)
+            | ScriptError::UnknownScriptVersion(_) => ErrorClass::InvalidInput,
+            ScriptError::FailedToConstruct(_)
+            | ScriptError::IdRetrieval(_)
+            | ScriptError::ScriptHexRetrieval(_) => ErrorClass::Configuration,
+        }
+    }
+}
+
+/// Report of a script failing evaluation, with what it was run on and how far it got
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScriptFailure {
+    name: Option<String>,
+    hash: Option<String>,
+    purpose: CtxScriptPurpose,
+    datum: Option<PlutusData>,
+    redeemer: PlutusData,
+    cost: ExecutionCost,
+    error: String,
+    traces: Vec<String>,
+}
+
+impl ScriptFailure {
+    /// Constructor for a [`ScriptFailure`] from the evaluation `error` and the budget
+    /// consumed before it
+    pub fn new(
+        purpose: CtxScriptPurpose,
+        redeemer: PlutusData,
+        cost: ExecutionCost,
+        error: String,
+    ) -> Self {
+        ScriptFailure {
+            name: None,
+            hash: None,
+            purpose,
+            datum: None,
+            redeemer,
+            cost,
+            error,
+            traces: Vec::new(),
+        }
+    }
+
+    /// Add the name of the script, e.g. its blueprint title
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Add the hex hash of the script
+    pub fn with_hash(mut self, hash: &str) -> Self {
+        self.hash = Some(hash.to_string());
+        self
+    }
+
+    /// Add the datum of the spent output
+    pub fn with_datum(mut self, datum: PlutusData) -> Self {
+        self.datum = Some(datum);
+        self
+    }
+
+    /// Add the traces logged before the failure
+    pub fn with_traces(mut self, traces: Vec<String>) -> Self {
+        self.traces = traces;
+        self
+    }
+
+    /// Getter for the script name
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Getter for the hex script hash
+    pub fn hash(&self) -> Option<&str> {
+        self.hash.as_deref()
+    }
+
+    /// Getter for what the script was run for
+    pub fn purpose(&self) -> &CtxScriptPurpose {
+        &self.purpose
+    }
+
+    /// Getter for the datum, `None` for minting policies
+    pub fn datum(&self) -> Option<&PlutusData> {
+        self.datum.as_ref()
+    }
+
+    /// Getter for the redeemer
+    pub fn redeemer(&self) -> &PlutusData {
+        &self.redeemer
+    }
+
+    /// Getter for the budget consumed before the failure
+    pub fn cost(&self) -> &ExecutionCost {
+        &self.cost
+    }
+
+    /// Getter for the evaluation error
+    pub fn error(&self) -> &str {
+        &self.error
+    }
+
+    /// Getter for the traces logged before the failure
+    pub fn traces(&self) -> &[String] {
+        &self.traces
+    }
+}
+
+impl fmt::Display for ScriptFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name.as_deref().unwrap_or("script"))?;
+        if let Some(hash) = &self.hash {
+            write!(f, " ({hash})")?;
+        }
+        match &self.purpose {
+            CtxScriptPurpose::Mint(policy) => {
+                write!(f, " minting {}", hex::encode(policy))?
+            }
+            CtxScriptPurpose::Spend(out_ref) => write!(
+                f,
+                " spending {}#{}",
+                hex::encode(&out_ref.transaction_id),
+                out_ref.output_index
+            )?,
+            CtxScriptPurpose::WithdrawFrom => write!(f, " withdrawing")?,
+            CtxScriptPurpose::Publish => write!(f, " publishing")?,
+        }
+        write!(f, ": {}", self.error)?;
+        if let Some(datum) = &self.datum {
+            write!(f, ", datum: {datum}")?;
+        }
+        write!(
+            f,
+            ", redeemer: {}, consumed mem {} cpu {}, traces: {:?}",
+            self.redeemer, self.cost.mem, self.cost.cpu, self.traces
+        )
+    }
 }
 
 /// Convert a generic error into a [`ScriptError'] `FailedToExecute` variant
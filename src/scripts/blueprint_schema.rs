@@ -0,0 +1,386 @@
+use crate::scripts::plutus_validator::plutus_data::{
+    BigInt,
+    Constr,
+    PlutusData,
+};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+const DEFINITIONS_PREFIX: &str = "#/definitions/";
+
+#[allow(missing_docs)]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BlueprintSchemaError {
+    #[error("No validator titled {0:?} in the blueprint")]
+    UnknownValidator(String),
+    #[error("Validator {0:?} doesn't take a datum")]
+    NoDatum(String),
+    #[error("Schema reference {0:?} isn't in the blueprint definitions")]
+    UnresolvedRef(String),
+    #[error("Unsupported schema at {path}: {schema}")]
+    UnsupportedSchema { path: String, schema: String },
+    #[error(
+        "Data at {path} doesn't match the schema: expected {expected}, found {found}"
+    )]
+    Mismatch {
+        path: String,
+        expected: String,
+        found: String,
+    },
+    #[error("Failed to convert the decoded data: {0}")]
+    Conversion(String),
+}
+
+/// Specialized result type for decoding data with blueprint schemas
+pub type BlueprintSchemaResult<T, E = BlueprintSchemaError> = Result<T, E>;
+
+/// [`PlutusData`] decoded with a blueprint schema, with constructor and field titles
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SchemaValue {
+    /// Integer
+    Integer(BigInt),
+    /// Byte array
+    Bytes(Vec<u8>),
+    /// List or tuple
+    List(Vec<SchemaValue>),
+    /// Map, in on-chain order
+    Map(Vec<(SchemaValue, SchemaValue)>),
+    /// Constructor of a sum or product type
+    Constructor {
+        /// Title of the constructor, if the schema has one
+        title: Option<String>,
+        /// Constructor index
+        index: u64,
+        /// Fields, with their titles if the schema has them
+        fields: Vec<(Option<String>, SchemaValue)>,
+    },
+    /// Data the schema allows to be anything
+    Opaque(PlutusData),
+}
+
+impl SchemaValue {
+    /// Value of the field titled `title`, if this is a constructor with such a field
+    pub fn field(&self, title: &str) -> Option<&SchemaValue> {
+        match self {
+            SchemaValue::Constructor { fields, .. } => fields
+                .iter()
+                .find(|(field_title, _)| field_title.as_deref() == Some(title))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+/// Checks [`PlutusData`] against schemas of a CIP-57 blueprint, resolving references to its
+/// `definitions`
+pub struct SchemaDecoder<'a> {
+    definitions: &'a BTreeMap<String, Value>,
+}
+
+impl<'a> SchemaDecoder<'a> {
+    /// Constructor for the [`SchemaDecoder`] struct from the blueprint `definitions`
+    pub fn new(definitions: &'a BTreeMap<String, Value>) -> Self {
+        SchemaDecoder { definitions }
+    }
+
+    /// Decode `data` with `schema`, failing at the first place it doesn't match
+    pub fn decode(
+        &self,
+        schema: &Value,
+        data: &PlutusData,
+    ) -> BlueprintSchemaResult<SchemaValue> {
+        self.decode_at("$", schema, data)
+    }
+
+    fn decode_at(
+        &self,
+        path: &str,
+        schema: &Value,
+        data: &PlutusData,
+    ) -> BlueprintSchemaResult<SchemaValue> {
+        let schema = self.resolve(schema)?;
+        if let Some(alternatives) = schema.get("anyOf").and_then(Value::as_array) {
+            return self.decode_any_of(path, alternatives, data)
+        }
+        let data_type = match schema.get("dataType").and_then(Value::as_str) {
+            Some(data_type) => data_type,
+            // Aiken's `Data` places no constraints
+            None if is_opaque(schema) => return Ok(SchemaValue::Opaque(data.clone())),
+            None => return Err(unsupported(path, schema)),
+        };
+        match (data_type, data) {
+            ("integer", PlutusData::BigInt(big_int)) => {
+                Ok(SchemaValue::Integer(big_int.clone()))
+            }
+            ("bytes", PlutusData::BoundedBytes(bytes)) => {
+                Ok(SchemaValue::Bytes(bytes.clone()))
+            }
+            ("list", PlutusData::Array(items)) => {
+                self.decode_list(path, schema, items).map(SchemaValue::List)
+            }
+            ("map", PlutusData::Map(map)) => {
+                let keys = schema
+                    .get("keys")
+                    .ok_or_else(|| unsupported(path, schema))?;
+                let values = schema
+                    .get("values")
+                    .ok_or_else(|| unsupported(path, schema))?;
+                map.iter()
+                    .map(|(key, value)| {
+                        let key_path = format!("{path}{{{key}}}");
+                        Ok((
+                            self.decode_at(&format!("{key_path}.key"), keys, key)?,
+                            self.decode_at(&key_path, values, value)?,
+                        ))
+                    })
+                    .collect::<BlueprintSchemaResult<_>>()
+                    .map(SchemaValue::Map)
+            }
+            ("constructor", PlutusData::Constr(constr)) => {
+                self.decode_constructor(path, schema, constr)
+            }
+            ("integer" | "bytes" | "list" | "map" | "constructor", _) => {
+                Err(mismatch(path, data_type, data))
+            }
+            _ => Err(unsupported(path, schema)),
+        }
+    }
+
+    fn decode_any_of(
+        &self,
+        path: &str,
+        alternatives: &[Value],
+        data: &PlutusData,
+    ) -> BlueprintSchemaResult<SchemaValue> {
+        let mut first_error = None;
+        for alternative in alternatives {
+            match self.decode_at(path, alternative, data) {
+                Ok(value) => return Ok(value),
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+        // A single alternative, like a record, has the most specific error to report
+        match (alternatives.len(), first_error) {
+            (1, Some(error)) => Err(error),
+            _ => Err(mismatch(path, "one of the schema's alternatives", data)),
+        }
+    }
+
+    fn decode_list(
+        &self,
+        path: &str,
+        schema: &Value,
+        items: &[PlutusData],
+    ) -> BlueprintSchemaResult<Vec<SchemaValue>> {
+        let item_schemas = schema
+            .get("items")
+            .ok_or_else(|| unsupported(path, schema))?;
+        match item_schemas {
+            // Tuples list the schema of each item
+            Value::Array(item_schemas) => {
+                if item_schemas.len() != items.len() {
+                    return Err(mismatch(
+                        path,
+                        &format!("a tuple of {}", item_schemas.len()),
+                        &PlutusData::Array(items.to_vec()),
+                    ))
+                }
+                item_schemas
+                    .iter()
+                    .zip(items.iter())
+                    .enumerate()
+                    .map(|(i, (item_schema, item))| {
+                        self.decode_at(&format!("{path}[{i}]"), item_schema, item)
+                    })
+                    .collect()
+            }
+            item_schema => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    self.decode_at(&format!("{path}[{i}]"), item_schema, item)
+                })
+                .collect(),
+        }
+    }
+
+    fn decode_constructor(
+        &self,
+        path: &str,
+        schema: &Value,
+        constr: &Constr<PlutusData>,
+    ) -> BlueprintSchemaResult<SchemaValue> {
+        let index = schema
+            .get("index")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| unsupported(path, schema))?;
+        let field_schemas = schema
+            .get("fields")
+            .and_then(Value::as_array)
+            .ok_or_else(|| unsupported(path, schema))?;
+        if constr.constr != index || constr.fields.len() != field_schemas.len() {
+            return Err(mismatch(
+                path,
+                &format!("constructor {index} with {} fields", field_schemas.len()),
+                &PlutusData::Constr(constr.clone()),
+            ))
+        }
+        let fields = field_schemas
+            .iter()
+            .zip(constr.fields.iter())
+            .enumerate()
+            .map(|(i, (field_schema, field))| {
+                let value =
+                    self.decode_at(&format!("{path}.{i}"), field_schema, field)?;
+                Ok((title(field_schema), value))
+            })
+            .collect::<BlueprintSchemaResult<_>>()?;
+        Ok(SchemaValue::Constructor {
+            title: title(schema),
+            index,
+            fields,
+        })
+    }
+
+    // Follow `$ref`s until reaching an actual schema
+    fn resolve<'s>(&'s self, schema: &'s Value) -> BlueprintSchemaResult<&'s Value> {
+        let mut schema = schema;
+        // Bounded so a reference cycle can't loop forever
+        for _ in 0..=self.definitions.len() {
+            let reference = match schema.get("$ref").and_then(Value::as_str) {
+                Some(reference) => reference,
+                None => return Ok(schema),
+            };
+            let name = reference
+                .strip_prefix(DEFINITIONS_PREFIX)
+                .map(|name| name.replace("~1", "/").replace("~0", "~"))
+                .ok_or_else(|| {
+                    BlueprintSchemaError::UnresolvedRef(reference.to_string())
+                })?;
+            schema = self.definitions.get(&name).ok_or_else(|| {
+                BlueprintSchemaError::UnresolvedRef(reference.to_string())
+            })?;
+        }
+        Err(BlueprintSchemaError::UnresolvedRef(format!(
+            "{schema} (reference cycle)"
+        )))
+    }
+}
+
+// A schema without `dataType`, `anyOf` or `$ref` accepts any data
+fn is_opaque(schema: &Value) -> bool {
+    match schema.as_object() {
+        Some(object) => object
+            .keys()
+            .all(|key| matches!(key.as_str(), "title" | "description")),
+        None => false,
+    }
+}
+
+fn title(schema: &Value) -> Option<String> {
+    schema
+        .get("title")
+        .and_then(Value::as_str)
+        .map(ToString::to_string)
+}
+
+fn unsupported(path: &str, schema: &Value) -> BlueprintSchemaError {
+    BlueprintSchemaError::UnsupportedSchema {
+        path: path.to_string(),
+        schema: schema.to_string(),
+    }
+}
+
+fn mismatch(path: &str, expected: &str, found: &PlutusData) -> BlueprintSchemaError {
+    BlueprintSchemaError::Mismatch {
+        path: path.to_string(),
+        expected: expected.to_string(),
+        found: found.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use serde_json::json;
+
+    fn definitions() -> BTreeMap<String, Value> {
+        serde_json::from_value(json!({
+            "ByteArray": { "dataType": "bytes" },
+            "Int": { "dataType": "integer" },
+            "pull_validator/PullDatum": {
+                "title": "PullDatum",
+                "anyOf": [{
+                    "title": "PullDatum",
+                    "dataType": "constructor",
+                    "index": 0,
+                    "fields": [
+                        { "title": "owner", "$ref": "#/definitions/ByteArray" },
+                        { "title": "amount_lovelace", "$ref": "#/definitions/Int" }
+                    ]
+                }]
+            }
+        }))
+        .unwrap()
+    }
+
+    fn datum_schema() -> Value {
+        json!({ "$ref": "#/definitions/pull_validator~1PullDatum" })
+    }
+
+    #[test]
+    fn decode__names_constructor_fields() {
+        // given
+        let definitions = definitions();
+        let decoder = SchemaDecoder::new(&definitions);
+        let data = PlutusData::Constr(Constr {
+            constr: 0,
+            fields: vec![
+                PlutusData::BoundedBytes(vec![1, 2]),
+                PlutusData::BigInt(5_i64.into()),
+            ],
+        });
+
+        // when
+        let value = decoder.decode(&datum_schema(), &data).unwrap();
+
+        // then
+        assert_eq!(
+            value.field("amount_lovelace"),
+            Some(&SchemaValue::Integer(5_i64.into()))
+        );
+        assert_eq!(value.field("owner"), Some(&SchemaValue::Bytes(vec![1, 2])));
+    }
+
+    #[test]
+    fn decode__reports_where_layout_drifted() {
+        // given
+        let definitions = definitions();
+        let decoder = SchemaDecoder::new(&definitions);
+        let data = PlutusData::Constr(Constr {
+            constr: 0,
+            fields: vec![
+                PlutusData::BoundedBytes(vec![1, 2]),
+                PlutusData::BoundedBytes(vec![5]),
+            ],
+        });
+
+        // when
+        let error = decoder.decode(&datum_schema(), &data).unwrap_err();
+
+        // then
+        assert_eq!(
+            error,
+            BlueprintSchemaError::Mismatch {
+                path: "$.1".to_string(),
+                expected: "integer".to_string(),
+                found: "h'05'".to_string(),
+            }
+        );
+    }
+}
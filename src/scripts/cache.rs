@@ -0,0 +1,243 @@
+use crate::scripts::{
+    plutus_validator::plutus_data::PlutusData,
+    raw_script::{
+        BlueprintFile,
+        RawPlutusScriptResult,
+        ValidatorBlueprint,
+    },
+    ScriptError,
+    ScriptResult,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        Mutex,
+        MutexGuard,
+        OnceLock,
+    },
+};
+
+/// Most parameter applications remembered by a single parameterized script
+pub const MAX_MEMOIZED_APPLICATIONS: usize = 64;
+
+/// Blueprint parsed on first use and kept for the life of the program
+///
+/// Meant for a `static` next to the `include_str!` of the `plutus.json`, so endpoints don't
+/// parse the whole file every time they need one of its validators:
+///
+/// ```ignore
+/// static BLUEPRINT: CachedBlueprint = CachedBlueprint::new(include_str!("../plutus.json"));
+/// ```
+pub struct CachedBlueprint {
+    source: &'static str,
+    parsed: OnceLock<Result<BlueprintFile, String>>,
+}
+
+impl CachedBlueprint {
+    /// Constructor for a [`CachedBlueprint`] of the blueprint JSON `source`
+    pub const fn new(source: &'static str) -> Self {
+        CachedBlueprint {
+            source,
+            parsed: OnceLock::new(),
+        }
+    }
+
+    /// The parsed blueprint, parsing it if this is the first call
+    pub fn blueprint(&self) -> ScriptResult<&BlueprintFile> {
+        // Parsing is deterministic, so a failure is cached as well
+        self.parsed
+            .get_or_init(|| serde_json::from_str(self.source).map_err(|e| e.to_string()))
+            .as_ref()
+            .map_err(|e| ScriptError::FailedToConstruct(e.clone()))
+    }
+
+    /// Get the validator titled `title` from the parsed blueprint
+    pub fn validator(&self, title: &str) -> ScriptResult<ValidatorBlueprint> {
+        self.blueprint()?.get_validator(title).ok_or_else(|| {
+            ScriptError::FailedToConstruct(format!(
+                "Validator not listed in Blueprint: {title:?}"
+            ))
+        })
+    }
+}
+
+/// Script, or any other value, constructed on first use and cloned out afterwards
+///
+/// Pairs with [`CachedBlueprint`] to decode the compiled code of a validator once, e.g.
+///
+/// ```ignore
+/// static VALIDATOR: ScriptCache<PlutusValidator<Datum, ()>> = ScriptCache::new();
+///
+/// pub fn validator() -> ScriptResult<PlutusValidator<Datum, ()>> {
+///     VALIDATOR.get_or_try_init(|| {
+///         PlutusValidator::from_blueprint(BLUEPRINT.validator(VALIDATOR_NAME)?)
+///             .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))
+///     })
+/// }
+/// ```
+pub struct ScriptCache<T> {
+    cell: OnceLock<T>,
+}
+
+impl<T> Default for ScriptCache<T> {
+    fn default() -> Self {
+        ScriptCache::new()
+    }
+}
+
+impl<T> ScriptCache<T> {
+    /// Constructor for an empty [`ScriptCache`]
+    pub const fn new() -> Self {
+        ScriptCache {
+            cell: OnceLock::new(),
+        }
+    }
+
+    /// Whether the value has been constructed yet
+    pub fn is_initialized(&self) -> bool {
+        self.cell.get().is_some()
+    }
+}
+
+impl<T: Clone> ScriptCache<T> {
+    /// Clone of the cached value, constructing it with `init` if there isn't one yet
+    ///
+    /// Failures aren't cached, so the next call tries `init` again. Callers racing on an empty
+    /// cache may each run `init`, but only the first value is kept.
+    pub fn get_or_try_init<E>(
+        &self,
+        init: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        if let Some(value) = self.cell.get() {
+            return Ok(value.clone())
+        }
+        let value = init()?;
+        Ok(self.cell.get_or_init(|| value).clone())
+    }
+}
+
+/// Programs produced by applying parameters to one parameterized script, keyed by the CBOR
+/// of the parameter
+///
+/// Clones share the memo, so a builder kept in a [`ScriptCache`] doesn't decode and re-encode
+/// its program for parameters it has already seen. Each program comes with its own memo for
+/// when it takes further parameters.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ParamApplications {
+    applied: Arc<Mutex<HashMap<Vec<u8>, Applied>>>,
+}
+
+type Applied = (Vec<u8>, ParamApplications);
+
+impl ParamApplications {
+    /// Program for `param` and its own memo, running `apply` only for new parameters
+    pub fn get_or_apply(
+        &self,
+        param: &PlutusData,
+        apply: impl FnOnce(&PlutusData) -> RawPlutusScriptResult<Vec<u8>>,
+    ) -> RawPlutusScriptResult<Applied> {
+        let key = param.to_cbor_bytes();
+        if let Some(applied) = self.lock().and_then(|memo| memo.get(&key).cloned()) {
+            return Ok(applied)
+        }
+        let applied = (apply(param)?, ParamApplications::default());
+        if let Some(mut memo) = self.lock() {
+            if memo.len() < MAX_MEMOIZED_APPLICATIONS {
+                memo.insert(key, applied.clone());
+            }
+        }
+        Ok(applied)
+    }
+
+    // A poisoned memo is skipped rather than trusted
+    fn lock(&self) -> Option<MutexGuard<'_, HashMap<Vec<u8>, Applied>>> {
+        self.applied.lock().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use std::sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    };
+
+    const BLUEPRINT_JSON: &str = r#"{
+        "preamble": { "title": "test", "description": "", "version": "0.0.0" },
+        "validators": [{
+            "title": "always_true.spend",
+            "redeemer": { "schema": {} },
+            "compiledCode": "4e4d01000033222220051200120011",
+            "hash": "00"
+        }]
+    }"#;
+
+    #[test]
+    fn cached_blueprint__finds_validators_and_reports_missing_ones() {
+        // given
+        let cached = CachedBlueprint::new(BLUEPRINT_JSON);
+
+        // when
+        let found = cached.validator("always_true.spend").unwrap();
+        let missing = cached.validator("nope.spend");
+
+        // then
+        assert_eq!(found.title(), "always_true.spend");
+        assert!(matches!(missing, Err(ScriptError::FailedToConstruct(_))));
+    }
+
+    #[test]
+    fn get_or_try_init__constructs_once_and_retries_failures() {
+        // given
+        let cache = ScriptCache::new();
+        let calls = AtomicUsize::new(0);
+        let init = |fail: bool| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            if fail {
+                Err("boom")
+            } else {
+                Ok(vec![1, 2, 3])
+            }
+        };
+
+        // when
+        let failed = cache.get_or_try_init(|| init(true));
+        let first = cache.get_or_try_init(|| init(false));
+        let second = cache.get_or_try_init(|| init(false));
+
+        // then
+        assert_eq!(failed, Err("boom"));
+        assert_eq!(first, Ok(vec![1, 2, 3]));
+        assert_eq!(second, Ok(vec![1, 2, 3]));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn get_or_apply__reuses_programs_for_equal_params_across_clones() {
+        // given
+        let applications = ParamApplications::default();
+        let shared = applications.clone();
+        let calls = AtomicUsize::new(0);
+        let apply = |param: &PlutusData| -> RawPlutusScriptResult<Vec<u8>> {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(param.to_cbor_bytes())
+        };
+        let owner = PlutusData::BoundedBytes(vec![1, 2, 3]);
+
+        // when
+        let (first, _) = applications.get_or_apply(&owner, apply).unwrap();
+        let (second, _) = shared.get_or_apply(&owner.clone(), apply).unwrap();
+        let (other, _) = shared
+            .get_or_apply(&PlutusData::BoundedBytes(vec![4]), apply)
+            .unwrap();
+
+        // then
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -1,10 +1,18 @@
 use crate::{
-    output::Output,
+    address::Credential,
+    output::{
+        Output,
+        OutputId,
+    },
     scripts::plutus_validator::plutus_data::PlutusData,
     values::Values,
     PolicyId,
 };
-use pallas_addresses::Address;
+use pallas_addresses::{
+    Address,
+    ShelleyDelegationPart,
+    StakePayload,
+};
 use serde::{
     Deserialize,
     Serialize,
@@ -25,6 +33,8 @@ pub struct TxContext {
     pub range: ValidRange,
     /// The input UTxOs of the transaction
     pub inputs: Vec<Input>,
+    /// The UTxOs read by the transaction without spending them
+    pub reference_inputs: Vec<Input>,
     /// The output UTxOs of the transaction
     pub outputs: Vec<CtxOutput>,
     /// The extra signatories of the transaction
@@ -34,7 +44,7 @@ pub struct TxContext {
 }
 
 /// The purpose of the script
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CtxScriptPurpose {
     /// Mint tokens
     Mint(Vec<u8>),
@@ -47,7 +57,7 @@ pub enum CtxScriptPurpose {
 }
 
 /// Specifies the output that is being spent in the script purpose
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CtxOutputReference {
     pub(crate) transaction_id: Vec<u8>,
     pub(crate) output_index: u64,
@@ -63,6 +73,18 @@ impl CtxOutputReference {
     }
 }
 
+impl From<&OutputId> for CtxOutputReference {
+    fn from(id: &OutputId) -> Self {
+        CtxOutputReference::new(id.tx_hash().to_vec(), id.index())
+    }
+}
+
+impl<Datum> From<&Output<Datum>> for CtxOutputReference {
+    fn from(output: &Output<Datum>) -> Self {
+        output.id().into()
+    }
+}
+
 /// The public key hash of the signer
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub struct PubKeyHash(Vec<u8>);
@@ -91,6 +113,52 @@ pub fn pub_key_hash_from_address_if_available(address: &Address) -> Option<PubKe
     }
 }
 
+/// Retrieves the payment credential if Address is a Shelley
+pub fn payment_credential_from_address(address: &Address) -> Option<Credential> {
+    match address {
+        Address::Shelley(shelley_address) => {
+            Some(Credential::from(shelley_address.payment()))
+        }
+        _ => None,
+    }
+}
+
+/// Retrieves the staking credential of a base or stake Address
+///
+/// Pointer addresses and addresses without a staking part return `None`.
+pub fn stake_credential_from_address(address: &Address) -> Option<Credential> {
+    match address {
+        Address::Shelley(shelley_address) => match shelley_address.delegation() {
+            ShelleyDelegationPart::Key(hash) => Some(Credential::Key(hash.to_vec())),
+            ShelleyDelegationPart::Script(hash) => {
+                Some(Credential::Script(hash.to_vec()))
+            }
+            _ => None,
+        },
+        Address::Stake(stake_address) => match stake_address.payload() {
+            StakePayload::Stake(hash) => Some(Credential::Key(hash.to_vec())),
+            StakePayload::Script(hash) => Some(Credential::Script(hash.to_vec())),
+        },
+        Address::Byron(_) => None,
+    }
+}
+
+/// Retrieves the payment script hash if Address is locked by a script
+pub fn script_hash_from_address(address: &Address) -> Option<Vec<u8>> {
+    match payment_credential_from_address(address)? {
+        Credential::Script(hash) => Some(hash),
+        Credential::Key(_) => None,
+    }
+}
+
+/// Retrieves the staking key hash if Address is delegated with a key
+pub fn stake_key_hash_from_address(address: &Address) -> Option<PubKeyHash> {
+    match stake_credential_from_address(address)? {
+        Credential::Key(hash) => Some(PubKeyHash::new(&hash)),
+        Credential::Script(_) => None,
+    }
+}
+
 // TODO: Remove the inclusive bool. It's not needed.
 /// Valid range of tx in milliseconds, and a `bool` specifying inclusive. If `None`, then the range is unbounded.
 #[derive(Clone, Debug)]
@@ -135,7 +203,7 @@ pub struct CtxOutput {
 #[derive(Clone, Debug)]
 pub struct CtxValue {
     /// Inner map of PolicyIds to Asset Names and amount
-    pub inner: HashMap<String, HashMap<String, u64>>,
+    pub inner: HashMap<String, HashMap<Vec<u8>, u64>>,
 }
 
 impl From<Values> for CtxValue {
@@ -143,12 +211,12 @@ impl From<Values> for CtxValue {
         let mut inner = HashMap::new();
         for (policy, amt) in values.as_iter() {
             let (policy_id, asset_name) = match policy {
-                PolicyId::Lovelace => ("", ""),
+                PolicyId::Lovelace => ("", [].as_slice()),
                 PolicyId::NativeToken(policy_id, a) => {
                     if let Some(asset_name) = a {
-                        (policy_id.as_str(), asset_name.as_str())
+                        (policy_id.as_str(), asset_name.as_slice())
                     } else {
-                        (policy_id.as_str(), "")
+                        (policy_id.as_str(), [].as_slice())
                     }
                 }
             };
@@ -197,6 +265,7 @@ pub struct ContextBuilder {
     signer: PubKeyHash,
     range: Option<ValidRange>,
     inputs: Vec<Input>,
+    reference_inputs: Vec<Input>,
     outputs: Vec<CtxOutput>,
     extra_signatories: Vec<PubKeyHash>,
     datums: Vec<(Vec<u8>, PlutusData)>,
@@ -209,6 +278,7 @@ impl ContextBuilder {
             signer,
             range: None,
             inputs: vec![],
+            reference_inputs: vec![],
             outputs: vec![],
             extra_signatories: vec![],
             datums: vec![],
@@ -273,6 +343,24 @@ impl ContextBuilder {
         self
     }
 
+    /// Add `output` as a reference input, read by the transaction without being spent
+    pub fn add_reference_input<D: Clone + Into<PlutusData>>(
+        mut self,
+        output: &Output<D>,
+    ) -> Self {
+        let id = output.id();
+        let reference_input = Input {
+            transaction_id: id.tx_hash().to_vec(),
+            output_index: id.index(),
+            address: output.owner(),
+            value: CtxValue::from(output.values().to_owned()),
+            datum: output.typed_datum().into(),
+            reference_script: output.reference_script().map(|s| s.hash().to_vec()),
+        };
+        self.reference_inputs.push(reference_input);
+        self
+    }
+
     /// Initializes [`CtxOutputBuilder`] sub-builder for adding an output to the `TxContext`
     pub fn with_output(self, address: &Address) -> CtxOutputBuilder {
         CtxOutputBuilder {
@@ -339,6 +427,7 @@ impl ContextBuilder {
             signer: self.signer.clone(),
             range,
             inputs: self.inputs.clone(),
+            reference_inputs: self.reference_inputs.clone(),
             outputs: self.outputs.clone(),
             extra_signatories: self.extra_signatories.clone(),
             datums: self.datums.clone(),
@@ -360,6 +449,7 @@ impl ContextBuilder {
             signer: self.signer.clone(),
             range,
             inputs: self.inputs.clone(),
+            reference_inputs: self.reference_inputs.clone(),
             outputs: self.outputs.clone(),
             extra_signatories: self.extra_signatories.clone(),
             datums: self.datums.clone(),
@@ -373,20 +463,20 @@ pub struct CtxInputBuilder {
     transaction_id: Vec<u8>,
     output_index: u64,
     address: Address,
-    value: HashMap<String, HashMap<String, u64>>,
+    value: HashMap<String, HashMap<Vec<u8>, u64>>,
     datum: CtxDatum,
     reference_script: Option<Vec<u8>>,
 }
 
 impl CtxInputBuilder {
-    /// Add single value to the `CtxInput`
+    /// Add single value to the `CtxInput`. The asset name can be text or raw bytes
     pub fn with_value(
         mut self,
         policy_id: &str,
-        asset_name: &str,
+        asset_name: impl AsRef<[u8]>,
         amt: u64,
     ) -> CtxInputBuilder {
-        add_to_nested(&mut self.value, policy_id, asset_name, amt);
+        add_to_nested(&mut self.value, policy_id, asset_name.as_ref(), amt);
         self
     }
 
@@ -433,15 +523,20 @@ impl CtxInputBuilder {
 pub struct CtxOutputBuilder {
     outer: ContextBuilder,
     address: Address,
-    value: HashMap<String, HashMap<String, u64>>,
+    value: HashMap<String, HashMap<Vec<u8>, u64>>,
     datum: CtxDatum,
     reference_script: Option<Vec<u8>>,
 }
 
 impl CtxOutputBuilder {
-    /// Add specific value to the `CtxOutput`
-    pub fn with_value(mut self, policy_id: &str, asset_name: &str, amt: u64) -> Self {
-        add_to_nested(&mut self.value, policy_id, asset_name, amt);
+    /// Add specific value to the `CtxOutput`. The asset name can be text or raw bytes
+    pub fn with_value(
+        mut self,
+        policy_id: &str,
+        asset_name: impl AsRef<[u8]>,
+        amt: u64,
+    ) -> Self {
+        add_to_nested(&mut self.value, policy_id, asset_name.as_ref(), amt);
         self
     }
 
@@ -480,23 +575,72 @@ impl CtxOutputBuilder {
 }
 
 fn add_to_nested(
-    values: &mut HashMap<String, HashMap<String, u64>>,
+    values: &mut HashMap<String, HashMap<Vec<u8>, u64>>,
     policy_id: &str,
-    asset_name: &str,
+    asset_name: &[u8],
     amt: u64,
 ) {
     let new_assets = if let Some(mut assets) = values.remove(policy_id) {
         if let Some(mut total_amt) = assets.remove(asset_name) {
             total_amt += amt;
-            assets.insert(asset_name.to_string(), total_amt);
+            assets.insert(asset_name.to_vec(), total_amt);
         } else {
-            assets.insert(asset_name.to_string(), amt);
+            assets.insert(asset_name.to_vec(), amt);
         }
         assets
     } else {
         let mut assets = HashMap::new();
-        assets.insert(asset_name.to_string(), amt);
+        assets.insert(asset_name.to_vec(), amt);
         assets
     };
     values.insert(policy_id.to_string(), new_assets);
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::address::{
+        base_address,
+        enterprise_address,
+        stake_address,
+    };
+    use pallas_addresses::Network;
+
+    #[test]
+    fn stake_credential_from_address__finds_delegation_part() {
+        // given
+        let payment = Credential::Script(vec![1; 28]);
+        let stake = Credential::Key(vec![2; 28]);
+        let base = base_address(Network::Testnet, &payment, &stake).unwrap();
+        let enterprise = enterprise_address(Network::Testnet, &payment).unwrap();
+        let reward = stake_address(Network::Testnet, &stake).unwrap();
+
+        // when
+        let from_base = stake_credential_from_address(&base);
+        let from_enterprise = stake_credential_from_address(&enterprise);
+        let from_reward = stake_credential_from_address(&reward);
+
+        // then
+        assert_eq!(from_base, Some(stake.clone()));
+        assert_eq!(from_enterprise, None);
+        assert_eq!(from_reward, Some(stake));
+    }
+
+    #[test]
+    fn script_hash_from_address__only_for_script_payment_parts() {
+        let script = Credential::Script(vec![1; 28]);
+        let key = Credential::Key(vec![2; 28]);
+        let script_address = base_address(Network::Testnet, &script, &key).unwrap();
+        let key_address = base_address(Network::Testnet, &key, &script).unwrap();
+
+        assert_eq!(script_hash_from_address(&script_address), Some(vec![1; 28]));
+        assert_eq!(script_hash_from_address(&key_address), None);
+        assert_eq!(stake_key_hash_from_address(&key_address), None);
+        assert_eq!(
+            stake_key_hash_from_address(&script_address),
+            Some(PubKeyHash::new(&[2; 28]))
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use crate::{
+    output::{
+        Output,
+        OutputId,
+        ReferenceScript,
+    },
+    transaction::TransactionVersion,
+    trireme_ledger_client::Network,
+    values::Values,
+    PolicyId,
+};
+use pallas_addresses::Address;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    collections::BTreeMap,
+    error,
+    fs,
+    future::Future,
+    io,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum DeploymentError {
+    #[error("Failed to read or write deployments file: {0}")]
+    Io(#[from] io::Error),
+    #[error("Malformed deployments file: {0}")]
+    Toml(String),
+    #[error("Output {0:?} doesn't hold a reference script")]
+    NoReferenceScript(OutputId),
+    #[error("Output {0:?} holds a different reference script than the one deployed")]
+    WrongReferenceScript(OutputId),
+    #[error("Failed to deploy reference script: {0}")]
+    Deploy(#[source] Box<dyn error::Error + Send + Sync>),
+}
+
+/// Specialized result type for the deployment registry
+pub type DeploymentResult<T, E = DeploymentError> = Result<T, E>;
+
+/// Where a reference script was deployed
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Deployment {
+    tx_id: String,
+    index: u64,
+    address: String,
+    lovelace: u64,
+    script_hex: String,
+    #[serde(default = "plutus_v2")]
+    version: TransactionVersion,
+}
+
+fn plutus_v2() -> TransactionVersion {
+    TransactionVersion::V2
+}
+
+impl Deployment {
+    /// Getter for the hex id of the deploying transaction
+    pub fn tx_id(&self) -> &str {
+        &self.tx_id
+    }
+
+    /// Getter for the index of the output holding the script
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// The output holding the script with hash `script_hash`, to use as a reference input
+    pub fn to_output<Datum>(&self, script_hash: &[u8]) -> Option<Output<Datum>> {
+        let tx_hash = hex::decode(&self.tx_id).ok()?;
+        let address = Address::from_bech32(&self.address).ok()?;
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, self.lovelace);
+        let reference_script =
+            ReferenceScript::new(script_hash.to_vec(), &self.script_hex)
+                .with_version(self.version.clone());
+        let output = Output::new_wallet(tx_hash, self.index, address, values)
+            .with_reference_script(reference_script);
+        Some(output)
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Deployments {
+    #[serde(default)]
+    networks: BTreeMap<String, BTreeMap<String, Deployment>>,
+}
+
+/// File backed record of where reference scripts were deployed, per network
+///
+/// Keyed by script hash, so once a script is deployed its spends and mints can use it by
+/// reference instead of deploying it again. The file is meant to be checked in, so everyone
+/// working on a contract shares the same deployments.
+#[derive(Debug)]
+pub struct DeploymentRegistry {
+    path: PathBuf,
+    deployments: Deployments,
+}
+
+impl DeploymentRegistry {
+    /// Load the registry at `path`, starting empty if the file doesn't exist yet
+    pub fn load(path: impl AsRef<Path>) -> DeploymentResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let deployments = match fs::read_to_string(&path) {
+            Ok(text) => {
+                toml::from_str(&text).map_err(|e| DeploymentError::Toml(e.to_string()))?
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Deployments::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(DeploymentRegistry { path, deployments })
+    }
+
+    /// Write the registry back to its file
+    pub fn save(&self) -> DeploymentResult<()> {
+        let text = toml::to_string(&self.deployments)
+            .map_err(|e| DeploymentError::Toml(e.to_string()))?;
+        fs::write(&self.path, text)?;
+        Ok(())
+    }
+
+    /// Record that `output` holds a reference script on `network`
+    ///
+    /// Replaces any earlier deployment of the same script on that network.
+    pub fn record<Datum>(
+        &mut self,
+        network: &Network,
+        output: &Output<Datum>,
+    ) -> DeploymentResult<()> {
+        let reference_script = output
+            .reference_script()
+            .ok_or_else(|| DeploymentError::NoReferenceScript(output.id().clone()))?;
+        let deployment = Deployment {
+            tx_id: hex::encode(output.id().tx_hash()),
+            index: output.id().index(),
+            address: output.owner().to_bech32().expect("Already validated"),
+            lovelace: output.values().get(&PolicyId::Lovelace).unwrap_or_default(),
+            script_hex: reference_script.script_hex().to_string(),
+            version: reference_script.version().clone(),
+        };
+        self.deployments
+            .networks
+            .entry(network_key(network))
+            .or_default()
+            .insert(hex::encode(reference_script.hash()), deployment);
+        Ok(())
+    }
+
+    /// Where the script with hash `script_hash` was deployed on `network`, if it was
+    pub fn find(&self, network: &Network, script_hash: &[u8]) -> Option<&Deployment> {
+        self.deployments
+            .networks
+            .get(&network_key(network))?
+            .get(&hex::encode(script_hash))
+    }
+
+    /// The output holding the script with hash `script_hash` on `network`
+    ///
+    /// If the script hasn't been deployed there yet, `deploy` is run to deploy it, and the
+    /// output it returns is recorded. Remember to [`save`](Self::save) the registry after.
+    pub async fn deploy_or_reuse<Datum, F, Fut, E>(
+        &mut self,
+        network: &Network,
+        script_hash: &[u8],
+        deploy: F,
+    ) -> DeploymentResult<Output<Datum>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Output<Datum>, E>>,
+        E: error::Error + Send + Sync + 'static,
+    {
+        if let Some(output) = self
+            .find(network, script_hash)
+            .and_then(|deployment| deployment.to_output(script_hash))
+        {
+            return Ok(output)
+        }
+        let output = deploy()
+            .await
+            .map_err(|e| DeploymentError::Deploy(Box::new(e)))?;
+        let deployed_hash = output
+            .reference_script()
+            .ok_or_else(|| DeploymentError::NoReferenceScript(output.id().clone()))?
+            .hash();
+        if deployed_hash != script_hash {
+            return Err(DeploymentError::WrongReferenceScript(output.id().clone()))
+        }
+        self.record(network, &output)?;
+        Ok(output)
+    }
+}
+
+// Files written before the registry took a `Network` used these names, so they still load
+fn network_key(network: &Network) -> String {
+    match network {
+        Network::Preprod => "preprod".to_string(),
+        Network::Preview => "preview".to_string(),
+        Network::Mainnet => "mainnet".to_string(),
+        Network::Custom { protocol_magic, .. } => format!("custom-{protocol_magic}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    const SCRIPT: &str =
+        "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu";
+
+    #[test]
+    fn load__reads_back_saved_deployments_per_network() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deployments.toml");
+        let script_hash = vec![3; 28];
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, 20_000_000);
+        let output: Output<()> = Output::new_wallet(
+            vec![1; 32],
+            2,
+            Address::from_bech32(SCRIPT).unwrap(),
+            values,
+        )
+        .with_reference_script(ReferenceScript::new(script_hash.clone(), "4e4d01"));
+        let mut registry = DeploymentRegistry::load(&path).unwrap();
+        registry.record(&Network::Preprod, &output).unwrap();
+        registry.save().unwrap();
+
+        // when
+        let loaded = DeploymentRegistry::load(&path).unwrap();
+
+        // then
+        let deployment = loaded.find(&Network::Preprod, &script_hash).unwrap();
+        assert_eq!(deployment.to_output::<()>(&script_hash), Some(output));
+        assert!(loaded.find(&Network::Mainnet, &script_hash).is_none());
+    }
+
+    #[tokio::test]
+    async fn deploy_or_reuse__only_deploys_once_per_network() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let script_hash = vec![3; 28];
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, 20_000_000);
+        let deployed: Output<()> = Output::new_wallet(
+            vec![1; 32],
+            0,
+            Address::from_bech32(SCRIPT).unwrap(),
+            values,
+        )
+        .with_reference_script(
+            ReferenceScript::new(script_hash.clone(), "4e4d01")
+                .with_version(TransactionVersion::V1),
+        );
+        let mut registry =
+            DeploymentRegistry::load(dir.path().join("deployments.toml")).unwrap();
+        let mut deploys = 0;
+        let mut deploy = || {
+            deploys += 1;
+            let output = deployed.clone();
+            async move { Ok::<_, io::Error>(output) }
+        };
+
+        // when
+        let first = registry
+            .deploy_or_reuse(&Network::Preprod, &script_hash, &mut deploy)
+            .await
+            .unwrap();
+        let second = registry
+            .deploy_or_reuse(&Network::Preprod, &script_hash, &mut deploy)
+            .await
+            .unwrap();
+        let other_script = registry
+            .deploy_or_reuse(&Network::Preview, &[4; 28], &mut deploy)
+            .await;
+
+        // then
+        assert_eq!(first, deployed);
+        assert_eq!(second, deployed);
+        assert!(matches!(
+            other_script,
+            Err(DeploymentError::WrongReferenceScript(_))
+        ));
+        assert_eq!(deploys, 2);
+    }
+}
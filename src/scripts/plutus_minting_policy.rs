@@ -1,8 +1,12 @@
 use crate::{
     scripts::{
         as_failed_to_execute,
+        cache::ParamApplications,
         context::TxContext,
-        plutus_validator::plutus_data::PlutusData,
+        plutus_validator::{
+            apply_param,
+            plutus_data::PlutusData,
+        },
         raw_script::{
             PlutusScriptError,
             PlutusScriptFile,
@@ -12,6 +16,7 @@ use crate::{
         ExecutionCost,
         MintingPolicy,
         ScriptError,
+        ScriptFailure,
         ScriptResult,
     },
     transaction::TransactionVersion,
@@ -45,9 +50,22 @@ use uplc::{
 pub struct PlutusMintingPolicy<Redeemer> {
     version: TransactionVersion,
     cbor: Vec<u8>,
+    name: Option<String>,
     _redeemer: PhantomData<Redeemer>,
 }
 
+// Manual impl so the redeemer type doesn't need to be `Clone`
+impl<R> Clone for PlutusMintingPolicy<R> {
+    fn clone(&self) -> Self {
+        PlutusMintingPolicy {
+            version: self.version.clone(),
+            cbor: self.cbor.clone(),
+            name: self.name.clone(),
+            _redeemer: PhantomData,
+        }
+    }
+}
+
 impl<R> PlutusMintingPolicy<R> {
     /// Constructor for new V1 [`PlutusMintingPolicy`] from a [`PlutusScriptFile`]
     pub fn new_v1(script_file: PlutusScriptFile) -> RawPlutusScriptResult<Self> {
@@ -60,6 +78,7 @@ impl<R> PlutusMintingPolicy<R> {
         let v1_policy = PlutusMintingPolicy {
             version: TransactionVersion::V1,
             cbor: outer.to_vec(),
+            name: None,
             _redeemer: Default::default(),
         };
         Ok(v1_policy)
@@ -76,6 +95,7 @@ impl<R> PlutusMintingPolicy<R> {
         let v2_policy = PlutusMintingPolicy {
             version: TransactionVersion::V2,
             cbor: outer.to_vec(),
+            name: None,
             _redeemer: Default::default(),
         };
         Ok(v2_policy)
@@ -88,6 +108,7 @@ impl<R> PlutusMintingPolicy<R> {
         let v2_policy = PlutusMintingPolicy {
             version: TransactionVersion::V2,
             cbor,
+            name: Some(blueprint.title()),
             _redeemer: Default::default(),
         };
         Ok(v2_policy)
@@ -100,20 +121,42 @@ impl<R> PlutusMintingPolicy<R> {
         let v2_policy = PlutusMintingPolicy {
             version: TransactionVersion::V2,
             cbor,
+            name: None,
             _redeemer: Default::default(),
         };
         Ok(v2_policy)
     }
+
+    /// Name the policy in failure reports, the blueprint title by default
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
 }
 
 /// Builder for [`PlutusMintingPolicy`] that requires a single parameter
 pub struct OneParamPlutusPolicy<One, Redeemer> {
     version: TransactionVersion,
     cbor: Vec<u8>,
+    name: Option<String>,
+    applied: ParamApplications,
     _one: PhantomData<One>,
     _redeemer: PhantomData<Redeemer>,
 }
 
+impl<One, R> Clone for OneParamPlutusPolicy<One, R> {
+    fn clone(&self) -> Self {
+        OneParamPlutusPolicy {
+            version: self.version.clone(),
+            cbor: self.cbor.clone(),
+            name: self.name.clone(),
+            applied: self.applied.clone(),
+            _one: PhantomData,
+            _redeemer: PhantomData,
+        }
+    }
+}
+
 impl<One: Into<PlutusData>, R> OneParamPlutusPolicy<One, R> {
     /// Constructor for new V2 [`OneParamPlutusPolicy`] from a [`PlutusScriptFile`]
     pub fn new_v2(script_file: PlutusScriptFile) -> RawPlutusScriptResult<Self> {
@@ -126,6 +169,8 @@ impl<One: Into<PlutusData>, R> OneParamPlutusPolicy<One, R> {
         let v2_val = OneParamPlutusPolicy {
             version: TransactionVersion::V2,
             cbor: outer.to_vec(),
+            name: None,
+            applied: Default::default(),
             _one: Default::default(),
             _redeemer: Default::default(),
         };
@@ -139,6 +184,8 @@ impl<One: Into<PlutusData>, R> OneParamPlutusPolicy<One, R> {
         let v2_val = OneParamPlutusPolicy {
             version: TransactionVersion::V2,
             cbor,
+            name: Some(blueprint.title()),
+            applied: Default::default(),
             _one: Default::default(),
             _redeemer: Default::default(),
         };
@@ -147,20 +194,14 @@ impl<One: Into<PlutusData>, R> OneParamPlutusPolicy<One, R> {
 
     /// Apply the singular parameter to the policy to get a complete [`PlutusMintingPolicy`]
     pub fn apply(&self, one: One) -> RawPlutusScriptResult<PlutusMintingPolicy<R>> {
-        let program: Program<NamedDeBruijn> =
-            Program::<FakeNamedDeBruijn>::from_cbor(&self.cbor, &mut Vec::new())
-                .unwrap()
-                .into();
         let one_data: PlutusData = one.into();
-        let one_term = Term::Constant(Rc::new(Constant::Data(one_data.into())));
-        let program = program.apply_term(&one_term);
-        let fake: Program<FakeNamedDeBruijn> = program.into();
-        let new_cbor = fake
-            .to_cbor()
-            .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?;
+        let (new_cbor, _) = self
+            .applied
+            .get_or_apply(&one_data, |param| apply_param(&self.cbor, param))?;
         let policy = PlutusMintingPolicy {
             version: self.version.clone(),
             cbor: new_cbor,
+            name: self.name.clone(),
             _redeemer: Default::default(),
         };
         Ok(policy)
@@ -171,11 +212,27 @@ impl<One: Into<PlutusData>, R> OneParamPlutusPolicy<One, R> {
 pub struct TwoParamMintingPolicy<One, Two, Redeemer> {
     version: TransactionVersion,
     cbor: Vec<u8>,
+    name: Option<String>,
+    applied: ParamApplications,
     _one: PhantomData<One>,
     _two: PhantomData<Two>,
     _redeemer: PhantomData<Redeemer>,
 }
 
+impl<One, Two, R> Clone for TwoParamMintingPolicy<One, Two, R> {
+    fn clone(&self) -> Self {
+        TwoParamMintingPolicy {
+            version: self.version.clone(),
+            cbor: self.cbor.clone(),
+            name: self.name.clone(),
+            applied: self.applied.clone(),
+            _one: PhantomData,
+            _two: PhantomData,
+            _redeemer: PhantomData,
+        }
+    }
+}
+
 impl<One: Into<PlutusData>, Two: Into<PlutusData>, R> TwoParamMintingPolicy<One, Two, R> {
     /// Constructor for new V2 [`TwoParamMintingPolicy`] from a [`PlutusScriptFile`]
     pub fn new_v2(script_file: PlutusScriptFile) -> RawPlutusScriptResult<Self> {
@@ -188,6 +245,8 @@ impl<One: Into<PlutusData>, Two: Into<PlutusData>, R> TwoParamMintingPolicy<One,
         let v2_pol = TwoParamMintingPolicy {
             version: TransactionVersion::V2,
             cbor: outer.to_vec(),
+            name: None,
+            applied: Default::default(),
             _one: Default::default(),
             _two: Default::default(),
             _redeemer: Default::default(),
@@ -202,6 +261,8 @@ impl<One: Into<PlutusData>, Two: Into<PlutusData>, R> TwoParamMintingPolicy<One,
         let v2_pol = TwoParamMintingPolicy {
             version: TransactionVersion::V2,
             cbor,
+            name: Some(blueprint.title()),
+            applied: Default::default(),
             _one: Default::default(),
             _two: Default::default(),
             _redeemer: Default::default(),
@@ -211,20 +272,15 @@ impl<One: Into<PlutusData>, Two: Into<PlutusData>, R> TwoParamMintingPolicy<One,
 
     /// Apply a single parameter to the policy to get a [`OneParamPlutusPolicy`]
     pub fn apply(&self, one: One) -> RawPlutusScriptResult<OneParamPlutusPolicy<Two, R>> {
-        let program: Program<NamedDeBruijn> =
-            Program::<FakeNamedDeBruijn>::from_cbor(&self.cbor, &mut Vec::new())
-                .unwrap()
-                .into();
         let one_data: PlutusData = one.into();
-        let one_term = Term::Constant(Rc::new(Constant::Data(one_data.into())));
-        let program = program.apply_term(&one_term);
-        let fake: Program<FakeNamedDeBruijn> = program.into();
-        let new_cbor = fake
-            .to_cbor()
-            .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?;
+        let (new_cbor, applied) = self
+            .applied
+            .get_or_apply(&one_data, |param| apply_param(&self.cbor, param))?;
         let policy = OneParamPlutusPolicy {
             version: self.version.clone(),
             cbor: new_cbor,
+            name: self.name.clone(),
+            applied,
             _one: Default::default(),
             _redeemer: Default::default(),
         };
@@ -242,8 +298,10 @@ where
                 .map_err(as_failed_to_execute)?
                 .into();
         let redeemer_data: PlutusData = redeemer.into();
-        let redeemer_term = Term::Constant(Rc::new(Constant::Data(redeemer_data.into())));
+        let redeemer_term =
+            Term::Constant(Rc::new(Constant::Data(redeemer_data.clone().into())));
         let program = program.apply_term(&redeemer_term);
+        let purpose = ctx.purpose.clone();
         let ctx_data: PlutusData = ctx.into();
         let ctx_term = Term::Constant(Rc::new(Constant::Data(ctx_data.into())));
         let program = program.apply_term(&ctx_term);
@@ -253,13 +311,22 @@ where
         };
         let logs = eval_result.logs();
         let cost = eval_result.cost();
-        eval_result
-            .result()
-            .map_err(|e| PlutusScriptError::AikenEval {
-                error: format!("{e:?}"),
-                logs,
-            })
-            .map_err(as_failed_to_execute)?;
+        if let Err(error) = eval_result.result() {
+            let mut failure = ScriptFailure::new(
+                purpose,
+                redeemer_data,
+                cost.into(),
+                format!("{error:?}"),
+            )
+            .with_traces(logs);
+            if let Ok(id) = self.id() {
+                failure = failure.with_hash(&id);
+            }
+            if let Some(name) = &self.name {
+                failure = failure.with_name(name);
+            }
+            return Err(ScriptError::Evaluation(Box::new(failure)))
+        }
         Ok(cost.into())
     }
 
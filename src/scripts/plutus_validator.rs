@@ -1,11 +1,13 @@
 use crate::{
     scripts::{
         as_failed_to_execute,
+        cache::ParamApplications,
         context::TxContext,
         plutus_validator::plutus_data::{
             BigInt,
             Constr,
             PlutusData,
+            GENERAL_CONSTR_TAG,
         },
         raw_script::{
             PlutusScriptError,
@@ -14,6 +16,7 @@ use crate::{
             ValidatorBlueprint,
         },
         ScriptError,
+        ScriptFailure,
         ScriptResult,
         Validator,
     },
@@ -53,10 +56,7 @@ use uplc::{
         Program,
         Term,
     },
-    machine::{
-        cost_model::ExBudget,
-        runtime::convert_constr_to_tag,
-    },
+    machine::cost_model::ExBudget,
     BigInt as AikenBigInt,
     Constr as AikenConstr,
     PlutusData as AikenPlutusData,
@@ -73,10 +73,24 @@ mod tests;
 pub struct PlutusValidator<Datum, Redeemer> {
     version: TransactionVersion,
     cbor: Vec<u8>,
+    name: Option<String>,
     _datum: PhantomData<Datum>,
     _redeemer: PhantomData<Redeemer>,
 }
 
+// Manual impl so the datum and redeemer types don't need to be `Clone`
+impl<D, R> Clone for PlutusValidator<D, R> {
+    fn clone(&self) -> Self {
+        PlutusValidator {
+            version: self.version.clone(),
+            cbor: self.cbor.clone(),
+            name: self.name.clone(),
+            _datum: PhantomData,
+            _redeemer: PhantomData,
+        }
+    }
+}
+
 impl<D, R> PlutusValidator<D, R> {
     /// Create a new V1 `PlutusValidator` from a [`PlutusScriptFile`]
     pub fn new_v1(script_file: PlutusScriptFile) -> RawPlutusScriptResult<Self> {
@@ -89,6 +103,7 @@ impl<D, R> PlutusValidator<D, R> {
         let v1_policy = PlutusValidator {
             version: TransactionVersion::V1,
             cbor: outer.to_vec(),
+            name: None,
             _datum: Default::default(),
             _redeemer: Default::default(),
         };
@@ -106,6 +121,7 @@ impl<D, R> PlutusValidator<D, R> {
         let v2_policy = PlutusValidator {
             version: TransactionVersion::V2,
             cbor: outer.to_vec(),
+            name: None,
             _datum: Default::default(),
             _redeemer: Default::default(),
         };
@@ -119,6 +135,7 @@ impl<D, R> PlutusValidator<D, R> {
         let v2_policy = PlutusValidator {
             version: TransactionVersion::V2,
             cbor,
+            name: Some(blueprint.title()),
             _datum: Default::default(),
             _redeemer: Default::default(),
         };
@@ -132,22 +149,66 @@ impl<D, R> PlutusValidator<D, R> {
         let v2_policy = PlutusValidator {
             version: TransactionVersion::V2,
             cbor,
+            name: None,
             _datum: Default::default(),
             _redeemer: Default::default(),
         };
         Ok(v2_policy)
     }
+
+    /// Name the validator in failure reports, the blueprint title by default
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    fn plutus_script(&self) -> ScriptResult<PlutusScript> {
+        let script_bytes = Encoder::new(Vec::new())
+            .bytes(&self.cbor)
+            .map_err(|e| ScriptError::IdRetrieval(e.to_string()))?
+            .clone()
+            .into_writer();
+        let script = match self.version {
+            TransactionVersion::V1 => {
+                let v1 = PlutusV1Script::from_bytes(script_bytes)
+                    .map_err(|e| ScriptError::IdRetrieval(e.to_string()))?;
+                PlutusScript::from_v1(&v1)
+            }
+            TransactionVersion::V2 => {
+                let v2 = PlutusV2Script::from_bytes(script_bytes)
+                    .map_err(|e| ScriptError::IdRetrieval(e.to_string()))?;
+                PlutusScript::from_v2(&v2)
+            }
+        };
+        Ok(script)
+    }
 }
 
 /// A builder for a `PlutusValidator` that takes one parameter
 pub struct OneParamRawValidator<One, Datum, Redeemer> {
     version: TransactionVersion,
     cbor: Vec<u8>,
+    name: Option<String>,
+    applied: ParamApplications,
     _one: PhantomData<One>,
     _datum: PhantomData<Datum>,
     _redeemer: PhantomData<Redeemer>,
 }
 
+impl<One, D, R> Clone for OneParamRawValidator<One, D, R> {
+    fn clone(&self) -> Self {
+        OneParamRawValidator {
+            version: self.version.clone(),
+            cbor: self.cbor.clone(),
+            name: self.name.clone(),
+            applied: self.applied.clone(),
+            _one: PhantomData,
+            _datum: PhantomData,
+            _redeemer: PhantomData,
+        }
+    }
+}
+
 impl<One: Into<PlutusData>, D, R> OneParamRawValidator<One, D, R> {
     /// Create a new V2 `OneParamRawValidator` from a [`PlutusScriptFile`]
     pub fn new_v2(script_file: PlutusScriptFile) -> RawPlutusScriptResult<Self> {
@@ -160,6 +221,8 @@ impl<One: Into<PlutusData>, D, R> OneParamRawValidator<One, D, R> {
         let v2_val = OneParamRawValidator {
             version: TransactionVersion::V2,
             cbor: outer.to_vec(),
+            name: None,
+            applied: Default::default(),
             _one: Default::default(),
             _datum: Default::default(),
             _redeemer: Default::default(),
@@ -174,6 +237,8 @@ impl<One: Into<PlutusData>, D, R> OneParamRawValidator<One, D, R> {
         let v2_val = OneParamRawValidator {
             version: TransactionVersion::V2,
             cbor,
+            name: Some(blueprint.title()),
+            applied: Default::default(),
             _one: Default::default(),
             _datum: Default::default(),
             _redeemer: Default::default(),
@@ -183,20 +248,14 @@ impl<One: Into<PlutusData>, D, R> OneParamRawValidator<One, D, R> {
 
     /// Apply the parameter to the validator to get a full [`PlutusValidator`]
     pub fn apply(&self, one: One) -> RawPlutusScriptResult<PlutusValidator<D, R>> {
-        let program: Program<NamedDeBruijn> =
-            Program::<FakeNamedDeBruijn>::from_cbor(&self.cbor, &mut Vec::new())
-                .unwrap()
-                .into();
         let one_data: PlutusData = one.into();
-        let one_term = Term::Constant(Rc::new(Constant::Data(one_data.into())));
-        let program = program.apply_term(&one_term);
-        let fake: Program<FakeNamedDeBruijn> = program.into();
-        let new_cbor = fake
-            .to_cbor()
-            .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?;
+        let (new_cbor, _) = self
+            .applied
+            .get_or_apply(&one_data, |param| apply_param(&self.cbor, param))?;
         let policy = PlutusValidator {
             version: self.version.clone(),
             cbor: new_cbor,
+            name: self.name.clone(),
             _datum: Default::default(),
             _redeemer: Default::default(),
         };
@@ -204,6 +263,22 @@ impl<One: Into<PlutusData>, D, R> OneParamRawValidator<One, D, R> {
     }
 }
 
+/// Apply `param` to the program in `cbor`, returning the CBOR of the resulting program
+pub(crate) fn apply_param(
+    cbor: &[u8],
+    param: &PlutusData,
+) -> RawPlutusScriptResult<Vec<u8>> {
+    let program: Program<NamedDeBruijn> =
+        Program::<FakeNamedDeBruijn>::from_cbor(cbor, &mut Vec::new())
+            .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?
+            .into();
+    let param_term = Term::Constant(Rc::new(Constant::Data(param.clone().into())));
+    let program = program.apply_term(&param_term);
+    let fake: Program<FakeNamedDeBruijn> = program.into();
+    fake.to_cbor()
+        .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))
+}
+
 impl From<PlutusData> for AikenPlutusData {
     fn from(data: PlutusData) -> Self {
         match data {
@@ -229,13 +304,10 @@ impl From<PlutusData> for AikenPlutusData {
 
 impl From<Constr<PlutusData>> for AikenConstr<AikenPlutusData> {
     fn from(constr: Constr<PlutusData>) -> Self {
-        let tag = convert_constr_to_tag(constr.constr);
+        let tag = constr.tag();
         AikenConstr {
-            tag: tag.unwrap_or(102),
-            any_constructor: match tag {
-                Some(_) => None,
-                None => Some(constr.constr),
-            },
+            tag,
+            any_constructor: (tag == GENERAL_CONSTR_TAG).then_some(constr.constr),
             fields: constr.fields.into_iter().map(Into::into).collect(),
         }
     }
@@ -274,12 +346,14 @@ where
                 .map_err(as_failed_to_execute)?
                 .into();
         let datum_data: PlutusData = datum.into();
-        let aiken_datum_data: uplc::PlutusData = datum_data.into();
+        let aiken_datum_data: uplc::PlutusData = datum_data.clone().into();
         let datum_term = Term::Constant(Rc::new(Constant::Data(aiken_datum_data)));
         let program = program.apply_term(&datum_term);
         let redeemer_data: PlutusData = redeemer.into();
-        let redeemer_term = Term::Constant(Rc::new(Constant::Data(redeemer_data.into())));
+        let redeemer_term =
+            Term::Constant(Rc::new(Constant::Data(redeemer_data.clone().into())));
         let program = program.apply_term(&redeemer_term);
+        let purpose = ctx.purpose.clone();
         let ctx_data: PlutusData = ctx.into();
         let ctx_term = Term::Constant(Rc::new(Constant::Data(ctx_data.into())));
         let program = program.apply_term(&ctx_term);
@@ -289,13 +363,23 @@ where
         };
         let logs = eval_result.logs();
         let cost = eval_result.cost();
-        eval_result
-            .result()
-            .map_err(|e| PlutusScriptError::AikenEval {
-                error: format!("{e:?}"),
-                logs,
-            })
-            .map_err(as_failed_to_execute)?;
+        if let Err(error) = eval_result.result() {
+            let mut failure = ScriptFailure::new(
+                purpose,
+                redeemer_data,
+                cost.into(),
+                format!("{error:?}"),
+            )
+            .with_datum(datum_data)
+            .with_traces(logs);
+            if let Ok(script) = self.plutus_script() {
+                failure = failure.with_hash(&script.hash().to_string());
+            }
+            if let Some(name) = &self.name {
+                failure = failure.with_name(name);
+            }
+            return Err(ScriptError::Evaluation(Box::new(failure)))
+        }
         Ok(cost.into())
     }
 
@@ -306,23 +390,7 @@ where
             Network::Mainnet => 1,
             Network::Other(inner) => inner,
         };
-        let cbor = self.script_hex().unwrap(); // TODO
-        let script = match self.version {
-            TransactionVersion::V1 => {
-                let script_bytes = hex::decode(&cbor)
-                    .map_err(|e| ScriptError::IdRetrieval(e.to_string()))?;
-                let v1 = PlutusV1Script::from_bytes(script_bytes)
-                    .map_err(|e| ScriptError::IdRetrieval(e.to_string()))?;
-                PlutusScript::from_v1(&v1)
-            }
-            TransactionVersion::V2 => {
-                let script_bytes = hex::decode(&cbor)
-                    .map_err(|e| ScriptError::IdRetrieval(e.to_string()))?;
-                let v2 = PlutusV2Script::from_bytes(script_bytes)
-                    .map_err(|e| ScriptError::IdRetrieval(e.to_string()))?;
-                PlutusScript::from_v2(&v2)
-            }
-        };
+        let script = self.plutus_script()?;
         let script_hash = script.hash();
         let stake_cred = StakeCredential::from_scripthash(&script_hash);
         let enterprise_addr = EnterpriseAddress::new(network_index, &stake_cred);
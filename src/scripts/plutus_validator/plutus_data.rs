@@ -22,7 +22,22 @@ use serde::{
     Deserialize,
     Serialize,
 };
-use std::collections::BTreeMap;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+};
+
+mod cbor;
+mod json;
+mod pretty;
+
+pub use json::DetailedJson;
+pub use pretty::{
+    diff,
+    PathSegment,
+    PlutusDataDiff,
+};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
 pub enum PlutusData {
@@ -53,12 +68,42 @@ impl PlutusData {
     }
 }
 
+/// CBOR tag of constructors whose index doesn't have a compact tag of its own
+pub const GENERAL_CONSTR_TAG: u64 = 102;
+
+/// Constructor of a sum type. `constr` is the constructor index, which is translated to
+/// and from its CBOR tag when encoding and decoding.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
 pub struct Constr<T> {
     pub constr: u64,
     pub fields: Vec<T>,
 }
 
+impl<T> Constr<T> {
+    /// CBOR tag this constructor is encoded with: 121-127 for indices 0-6, 1280-1400 for
+    /// indices 7-127 and [`GENERAL_CONSTR_TAG`] for the rest, which then also encode
+    /// their index
+    pub fn tag(&self) -> u64 {
+        match self.constr {
+            0..=6 => 121 + self.constr,
+            7..=127 => 1280 + self.constr - 7,
+            _ => GENERAL_CONSTR_TAG,
+        }
+    }
+
+    /// Constructor index of a compact constructor `tag`, `None` for
+    /// [`GENERAL_CONSTR_TAG`] and tags that aren't constructors
+    pub fn index_from_tag(tag: u64) -> Option<u64> {
+        match tag {
+            121..=127 => Some(tag - 121),
+            1280..=1400 => Some(tag - 1280 + 7),
+            _ => None,
+        }
+    }
+}
+
+/// `BigUInt` holds the big-endian bytes of the value and `BigNInt` those of `-1 - value`,
+/// matching CBOR bignums. Values whose magnitude fits in a `u64` are kept as `Int`.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
 pub enum BigInt {
     Int { neg: bool, val: u64 },
@@ -66,6 +111,40 @@ pub enum BigInt {
     BigNInt(Vec<u8>),
 }
 
+impl BigInt {
+    // Sign and big-endian magnitude without leading zeros
+    fn to_magnitude(&self) -> (bool, Vec<u8>) {
+        match self {
+            BigInt::Int { neg, val } => {
+                (*neg && *val != 0, trim_zeros(&val.to_be_bytes()))
+            }
+            BigInt::BigUInt(bytes) => (false, trim_zeros(bytes)),
+            BigInt::BigNInt(bytes) => (true, increment(&trim_zeros(bytes))),
+        }
+    }
+
+    fn from_magnitude(neg: bool, magnitude: &[u8]) -> BigInt {
+        let magnitude = trim_zeros(magnitude);
+        if magnitude.len() <= 8 {
+            let mut buf = [0u8; 8];
+            buf[8 - magnitude.len()..].copy_from_slice(&magnitude);
+            let val = u64::from_be_bytes(buf);
+            BigInt::Int {
+                neg: neg && val != 0,
+                val,
+            }
+        } else if neg {
+            BigInt::BigNInt(decrement(&magnitude))
+        } else {
+            BigInt::BigUInt(magnitude)
+        }
+    }
+
+    fn out_of_range(&self, target: &str) -> ScriptError {
+        ScriptError::DatumDeserialization(format!("{self} doesn't fit in {target}"))
+    }
+}
+
 impl From<i64> for BigInt {
     fn from(num: i64) -> Self {
         let neg = num.is_negative();
@@ -74,38 +153,162 @@ impl From<i64> for BigInt {
     }
 }
 
-impl From<&BigInt> for i64 {
-    fn from(big_int: &BigInt) -> Self {
-        match big_int {
-            BigInt::Int { neg, val } => {
-                let value = (*val) as i64;
-                if *neg {
-                    -value
-                } else {
-                    value
-                }
-            }
-            BigInt::BigUInt(_) => todo!(),
-            BigInt::BigNInt(_) => todo!(),
+impl From<u64> for BigInt {
+    fn from(val: u64) -> Self {
+        BigInt::Int { neg: false, val }
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(num: i128) -> Self {
+        BigInt::from_magnitude(num.is_negative(), &num.unsigned_abs().to_be_bytes())
+    }
+}
+
+impl TryFrom<&BigInt> for i128 {
+    type Error = ScriptError;
+
+    fn try_from(big_int: &BigInt) -> Result<Self, Self::Error> {
+        let (neg, magnitude) = big_int.to_magnitude();
+        if magnitude.len() > 16 {
+            return Err(big_int.out_of_range("i128"))
+        }
+        let mut buf = [0u8; 16];
+        buf[16 - magnitude.len()..].copy_from_slice(&magnitude);
+        let magnitude = u128::from_be_bytes(buf);
+        if neg {
+            0i128
+                .checked_sub_unsigned(magnitude)
+                .ok_or_else(|| big_int.out_of_range("i128"))
+        } else {
+            i128::try_from(magnitude).map_err(|_| big_int.out_of_range("i128"))
         }
     }
 }
 
-impl From<BigInt> for i64 {
-    fn from(big_int: BigInt) -> Self {
-        match big_int {
-            BigInt::Int { neg, val } => {
-                let value = val as i64;
-                if neg {
-                    -value
-                } else {
-                    value
-                }
+impl TryFrom<&BigInt> for i64 {
+    type Error = ScriptError;
+
+    fn try_from(big_int: &BigInt) -> Result<Self, Self::Error> {
+        let num = i128::try_from(big_int)?;
+        i64::try_from(num).map_err(|_| big_int.out_of_range("i64"))
+    }
+}
+
+impl TryFrom<BigInt> for i64 {
+    type Error = ScriptError;
+
+    fn try_from(big_int: BigInt) -> Result<Self, Self::Error> {
+        i64::try_from(&big_int)
+    }
+}
+
+impl TryFrom<&BigInt> for u64 {
+    type Error = ScriptError;
+
+    fn try_from(big_int: &BigInt) -> Result<Self, Self::Error> {
+        let num = i128::try_from(big_int)?;
+        u64::try_from(num).map_err(|_| big_int.out_of_range("u64"))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (neg, magnitude) = self.to_magnitude();
+        if neg {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", to_decimal(&magnitude))
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (neg, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ScriptError::DatumDeserialization(format!(
+                "{s:?} isn't an integer"
+            )))
+        }
+        Ok(BigInt::from_magnitude(neg, &from_decimal(digits)))
+    }
+}
+
+fn trim_zeros(bytes: &[u8]) -> Vec<u8> {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    bytes[start..].to_vec()
+}
+
+fn increment(magnitude: &[u8]) -> Vec<u8> {
+    let mut bytes = magnitude.to_vec();
+    for byte in bytes.iter_mut().rev() {
+        let (sum, overflow) = byte.overflowing_add(1);
+        *byte = sum;
+        if !overflow {
+            return bytes
+        }
+    }
+    bytes.insert(0, 1);
+    bytes
+}
+
+// Only called with non-zero magnitudes
+fn decrement(magnitude: &[u8]) -> Vec<u8> {
+    let mut bytes = magnitude.to_vec();
+    for byte in bytes.iter_mut().rev() {
+        let (difference, underflow) = byte.overflowing_sub(1);
+        *byte = difference;
+        if !underflow {
+            break
+        }
+    }
+    trim_zeros(&bytes)
+}
+
+fn to_decimal(magnitude: &[u8]) -> String {
+    let mut digits = Vec::new();
+    let mut remaining = trim_zeros(magnitude);
+    while !remaining.is_empty() {
+        let mut quotient = Vec::with_capacity(remaining.len());
+        let mut rem = 0u16;
+        for byte in remaining {
+            let current = (rem << 8) | u16::from(byte);
+            let digit = current / 10;
+            rem = current % 10;
+            if !(quotient.is_empty() && digit == 0) {
+                quotient.push(digit as u8);
             }
-            BigInt::BigUInt(_) => todo!(),
-            BigInt::BigNInt(_) => todo!(),
+        }
+        digits.push(b'0' + rem as u8);
+        remaining = quotient;
+    }
+    if digits.is_empty() {
+        return "0".to_string()
+    }
+    digits.reverse();
+    String::from_utf8(digits).expect("only ASCII digits")
+}
+
+// Expects only ASCII digits
+fn from_decimal(digits: &str) -> Vec<u8> {
+    let mut magnitude: Vec<u8> = Vec::new();
+    for digit in digits.bytes() {
+        let mut carry = u16::from(digit - b'0');
+        for byte in magnitude.iter_mut().rev() {
+            let current = u16::from(*byte) * 10 + carry;
+            *byte = current as u8;
+            carry = current >> 8;
+        }
+        if carry > 0 {
+            magnitude.insert(0, carry as u8);
         }
     }
+    magnitude
 }
 
 impl From<i64> for PlutusData {
@@ -121,7 +324,7 @@ impl TryFrom<PlutusData> for i64 {
 
     fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
         match data {
-            PlutusData::BigInt(inner) => Ok(inner.into()),
+            PlutusData::BigInt(inner) => i64::try_from(&inner),
             _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
         }
     }
@@ -132,7 +335,8 @@ impl TryFrom<PlutusData> for i64 {
 impl From<TxContext> for PlutusData {
     fn from(ctx: TxContext) -> Self {
         let inputs = PlutusData::Array(ctx.inputs.into_iter().map(Into::into).collect());
-        let reference_inputs = PlutusData::Array(vec![]);
+        let reference_inputs =
+            PlutusData::Array(ctx.reference_inputs.into_iter().map(Into::into).collect());
         let outputs =
             PlutusData::Array(ctx.outputs.into_iter().map(Into::into).collect());
         let fee = PlutusData::Map(BTreeMap::from([(
@@ -266,30 +470,25 @@ impl From<Address> for PlutusData {
 
 fn wrap_with_constr(index: u64, data: PlutusData) -> PlutusData {
     PlutusData::Constr(Constr {
-        constr: constr_index(index),
+        constr: index,
         fields: vec![data],
     })
 }
 
 fn wrap_multiple_with_constr(index: u64, data: Vec<PlutusData>) -> PlutusData {
     PlutusData::Constr(Constr {
-        constr: constr_index(index),
+        constr: index,
         fields: data,
     })
 }
 
 fn empty_constr(index: u64) -> PlutusData {
     PlutusData::Constr(Constr {
-        constr: constr_index(index),
+        constr: index,
         fields: vec![],
     })
 }
 
-/// Translate constructor index to cbor tag.
-fn constr_index(index: u64) -> u64 {
-    index
-}
-
 impl From<ValidRange> for PlutusData {
     fn from(value: ValidRange) -> Self {
         match (value.lower, value.upper) {
@@ -412,7 +611,7 @@ impl From<CtxOutputReference> for PlutusData {
     fn from(out_ref: CtxOutputReference) -> Self {
         let tx_id_bytes = out_ref.transaction_id;
         let transaction_id = wrap_with_constr(0, PlutusData::BoundedBytes(tx_id_bytes));
-        let output_index = PlutusData::BigInt((out_ref.output_index as i64).into()); // TODO: panic
+        let output_index = PlutusData::BigInt(out_ref.output_index.into());
         PlutusData::Constr(Constr {
             constr: 0,
             fields: vec![transaction_id, output_index],
@@ -443,8 +642,8 @@ impl From<CtxValue> for PlutusData {
                 let assets = a
                     .iter()
                     .map(|(an, amt)| {
-                        let asset_name = PlutusData::BoundedBytes(an.as_bytes().to_vec()); // TODO: Should this be bytes? or hex decoded?
-                        let amount = PlutusData::BigInt((*amt as i64).into()); // TODO
+                        let asset_name = PlutusData::BoundedBytes(an.to_owned());
+                        let amount = PlutusData::BigInt((*amt).into());
                         (asset_name, amount)
                     })
                     .collect();
@@ -512,6 +711,264 @@ impl From<PlutusData> for () {
 
 impl From<u64> for PlutusData {
     fn from(value: u64) -> Self {
-        PlutusData::BigInt((value as i64).into()) // TODO: unwrap
+        PlutusData::BigInt(value.into())
+    }
+}
+
+impl TryFrom<PlutusData> for u64 {
+    type Error = ScriptError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        match data {
+            PlutusData::BigInt(inner) => u64::try_from(&inner),
+            _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
+        }
+    }
+}
+
+impl TryFrom<PlutusData> for Vec<u8> {
+    type Error = ScriptError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        match data {
+            PlutusData::BoundedBytes(bytes) => Ok(bytes),
+            _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
+        }
+    }
+}
+
+/// Same as Aiken's `Bool`: `False` is constructor 0 and `True` is constructor 1
+impl From<bool> for PlutusData {
+    fn from(value: bool) -> Self {
+        empty_constr(u64::from(value))
+    }
+}
+
+impl TryFrom<PlutusData> for bool {
+    type Error = ScriptError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        match &data {
+            PlutusData::Constr(Constr { constr: 0, fields }) if fields.is_empty() => {
+                Ok(false)
+            }
+            PlutusData::Constr(Constr { constr: 1, fields }) if fields.is_empty() => {
+                Ok(true)
+            }
+            _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
+        }
+    }
+}
+
+/// Strings are stored as their UTF-8 bytes, like Aiken's `String` in data
+impl From<String> for PlutusData {
+    fn from(value: String) -> Self {
+        PlutusData::BoundedBytes(value.into_bytes())
+    }
+}
+
+impl From<&str> for PlutusData {
+    fn from(value: &str) -> Self {
+        PlutusData::BoundedBytes(value.as_bytes().to_vec())
+    }
+}
+
+impl TryFrom<PlutusData> for String {
+    type Error = ScriptError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        match data {
+            PlutusData::BoundedBytes(bytes) => String::from_utf8(bytes)
+                .map_err(|e| ScriptError::DatumDeserialization(e.to_string())),
+            _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
+        }
+    }
+}
+
+/// Lists of anything but bytes, which convert to `BoundedBytes`
+impl<T: Into<PlutusData>> From<Vec<T>> for PlutusData {
+    fn from(value: Vec<T>) -> Self {
+        PlutusData::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T> TryFrom<PlutusData> for Vec<T>
+where
+    T: TryFrom<PlutusData, Error = ScriptError>,
+{
+    type Error = ScriptError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        match data {
+            PlutusData::Array(items) => items.into_iter().map(T::try_from).collect(),
+            _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
+        }
+    }
+}
+
+impl<K: Into<PlutusData>, V: Into<PlutusData>> From<BTreeMap<K, V>> for PlutusData {
+    fn from(value: BTreeMap<K, V>) -> Self {
+        PlutusData::Map(
+            value
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl<K, V> TryFrom<PlutusData> for BTreeMap<K, V>
+where
+    K: TryFrom<PlutusData, Error = ScriptError> + Ord,
+    V: TryFrom<PlutusData, Error = ScriptError>,
+{
+    type Error = ScriptError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        match data {
+            PlutusData::Map(map) => map
+                .into_iter()
+                .map(|(key, value)| Ok((K::try_from(key)?, V::try_from(value)?)))
+                .collect(),
+            _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
+        }
+    }
+}
+
+/// Pairs are 2 element lists, like Aiken's tuples
+impl<A: Into<PlutusData>, B: Into<PlutusData>> From<(A, B)> for PlutusData {
+    fn from((a, b): (A, B)) -> Self {
+        PlutusData::Array(vec![a.into(), b.into()])
+    }
+}
+
+impl<A, B> TryFrom<PlutusData> for (A, B)
+where
+    A: TryFrom<PlutusData, Error = ScriptError>,
+    B: TryFrom<PlutusData, Error = ScriptError>,
+{
+    type Error = ScriptError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        match data {
+            PlutusData::Array(items) if items.len() == 2 => {
+                let mut items = items.into_iter();
+                let a = A::try_from(items.next().expect("checked length"))?;
+                let b = B::try_from(items.next().expect("checked length"))?;
+                Ok((a, b))
+            }
+            _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
+        }
+    }
+}
+
+impl<A, B, C> From<(A, B, C)> for PlutusData
+where
+    A: Into<PlutusData>,
+    B: Into<PlutusData>,
+    C: Into<PlutusData>,
+{
+    fn from((a, b, c): (A, B, C)) -> Self {
+        PlutusData::Array(vec![a.into(), b.into(), c.into()])
+    }
+}
+
+impl<A, B, C> TryFrom<PlutusData> for (A, B, C)
+where
+    A: TryFrom<PlutusData, Error = ScriptError>,
+    B: TryFrom<PlutusData, Error = ScriptError>,
+    C: TryFrom<PlutusData, Error = ScriptError>,
+{
+    type Error = ScriptError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        match data {
+            PlutusData::Array(items) if items.len() == 3 => {
+                let mut items = items.into_iter();
+                let a = A::try_from(items.next().expect("checked length"))?;
+                let b = B::try_from(items.next().expect("checked length"))?;
+                let c = C::try_from(items.next().expect("checked length"))?;
+                Ok((a, b, c))
+            }
+            _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn big_int__uses_bignums_beyond_u64() {
+        // given
+        let big = "18446744073709551616"; // u64::MAX + 1
+        let big_negative = "-18446744073709551617";
+
+        // when
+        let positive: BigInt = big.parse().unwrap();
+        let negative: BigInt = big_negative.parse().unwrap();
+
+        // then
+        assert_eq!(positive, BigInt::BigUInt(vec![1, 0, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(negative, BigInt::BigNInt(vec![1, 0, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(positive.to_string(), big);
+        assert_eq!(negative.to_string(), big_negative);
+    }
+
+    #[test]
+    fn try_from__rejects_values_out_of_range() {
+        let big_int = BigInt::from(i128::from(i64::MAX) + 1);
+
+        assert!(i64::try_from(&big_int).is_err());
+        assert_eq!(u64::try_from(&big_int), Ok(i64::MAX as u64 + 1));
+        assert!(u64::try_from(&BigInt::from(-1_i64)).is_err());
+    }
+
+    #[test]
+    fn std_types__round_trip_through_plutus_data() {
+        // given
+        let map = BTreeMap::from([
+            ("one".to_string(), vec![(1_u64, true)]),
+            ("two".to_string(), vec![(2, false), (3, true)]),
+        ]);
+
+        // when
+        let data: PlutusData = map.clone().into();
+        let round_tripped = BTreeMap::<String, Vec<(u64, bool)>>::try_from(data).unwrap();
+
+        // then
+        assert_eq!(round_tripped, map);
+    }
+
+    #[test]
+    fn bool__uses_aiken_constructors() {
+        assert_eq!(PlutusData::from(true), empty_constr(1));
+        assert_eq!(bool::try_from(empty_constr(0)), Ok(false));
+        assert!(bool::try_from(empty_constr(2)).is_err());
+    }
+
+    #[test]
+    fn constr_tag__round_trips_compact_tags() {
+        for constr in [0, 6, 7, 127] {
+            let tag = Constr::<PlutusData> {
+                constr,
+                fields: vec![],
+            }
+            .tag();
+
+            assert_eq!(Constr::<PlutusData>::index_from_tag(tag), Some(constr));
+        }
+        let general = Constr::<PlutusData> {
+            constr: 128,
+            fields: vec![],
+        };
+        assert_eq!(general.tag(), GENERAL_CONSTR_TAG);
+        assert_eq!(
+            Constr::<PlutusData>::index_from_tag(GENERAL_CONSTR_TAG),
+            None
+        );
     }
 }
@@ -0,0 +1,470 @@
+use super::{
+    BigInt,
+    Constr,
+    PlutusData,
+    GENERAL_CONSTR_TAG,
+};
+use crate::scripts::{
+    ScriptError,
+    ScriptResult,
+};
+use std::collections::BTreeMap;
+
+// Longest chunk of a byte string the ledger accepts in PlutusData
+const BYTES_CHUNK_SIZE: usize = 64;
+
+// Deepest nesting of lists, maps and constructors decoded, so hostile CBOR can't overflow
+// the stack
+const MAX_NESTING_DEPTH: usize = 256;
+
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const BYTES: u8 = 2;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const TAG: u8 = 6;
+const SIMPLE: u8 = 7;
+
+const INDEFINITE: u8 = 31;
+const BREAK: u8 = 0xff;
+
+const POS_BIGNUM_TAG: u64 = 2;
+const NEG_BIGNUM_TAG: u64 = 3;
+
+impl PlutusData {
+    /// Encode as CBOR the way the ledger and Aiken do
+    ///
+    /// Non-empty lists and constructor fields are indefinite-length arrays and byte strings
+    /// longer than 64 bytes are split into 64 byte chunks.
+    pub fn to_cbor_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        encode(self, &mut bytes);
+        bytes
+    }
+
+    /// Decode from CBOR, accepting definite and indefinite-length encodings
+    pub fn from_cbor_bytes(bytes: &[u8]) -> ScriptResult<PlutusData> {
+        let mut decoder = Decoder {
+            bytes,
+            position: 0,
+            depth: 0,
+        };
+        let data = decoder.data()?;
+        if decoder.position != bytes.len() {
+            return Err(decoder.error("trailing bytes"))
+        }
+        Ok(data)
+    }
+}
+
+fn encode(data: &PlutusData, out: &mut Vec<u8>) {
+    match data {
+        PlutusData::Constr(constr) => {
+            let tag = constr.tag();
+            write_head(TAG, tag, out);
+            if tag == GENERAL_CONSTR_TAG {
+                write_head(ARRAY, 2, out);
+                write_head(UNSIGNED, constr.constr, out);
+            }
+            encode_list(&constr.fields, out);
+        }
+        PlutusData::Map(map) => {
+            write_head(MAP, map.len() as u64, out);
+            for (key, value) in map.iter() {
+                encode(key, out);
+                encode(value, out);
+            }
+        }
+        PlutusData::BigInt(BigInt::Int { neg, val }) => {
+            if *neg && *val != 0 {
+                write_head(NEGATIVE, val - 1, out);
+            } else {
+                write_head(UNSIGNED, *val, out);
+            }
+        }
+        PlutusData::BigInt(BigInt::BigUInt(bytes)) => match small_bignum(bytes) {
+            Some(val) => write_head(UNSIGNED, val, out),
+            None => {
+                write_head(TAG, POS_BIGNUM_TAG, out);
+                encode_bytes(bytes, out);
+            }
+        },
+        PlutusData::BigInt(BigInt::BigNInt(bytes)) => match small_bignum(bytes) {
+            Some(n) if n < u64::MAX => write_head(NEGATIVE, n, out),
+            _ => {
+                write_head(TAG, NEG_BIGNUM_TAG, out);
+                encode_bytes(bytes, out);
+            }
+        },
+        PlutusData::BoundedBytes(bytes) => encode_bytes(bytes, out),
+        PlutusData::Array(items) => encode_list(items, out),
+    }
+}
+
+fn encode_list(items: &[PlutusData], out: &mut Vec<u8>) {
+    if items.is_empty() {
+        write_head(ARRAY, 0, out);
+    } else {
+        out.push((ARRAY << 5) | INDEFINITE);
+        for item in items {
+            encode(item, out);
+        }
+        out.push(BREAK);
+    }
+}
+
+fn encode_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    if bytes.len() <= BYTES_CHUNK_SIZE {
+        write_head(BYTES, bytes.len() as u64, out);
+        out.extend_from_slice(bytes);
+    } else {
+        out.push((BYTES << 5) | INDEFINITE);
+        for chunk in bytes.chunks(BYTES_CHUNK_SIZE) {
+            write_head(BYTES, chunk.len() as u64, out);
+            out.extend_from_slice(chunk);
+        }
+        out.push(BREAK);
+    }
+}
+
+fn write_head(major: u8, value: u64, out: &mut Vec<u8>) {
+    let major = major << 5;
+    match value {
+        0..=23 => out.push(major | value as u8),
+        24..=0xff => out.extend([major | 24, value as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend((value as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend((value as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend(value.to_be_bytes());
+        }
+    }
+}
+
+// Bignum bytes as a `u64`, when they fit in one
+fn small_bignum(bytes: &[u8]) -> Option<u64> {
+    let leading_zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    let significant = &bytes[leading_zeros..];
+    if significant.len() > 8 {
+        return None
+    }
+    Some(
+        significant
+            .iter()
+            .fold(0, |val, byte| (val << 8) | u64::from(*byte)),
+    )
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    depth: usize,
+}
+
+// Argument of a CBOR head: a value, or the start of an indefinite-length item
+enum Argument {
+    Value(u64),
+    Indefinite,
+}
+
+impl<'a> Decoder<'a> {
+    fn error(&self, message: &str) -> ScriptError {
+        ScriptError::DatumDeserialization(format!(
+            "Invalid PlutusData CBOR at byte {}: {message}",
+            self.position
+        ))
+    }
+
+    fn peek(&self) -> ScriptResult<u8> {
+        self.bytes
+            .get(self.position)
+            .copied()
+            .ok_or_else(|| self.error("unexpected end of input"))
+    }
+
+    fn take(&mut self, len: usize) -> ScriptResult<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| self.error("unexpected end of input"))?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn at_break(&mut self) -> ScriptResult<bool> {
+        if self.peek()? == BREAK {
+            self.position += 1;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn head(&mut self) -> ScriptResult<(u8, Argument)> {
+        let initial = self.take(1)?[0];
+        let major = initial >> 5;
+        let argument = match initial & 0x1f {
+            info @ 0..=23 => Argument::Value(u64::from(info)),
+            24 => Argument::Value(u64::from(self.take(1)?[0])),
+            25 => Argument::Value(u64::from(u16::from_be_bytes(self.fixed()?))),
+            26 => Argument::Value(u64::from(u32::from_be_bytes(self.fixed()?))),
+            27 => Argument::Value(u64::from_be_bytes(self.fixed()?)),
+            INDEFINITE => Argument::Indefinite,
+            _ => return Err(self.error("reserved additional info")),
+        };
+        Ok((major, argument))
+    }
+
+    fn fixed<const N: usize>(&mut self) -> ScriptResult<[u8; N]> {
+        let bytes = self.take(N)?;
+        Ok(bytes.try_into().expect("took exactly N bytes"))
+    }
+
+    fn length(&self, argument: u64) -> ScriptResult<usize> {
+        usize::try_from(argument).map_err(|_| self.error("length too large"))
+    }
+
+    fn data(&mut self) -> ScriptResult<PlutusData> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(self.error("nested too deeply"))
+        }
+        self.depth += 1;
+        let data = self.item();
+        self.depth -= 1;
+        data
+    }
+
+    fn item(&mut self) -> ScriptResult<PlutusData> {
+        let (major, argument) = self.head()?;
+        match (major, argument) {
+            (UNSIGNED, Argument::Value(val)) => {
+                Ok(PlutusData::BigInt(BigInt::Int { neg: false, val }))
+            }
+            (NEGATIVE, Argument::Value(n)) => match n.checked_add(1) {
+                Some(val) => Ok(PlutusData::BigInt(BigInt::Int { neg: true, val })),
+                // -2^64 doesn't fit in `Int`
+                None => Ok(PlutusData::BigInt(BigInt::BigNInt(
+                    n.to_be_bytes().to_vec(),
+                ))),
+            },
+            (BYTES, argument) => Ok(PlutusData::BoundedBytes(self.bytes_body(argument)?)),
+            (ARRAY, argument) => Ok(PlutusData::Array(self.list_body(argument)?)),
+            (MAP, argument) => {
+                let mut map = BTreeMap::new();
+                match argument {
+                    Argument::Value(len) => {
+                        for _ in 0..len {
+                            let key = self.data()?;
+                            map.insert(key, self.data()?);
+                        }
+                    }
+                    Argument::Indefinite => {
+                        while !self.at_break()? {
+                            let key = self.data()?;
+                            map.insert(key, self.data()?);
+                        }
+                    }
+                }
+                Ok(PlutusData::Map(map))
+            }
+            (TAG, Argument::Value(tag)) => self.tagged(tag),
+            (SIMPLE, _) => Err(self.error("floats and simple values aren't PlutusData")),
+            _ => Err(self.error("unexpected indefinite length")),
+        }
+    }
+
+    fn tagged(&mut self, tag: u64) -> ScriptResult<PlutusData> {
+        let constr = |constr, fields| PlutusData::Constr(Constr { constr, fields });
+        match tag {
+            POS_BIGNUM_TAG | NEG_BIGNUM_TAG => {
+                let (major, argument) = self.head()?;
+                if major != BYTES {
+                    return Err(self.error("expected bignum bytes"))
+                }
+                let bytes = self.bytes_body(argument)?;
+                // Bignums that fit an `Int` are decoded as one, like their canonical encoding
+                let big_int = match (tag, small_bignum(&bytes)) {
+                    (POS_BIGNUM_TAG, Some(val)) => BigInt::Int { neg: false, val },
+                    (POS_BIGNUM_TAG, None) => BigInt::BigUInt(bytes),
+                    (_, Some(n)) if n < u64::MAX => BigInt::Int {
+                        neg: true,
+                        val: n + 1,
+                    },
+                    _ => BigInt::BigNInt(bytes),
+                };
+                Ok(PlutusData::BigInt(big_int))
+            }
+            GENERAL_CONSTR_TAG => {
+                let (major, argument) = self.head()?;
+                if major != ARRAY || !matches!(argument, Argument::Value(2)) {
+                    return Err(self.error("expected [constructor, fields]"))
+                }
+                match self.head()? {
+                    (UNSIGNED, Argument::Value(index)) => {
+                        Ok(constr(index, self.fields()?))
+                    }
+                    _ => Err(self.error("expected constructor index")),
+                }
+            }
+            _ => match Constr::<PlutusData>::index_from_tag(tag) {
+                Some(index) => Ok(constr(index, self.fields()?)),
+                None => Err(self.error(&format!("unexpected tag {tag}"))),
+            },
+        }
+    }
+
+    fn fields(&mut self) -> ScriptResult<Vec<PlutusData>> {
+        match self.head()? {
+            (ARRAY, argument) => self.list_body(argument),
+            _ => Err(self.error("expected constructor fields")),
+        }
+    }
+
+    fn list_body(&mut self, argument: Argument) -> ScriptResult<Vec<PlutusData>> {
+        let mut items = Vec::new();
+        match argument {
+            Argument::Value(len) => {
+                for _ in 0..len {
+                    items.push(self.data()?);
+                }
+            }
+            Argument::Indefinite => {
+                while !self.at_break()? {
+                    items.push(self.data()?);
+                }
+            }
+        }
+        Ok(items)
+    }
+
+    fn bytes_body(&mut self, argument: Argument) -> ScriptResult<Vec<u8>> {
+        match argument {
+            Argument::Value(len) => {
+                let len = self.length(len)?;
+                Ok(self.take(len)?.to_vec())
+            }
+            Argument::Indefinite => {
+                let mut bytes = Vec::new();
+                while !self.at_break()? {
+                    match self.head()? {
+                        (BYTES, Argument::Value(len)) => {
+                            let len = self.length(len)?;
+                            bytes.extend_from_slice(self.take(len)?);
+                        }
+                        _ => return Err(self.error("expected a byte string chunk")),
+                    }
+                }
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    fn int(num: i64) -> PlutusData {
+        PlutusData::BigInt(num.into())
+    }
+
+    #[test]
+    fn to_cbor_bytes__uses_compact_constr_tags() {
+        // given
+        let data = PlutusData::Constr(Constr {
+            constr: 1,
+            fields: vec![int(42), int(-1)],
+        });
+
+        // when
+        let bytes = data.to_cbor_bytes();
+
+        // then
+        assert_eq!(hex::encode(bytes), "d87a9f182a20ff");
+    }
+
+    #[test]
+    fn to_cbor_bytes__uses_general_form_for_large_constr_indices() {
+        let data = PlutusData::Constr(Constr {
+            constr: 200,
+            fields: vec![],
+        });
+
+        let bytes = data.to_cbor_bytes();
+
+        assert_eq!(hex::encode(bytes), "d8668218c880");
+    }
+
+    #[test]
+    fn from_cbor_bytes__round_trips() {
+        // given
+        let data = PlutusData::Map(BTreeMap::from([
+            (
+                PlutusData::BoundedBytes(vec![7; 100]),
+                PlutusData::Array(vec![int(i64::MIN), int(0)]),
+            ),
+            (
+                PlutusData::BigInt(BigInt::BigUInt(vec![1, 0, 0, 0, 0, 0, 0, 0, 0])),
+                PlutusData::Constr(Constr {
+                    constr: 8,
+                    fields: vec![PlutusData::BoundedBytes(vec![])],
+                }),
+            ),
+        ]));
+
+        // when
+        let decoded = PlutusData::from_cbor_bytes(&data.to_cbor_bytes()).unwrap();
+
+        // then
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn from_cbor_bytes__decodes_small_bignums_as_ints() {
+        // given
+        let positive = [0xc2, 0x43, 0x00, 0x01, 0x00];
+        let negative = [0xc3, 0x41, 0x04];
+
+        // when
+        let positive = PlutusData::from_cbor_bytes(&positive).unwrap();
+        let negative = PlutusData::from_cbor_bytes(&negative).unwrap();
+
+        // then
+        assert_eq!(positive, int(256));
+        assert_eq!(negative, int(-5));
+        assert_eq!(
+            PlutusData::BigInt(BigInt::BigUInt(vec![1, 0])).to_cbor_bytes(),
+            int(256).to_cbor_bytes()
+        );
+    }
+
+    #[test]
+    fn from_cbor_bytes__rejects_deeply_nested_data() {
+        // given
+        let mut bytes = vec![0x81; 100_000];
+        bytes.push(0x00);
+
+        // when
+        let res = PlutusData::from_cbor_bytes(&bytes);
+
+        // then
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn from_cbor_bytes__rejects_trailing_bytes() {
+        let res = PlutusData::from_cbor_bytes(&[0x01, 0x02]);
+
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,254 @@
+use super::{
+    BigInt,
+    Constr,
+    PlutusData,
+};
+use crate::scripts::{
+    ScriptError,
+    ScriptResult,
+};
+use serde::{
+    de,
+    ser::{
+        self,
+        SerializeMap,
+    },
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+use serde_json::value::RawValue;
+use std::collections::BTreeMap;
+
+/// [`PlutusData`] that (de)serializes as the cardano-cli "detailed schema" JSON, e.g.
+/// `{"constructor": 0, "fields": [{"int": 42}, {"bytes": "deadbeef"}]}`
+///
+/// Integers of any size are written out in full, as JSON numbers. They are read from the
+/// number's text, so they only survive when (de)serializing straight to or from JSON text,
+/// not through a [`serde_json::Value`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DetailedJson(pub PlutusData);
+
+impl PlutusData {
+    /// Convert to the cardano-cli "detailed schema" JSON
+    pub fn to_detailed_json(&self) -> String {
+        serde_json::to_string(&Detailed(self)).expect("Integers are always valid JSON")
+    }
+
+    /// Parse the cardano-cli "detailed schema" JSON
+    pub fn from_detailed_json(json: &str) -> ScriptResult<PlutusData> {
+        let DetailedJson(data) = serde_json::from_str(json).map_err(|e| {
+            ScriptError::DatumDeserialization(format!(
+                "Invalid PlutusData JSON {json}: {e}"
+            ))
+        })?;
+        Ok(data)
+    }
+}
+
+impl Serialize for DetailedJson {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Detailed(&self.0).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DetailedJson {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = RawDetailed::deserialize(deserializer)?;
+        raw.into_plutus_data()
+            .map(DetailedJson)
+            .map_err(de::Error::custom)
+    }
+}
+
+struct Detailed<'a>(&'a PlutusData);
+
+#[derive(Serialize)]
+struct DetailedEntry<'a> {
+    k: Detailed<'a>,
+    v: Detailed<'a>,
+}
+
+impl Serialize for Detailed<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        match self.0 {
+            PlutusData::Constr(Constr { constr, fields }) => {
+                map.serialize_entry("constructor", constr)?;
+                map.serialize_entry("fields", &list(fields))?;
+            }
+            PlutusData::Map(entries) => {
+                let entries: Vec<_> = entries
+                    .iter()
+                    .map(|(k, v)| DetailedEntry {
+                        k: Detailed(k),
+                        v: Detailed(v),
+                    })
+                    .collect();
+                map.serialize_entry("map", &entries)?;
+            }
+            PlutusData::BigInt(big_int) => {
+                // Written from the integer's text, so it isn't squeezed into 64 bits
+                let number = RawValue::from_string(big_int.to_string())
+                    .map_err(ser::Error::custom)?;
+                map.serialize_entry("int", &number)?;
+            }
+            PlutusData::BoundedBytes(bytes) => {
+                map.serialize_entry("bytes", &hex::encode(bytes))?;
+            }
+            PlutusData::Array(items) => map.serialize_entry("list", &list(items))?,
+        }
+        map.end()
+    }
+}
+
+fn list(items: &[PlutusData]) -> Vec<Detailed<'_>> {
+    items.iter().map(Detailed).collect()
+}
+
+// Every schema's keys, so any object parses and unknown schemas are reported by
+// `into_plutus_data`
+#[derive(Deserialize)]
+struct RawDetailed {
+    constructor: Option<u64>,
+    fields: Option<Vec<RawDetailed>>,
+    map: Option<Vec<RawEntry>>,
+    int: Option<Box<RawValue>>,
+    bytes: Option<String>,
+    list: Option<Vec<RawDetailed>>,
+}
+
+#[derive(Deserialize)]
+struct RawEntry {
+    k: RawDetailed,
+    v: RawDetailed,
+}
+
+impl RawDetailed {
+    fn into_plutus_data(self) -> Result<PlutusData, String> {
+        if let Some(constr) = self.constructor {
+            let fields = self.fields.ok_or("missing \"fields\"")?;
+            Ok(PlutusData::Constr(Constr {
+                constr,
+                fields: list_from_raw(fields)?,
+            }))
+        } else if let Some(entries) = self.map {
+            let map = entries
+                .into_iter()
+                .map(|RawEntry { k, v }| {
+                    Ok((k.into_plutus_data()?, v.into_plutus_data()?))
+                })
+                .collect::<Result<BTreeMap<_, _>, String>>()?;
+            Ok(PlutusData::Map(map))
+        } else if let Some(int) = self.int {
+            // `BigInt` parsing rejects fractions, exponents and non-numbers
+            let big_int = int
+                .get()
+                .parse::<BigInt>()
+                .map_err(|_| format!("expected an integer, got {}", int.get()))?;
+            Ok(PlutusData::BigInt(big_int))
+        } else if let Some(bytes) = self.bytes {
+            let bytes = hex::decode(&bytes)
+                .map_err(|_| format!("expected hex bytes, got {bytes:?}"))?;
+            Ok(PlutusData::BoundedBytes(bytes))
+        } else if let Some(items) = self.list {
+            Ok(PlutusData::Array(list_from_raw(items)?))
+        } else {
+            Err("unknown PlutusData schema".to_string())
+        }
+    }
+}
+
+fn list_from_raw(items: Vec<RawDetailed>) -> Result<Vec<PlutusData>, String> {
+    items
+        .into_iter()
+        .map(RawDetailed::into_plutus_data)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn from_detailed_json__parses_cardano_cli_output() {
+        // given
+        let json = r#"{
+            "constructor": 1,
+            "fields": [
+                {"int": -5},
+                {"bytes": "cafe"},
+                {"list": [{"int": 18446744073709551615}]},
+                {"map": [{"k": {"bytes": ""}, "v": {"int": 0}}]}
+            ]
+        }"#;
+
+        // when
+        let data = PlutusData::from_detailed_json(json).unwrap();
+
+        // then
+        let expected = PlutusData::Constr(Constr {
+            constr: 1,
+            fields: vec![
+                PlutusData::BigInt((-5_i64).into()),
+                PlutusData::BoundedBytes(vec![0xca, 0xfe]),
+                PlutusData::Array(vec![PlutusData::BigInt(u64::MAX.into())]),
+                PlutusData::Map(BTreeMap::from([(
+                    PlutusData::BoundedBytes(vec![]),
+                    PlutusData::BigInt(0_i64.into()),
+                )])),
+            ],
+        });
+        assert_eq!(data, expected);
+        let compact: String = json.split_whitespace().collect();
+        assert_eq!(data.to_detailed_json(), compact);
+    }
+
+    #[test]
+    fn from_detailed_json__keeps_integers_beyond_64_bits() {
+        // given
+        let json = concat!(
+            r#"{"list":["#,
+            r#"{"int":1606938044258990275541962092341162602522202993782792835301376},"#,
+            r#"{"int":-1606938044258990275541962092341162602522202993782792835301377}"#,
+            r#"]}"#,
+        );
+
+        // when
+        let data = PlutusData::from_detailed_json(json).unwrap();
+
+        // then
+        let two_to_the_200 = {
+            let mut bytes = vec![0; 26];
+            bytes[0] = 1;
+            bytes
+        };
+        let expected = PlutusData::Array(vec![
+            PlutusData::BigInt(BigInt::BigUInt(two_to_the_200.clone())),
+            PlutusData::BigInt(BigInt::BigNInt(two_to_the_200)),
+        ]);
+        assert_eq!(data, expected);
+        assert_eq!(data.to_detailed_json(), json);
+    }
+
+    #[test]
+    fn from_detailed_json__rejects_fractional_integers() {
+        let json = r#"{"int": 1.5}"#;
+
+        let res = PlutusData::from_detailed_json(json);
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn from_detailed_json__rejects_unknown_schemas() {
+        let json = r#"{"string": "nope"}"#;
+
+        let res = PlutusData::from_detailed_json(json);
+
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,271 @@
+use super::{
+    Constr,
+    PlutusData,
+};
+use std::{
+    collections::BTreeSet,
+    fmt,
+};
+
+/// Compact on one line, e.g. `Constr 0 [42, h'cafe', {1: []}]`, or indented over multiple
+/// lines with the alternate flag (`{:#}`)
+impl fmt::Display for PlutusData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let indent = if f.alternate() { Some(0) } else { None };
+        write_data(self, f, indent)
+    }
+}
+
+fn write_data(
+    data: &PlutusData,
+    f: &mut fmt::Formatter<'_>,
+    indent: Option<usize>,
+) -> fmt::Result {
+    match data {
+        PlutusData::Constr(Constr { constr, fields }) => {
+            write!(f, "Constr {constr} ")?;
+            write_seq(f, ("[", "]"), fields.iter(), indent, write_data)
+        }
+        PlutusData::Map(map) => write_seq(
+            f,
+            ("{", "}"),
+            map.iter(),
+            indent,
+            |(key, value), f, indent| {
+                write_data(key, f, indent)?;
+                write!(f, ": ")?;
+                write_data(value, f, indent)
+            },
+        ),
+        PlutusData::BigInt(big_int) => write!(f, "{big_int}"),
+        PlutusData::BoundedBytes(bytes) => write!(f, "h'{}'", hex::encode(bytes)),
+        PlutusData::Array(items) => {
+            write_seq(f, ("[", "]"), items.iter(), indent, write_data)
+        }
+    }
+}
+
+fn write_seq<T>(
+    f: &mut fmt::Formatter<'_>,
+    (open, close): (&str, &str),
+    items: impl ExactSizeIterator<Item = T>,
+    indent: Option<usize>,
+    write_item: impl Fn(T, &mut fmt::Formatter<'_>, Option<usize>) -> fmt::Result,
+) -> fmt::Result {
+    write!(f, "{open}")?;
+    match indent {
+        Some(indent) if items.len() > 0 => {
+            writeln!(f)?;
+            for item in items {
+                write!(f, "{:width$}", "", width = (indent + 1) * 2)?;
+                write_item(item, f, Some(indent + 1))?;
+                writeln!(f, ",")?;
+            }
+            write!(f, "{:width$}", "", width = indent * 2)?;
+        }
+        _ => {
+            for (i, item) in items.enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write_item(item, f, indent)?;
+            }
+        }
+    }
+    write!(f, "{close}")
+}
+
+/// Step into a [`PlutusData`] on the way to a difference
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    /// Field of a constructor
+    Field(usize),
+    /// Item of a list
+    Item(usize),
+    /// Value of a map entry
+    Key(PlutusData),
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSegment::Field(index) => write!(f, ".{index}"),
+            PathSegment::Item(index) => write!(f, "[{index}]"),
+            PathSegment::Key(key) => write!(f, "{{{key}}}"),
+        }
+    }
+}
+
+/// First difference between two [`PlutusData`], as found by [`diff`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlutusDataDiff {
+    path: Vec<PathSegment>,
+    left: Option<PlutusData>,
+    right: Option<PlutusData>,
+}
+
+impl PlutusDataDiff {
+    /// Getter for the path from the root to the difference
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path
+    }
+
+    /// Left value at the path, `None` if it's a map entry only the right one has
+    pub fn left(&self) -> Option<&PlutusData> {
+        self.left.as_ref()
+    }
+
+    /// Right value at the path, `None` if it's a map entry only the left one has
+    pub fn right(&self) -> Option<&PlutusData> {
+        self.right.as_ref()
+    }
+}
+
+impl fmt::Display for PlutusDataDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at $")?;
+        for segment in self.path.iter() {
+            write!(f, "{segment}")?;
+        }
+        let show = |data: &Option<PlutusData>| match data {
+            Some(data) => data.to_string(),
+            None => "<missing>".to_string(),
+        };
+        write!(f, ": {} != {}", show(&self.left), show(&self.right))
+    }
+}
+
+/// Find the first place where `left` and `right` differ, or `None` if they're equal
+///
+/// Differences inside constructor fields, list items and map values are reported at the
+/// deepest path. Constructors with different indices, and constructors or lists of
+/// different lengths whose shared items match, are reported as a whole.
+pub fn diff(left: &PlutusData, right: &PlutusData) -> Option<PlutusDataDiff> {
+    let mut path = Vec::new();
+    diff_at(&mut path, left, right)
+}
+
+fn diff_at(
+    path: &mut Vec<PathSegment>,
+    left: &PlutusData,
+    right: &PlutusData,
+) -> Option<PlutusDataDiff> {
+    let nested = match (left, right) {
+        (PlutusData::Constr(l), PlutusData::Constr(r)) if l.constr == r.constr => {
+            diff_items(path, PathSegment::Field, &l.fields, &r.fields)
+        }
+        (PlutusData::Array(l), PlutusData::Array(r)) => {
+            diff_items(path, PathSegment::Item, l, r)
+        }
+        (PlutusData::Map(l), PlutusData::Map(r)) => {
+            let keys: BTreeSet<_> = l.keys().chain(r.keys()).collect();
+            keys.into_iter().find_map(|key| {
+                path.push(PathSegment::Key(key.clone()));
+                let found = match (l.get(key), r.get(key)) {
+                    (Some(l_value), Some(r_value)) => diff_at(path, l_value, r_value),
+                    (l_value, r_value) => Some(PlutusDataDiff {
+                        path: path.clone(),
+                        left: l_value.cloned(),
+                        right: r_value.cloned(),
+                    }),
+                };
+                path.pop();
+                found
+            })
+        }
+        _ => None,
+    };
+    if nested.is_some() || left == right {
+        return nested
+    }
+    Some(PlutusDataDiff {
+        path: path.clone(),
+        left: Some(left.clone()),
+        right: Some(right.clone()),
+    })
+}
+
+fn diff_items(
+    path: &mut Vec<PathSegment>,
+    segment: fn(usize) -> PathSegment,
+    left: &[PlutusData],
+    right: &[PlutusData],
+) -> Option<PlutusDataDiff> {
+    left.iter()
+        .zip(right.iter())
+        .enumerate()
+        .find_map(|(index, (l, r))| {
+            path.push(segment(index));
+            let found = diff_at(path, l, r);
+            path.pop();
+            found
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn int(num: i64) -> PlutusData {
+        PlutusData::BigInt(num.into())
+    }
+
+    fn constr(constr: u64, fields: Vec<PlutusData>) -> PlutusData {
+        PlutusData::Constr(Constr { constr, fields })
+    }
+
+    #[test]
+    fn display__is_compact_or_indented() {
+        // given
+        let data = constr(
+            0,
+            vec![
+                int(42),
+                PlutusData::BoundedBytes(vec![0xca, 0xfe]),
+                PlutusData::Map(BTreeMap::from([(int(1), PlutusData::Array(vec![]))])),
+            ],
+        );
+
+        // when
+        let compact = data.to_string();
+        let pretty = format!("{data:#}");
+
+        // then
+        assert_eq!(compact, "Constr 0 [42, h'cafe', {1: []}]");
+        assert_eq!(
+            pretty,
+            "Constr 0 [\n  42,\n  h'cafe',\n  {\n    1: [],\n  },\n]"
+        );
+    }
+
+    #[test]
+    fn diff__finds_first_differing_path() {
+        // given
+        let mine = constr(0, vec![int(1), PlutusData::Array(vec![int(2), int(3)])]);
+        let expected = constr(0, vec![int(1), PlutusData::Array(vec![int(2), int(4)])]);
+
+        // when
+        let found = diff(&mine, &expected).unwrap();
+
+        // then
+        assert_eq!(found.path(), &[PathSegment::Field(1), PathSegment::Item(1)]);
+        assert_eq!(found.to_string(), "at $.1[1]: 3 != 4");
+    }
+
+    #[test]
+    fn diff__reports_missing_map_entries() {
+        let mine = PlutusData::Map(BTreeMap::from([(int(1), int(1))]));
+        let expected =
+            PlutusData::Map(BTreeMap::from([(int(1), int(1)), (int(2), int(2))]));
+
+        let found = diff(&mine, &expected).unwrap();
+
+        assert_eq!(found.path(), &[PathSegment::Key(int(2))]);
+        assert_eq!(found.left(), None);
+        assert_eq!(found.right(), Some(&int(2)));
+        assert_eq!(diff(&mine, &mine), None);
+    }
+}
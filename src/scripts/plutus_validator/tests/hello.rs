@@ -3,7 +3,10 @@ use crate::scripts::{
         pub_key_hash_from_address_if_available,
         ContextBuilder,
     },
-    plutus_validator::PlutusValidator,
+    plutus_validator::{
+        plutus_data::PlutusData,
+        PlutusValidator,
+    },
     raw_script::PlutusScriptFile,
     ScriptError,
     Validator,
@@ -56,10 +59,12 @@ fn execute_hello_fails() {
     let ctx = ContextBuilder::new(signer_pkh).build_spend(&[], 0);
 
     // PT5: 'check' input is 'False'
-    assert_eq!(
-        script.execute(datum, redeemer, ctx).unwrap_err(),
-        ScriptError::FailedToExecute(
-            "AikenEval { error: \"EvaluationFailure\", logs: [\"PT5\"] }".to_string()
-        )
-    );
+    match script.execute(datum, redeemer, ctx).unwrap_err() {
+        ScriptError::Evaluation(failure) => {
+            assert_eq!(failure.error(), "EvaluationFailure");
+            assert_eq!(failure.traces(), &["PT5".to_string()]);
+            assert_eq!(failure.datum(), Some(&PlutusData::from(datum)));
+        }
+        other => panic!("expected an evaluation failure, got {other:?}"),
+    }
 }
@@ -1,8 +1,18 @@
-use crate::scripts::ExecutionCost;
+use crate::scripts::{
+    blueprint_schema::{
+        BlueprintSchemaError,
+        BlueprintSchemaResult,
+        SchemaDecoder,
+        SchemaValue,
+    },
+    plutus_validator::plutus_data::PlutusData,
+    ExecutionCost,
+};
 use serde::{
     Deserialize,
     Serialize,
 };
+use std::collections::BTreeMap;
 use thiserror::Error;
 use uplc::machine::cost_model::ExBudget;
 
@@ -35,6 +45,8 @@ impl PlutusScriptFile {
 pub struct BlueprintFile {
     preamble: Preamble,
     validators: Vec<ValidatorBlueprint>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    definitions: BTreeMap<String, serde_json::Value>,
 }
 
 impl BlueprintFile {
@@ -42,6 +54,67 @@ impl BlueprintFile {
     pub fn get_validator(&self, title: &str) -> Option<ValidatorBlueprint> {
         self.validators.iter().find(|v| v.title == title).cloned()
     }
+
+    /// Check `data` against the datum schema of the validator titled `validator_title`
+    /// and decode it with the constructor and field titles of the schema
+    pub fn decode_datum(
+        &self,
+        validator_title: &str,
+        data: &PlutusData,
+    ) -> BlueprintSchemaResult<SchemaValue> {
+        let validator = self.find_validator(validator_title)?;
+        let datum = validator
+            .datum
+            .as_ref()
+            .ok_or_else(|| BlueprintSchemaError::NoDatum(validator_title.to_string()))?;
+        SchemaDecoder::new(&self.definitions).decode(schema_of(datum), data)
+    }
+
+    /// Check `data` against the redeemer schema of the validator titled `validator_title`
+    /// and decode it with the constructor and field titles of the schema
+    pub fn decode_redeemer(
+        &self,
+        validator_title: &str,
+        data: &PlutusData,
+    ) -> BlueprintSchemaResult<SchemaValue> {
+        let validator = self.find_validator(validator_title)?;
+        SchemaDecoder::new(&self.definitions).decode(schema_of(&validator.redeemer), data)
+    }
+
+    /// Check `data` against the datum schema before converting it into the user type `T`
+    ///
+    /// Catches layout drift between the on-chain and Rust definitions of the datum, which a
+    /// bare [`TryFrom`] conversion might silently accept.
+    pub fn decode_datum_as<T>(
+        &self,
+        validator_title: &str,
+        data: &PlutusData,
+    ) -> BlueprintSchemaResult<T>
+    where
+        T: TryFrom<PlutusData>,
+        T::Error: std::fmt::Debug,
+    {
+        self.decode_datum(validator_title, data)?;
+        T::try_from(data.clone())
+            .map_err(|e| BlueprintSchemaError::Conversion(format!("{e:?}")))
+    }
+
+    fn find_validator(
+        &self,
+        validator_title: &str,
+    ) -> BlueprintSchemaResult<&ValidatorBlueprint> {
+        self.validators
+            .iter()
+            .find(|v| v.title == validator_title)
+            .ok_or_else(|| {
+                BlueprintSchemaError::UnknownValidator(validator_title.to_string())
+            })
+    }
+}
+
+// Datum and redeemer entries wrap their schema with a title
+fn schema_of(argument: &serde_json::Value) -> &serde_json::Value {
+    argument.get("schema").unwrap_or(argument)
 }
 
 /// Preable of a CIP-0057 Blueprint file
@@ -57,13 +130,18 @@ pub struct Preamble {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ValidatorBlueprint {
     title: String,
-    datum: Option<serde_json::Value>, // TODO: what is this type actually?
-    redeemer: serde_json::Value,      // TODO: what is this type actually?
+    datum: Option<serde_json::Value>,
+    redeemer: serde_json::Value,
     compiledCode: String,
     hash: String,
 }
 
 impl ValidatorBlueprint {
+    /// Getter for the validator title, e.g. `time_lock.spend`
+    pub fn title(&self) -> String {
+        self.title.clone()
+    }
+
     /// Get the hex bytes of the compiled Plutus script
     pub fn compiled_code(&self) -> String {
         self.compiledCode.clone()
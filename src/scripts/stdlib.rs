@@ -0,0 +1,16 @@
+//! Scripts from the Aiken project in `aiken/stdlib`, with their parameters typed
+//!
+//! The compiled `plutus.json` is checked in, so building naumachia doesn't need Aiken. Run
+//! `aiken build` in that directory after changing a validator.
+
+use crate::scripts::cache::CachedBlueprint;
+
+/// NFT policy that can only mint once, in the tx spending a given output
+pub mod one_shot;
+/// Policy minting tokens for whoever owns them, as long as the owner signs
+pub mod spend_token;
+/// Funds a beneficiary can claim as they unlock, following a schedule of tranches
+pub mod vesting;
+
+static BLUEPRINT: CachedBlueprint =
+    CachedBlueprint::new(include_str!("../../aiken/stdlib/plutus.json"));
@@ -0,0 +1,96 @@
+use crate::{
+    output::Output,
+    scripts::{
+        cache::ScriptCache,
+        context::CtxOutputReference,
+        plutus_minting_policy::{
+            OneParamPlutusPolicy,
+            PlutusMintingPolicy,
+        },
+        stdlib::BLUEPRINT,
+        ScriptError,
+        ScriptResult,
+    },
+};
+
+const VALIDATOR_NAME: &str = "one_shot_nft.mint";
+
+/// One-shot NFT policy, still waiting for the output that has to be spent to mint
+pub fn one_shot_nft_policy() -> ScriptResult<OneParamPlutusPolicy<CtxOutputReference, ()>>
+{
+    static POLICY: ScriptCache<OneParamPlutusPolicy<CtxOutputReference, ()>> =
+        ScriptCache::new();
+    POLICY.get_or_try_init(|| {
+        let validator_blueprint = BLUEPRINT.validator(VALIDATOR_NAME)?;
+        OneParamPlutusPolicy::from_blueprint(validator_blueprint)
+            .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))
+    })
+}
+
+/// One-shot NFT policy that only mints in the tx spending `output`
+///
+/// The same `output` must be spent by the minting tx, e.g. with
+/// [`TxActions::with_specific_input`](crate::transaction::TxActions::with_specific_input).
+pub fn one_shot_nft_policy_for<Datum>(
+    output: &Output<Datum>,
+) -> ScriptResult<PlutusMintingPolicy<()>> {
+    one_shot_nft_policy()?
+        .apply(output.into())
+        .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scripts::{
+        context::{
+            pub_key_hash_from_address_if_available,
+            ContextBuilder,
+        },
+        MintingPolicy,
+    };
+    use pallas_addresses::Address;
+
+    const OWNER: &str = "addr_test1qpmtp5t0t5y6cqkaz7rfsyrx7mld77kpvksgkwm0p7en7qum7a589n30e80tclzrrnj8qr4qvzj6al0vpgtnmrkkksnqd8upj0";
+
+    #[test]
+    fn execute__succeeds_when_output_included() {
+        let owner = Address::from_bech32(OWNER).unwrap();
+        let output: Output<()> =
+            Output::new_wallet(vec![1, 2, 3, 4], 0, owner.clone(), Default::default());
+        let script = one_shot_nft_policy_for(&output).unwrap();
+
+        let owner_pkh = pub_key_hash_from_address_if_available(&owner).unwrap();
+        let ctx = ContextBuilder::new(owner_pkh)
+            .add_specific_input(&output)
+            .build_mint(&[]);
+        let _eval = script.execute((), ctx).unwrap();
+    }
+
+    #[test]
+    fn execute__fails_when_output_not_included() {
+        let owner = Address::from_bech32(OWNER).unwrap();
+        let output: Output<()> =
+            Output::new_wallet(vec![1, 2, 3, 4], 0, owner.clone(), Default::default());
+        let script = one_shot_nft_policy_for(&output).unwrap();
+
+        let owner_pkh = pub_key_hash_from_address_if_available(&owner).unwrap();
+        let ctx = ContextBuilder::new(owner_pkh).build_mint(&[]);
+        let _eval = script.execute((), ctx).unwrap_err();
+    }
+
+    #[test]
+    fn one_shot_nft_policy_for__gives_each_output_its_own_policy() {
+        let owner = Address::from_bech32(OWNER).unwrap();
+        let first: Output<()> =
+            Output::new_wallet(vec![1, 2, 3, 4], 0, owner.clone(), Default::default());
+        let second: Output<()> =
+            Output::new_wallet(vec![1, 2, 3, 4], 1, owner, Default::default());
+
+        let first_id = one_shot_nft_policy_for(&first).unwrap().id().unwrap();
+        let second_id = one_shot_nft_policy_for(&second).unwrap().id().unwrap();
+
+        assert_ne!(first_id, second_id);
+    }
+}
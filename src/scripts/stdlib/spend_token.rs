@@ -0,0 +1,77 @@
+use crate::scripts::{
+    cache::ScriptCache,
+    context::PubKeyHash,
+    plutus_minting_policy::{
+        PlutusMintingPolicy,
+        TwoParamMintingPolicy,
+    },
+    stdlib::BLUEPRINT,
+    ScriptError,
+    ScriptResult,
+};
+
+const VALIDATOR_NAME: &str = "spend_token.mint";
+
+/// Spend token policy, still waiting for its seed and then its owner
+pub fn spend_token_policy() -> ScriptResult<TwoParamMintingPolicy<Vec<u8>, PubKeyHash, ()>>
+{
+    static POLICY: ScriptCache<TwoParamMintingPolicy<Vec<u8>, PubKeyHash, ()>> =
+        ScriptCache::new();
+    POLICY.get_or_try_init(|| {
+        let validator_blueprint = BLUEPRINT.validator(VALIDATOR_NAME)?;
+        TwoParamMintingPolicy::from_blueprint(validator_blueprint)
+            .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))
+    })
+}
+
+/// Spend token policy of `owner`, with its own policy id for every `seed`
+///
+/// The seed is usually the id of the NFT of whatever the token grants spending from.
+pub fn spend_token_policy_for(
+    seed: &[u8],
+    owner: &PubKeyHash,
+) -> ScriptResult<PlutusMintingPolicy<()>> {
+    spend_token_policy()?
+        .apply(seed.to_vec())
+        .and_then(|policy| policy.apply(owner.clone()))
+        .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scripts::{
+        context::{
+            pub_key_hash_from_address_if_available,
+            ContextBuilder,
+        },
+        MintingPolicy,
+    };
+    use pallas_addresses::Address;
+
+    #[test]
+    fn execute__correct_signer_can_mint() {
+        let signer = Address::from_bech32("addr_test1qpmtp5t0t5y6cqkaz7rfsyrx7mld77kpvksgkwm0p7en7qum7a589n30e80tclzrrnj8qr4qvzj6al0vpgtnmrkkksnqd8upj0").unwrap();
+        let signer_pkh = pub_key_hash_from_address_if_available(&signer).unwrap();
+        let script = spend_token_policy_for(&[1, 2, 3], &signer_pkh).unwrap();
+
+        let ctx = ContextBuilder::new(signer_pkh).build_mint(&[]);
+
+        script.execute((), ctx).unwrap();
+    }
+
+    #[test]
+    fn execute__incorrect_signer_cannot_mint() {
+        let correct_signer = Address::from_bech32("addr_test1qrksjmprvgcedgdt6rhg40590vr6exdzdc2hm5wc6pyl9ymkyskmqs55usm57gflrumk9kd63f3ty6r0l2tdfwfm28qs0rurdr").unwrap();
+        let incorrect_signer = Address::from_bech32("addr_test1qqddk5xnz08mxsqw6jdaenvhdah835lhvm62tt5lydk2as7kfjf77qy57hqhnefcqyy7hmhsygj9j38rj984hn9r57fs066hcl").unwrap();
+        let owner = pub_key_hash_from_address_if_available(&correct_signer).unwrap();
+        let script = spend_token_policy_for(&[1, 2, 3], &owner).unwrap();
+
+        let incorrect_signer_pkh =
+            pub_key_hash_from_address_if_available(&incorrect_signer).unwrap();
+        let ctx = ContextBuilder::new(incorrect_signer_pkh).build_mint(&[]);
+
+        script.execute((), ctx).unwrap_err();
+    }
+}
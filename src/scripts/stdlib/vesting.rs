@@ -0,0 +1,375 @@
+//! The datum holds the whole schedule, so claiming only unlocked tranches sends the rest back
+//! to the script with the same datum. Contracts using [`VestingDatum`] as their datum can
+//! build their endpoints from [`lock`], [`claim`] and [`claimable`].
+//!
+//! The validator only lets one vesting output be spent per transaction, so a single
+//! relocked output can't count for several of them.
+
+use crate::{
+    ledger_client::LedgerClient,
+    logic::error::{
+        SCLogicError,
+        SCLogicResult,
+    },
+    output::{
+        Output,
+        OutputId,
+    },
+    policy_id::PolicyId,
+    scripts::{
+        cache::ScriptCache,
+        context::PubKeyHash,
+        plutus_validator::{
+            plutus_data::{
+                Constr,
+                PlutusData,
+            },
+            PlutusValidator,
+        },
+        stdlib::BLUEPRINT,
+        ScriptError,
+        ScriptResult,
+        Validator,
+    },
+    transaction::TxActions,
+    values::Values,
+};
+use thiserror::Error;
+
+const VALIDATOR_NAME: &str = "vesting.spend";
+
+/// Lovelace unlocking at `unlock_at_secs`
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Tranche {
+    /// POSIX time the tranche unlocks at, in seconds
+    pub unlock_at_secs: i64,
+    /// Lovelace unlocking
+    pub lovelace: u64,
+}
+
+/// Beneficiary of the vested funds, and when they unlock
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VestingDatum {
+    /// Only key that can claim the funds
+    pub beneficiary: PubKeyHash,
+    /// Tranches, in any order
+    pub schedule: Vec<Tranche>,
+}
+
+impl VestingDatum {
+    /// Lovelace of every tranche in the schedule
+    pub fn total(&self) -> u64 {
+        self.schedule.iter().map(|tranche| tranche.lovelace).sum()
+    }
+
+    /// Lovelace still locked at `now_secs`
+    pub fn locked_at(&self, now_secs: i64) -> u64 {
+        self.schedule
+            .iter()
+            .filter(|tranche| tranche.unlock_at_secs > now_secs)
+            .map(|tranche| tranche.lovelace)
+            .sum()
+    }
+}
+
+// The validator compares against the validity range, which is in milliseconds
+impl From<Tranche> for PlutusData {
+    fn from(tranche: Tranche) -> Self {
+        PlutusData::Constr(Constr {
+            constr: 0,
+            fields: vec![
+                (tranche.unlock_at_secs * 1000).into(),
+                tranche.lovelace.into(),
+            ],
+        })
+    }
+}
+
+impl TryFrom<PlutusData> for Tranche {
+    type Error = ScriptError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        match data {
+            PlutusData::Constr(Constr { constr: 0, fields }) if fields.len() == 2 => {
+                let mut fields = fields.into_iter();
+                let unlock_at_ms = i64::try_from(fields.next().unwrap())?;
+                let lovelace = u64::try_from(fields.next().unwrap())?;
+                Ok(Tranche {
+                    unlock_at_secs: unlock_at_ms / 1000,
+                    lovelace,
+                })
+            }
+            _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
+        }
+    }
+}
+
+impl From<VestingDatum> for PlutusData {
+    fn from(datum: VestingDatum) -> Self {
+        let schedule = datum.schedule.into_iter().map(Into::into).collect();
+        PlutusData::Constr(Constr {
+            constr: 0,
+            fields: vec![datum.beneficiary.into(), PlutusData::Array(schedule)],
+        })
+    }
+}
+
+impl TryFrom<PlutusData> for VestingDatum {
+    type Error = ScriptError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        match &data {
+            PlutusData::Constr(Constr { constr: 0, fields }) => match fields.as_slice() {
+                [PlutusData::BoundedBytes(beneficiary), PlutusData::Array(schedule)] => {
+                    let schedule = schedule
+                        .iter()
+                        .cloned()
+                        .map(Tranche::try_from)
+                        .collect::<Result<_, _>>()?;
+                    Ok(VestingDatum {
+                        beneficiary: PubKeyHash::new(beneficiary),
+                        schedule,
+                    })
+                }
+                _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
+            },
+            _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
+        }
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum VestingError {
+    #[error("Vesting schedule doesn't lock any lovelace")]
+    EmptySchedule,
+    #[error("Could not find a vesting output with id: {0:?}")]
+    OutputNotFound(OutputId),
+    #[error("Output {0:?} doesn't have a vesting datum")]
+    DatumUnreadable(OutputId),
+    #[error("Nothing has unlocked yet")]
+    NothingUnlocked,
+}
+
+/// Validator holding vested funds
+pub fn vesting_validator() -> ScriptResult<PlutusValidator<VestingDatum, ()>> {
+    static VALIDATOR: ScriptCache<PlutusValidator<VestingDatum, ()>> = ScriptCache::new();
+    VALIDATOR.get_or_try_init(|| {
+        let validator_blueprint = BLUEPRINT.validator(VALIDATOR_NAME)?;
+        PlutusValidator::from_blueprint(validator_blueprint)
+            .map_err(|e| ScriptError::FailedToConstruct(e.to_string()))
+    })
+}
+
+/// Lovelace of `output` the beneficiary can claim at `now_secs`
+pub fn claimable(output: &Output<VestingDatum>, now_secs: i64) -> u64 {
+    let held = output.values().get(&PolicyId::Lovelace).unwrap_or(0);
+    match output.typed_datum() {
+        Some(datum) => held.saturating_sub(datum.locked_at(now_secs)),
+        None => 0,
+    }
+}
+
+/// Lock every tranche of `schedule` for `beneficiary`
+pub async fn lock<LC: LedgerClient<VestingDatum, ()>>(
+    ledger_client: &LC,
+    beneficiary: PubKeyHash,
+    schedule: Vec<Tranche>,
+) -> SCLogicResult<TxActions<VestingDatum, ()>> {
+    let datum = VestingDatum {
+        beneficiary,
+        schedule,
+    };
+    let total = datum.total();
+    if total == 0 {
+        return Err(SCLogicError::Endpoint(Box::new(
+            VestingError::EmptySchedule,
+        )))
+    }
+    let network = ledger_client.network().await?;
+    let address = vesting_validator()?.address(network)?;
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, total);
+    Ok(TxActions::v2().with_script_init_inline_datum(datum, values, address))
+}
+
+/// Claim what has unlocked from the output with `output_id`, locking the rest again
+pub async fn claim<LC: LedgerClient<VestingDatum, ()>>(
+    ledger_client: &LC,
+    output_id: OutputId,
+) -> SCLogicResult<TxActions<VestingDatum, ()>> {
+    let network = ledger_client.network().await?;
+    let script = vesting_validator()?;
+    let address = script.address(network)?;
+    let output = ledger_client
+        .all_outputs_at_address(&address)
+        .await?
+        .into_iter()
+        .find(|o| o.id() == &output_id)
+        .ok_or(VestingError::OutputNotFound(output_id.clone()))
+        .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
+    let datum = output
+        .typed_datum()
+        .ok_or(VestingError::DatumUnreadable(output_id))
+        .map_err(|e| SCLogicError::Endpoint(Box::new(e)))?;
+    let now = ledger_client.current_time_secs().await?;
+    if claimable(&output, now) == 0 {
+        return Err(SCLogicError::Endpoint(Box::new(
+            VestingError::NothingUnlocked,
+        )))
+    }
+
+    let still_locked = datum.locked_at(now);
+    let mut actions = TxActions::v2()
+        .with_script_redeem(output, (), Box::new(script))
+        .with_valid_range_secs(Some(now), None);
+    if still_locked > 0 {
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, still_locked);
+        actions = actions.with_script_init_inline_datum(datum, values, address);
+    }
+    Ok(actions)
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ledger_client::test_ledger_client::TestLedgerClientBuilder,
+        scripts::context::{
+            pub_key_hash_from_address_if_available,
+            ContextBuilder,
+        },
+        Address,
+        Network,
+    };
+
+    const BENEFICIARY: &str = "addr_test1qpmtp5t0t5y6cqkaz7rfsyrx7mld77kpvksgkwm0p7en7qum7a589n30e80tclzrrnj8qr4qvzj6al0vpgtnmrkkksnqd8upj0";
+
+    fn schedule() -> Vec<Tranche> {
+        vec![
+            Tranche {
+                unlock_at_secs: 1_000,
+                lovelace: 5_000_000,
+            },
+            Tranche {
+                unlock_at_secs: 2_000,
+                lovelace: 10_000_000,
+            },
+        ]
+    }
+
+    #[test]
+    fn execute__fails_before_anything_unlocks() {
+        let beneficiary = Address::from_bech32(BENEFICIARY).unwrap();
+        let pkh = pub_key_hash_from_address_if_available(&beneficiary).unwrap();
+        let datum = VestingDatum {
+            beneficiary: pkh.clone(),
+            schedule: schedule(),
+        };
+        let tx_hash = vec![1, 2, 3, 4];
+        let script = vesting_validator().unwrap();
+        let ctx = ContextBuilder::new(pkh)
+            .with_range(Some((500_000, true)), None)
+            .with_input(&tx_hash, 0, &script.address(Network::Testnet).unwrap())
+            .with_value("", "", 15_000_000)
+            .finish_input()
+            .build_spend(&tx_hash, 0);
+
+        script.execute(datum, (), ctx).unwrap_err();
+    }
+
+    #[test]
+    fn execute__fails_when_one_output_relocks_two_vesting_inputs() {
+        // given
+        let beneficiary = Address::from_bech32(BENEFICIARY).unwrap();
+        let pkh = pub_key_hash_from_address_if_available(&beneficiary).unwrap();
+        let datum = VestingDatum {
+            beneficiary: pkh.clone(),
+            schedule: schedule(),
+        };
+        let tx_hash = vec![1, 2, 3, 4];
+        let script = vesting_validator().unwrap();
+        let address = script.address(Network::Testnet).unwrap();
+        let builder = ContextBuilder::new(pkh)
+            .with_range(Some((1_500_000, true)), None)
+            .with_input(&tx_hash, 0, &address)
+            .with_value("", "", 15_000_000)
+            .with_inline_datum(datum.clone())
+            .finish_input()
+            .with_output(&address)
+            .with_value("", "", 10_000_000)
+            .with_inline_datum(datum.clone())
+            .finish_output();
+        let alone = builder.build_spend(&tx_hash, 0);
+        let doubled = builder
+            .with_input(&tx_hash, 1, &address)
+            .with_value("", "", 15_000_000)
+            .with_inline_datum(datum.clone())
+            .finish_input()
+            .build_spend(&tx_hash, 0);
+
+        // when
+        let alone_res = script.execute(datum.clone(), (), alone);
+        let doubled_res = script.execute(datum, (), doubled);
+
+        // then
+        alone_res.unwrap();
+        doubled_res.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn claim__locks_the_tranches_that_havent_unlocked_again() {
+        // given
+        let beneficiary = Address::from_bech32(BENEFICIARY).unwrap();
+        let pkh = pub_key_hash_from_address_if_available(&beneficiary).unwrap();
+        let start_amount = 100_000_000;
+        let ledger_client = TestLedgerClientBuilder::new(&beneficiary)
+            .with_starting_time(0)
+            .start_output(&beneficiary)
+            .with_value(PolicyId::Lovelace, start_amount)
+            .finish_output()
+            .build_in_memory();
+        let actions = lock(&ledger_client, pkh, schedule()).await.unwrap();
+        ledger_client
+            .issue(actions.to_unbuilt_tx().unwrap())
+            .await
+            .unwrap();
+        let address = vesting_validator()
+            .unwrap()
+            .address(ledger_client.network().await.unwrap())
+            .unwrap();
+        let locked = ledger_client
+            .all_outputs_at_address(&address)
+            .await
+            .unwrap();
+        let output_id = locked.first().unwrap().id().clone();
+
+        // when
+        ledger_client.set_current_time_secs(1_500).await.unwrap();
+        let actions = claim(&ledger_client, output_id).await.unwrap();
+        ledger_client
+            .issue(actions.to_unbuilt_tx().unwrap())
+            .await
+            .unwrap();
+
+        // then
+        let remaining = ledger_client
+            .all_outputs_at_address(&address)
+            .await
+            .unwrap();
+        let remaining = remaining.first().unwrap();
+        assert_eq!(
+            remaining.values().get(&PolicyId::Lovelace),
+            Some(10_000_000)
+        );
+        assert_eq!(claimable(remaining, 1_500), 0);
+        assert_eq!(claimable(remaining, 2_000), 10_000_000);
+        let balance = ledger_client
+            .balance_at_address(&beneficiary, &PolicyId::Lovelace)
+            .await
+            .unwrap();
+        assert_eq!(balance, start_amount - 10_000_000);
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{
+    output::Output,
+    scripts::{
+        ScriptError,
+        ScriptResult,
+        SharedValidator,
+        Validator,
+    },
+    TxActions,
+};
+use pallas_addresses::{
+    Address,
+    Network,
+};
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+};
+
+/// A single version of a [`VersionedScript`]
+pub struct ScriptVersion<Datum, Redeemer> {
+    version: u32,
+    validator: Arc<dyn Validator<Datum, Redeemer>>,
+    blueprint_title: Option<String>,
+    deployment: Option<Output<Datum>>,
+}
+
+impl<Datum, Redeemer> ScriptVersion<Datum, Redeemer> {
+    /// Getter for the version number
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Getter for the validator of this version
+    pub fn validator(&self) -> &dyn Validator<Datum, Redeemer> {
+        self.validator.as_ref()
+    }
+
+    /// Getter for the title of the validator in its blueprint, if known
+    pub fn blueprint_title(&self) -> Option<&str> {
+        self.blueprint_title.as_deref()
+    }
+
+    /// Getter for the output holding this version as a reference script, if deployed
+    pub fn deployment(&self) -> Option<&Output<Datum>> {
+        self.deployment.as_ref()
+    }
+
+    /// Address of outputs locked by this version
+    pub fn address(&self, network: Network) -> ScriptResult<Address> {
+        self.validator.address(network)
+    }
+}
+
+/// All deployed versions of a validator
+///
+/// Outputs created by older versions stay locked at their old addresses, so spending them
+/// has to use the version that locked them, while new outputs should go to the latest
+/// version. [`VersionedScript::with_script_redeem`] picks the version from the output's
+/// address.
+pub struct VersionedScript<Datum, Redeemer> {
+    versions: BTreeMap<u32, ScriptVersion<Datum, Redeemer>>,
+}
+
+impl<Datum, Redeemer> Default for VersionedScript<Datum, Redeemer> {
+    fn default() -> Self {
+        VersionedScript {
+            versions: BTreeMap::new(),
+        }
+    }
+}
+
+impl<Datum, Redeemer> VersionedScript<Datum, Redeemer>
+where
+    Datum: Clone + 'static,
+    Redeemer: 'static,
+{
+    /// Add `validator` as `version`, replacing any validator already added for it
+    pub fn with_version(
+        mut self,
+        version: u32,
+        validator: impl Validator<Datum, Redeemer> + 'static,
+    ) -> Self {
+        let script_version = ScriptVersion {
+            version,
+            validator: Arc::new(validator),
+            blueprint_title: None,
+            deployment: None,
+        };
+        self.versions.insert(version, script_version);
+        self
+    }
+
+    /// Record the blueprint title of the validator for `version`
+    pub fn with_blueprint_title(mut self, version: u32, title: &str) -> Self {
+        if let Some(script_version) = self.versions.get_mut(&version) {
+            script_version.blueprint_title = Some(title.to_string());
+        }
+        self
+    }
+
+    /// Record the output holding `version` as a reference script, so its spends don't
+    /// need to include the script
+    pub fn with_deployment(mut self, version: u32, deployment: Output<Datum>) -> Self {
+        if let Some(script_version) = self.versions.get_mut(&version) {
+            script_version.deployment = Some(deployment);
+        }
+        self
+    }
+
+    /// All versions, oldest first
+    pub fn versions(&self) -> impl Iterator<Item = &ScriptVersion<Datum, Redeemer>> {
+        self.versions.values()
+    }
+
+    /// The newest version, which new outputs should be locked at
+    pub fn latest(&self) -> Option<&ScriptVersion<Datum, Redeemer>> {
+        self.versions.values().next_back()
+    }
+
+    /// Address of the newest version
+    pub fn latest_address(&self, network: Network) -> ScriptResult<Address> {
+        self.latest()
+            .ok_or_else(|| {
+                ScriptError::FailedToConstruct("No versions of the script".to_string())
+            })?
+            .address(network)
+    }
+
+    /// Addresses of all versions, for looking up outputs locked by any of them
+    pub fn addresses(&self, network: Network) -> ScriptResult<Vec<Address>> {
+        self.versions()
+            .map(|script_version| script_version.address(network))
+            .collect()
+    }
+
+    /// The version that locked `output`, if any
+    pub fn version_for(
+        &self,
+        output: &Output<Datum>,
+        network: Network,
+    ) -> ScriptResult<Option<&ScriptVersion<Datum, Redeemer>>> {
+        let owner = output.owner();
+        for script_version in self.versions() {
+            if script_version.address(network)? == owner {
+                return Ok(Some(script_version))
+            }
+        }
+        Ok(None)
+    }
+
+    /// Add spending `output` with `redeemer` to `actions`, using the version that locked it
+    /// and its deployment if there is one
+    pub fn with_script_redeem(
+        &self,
+        actions: TxActions<Datum, Redeemer>,
+        output: Output<Datum>,
+        redeemer: Redeemer,
+        network: Network,
+    ) -> ScriptResult<TxActions<Datum, Redeemer>> {
+        let script_version = self.version_for(&output, network)?.ok_or_else(|| {
+            let owner = output.owner().to_bech32().unwrap_or_default();
+            ScriptError::UnknownScriptVersion(owner)
+        })?;
+        let mut actions = actions;
+        if let Some(deployment) = &script_version.deployment {
+            actions = actions.with_reference_script(deployment.clone());
+        }
+        let validator = Box::new(SharedValidator(script_version.validator.clone()));
+        Ok(actions.with_script_redeem(output, redeemer, validator))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::{
+        scripts::{
+            context::TxContext,
+            ExecutionCost,
+        },
+        values::Values,
+    };
+
+    struct FakeValidator(&'static str);
+
+    impl Validator<(), ()> for FakeValidator {
+        fn execute(
+            &self,
+            _datum: (),
+            _redeemer: (),
+            _ctx: TxContext,
+        ) -> ScriptResult<ExecutionCost> {
+            Ok(ExecutionCost::default())
+        }
+
+        fn address(&self, _network: Network) -> ScriptResult<Address> {
+            Ok(Address::from_bech32(self.0).unwrap())
+        }
+
+        fn script_hex(&self) -> ScriptResult<String> {
+            todo!()
+        }
+    }
+
+    const V1_ADDRESS: &str =
+        "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu";
+    const V2_ADDRESS: &str =
+        "addr_test1wpe9mt7mkjmkkuqjmevzafm6mle9t0spprr9335q0e6p92cur7fvl";
+    const UNKNOWN_ADDRESS: &str =
+        "addr_test1wr34avr87aq3aj0xlgj78jqjwjfppcj2ctsz8rppr2w8upc4jayvq";
+
+    fn output_at(address: &str) -> Output<()> {
+        let address = Address::from_bech32(address).unwrap();
+        Output::new_validator(vec![1; 32], 0, address, Values::default(), ())
+    }
+
+    #[test]
+    fn version_for__routes_legacy_outputs_to_the_version_that_locked_them() {
+        // given
+        let script = VersionedScript::default()
+            .with_version(2, FakeValidator(V2_ADDRESS))
+            .with_version(1, FakeValidator(V1_ADDRESS));
+        let legacy = output_at(V1_ADDRESS);
+        let unknown = output_at(UNKNOWN_ADDRESS);
+
+        // when
+        let legacy_version = script.version_for(&legacy, Network::Testnet).unwrap();
+        let unknown_redeem =
+            script.with_script_redeem(TxActions::v2(), unknown, (), Network::Testnet);
+
+        // then
+        assert_eq!(legacy_version.map(ScriptVersion::version), Some(1));
+        assert_eq!(
+            script.latest_address(Network::Testnet).unwrap(),
+            Address::from_bech32(V2_ADDRESS).unwrap()
+        );
+        assert!(matches!(
+            unknown_redeem,
+            Err(ScriptError::UnknownScriptVersion(_))
+        ));
+    }
+}
@@ -0,0 +1,179 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use thiserror::Error;
+
+const MILLIS_PER_SEC: i64 = 1000;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SlotConfigError {
+    #[error("Slot length must be positive, got {0}")]
+    InvalidSlotLength(i64),
+    #[error("Time {0} is out of range in milliseconds")]
+    TimeOutOfRange(i64),
+}
+
+/// How slots map to POSIX time on a network, from the slot where the current slot length
+/// took effect
+///
+/// All times are POSIX milliseconds, the unit scripts see in their validity range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SlotConfig {
+    zero_time: i64,
+    zero_slot: u64,
+    slot_length: u64,
+}
+
+impl Default for SlotConfig {
+    /// One second slots starting at the Unix epoch, as used by the
+    /// [`TestLedgerClient`](crate::ledger_client::test_ledger_client::TestLedgerClient)
+    fn default() -> Self {
+        SlotConfig::new(0, 0, 1000)
+    }
+}
+
+impl SlotConfig {
+    /// Cardano mainnet, from the Shelley hard fork
+    pub const MAINNET: SlotConfig = SlotConfig::new(1596059091000, 4492800, 1000);
+    /// Preprod test network, from the Shelley hard fork
+    pub const PREPROD: SlotConfig = SlotConfig::new(1655769600000, 86400, 1000);
+    /// Preview test network
+    pub const PREVIEW: SlotConfig = SlotConfig::new(1666656000000, 0, 1000);
+
+    /// Constructor for a [`SlotConfig`] where slot `zero_slot` starts at `zero_time` and
+    /// each slot lasts `slot_length` milliseconds
+    pub const fn new(zero_time: i64, zero_slot: u64, slot_length: u64) -> Self {
+        SlotConfig {
+            zero_time,
+            zero_slot,
+            slot_length,
+        }
+    }
+
+    /// Constructor for a [`SlotConfig`] from times in seconds, e.g. from a network config
+    ///
+    /// Fails unless `slot_length` is positive and the times fit in milliseconds.
+    pub fn from_secs(
+        zero_time: i64,
+        zero_slot: u64,
+        slot_length: i64,
+    ) -> Result<Self, SlotConfigError> {
+        let slot_length_ms = slot_length
+            .checked_mul(MILLIS_PER_SEC)
+            .and_then(|ms| u64::try_from(ms).ok())
+            .filter(|ms| *ms > 0)
+            .ok_or(SlotConfigError::InvalidSlotLength(slot_length))?;
+        let zero_time_ms = zero_time
+            .checked_mul(MILLIS_PER_SEC)
+            .ok_or(SlotConfigError::TimeOutOfRange(zero_time))?;
+        Ok(SlotConfig::new(zero_time_ms, zero_slot, slot_length_ms))
+    }
+
+    /// Getter for the POSIX time in milliseconds of the start of [`SlotConfig::zero_slot`]
+    pub fn zero_time(&self) -> i64 {
+        self.zero_time
+    }
+
+    /// Getter for the slot from which [`SlotConfig::slot_length`] applies
+    pub fn zero_slot(&self) -> u64 {
+        self.zero_slot
+    }
+
+    /// Getter for the length of a slot in milliseconds
+    pub fn slot_length(&self) -> u64 {
+        self.slot_length
+    }
+
+    /// Slot containing the POSIX time `posix_ms`, or `None` if it's before
+    /// [`SlotConfig::zero_time`] or slots have no length
+    pub fn slot_from_posix_ms(&self, posix_ms: i64) -> Option<u64> {
+        let elapsed = u64::try_from(posix_ms.checked_sub(self.zero_time)?).ok()?;
+        elapsed
+            .checked_div(self.slot_length)?
+            .checked_add(self.zero_slot)
+    }
+
+    /// POSIX time in milliseconds at the start of `slot`
+    pub fn posix_ms_from_slot(&self, slot: u64) -> i64 {
+        let slots = slot as i64 - self.zero_slot as i64;
+        self.zero_time + slots * self.slot_length as i64
+    }
+
+    /// Slot containing the POSIX time `posix_secs`, or `None` if it's before
+    /// [`SlotConfig::zero_time`]
+    pub fn slot_from_posix_secs(&self, posix_secs: i64) -> Option<u64> {
+        self.slot_from_posix_ms(posix_secs.checked_mul(MILLIS_PER_SEC)?)
+    }
+
+    /// POSIX time in whole seconds at the start of `slot`
+    pub fn posix_secs_from_slot(&self, slot: u64) -> i64 {
+        self.posix_ms_from_slot(slot).div_euclid(MILLIS_PER_SEC)
+    }
+
+    /// Round `posix_ms` down to the start of its slot, which is what scripts see for a
+    /// bound set at that time
+    pub fn slot_start(&self, posix_ms: i64) -> Option<i64> {
+        self.slot_from_posix_ms(posix_ms)
+            .map(|slot| self.posix_ms_from_slot(slot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn slot_from_posix_ms__matches_known_slots_of_each_network() {
+        // given
+        let known = [
+            (SlotConfig::MAINNET, 1693686614000, 102120323),
+            (SlotConfig::PREPROD, 1693686777000, 38003577),
+            (SlotConfig::PREVIEW, 1693686823000, 27030823),
+        ];
+
+        for (config, posix_ms, slot) in known {
+            // when
+            let actual_slot = config.slot_from_posix_ms(posix_ms + 999).unwrap();
+            let actual_posix_ms = config.posix_ms_from_slot(slot);
+
+            // then
+            assert_eq!(actual_slot, slot);
+            assert_eq!(actual_posix_ms, posix_ms);
+        }
+    }
+
+    #[test]
+    fn slot_from_posix_ms__is_none_before_zero_time() {
+        // given
+        let config = SlotConfig::new(10_000, 5, 200);
+
+        // when
+        let before = config.slot_from_posix_ms(9_999);
+        let at_start = config.slot_from_posix_ms(10_000);
+        let later = config.slot_from_posix_ms(10_450);
+
+        // then
+        assert_eq!(before, None);
+        assert_eq!(at_start, Some(5));
+        assert_eq!(later, Some(7));
+        assert_eq!(config.slot_start(10_450), Some(10_400));
+    }
+
+    #[test]
+    fn from_secs__rejects_slot_lengths_that_arent_positive() {
+        // when
+        let zero = SlotConfig::from_secs(0, 0, 0);
+        let negative = SlotConfig::from_secs(0, 0, -1);
+        let valid = SlotConfig::from_secs(10, 5, 2);
+
+        // then
+        assert_eq!(zero, Err(SlotConfigError::InvalidSlotLength(0)));
+        assert_eq!(negative, Err(SlotConfigError::InvalidSlotLength(-1)));
+        assert_eq!(valid, Ok(SlotConfig::new(10_000, 5, 2_000)));
+        assert_eq!(SlotConfig::new(0, 0, 0).slot_from_posix_ms(1_000), None);
+    }
+}
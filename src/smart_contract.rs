@@ -1,11 +1,65 @@
 use async_trait::async_trait;
-use std::fmt::Debug;
+use futures::Stream;
+use pallas_addresses::Address;
+use std::{
+    fmt,
+    fmt::Debug,
+    time::Duration,
+};
 
 use crate::{
-    error::Result,
-    ledger_client::LedgerClient,
-    logic::SCLogic,
-    transaction::TxId,
+    error::{
+        Error,
+        Result,
+    },
+    ledger_client::{
+        retry::RetryPolicy,
+        LedgerClient,
+        LedgerClientResult,
+    },
+    logic::{
+        SCLogic,
+        SCLogicWithConfig,
+        SCLogicWithOutput,
+        SCLogicWithSteps,
+    },
+    scripts::{
+        deployments::DeploymentRegistry,
+        plutus_validator::plutus_data::PlutusData,
+    },
+    transaction::{
+        TxActions,
+        TxId,
+        TxPreview,
+    },
+    trireme_ledger_client::Network,
+};
+
+/// Backtesting bots against replayed chain history
+pub mod backtest;
+/// Following the chain for changes at a contract's addresses
+pub mod chain_follower;
+/// Indexes of outputs kept up to date from chain events
+pub mod indexer;
+/// Guarding against accidental mainnet submissions
+pub mod mainnet_safety;
+/// Exporting the outputs at a contract's addresses
+pub mod snapshot;
+/// Structured records of submitted transactions
+pub mod tx_record;
+/// Watching addresses for new and spent outputs
+pub mod watch;
+
+use mainnet_safety::MainnetSafety;
+use tx_record::{
+    TxRecordSink,
+    TxRecorder,
+};
+
+use watch::{
+    poll_address,
+    OutputEvent,
+    DEFAULT_POLL_INTERVAL,
 };
 
 /// Interface defining how to interact with your smart contract
@@ -26,6 +80,42 @@ pub trait SmartContractTrait {
     async fn lookup(&self, lookup: Self::Lookup) -> Result<Self::LookupResponse>;
 }
 
+/// Hook run before an endpoint is handled. Returning an error aborts the endpoint.
+pub type BeforeEndpointHook<Endpoint> = fn(&Endpoint) -> Result<()>;
+/// Hook run on the [`TxActions`] of an endpoint before they are submitted. Returning an
+/// error aborts the submission.
+pub type BeforeSubmitHook<Datum, Redeemer> =
+    fn(&TxActions<Datum, Redeemer>) -> Result<()>;
+/// Hook run after a transaction was submitted successfully
+pub type AfterSubmitHook = fn(&TxId);
+
+/// Hooks registered on a [`SmartContract`], run in registration order
+pub struct EndpointHooks<Logic: SCLogic> {
+    before_endpoint: Vec<BeforeEndpointHook<Logic::Endpoints>>,
+    before_submit: Vec<BeforeSubmitHook<Logic::Datums, Logic::Redeemers>>,
+    after_submit: Vec<AfterSubmitHook>,
+}
+
+impl<Logic: SCLogic> Default for EndpointHooks<Logic> {
+    fn default() -> Self {
+        EndpointHooks {
+            before_endpoint: Vec::new(),
+            before_submit: Vec::new(),
+            after_submit: Vec::new(),
+        }
+    }
+}
+
+impl<Logic: SCLogic> Debug for EndpointHooks<Logic> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EndpointHooks")
+            .field("before_endpoint", &self.before_endpoint.len())
+            .field("before_submit", &self.before_submit.len())
+            .field("after_submit", &self.after_submit.len())
+            .finish()
+    }
+}
+
 /// Standard, concrete implementation of a Smart Contract
 #[derive(Debug)]
 pub struct SmartContract<Logic, LC>
@@ -35,6 +125,29 @@ where
 {
     offchain_logic: Logic,
     ledger_client: LC,
+    hooks: EndpointHooks<Logic>,
+    retry_policy: Option<RetryPolicy>,
+    mainnet_safety: Option<MainnetSafety<Logic::Datums, Logic::Redeemers>>,
+    deployments: Option<(DeploymentRegistry, Network)>,
+    tx_recorder: Option<TxRecorder<Logic::Datums, Logic::Redeemers>>,
+    confirmation_polling: ConfirmationPolling,
+}
+
+/// How long to wait for each step of
+/// [`hit_endpoint_in_steps`](SmartContract::hit_endpoint_in_steps) to be confirmed
+#[derive(Clone, Copy, Debug)]
+struct ConfirmationPolling {
+    interval: Duration,
+    timeout: Duration,
+}
+
+impl Default for ConfirmationPolling {
+    fn default() -> Self {
+        ConfirmationPolling {
+            interval: DEFAULT_POLL_INTERVAL,
+            timeout: Duration::from_secs(600),
+        }
+    }
 }
 
 impl<Logic, LC> SmartContract<Logic, LC>
@@ -47,44 +160,378 @@ where
         SmartContract {
             offchain_logic,
             ledger_client: backend,
+            hooks: EndpointHooks::default(),
+            retry_policy: None,
+            mainnet_safety: None,
+            deployments: None,
+            tx_recorder: None,
+            confirmation_polling: ConfirmationPolling::default(),
         }
     }
 
+    /// Retry failed submissions in [`hit_endpoint`](SmartContractTrait::hit_endpoint)
+    /// according to `retry_policy`
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Check every submission against `mainnet_safety` before issuing it
+    pub fn with_mainnet_safety(
+        mut self,
+        mainnet_safety: MainnetSafety<Logic::Datums, Logic::Redeemers>,
+    ) -> Self {
+        self.mainnet_safety = Some(mainnet_safety);
+        self
+    }
+
+    /// Use the reference scripts `registry` knows were deployed on `network` in every
+    /// transaction
+    pub fn with_deployment_registry(
+        mut self,
+        registry: DeploymentRegistry,
+        network: Network,
+    ) -> Self {
+        self.deployments = Some((registry, network));
+        self
+    }
+
+    /// Send a [`TxRecord`](tx_record::TxRecord) of every transaction submitted, or that
+    /// failed to submit, to `sink`
+    ///
+    /// Transactions issued with a retry policy are recorded without their fee and
+    /// execution units.
+    pub fn with_tx_record_sink(mut self, sink: impl TxRecordSink + 'static) -> Self
+    where
+        Logic::Datums: Into<PlutusData>,
+        Logic::Redeemers: Into<PlutusData>,
+    {
+        self.tx_recorder = Some(TxRecorder::new(sink));
+        self
+    }
+
+    /// Check whether each step of
+    /// [`hit_endpoint_in_steps`](SmartContract::hit_endpoint_in_steps) is confirmed every
+    /// `interval`, giving up after `timeout`
+    ///
+    /// Defaults to every [`DEFAULT_POLL_INTERVAL`] for up to 10 minutes.
+    pub fn with_confirmation_polling(
+        mut self,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Self {
+        self.confirmation_polling = ConfirmationPolling { interval, timeout };
+        self
+    }
+
+    /// Run `hook` on every endpoint before it is handled, e.g. for authorization checks
+    pub fn with_before_endpoint(
+        mut self,
+        hook: BeforeEndpointHook<Logic::Endpoints>,
+    ) -> Self {
+        self.hooks.before_endpoint.push(hook);
+        self
+    }
+
+    /// Run `hook` on the [`TxActions`] of every endpoint before they are submitted
+    pub fn with_before_submit(
+        mut self,
+        hook: BeforeSubmitHook<Logic::Datums, Logic::Redeemers>,
+    ) -> Self {
+        self.hooks.before_submit.push(hook);
+        self
+    }
+
+    /// Run `hook` with the id of every successfully submitted transaction
+    pub fn with_after_submit(mut self, hook: AfterSubmitHook) -> Self {
+        self.hooks.after_submit.push(hook);
+        self
+    }
+
     /// Returns reference to LedgerClient used by the SmartContract
 
     pub fn ledger_client(&self) -> &LC {
         &self.ledger_client
     }
 
+    /// Stream of outputs created at and spent from `address`
+    ///
+    /// Polls the ledger client every [`DEFAULT_POLL_INTERVAL`]. Outputs already at the
+    /// address when the stream is first polled aren't reported.
+    pub fn watch<'a>(
+        &'a self,
+        address: Address,
+    ) -> impl Stream<Item = LedgerClientResult<OutputEvent<Logic::Datums>>> + 'a
+    where
+        Logic::Datums: 'a,
+    {
+        self.watch_every(address, DEFAULT_POLL_INTERVAL)
+    }
+
+    /// Like [`watch`](Self::watch), polling every `interval`
+    pub fn watch_every<'a>(
+        &'a self,
+        address: Address,
+        interval: Duration,
+    ) -> impl Stream<Item = LedgerClientResult<OutputEvent<Logic::Datums>>> + 'a
+    where
+        Logic::Datums: 'a,
+    {
+        poll_address::<_, Logic::Redeemers, _>(&self.ledger_client, address, interval)
+    }
+
     /// Returns reference to the Smart contract logic used by the SmartContract
     pub fn logic(&self) -> &Logic {
         &self.offchain_logic
     }
 }
 
-#[async_trait]
-impl<Logic, Record> SmartContractTrait for SmartContract<Logic, Record>
+impl<Logic, LC> SmartContract<Logic, LC>
 where
-    Logic: SCLogic + Eq + Debug + Send + Sync,
+    Logic: SCLogic,
     Logic::Endpoints: Debug,
-    Logic::Lookups: Debug,
-    Logic::LookupResponses: Debug,
-    Record: LedgerClient<Logic::Datums, Logic::Redeemers> + Send + Sync,
+    LC: LedgerClient<Logic::Datums, Logic::Redeemers>,
 {
-    type Endpoint = Logic::Endpoints;
-    type Lookup = Logic::Lookups;
-    type LookupResponse = Logic::LookupResponses;
+    /// Build the transaction for `endpoint` without submitting it
+    ///
+    /// Lets UIs show the fee, inputs, outputs, mints and execution units and ask for
+    /// confirmation before calling [`hit_endpoint`](SmartContractTrait::hit_endpoint).
+    /// Only the before-endpoint hooks are run.
+    #[tracing::instrument(
+        skip_all,
+        fields(endpoint = ?endpoint, fee = tracing::field::Empty)
+    )]
+    pub async fn preview_endpoint(
+        &self,
+        endpoint: Logic::Endpoints,
+    ) -> Result<TxPreview<Logic::Datums>> {
+        tracing::info!("Previewing smart contract endpoint: {:?}", &endpoint);
+        for hook in &self.hooks.before_endpoint {
+            hook(&endpoint)?;
+        }
+        let tx_actions = self
+            .offchain_logic
+            .handle_instance_endpoint(endpoint, &self.ledger_client)
+            .await?;
+        let tx = self.with_deployments(tx_actions).to_unbuilt_tx()?;
+        let preview = self.ledger_client.preview(tx).await?;
+        tracing::Span::current().record("fee", preview.fee());
+        Ok(preview)
+    }
 
-    async fn hit_endpoint(&self, endpoint: Logic::Endpoints) -> Result<TxId> {
+    /// Handle every endpoint in `endpoints` and submit all their actions as one transaction
+    ///
+    /// Either all the endpoints take effect or none do, e.g. removing a puller and
+    /// withdrawing in the same transaction. Endpoints are handled in order against the
+    /// same ledger state, so they shouldn't spend the same outputs.
+    #[tracing::instrument(
+        skip_all,
+        fields(endpoints = ?endpoints, tx_id = tracing::field::Empty)
+    )]
+    pub async fn hit_endpoints_atomically(
+        &self,
+        endpoints: Vec<Logic::Endpoints>,
+    ) -> Result<TxId> {
+        tracing::info!("Hitting smart contract endpoints: {:?}", &endpoints);
+        let description = format!("{endpoints:?}");
+        for endpoint in endpoints.iter() {
+            for hook in &self.hooks.before_endpoint {
+                hook(endpoint)?;
+            }
+        }
+        let mut merged: Option<TxActions<Logic::Datums, Logic::Redeemers>> = None;
+        for endpoint in endpoints {
+            let tx_actions = self
+                .offchain_logic
+                .handle_instance_endpoint(endpoint, &self.ledger_client)
+                .await?;
+            merged = Some(match merged {
+                Some(merged) => merged.merge(tx_actions)?,
+                None => tx_actions,
+            });
+        }
+        let tx_actions = merged.ok_or(Error::NoEndpoints)?;
+        self.submit(tx_actions, &description).await
+    }
+
+    /// Hit `endpoint`, returning the domain data from
+    /// [`SCLogicWithOutput::handle_instance_endpoint_with_output`] along with the
+    /// transaction id
+    #[tracing::instrument(
+        skip_all,
+        fields(endpoint = ?endpoint, tx_id = tracing::field::Empty)
+    )]
+    pub async fn hit_endpoint_with_output(
+        &self,
+        endpoint: Logic::Endpoints,
+    ) -> Result<(TxId, Logic::EndpointOutput)>
+    where
+        Logic: SCLogicWithOutput,
+    {
+        tracing::info!("Hitting smart contract endpoint: {:?}", &endpoint);
+        for hook in &self.hooks.before_endpoint {
+            hook(&endpoint)?;
+        }
+        let description = format!("{endpoint:?}");
+        let (tx_actions, output) = self
+            .offchain_logic
+            .handle_instance_endpoint_with_output(endpoint, &self.ledger_client)
+            .await?;
+        let tx_id = self.submit(tx_actions, &description).await?;
+        Ok((tx_id, output))
+    }
+
+    /// Hit `endpoint`, handling it with the configuration of this contract's logic from
+    /// [`SCLogicWithConfig::config`]
+    #[tracing::instrument(
+        skip_all,
+        fields(endpoint = ?endpoint, tx_id = tracing::field::Empty)
+    )]
+    pub async fn hit_endpoint_with_config(
+        &self,
+        endpoint: Logic::Endpoints,
+    ) -> Result<TxId>
+    where
+        Logic: SCLogicWithConfig,
+    {
         tracing::info!("Hitting smart contract endpoint: {:?}", &endpoint);
-        let tx_actions = Logic::handle_endpoint(endpoint, &self.ledger_client).await?;
-        let tx = tx_actions.to_unbuilt_tx()?;
-        match self.ledger_client.issue(tx).await {
+        for hook in &self.hooks.before_endpoint {
+            hook(&endpoint)?;
+        }
+        let description = format!("{endpoint:?}");
+        let config = self.offchain_logic.config();
+        let tx_actions =
+            Logic::handle_endpoint_with_config(endpoint, config, &self.ledger_client)
+                .await?;
+        self.submit(tx_actions, &description).await
+    }
+
+    /// Hit `endpoint`, submitting the transactions of every step of
+    /// [`SCLogicWithSteps::handle_endpoint_step`] in order
+    ///
+    /// Each transaction has to be confirmed before the next step is handled. Returns the ids
+    /// of all the transactions. If a step fails after earlier ones were submitted, the error
+    /// is an [`Error::IncompleteSteps`] holding the ids of the ones that were.
+    #[tracing::instrument(
+        skip_all,
+        fields(endpoint = ?endpoint, tx_id = tracing::field::Empty)
+    )]
+    pub async fn hit_endpoint_in_steps(
+        &self,
+        endpoint: Logic::Endpoints,
+    ) -> Result<Vec<TxId>>
+    where
+        Logic: SCLogicWithSteps,
+    {
+        tracing::info!("Hitting smart contract endpoint in steps: {:?}", &endpoint);
+        for hook in &self.hooks.before_endpoint {
+            hook(&endpoint)?;
+        }
+        let mut submitted = Vec::new();
+        loop {
+            match self.next_step(&endpoint, &submitted).await {
+                Ok(Some(tx_id)) => submitted.push(tx_id),
+                Ok(None) => return Ok(submitted),
+                Err(error) if submitted.is_empty() => return Err(error),
+                Err(error) => {
+                    return Err(Error::IncompleteSteps {
+                        submitted,
+                        error: Box::new(error),
+                    })
+                }
+            }
+        }
+    }
+
+    async fn next_step(
+        &self,
+        endpoint: &Logic::Endpoints,
+        previous: &[TxId],
+    ) -> Result<Option<TxId>>
+    where
+        Logic: SCLogicWithSteps,
+    {
+        let step = previous.len();
+        let Some(tx_actions) =
+            Logic::handle_endpoint_step(endpoint, step, previous, &self.ledger_client)
+                .await?
+        else {
+            return Ok(None)
+        };
+        let description = format!("{endpoint:?} step {step}");
+        let tx_id = self.submit(tx_actions, &description).await?;
+        self.wait_for_confirmation(&tx_id).await?;
+        Ok(Some(tx_id))
+    }
+
+    async fn wait_for_confirmation(&self, tx_id: &TxId) -> Result<()> {
+        let ConfirmationPolling { interval, timeout } = self.confirmation_polling;
+        let mut waited = Duration::ZERO;
+        while !self.ledger_client.is_confirmed(tx_id).await? {
+            if waited >= timeout {
+                return Err(Error::TxNotConfirmed(tx_id.clone()))
+            }
+            crate::runtime::sleep(interval).await;
+            waited += interval;
+        }
+        Ok(())
+    }
+
+    fn with_deployments(
+        &self,
+        tx_actions: TxActions<Logic::Datums, Logic::Redeemers>,
+    ) -> TxActions<Logic::Datums, Logic::Redeemers> {
+        match &self.deployments {
+            Some((registry, network)) => tx_actions.with_deployments(registry, network),
+            None => tx_actions,
+        }
+    }
+
+    async fn submit(
+        &self,
+        tx_actions: TxActions<Logic::Datums, Logic::Redeemers>,
+        endpoint: &str,
+    ) -> Result<TxId> {
+        for hook in &self.hooks.before_submit {
+            hook(&tx_actions)?;
+        }
+        if let Some(mainnet_safety) = &self.mainnet_safety {
+            mainnet_safety
+                .check(&tx_actions, &self.ledger_client)
+                .await?;
+        }
+        let tx = self.with_deployments(tx_actions).to_unbuilt_tx()?;
+        let record = self
+            .tx_recorder
+            .as_ref()
+            .map(|recorder| recorder.describe(endpoint, &tx));
+        let res = match (&self.retry_policy, &self.tx_recorder) {
+            (Some(policy), _) => self
+                .ledger_client
+                .issue_with_retry(tx, policy)
+                .await
+                .map(|tx_id| (tx_id, None)),
+            (None, Some(_)) => self.ledger_client.issue_with_preview(tx).await,
+            (None, None) => self
+                .ledger_client
+                .issue(tx)
+                .await
+                .map(|tx_id| (tx_id, None)),
+        };
+        if let (Some(recorder), Some(record)) = (&self.tx_recorder, record) {
+            recorder.record(record, &res);
+        }
+        match res.map(|(tx_id, _)| tx_id) {
             Ok(tx_id) => {
+                tracing::Span::current()
+                    .record("tx_id", tracing::field::display(tx_id.as_str()));
                 tracing::info!(
                     "Successfully submitted transaction with id: {:?}",
                     &tx_id
                 );
+                for hook in &self.hooks.after_submit {
+                    hook(&tx_id);
+                }
                 Ok(tx_id)
             }
             Err(err) => {
@@ -93,7 +540,39 @@ where
             }
         }
     }
+}
+
+#[async_trait]
+impl<Logic, Record> SmartContractTrait for SmartContract<Logic, Record>
+where
+    Logic: SCLogic + Eq + Debug + Send + Sync,
+    Logic::Endpoints: Debug,
+    Logic::Lookups: Debug,
+    Logic::LookupResponses: Debug,
+    Record: LedgerClient<Logic::Datums, Logic::Redeemers> + Send + Sync,
+{
+    type Endpoint = Logic::Endpoints;
+    type Lookup = Logic::Lookups;
+    type LookupResponse = Logic::LookupResponses;
+
+    #[tracing::instrument(
+        skip_all,
+        fields(endpoint = ?endpoint, tx_id = tracing::field::Empty)
+    )]
+    async fn hit_endpoint(&self, endpoint: Logic::Endpoints) -> Result<TxId> {
+        tracing::info!("Hitting smart contract endpoint: {:?}", &endpoint);
+        for hook in &self.hooks.before_endpoint {
+            hook(&endpoint)?;
+        }
+        let description = format!("{endpoint:?}");
+        let tx_actions = self
+            .offchain_logic
+            .handle_instance_endpoint(endpoint, &self.ledger_client)
+            .await?;
+        self.submit(tx_actions, &description).await
+    }
 
+    #[tracing::instrument(skip_all, fields(lookup = ?lookup))]
     async fn lookup(&self, lookup: Self::Lookup) -> Result<Self::LookupResponse> {
         tracing::info!("Looking up smart contract information: {:?}", &lookup);
         match Logic::lookup(lookup, &self.ledger_client).await {
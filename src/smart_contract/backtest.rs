@@ -0,0 +1,399 @@
+use crate::{
+    ledger_client::{
+        test_ledger_client::{
+            in_memory_storage::InMemoryStorage,
+            TestLedgerClient,
+        },
+        LedgerClientError,
+    },
+    logic::SCLogic,
+    scripts::plutus_validator::plutus_data::PlutusData,
+    slot_config::SlotConfig,
+    smart_contract::{
+        chain_follower::{
+            ChainEvent,
+            ChainFollower,
+            ChainFollowerError,
+            ChainFollowerResult,
+            OuraEvent,
+        },
+        SmartContract,
+        SmartContractTrait,
+    },
+    transaction::TxId,
+};
+use async_trait::async_trait;
+use futures::{
+    Stream,
+    StreamExt,
+};
+use pallas_addresses::Address;
+use std::fmt::Debug;
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum BacktestError {
+    #[error("From ChainFollower: {0:?}")]
+    ChainFollower(#[from] ChainFollowerError),
+    #[error("From LedgerClient: {0:?}")]
+    LedgerClient(#[from] LedgerClientError),
+}
+
+/// Specialized result type for backtests
+pub type BacktestResult<T, E = BacktestError> = Result<T, E>;
+
+/// Simulated ledger history is replayed into
+pub type BacktestLedger<Logic> = TestLedgerClient<
+    <Logic as SCLogic>::Datums,
+    <Logic as SCLogic>::Redeemers,
+    InMemoryStorage<<Logic as SCLogic>::Datums>,
+>;
+
+/// Contract a [`Strategy`] hits endpoints on during a backtest
+pub type BacktestContract<Logic> = SmartContract<Logic, BacktestLedger<Logic>>;
+
+/// The automated part of a bot, e.g. a market maker or liquidator, deciding which endpoints
+/// to hit as history is replayed
+#[async_trait]
+pub trait Strategy<Logic>: Send
+where
+    Logic: SCLogic,
+    Logic::Endpoints: Debug,
+    Logic::Datums: PartialEq + Into<PlutusData>,
+    Logic::Redeemers: Debug,
+{
+    /// Endpoints to hit once the block at `slot` has been replayed into `contract`
+    async fn on_block(
+        &mut self,
+        contract: &BacktestContract<Logic>,
+        slot: u64,
+    ) -> Vec<Logic::Endpoints>;
+}
+
+/// Endpoint a [`Strategy`] hit during a backtest
+#[derive(Clone, Debug)]
+pub struct EndpointHit {
+    slot: u64,
+    endpoint: String,
+    result: Result<TxId, String>,
+}
+
+impl EndpointHit {
+    /// Getter for the slot of the block after which the endpoint was hit
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    /// Getter for the debug representation of the endpoint
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Getter for the id of the simulated transaction, or why the endpoint failed
+    pub fn result(&self) -> &Result<TxId, String> {
+        &self.result
+    }
+}
+
+/// What happened during a backtest
+#[derive(Clone, Debug, Default)]
+pub struct BacktestReport {
+    blocks: u64,
+    hits: Vec<EndpointHit>,
+}
+
+impl BacktestReport {
+    /// Getter for the number of blocks replayed
+    pub fn blocks(&self) -> u64 {
+        self.blocks
+    }
+
+    /// Getter for every endpoint hit, in order
+    pub fn hits(&self) -> &[EndpointHit] {
+        &self.hits
+    }
+
+    /// Endpoint hits that submitted a transaction
+    pub fn successes(&self) -> impl Iterator<Item = &EndpointHit> {
+        self.hits.iter().filter(|hit| hit.result.is_ok())
+    }
+
+    /// Endpoint hits that failed
+    pub fn failures(&self) -> impl Iterator<Item = &EndpointHit> {
+        self.hits.iter().filter(|hit| hit.result.is_err())
+    }
+}
+
+/// Replays chain history at a contract's addresses into a [`TestLedgerClient`] and lets a
+/// [`Strategy`] act after every block
+///
+/// Only outputs at the contract's addresses are replayed, so fund the strategy's wallet when
+/// building the ledger client. Transactions the strategy submits only exist in the simulated
+/// ledger: if history later spends an output the strategy already spent, the historical
+/// spend is skipped.
+pub struct Backtest<Logic>
+where
+    Logic: SCLogic,
+    Logic::Endpoints: Debug,
+    Logic::Datums: PartialEq + Into<PlutusData>,
+    Logic::Redeemers: Debug,
+{
+    contract: BacktestContract<Logic>,
+    follower: ChainFollower<Logic::Datums>,
+    slot_config: SlotConfig,
+}
+
+impl<Logic> Backtest<Logic>
+where
+    Logic: SCLogic + Eq + Debug + Send + Sync,
+    Logic::Endpoints: Debug,
+    Logic::Lookups: Debug,
+    Logic::LookupResponses: Debug,
+    Logic::Datums: PartialEq + Into<PlutusData> + TryFrom<PlutusData>,
+    Logic::Redeemers: Debug,
+{
+    /// Constructor for a [`Backtest`] of `logic` at `addresses`, against `ledger_client`
+    pub fn new(
+        logic: Logic,
+        ledger_client: BacktestLedger<Logic>,
+        addresses: &[Address],
+    ) -> Self {
+        Backtest {
+            contract: SmartContract::new(logic, ledger_client),
+            follower: ChainFollower::new(addresses),
+            slot_config: SlotConfig::default(),
+        }
+    }
+
+    /// Use `slot_config` to turn the slots of replayed blocks into ledger time
+    ///
+    /// Should match the slot config of the ledger client, e.g. [`SlotConfig::MAINNET`] for
+    /// both when replaying mainnet history.
+    pub fn with_slot_config(mut self, slot_config: SlotConfig) -> Self {
+        self.slot_config = slot_config;
+        self
+    }
+
+    /// Getter for the contract, e.g. to inspect the simulated ledger after a run
+    pub fn contract(&self) -> &BacktestContract<Logic> {
+        &self.contract
+    }
+
+    /// Replay `events`, e.g. from [`read_oura_events`](super::chain_follower::read_oura_events)
+    /// or `read_db_sync_events` with the `db-sync` feature, calling `strategy` after every
+    /// block
+    pub async fn run<Events, S>(
+        &mut self,
+        events: Events,
+        strategy: &mut S,
+    ) -> BacktestResult<BacktestReport>
+    where
+        Events: Stream<Item = ChainFollowerResult<OuraEvent>>,
+        S: Strategy<Logic>,
+    {
+        let mut report = BacktestReport::default();
+        let mut current_slot = None;
+        futures::pin_mut!(events);
+        while let Some(event) = events.next().await {
+            let event = event?;
+            if let Some(slot) = event.slot() {
+                match current_slot {
+                    Some(current) if slot > current => {
+                        self.end_block(current, strategy, &mut report).await?;
+                        current_slot = Some(slot);
+                    }
+                    None => current_slot = Some(slot),
+                    _ => {}
+                }
+            }
+            for chain_event in self.follower.apply(&event)? {
+                self.replay(chain_event).await?;
+            }
+        }
+        if let Some(current) = current_slot {
+            self.end_block(current, strategy, &mut report).await?;
+        }
+        Ok(report)
+    }
+
+    async fn replay(&self, chain_event: ChainEvent<Logic::Datums>) -> BacktestResult<()> {
+        let ledger_client = self.contract.ledger_client();
+        match chain_event {
+            ChainEvent::OutputCreated(output) => {
+                ledger_client.insert_output(&output).await?
+            }
+            ChainEvent::OutputSpent(output) => {
+                if let Err(e) = ledger_client.delete_output(&output).await {
+                    tracing::debug!("Skipping spend of {:?}: {e:?}", output.id());
+                }
+            }
+            ChainEvent::TxConfirmed { .. } | ChainEvent::Rollback { .. } => {}
+        }
+        Ok(())
+    }
+
+    async fn end_block<S: Strategy<Logic>>(
+        &self,
+        slot: u64,
+        strategy: &mut S,
+        report: &mut BacktestReport,
+    ) -> BacktestResult<()> {
+        report.blocks += 1;
+        self.contract
+            .ledger_client()
+            .set_current_time_secs(self.slot_config.posix_secs_from_slot(slot))
+            .await?;
+        for endpoint in strategy.on_block(&self.contract, slot).await {
+            let endpoint_debug = format!("{endpoint:?}");
+            let result = self
+                .contract
+                .hit_endpoint(endpoint)
+                .await
+                .map_err(|e| e.to_string());
+            report.hits.push(EndpointHit {
+                slot,
+                endpoint: endpoint_debug,
+                result,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::{
+        ledger_client::{
+            test_ledger_client::TestLedgerClientBuilder,
+            LedgerClient,
+        },
+        logic::error::SCLogicResult,
+        values::Values,
+        PolicyId,
+        TxActions,
+    };
+    use futures::stream;
+
+    const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+    const SCRIPT: &str =
+        "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu";
+    const TX_1: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+    const TX_2: &str = "0202020202020202020202020202020202020202020202020202020202020202";
+
+    #[derive(Debug, Eq, PartialEq)]
+    struct Poster;
+
+    #[async_trait]
+    impl SCLogic for Poster {
+        type Endpoints = i64;
+        type Lookups = ();
+        type LookupResponses = ();
+        type Datums = i64;
+        type Redeemers = ();
+
+        async fn handle_endpoint<Record: LedgerClient<i64, ()>>(
+            price: i64,
+            _ledger_client: &Record,
+        ) -> SCLogicResult<TxActions<i64, ()>> {
+            let mut values = Values::default();
+            values.add_one_value(&PolicyId::Lovelace, 5_000_000);
+            let address = Address::from_bech32(SCRIPT).unwrap();
+            Ok(TxActions::v2().with_script_init(price, values, address))
+        }
+
+        async fn lookup<Record: LedgerClient<i64, ()>>(
+            _query: (),
+            _ledger_client: &Record,
+        ) -> SCLogicResult<()> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct QuoteOnce {
+        seen: Vec<usize>,
+    }
+
+    #[async_trait]
+    impl Strategy<Poster> for QuoteOnce {
+        async fn on_block(
+            &mut self,
+            contract: &BacktestContract<Poster>,
+            _slot: u64,
+        ) -> Vec<i64> {
+            let address = Address::from_bech32(SCRIPT).unwrap();
+            let outputs = contract
+                .ledger_client()
+                .all_outputs_at_address(&address)
+                .await
+                .unwrap();
+            self.seen.push(outputs.len());
+            if self.seen.len() == 1 {
+                vec![outputs[0].typed_datum().unwrap() + 1]
+            } else {
+                Vec::new()
+            }
+        }
+    }
+
+    fn history() -> Vec<ChainFollowerResult<OuraEvent>> {
+        [
+            format!(
+                r#"{{"context": {{"slot": 10, "tx_hash": "{TX_1}", "output_idx": 0}},
+                "tx_output": {{"address": "{SCRIPT}", "amount": 2000000,
+                "inline_datum": {{"plutus_data": {{"int": 7}}}}}}}}"#
+            ),
+            format!(
+                r#"{{"context": {{"slot": 20, "tx_hash": "{TX_2}"}},
+                "tx_input": {{"tx_id": "{TX_1}", "index": 0}}}}"#
+            ),
+            format!(
+                r#"{{"context": {{"slot": 20, "tx_hash": "{TX_2}", "output_idx": 0}},
+                "tx_output": {{"address": "{SCRIPT}", "amount": 2000000,
+                "inline_datum": {{"plutus_data": {{"int": 9}}}}}}}}"#
+            ),
+        ]
+        .iter()
+        .map(|json| OuraEvent::from_json(json))
+        .collect()
+    }
+
+    #[tokio::test]
+    async fn run__lets_strategy_act_on_replayed_history_after_each_block() {
+        // given
+        let alice = Address::from_bech32(ALICE).unwrap();
+        let script = Address::from_bech32(SCRIPT).unwrap();
+        let ledger_client = TestLedgerClientBuilder::new(&alice)
+            .fund_lovelace(&alice, 100_000_000)
+            .build_in_memory();
+        let mut backtest = Backtest::new(Poster, ledger_client, &[script.clone()]);
+        let mut strategy = QuoteOnce::default();
+
+        // when
+        let report = backtest
+            .run(stream::iter(history()), &mut strategy)
+            .await
+            .unwrap();
+
+        // then
+        assert_eq!(report.blocks(), 2);
+        assert_eq!(report.successes().count(), 1);
+        assert_eq!(report.hits()[0].endpoint(), "8");
+        assert_eq!(strategy.seen, vec![1, 2]);
+        let ledger_client = backtest.contract().ledger_client();
+        let mut prices: Vec<_> = ledger_client
+            .all_outputs_at_address(&script)
+            .await
+            .unwrap()
+            .iter()
+            .filter_map(|output| output.typed_datum())
+            .collect();
+        prices.sort();
+        assert_eq!(prices, vec![8, 9]);
+        assert_eq!(ledger_client.current_time_secs().await.unwrap(), 20);
+    }
+}
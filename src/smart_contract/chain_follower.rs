@@ -0,0 +1,330 @@
+use crate::{
+    output::{
+        Output,
+        OutputId,
+    },
+    scripts::plutus_validator::plutus_data::PlutusData,
+    smart_contract::watch::OutputEvent,
+    transaction::TxId,
+};
+use futures::{
+    stream,
+    Stream,
+    StreamExt,
+};
+use oura::OuraEventKind;
+use pallas_addresses::Address;
+use std::{
+    collections::{
+        HashMap,
+        HashSet,
+        VecDeque,
+    },
+    error,
+    io,
+};
+use thiserror::Error;
+
+/// Reading chain history from a cardano-db-sync database
+#[cfg(feature = "db-sync")]
+pub mod db_sync;
+/// Reading Oura's JSON events
+pub mod oura;
+
+#[cfg(feature = "db-sync")]
+pub use db_sync::read_db_sync_events;
+pub use oura::{
+    read_oura_events,
+    OuraEvent,
+};
+
+/// Slots of history kept to undo rollbacks, 3k/f on mainnet
+pub const ROLLBACK_WINDOW_SLOTS: u64 = 129_600;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum ChainFollowerError {
+    #[error("Failed to read chain events: {0}")]
+    Io(#[from] io::Error),
+    #[error("Failed to query chain history: {0:?}")]
+    History(#[source] Box<dyn error::Error + Send + Sync>),
+    #[error("Invalid chain event JSON: {0}")]
+    Json(String),
+    #[error("Malformed chain event: {0}")]
+    MalformedEvent(String),
+}
+
+/// Specialized result type for the chain follower
+pub type ChainFollowerResult<T, E = ChainFollowerError> = Result<T, E>;
+
+/// Change on chain that concerns the followed addresses
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChainEvent<Datum> {
+    /// A new output was created at a followed address
+    OutputCreated(Output<Datum>),
+    /// An output at a followed address was spent
+    OutputSpent(Output<Datum>),
+    /// A transaction touching a followed address made it into a block
+    TxConfirmed {
+        /// Id of the transaction
+        tx_id: TxId,
+        /// Slot of the block holding it
+        slot: u64,
+    },
+    /// The chain rolled back to the block at `slot`
+    ///
+    /// Followed by the events undoing what happened after it.
+    Rollback {
+        /// Slot of the block the chain rolled back to
+        slot: u64,
+        /// Hash of that block
+        block_hash: String,
+    },
+}
+
+impl<Datum: Clone> ChainEvent<Datum> {
+    /// The change to the outputs at the followed addresses, if this event is one, e.g. to
+    /// feed an [`Indexer`](crate::smart_contract::indexer::Indexer)
+    pub fn to_output_event(&self) -> Option<OutputEvent<Datum>> {
+        match self {
+            ChainEvent::OutputCreated(output) => {
+                Some(OutputEvent::Created(output.clone()))
+            }
+            ChainEvent::OutputSpent(output) => Some(OutputEvent::Spent(output.clone())),
+            ChainEvent::TxConfirmed { .. } | ChainEvent::Rollback { .. } => None,
+        }
+    }
+}
+
+/// Turns chain-sync events from Oura into [`ChainEvent`]s for a contract's addresses
+///
+/// Spends are only reported for outputs the follower knows about, so either start following
+/// from before the outputs were created or seed it with [`ChainFollower::with_unspent`].
+pub struct ChainFollower<Datum> {
+    addresses: HashSet<String>,
+    unspent: HashMap<OutputId, (u64, Output<Datum>)>,
+    spent: VecDeque<(u64, u64, Output<Datum>)>,
+    last_confirmed: Option<String>,
+}
+
+impl<Datum> ChainFollower<Datum>
+where
+    Datum: Clone + TryFrom<PlutusData>,
+{
+    /// Constructor for a [`ChainFollower`] of `addresses`, e.g. the contract's validators
+    pub fn new(addresses: &[Address]) -> Self {
+        ChainFollower {
+            addresses: addresses
+                .iter()
+                .filter_map(|address| address.to_bech32().ok())
+                .collect(),
+            unspent: HashMap::new(),
+            spent: VecDeque::new(),
+            last_confirmed: None,
+        }
+    }
+
+    /// Start from `outputs` already at the followed addresses, e.g. from
+    /// [`LedgerClient::all_outputs_at_address`](crate::ledger_client::LedgerClient::all_outputs_at_address)
+    pub fn with_unspent(mut self, outputs: Vec<Output<Datum>>) -> Self {
+        for output in outputs {
+            self.unspent.insert(output.id().clone(), (0, output));
+        }
+        self
+    }
+
+    /// Outputs at the followed addresses that haven't been spent
+    pub fn unspent(&self) -> Vec<Output<Datum>> {
+        self.unspent
+            .values()
+            .map(|(_, output)| output.clone())
+            .collect()
+    }
+
+    /// Update the follower with `event`, returning what it means for the followed addresses
+    pub fn apply(
+        &mut self,
+        event: &OuraEvent,
+    ) -> ChainFollowerResult<Vec<ChainEvent<Datum>>> {
+        let slot = event.slot().unwrap_or_default();
+        let mut chain_events = Vec::new();
+        if let Some(address) = event.output_address() {
+            if !self.addresses.contains(address) {
+                return Ok(chain_events)
+            }
+        }
+        match event.kind::<Datum>()? {
+            OuraEventKind::Output { tx_hash, output } => {
+                self.confirm(&tx_hash, slot, &mut chain_events);
+                self.unspent
+                    .insert(output.id().clone(), (slot, output.clone()));
+                chain_events.push(ChainEvent::OutputCreated(output));
+            }
+            OuraEventKind::Input { tx_hash, spent } => {
+                if let Some((created, output)) = self.unspent.remove(&spent) {
+                    self.confirm(&tx_hash, slot, &mut chain_events);
+                    self.spent.push_back((slot, created, output.clone()));
+                    chain_events.push(ChainEvent::OutputSpent(output));
+                }
+            }
+            OuraEventKind::RollBack { slot, block_hash } => {
+                chain_events.push(ChainEvent::Rollback { slot, block_hash });
+                self.roll_back(slot, &mut chain_events);
+            }
+            OuraEventKind::Other => {}
+        }
+        self.prune(slot);
+        Ok(chain_events)
+    }
+
+    /// Stream of the [`ChainEvent`]s for `events`, e.g. from [`read_oura_events`]
+    pub fn follow<Events>(
+        self,
+        events: Events,
+    ) -> impl Stream<Item = ChainFollowerResult<ChainEvent<Datum>>>
+    where
+        Events: Stream<Item = ChainFollowerResult<OuraEvent>> + Unpin,
+    {
+        let state = (self, events, VecDeque::new());
+        stream::unfold(
+            state,
+            |(mut follower, mut events, mut pending)| async move {
+                loop {
+                    if let Some(chain_event) = pending.pop_front() {
+                        return Some((Ok(chain_event), (follower, events, pending)))
+                    }
+                    let applied = match events.next().await? {
+                        Ok(event) => follower.apply(&event),
+                        Err(e) => Err(e),
+                    };
+                    match applied {
+                        Ok(chain_events) => pending.extend(chain_events),
+                        Err(e) => return Some((Err(e), (follower, events, pending))),
+                    }
+                }
+            },
+        )
+    }
+
+    fn confirm(
+        &mut self,
+        tx_hash: &str,
+        slot: u64,
+        chain_events: &mut Vec<ChainEvent<Datum>>,
+    ) {
+        if self.last_confirmed.as_deref() != Some(tx_hash) {
+            self.last_confirmed = Some(tx_hash.to_string());
+            chain_events.push(ChainEvent::TxConfirmed {
+                tx_id: TxId::new(tx_hash),
+                slot,
+            });
+        }
+    }
+
+    fn roll_back(&mut self, slot: u64, chain_events: &mut Vec<ChainEvent<Datum>>) {
+        let rolled_back: Vec<_> = self
+            .unspent
+            .iter()
+            .filter(|(_, (created, _))| *created > slot)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in rolled_back {
+            if let Some((_, output)) = self.unspent.remove(&id) {
+                chain_events.push(ChainEvent::OutputSpent(output));
+            }
+        }
+        while matches!(self.spent.back(), Some((spent, _, _)) if *spent > slot) {
+            let (_, created, output) = self.spent.pop_back().expect("Just checked");
+            self.unspent
+                .insert(output.id().clone(), (created, output.clone()));
+            chain_events.push(ChainEvent::OutputCreated(output));
+        }
+        self.last_confirmed = None;
+    }
+
+    fn prune(&mut self, slot: u64) {
+        let oldest = slot.saturating_sub(ROLLBACK_WINDOW_SLOTS);
+        while matches!(self.spent.front(), Some((spent, _, _)) if *spent < oldest) {
+            self.spent.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::PolicyId;
+
+    const SCRIPT: &str =
+        "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu";
+    const OTHER: &str = "addr_test1wpe9mt7mkjmkkuqjmevzafm6mle9t0spprr9335q0e6p92cur7fvl";
+    const TX_1: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+    const TX_2: &str = "0202020202020202020202020202020202020202020202020202020202020202";
+
+    fn events() -> Vec<OuraEvent> {
+        [
+            format!(
+                r#"{{"context": {{"slot": 10, "tx_hash": "{TX_1}", "output_idx": 0}},
+                "tx_output": {{"address": "{SCRIPT}", "amount": 5000000,
+                "inline_datum": {{"plutus_data": {{"int": 7}}}}}}}}"#
+            ),
+            format!(
+                r#"{{"context": {{"slot": 10, "tx_hash": "{TX_1}", "output_idx": 1}},
+                "tx_output": {{"address": "{OTHER}", "amount": 1000000}}}}"#
+            ),
+            format!(
+                r#"{{"context": {{"slot": 20, "tx_hash": "{TX_2}"}},
+                "tx_input": {{"tx_id": "{TX_1}", "index": 0}}}}"#
+            ),
+            r#"{"context": {"slot": 15}, "roll_back": {"block_slot": 15, "block_hash": "ab"}}"#
+                .to_string(),
+        ]
+        .iter()
+        .map(|json| OuraEvent::from_json(json).unwrap())
+        .collect()
+    }
+
+    #[tokio::test]
+    async fn follow__reports_changes_at_followed_addresses_and_undoes_rollbacks() {
+        // given
+        let address = Address::from_bech32(SCRIPT).unwrap();
+        let follower = ChainFollower::<i64>::new(&[address]);
+        let events = stream::iter(events().into_iter().map(Ok));
+
+        // when
+        let chain_events: Vec<_> = follower
+            .follow(events)
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+
+        // then
+        let ChainEvent::OutputCreated(created) = &chain_events[1] else {
+            panic!("expected a created output, got {:?}", chain_events[1])
+        };
+        assert_eq!(created.typed_datum(), Some(7));
+        assert_eq!(created.values().get(&PolicyId::Lovelace), Some(5_000_000));
+        assert_eq!(
+            chain_events,
+            vec![
+                ChainEvent::TxConfirmed {
+                    tx_id: TxId::new(TX_1),
+                    slot: 10
+                },
+                ChainEvent::OutputCreated(created.clone()),
+                ChainEvent::TxConfirmed {
+                    tx_id: TxId::new(TX_2),
+                    slot: 20
+                },
+                ChainEvent::OutputSpent(created.clone()),
+                ChainEvent::Rollback {
+                    slot: 15,
+                    block_hash: "ab".to_string()
+                },
+                ChainEvent::OutputCreated(created.clone()),
+            ]
+        );
+    }
+}
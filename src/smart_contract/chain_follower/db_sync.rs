@@ -0,0 +1,317 @@
+use super::{
+    oura::{
+        AssetRecord,
+        OuraContext,
+        OuraEvent,
+        PlutusDatumRecord,
+        TxInputRecord,
+        TxOutputRecord,
+    },
+    ChainFollowerError,
+    ChainFollowerResult,
+};
+use futures::{
+    stream,
+    Stream,
+};
+use pallas_addresses::Address;
+use serde_json::value::RawValue;
+use tokio_postgres::{
+    types::FromSql,
+    Client,
+    Row,
+};
+
+// One row per output and native token it holds, so outputs with several tokens span
+// several rows
+const OUTPUTS_QUERY: &str = "
+SELECT block.slot_no::bigint, tx.block_index::bigint, encode(tx.hash, 'hex'),
+    tx_out.index::bigint, tx_out.address, tx_out.value::text,
+    encode(tx_out.data_hash, 'hex'), datum.value::text,
+    encode(multi_asset.policy, 'hex'), encode(multi_asset.name, 'hex'),
+    ma_tx_out.quantity::text
+FROM tx_out
+JOIN tx ON tx.id = tx_out.tx_id
+JOIN block ON block.id = tx.block_id
+LEFT JOIN datum ON datum.id = tx_out.inline_datum_id
+LEFT JOIN ma_tx_out ON ma_tx_out.tx_out_id = tx_out.id
+LEFT JOIN multi_asset ON multi_asset.id = ma_tx_out.ident
+WHERE tx_out.address = ANY($1) AND block.slot_no BETWEEN $2 AND $3 AND tx.valid_contract
+ORDER BY block.slot_no, tx.block_index, tx_out.index";
+
+const SPENDS_QUERY: &str = "
+SELECT block.slot_no::bigint, tx.block_index::bigint, encode(tx.hash, 'hex'),
+    encode(spent_tx.hash, 'hex'), tx_out.index::bigint
+FROM tx_in
+JOIN tx ON tx.id = tx_in.tx_in_id
+JOIN block ON block.id = tx.block_id
+JOIN tx_out ON tx_out.tx_id = tx_in.tx_out_id AND tx_out.index = tx_in.tx_out_index
+JOIN tx AS spent_tx ON spent_tx.id = tx_out.tx_id
+WHERE tx_out.address = ANY($1) AND block.slot_no BETWEEN $2 AND $3 AND tx.valid_contract
+ORDER BY block.slot_no, tx.block_index";
+
+/// History of `addresses` from `from_slot` to `to_slot` in a cardano-db-sync database, as
+/// the [`OuraEvent`]s Oura would have emitted for it
+///
+/// Only outputs created at or spent from `addresses` by valid transactions are read. db-sync
+/// only keeps the current chain, so there are no rollbacks. The whole range is loaded before
+/// the stream starts, so replay long histories a range at a time.
+pub async fn read_db_sync_events(
+    client: &Client,
+    addresses: &[Address],
+    from_slot: u64,
+    to_slot: u64,
+) -> ChainFollowerResult<impl Stream<Item = ChainFollowerResult<OuraEvent>>> {
+    let addresses = addresses
+        .iter()
+        .map(|address| {
+            address.to_bech32().map_err(|e| {
+                ChainFollowerError::MalformedEvent(format!("Invalid address: {e:?}"))
+            })
+        })
+        .collect::<ChainFollowerResult<Vec<_>>>()?;
+    let from_slot = slot_param(from_slot)?;
+    let to_slot = slot_param(to_slot)?;
+    let outputs = client
+        .query(OUTPUTS_QUERY, &[&addresses, &from_slot, &to_slot])
+        .await
+        .map_err(as_history_err)?
+        .iter()
+        .map(OutputRow::from_row)
+        .collect::<ChainFollowerResult<Vec<_>>>()?;
+    let spends = client
+        .query(SPENDS_QUERY, &[&addresses, &from_slot, &to_slot])
+        .await
+        .map_err(as_history_err)?
+        .iter()
+        .map(SpendRow::from_row)
+        .collect::<ChainFollowerResult<Vec<_>>>()?;
+    let events = events_from_rows(outputs, spends);
+    Ok(stream::iter(events.into_iter().map(Ok)))
+}
+
+struct OutputRow {
+    slot: u64,
+    block_index: u64,
+    tx_hash: String,
+    index: u64,
+    address: String,
+    lovelace: u64,
+    datum_hash: Option<String>,
+    inline_datum: Option<Box<RawValue>>,
+    asset: Option<AssetRecord>,
+}
+
+impl OutputRow {
+    fn from_row(row: &Row) -> ChainFollowerResult<Self> {
+        let asset = match get::<Option<String>>(row, 8)? {
+            Some(policy) => Some(AssetRecord {
+                policy,
+                asset: get(row, 9)?,
+                amount: parse_amount(&get::<String>(row, 10)?)?,
+            }),
+            None => None,
+        };
+        let inline_datum = get::<Option<String>>(row, 7)?
+            .map(RawValue::from_string)
+            .transpose()
+            .map_err(|e| ChainFollowerError::Json(e.to_string()))?;
+        Ok(OutputRow {
+            slot: get_u64(row, 0)?,
+            block_index: get_u64(row, 1)?,
+            tx_hash: get(row, 2)?,
+            index: get_u64(row, 3)?,
+            address: get(row, 4)?,
+            lovelace: parse_amount(&get::<String>(row, 5)?)?,
+            datum_hash: get(row, 6)?,
+            inline_datum,
+            asset,
+        })
+    }
+}
+
+struct SpendRow {
+    slot: u64,
+    block_index: u64,
+    tx_hash: String,
+    spent_tx_hash: String,
+    spent_index: u64,
+}
+
+impl SpendRow {
+    fn from_row(row: &Row) -> ChainFollowerResult<Self> {
+        Ok(SpendRow {
+            slot: get_u64(row, 0)?,
+            block_index: get_u64(row, 1)?,
+            tx_hash: get(row, 2)?,
+            spent_tx_hash: get(row, 3)?,
+            spent_index: get_u64(row, 4)?,
+        })
+    }
+}
+
+// Events in chain order, spends of a transaction before the outputs it creates
+fn events_from_rows(outputs: Vec<OutputRow>, spends: Vec<SpendRow>) -> Vec<OuraEvent> {
+    let mut events: Vec<_> = spends
+        .into_iter()
+        .map(|spend| {
+            let event = OuraEvent {
+                context: OuraContext {
+                    slot: Some(spend.slot),
+                    tx_hash: Some(spend.tx_hash),
+                    output_idx: None,
+                },
+                tx_output: None,
+                tx_input: Some(TxInputRecord {
+                    tx_id: spend.spent_tx_hash,
+                    index: spend.spent_index,
+                }),
+                roll_back: None,
+            };
+            ((spend.slot, spend.block_index, 0), event)
+        })
+        .collect();
+    let mut output_events: Vec<((u64, u64, u8), OuraEvent)> = Vec::new();
+    for row in outputs {
+        let same_output = output_events.last().map_or(false, |(_, event)| {
+            event.context.tx_hash.as_ref() == Some(&row.tx_hash)
+                && event.context.output_idx == Some(row.index)
+        });
+        if !same_output {
+            let event = OuraEvent {
+                context: OuraContext {
+                    slot: Some(row.slot),
+                    tx_hash: Some(row.tx_hash),
+                    output_idx: Some(row.index),
+                },
+                tx_output: Some(TxOutputRecord {
+                    address: row.address,
+                    amount: row.lovelace,
+                    assets: Some(Vec::new()),
+                    datum_hash: row.datum_hash,
+                    inline_datum: row
+                        .inline_datum
+                        .map(|plutus_data| PlutusDatumRecord { plutus_data }),
+                }),
+                tx_input: None,
+                roll_back: None,
+            };
+            output_events.push(((row.slot, row.block_index, 1), event));
+        }
+        if let (Some(asset), Some((_, event))) = (row.asset, output_events.last_mut()) {
+            if let Some(assets) = event
+                .tx_output
+                .as_mut()
+                .and_then(|output| output.assets.as_mut())
+            {
+                assets.push(asset);
+            }
+        }
+    }
+    events.extend(output_events);
+    events.sort_by_key(|(key, _)| *key);
+    events.into_iter().map(|(_, event)| event).collect()
+}
+
+fn get<'a, T: FromSql<'a>>(row: &'a Row, index: usize) -> ChainFollowerResult<T> {
+    row.try_get(index).map_err(as_history_err)
+}
+
+fn get_u64(row: &Row, index: usize) -> ChainFollowerResult<u64> {
+    u64::try_from(get::<i64>(row, index)?)
+        .map_err(|e| ChainFollowerError::MalformedEvent(e.to_string()))
+}
+
+fn parse_amount(amount: &str) -> ChainFollowerResult<u64> {
+    amount
+        .parse()
+        .map_err(|e| ChainFollowerError::MalformedEvent(format!("Invalid amount: {e}")))
+}
+
+fn slot_param(slot: u64) -> ChainFollowerResult<i64> {
+    i64::try_from(slot).map_err(|e| ChainFollowerError::MalformedEvent(e.to_string()))
+}
+
+fn as_history_err(error: tokio_postgres::Error) -> ChainFollowerError {
+    ChainFollowerError::History(Box::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::{
+        smart_contract::chain_follower::oura::OuraEventKind,
+        PolicyId,
+    };
+
+    const SCRIPT: &str =
+        "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu";
+    const TX_1: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+    const TX_2: &str = "0202020202020202020202020202020202020202020202020202020202020202";
+    const POLICY: &str = "57fca08abbaddee36da742a839f7d83a7e1d2419f1507fcbf3916522";
+
+    fn output_row(
+        slot: u64,
+        tx_hash: &str,
+        asset: Option<(&str, u64)>,
+        inline_datum: Option<&str>,
+    ) -> OutputRow {
+        OutputRow {
+            slot,
+            block_index: 0,
+            tx_hash: tx_hash.to_string(),
+            index: 0,
+            address: SCRIPT.to_string(),
+            lovelace: 2_000_000,
+            datum_hash: None,
+            inline_datum: inline_datum
+                .map(|json| RawValue::from_string(json.to_string()).unwrap()),
+            asset: asset.map(|(name, amount)| AssetRecord {
+                policy: POLICY.to_string(),
+                asset: name.to_string(),
+                amount,
+            }),
+        }
+    }
+
+    #[test]
+    fn events_from_rows__groups_tokens_by_output_and_spends_before_outputs() {
+        // given
+        let outputs = vec![
+            output_row(10, TX_1, Some(("aa", 1)), None),
+            output_row(10, TX_1, Some(("bb", 2)), None),
+            output_row(20, TX_2, None, Some(r#"{"int": 9}"#)),
+        ];
+        let spends = vec![SpendRow {
+            slot: 20,
+            block_index: 0,
+            tx_hash: TX_2.to_string(),
+            spent_tx_hash: TX_1.to_string(),
+            spent_index: 0,
+        }];
+
+        // when
+        let events = events_from_rows(outputs, spends);
+
+        // then
+        assert_eq!(events.len(), 3);
+        let OuraEventKind::Output { output: first, .. } =
+            events[0].kind::<i64>().unwrap()
+        else {
+            panic!("expected an output")
+        };
+        let bb = PolicyId::native_token(POLICY, &Some(vec![0xbb]));
+        assert_eq!(first.values().get(&bb), Some(2));
+        let OuraEventKind::Input { spent, .. } = events[1].kind::<i64>().unwrap() else {
+            panic!("expected a spend")
+        };
+        assert_eq!(spent, *first.id());
+        let OuraEventKind::Output { output: last, .. } = events[2].kind::<i64>().unwrap()
+        else {
+            panic!("expected an output")
+        };
+        assert_eq!(last.typed_datum(), Some(9));
+    }
+}
@@ -0,0 +1,210 @@
+use super::{
+    ChainFollowerError,
+    ChainFollowerResult,
+};
+use crate::{
+    output::{
+        Output,
+        OutputId,
+    },
+    scripts::plutus_validator::plutus_data::PlutusData,
+    values::Values,
+    PolicyId,
+};
+use futures::{
+    stream,
+    Stream,
+};
+use pallas_addresses::Address;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use tokio::io::{
+    AsyncBufRead,
+    AsyncBufReadExt,
+};
+
+/// Event emitted by Oura, as written by its JSON sinks (stdout, logs, webhook)
+///
+/// Only the events the [`ChainFollower`](super::ChainFollower) needs are read. Other kinds
+/// of event, e.g. blocks or certificates, parse fine and are ignored.
+#[derive(Clone, Debug, Deserialize)]
+pub struct OuraEvent {
+    pub(super) context: OuraContext,
+    #[serde(default)]
+    pub(super) tx_output: Option<TxOutputRecord>,
+    #[serde(default)]
+    pub(super) tx_input: Option<TxInputRecord>,
+    #[serde(default)]
+    pub(super) roll_back: Option<RollBackRecord>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub(super) struct OuraContext {
+    pub(super) slot: Option<u64>,
+    pub(super) tx_hash: Option<String>,
+    pub(super) output_idx: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(super) struct TxOutputRecord {
+    pub(super) address: String,
+    pub(super) amount: u64,
+    #[serde(default)]
+    pub(super) assets: Option<Vec<AssetRecord>>,
+    #[serde(default)]
+    pub(super) datum_hash: Option<String>,
+    #[serde(default)]
+    pub(super) inline_datum: Option<PlutusDatumRecord>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(super) struct AssetRecord {
+    pub(super) policy: String,
+    pub(super) asset: String,
+    pub(super) amount: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(super) struct PlutusDatumRecord {
+    pub(super) plutus_data: Box<RawValue>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(super) struct TxInputRecord {
+    pub(super) tx_id: String,
+    pub(super) index: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(super) struct RollBackRecord {
+    block_slot: u64,
+    block_hash: String,
+}
+
+/// What an [`OuraEvent`] reports
+pub(crate) enum OuraEventKind<Datum> {
+    Output {
+        tx_hash: String,
+        output: Output<Datum>,
+    },
+    Input {
+        tx_hash: String,
+        spent: OutputId,
+    },
+    RollBack {
+        slot: u64,
+        block_hash: String,
+    },
+    Other,
+}
+
+impl OuraEvent {
+    /// Parse a single event, as written by Oura
+    pub fn from_json(json: &str) -> ChainFollowerResult<Self> {
+        serde_json::from_str(json).map_err(|e| ChainFollowerError::Json(e.to_string()))
+    }
+
+    /// Slot of the block the event belongs to, if Oura reported it
+    pub fn slot(&self) -> Option<u64> {
+        self.context.slot
+    }
+
+    /// Bech32 address of the output, if this is an output event
+    pub(crate) fn output_address(&self) -> Option<&str> {
+        self.tx_output
+            .as_ref()
+            .map(|output| output.address.as_str())
+    }
+
+    pub(crate) fn kind<Datum: Clone + TryFrom<PlutusData>>(
+        &self,
+    ) -> ChainFollowerResult<OuraEventKind<Datum>> {
+        if let Some(roll_back) = &self.roll_back {
+            return Ok(OuraEventKind::RollBack {
+                slot: roll_back.block_slot,
+                block_hash: roll_back.block_hash.clone(),
+            })
+        }
+        if let Some(input) = &self.tx_input {
+            let spent = OutputId::new(decode_hex(&input.tx_id)?, input.index);
+            return Ok(OuraEventKind::Input {
+                tx_hash: self.tx_hash()?.to_string(),
+                spent,
+            })
+        }
+        if let Some(record) = &self.tx_output {
+            let tx_hash = self.tx_hash()?.to_string();
+            let index = self.context.output_idx.ok_or_else(|| {
+                ChainFollowerError::MalformedEvent("Output without an index".to_string())
+            })?;
+            let output = record.to_output(decode_hex(&tx_hash)?, index)?;
+            return Ok(OuraEventKind::Output { tx_hash, output })
+        }
+        Ok(OuraEventKind::Other)
+    }
+
+    fn tx_hash(&self) -> ChainFollowerResult<&str> {
+        self.context.tx_hash.as_deref().ok_or_else(|| {
+            ChainFollowerError::MalformedEvent("Event without a tx hash".to_string())
+        })
+    }
+}
+
+impl TxOutputRecord {
+    fn to_output<Datum: Clone + TryFrom<PlutusData>>(
+        &self,
+        tx_hash: Vec<u8>,
+        index: u64,
+    ) -> ChainFollowerResult<Output<Datum>> {
+        let address = Address::from_bech32(&self.address).map_err(|e| {
+            ChainFollowerError::MalformedEvent(format!("Invalid address: {e:?}"))
+        })?;
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, self.amount);
+        for asset in self.assets.iter().flatten() {
+            let asset_name = decode_hex(&asset.asset)?;
+            let asset_name = (!asset_name.is_empty()).then_some(asset_name);
+            let policy_id = PolicyId::native_token(&asset.policy, &asset_name);
+            values.add_one_value(&policy_id, asset.amount);
+        }
+        let output = if let Some(datum) = &self.inline_datum {
+            let data = PlutusData::from_detailed_json(datum.plutus_data.get())
+                .map_err(|e| ChainFollowerError::MalformedEvent(e.to_string()))?;
+            Output::new_untyped_validator(tx_hash, index, address, values, data)
+                .with_typed_datum_if_possible()
+        } else if let Some(datum_hash) = &self.datum_hash {
+            Output::new_wallet(tx_hash, index, address, values)
+                .with_datum_hash(decode_hex(datum_hash)?)
+        } else {
+            Output::new_wallet(tx_hash, index, address, values)
+        };
+        Ok(output)
+    }
+}
+
+fn decode_hex(s: &str) -> ChainFollowerResult<Vec<u8>> {
+    hex::decode(s).map_err(|e| ChainFollowerError::MalformedEvent(e.to_string()))
+}
+
+/// Stream of the events in `reader`, one JSON event per line, e.g. Oura's stdout sink or
+/// a dump of it
+///
+/// Ends after the first read error.
+pub fn read_oura_events<R>(
+    reader: R,
+) -> impl Stream<Item = ChainFollowerResult<OuraEvent>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    stream::unfold(Some(reader.lines()), |lines| async move {
+        let mut lines = lines?;
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) if line.trim().is_empty() => continue,
+                Ok(Some(line)) => return Some((OuraEvent::from_json(&line), Some(lines))),
+                Ok(None) => return None,
+                Err(e) => return Some((Err(ChainFollowerError::Io(e)), None)),
+            }
+        }
+    })
+}
@@ -0,0 +1,173 @@
+use crate::{
+    ledger_client::{
+        LedgerClientError,
+        LedgerClientResult,
+    },
+    output::Output,
+    smart_contract::watch::OutputEvent,
+};
+use async_trait::async_trait;
+use futures::{
+    Stream,
+    StreamExt,
+};
+use std::{
+    collections::HashMap,
+    error,
+    sync::Mutex,
+};
+use thiserror::Error;
+
+/// SQLite backed [`Indexer`]
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum IndexerError {
+    #[error("Index storage error: {0:?}")]
+    Storage(#[source] Box<dyn error::Error + Send + Sync>),
+    #[error("From LedgerClient: {0:?}")]
+    LedgerClient(#[from] LedgerClientError),
+}
+
+/// Specialized result type for indexers
+pub type IndexerResult<T, E = IndexerError> = Result<T, E>;
+
+/// Keys an output should be found under in an index, e.g. the owner stored in a checking
+/// account's datum
+///
+/// Implemented by the contract. Closures taking an output work too.
+pub trait IndexKeys<Datum>: Send + Sync {
+    /// Keys for `output`, empty if it shouldn't be indexed
+    fn keys(&self, output: &Output<Datum>) -> Vec<String>;
+}
+
+impl<Datum, F> IndexKeys<Datum> for F
+where
+    F: Fn(&Output<Datum>) -> Vec<String> + Send + Sync,
+{
+    fn keys(&self, output: &Output<Datum>) -> Vec<String> {
+        self(output)
+    }
+}
+
+/// Derived state of a contract, kept up to date from chain events
+///
+/// Lookups read the index instead of scanning all the outputs at the contract's address.
+#[async_trait]
+pub trait Indexer<Datum: Send + Sync>: Send + Sync {
+    /// Update the index with `event`
+    async fn apply(&self, event: &OutputEvent<Datum>) -> IndexerResult<()>;
+
+    /// Unspent outputs indexed under `key`
+    async fn outputs_for(&self, key: &str) -> IndexerResult<Vec<Output<Datum>>>;
+
+    /// Apply every event of `events`, e.g. from
+    /// [`poll_address`](crate::smart_contract::watch::poll_address), until it ends or fails
+    async fn follow<Events>(&self, events: Events) -> IndexerResult<()>
+    where
+        Events: Stream<Item = LedgerClientResult<OutputEvent<Datum>>> + Send,
+    {
+        futures::pin_mut!(events);
+        while let Some(event) = events.next().await {
+            self.apply(&event?).await?;
+        }
+        Ok(())
+    }
+}
+
+/// [`Indexer`] kept in memory, which has to be rebuilt when the program restarts
+pub struct InMemoryIndexer<Datum, Keys> {
+    keys: Keys,
+    outputs: Mutex<HashMap<String, Vec<Output<Datum>>>>,
+}
+
+impl<Datum, Keys: IndexKeys<Datum>> InMemoryIndexer<Datum, Keys> {
+    /// Constructor for an empty [`InMemoryIndexer`], indexing outputs under `keys`
+    pub fn new(keys: Keys) -> Self {
+        InMemoryIndexer {
+            keys,
+            outputs: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl<Datum, Keys> Indexer<Datum> for InMemoryIndexer<Datum, Keys>
+where
+    Datum: Clone + Send + Sync,
+    Keys: IndexKeys<Datum>,
+{
+    async fn apply(&self, event: &OutputEvent<Datum>) -> IndexerResult<()> {
+        let mut outputs = self.outputs.lock().expect("Lock poisoned");
+        match event {
+            OutputEvent::Created(output) => {
+                for key in self.keys.keys(output) {
+                    let indexed = outputs.entry(key).or_default();
+                    if !indexed.iter().any(|o| o.id() == output.id()) {
+                        indexed.push(output.clone());
+                    }
+                }
+            }
+            OutputEvent::Spent(output) => {
+                for indexed in outputs.values_mut() {
+                    indexed.retain(|o| o.id() != output.id());
+                }
+                outputs.retain(|_, indexed| !indexed.is_empty());
+            }
+        }
+        Ok(())
+    }
+
+    async fn outputs_for(&self, key: &str) -> IndexerResult<Vec<Output<Datum>>> {
+        let outputs = self.outputs.lock().expect("Lock poisoned");
+        Ok(outputs.get(key).cloned().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::values::Values;
+    use futures::stream;
+    use pallas_addresses::Address;
+
+    const SCRIPT: &str =
+        "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu";
+
+    fn account(id: u8, owner: i64) -> Output<i64> {
+        let address = Address::from_bech32(SCRIPT).unwrap();
+        Output::new_validator(vec![id; 32], 0, address, Values::default(), owner)
+    }
+
+    fn by_owner(output: &Output<i64>) -> Vec<String> {
+        output
+            .typed_datum()
+            .map(|owner| vec![owner.to_string()])
+            .unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn follow__keeps_only_unspent_outputs_under_their_keys() {
+        // given
+        let indexer = InMemoryIndexer::<i64, _>::new(by_owner);
+        let events = stream::iter(vec![
+            Ok(OutputEvent::Created(account(1, 7))),
+            Ok(OutputEvent::Created(account(2, 7))),
+            Ok(OutputEvent::Created(account(3, 8))),
+            Ok(OutputEvent::Spent(account(1, 7))),
+        ]);
+
+        // when
+        indexer.follow(events).await.unwrap();
+
+        // then
+        let sevens = indexer.outputs_for("7").await.unwrap();
+        assert_eq!(sevens, vec![account(2, 7)]);
+        assert_eq!(indexer.outputs_for("8").await.unwrap().len(), 1);
+        assert!(indexer.outputs_for("9").await.unwrap().is_empty());
+    }
+}
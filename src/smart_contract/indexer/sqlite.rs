@@ -0,0 +1,322 @@
+use super::{
+    IndexKeys,
+    Indexer,
+    IndexerError,
+    IndexerResult,
+};
+use crate::{
+    output::{
+        Output,
+        OutputId,
+        ReferenceScript,
+    },
+    scripts::plutus_validator::plutus_data::PlutusData,
+    smart_contract::watch::OutputEvent,
+    values::Values,
+};
+use async_trait::async_trait;
+use pallas_addresses::Address;
+use rusqlite::{
+    params,
+    Connection,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    marker::PhantomData,
+    path::Path,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS indexed_outputs (
+    key TEXT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    output_index INTEGER NOT NULL,
+    output TEXT NOT NULL,
+    PRIMARY KEY (key, tx_hash, output_index)
+)";
+
+/// Output as stored in the `output` column
+///
+/// Tagged with its version, so rows written by older versions can still be read after the
+/// format changes.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "version")]
+enum IndexedOutput {
+    #[serde(rename = "1")]
+    V1 {
+        id: OutputId,
+        owner: String,
+        values: Values,
+        datum: Option<PlutusData>,
+        datum_hash: Option<Vec<u8>>,
+        reference_script: Option<ReferenceScript>,
+    },
+}
+
+impl IndexedOutput {
+    fn from_output<Datum: Clone + Into<PlutusData>>(
+        output: &Output<Datum>,
+    ) -> IndexerResult<Self> {
+        let owner = output.owner().to_bech32().map_err(as_storage_err)?;
+        Ok(IndexedOutput::V1 {
+            id: output.id().to_owned(),
+            owner,
+            values: output.values().clone(),
+            datum: output.datum_plutus_data(),
+            datum_hash: output.datum_hash().map(|hash| hash.to_vec()),
+            reference_script: output.reference_script().cloned(),
+        })
+    }
+
+    fn into_output<Datum>(self) -> IndexerResult<Output<Datum>> {
+        let IndexedOutput::V1 {
+            id,
+            owner,
+            values,
+            datum,
+            datum_hash,
+            reference_script,
+        } = self;
+        let tx_hash = id.tx_hash().to_owned();
+        let index = id.index();
+        let owner = Address::from_bech32(&owner).map_err(as_storage_err)?;
+        let mut output = match datum {
+            Some(datum) => {
+                Output::new_untyped_validator(tx_hash, index, owner, values, datum)
+            }
+            None => Output::new_wallet(tx_hash, index, owner, values),
+        };
+        if let Some(datum_hash) = datum_hash {
+            output = output.with_datum_hash(datum_hash);
+        }
+        if let Some(reference_script) = reference_script {
+            output = output.with_reference_script(reference_script);
+        }
+        Ok(output)
+    }
+}
+
+/// [`Indexer`] persisted in a SQLite database, so it survives restarts
+///
+/// Queries run on Tokio's blocking thread pool, so they don't stall the async runtime.
+pub struct SqliteIndexer<Datum, Keys> {
+    keys: Keys,
+    connection: Arc<Mutex<Connection>>,
+    _datum: PhantomData<Datum>,
+}
+
+impl<Datum, Keys: IndexKeys<Datum>> SqliteIndexer<Datum, Keys> {
+    /// Open, or create, the index in the database at `path`, indexing outputs under `keys`
+    pub fn open(path: impl AsRef<Path>, keys: Keys) -> IndexerResult<Self> {
+        let connection = Connection::open(path).map_err(as_storage_err)?;
+        Self::from_connection(connection, keys)
+    }
+
+    /// Create an index in a fresh in-memory database, indexing outputs under `keys`
+    pub fn in_memory(keys: Keys) -> IndexerResult<Self> {
+        let connection = Connection::open_in_memory().map_err(as_storage_err)?;
+        Self::from_connection(connection, keys)
+    }
+
+    fn from_connection(connection: Connection, keys: Keys) -> IndexerResult<Self> {
+        connection
+            .execute(CREATE_TABLE, [])
+            .map_err(as_storage_err)?;
+        Ok(SqliteIndexer {
+            keys,
+            connection: Arc::new(Mutex::new(connection)),
+            _datum: PhantomData,
+        })
+    }
+}
+
+#[async_trait]
+impl<Datum, Keys> Indexer<Datum> for SqliteIndexer<Datum, Keys>
+where
+    Datum: Clone + Send + Sync + Into<PlutusData> + TryFrom<PlutusData>,
+    Keys: IndexKeys<Datum>,
+{
+    async fn apply(&self, event: &OutputEvent<Datum>) -> IndexerResult<()> {
+        match event {
+            OutputEvent::Created(output) => {
+                let tx_hash = hex::encode(output.id().tx_hash());
+                let index = output.id().index();
+                let serialized =
+                    serde_json::to_string(&IndexedOutput::from_output(output)?)
+                        .map_err(as_storage_err)?;
+                let keys = self.keys.keys(output);
+                self.blocking(move |connection| {
+                    for key in keys {
+                        connection.execute(
+                            "INSERT OR REPLACE INTO indexed_outputs
+                                (key, tx_hash, output_index, output)
+                                VALUES (?1, ?2, ?3, ?4)",
+                            params![key, tx_hash, index, serialized],
+                        )?;
+                    }
+                    Ok(())
+                })
+                .await
+            }
+            OutputEvent::Spent(output) => {
+                let tx_hash = hex::encode(output.id().tx_hash());
+                let index = output.id().index();
+                self.blocking(move |connection| {
+                    connection.execute(
+                        "DELETE FROM indexed_outputs
+                            WHERE tx_hash = ?1 AND output_index = ?2",
+                        params![tx_hash, index],
+                    )?;
+                    Ok(())
+                })
+                .await
+            }
+        }
+    }
+
+    async fn outputs_for(&self, key: &str) -> IndexerResult<Vec<Output<Datum>>> {
+        let key = key.to_string();
+        let rows = self
+            .blocking(move |connection| {
+                let mut statement = connection.prepare(
+                    "SELECT output FROM indexed_outputs
+                        WHERE key = ?1 ORDER BY tx_hash, output_index",
+                )?;
+                let rows = statement
+                    .query_map(params![key], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await?;
+        rows.iter()
+            .map(|serialized| {
+                let output: IndexedOutput =
+                    serde_json::from_str(serialized).map_err(as_storage_err)?;
+                let output: Output<Datum> = output.into_output()?;
+                Ok(output.with_typed_datum_if_possible())
+            })
+            .collect()
+    }
+}
+
+impl<Datum, Keys> SqliteIndexer<Datum, Keys> {
+    // rusqlite blocks, so run `query` on the blocking pool with the connection locked
+    async fn blocking<T, F>(&self, query: F) -> IndexerResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || {
+            let connection = connection.lock().expect("Lock poisoned");
+            query(&connection)
+        })
+        .await
+        .map_err(as_storage_err)?
+        .map_err(as_storage_err)
+    }
+}
+
+fn as_storage_err<E: std::error::Error + Send + Sync + 'static>(
+    error: E,
+) -> IndexerError {
+    IndexerError::Storage(Box::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::{
+        values::Values,
+        PolicyId,
+    };
+    use pallas_addresses::Address;
+
+    const SCRIPT: &str =
+        "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu";
+
+    fn account(id: u8, owner: i64) -> Output<i64> {
+        let address = Address::from_bech32(SCRIPT).unwrap();
+        Output::new_validator(vec![id; 32], 0, address, Values::default(), owner)
+    }
+
+    #[tokio::test]
+    async fn apply__persists_typed_outputs_until_spent() {
+        // given
+        let indexer = SqliteIndexer::<i64, _>::in_memory(|output: &Output<i64>| {
+            vec![output.typed_datum().unwrap().to_string()]
+        })
+        .unwrap();
+
+        // when
+        indexer
+            .apply(&OutputEvent::Created(account(1, 7)))
+            .await
+            .unwrap();
+        indexer
+            .apply(&OutputEvent::Created(account(2, 7)))
+            .await
+            .unwrap();
+        indexer
+            .apply(&OutputEvent::Spent(account(1, 7)))
+            .await
+            .unwrap();
+
+        // then
+        let sevens = indexer.outputs_for("7").await.unwrap();
+        assert_eq!(sevens.len(), 1);
+        assert_eq!(sevens[0].id(), account(2, 7).id());
+        assert_eq!(sevens[0].typed_datum(), Some(7));
+    }
+
+    #[tokio::test]
+    async fn outputs_for__round_trips_values_and_datums_through_the_database() {
+        // given
+        let indexer =
+            SqliteIndexer::<i64, _>::in_memory(|_: &Output<i64>| vec!["all".to_string()])
+                .unwrap();
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, 2_000_000);
+        values.add_one_value(
+            &PolicyId::native_token("aabbcc", &Some(vec![0x01])),
+            u64::MAX,
+        );
+        let address = Address::from_bech32(SCRIPT).unwrap();
+        let output = Output::new_validator(vec![3; 32], 4, address, values, i64::MIN)
+            .with_datum_hash(vec![5; 32]);
+
+        // when
+        indexer
+            .apply(&OutputEvent::Created(output.clone()))
+            .await
+            .unwrap();
+
+        // then
+        let stored = indexer.outputs_for("all").await.unwrap();
+        assert_eq!(stored, vec![output]);
+    }
+
+    #[test]
+    fn indexed_output__is_stored_with_its_version() {
+        // given
+        let output = account(1, 7);
+
+        // when
+        let serialized =
+            serde_json::to_value(IndexedOutput::from_output(&output).unwrap()).unwrap();
+
+        // then
+        assert_eq!(serialized["version"], "1");
+        let stored: IndexedOutput = serde_json::from_value(serialized).unwrap();
+        let stored: Output<i64> = stored.into_output().unwrap();
+        assert_eq!(stored.id(), output.id());
+    }
+}
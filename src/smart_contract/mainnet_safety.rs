@@ -0,0 +1,199 @@
+use crate::{
+    error::{
+        Error,
+        Result,
+    },
+    ledger_client::LedgerClient,
+    policy_id::PolicyId,
+    transaction::{
+        Action,
+        TxActions,
+    },
+};
+use pallas_addresses::{
+    Address,
+    Network,
+};
+use std::fmt;
+
+/// Asks whether to submit `tx_actions` to mainnet, putting the given lovelace at risk
+pub type MainnetConfirmation<Datum, Redeemer> =
+    fn(tx_actions: &TxActions<Datum, Redeemer>, lovelace_at_risk: u64) -> bool;
+
+/// Interlock guarding against accidentally submitting transactions to mainnet
+///
+/// Every mainnet submission is refused until mainnet is explicitly allowed or a
+/// confirmation callback is set. A cap on the lovelace at risk can be added on top.
+/// Transactions on other networks are never affected.
+pub struct MainnetSafety<Datum, Redeemer> {
+    allow_mainnet: bool,
+    confirmation: Option<MainnetConfirmation<Datum, Redeemer>>,
+    max_lovelace_at_risk: Option<u64>,
+}
+
+impl<Datum, Redeemer> Default for MainnetSafety<Datum, Redeemer> {
+    fn default() -> Self {
+        MainnetSafety {
+            allow_mainnet: false,
+            confirmation: None,
+            max_lovelace_at_risk: None,
+        }
+    }
+}
+
+impl<Datum, Redeemer> fmt::Debug for MainnetSafety<Datum, Redeemer> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MainnetSafety")
+            .field("allow_mainnet", &self.allow_mainnet)
+            .field("confirmation", &self.confirmation.is_some())
+            .field("max_lovelace_at_risk", &self.max_lovelace_at_risk)
+            .finish()
+    }
+}
+
+impl<Datum, Redeemer> MainnetSafety<Datum, Redeemer> {
+    /// Constructor for a [`MainnetSafety`] that refuses every mainnet submission
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Submit to mainnet without asking for confirmation
+    pub fn allow_mainnet(mut self) -> Self {
+        self.allow_mainnet = true;
+        self
+    }
+
+    /// Ask `confirmation` before every mainnet submission
+    pub fn with_confirmation(
+        mut self,
+        confirmation: MainnetConfirmation<Datum, Redeemer>,
+    ) -> Self {
+        self.confirmation = Some(confirmation);
+        self
+    }
+
+    /// Refuse mainnet submissions sending more than `max` lovelace away from the signer
+    pub fn with_max_lovelace_at_risk(mut self, max: u64) -> Self {
+        self.max_lovelace_at_risk = Some(max);
+        self
+    }
+
+    /// Check whether `tx_actions` may be submitted with `ledger_client`
+    pub async fn check<LC: LedgerClient<Datum, Redeemer>>(
+        &self,
+        tx_actions: &TxActions<Datum, Redeemer>,
+        ledger_client: &LC,
+    ) -> Result<()> {
+        let network = ledger_client.network().await?;
+        if !matches!(network, Network::Mainnet) {
+            return Ok(())
+        }
+        let signer = ledger_client.signer_base_address().await?;
+        self.check_mainnet(tx_actions, lovelace_at_risk(tx_actions, &signer))
+    }
+
+    fn check_mainnet(
+        &self,
+        tx_actions: &TxActions<Datum, Redeemer>,
+        at_risk: u64,
+    ) -> Result<()> {
+        if let Some(max) = self.max_lovelace_at_risk {
+            if at_risk > max {
+                return Err(Error::MainnetSafety(format!(
+                    "{at_risk} lovelace at risk is over the limit of {max}"
+                )))
+            }
+        }
+        match self.confirmation {
+            Some(confirmation) if confirmation(tx_actions, at_risk) => Ok(()),
+            Some(_) => Err(Error::MainnetSafety(
+                "Submission wasn't confirmed".to_string(),
+            )),
+            None if self.allow_mainnet => Ok(()),
+            None => Err(Error::MainnetSafety(
+                "Mainnet submissions aren't allowed".to_string(),
+            )),
+        }
+    }
+}
+
+/// Lovelace `tx_actions` send away from `signer`, to other wallets or locked at scripts
+pub fn lovelace_at_risk<Datum, Redeemer>(
+    tx_actions: &TxActions<Datum, Redeemer>,
+    signer: &Address,
+) -> u64 {
+    tx_actions
+        .actions
+        .iter()
+        .map(|action| match action {
+            Action::Transfer {
+                amount,
+                recipient,
+                policy_id: PolicyId::Lovelace,
+            } if recipient != signer => *amount,
+            Action::InitScript { values, .. } => {
+                values.get(&PolicyId::Lovelace).unwrap_or(0)
+            }
+            _ => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    fn me() -> Address {
+        Address::from_bech32("addr_test1qpuy2q9xel76qxdw8r29skldzc876cdgg9cugfg7mwh0zvpg3292mxuf3kq7nysjumlxjrlsfn9tp85r0l54l29x3qcs7nvyfm").unwrap()
+    }
+
+    fn alice() -> Address {
+        Address::from_bech32("addr_test1qzvrhz9v6lwcr26a52y8mmk2nzq37lky68359keq3dgth4lkzpnnjv8vf98m20lhqdzl60mcftq7r2lc4xtcsv0w6xjstag0ua").unwrap()
+    }
+
+    #[test]
+    fn lovelace_at_risk__ignores_transfers_to_signer() {
+        // given
+        let tx_actions: TxActions<(), ()> = TxActions::v2()
+            .with_transfer(100, alice(), PolicyId::Lovelace)
+            .with_transfer(50, me(), PolicyId::Lovelace);
+
+        // when
+        let actual = lovelace_at_risk(&tx_actions, &me());
+
+        // then
+        assert_eq!(actual, 100);
+    }
+
+    #[test]
+    fn check_mainnet__refuses_by_default() {
+        let tx_actions: TxActions<(), ()> = TxActions::v2();
+        let safety = MainnetSafety::new();
+
+        assert!(safety.check_mainnet(&tx_actions, 0).is_err());
+        assert!(safety.allow_mainnet().check_mainnet(&tx_actions, 0).is_ok());
+    }
+
+    #[test]
+    fn check_mainnet__refuses_over_cap_even_if_allowed() {
+        let tx_actions: TxActions<(), ()> = TxActions::v2();
+        let safety = MainnetSafety::new()
+            .allow_mainnet()
+            .with_max_lovelace_at_risk(10);
+
+        assert!(safety.check_mainnet(&tx_actions, 10).is_ok());
+        assert!(safety.check_mainnet(&tx_actions, 11).is_err());
+    }
+
+    #[test]
+    fn check_mainnet__asks_confirmation() {
+        let tx_actions: TxActions<(), ()> = TxActions::v2();
+        let safety =
+            MainnetSafety::new().with_confirmation(|_, at_risk| at_risk < 1_000_000);
+
+        assert!(safety.check_mainnet(&tx_actions, 999_999).is_ok());
+        assert!(safety.check_mainnet(&tx_actions, 1_000_000).is_err());
+    }
+}
@@ -0,0 +1,325 @@
+use crate::{
+    ledger_client::{
+        LedgerClient,
+        LedgerClientError,
+    },
+    output::{
+        Output,
+        ReferenceScript,
+    },
+    scripts::plutus_validator::plutus_data::{
+        DetailedJson,
+        PlutusData,
+    },
+    values::Values,
+    PolicyId,
+};
+use pallas_addresses::Address;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::fmt::Debug;
+use thiserror::Error;
+
+const CSV_HEADER: &str = "tx_hash,index,address,lovelace,assets,datum_hash,datum";
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("From LedgerClient: {0:?}")]
+    LedgerClient(#[from] LedgerClientError),
+    #[error("Invalid snapshot JSON: {0}")]
+    Json(String),
+    #[error("Invalid output in snapshot: {0}")]
+    InvalidOutput(String),
+}
+
+/// Specialized result type for snapshots
+pub type SnapshotResult<T, E = SnapshotError> = Result<T, E>;
+
+/// Native token held by a [`SnapshotOutput`]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SnapshotAsset {
+    policy_id: String,
+    asset_name: String,
+    amount: u64,
+}
+
+/// Single output of a [`ContractSnapshot`]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SnapshotOutput {
+    tx_hash: String,
+    index: u64,
+    address: String,
+    lovelace: u64,
+    assets: Vec<SnapshotAsset>,
+    #[serde(default)]
+    datum_hash: Option<String>,
+    #[serde(default)]
+    datum: Option<DetailedJson>,
+    #[serde(default)]
+    decoded_datum: Option<String>,
+    #[serde(default)]
+    reference_script: Option<ReferenceScript>,
+}
+
+impl SnapshotOutput {
+    fn new<Datum: Clone + Debug + Into<PlutusData>>(output: &Output<Datum>) -> Self {
+        SnapshotOutput {
+            tx_hash: hex::encode(output.id().tx_hash()),
+            index: output.id().index(),
+            address: output.owner().to_bech32().expect("Already validated"),
+            lovelace: output.values().get(&PolicyId::Lovelace).unwrap_or_default(),
+            assets: assets_of(output.values()),
+            datum_hash: output.datum_hash().map(hex::encode),
+            datum: output.datum_plutus_data().map(DetailedJson),
+            decoded_datum: output.typed_datum().map(|datum| format!("{datum:?}")),
+            reference_script: output.reference_script().cloned(),
+        }
+    }
+
+    /// Getter for the hex hash of the transaction that created the output
+    pub fn tx_hash(&self) -> &str {
+        &self.tx_hash
+    }
+
+    /// Getter for the index of the output in its transaction
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Getter for the bech32 address of the output
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Getter for the datum, if the output has one
+    ///
+    /// It's stored in cardano-cli's detailed schema JSON.
+    pub fn datum(&self) -> Option<&PlutusData> {
+        self.datum.as_ref().map(|DetailedJson(data)| data)
+    }
+
+    /// Getter for the debug representation of the typed datum, if it could be decoded
+    pub fn decoded_datum(&self) -> Option<&str> {
+        self.decoded_datum.as_deref()
+    }
+
+    fn to_output<Datum: Clone + TryFrom<PlutusData>>(
+        &self,
+    ) -> SnapshotResult<Output<Datum>> {
+        let invalid = |e: String| SnapshotError::InvalidOutput(e);
+        let tx_hash = hex::decode(&self.tx_hash).map_err(|e| invalid(e.to_string()))?;
+        let address =
+            Address::from_bech32(&self.address).map_err(|e| invalid(format!("{e:?}")))?;
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, self.lovelace);
+        for asset in self.assets.iter() {
+            let asset_name =
+                hex::decode(&asset.asset_name).map_err(|e| invalid(e.to_string()))?;
+            let asset_name = (!asset_name.is_empty()).then_some(asset_name);
+            values.add_one_value(
+                &PolicyId::native_token(&asset.policy_id, &asset_name),
+                asset.amount,
+            );
+        }
+        let mut output = match &self.datum {
+            Some(DetailedJson(data)) => Output::new_untyped_validator(
+                tx_hash,
+                self.index,
+                address,
+                values,
+                data.clone(),
+            )
+            .with_typed_datum_if_possible(),
+            None => Output::new_wallet(tx_hash, self.index, address, values),
+        };
+        if let Some(datum_hash) = &self.datum_hash {
+            let datum_hash =
+                hex::decode(datum_hash).map_err(|e| invalid(e.to_string()))?;
+            output = output.with_datum_hash(datum_hash);
+        }
+        if let Some(reference_script) = &self.reference_script {
+            output = output.with_reference_script(reference_script.clone());
+        }
+        Ok(output)
+    }
+
+    fn to_csv_row(&self) -> String {
+        let assets = self
+            .assets
+            .iter()
+            .map(|asset| {
+                format!("{}.{}:{}", asset.policy_id, asset.asset_name, asset.amount)
+            })
+            .collect::<Vec<_>>()
+            .join(";");
+        let datum = self
+            .datum
+            .as_ref()
+            .map(|DetailedJson(data)| data.to_detailed_json())
+            .unwrap_or_default();
+        [
+            self.tx_hash.clone(),
+            self.index.to_string(),
+            self.address.clone(),
+            self.lovelace.to_string(),
+            assets,
+            self.datum_hash.clone().unwrap_or_default(),
+            datum,
+        ]
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+/// Native tokens in `values`, sorted by policy id and asset name
+pub(crate) fn assets_of(values: &Values) -> Vec<SnapshotAsset> {
+    let mut assets: Vec<_> = values
+        .as_iter()
+        .filter_map(|(policy, amount)| match policy {
+            PolicyId::Lovelace => None,
+            PolicyId::NativeToken(id, name) => Some(SnapshotAsset {
+                policy_id: id.clone(),
+                asset_name: name.as_deref().map(hex::encode).unwrap_or_default(),
+                amount: *amount,
+            }),
+        })
+        .collect();
+    assets.sort_by(|a, b| {
+        (&a.policy_id, &a.asset_name).cmp(&(&b.policy_id, &b.asset_name))
+    });
+    assets
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Every output at a contract's addresses at a point in time
+///
+/// For audits, export it with [`ContractSnapshot::to_json`] or [`ContractSnapshot::to_csv`].
+/// To test against production-like state, read it back and seed a test ledger with
+/// [`ContractSnapshot::to_outputs`].
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ContractSnapshot {
+    taken_at: i64,
+    outputs: Vec<SnapshotOutput>,
+}
+
+impl ContractSnapshot {
+    /// Snapshot of all outputs at `addresses`, e.g. the contract's validators
+    pub async fn take<Datum, Redeemer, LC>(
+        ledger_client: &LC,
+        addresses: &[Address],
+    ) -> SnapshotResult<Self>
+    where
+        Datum: Clone + Debug + Send + Into<PlutusData>,
+        LC: LedgerClient<Datum, Redeemer>,
+    {
+        let taken_at = ledger_client.current_time_secs().await?;
+        let outputs = ledger_client
+            .all_outputs_at_addresses(addresses)
+            .await?
+            .iter()
+            .map(SnapshotOutput::new)
+            .collect();
+        Ok(ContractSnapshot { taken_at, outputs })
+    }
+
+    /// Getter for the ledger time in POSIX seconds when the snapshot was taken
+    pub fn taken_at(&self) -> i64 {
+        self.taken_at
+    }
+
+    /// Getter for the outputs in the snapshot
+    pub fn outputs(&self) -> &[SnapshotOutput] {
+        &self.outputs
+    }
+
+    /// Read a snapshot written by [`ContractSnapshot::to_json`]
+    pub fn from_json(json: &str) -> SnapshotResult<Self> {
+        serde_json::from_str(json).map_err(|e| SnapshotError::Json(e.to_string()))
+    }
+
+    /// Pretty printed JSON of the snapshot
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Snapshots always serialize")
+    }
+
+    /// CSV of the outputs, one row per output, with assets as `policy.name:amount` separated
+    /// by `;` and datums as detailed schema JSON
+    pub fn to_csv(&self) -> String {
+        let mut csv = CSV_HEADER.to_string();
+        for output in self.outputs.iter() {
+            csv.push('\n');
+            csv.push_str(&output.to_csv_row());
+        }
+        csv.push('\n');
+        csv
+    }
+
+    /// The snapshotted outputs, e.g. for
+    /// [`TestLedgerClientBuilder::with_outputs`](crate::ledger_client::test_ledger_client::TestLedgerClientBuilder::with_outputs)
+    pub fn to_outputs<Datum: Clone + TryFrom<PlutusData>>(
+        &self,
+    ) -> SnapshotResult<Vec<Output<Datum>>> {
+        self.outputs.iter().map(SnapshotOutput::to_output).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::ledger_client::test_ledger_client::TestLedgerClientBuilder;
+
+    const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+    const SCRIPT: &str =
+        "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu";
+
+    #[tokio::test]
+    async fn take__round_trips_outputs_into_a_new_test_ledger() {
+        // given
+        let alice = Address::from_bech32(ALICE).unwrap();
+        let script = Address::from_bech32(SCRIPT).unwrap();
+        let token = PolicyId::native_token("abcd", &Some(b"NFT".to_vec()));
+        let production = TestLedgerClientBuilder::<i64, ()>::new(&alice)
+            .start_output(&script)
+            .with_value(PolicyId::Lovelace, 2_000_000)
+            .with_value(token.clone(), 1)
+            .with_datum(42)
+            .finish_output()
+            .fund_lovelace(&alice, 10_000_000)
+            .build_in_memory();
+
+        // when
+        let snapshot = ContractSnapshot::take(&production, &[script.clone()])
+            .await
+            .unwrap();
+        let read_back = ContractSnapshot::from_json(&snapshot.to_json()).unwrap();
+        let seeded = TestLedgerClientBuilder::<i64, ()>::new(&alice)
+            .with_outputs(read_back.to_outputs().unwrap())
+            .build_in_memory();
+
+        // then
+        assert_eq!(read_back, snapshot);
+        assert_eq!(snapshot.outputs().len(), 1);
+        assert_eq!(snapshot.outputs()[0].decoded_datum(), Some("42"));
+        let csv = snapshot.to_csv();
+        assert!(csv.starts_with(CSV_HEADER));
+        assert!(csv.contains("2000000,abcd.4e4654:1,,\"{\"\"int\"\":42}\""));
+        assert_eq!(
+            seeded.all_outputs_at_address(&script).await.unwrap(),
+            production.all_outputs_at_address(&script).await.unwrap()
+        );
+    }
+}
@@ -0,0 +1,444 @@
+use crate::{
+    ledger_client::LedgerClientResult,
+    output::{
+        Output,
+        UnbuiltOutput,
+    },
+    scripts::plutus_validator::plutus_data::{
+        DetailedJson,
+        PlutusData,
+    },
+    smart_contract::snapshot::{
+        assets_of,
+        SnapshotAsset,
+    },
+    transaction::{
+        TxId,
+        TxPreview,
+        UnbuiltTransaction,
+    },
+    values::Values,
+    PolicyId,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    fmt,
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+};
+
+/// Script input spent by a recorded transaction
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RedeemedInput {
+    output_id: String,
+    address: String,
+    datum: Option<DetailedJson>,
+    redeemer: Option<DetailedJson>,
+}
+
+/// Output created by a recorded transaction
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RecordedOutput {
+    address: String,
+    lovelace: u64,
+    assets: Vec<SnapshotAsset>,
+    datum: Option<DetailedJson>,
+}
+
+/// Tokens minted, or burned with a negative amount, by a recorded transaction
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RecordedMint {
+    policy_id: String,
+    asset_name: String,
+    amount: i64,
+    redeemer: Option<DetailedJson>,
+}
+
+/// Machine-readable account of a transaction a
+/// [`SmartContract`](crate::smart_contract::SmartContract) submitted, or tried to
+///
+/// Datums and redeemers are in cardano-cli's detailed schema JSON. The inputs, outputs,
+/// fee and execution units are those of the built transaction when the ledger client can
+/// describe it, see
+/// [`LedgerClient::issue_with_preview`](crate::ledger_client::LedgerClient::issue_with_preview).
+/// Otherwise they are the ones the endpoint asked for and the fee is missing.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TxRecord {
+    endpoint: String,
+    tx_id: Option<String>,
+    error: Option<String>,
+    inputs: Vec<String>,
+    redeemed: Vec<RedeemedInput>,
+    outputs: Vec<RecordedOutput>,
+    mints: Vec<RecordedMint>,
+    valid_range: (Option<i64>, Option<i64>),
+    fee: Option<u64>,
+    ex_units: Vec<(i64, i64)>,
+}
+
+impl TxRecord {
+    /// Getter for the debug representation of the endpoint(s) the transaction is for
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Getter for the id of the transaction, if it was submitted
+    pub fn tx_id(&self) -> Option<&str> {
+        self.tx_id.as_deref()
+    }
+
+    /// Getter for why the transaction failed, if it did
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Getter for the ids, as `<tx hash>#<index>`, of all the spent outputs
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    /// Getter for the outputs created by the transaction
+    pub fn outputs(&self) -> &[RecordedOutput] {
+        &self.outputs
+    }
+
+    /// Getter for the fee in lovelace, if known
+    pub fn fee(&self) -> Option<u64> {
+        self.fee
+    }
+
+    /// Getter for the memory and CPU budget of each script run by the transaction
+    pub fn ex_units(&self) -> &[(i64, i64)] {
+        &self.ex_units
+    }
+
+    /// Single line JSON of the record
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Records always serialize")
+    }
+}
+
+/// Where [`TxRecord`]s go, e.g. a file, a log or a database
+///
+/// Recording happens after submission, so sinks can't stop a transaction and should deal
+/// with their own failures. Closures taking a record work too.
+pub trait TxRecordSink: Send + Sync {
+    /// Keep `record`
+    fn record(&self, record: &TxRecord);
+}
+
+impl<F> TxRecordSink for F
+where
+    F: Fn(&TxRecord) + Send + Sync,
+{
+    fn record(&self, record: &TxRecord) {
+        self(record)
+    }
+}
+
+/// [`TxRecordSink`] appending each record as a line of JSON to a file
+pub struct JsonLinesSink {
+    path: PathBuf,
+}
+
+impl JsonLinesSink {
+    /// Constructor for a [`JsonLinesSink`] appending to the file at `path`, created if missing
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        JsonLinesSink { path: path.into() }
+    }
+}
+
+impl TxRecordSink for JsonLinesSink {
+    fn record(&self, record: &TxRecord) {
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{}", record.to_json()));
+        if let Err(e) = written {
+            tracing::warn!("Failed to record transaction to {:?}: {e}", self.path);
+        }
+    }
+}
+
+/// [`TxRecordSink`] emitting each record as a `tracing` event with target
+/// `naumachia::tx_record`
+pub struct TracingSink;
+
+impl TxRecordSink for TracingSink {
+    fn record(&self, record: &TxRecord) {
+        tracing::info!(target: "naumachia::tx_record", record = %record.to_json());
+    }
+}
+
+/// Turns the transactions of a smart contract into [`TxRecord`]s for a [`TxRecordSink`]
+///
+/// Holds the datum and redeemer conversions, so only contracts whose types convert to
+/// [`PlutusData`] can record.
+pub(crate) struct TxRecorder<Datum, Redeemer> {
+    sink: Box<dyn TxRecordSink>,
+    describe: fn(&str, &UnbuiltTransaction<Datum, Redeemer>) -> TxRecord,
+    add_preview: fn(&mut TxRecord, &TxPreview<Datum>),
+}
+
+impl<Datum, Redeemer> TxRecorder<Datum, Redeemer>
+where
+    Datum: Clone + Into<PlutusData>,
+    Redeemer: Clone + Into<PlutusData>,
+{
+    pub(crate) fn new(sink: impl TxRecordSink + 'static) -> Self {
+        TxRecorder {
+            sink: Box::new(sink),
+            describe: describe::<Datum, Redeemer>,
+            add_preview: add_preview::<Datum>,
+        }
+    }
+}
+
+impl<Datum, Redeemer> TxRecorder<Datum, Redeemer> {
+    /// What `tx`, built for `endpoint`, asks for
+    pub(crate) fn describe(
+        &self,
+        endpoint: &str,
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
+    ) -> TxRecord {
+        (self.describe)(endpoint, tx)
+    }
+
+    /// Complete `record` with how issuing went and send it to the sink
+    pub(crate) fn record(
+        &self,
+        mut record: TxRecord,
+        issued: &LedgerClientResult<(TxId, Option<TxPreview<Datum>>)>,
+    ) {
+        match issued {
+            Ok((tx_id, preview)) => {
+                record.tx_id = Some(tx_id.as_str());
+                if let Some(preview) = preview {
+                    (self.add_preview)(&mut record, preview);
+                }
+            }
+            Err(e) => record.error = Some(e.to_string()),
+        }
+        self.sink.record(&record);
+    }
+}
+
+impl<Datum, Redeemer> fmt::Debug for TxRecorder<Datum, Redeemer> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TxRecorder").finish_non_exhaustive()
+    }
+}
+
+fn describe<Datum, Redeemer>(
+    endpoint: &str,
+    tx: &UnbuiltTransaction<Datum, Redeemer>,
+) -> TxRecord
+where
+    Datum: Clone + Into<PlutusData>,
+    Redeemer: Clone + Into<PlutusData>,
+{
+    let redeemed: Vec<_> = tx
+        .script_inputs()
+        .iter()
+        .map(|(output, redeemer, _)| RedeemedInput {
+            output_id: output.id().to_string(),
+            address: output.owner().to_bech32().expect("Already validated"),
+            datum: output.datum_plutus_data().map(DetailedJson),
+            redeemer: Some(DetailedJson(redeemer.clone().into())),
+        })
+        .collect();
+    let inputs = redeemed
+        .iter()
+        .map(|input| input.output_id.clone())
+        .chain(
+            tx.specific_wallet_inputs
+                .iter()
+                .map(|input| input.id().to_string()),
+        )
+        .collect();
+    let outputs = tx
+        .unbuilt_outputs()
+        .iter()
+        .map(|output| match output {
+            UnbuiltOutput::Wallet { owner, values } => {
+                recorded_output(owner.clone(), values, None)
+            }
+            UnbuiltOutput::Validator {
+                script_address,
+                values,
+                datum,
+                ..
+            } => recorded_output(
+                script_address.clone(),
+                values,
+                Some(datum.clone().into()),
+            ),
+        })
+        .collect();
+    let mints = tx
+        .minting
+        .iter()
+        .map(|(amount, asset_name, redeemer, policy)| RecordedMint {
+            policy_id: policy.id().unwrap_or_default(),
+            asset_name: asset_name.as_deref().map(hex::encode).unwrap_or_default(),
+            amount: *amount,
+            redeemer: Some(DetailedJson(redeemer.clone().into())),
+        })
+        .collect();
+    TxRecord {
+        endpoint: endpoint.to_string(),
+        tx_id: None,
+        error: None,
+        inputs,
+        redeemed,
+        outputs,
+        mints,
+        valid_range: tx.valid_range,
+        fee: None,
+        ex_units: Vec::new(),
+    }
+}
+
+fn add_preview<Datum: Clone + Into<PlutusData>>(
+    record: &mut TxRecord,
+    preview: &TxPreview<Datum>,
+) {
+    record.inputs = preview.inputs().iter().map(|id| id.to_string()).collect();
+    record.outputs = preview.outputs().iter().map(built_output).collect();
+    record.fee = Some(preview.fee());
+    record.ex_units = preview
+        .ex_units()
+        .iter()
+        .map(|cost| (cost.mem(), cost.cpu()))
+        .collect();
+}
+
+fn built_output<Datum: Clone + Into<PlutusData>>(
+    output: &Output<Datum>,
+) -> RecordedOutput {
+    recorded_output(
+        output.owner().to_bech32().expect("Already validated"),
+        output.values(),
+        output.datum_plutus_data(),
+    )
+}
+
+fn recorded_output(
+    address: String,
+    values: &Values,
+    datum: Option<PlutusData>,
+) -> RecordedOutput {
+    RecordedOutput {
+        address,
+        lovelace: values.get(&PolicyId::Lovelace).unwrap_or_default(),
+        assets: assets_of(values),
+        datum: datum.map(DetailedJson),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::{
+        ledger_client::{
+            test_ledger_client::TestLedgerClientBuilder,
+            LedgerClient,
+        },
+        logic::{
+            error::SCLogicResult,
+            SCLogic,
+        },
+        smart_contract::{
+            SmartContract,
+            SmartContractTrait,
+        },
+        TxActions,
+    };
+    use async_trait::async_trait;
+    use pallas_addresses::Address;
+    use std::sync::{
+        Arc,
+        Mutex,
+    };
+
+    const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+    const SCRIPT: &str =
+        "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu";
+
+    #[derive(Debug, Eq, PartialEq)]
+    struct Lock;
+
+    #[async_trait]
+    impl SCLogic for Lock {
+        type Endpoints = i64;
+        type Lookups = ();
+        type LookupResponses = ();
+        type Datums = i64;
+        type Redeemers = ();
+
+        async fn handle_endpoint<Record: LedgerClient<i64, ()>>(
+            datum: i64,
+            _ledger_client: &Record,
+        ) -> SCLogicResult<TxActions<i64, ()>> {
+            let mut values = Values::default();
+            values.add_one_value(&PolicyId::Lovelace, 5_000_000);
+            let address = Address::from_bech32(SCRIPT).unwrap();
+            Ok(TxActions::v2().with_script_init(datum, values, address))
+        }
+
+        async fn lookup<Record: LedgerClient<i64, ()>>(
+            _query: (),
+            _ledger_client: &Record,
+        ) -> SCLogicResult<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn hit_endpoint__records_built_transaction_and_failures() {
+        // given
+        let alice = Address::from_bech32(ALICE).unwrap();
+        let ledger_client = TestLedgerClientBuilder::new(&alice)
+            .fund_lovelace(&alice, 7_000_000)
+            .build_in_memory();
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let sink_records = records.clone();
+        let contract = SmartContract::new(Lock, ledger_client).with_tx_record_sink(
+            move |record: &TxRecord| sink_records.lock().unwrap().push(record.clone()),
+        );
+
+        // when
+        let tx_id = contract.hit_endpoint(42).await.unwrap();
+        let failed = contract.hit_endpoint(43).await;
+
+        // then
+        assert!(failed.is_err());
+        let records = records.lock().unwrap();
+        assert_eq!(records.len(), 2);
+        let recorded = &records[0];
+        assert_eq!(recorded.endpoint(), "42");
+        assert_eq!(recorded.tx_id(), Some(tx_id.as_str().as_str()));
+        assert_eq!(recorded.fee(), Some(0));
+        let locked = recorded
+            .outputs()
+            .iter()
+            .find(|output| output.address == SCRIPT)
+            .unwrap();
+        assert_eq!(
+            locked.datum,
+            Some(DetailedJson(PlutusData::BigInt(42_i64.into())))
+        );
+        assert!(recorded.to_json().contains(r#""datum":{"int":42}"#));
+        let read_back: TxRecord = serde_json::from_str(&recorded.to_json()).unwrap();
+        assert_eq!(&read_back, recorded);
+        assert!(records[1].error().is_some());
+        assert_eq!(records[1].tx_id(), None);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{
+    ledger_client::{
+        LedgerClient,
+        LedgerClientResult,
+    },
+    output::Output,
+};
+use futures::{
+    stream,
+    Stream,
+};
+use pallas_addresses::Address;
+use std::{
+    collections::VecDeque,
+    time::Duration,
+};
+
+/// Default time between polls, roughly one block
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Change to the outputs at a watched address
+#[derive(Clone, Debug, PartialEq)]
+pub enum OutputEvent<Datum> {
+    /// A new output was created at the address
+    Created(Output<Datum>),
+    /// An output at the address was spent
+    Spent(Output<Datum>),
+}
+
+struct WatchState<Datum> {
+    known: Option<Vec<Output<Datum>>>,
+    pending: VecDeque<OutputEvent<Datum>>,
+    polled: bool,
+}
+
+impl<Datum: Clone> WatchState<Datum> {
+    fn update(&mut self, current: Vec<Output<Datum>>) {
+        if let Some(known) = &self.known {
+            for output in current.iter() {
+                if !known.iter().any(|k| k.id() == output.id()) {
+                    self.pending.push_back(OutputEvent::Created(output.clone()));
+                }
+            }
+            for output in known.iter() {
+                if !current.iter().any(|c| c.id() == output.id()) {
+                    self.pending.push_back(OutputEvent::Spent(output.clone()));
+                }
+            }
+        }
+        self.known = Some(current);
+    }
+}
+
+/// Stream of [`OutputEvent`]s at `address`, found by polling `ledger_client` every `interval`
+///
+/// The outputs present at the first poll are the baseline and aren't reported. Failed
+/// polls are yielded as errors and the stream keeps polling.
+pub fn poll_address<'a, Datum, Redeemer, LC>(
+    ledger_client: &'a LC,
+    address: Address,
+    interval: Duration,
+) -> impl Stream<Item = LedgerClientResult<OutputEvent<Datum>>> + 'a
+where
+    Datum: Clone + 'a,
+    LC: LedgerClient<Datum, Redeemer>,
+{
+    let state = WatchState {
+        known: None,
+        pending: VecDeque::new(),
+        polled: false,
+    };
+    stream::unfold(state, move |mut state| {
+        let address = address.clone();
+        async move {
+            loop {
+                if let Some(event) = state.pending.pop_front() {
+                    return Some((Ok(event), state))
+                }
+                if state.polled {
+                    crate::runtime::sleep(interval).await;
+                }
+                state.polled = true;
+                match ledger_client.all_outputs_at_address(&address).await {
+                    Ok(current) => state.update(current),
+                    Err(e) => return Some((Err(e), state)),
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::{
+        ledger_client::test_ledger_client::TestLedgerClientBuilder,
+        PolicyId,
+        TxActions,
+    };
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn poll_address__reports_new_outputs() {
+        // given
+        let me = Address::from_bech32("addr_test1qpuy2q9xel76qxdw8r29skldzc876cdgg9cugfg7mwh0zvpg3292mxuf3kq7nysjumlxjrlsfn9tp85r0l54l29x3qcs7nvyfm").unwrap();
+        let alice = Address::from_bech32("addr_test1qzvrhz9v6lwcr26a52y8mmk2nzq37lky68359keq3dgth4lkzpnnjv8vf98m20lhqdzl60mcftq7r2lc4xtcsv0w6xjstag0ua").unwrap();
+        let ledger_client = TestLedgerClientBuilder::<(), ()>::new(&me)
+            .start_output(&me)
+            .with_value(PolicyId::Lovelace, 100)
+            .finish_output()
+            .build_in_memory();
+        let events =
+            poll_address(&ledger_client, alice.clone(), Duration::from_millis(1));
+        futures::pin_mut!(events);
+
+        // when
+        let transfer = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let tx = TxActions::v1()
+                .with_transfer(60, alice.clone(), PolicyId::Lovelace)
+                .to_unbuilt_tx()
+                .unwrap();
+            ledger_client.issue(tx).await.unwrap();
+        };
+        let (event, _) = tokio::join!(events.next(), transfer);
+
+        // then
+        match event.unwrap().unwrap() {
+            OutputEvent::Created(output) => {
+                assert_eq!(output.owner(), alice);
+                assert_eq!(output.values().get(&PolicyId::Lovelace), Some(60));
+            }
+            OutputEvent::Spent(_) => panic!("expected a created output"),
+        }
+    }
+}
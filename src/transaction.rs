@@ -1,32 +1,56 @@
 use crate::{
+    address::{
+        check_network,
+        AddressResult,
+    },
     error::*,
     output::{
+        DatumAttachment,
         Output,
+        OutputId,
         UnbuiltOutput,
     },
     policy_id::PolicyId,
     scripts::{
+        deployments::DeploymentRegistry,
+        ExecutionCost,
         MintingPolicy,
         Validator,
     },
-    transaction::nested_value_map::{
-        add_amount_to_nested_map,
-        nested_map_to_vecs,
+    transaction::{
+        metadata::TxMetadata,
+        nested_value_map::TransferOutputs,
     },
+    trireme_ledger_client::Network as TriremeNetwork,
     values::Values,
 };
-use pallas_addresses::Address;
-use std::{
-    cell::RefCell,
-    collections::HashMap,
-    fmt::Debug,
+use pallas_addresses::{
+    Address,
+    Network,
+};
+use serde::{
+    Deserialize,
+    Serialize,
 };
+use std::fmt::Debug;
 
+/// Ledger eras module
+pub mod era;
+/// Transaction metadata module
+pub mod metadata;
 pub(crate) mod nested_value_map;
+pub mod snapshot;
 
 type RedemptionDetails<Datum, Redeemer> =
     (Output<Datum>, Redeemer, Box<dyn Validator<Datum, Redeemer>>);
 
+/// Redeemer computed from the [`TxShape`] once every action is known
+///
+/// Returning an error fails [`TxActions::to_unbuilt_tx`], e.g. when the output the redeemer
+/// should point at isn't in the transaction.
+pub type LazyRedeemer<Datum, Redeemer> =
+    Box<dyn FnOnce(&TxShape<'_, Datum>) -> Result<Redeemer> + Send + Sync>;
+
 /// Declarative constraints for specifying what a transaction should do.
 ///
 /// Primarily used within the `endpoint` for a [`SCLogic`](crate::logic::SCLogic)
@@ -64,7 +88,18 @@ pub enum Action<Datum, Redeemer> {
         /// Amount to mint
         amount: u64,
         /// Name of the asset to mint
-        asset_name: Option<String>,
+        asset_name: Option<Vec<u8>>,
+        /// Redeemer used with the minting policy
+        redeemer: Redeemer,
+        /// Minting policy
+        policy: Box<dyn MintingPolicy<Redeemer>>,
+    },
+    /// Specify a burning of `amount` of `asset_name` with `redeemer` and `policy`
+    Burn {
+        /// Amount to burn
+        amount: u64,
+        /// Name of the asset to burn
+        asset_name: Option<Vec<u8>>,
         /// Redeemer used with the minting policy
         redeemer: Redeemer,
         /// Minting policy
@@ -78,6 +113,8 @@ pub enum Action<Datum, Redeemer> {
         values: Values,
         /// Address to lock at
         address: Address,
+        /// How the output carries `datum`
+        datum_attachment: DatumAttachment,
     },
     /// Specify a script output that will be redeemed with `redeemer` and `script`
     RedeemScriptOutput {
@@ -88,11 +125,31 @@ pub enum Action<Datum, Redeemer> {
         /// Validator used to validate the transaction
         script: Box<dyn Validator<Datum, Redeemer>>, /* Is there a way to do this without `dyn`? */
     },
+    /// Specify a script output that will be redeemed with a redeemer computed from the
+    /// [`TxShape`] and `script`
+    RedeemScriptOutputLazily {
+        /// Output to redeem that has attached datum
+        output: Output<Datum>,
+        /// Computes the redeemer used with the validator
+        redeemer: LazyRedeemer<Datum, Redeemer>,
+        /// Validator used to validate the transaction
+        script: Box<dyn Validator<Datum, Redeemer>>,
+    },
     /// Specify a specific input to use in the transaction
     SpecificInput {
         /// Input to use
         input: Output<Datum>,
     },
+    /// Specify an output holding a deployed reference script, read without spending it
+    ReferenceScript {
+        /// Output holding the script
+        output: Output<Datum>,
+    },
+    /// Specify an output to read without spending it, e.g. an oracle's datum
+    ReferenceInput {
+        /// Output to read
+        output: Output<Datum>,
+    },
 }
 
 // TODO: Maybe we should make V1 and V2 TxActions be completely different types,
@@ -105,6 +162,8 @@ pub struct TxActions<Datum, Redeemer> {
     pub actions: Vec<Action<Datum, Redeemer>>,
     /// Valid range in seconds since the Unix epoch
     pub valid_range: Range,
+    /// Metadata to attach
+    pub metadata: TxMetadata,
 }
 
 impl<Datum, Redeemer> TxActions<Datum, Redeemer> {
@@ -114,6 +173,7 @@ impl<Datum, Redeemer> TxActions<Datum, Redeemer> {
             script_version: TransactionVersion::V1,
             actions: Vec::new(),
             valid_range: (None, None),
+            metadata: TxMetadata::default(),
         }
     }
 
@@ -123,11 +183,12 @@ impl<Datum, Redeemer> TxActions<Datum, Redeemer> {
             script_version: TransactionVersion::V2,
             actions: Vec::new(),
             valid_range: (None, None),
+            metadata: TxMetadata::default(),
         }
     }
 }
 
-impl<Datum: Clone, Redeemer> TxActions<Datum, Redeemer> {
+impl<Datum, Redeemer> TxActions<Datum, Redeemer> {
     /// Add a transfer to the actions.
     /// This will transfer `amount` of `policy_id` to `recipient` without specifying specific inputs
     /// and outputs.
@@ -152,7 +213,7 @@ impl<Datum: Clone, Redeemer> TxActions<Datum, Redeemer> {
     pub fn with_mint(
         mut self,
         amount: u64,
-        asset_name: Option<String>,
+        asset_name: Option<Vec<u8>>,
         redeemer: Redeemer,
         policy: Box<dyn MintingPolicy<Redeemer>>,
     ) -> Self {
@@ -166,18 +227,59 @@ impl<Datum: Clone, Redeemer> TxActions<Datum, Redeemer> {
         self
     }
 
+    /// Add a burn to the actions.
+    /// This will burn `amount` of `asset_name` from the signer's wallet, with `redeemer` for
+    /// `policy`.
+    pub fn with_burn(
+        mut self,
+        amount: u64,
+        asset_name: Option<Vec<u8>>,
+        redeemer: Redeemer,
+        policy: Box<dyn MintingPolicy<Redeemer>>,
+    ) -> Self {
+        let action = Action::Burn {
+            amount,
+            asset_name,
+            redeemer,
+            policy,
+        };
+        self.actions.push(action);
+        self
+    }
+
     /// Add a script init to the actions.
-    /// This will lock the `values` at the `address` with the `datum`.
+    /// This will lock the `values` at the `address` with the `datum`. The output carries
+    /// only the datum hash, use [`Self::with_script_init_inline_datum`] to store it inline.
     pub fn with_script_init(
         mut self,
         datum: Datum,
-        values: Values,
+        values: impl Into<Values>,
         address: Address,
     ) -> Self {
         let action = Action::InitScript {
             datum,
-            values,
+            values: values.into(),
             address,
+            datum_attachment: DatumAttachment::Hash,
+        };
+        self.actions.push(action);
+        self
+    }
+
+    /// Add a script init that stores `datum` inline on the output, for Plutus V2 scripts
+    /// that read it from there.
+    /// This will lock the `values` at the `address` with the `datum`.
+    pub fn with_script_init_inline_datum(
+        mut self,
+        datum: Datum,
+        values: impl Into<Values>,
+        address: Address,
+    ) -> Self {
+        let action = Action::InitScript {
+            datum,
+            values: values.into(),
+            address,
+            datum_attachment: DatumAttachment::Inline,
         };
         self.actions.push(action);
         self
@@ -202,6 +304,25 @@ impl<Datum: Clone, Redeemer> TxActions<Datum, Redeemer> {
         self
     }
 
+    /// Add a script redeem whose redeemer is computed by `redeemer` from the [`TxShape`].
+    /// For validators whose redeemer points at parts of the transaction, e.g. the index of a
+    /// continuing output, which aren't known until every action has been added.
+    /// An error from `redeemer` is returned by [`TxActions::to_unbuilt_tx`].
+    pub fn with_script_redeem_lazy(
+        mut self,
+        output: Output<Datum>,
+        redeemer: impl FnOnce(&TxShape<'_, Datum>) -> Result<Redeemer> + Send + Sync + 'static,
+        script: Box<dyn Validator<Datum, Redeemer>>,
+    ) -> Self {
+        let action = Action::RedeemScriptOutputLazily {
+            output,
+            redeemer: Box::new(redeemer),
+            script,
+        };
+        self.actions.push(action);
+        self
+    }
+
     /// Add a specific input to the actions.
     /// **NOTE**: if you are using CML, this can break if your input is too small and you don't
     /// specify any specific OUTPUTs: https://github.com/MitchTurner/naumachia/issues/73
@@ -211,6 +332,76 @@ impl<Datum: Clone, Redeemer> TxActions<Datum, Redeemer> {
         self
     }
 
+    /// Use the script deployed on `output` by reference.
+    /// Script inputs and minting policies with the same script hash are then witnessed
+    /// through `output` as a reference input, instead of attaching the whole script.
+    pub fn with_reference_script(mut self, output: Output<Datum>) -> Self {
+        let action = Action::ReferenceScript { output };
+        self.actions.push(action);
+        self
+    }
+
+    /// Read `output` as a reference input, without spending it.
+    /// Scripts run by the transaction can see its value and datum, e.g. to read a price
+    /// posted by an oracle. Needs a V2 transaction.
+    pub fn with_reference_input(mut self, output: Output<Datum>) -> Self {
+        let action = Action::ReferenceInput { output };
+        self.actions.push(action);
+        self
+    }
+
+    /// Use the scripts that `registry` knows were deployed on `network` by reference.
+    /// Scripts that are already used by reference are left alone, and V1 transactions,
+    /// which can't have reference inputs, are returned unchanged.
+    pub fn with_deployments(
+        self,
+        registry: &DeploymentRegistry,
+        network: &TriremeNetwork,
+    ) -> Self {
+        if let TransactionVersion::V1 = self.script_version {
+            return self
+        }
+        let mut referenced: Vec<Vec<u8>> = self
+            .actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::ReferenceScript { output }
+                | Action::ReferenceInput { output } => output
+                    .reference_script()
+                    .map(|script| script.hash().to_vec()),
+                _ => None,
+            })
+            .collect();
+        let script_hashes: Vec<Vec<u8>> = self
+            .actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::RedeemScriptOutput { script, .. }
+                | Action::RedeemScriptOutputLazily { script, .. } => {
+                    validator_hash(script.as_ref())
+                }
+                Action::Mint { policy, .. } | Action::Burn { policy, .. } => {
+                    policy.id().ok().and_then(|id| hex::decode(id).ok())
+                }
+                _ => None,
+            })
+            .collect();
+        let mut actions = self;
+        for script_hash in script_hashes {
+            if referenced.contains(&script_hash) {
+                continue
+            }
+            let deployment = registry
+                .find(network, &script_hash)
+                .and_then(|deployment| deployment.to_output(&script_hash));
+            if let Some(output) = deployment {
+                actions = actions.with_reference_script(output);
+                referenced.push(script_hash);
+            }
+        }
+        actions
+    }
+
     /// Specify valid range in seconds since the Unix epoch
     pub fn with_valid_range_secs(
         mut self,
@@ -221,19 +412,76 @@ impl<Datum: Clone, Redeemer> TxActions<Datum, Redeemer> {
         self
     }
 
+    /// Attach `metadata`, replacing metadata added earlier under the same labels
+    pub fn with_metadata(mut self, metadata: TxMetadata) -> Self {
+        self.metadata.extend(metadata);
+        self
+    }
+
+    /// Combine the actions of `self` and `other` so they are submitted as one transaction
+    ///
+    /// Both must use the same script version and can't attach metadata under the same
+    /// label. The combined valid range is the overlap of both ranges.
+    pub fn merge(mut self, other: TxActions<Datum, Redeemer>) -> Result<Self> {
+        let TxActions {
+            script_version,
+            actions,
+            valid_range,
+            metadata,
+        } = other;
+        match (&self.script_version, &script_version) {
+            (TransactionVersion::V1, TransactionVersion::V1)
+            | (TransactionVersion::V2, TransactionVersion::V2) => {}
+            _ => {
+                return Err(Error::IncompatibleTxActions(
+                    "script versions differ".to_string(),
+                ))
+            }
+        }
+        let lower = match (self.valid_range.0, valid_range.0) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        let upper = match (self.valid_range.1, valid_range.1) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        if let (Some(lower), Some(upper)) = (lower, upper) {
+            if lower >= upper {
+                return Err(Error::IncompatibleTxActions(
+                    "valid ranges don't overlap".to_string(),
+                ))
+            }
+        }
+        if let Some(label) = metadata
+            .labels()
+            .find(|label| self.metadata.get(*label).is_some())
+        {
+            return Err(Error::IncompatibleTxActions(format!(
+                "both attach metadata under label {label}"
+            )))
+        }
+        self.actions.extend(actions);
+        self.valid_range = (lower, upper);
+        self.metadata.extend(metadata);
+        Ok(self)
+    }
+
     /// Convert the TxActions into an [`UnbuiltTransaction`] that can be consumed by a [`LedgerClient`]
     /// to submit a fully formed transaction.
     pub fn to_unbuilt_tx(self) -> Result<UnbuiltTransaction<Datum, Redeemer>> {
         let TxActions {
             script_version,
             actions,
-            ..
+            valid_range,
+            metadata,
         } = self;
-        let mut min_output_values: HashMap<String, RefCell<Values>> = HashMap::new();
+        let mut transfers = TransferOutputs::default();
         let mut minting = Vec::new();
-        let mut script_inputs: Vec<RedemptionDetails<Datum, Redeemer>> = Vec::new();
+        let mut redemptions = Vec::new();
         let mut specific_outputs: Vec<UnbuiltOutput<Datum>> = Vec::new();
         let mut specific_wallet_inputs: Vec<Output<Datum>> = Vec::new();
+        let mut reference_inputs: Vec<Output<Datum>> = Vec::new();
 
         for action in actions {
             match action {
@@ -242,12 +490,7 @@ impl<Datum: Clone, Redeemer> TxActions<Datum, Redeemer> {
                     recipient,
                     policy_id: policy,
                 } => {
-                    add_amount_to_nested_map(
-                        &mut min_output_values,
-                        amount,
-                        &recipient,
-                        &policy,
-                    );
+                    transfers.add_amount(amount, recipient, &policy);
                 }
                 Action::Mint {
                     amount,
@@ -255,18 +498,28 @@ impl<Datum: Clone, Redeemer> TxActions<Datum, Redeemer> {
                     redeemer,
                     policy,
                 } => {
-                    minting.push((amount, asset_name, redeemer, policy));
+                    minting.push((mint_amount(amount)?, asset_name, redeemer, policy));
+                }
+                Action::Burn {
+                    amount,
+                    asset_name,
+                    redeemer,
+                    policy,
+                } => {
+                    minting.push((-mint_amount(amount)?, asset_name, redeemer, policy));
                 }
                 Action::InitScript {
                     datum,
                     values,
                     address,
+                    datum_attachment,
                 } => {
                     let owner = address.to_bech32().expect("Already Validated");
                     let output = UnbuiltOutput::Validator {
                         script_address: owner,
                         values,
                         datum,
+                        datum_attachment,
                     };
                     specific_outputs.push(output);
                 }
@@ -275,51 +528,124 @@ impl<Datum: Clone, Redeemer> TxActions<Datum, Redeemer> {
                     redeemer,
                     script,
                 } => {
-                    script_inputs.push((output.clone(), redeemer, script));
+                    redemptions.push((output, PendingRedeemer::Ready(redeemer), script));
+                }
+                Action::RedeemScriptOutputLazily {
+                    output,
+                    redeemer,
+                    script,
+                } => {
+                    redemptions.push((output, PendingRedeemer::Lazy(redeemer), script));
                 }
                 Action::SpecificInput { input } => specific_wallet_inputs.push(input),
+                Action::ReferenceScript { output } => {
+                    if output.reference_script().is_none() {
+                        return Err(Error::IncompatibleTxActions(format!(
+                            "output {:?} doesn't hold a reference script",
+                            output.id()
+                        )))
+                    }
+                    if !reference_inputs.iter().any(|o| o.id() == output.id()) {
+                        reference_inputs.push(output)
+                    }
+                }
+                Action::ReferenceInput { output } => {
+                    if !reference_inputs.iter().any(|o| o.id() == output.id()) {
+                        reference_inputs.push(output)
+                    }
+                }
             }
         }
+        if let (TransactionVersion::V1, false) =
+            (&script_version, reference_inputs.is_empty())
+        {
+            return Err(Error::IncompatibleTxActions(
+                "reference inputs need a V2 transaction".to_string(),
+            ))
+        }
 
-        let out_vecs = nested_map_to_vecs(min_output_values);
-        let mut outputs = create_outputs_for(out_vecs)?;
+        let mut outputs: Vec<_> = transfers
+            .into_outputs()
+            .into_iter()
+            .map(|(owner, values)| UnbuiltOutput::new_wallet(owner, values))
+            .collect();
         outputs.extend(specific_outputs);
 
+        let shape = TxShape { outputs: &outputs };
+        let script_inputs: Vec<RedemptionDetails<Datum, Redeemer>> = redemptions
+            .into_iter()
+            .map(|(output, redeemer, script)| {
+                let redeemer = match redeemer {
+                    PendingRedeemer::Ready(redeemer) => redeemer,
+                    PendingRedeemer::Lazy(compute) => compute(&shape)?,
+                };
+                Ok((output, redeemer, script))
+            })
+            .collect::<Result<_>>()?;
+
         let tx = UnbuiltTransaction {
             script_version,
             script_inputs,
             unbuilt_outputs: outputs,
             minting,
             specific_wallet_inputs,
-            valid_range: self.valid_range,
+            reference_inputs,
+            valid_range,
+            metadata,
         };
         Ok(tx)
     }
 }
 
-fn create_outputs_for<Datum>(
-    values: Vec<(String, Vec<(PolicyId, u64)>)>,
-) -> Result<Vec<UnbuiltOutput<Datum>>> {
-    let outputs: Result<Vec<_>> = values
-        .into_iter()
-        .map(|(owner, val_vec)| {
-            let values =
-                val_vec
-                    .iter()
-                    .fold(Values::default(), |mut acc, (policy, amt)| {
-                        acc.add_one_value(policy, *amt);
-                        acc
-                    });
-            let addr = Address::from_bech32(&owner)
-                .map_err(|e| Error::Address(format!("Bad Bech32: {e:?}")))?;
-            Ok(UnbuiltOutput::new_wallet(addr, values))
-        })
-        .collect();
-    outputs
+enum PendingRedeemer<Datum, Redeemer> {
+    Ready(Redeemer),
+    Lazy(LazyRedeemer<Datum, Redeemer>),
+}
+
+/// Layout of the transaction built from a [`TxActions`], as seen by lazy redeemers
+///
+/// Outputs are in the order the ledger clients add them, before any change output. Inputs
+/// aren't included, since wallet inputs are only picked while balancing.
+pub struct TxShape<'a, Datum> {
+    outputs: &'a [UnbuiltOutput<Datum>],
+}
+
+impl<'a, Datum> TxShape<'a, Datum> {
+    /// Outputs of the transaction, in order
+    pub fn outputs(&self) -> &[UnbuiltOutput<Datum>] {
+        self.outputs
+    }
+
+    /// Index of the first output owned by `address`, if there is one
+    pub fn output_index(&self, address: &Address) -> Option<u64> {
+        self.outputs
+            .iter()
+            .position(|output| &output.owner() == address)
+            .map(|index| index as u64)
+    }
+}
+
+// The payment part of a script address is the script's hash, whatever the network
+fn validator_hash<Datum, Redeemer>(
+    script: &dyn Validator<Datum, Redeemer>,
+) -> Option<Vec<u8>> {
+    match script.address(Network::Testnet).ok()? {
+        Address::Shelley(address) => Some(address.payment().as_hash().to_vec()),
+        _ => None,
+    }
+}
+
+// Mint amounts are signed on chain, so burns can be negative
+fn mint_amount(amount: u64) -> Result<i64> {
+    i64::try_from(amount).map_err(|_| {
+        Error::IncompatibleTxActions(format!(
+            "can't mint or burn {amount} tokens at once"
+        ))
+    })
 }
 
 /// Version of the transaction
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 #[non_exhaustive]
 pub enum TransactionVersion {
     /// V1 transaction
@@ -340,17 +666,21 @@ pub struct UnbuiltTransaction<Datum, Redeemer> {
     /// Outputs to be created
     pub unbuilt_outputs: Vec<UnbuiltOutput<Datum>>,
     #[allow(clippy::type_complexity)]
-    /// Minting policies to be used
+    /// Minting policies to be used, with negative amounts for burning
     pub minting: Vec<(
-        u64,
-        Option<String>,
+        i64,
+        Option<Vec<u8>>,
         Redeemer,
         Box<dyn MintingPolicy<Redeemer>>,
     )>,
     /// Specific wallet inputs to be used
     pub specific_wallet_inputs: Vec<Output<Datum>>,
+    /// Outputs holding reference scripts, read without being spent
+    pub reference_inputs: Vec<Output<Datum>>,
     /// Valid range in seconds since the Unix epoch
     pub valid_range: Range,
+    /// Metadata to attach
+    pub metadata: TxMetadata,
 }
 
 impl<Datum, Redeemer> UnbuiltTransaction<Datum, Redeemer> {
@@ -363,10 +693,34 @@ impl<Datum, Redeemer> UnbuiltTransaction<Datum, Redeemer> {
     pub fn script_inputs(&self) -> &Vec<RedemptionDetails<Datum, Redeemer>> {
         &self.script_inputs
     }
+
+    /// Reference input holding the script with `script_hash`, if there is one
+    pub fn reference_script_input(&self, script_hash: &[u8]) -> Option<&Output<Datum>> {
+        self.reference_inputs.iter().find(|output| {
+            output
+                .reference_script()
+                .map_or(false, |script| script.hash() == script_hash)
+        })
+    }
+
+    /// Check that every output and input address belongs to `network`
+    pub fn check_network(&self, network: Network) -> AddressResult<()> {
+        let output_owners = self.unbuilt_outputs.iter().map(UnbuiltOutput::owner);
+        let input_owners = self
+            .script_inputs
+            .iter()
+            .map(|(input, _, _)| input.owner())
+            .chain(self.specific_wallet_inputs.iter().map(Output::owner))
+            .chain(self.reference_inputs.iter().map(Output::owner));
+        for owner in output_owners.chain(input_owners) {
+            check_network(&owner, network)?;
+        }
+        Ok(())
+    }
 }
 
 /// The resulting transaction from a [`LedgerClient`] submission
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TxId(String);
 
 impl TxId {
@@ -380,3 +734,58 @@ impl TxId {
         self.0.clone()
     }
 }
+
+/// A transaction built by a [`LedgerClient`](crate::ledger_client::LedgerClient) but not
+/// signed or submitted, so it can be shown to the user for confirmation
+#[derive(Clone, Debug)]
+pub struct TxPreview<Datum> {
+    inputs: Vec<OutputId>,
+    outputs: Vec<Output<Datum>>,
+    fee: u64,
+    mint: Vec<(PolicyId, i64)>,
+    ex_units: Vec<ExecutionCost>,
+}
+
+impl<Datum> TxPreview<Datum> {
+    /// Constructor for a TxPreview
+    pub fn new(
+        inputs: Vec<OutputId>,
+        outputs: Vec<Output<Datum>>,
+        fee: u64,
+        mint: Vec<(PolicyId, i64)>,
+        ex_units: Vec<ExecutionCost>,
+    ) -> Self {
+        TxPreview {
+            inputs,
+            outputs,
+            fee,
+            mint,
+            ex_units,
+        }
+    }
+
+    /// Outputs the transaction would spend, including the ones selected from the wallet
+    pub fn inputs(&self) -> &[OutputId] {
+        &self.inputs
+    }
+
+    /// Outputs the transaction would create, including change
+    pub fn outputs(&self) -> &[Output<Datum>] {
+        &self.outputs
+    }
+
+    /// Fee in Lovelace
+    pub fn fee(&self) -> u64 {
+        self.fee
+    }
+
+    /// Minted (positive) and burned (negative) tokens
+    pub fn mint(&self) -> &[(PolicyId, i64)] {
+        &self.mint
+    }
+
+    /// Execution budget of each script run by the transaction
+    pub fn ex_units(&self) -> &[ExecutionCost] {
+        &self.ex_units
+    }
+}
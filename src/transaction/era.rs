@@ -0,0 +1,201 @@
+use crate::{
+    output::DatumAttachment,
+    transaction::{
+        TransactionVersion,
+        UnbuiltTransaction,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::fmt;
+use thiserror::Error;
+
+/// Ledger era that transactions are built for
+///
+/// Transactions are serialized in the Babbage format, which Conway still accepts as long as
+/// it doesn't use anything Conway removed. The era decides which features may be used.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize,
+)]
+pub enum Era {
+    /// Alonzo, which introduced Plutus
+    Alonzo,
+    /// Babbage, from the Vasil hard fork
+    Babbage,
+    /// Conway, from the Chang hard fork
+    #[default]
+    Conway,
+}
+
+impl fmt::Display for Era {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Era::Alonzo => write!(f, "Alonzo"),
+            Era::Babbage => write!(f, "Babbage"),
+            Era::Conway => write!(f, "Conway"),
+        }
+    }
+}
+
+/// Transaction feature that's only available from some era
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EraFeature {
+    /// Plutus V2 scripts
+    PlutusV2Scripts,
+    /// Datums inlined in outputs
+    InlineDatums,
+    /// Reading outputs without spending them
+    ReferenceInputs,
+    /// Scripts stored in outputs
+    ReferenceScripts,
+    /// Returning the unused part of the collateral
+    CollateralReturn,
+    /// DRep registration and vote delegation certificates
+    GovernanceCertificates,
+    /// Votes on governance actions
+    Votes,
+}
+
+impl EraFeature {
+    /// First era with the feature
+    pub fn introduced_in(&self) -> Era {
+        match self {
+            EraFeature::PlutusV2Scripts
+            | EraFeature::InlineDatums
+            | EraFeature::ReferenceInputs
+            | EraFeature::ReferenceScripts
+            | EraFeature::CollateralReturn => Era::Babbage,
+            EraFeature::GovernanceCertificates | EraFeature::Votes => Era::Conway,
+        }
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum EraError {
+    #[error(
+        "{feature:?} needs {}, but transactions are built for {era}",
+        feature.introduced_in()
+    )]
+    Unsupported { feature: EraFeature, era: Era },
+}
+
+/// Specialized result type for era checks
+pub type EraResult<T, E = EraError> = Result<T, E>;
+
+impl Era {
+    /// Whether transactions of this era can use `feature`
+    pub fn supports(&self, feature: EraFeature) -> bool {
+        *self >= feature.introduced_in()
+    }
+
+    /// Fail if transactions of this era can't use `feature`
+    pub fn check(&self, feature: EraFeature) -> EraResult<()> {
+        if !self.supports(feature) {
+            return Err(EraError::Unsupported {
+                feature,
+                era: *self,
+            })
+        }
+        Ok(())
+    }
+
+    /// Fail if `tx` uses anything that isn't available in this era
+    ///
+    /// Covers what the transaction asks for: Plutus V2 scripts, inline datums, reference
+    /// inputs and reference scripts. Collateral return is added by the ledger client while
+    /// building, and only in eras that support it.
+    pub fn check_tx<Datum, Redeemer>(
+        &self,
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
+    ) -> EraResult<()> {
+        if let TransactionVersion::V2 = tx.script_version {
+            self.check(EraFeature::PlutusV2Scripts)?;
+        }
+        if tx
+            .unbuilt_outputs
+            .iter()
+            .any(|output| output.datum_attachment() == Some(DatumAttachment::Inline))
+        {
+            self.check(EraFeature::InlineDatums)?;
+        }
+        if !tx.reference_inputs.is_empty() {
+            self.check(EraFeature::ReferenceInputs)?;
+        }
+        if tx
+            .reference_inputs
+            .iter()
+            .any(|input| input.reference_script().is_some())
+        {
+            self.check(EraFeature::ReferenceScripts)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::{
+        transaction::TxActions,
+        values::Values,
+    };
+
+    #[test]
+    fn check_tx__rejects_features_from_later_eras() {
+        // given
+        let v2_tx = TxActions::<(), ()>::v2().to_unbuilt_tx().unwrap();
+        let v1_tx = TxActions::<(), ()>::v1().to_unbuilt_tx().unwrap();
+
+        // when
+        let alonzo_v2 = Era::Alonzo.check_tx(&v2_tx);
+        let alonzo_v1 = Era::Alonzo.check_tx(&v1_tx);
+        let babbage_votes = Era::Babbage.check(EraFeature::Votes);
+
+        // then
+        let expected = EraError::Unsupported {
+            feature: EraFeature::PlutusV2Scripts,
+            era: Era::Alonzo,
+        };
+        assert_eq!(alonzo_v2, Err(expected));
+        assert_eq!(alonzo_v1, Ok(()));
+        assert!(babbage_votes.is_err());
+        assert!(Era::Conway.supports(EraFeature::ReferenceScripts));
+    }
+
+    #[test]
+    fn check_tx__rejects_inline_datums_before_babbage() {
+        // given
+        let script_address = pallas_addresses::Address::from_bech32(
+            "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu",
+        )
+        .unwrap();
+        let tx = TxActions::<(), ()>::v1()
+            .with_script_init_inline_datum((), Values::default(), script_address.clone())
+            .to_unbuilt_tx()
+            .unwrap();
+        let hashed = TxActions::<(), ()>::v1()
+            .with_script_init((), Values::default(), script_address)
+            .to_unbuilt_tx()
+            .unwrap();
+
+        // when
+        let inline_res = Era::Alonzo.check_tx(&tx);
+        let hashed_res = Era::Alonzo.check_tx(&hashed);
+
+        // then
+        assert_eq!(
+            inline_res,
+            Err(EraError::Unsupported {
+                feature: EraFeature::InlineDatums,
+                era: Era::Alonzo,
+            })
+        );
+        assert_eq!(hashed_res, Ok(()));
+        assert_eq!(Era::Babbage.check_tx(&tx), Ok(()));
+    }
+}
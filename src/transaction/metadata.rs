@@ -0,0 +1,191 @@
+use minicbor::{
+    encode::Error as EncodeError,
+    Encoder,
+};
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+};
+use thiserror::Error;
+
+/// Longest bytes or text the ledger accepts in a single metadatum
+pub const MAX_METADATUM_LEN: usize = 64;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum MetadataError {
+    #[error("Metadatum is {0} bytes long, but at most {MAX_METADATUM_LEN} are allowed")]
+    TooLong(usize),
+    #[error("Metadatum integer out of range: {0}")]
+    IntOutOfRange(i128),
+    #[error("Failed to encode metadata: {0}")]
+    Encoding(String),
+}
+
+/// Specialized result type for transaction metadata
+pub type MetadataResult<T, E = MetadataError> = Result<T, E>;
+
+/// Single value of transaction metadata
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Metadatum {
+    /// Integer between `-2^63` and `2^64 - 1`
+    Int(i128),
+    /// Up to [`MAX_METADATUM_LEN`] bytes
+    Bytes(Vec<u8>),
+    /// Up to [`MAX_METADATUM_LEN`] bytes of UTF-8
+    Text(String),
+    /// List of metadata
+    List(Vec<Metadatum>),
+    /// Map of metadata, in the given order
+    Map(Vec<(Metadatum, Metadatum)>),
+}
+
+impl From<i128> for Metadatum {
+    fn from(value: i128) -> Self {
+        Metadatum::Int(value)
+    }
+}
+
+impl From<u64> for Metadatum {
+    fn from(value: u64) -> Self {
+        Metadatum::Int(value.into())
+    }
+}
+
+impl From<Vec<u8>> for Metadatum {
+    fn from(value: Vec<u8>) -> Self {
+        Metadatum::Bytes(value)
+    }
+}
+
+impl From<&str> for Metadatum {
+    fn from(value: &str) -> Self {
+        Metadatum::Text(value.to_string())
+    }
+}
+
+impl Metadatum {
+    fn encode(&self, e: &mut Encoder<Vec<u8>>) -> MetadataResult<()> {
+        match self {
+            Metadatum::Int(value) => {
+                if let Ok(value) = u64::try_from(*value) {
+                    e.u64(value).map_err(encoding)?;
+                } else if let Ok(value) = i64::try_from(*value) {
+                    e.i64(value).map_err(encoding)?;
+                } else {
+                    return Err(MetadataError::IntOutOfRange(*value))
+                }
+            }
+            Metadatum::Bytes(bytes) => {
+                check_len(bytes.len())?;
+                e.bytes(bytes).map_err(encoding)?;
+            }
+            Metadatum::Text(text) => {
+                check_len(text.len())?;
+                e.str(text).map_err(encoding)?;
+            }
+            Metadatum::List(items) => {
+                e.array(items.len() as u64).map_err(encoding)?;
+                for item in items {
+                    item.encode(e)?;
+                }
+            }
+            Metadatum::Map(entries) => {
+                e.map(entries.len() as u64).map_err(encoding)?;
+                for (key, value) in entries {
+                    key.encode(e)?;
+                    value.encode(e)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Metadata attached to a transaction, by label
+///
+/// Labels are registered in CIP-10, e.g. `674` for messages and `721` for CIP-25 NFTs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TxMetadata {
+    entries: BTreeMap<u64, Metadatum>,
+}
+
+impl TxMetadata {
+    /// Add `metadatum` under `label`, replacing what was there
+    pub fn with_entry(mut self, label: u64, metadatum: impl Into<Metadatum>) -> Self {
+        self.entries.insert(label, metadatum.into());
+        self
+    }
+
+    /// Getter for the metadatum under `label`
+    pub fn get(&self, label: u64) -> Option<&Metadatum> {
+        self.entries.get(&label)
+    }
+
+    /// Labels in use
+    pub fn labels(&self) -> impl Iterator<Item = u64> + '_ {
+        self.entries.keys().copied()
+    }
+
+    /// Number of labels in use
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there is no metadata
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Add all entries of `other`, replacing those under the same labels
+    pub fn extend(&mut self, other: TxMetadata) {
+        self.entries.extend(other.entries);
+    }
+
+    /// Encode as the metadata of a transaction's auxiliary data
+    pub fn to_cbor(&self) -> MetadataResult<Vec<u8>> {
+        let mut encoder = Encoder::new(Vec::new());
+        encoder.map(self.entries.len() as u64).map_err(encoding)?;
+        for (label, metadatum) in &self.entries {
+            encoder.u64(*label).map_err(encoding)?;
+            metadatum.encode(&mut encoder)?;
+        }
+        Ok(encoder.into_writer())
+    }
+}
+
+fn check_len(len: usize) -> MetadataResult<()> {
+    if len > MAX_METADATUM_LEN {
+        return Err(MetadataError::TooLong(len))
+    }
+    Ok(())
+}
+
+fn encoding(error: EncodeError<Infallible>) -> MetadataError {
+    MetadataError::Encoding(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn to_cbor__encodes_labels_in_order_and_rejects_long_values() {
+        // given
+        let metadata = TxMetadata::default()
+            .with_entry(674, Metadatum::Map(vec![("msg".into(), "hi".into())]))
+            .with_entry(1, Metadatum::Int(-1));
+        let too_long = TxMetadata::default().with_entry(1, vec![0u8; 65]);
+
+        // when
+        let cbor = metadata.to_cbor().unwrap();
+        let res = too_long.to_cbor();
+
+        // then
+        let expected = hex::decode("a201201902a2a1636d7367626869").unwrap();
+        assert_eq!(cbor, expected);
+        assert_eq!(res, Err(MetadataError::TooLong(65)));
+    }
+}
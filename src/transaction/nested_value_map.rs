@@ -3,33 +3,31 @@ use crate::{
     PolicyId,
 };
 use pallas_addresses::Address;
-use std::{
-    cell::RefCell,
-    collections::HashMap,
-};
+use std::collections::HashMap;
 
-pub(crate) fn nested_map_to_vecs(
-    nested_map: HashMap<String, RefCell<Values>>,
-) -> Vec<(String, Vec<(PolicyId, u64)>)> {
-    nested_map
-        .into_iter()
-        .map(|(addr, values)| (addr, values.borrow().vec()))
-        .collect()
+/// Amounts transferred to each recipient, in the order recipients were first seen
+#[derive(Default)]
+pub(crate) struct TransferOutputs {
+    // Keyed by address bytes, to avoid a bech32 round trip per transfer
+    index: HashMap<Vec<u8>, usize>,
+    outputs: Vec<(Address, Values)>,
 }
 
-pub(crate) fn add_amount_to_nested_map(
-    output_map: &mut HashMap<String, RefCell<Values>>,
-    amount: u64,
-    owner: &Address,
-    policy_id: &PolicyId,
-) {
-    let owner_str = owner.to_bech32().expect("Already validated");
-    if let Some(values) = output_map.get(&owner_str) {
-        let mut inner = values.borrow_mut();
-        inner.add_one_value(policy_id, amount);
-    } else {
-        let mut new_values = Values::default();
-        new_values.add_one_value(policy_id, amount);
-        output_map.insert(owner_str.clone(), RefCell::new(new_values));
+impl TransferOutputs {
+    pub fn add_amount(&mut self, amount: u64, owner: Address, policy_id: &PolicyId) {
+        let key = owner.to_vec();
+        let position = match self.index.get(&key) {
+            Some(position) => *position,
+            None => {
+                self.index.insert(key, self.outputs.len());
+                self.outputs.push((owner, Values::default()));
+                self.outputs.len() - 1
+            }
+        };
+        self.outputs[position].1.add_one_value(policy_id, amount);
+    }
+
+    pub fn into_outputs(self) -> Vec<(Address, Values)> {
+        self.outputs
     }
 }
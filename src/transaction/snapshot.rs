@@ -0,0 +1,352 @@
+//! Stable CBOR snapshots of an [`UnbuiltTransaction`], for golden-file tests
+//!
+//! The snapshot is a CBOR array of:
+//!
+//! ```text
+//! [ version            ; 1 or 2
+//! , inputs             ; [[tx_hash, index, redeemer / null]], sorted
+//! , reference_inputs   ; [[tx_hash, index]], sorted
+//! , outputs            ; [[address, value, datum / null, min_lovelace]], in order
+//! , mint               ; [[policy, asset_name, amount, redeemer]], sorted
+//! , valid_range        ; [lower / null, upper / null], in POSIX seconds
+//! , metadata           ; as in the auxiliary data
+//! ]
+//! ```
+//!
+//! Values are encoded as in the ledger, with policies and asset names sorted by their bytes.
+//! Datums are `[0, hash]` or `[1, data]` for inline ones. It isn't a submittable
+//! transaction: wallet inputs, change and fees are only picked by a ledger client.
+
+use crate::{
+    output::{
+        DatumAttachment,
+        Output,
+        UnbuiltOutput,
+    },
+    protocol_params::ProtocolParameters,
+    scripts::{
+        plutus_validator::plutus_data::PlutusData,
+        ScriptError,
+    },
+    transaction::{
+        metadata::MetadataError,
+        TransactionVersion,
+    },
+    values::Values,
+    PolicyId,
+    UnbuiltTransaction,
+};
+use minicbor::{
+    encode::Error as EncodeError,
+    Encoder,
+};
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+};
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("Failed to get minting policy id: {0}")]
+    Script(#[from] ScriptError),
+    #[error("Invalid policy id: {0:?}")]
+    InvalidPolicyId(String),
+    #[error(transparent)]
+    Metadata(#[from] MetadataError),
+    #[error("Failed to encode snapshot: {0}")]
+    Encoding(String),
+    #[error("Input {tx_hash}#{index} is spent more than once")]
+    DuplicateInput { tx_hash: String, index: u64 },
+    #[error("Asset {asset_name:?} of policy {policy} is minted more than once")]
+    DuplicateMint { policy: String, asset_name: String },
+}
+
+/// Specialized result type for transaction snapshots
+pub type SnapshotResult<T, E = SnapshotError> = Result<T, E>;
+
+type SnapshotEncoder = Encoder<Vec<u8>>;
+
+impl<Datum, Redeemer> UnbuiltTransaction<Datum, Redeemer>
+where
+    Datum: Clone + Into<PlutusData>,
+    Redeemer: Clone + Into<PlutusData>,
+{
+    /// Encode everything the transaction declares as CBOR that is the same for the same
+    /// transaction, run after run and across versions. See the [module docs](self) for the
+    /// layout.
+    ///
+    /// `params` give the `min_lovelace` of each output.
+    pub fn to_deterministic_cbor(
+        &self,
+        params: &ProtocolParameters,
+    ) -> SnapshotResult<Vec<u8>> {
+        let mut e = Encoder::new(Vec::new());
+        e.array(7).map_err(encoding)?;
+        let version = match self.script_version {
+            TransactionVersion::V1 => 1,
+            TransactionVersion::V2 => 2,
+        };
+        e.u8(version).map_err(encoding)?;
+
+        // The ledger orders inputs by their reference, whatever order they were added in
+        let mut inputs: BTreeMap<(Vec<u8>, u64), Option<Vec<u8>>> = BTreeMap::new();
+        let script_inputs = self
+            .script_inputs
+            .iter()
+            .map(|(input, redeemer, _)| (input, Some(plutus_bytes(redeemer.clone()))));
+        let wallet_inputs = self
+            .specific_wallet_inputs
+            .iter()
+            .map(|input| (input, None));
+        for (input, redeemer) in script_inputs.chain(wallet_inputs) {
+            let (tx_hash, index) = output_ref(input);
+            if inputs.contains_key(&(tx_hash.clone(), index)) {
+                return Err(SnapshotError::DuplicateInput {
+                    tx_hash: hex::encode(tx_hash),
+                    index,
+                })
+            }
+            inputs.insert((tx_hash, index), redeemer);
+        }
+        e.array(inputs.len() as u64).map_err(encoding)?;
+        for ((tx_hash, index), redeemer) in &inputs {
+            e.array(3).map_err(encoding)?;
+            e.bytes(tx_hash)
+                .map_err(encoding)?
+                .u64(*index)
+                .map_err(encoding)?;
+            match redeemer {
+                Some(redeemer) => raw(&mut e, redeemer),
+                None => {
+                    e.null().map_err(encoding)?;
+                }
+            }
+        }
+
+        let mut reference_inputs: Vec<_> =
+            self.reference_inputs.iter().map(output_ref).collect();
+        reference_inputs.sort();
+        e.array(reference_inputs.len() as u64).map_err(encoding)?;
+        for (tx_hash, index) in &reference_inputs {
+            e.array(2).map_err(encoding)?;
+            e.bytes(tx_hash)
+                .map_err(encoding)?
+                .u64(*index)
+                .map_err(encoding)?;
+        }
+
+        e.array(self.unbuilt_outputs.len() as u64)
+            .map_err(encoding)?;
+        for output in &self.unbuilt_outputs {
+            encode_output(&mut e, output, params)?;
+        }
+
+        let mut mints = BTreeMap::new();
+        for (amount, asset_name, redeemer, policy) in &self.minting {
+            let policy = decode_hex(&policy.id()?)?;
+            let asset_name = asset_name.clone().unwrap_or_default();
+            if mints.contains_key(&(policy.clone(), asset_name.clone())) {
+                return Err(SnapshotError::DuplicateMint {
+                    policy: hex::encode(policy),
+                    asset_name: hex::encode(asset_name),
+                })
+            }
+            mints.insert(
+                (policy, asset_name),
+                (*amount, plutus_bytes(redeemer.clone())),
+            );
+        }
+        e.array(mints.len() as u64).map_err(encoding)?;
+        for ((policy, asset_name), (amount, redeemer)) in &mints {
+            e.array(4).map_err(encoding)?;
+            e.bytes(policy)
+                .map_err(encoding)?
+                .bytes(asset_name)
+                .map_err(encoding)?
+                .i64(*amount)
+                .map_err(encoding)?;
+            raw(&mut e, redeemer);
+        }
+
+        let (lower, upper) = self.valid_range;
+        e.array(2).map_err(encoding)?;
+        for bound in [lower, upper] {
+            match bound {
+                Some(secs) => e.i64(secs).map_err(encoding)?,
+                None => e.null().map_err(encoding)?,
+            };
+        }
+
+        raw(&mut e, &self.metadata.to_cbor()?);
+        Ok(e.into_writer())
+    }
+}
+
+fn encode_output<Datum: Clone + Into<PlutusData>>(
+    e: &mut SnapshotEncoder,
+    output: &UnbuiltOutput<Datum>,
+    params: &ProtocolParameters,
+) -> SnapshotResult<()> {
+    e.array(4).map_err(encoding)?;
+    e.bytes(&output.owner().to_vec()).map_err(encoding)?;
+    encode_value(e, output.values())?;
+    match (output.datum(), output.datum_attachment()) {
+        (Some(datum), Some(DatumAttachment::Hash)) => {
+            let data: PlutusData = datum.clone().into();
+            e.array(2).map_err(encoding)?;
+            e.u8(0)
+                .map_err(encoding)?
+                .bytes(&data.hash())
+                .map_err(encoding)?;
+        }
+        (Some(datum), _) => {
+            e.array(2).map_err(encoding)?;
+            e.u8(1).map_err(encoding)?;
+            raw(e, &plutus_bytes(datum.clone()));
+        }
+        (None, _) => {
+            e.null().map_err(encoding)?;
+        }
+    }
+    e.u64(output.min_ada_required(params)).map_err(encoding)?;
+    Ok(())
+}
+
+// `Values` are hashed, so policies and asset names are sorted before encoding
+fn encode_value(e: &mut SnapshotEncoder, values: &Values) -> SnapshotResult<()> {
+    let lovelace = values.get(&PolicyId::Lovelace).unwrap_or_default();
+    let mut assets: BTreeMap<Vec<u8>, BTreeMap<Vec<u8>, u64>> = BTreeMap::new();
+    for (policy, amount) in values.as_iter() {
+        if let PolicyId::NativeToken(id, asset_name) = policy {
+            assets
+                .entry(decode_hex(id)?)
+                .or_default()
+                .insert(asset_name.clone().unwrap_or_default(), *amount);
+        }
+    }
+    if assets.is_empty() {
+        e.u64(lovelace).map_err(encoding)?;
+        return Ok(())
+    }
+    e.array(2).map_err(encoding)?;
+    e.u64(lovelace).map_err(encoding)?;
+    e.map(assets.len() as u64).map_err(encoding)?;
+    for (policy, names) in &assets {
+        e.bytes(policy).map_err(encoding)?;
+        e.map(names.len() as u64).map_err(encoding)?;
+        for (asset_name, amount) in names {
+            e.bytes(asset_name)
+                .map_err(encoding)?
+                .u64(*amount)
+                .map_err(encoding)?;
+        }
+    }
+    Ok(())
+}
+
+fn output_ref<Datum>(output: &Output<Datum>) -> (Vec<u8>, u64) {
+    (output.id().tx_hash().to_vec(), output.id().index())
+}
+
+fn plutus_bytes(data: impl Into<PlutusData>) -> Vec<u8> {
+    data.into().bytes()
+}
+
+// Writes already encoded CBOR, e.g. Plutus data, as it is
+fn raw(e: &mut SnapshotEncoder, cbor: &[u8]) {
+    e.writer_mut().extend_from_slice(cbor);
+}
+
+fn decode_hex(id: &str) -> SnapshotResult<Vec<u8>> {
+    hex::decode(id).map_err(|_| SnapshotError::InvalidPolicyId(id.to_string()))
+}
+
+fn encoding(error: EncodeError<Infallible>) -> SnapshotError {
+    SnapshotError::Encoding(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::{
+        transaction::TxActions,
+        Address,
+    };
+
+    const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+    const BOB: &str = "addr_test1qzvrhz9v6lwcr26a52y8mmk2nzq37lky68359keq3dgth4lkzpnnjv8vf98m20lhqdzl60mcftq7r2lc4xtcsv0w6xjstag0ua";
+
+    fn wallet_input(tx_hash: u8, owner: &Address) -> Output<()> {
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, 5_000_000);
+        Output::new_wallet(vec![tx_hash; 32], 0, owner.clone(), values)
+    }
+
+    fn transfer(inputs: Vec<Output<()>>) -> UnbuiltTransaction<(), ()> {
+        let bob = Address::from_bech32(BOB).unwrap();
+        let tokens = ["aaaa", "bbbb", "cccc"]
+            .map(|id| PolicyId::native_token(id, &Some(b"Coin".to_vec())));
+        let mut actions = TxActions::v2()
+            .with_transfer(2_000_000, bob.clone(), PolicyId::Lovelace)
+            .with_valid_range_secs(Some(100), None);
+        for token in tokens {
+            actions = actions.with_transfer(10, bob.clone(), token);
+        }
+        for input in inputs {
+            actions = actions.with_specific_input(input);
+        }
+        actions.to_unbuilt_tx().unwrap()
+    }
+
+    #[test]
+    fn to_deterministic_cbor__is_stable_whatever_order_inputs_are_added_in() {
+        // given
+        let alice = Address::from_bech32(ALICE).unwrap();
+        let params = ProtocolParameters::default();
+        let first = transfer(vec![wallet_input(1, &alice), wallet_input(2, &alice)]);
+        let second = transfer(vec![wallet_input(2, &alice), wallet_input(1, &alice)]);
+
+        // when
+        let first = first.to_deterministic_cbor(&params).unwrap();
+        let second = second.to_deterministic_cbor(&params).unwrap();
+
+        // then
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn to_deterministic_cbor__rejects_inputs_spent_twice() {
+        // given
+        let alice = Address::from_bech32(ALICE).unwrap();
+        let params = ProtocolParameters::default();
+        let tx = transfer(vec![wallet_input(1, &alice), wallet_input(1, &alice)]);
+
+        // when
+        let res = tx.to_deterministic_cbor(&params);
+
+        // then
+        assert!(matches!(
+            res,
+            Err(SnapshotError::DuplicateInput { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn to_deterministic_cbor__changes_with_the_protocol_parameters() {
+        // given
+        let tx = transfer(vec![]);
+        let params = ProtocolParameters::default();
+        let cheaper = params.with_coins_per_utxo_byte(params.coins_per_utxo_byte() / 2);
+
+        // when
+        let snapshot = tx.to_deterministic_cbor(&params).unwrap();
+        let cheaper_snapshot = tx.to_deterministic_cbor(&cheaper).unwrap();
+
+        // then
+        assert_ne!(snapshot, cheaper_snapshot);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     error::*,
     ledger_client::{
+        retry::RetryPolicy,
         test_ledger_client::{
             local_persisted_storage::LocalPersistedStorage,
             TestLedgerClient,
@@ -11,10 +12,26 @@ use crate::{
     },
     output::Output,
     scripts::plutus_validator::plutus_data::PlutusData,
-    transaction::TxId,
+    transaction::{
+        TxId,
+        TxPreview,
+    },
     trireme_ledger_client::{
         cml_client::blockfrost_ledger::BlockfrostApiKey,
+        env_config::{
+            apply_overrides,
+            config_from_vars,
+            env_var,
+            ALLOW_MAINNET_VAR,
+            BLOCKFROST_API_KEY_VAR,
+            CONFIG_DIR_VAR,
+            ENV_VAR,
+            KEYSTORE_PASSPHRASE_VAR,
+            REMOTE_SIGNER_TOKEN_VAR,
+        },
+        keystore::KeystoreKeys,
         raw_secret_phrase::RawSecretPhraseKeys,
+        secret_phrase::DerivationPath,
     },
     UnbuiltTransaction,
 };
@@ -37,17 +54,38 @@ use blockfrost_http_client::{
 };
 use cardano_multiplatform_lib::{
     address::BaseAddress,
-    crypto::PrivateKey,
+    crypto::{
+        PrivateKey,
+        PublicKey,
+        TransactionHash,
+        Vkeywitness,
+    },
+    Transaction as CMLTransaction,
 };
+#[cfg(feature = "ledger")]
+use cml_client::hardware_wallet::ledger::LedgerDevice;
 use cml_client::{
     blockfrost_ledger::BlockFrostLedger,
+    error::CMLLCError,
+    hardware_wallet::{
+        trezor::TrezorDevice,
+        HardwareWalletKeys,
+    },
+    message_signing::DataSignature,
     plutus_data_interop::PlutusDataInterop,
+    remote_signer::RemoteSignerKeys,
+    tx_inspect::InspectedTx,
+    watch_only::WatchOnlyKeys,
     CMLLedgerCLient,
 };
 use dirs::home_dir;
+use futures::stream::BoxStream;
 use ogmios_client::OgmiosClient;
+#[cfg(feature = "keychain")]
+use os_keychain::KeychainKeys;
 use pallas_addresses::Address;
 use scrolls_client::ScrollsClient;
+use secrecy::Secret;
 use serde::{
     de::DeserializeOwned,
     ser,
@@ -55,9 +93,11 @@ use serde::{
     Serialize,
 };
 use std::{
+    collections::BTreeMap,
     fmt::Debug,
     hash::Hash,
     marker::PhantomData,
+    net::SocketAddr,
     path::PathBuf,
 };
 use thiserror::Error;
@@ -68,13 +108,23 @@ use tokio::{
 
 /// CML CLient module
 pub mod cml_client;
+/// Environment variable config overrides module
+pub mod env_config;
+/// Encrypted keystore module
+pub mod keystore;
+/// OS keychain secret storage module
+#[cfg(feature = "keychain")]
+pub mod os_keychain;
 /// Raw secret phrase module
 pub mod raw_secret_phrase;
-/// Secret Phrase module
-pub mod secret_phrase;
 /// Terminal password upfront module
 pub mod terminal_password_phrase;
 
+pub use cml_client::{
+    network_settings::Network,
+    secret_phrase,
+};
+
 /// Default Trireme config folder
 pub const TRIREME_CONFIG_FOLDER: &str = ".trireme";
 /// Default Trireme config file name
@@ -82,8 +132,11 @@ pub const TRIREME_CONFIG_FILE: &str = "config.toml";
 /// Default Trireme client config file name
 pub const CLIENT_CONFIG_FILE: &str = "config.toml";
 
-/// Default Trireme config folder
+/// Default Trireme config folder, unless overridden by `TRIREME_CONFIG_DIR`
 pub fn path_to_trireme_config_dir() -> Result<PathBuf> {
+    if let Some(dir) = env_var(CONFIG_DIR_VAR) {
+        return Ok(PathBuf::from(dir))
+    }
     let mut dir = home_dir()
         .ok_or_else(|| Error::Trireme("Could not find home directory :(".to_string()))?;
     dir.push(TRIREME_CONFIG_FOLDER);
@@ -106,16 +159,42 @@ pub fn path_to_client_config_file(sub_dir: &str) -> Result<PathBuf> {
 }
 
 /// Attempts to read the default config and returns the current client config from file
+///
+/// Environment variables take precedence over the files, see [`env_config`].
 pub async fn get_current_client_config_from_file() -> Result<Option<ClientConfig>> {
-    let trireme_config = get_trireme_config_from_file().await?;
-    let current_env = trireme_config
-        .ok_or(Error::Trireme(
-            "Trireme not initialized (config not found)".to_string(),
-        ))?
-        .get_current_env_subdir()
+    let trireme_config = match get_trireme_config_from_file().await? {
+        Some(trireme_config) => trireme_config,
+        None => {
+            return config_from_vars(env_var)?
+                .ok_or(Error::Trireme(
+                    "Trireme not initialized (config not found)".to_string(),
+                ))
+                .map(Some)
+        }
+    };
+    let current_env = env_var(ENV_VAR)
+        .or_else(|| trireme_config.get_current_env_subdir())
         .ok_or(Error::Trireme("No environment initialized".to_string()))?;
-    let client_config_path = path_to_client_config_file(&current_env)?;
-    read_toml_struct_from_file::<ClientConfig>(&client_config_path).await
+    get_client_config_from_file(&current_env).await
+}
+
+/// Reads the client config of the named environment, regardless of which one is active
+pub async fn get_client_config_from_file(env_name: &str) -> Result<Option<ClientConfig>> {
+    let client_config_path = path_to_client_config_file(env_name)?;
+    read_toml_struct_from_file::<ClientConfig>(&client_config_path)
+        .await?
+        .map(|config| apply_overrides(config, env_var))
+        .transpose()
+}
+
+/// Makes the named environment the active one in the default Trireme config file
+pub async fn switch_env_in_file(env_name: &str) -> Result<()> {
+    let mut trireme_config = get_trireme_config_from_file().await?.ok_or(
+        Error::Trireme("Trireme not initialized (config not found)".to_string()),
+    )?;
+    trireme_config.switch_env(env_name)?;
+    let trireme_config_path = path_to_trireme_config_file()?;
+    write_toml_struct_to_file(&trireme_config_path, &trireme_config).await
 }
 
 /// Attempts to read the default config and returns the current client proper based on current config
@@ -140,6 +219,96 @@ pub async fn get_trireme_ledger_client_from_file<
     }
 }
 
+/// Builds the client for the named environment without changing the active environment
+pub async fn get_trireme_ledger_client_for_env_from_file<
+    Datum: PlutusDataInterop
+        + Clone
+        + Eq
+        + PartialEq
+        + Debug
+        + Hash
+        + Send
+        + Sync
+        + Into<PlutusData>
+        + TryFrom<PlutusData>,
+    Redeemer: PlutusDataInterop + Clone + Eq + Debug + Hash + Send + Sync + DeserializeOwned,
+>(
+    env_name: &str,
+) -> Result<TriremeLedgerClient<Datum, Redeemer>> {
+    if let Some(config) = get_client_config_from_file(env_name).await? {
+        config.to_client().await
+    } else {
+        Err(Error::Trireme(format!(
+            "Config for environment {env_name:?} could not be read"
+        )))
+    }
+}
+
+/// Builds the client for the current environment, but on `network` instead of the
+/// configured one
+pub async fn get_trireme_ledger_client_for_network_from_file<
+    Datum: PlutusDataInterop
+        + Clone
+        + Eq
+        + PartialEq
+        + Debug
+        + Hash
+        + Send
+        + Sync
+        + Into<PlutusData>
+        + TryFrom<PlutusData>,
+    Redeemer: PlutusDataInterop + Clone + Eq + Debug + Hash + Send + Sync + DeserializeOwned,
+>(
+    network: Network,
+) -> Result<TriremeLedgerClient<Datum, Redeemer>> {
+    if let Some(config) = get_current_client_config_from_file().await? {
+        config.with_network(network).to_client().await
+    } else {
+        Err(Error::Trireme("Config could not be read".to_string()))
+    }
+}
+
+/// Resolve `name_or_address` against the address book of the current environment
+///
+/// See [`ClientConfig::resolve_address`].
+pub async fn resolve_address_from_file(name_or_address: &str) -> Result<Address> {
+    match get_current_client_config_from_file().await? {
+        Some(config) => config.resolve_address(name_or_address),
+        None => Address::from_bech32(name_or_address)
+            .map_err(|e| Error::Trireme(format!("Invalid address: {e:?}"))),
+    }
+}
+
+/// Save `address` under `name` in the address book of the current environment
+pub async fn add_address_to_file(name: &str, address: &str) -> Result<()> {
+    update_current_client_config_in_file(|config| config.with_address(name, address))
+        .await
+}
+
+/// Remove `name` from the address book of the current environment
+pub async fn remove_address_from_file(name: &str) -> Result<()> {
+    update_current_client_config_in_file(|config| Ok(config.without_address(name))).await
+}
+
+async fn update_current_client_config_in_file(
+    update: impl FnOnce(ClientConfig) -> Result<ClientConfig>,
+) -> Result<()> {
+    let current_env = match env_var(ENV_VAR) {
+        Some(env) => Some(env),
+        None => get_trireme_config_from_file()
+            .await?
+            .and_then(|config| config.current_env()),
+    }
+    .ok_or(Error::Trireme("No environment initialized".to_string()))?;
+    let path = path_to_client_config_file(&current_env)?;
+    let config = read_toml_struct_from_file::<ClientConfig>(&path)
+        .await?
+        .ok_or(Error::Trireme(format!(
+            "Config for environment {current_env:?} could not be read"
+        )))?;
+    write_toml_struct_to_file(&path, &update(config)?).await
+}
+
 /// Attempts to read the default Trireme config
 pub async fn get_trireme_config_from_file() -> Result<Option<TriremeConfig>> {
     let trireme_config_path = path_to_trireme_config_file()?;
@@ -168,6 +337,16 @@ pub enum LedgerSource {
     },
 }
 
+impl LedgerSource {
+    /// Human readable name of the provider
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LedgerSource::BlockFrost { .. } => "BlockFrost",
+            LedgerSource::OgmiosAndScrolls { .. } => "Ogmios and Scrolls",
+        }
+    }
+}
+
 /// Type of key storage
 #[derive(Deserialize, Serialize, Clone)]
 #[serde(tag = "type")]
@@ -176,6 +355,9 @@ pub enum KeySource {
     RawSecretPhrase {
         /// Path to secret phrase file
         phrase_file: PathBuf,
+        /// Derivation path of the signing key
+        #[serde(default)]
+        derivation_path: DerivationPath,
     },
     /// Read from a password-protected secret phrase file
     TerminalPasswordUpfrontSecretPhrase {
@@ -185,26 +367,83 @@ pub enum KeySource {
         password_salt: Vec<u8>,
         /// Nonce used to encrypt the secret phrase
         encrpytion_nonce: [u8; 12],
+        /// Derivation path of the signing key
+        #[serde(default)]
+        derivation_path: DerivationPath,
+    },
+    /// Read from a passphrase-encrypted keystore file
+    EncryptedKeystore {
+        /// Path to keystore file
+        keystore_file: PathBuf,
+        /// Derivation path of the signing key
+        #[serde(default)]
+        derivation_path: DerivationPath,
+    },
+    /// Sign on a Ledger hardware wallet connected over USB
+    LedgerHardwareWallet {
+        /// CIP-1852 account index on the device
+        account: u32,
+        /// Payment key index within the account
+        #[serde(default)]
+        address_index: u32,
+    },
+    /// Sign on a Trezor hardware wallet through Trezor Bridge
+    TrezorHardwareWallet {
+        /// CIP-1852 account index on the device
+        account: u32,
+        /// Payment key index within the account
+        #[serde(default)]
+        address_index: u32,
+    },
+    /// Sign with a CIP-30 browser wallet through a local bridge page
+    Cip30BrowserWallet {
+        /// Local port to serve the bridge page on
+        port: u16,
+    },
+    /// Watch a base address without being able to sign
+    WatchOnlyAddress {
+        /// Bech32 encoded base address
+        address: String,
+    },
+    /// Watch a CIP-1852 account without being able to sign
+    WatchOnlyXpub {
+        /// Bech32 or hex encoded account extended public key
+        account_xpub: String,
+    },
+    /// Read from the OS keychain
+    OsKeychain {
+        /// Keychain service name
+        service: String,
+        /// Keychain account name
+        account: String,
+        /// Derivation path of the signing key
+        #[serde(default)]
+        derivation_path: DerivationPath,
+    },
+    /// Sign with a remote signing service, e.g. in front of a KMS or HSM
+    RemoteSigner {
+        /// Base URL of the signing service
+        url: String,
     },
 }
 
-/// The networks supported by Trireme
-#[derive(Deserialize, Serialize, Clone)]
-#[serde(tag = "type")]
-pub enum Network {
-    /// "Preprod" Cardano test network
-    Preprod,
-    /// "Preview" Cardano test network
-    Preview,
-    /// Cardano Mainnet
-    Mainnet,
-}
-
-impl From<Network> for u8 {
-    fn from(network: Network) -> Self {
-        match network {
-            Network::Mainnet => 1,
-            Network::Preprod | Network::Preview => 0,
+impl KeySource {
+    /// Human readable name of the wallet type
+    pub fn kind(&self) -> &'static str {
+        match self {
+            KeySource::RawSecretPhrase { .. } => "raw secret phrase",
+            KeySource::TerminalPasswordUpfrontSecretPhrase { .. } => {
+                "password-protected secret phrase"
+            }
+            KeySource::EncryptedKeystore { .. } => "encrypted keystore",
+            KeySource::OsKeychain { .. } => "OS keychain",
+            KeySource::LedgerHardwareWallet { .. } => "Ledger hardware wallet",
+            KeySource::TrezorHardwareWallet { .. } => "Trezor hardware wallet",
+            KeySource::Cip30BrowserWallet { .. } => "browser wallet",
+            KeySource::WatchOnlyAddress { .. } | KeySource::WatchOnlyXpub { .. } => {
+                "watch-only wallet"
+            }
+            KeySource::RemoteSigner { .. } => "remote signer",
         }
     }
 }
@@ -284,6 +523,8 @@ impl TriremeConfig {
 pub struct ClientConfig {
     name: String,
     variant: ClientVariant,
+    #[serde(default)]
+    addresses: BTreeMap<String, String>,
 }
 
 /// Variant of client config
@@ -302,6 +543,15 @@ pub struct CMLClientConfig {
     ledger_source: LedgerSource,
     key_source: KeySource,
     network: Network,
+    #[serde(default)]
+    allow_mainnet: bool,
+}
+
+impl CMLClientConfig {
+    /// Getter for the key source
+    pub fn key_source(&self) -> &KeySource {
+        &self.key_source
+    }
 }
 
 /// Config for Test client
@@ -329,11 +579,13 @@ impl ClientConfig {
             ledger_source,
             key_source,
             network,
+            allow_mainnet: false,
         };
         let variant = ClientVariant::CML(inner);
         ClientConfig {
             name: name.to_string(),
             variant,
+            addresses: BTreeMap::new(),
         }
     }
 
@@ -346,6 +598,7 @@ impl ClientConfig {
         ClientConfig {
             name: name.to_string(),
             variant,
+            addresses: BTreeMap::new(),
         }
     }
 
@@ -359,6 +612,88 @@ impl ClientConfig {
         self.variant.clone()
     }
 
+    /// Use `network` instead of the network saved in the config
+    ///
+    /// Keys and provider settings are kept, so the same wallet can be used against
+    /// several networks from one process. Has no effect on test configs.
+    pub fn with_network(mut self, network: Network) -> Self {
+        if let ClientVariant::CML(inner) = &mut self.variant {
+            inner.network = network;
+        }
+        self
+    }
+
+    /// Allow issuing transactions when this config targets mainnet
+    ///
+    /// Clients built from CML configs refuse to issue on mainnet unless this is set or
+    /// `TRIREME_ALLOW_MAINNET` is `true`. Has no effect on test configs.
+    pub fn with_mainnet_allowed(mut self, allow_mainnet: bool) -> Self {
+        if let ClientVariant::CML(inner) = &mut self.variant {
+            inner.allow_mainnet = allow_mainnet;
+        }
+        self
+    }
+
+    /// Save `address` under `name` in this environment's address book, replacing any
+    /// address already saved under it
+    ///
+    /// Names let endpoint parameters and CLIs refer to e.g. `treasury` instead of a raw
+    /// bech32 string.
+    pub fn with_address(mut self, name: &str, address: &str) -> Result<Self> {
+        if Address::from_bech32(name).is_ok() {
+            return Err(Error::Trireme(format!(
+                "Address book name {name:?} is itself an address"
+            )))
+        }
+        Address::from_bech32(address)
+            .map_err(|e| Error::Trireme(format!("Invalid address {address:?}: {e:?}")))?;
+        self.addresses.insert(name.to_string(), address.to_string());
+        Ok(self)
+    }
+
+    /// Remove `name` from this environment's address book
+    pub fn without_address(mut self, name: &str) -> Self {
+        self.addresses.remove(name);
+        self
+    }
+
+    /// Getter for the address book, as names and bech32 addresses
+    pub fn addresses(&self) -> &BTreeMap<String, String> {
+        &self.addresses
+    }
+
+    /// The address saved under `name`, if any
+    pub fn address(&self, name: &str) -> Option<Address> {
+        self.addresses
+            .get(name)
+            .and_then(|address| Address::from_bech32(address).ok())
+    }
+
+    /// Resolve `name_or_address`, which is either an address book name or a bech32 address
+    pub fn resolve_address(&self, name_or_address: &str) -> Result<Address> {
+        if let Some(address) = self.address(name_or_address) {
+            return Ok(address)
+        }
+        Address::from_bech32(name_or_address).map_err(|_| {
+            Error::Trireme(format!(
+                "{name_or_address:?} is neither a saved name nor a valid address"
+            ))
+        })
+    }
+
+    /// One-line description of the network, provider and wallet of this config
+    pub fn summary(&self) -> String {
+        match &self.variant {
+            ClientVariant::CML(inner) => format!(
+                "{} via {} with {}",
+                inner.network,
+                inner.ledger_source.kind(),
+                inner.key_source.kind()
+            ),
+            ClientVariant::Test(_) => "Local mocked ledger".to_string(),
+        }
+    }
+
     /// Convert config into a [`TriremeLedgerClient`]
     pub async fn to_client<
         Datum: PlutusDataInterop
@@ -378,16 +713,23 @@ impl ClientConfig {
         match self.variant {
             ClientVariant::CML(inner) => {
                 let network = inner.network;
+                let mainnet_locked =
+                    matches!(network, Network::Mainnet) && !inner.allow_mainnet;
                 let keys = match inner.key_source {
-                    KeySource::RawSecretPhrase { phrase_file } => {
+                    KeySource::RawSecretPhrase {
+                        phrase_file,
+                        derivation_path,
+                    } => {
                         let keys =
-                            RawSecretPhraseKeys::new(phrase_file, network.clone().into());
-                        SecretPhraseKeys::RawSecretPhraseKeys(keys)
+                            RawSecretPhraseKeys::new(phrase_file, network.clone().into())
+                                .with_derivation_path(derivation_path);
+                        TriremeKeys::RawSecretPhraseKeys(keys)
                     }
                     KeySource::TerminalPasswordUpfrontSecretPhrase {
                         phrase_file,
                         password_salt,
                         encrpytion_nonce,
+                        derivation_path,
                     } => {
                         let password = TerminalPasswordUpfront::init(&password_salt)?;
                         let keys = PasswordProtectedPhraseKeys::new(
@@ -395,22 +737,104 @@ impl ClientConfig {
                             phrase_file,
                             network.clone().into(),
                             encrpytion_nonce,
+                        )
+                        .with_derivation_path(derivation_path);
+                        TriremeKeys::PasswordProtectedPhraseKeys(keys)
+                    }
+                    KeySource::EncryptedKeystore {
+                        keystore_file,
+                        derivation_path,
+                    } => {
+                        let keys = match env_var(KEYSTORE_PASSPHRASE_VAR) {
+                            Some(passphrase) => KeystoreKeys::new(
+                                keystore_file,
+                                Secret::new(passphrase),
+                                network.clone().into(),
+                            ),
+                            None => KeystoreKeys::prompt(
+                                keystore_file,
+                                network.clone().into(),
+                            )?,
+                        }
+                        .with_derivation_path(derivation_path);
+                        TriremeKeys::Keystore(keys)
+                    }
+                    KeySource::OsKeychain {
+                        service,
+                        account,
+                        derivation_path,
+                    } => os_keychain_keys(&service, &account, &network, derivation_path)?,
+                    KeySource::LedgerHardwareWallet {
+                        account,
+                        address_index,
+                    } => ledger_hardware_wallet_keys(account, address_index, &network)?,
+                    KeySource::TrezorHardwareWallet {
+                        account,
+                        address_index,
+                    } => {
+                        let device = TrezorDevice::connect(
+                            network.clone().into(),
+                            network.protocol_magic(),
+                        )
+                        .await
+                        .map_err(|e| Error::Trireme(e.to_string()))?;
+                        let keys = HardwareWalletKeys::new(
+                            device,
+                            account,
+                            network.clone().into(),
+                        )
+                        .with_address_index(address_index);
+                        TriremeKeys::TrezorHardwareWallet(keys)
+                    }
+                    KeySource::Cip30BrowserWallet { port } => {
+                        let addr = SocketAddr::from(([127, 0, 0, 1], port));
+                        let bridge = Cip30Bridge::start(addr)
+                            .await
+                            .map_err(|e| Error::Trireme(e.to_string()))?;
+                        tracing::info!(
+                            url = %bridge.url(),
+                            "Open the URL to connect your browser wallet"
                         );
-                        SecretPhraseKeys::PasswordProtectedPhraseKeys(keys)
+                        TriremeKeys::Cip30BrowserWallet(bridge)
+                    }
+                    KeySource::WatchOnlyAddress { address } => {
+                        let keys = WatchOnlyKeys::from_address(&address)
+                            .map_err(|e| Error::Trireme(e.to_string()))?;
+                        TriremeKeys::WatchOnly(keys)
+                    }
+                    KeySource::WatchOnlyXpub { account_xpub } => {
+                        let keys = WatchOnlyKeys::from_account_xpub(
+                            &account_xpub,
+                            network.clone().into(),
+                        )
+                        .map_err(|e| Error::Trireme(e.to_string()))?;
+                        TriremeKeys::WatchOnly(keys)
+                    }
+                    KeySource::RemoteSigner { url } => {
+                        let keys = RemoteSignerKeys::new(&url);
+                        let keys = match env_var(REMOTE_SIGNER_TOKEN_VAR) {
+                            Some(token) => keys.with_auth_token(Secret::new(token)),
+                            None => keys,
+                        };
+                        TriremeKeys::RemoteSigner(keys)
                     }
                 };
                 let inner_client = match inner.ledger_source {
                     LedgerSource::BlockFrost { api_key_file } => {
-                        let blockfrost_key =
-                            read_toml_struct_from_file::<BlockfrostApiKey>(&api_key_file)
-                                .await?
-                                .ok_or_else(|| {
-                                    Error::Trireme(
+                        let key = match env_var(BLOCKFROST_API_KEY_VAR) {
+                            Some(key) => key,
+                            None => read_toml_struct_from_file::<BlockfrostApiKey>(
+                                &api_key_file,
+                            )
+                            .await?
+                            .ok_or_else(|| {
+                                Error::Trireme(
                                 "Couldn't find blockfrost config, please try reinitialize Trireme"
                                     .to_string(),
                             )
-                                })?;
-                        let key: String = blockfrost_key.into();
+                            })?
+                            .into(),
+                        };
                         let url = match &network {
                             Network::Preprod => PREPROD_NETWORK_URL,
                             Network::Mainnet => MAINNET_URL,
@@ -419,9 +843,16 @@ impl ClientConfig {
                                     "Preview network not supported yet".to_string(),
                                 ))
                             }
+                            Network::Custom { .. } => {
+                                return Err(Error::Trireme(
+                                    "BlockFrost doesn't serve custom networks"
+                                        .to_string(),
+                                ))
+                            }
                         };
                         let ledger = BlockFrostLedger::new(url, &key);
-                        let network_settings = network.clone().into();
+                        let network_settings = NetworkSettings::try_from(network.clone())
+                            .map_err(|e| Error::Trireme(e.to_string()))?;
                         InnerClient::BlockFrost(CMLLedgerCLient::new(
                             ledger,
                             keys,
@@ -436,13 +867,16 @@ impl ClientConfig {
                     } => {
                         let scrolls_client =
                             ScrollsClient::new_redis(scrolls_ip, scrolls_port);
+                        let ogmios_url = format!("http://{ogmios_ip}:{ogmios_port}");
                         let ogmios_client = OgmiosClient::new(ogmios_ip, ogmios_port);
-                        let network_settings: NetworkSettings = network.into();
+                        let network_settings = NetworkSettings::try_from(network.clone())
+                            .map_err(|e| Error::Trireme(e.to_string()))?;
                         let ledger = OgmiosScrollsLedger::new(
                             scrolls_client,
                             ogmios_client,
                             network_settings,
-                        );
+                        )
+                        .with_ogmios_url(&ogmios_url);
                         InnerClient::OgmiosScrolls(CMLLedgerCLient::new(
                             ledger,
                             keys,
@@ -455,6 +889,7 @@ impl ClientConfig {
                     _datum: Default::default(),
                     _redeemer: Default::default(),
                     inner_client,
+                    mainnet_locked,
                 };
                 Ok(trireme_client)
             }
@@ -466,6 +901,7 @@ impl ClientConfig {
                     _datum: Default::default(),
                     _redeemer: Default::default(),
                     inner_client,
+                    mainnet_locked: false,
                 };
                 Ok(trireme_client)
             }
@@ -473,29 +909,206 @@ impl ClientConfig {
     }
 }
 
-/// Variants of Secret Phrase [`Keys`] impl
-pub enum SecretPhraseKeys {
+#[cfg(feature = "ledger")]
+fn ledger_hardware_wallet_keys(
+    account: u32,
+    address_index: u32,
+    network: &Network,
+) -> Result<TriremeKeys> {
+    let device = LedgerDevice::connect(network.clone().into(), network.protocol_magic())
+        .map_err(|e| Error::Trireme(e.to_string()))?;
+    let keys = HardwareWalletKeys::new(device, account, network.clone().into())
+        .with_address_index(address_index);
+    Ok(TriremeKeys::LedgerHardwareWallet(keys))
+}
+
+#[cfg(not(feature = "ledger"))]
+fn ledger_hardware_wallet_keys(
+    _account: u32,
+    _address_index: u32,
+    _network: &Network,
+) -> Result<TriremeKeys> {
+    Err(Error::Trireme(
+        "Naumachia was built without the `ledger` feature".to_string(),
+    ))
+}
+
+#[cfg(feature = "keychain")]
+fn os_keychain_keys(
+    service: &str,
+    account: &str,
+    network: &Network,
+    derivation_path: DerivationPath,
+) -> Result<TriremeKeys> {
+    let keys = KeychainKeys::new(service, account, network.clone().into())
+        .with_derivation_path(derivation_path);
+    Ok(TriremeKeys::OsKeychain(keys))
+}
+
+#[cfg(not(feature = "keychain"))]
+fn os_keychain_keys(
+    _service: &str,
+    _account: &str,
+    _network: &Network,
+    _derivation_path: DerivationPath,
+) -> Result<TriremeKeys> {
+    Err(Error::Trireme(
+        "Naumachia was built without the `keychain` feature".to_string(),
+    ))
+}
+
+/// Variants of [`Keys`] impl available to Trireme
+pub enum TriremeKeys {
     /// Raw secret phrase
     RawSecretPhraseKeys(RawSecretPhraseKeys),
     /// Password-protected secret phrase
     PasswordProtectedPhraseKeys(PasswordProtectedPhraseKeys<TerminalPasswordUpfront>),
+    /// Passphrase-encrypted keystore
+    Keystore(KeystoreKeys),
+    /// Secret phrase in the OS keychain
+    #[cfg(feature = "keychain")]
+    OsKeychain(KeychainKeys),
+    /// Ledger hardware wallet
+    #[cfg(feature = "ledger")]
+    LedgerHardwareWallet(HardwareWalletKeys<LedgerDevice>),
+    /// Trezor hardware wallet
+    TrezorHardwareWallet(HardwareWalletKeys<TrezorDevice>),
+    /// CIP-30 browser wallet
+    Cip30BrowserWallet(Cip30Bridge),
+    /// Watch-only wallet without secrets
+    WatchOnly(WatchOnlyKeys),
+    /// Remote signing service
+    RemoteSigner(RemoteSignerKeys),
 }
 
 #[async_trait]
-impl Keys for SecretPhraseKeys {
+impl Keys for TriremeKeys {
     async fn base_addr(&self) -> cml_client::error::Result<BaseAddress> {
         match self {
-            SecretPhraseKeys::RawSecretPhraseKeys(keys) => keys.base_addr().await,
-            SecretPhraseKeys::PasswordProtectedPhraseKeys(keys) => keys.base_addr().await,
+            TriremeKeys::RawSecretPhraseKeys(keys) => keys.base_addr().await,
+            TriremeKeys::PasswordProtectedPhraseKeys(keys) => keys.base_addr().await,
+            TriremeKeys::Keystore(keys) => keys.base_addr().await,
+            #[cfg(feature = "keychain")]
+            TriremeKeys::OsKeychain(keys) => keys.base_addr().await,
+            #[cfg(feature = "ledger")]
+            TriremeKeys::LedgerHardwareWallet(keys) => keys.base_addr().await,
+            TriremeKeys::TrezorHardwareWallet(keys) => keys.base_addr().await,
+            TriremeKeys::Cip30BrowserWallet(keys) => keys.base_addr().await,
+            TriremeKeys::WatchOnly(keys) => keys.base_addr().await,
+            TriremeKeys::RemoteSigner(keys) => keys.base_addr().await,
         }
     }
 
     async fn private_key(&self) -> cml_client::error::Result<PrivateKey> {
         match self {
-            SecretPhraseKeys::RawSecretPhraseKeys(keys) => keys.private_key().await,
-            SecretPhraseKeys::PasswordProtectedPhraseKeys(keys) => {
-                keys.private_key().await
+            TriremeKeys::RawSecretPhraseKeys(keys) => keys.private_key().await,
+            TriremeKeys::PasswordProtectedPhraseKeys(keys) => keys.private_key().await,
+            TriremeKeys::Keystore(keys) => keys.private_key().await,
+            #[cfg(feature = "keychain")]
+            TriremeKeys::OsKeychain(keys) => keys.private_key().await,
+            #[cfg(feature = "ledger")]
+            TriremeKeys::LedgerHardwareWallet(keys) => keys.private_key().await,
+            TriremeKeys::TrezorHardwareWallet(keys) => keys.private_key().await,
+            TriremeKeys::Cip30BrowserWallet(keys) => keys.private_key().await,
+            TriremeKeys::WatchOnly(keys) => keys.private_key().await,
+            TriremeKeys::RemoteSigner(keys) => keys.private_key().await,
+        }
+    }
+
+    async fn vkey_witnesses(
+        &self,
+        tx: &CMLTransaction,
+    ) -> cml_client::error::Result<Vec<Vkeywitness>> {
+        match self {
+            TriremeKeys::RawSecretPhraseKeys(keys) => keys.vkey_witnesses(tx).await,
+            TriremeKeys::PasswordProtectedPhraseKeys(keys) => {
+                keys.vkey_witnesses(tx).await
+            }
+            TriremeKeys::Keystore(keys) => keys.vkey_witnesses(tx).await,
+            #[cfg(feature = "keychain")]
+            TriremeKeys::OsKeychain(keys) => keys.vkey_witnesses(tx).await,
+            #[cfg(feature = "ledger")]
+            TriremeKeys::LedgerHardwareWallet(keys) => keys.vkey_witnesses(tx).await,
+            TriremeKeys::TrezorHardwareWallet(keys) => keys.vkey_witnesses(tx).await,
+            TriremeKeys::Cip30BrowserWallet(keys) => keys.vkey_witnesses(tx).await,
+            TriremeKeys::WatchOnly(keys) => keys.vkey_witnesses(tx).await,
+            TriremeKeys::RemoteSigner(keys) => keys.vkey_witnesses(tx).await,
+        }
+    }
+
+    async fn payment_public_key(&self) -> cml_client::error::Result<PublicKey> {
+        match self {
+            TriremeKeys::RawSecretPhraseKeys(keys) => keys.payment_public_key().await,
+            TriremeKeys::PasswordProtectedPhraseKeys(keys) => {
+                keys.payment_public_key().await
+            }
+            TriremeKeys::Keystore(keys) => keys.payment_public_key().await,
+            #[cfg(feature = "keychain")]
+            TriremeKeys::OsKeychain(keys) => keys.payment_public_key().await,
+            #[cfg(feature = "ledger")]
+            TriremeKeys::LedgerHardwareWallet(keys) => keys.payment_public_key().await,
+            TriremeKeys::TrezorHardwareWallet(keys) => keys.payment_public_key().await,
+            TriremeKeys::Cip30BrowserWallet(keys) => keys.payment_public_key().await,
+            TriremeKeys::WatchOnly(keys) => keys.payment_public_key().await,
+            TriremeKeys::RemoteSigner(keys) => keys.payment_public_key().await,
+        }
+    }
+
+    async fn sign_data(
+        &self,
+        payload: &[u8],
+    ) -> cml_client::error::Result<DataSignature> {
+        match self {
+            TriremeKeys::RawSecretPhraseKeys(keys) => keys.sign_data(payload).await,
+            TriremeKeys::PasswordProtectedPhraseKeys(keys) => {
+                keys.sign_data(payload).await
+            }
+            TriremeKeys::Keystore(keys) => keys.sign_data(payload).await,
+            #[cfg(feature = "keychain")]
+            TriremeKeys::OsKeychain(keys) => keys.sign_data(payload).await,
+            #[cfg(feature = "ledger")]
+            TriremeKeys::LedgerHardwareWallet(keys) => keys.sign_data(payload).await,
+            TriremeKeys::TrezorHardwareWallet(keys) => keys.sign_data(payload).await,
+            TriremeKeys::Cip30BrowserWallet(keys) => keys.sign_data(payload).await,
+            TriremeKeys::WatchOnly(keys) => keys.sign_data(payload).await,
+            TriremeKeys::RemoteSigner(keys) => keys.sign_data(payload).await,
+        }
+    }
+
+    async fn drep_key(&self) -> cml_client::error::Result<PrivateKey> {
+        match self {
+            TriremeKeys::RawSecretPhraseKeys(keys) => keys.drep_key().await,
+            TriremeKeys::PasswordProtectedPhraseKeys(keys) => keys.drep_key().await,
+            TriremeKeys::Keystore(keys) => keys.drep_key().await,
+            #[cfg(feature = "keychain")]
+            TriremeKeys::OsKeychain(keys) => keys.drep_key().await,
+            #[cfg(feature = "ledger")]
+            TriremeKeys::LedgerHardwareWallet(keys) => keys.drep_key().await,
+            TriremeKeys::TrezorHardwareWallet(keys) => keys.drep_key().await,
+            TriremeKeys::Cip30BrowserWallet(keys) => keys.drep_key().await,
+            TriremeKeys::WatchOnly(keys) => keys.drep_key().await,
+            TriremeKeys::RemoteSigner(keys) => keys.drep_key().await,
+        }
+    }
+
+    async fn drep_witness(
+        &self,
+        tx_hash: &TransactionHash,
+    ) -> cml_client::error::Result<Vkeywitness> {
+        match self {
+            TriremeKeys::RawSecretPhraseKeys(keys) => keys.drep_witness(tx_hash).await,
+            TriremeKeys::PasswordProtectedPhraseKeys(keys) => {
+                keys.drep_witness(tx_hash).await
             }
+            TriremeKeys::Keystore(keys) => keys.drep_witness(tx_hash).await,
+            #[cfg(feature = "keychain")]
+            TriremeKeys::OsKeychain(keys) => keys.drep_witness(tx_hash).await,
+            #[cfg(feature = "ledger")]
+            TriremeKeys::LedgerHardwareWallet(keys) => keys.drep_witness(tx_hash).await,
+            TriremeKeys::TrezorHardwareWallet(keys) => keys.drep_witness(tx_hash).await,
+            TriremeKeys::Cip30BrowserWallet(keys) => keys.drep_witness(tx_hash).await,
+            TriremeKeys::WatchOnly(keys) => keys.drep_witness(tx_hash).await,
+            TriremeKeys::RemoteSigner(keys) => keys.drep_witness(tx_hash).await,
         }
     }
 }
@@ -513,11 +1126,9 @@ where
     Redeemer: PlutusDataInterop,
 {
     /// BlockFrost client
-    BlockFrost(CMLLedgerCLient<BlockFrostLedger, SecretPhraseKeys, Datum, Redeemer>),
+    BlockFrost(CMLLedgerCLient<BlockFrostLedger, TriremeKeys, Datum, Redeemer>),
     /// Ogmios + Scrolls client
-    OgmiosScrolls(
-        CMLLedgerCLient<OgmiosScrollsLedger, SecretPhraseKeys, Datum, Redeemer>,
-    ),
+    OgmiosScrolls(CMLLedgerCLient<OgmiosScrollsLedger, TriremeKeys, Datum, Redeemer>),
     /// Test client
     Mocked(TestLedgerClient<Datum, Redeemer, LocalPersistedStorage<PathBuf, Datum>>),
 }
@@ -537,6 +1148,7 @@ where
     _datum: PhantomData<Datum>,
     _redeemer: PhantomData<Redeemer>,
     inner_client: InnerClient<Datum, Redeemer>,
+    mainnet_locked: bool,
 }
 
 impl<Datum, Redeemer> TriremeLedgerClient<Datum, Redeemer>
@@ -550,6 +1162,17 @@ where
         + TryFrom<PlutusData>,
     Redeemer: PlutusDataInterop,
 {
+    fn check_mainnet_unlocked(&self) -> LedgerClientResult<()> {
+        if self.mainnet_locked {
+            return Err(LedgerClientError::FailedToIssueTx(Box::new(
+                Error::MainnetSafety(format!(
+                    "Set `allow_mainnet` in the environment config or {ALLOW_MAINNET_VAR}=true to issue on mainnet"
+                )),
+            )))
+        }
+        Ok(())
+    }
+
     /// Get current time within context of current ledger
     pub async fn current_time(&self) -> LedgerClientResult<i64> {
         match &self.inner_client {
@@ -567,6 +1190,105 @@ where
         }
     }
 
+    /// Get the payment verification key of the signer
+    ///
+    /// **NOTE:** Not available for the test client, which has no real keys.
+    pub async fn signer_payment_public_key(&self) -> LedgerClientResult<PublicKey> {
+        match &self.inner_client {
+            InnerClient::BlockFrost(cml_client) => {
+                cml_client.signer_payment_public_key().await
+            }
+            InnerClient::Mocked(_) => {
+                return Err(LedgerClientError::ConfigError(
+                    "The mocked ledger has no verification keys".to_string(),
+                ))
+            }
+            InnerClient::OgmiosScrolls(cml_client) => {
+                cml_client.signer_payment_public_key().await
+            }
+        }
+        .map_err(|e| LedgerClientError::PaymentPublicKey(Box::new(e)))
+    }
+
+    /// Balance `tx` against the signer's UTxOs and return it unsigned, as CBOR
+    ///
+    /// This is how watch-only wallets prepare transactions to be signed elsewhere.
+    /// **NOTE:** Not available for the test client.
+    pub async fn build_unsigned_tx(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<Vec<u8>>
+    where
+        Datum: Debug,
+    {
+        let tx = match &self.inner_client {
+            InnerClient::BlockFrost(cml_client) => {
+                cml_client.build_unsigned_tx(tx).await?
+            }
+            InnerClient::Mocked(_) => {
+                return Err(LedgerClientError::ConfigError(
+                    "The mocked ledger doesn't build CBOR transactions".to_string(),
+                ))
+            }
+            InnerClient::OgmiosScrolls(cml_client) => {
+                cml_client.build_unsigned_tx(tx).await?
+            }
+        };
+        Ok(tx.to_bytes())
+    }
+
+    /// Issue `tx` signed by the signer and all of `extra_signers`, e.g. a policy admin key
+    ///
+    /// **NOTE:** Not available for the test client, which has no real keys.
+    pub async fn issue_with_extra_signers(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+        extra_signers: &[&(dyn Keys + Sync)],
+    ) -> LedgerClientResult<TxId>
+    where
+        Datum: Debug,
+    {
+        self.check_mainnet_unlocked()?;
+        match &self.inner_client {
+            InnerClient::BlockFrost(cml_client) => {
+                cml_client.issue_with_extra_signers(tx, extra_signers).await
+            }
+            InnerClient::Mocked(_) => Err(LedgerClientError::ConfigError(
+                "The mocked ledger has no keys to sign with".to_string(),
+            )),
+            InnerClient::OgmiosScrolls(cml_client) => {
+                cml_client.issue_with_extra_signers(tx, extra_signers).await
+            }
+        }
+    }
+
+    /// Fetch a submitted transaction and decode it into Naumachia types
+    ///
+    /// **NOTE:** This is only implemented for the BlockFrost client.
+    pub async fn inspect_tx(
+        &self,
+        tx_id: &TxId,
+    ) -> LedgerClientResult<InspectedTx<Datum, Redeemer>> {
+        let as_failed_to_retrieve_tx = |e: CMLLCError| {
+            LedgerClientError::FailedToRetrieveTx(tx_id.to_owned(), Box::new(e))
+        };
+        match &self.inner_client {
+            InnerClient::BlockFrost(cml_client) => cml_client
+                .inspect_tx(tx_id)
+                .await
+                .map_err(as_failed_to_retrieve_tx),
+            InnerClient::Mocked(_) => Err(as_failed_to_retrieve_tx(
+                CMLLCError::LedgerError(Box::new(Error::Trireme(
+                    "The mocked ledger doesn't keep transaction CBOR".to_string(),
+                ))),
+            )),
+            InnerClient::OgmiosScrolls(cml_client) => cml_client
+                .inspect_tx(tx_id)
+                .await
+                .map_err(as_failed_to_retrieve_tx),
+        }
+    }
+
     /// Advance time for current context.
     ///
     /// **NOTE:** This is only implemented for the test client.
@@ -649,10 +1371,54 @@ where
         .await
     }
 
+    fn stream_outputs_at_address<'a>(
+        &'a self,
+        address: &'a Address,
+    ) -> BoxStream<'a, LedgerClientResult<Output<Datum>>>
+    where
+        Datum: Send + 'a,
+    {
+        match &self.inner_client {
+            InnerClient::BlockFrost(cml_client) => {
+                cml_client.stream_outputs_at_address(address)
+            }
+            InnerClient::Mocked(test_client) => {
+                test_client.stream_outputs_at_address(address)
+            }
+            InnerClient::OgmiosScrolls(cml_client) => {
+                cml_client.stream_outputs_at_address(address)
+            }
+        }
+    }
+
+    async fn outputs_at_address_matching<P>(
+        &self,
+        address: &Address,
+        predicate: P,
+    ) -> LedgerClientResult<Vec<Output<Datum>>>
+    where
+        Datum: Clone + Into<PlutusData> + Send,
+        P: Fn(&PlutusData) -> bool + Send + Sync,
+    {
+        match &self.inner_client {
+            InnerClient::BlockFrost(cml_client) => {
+                cml_client.outputs_at_address_matching(address, predicate)
+            }
+            InnerClient::Mocked(test_client) => {
+                test_client.outputs_at_address_matching(address, predicate)
+            }
+            InnerClient::OgmiosScrolls(cml_client) => {
+                cml_client.outputs_at_address_matching(address, predicate)
+            }
+        }
+        .await
+    }
+
     async fn issue(
         &self,
         tx: UnbuiltTransaction<Datum, Redeemer>,
     ) -> LedgerClientResult<TxId> {
+        self.check_mainnet_unlocked()?;
         match &self.inner_client {
             InnerClient::BlockFrost(cml_client) => cml_client.issue(tx),
             InnerClient::Mocked(test_client) => test_client.issue(tx),
@@ -661,6 +1427,58 @@ where
         .await
     }
 
+    async fn issue_with_retry(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+        policy: &RetryPolicy,
+    ) -> LedgerClientResult<TxId> {
+        self.check_mainnet_unlocked()?;
+        match &self.inner_client {
+            InnerClient::BlockFrost(cml_client) => {
+                cml_client.issue_with_retry(tx, policy)
+            }
+            InnerClient::Mocked(test_client) => test_client.issue_with_retry(tx, policy),
+            InnerClient::OgmiosScrolls(cml_client) => {
+                cml_client.issue_with_retry(tx, policy)
+            }
+        }
+        .await
+    }
+
+    async fn issue_with_preview(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<(TxId, Option<TxPreview<Datum>>)> {
+        self.check_mainnet_unlocked()?;
+        match &self.inner_client {
+            InnerClient::BlockFrost(cml_client) => cml_client.issue_with_preview(tx),
+            InnerClient::Mocked(test_client) => test_client.issue_with_preview(tx),
+            InnerClient::OgmiosScrolls(cml_client) => cml_client.issue_with_preview(tx),
+        }
+        .await
+    }
+
+    async fn preview(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<TxPreview<Datum>> {
+        match &self.inner_client {
+            InnerClient::BlockFrost(cml_client) => cml_client.preview(tx),
+            InnerClient::Mocked(test_client) => test_client.preview(tx),
+            InnerClient::OgmiosScrolls(cml_client) => cml_client.preview(tx),
+        }
+        .await
+    }
+
+    async fn is_confirmed(&self, tx_id: &TxId) -> LedgerClientResult<bool> {
+        match &self.inner_client {
+            InnerClient::BlockFrost(cml_client) => cml_client.is_confirmed(tx_id),
+            InnerClient::Mocked(test_client) => test_client.is_confirmed(tx_id),
+            InnerClient::OgmiosScrolls(cml_client) => cml_client.is_confirmed(tx_id),
+        }
+        .await
+    }
+
     async fn network(&self) -> LedgerClientResult<pallas_addresses::Network> {
         match &self.inner_client {
             InnerClient::BlockFrost(cml_client) => cml_client.network(),
@@ -744,3 +1562,117 @@ pub async fn read_toml_struct_from_file<Toml: DeserializeOwned>(
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::transaction::TxActions;
+
+    #[test]
+    fn switch_env__changes_current_env_to_existing_env() {
+        // given
+        let mut config = TriremeConfig::new("preprod-test-wallet");
+        config.set_new_env("mainnet-treasury").unwrap();
+
+        // when
+        config.switch_env("preprod-test-wallet").unwrap();
+
+        // then
+        assert_eq!(
+            config.current_env(),
+            Some("preprod-test-wallet".to_string())
+        );
+    }
+
+    #[test]
+    fn with_network__overrides_configured_network() {
+        // given
+        let config = ClientConfig::new_cml(
+            "preprod-test-wallet",
+            LedgerSource::OgmiosAndScrolls {
+                scrolls_ip: "127.0.0.1".to_string(),
+                scrolls_port: "6379".to_string(),
+                ogmios_ip: "127.0.0.1".to_string(),
+                ogmios_port: "1337".to_string(),
+            },
+            KeySource::LedgerHardwareWallet {
+                account: 0,
+                address_index: 0,
+            },
+            Network::Preprod,
+        );
+
+        // when
+        let config = config.with_network(Network::Custom {
+            protocol_magic: 42,
+            network_id: 0,
+            slot_length: 1,
+            starting_slot_time: 0,
+            starting_slot_number: 0,
+        });
+
+        // then
+        assert_eq!(
+            config.summary(),
+            "Custom (magic 42) via Ogmios and Scrolls with Ledger hardware wallet"
+        );
+    }
+
+    #[test]
+    fn switch_env__fails_for_unknown_env() {
+        // given
+        let mut config = TriremeConfig::new("preprod-test-wallet");
+
+        // when
+        let res = config.switch_env("mainnet-treasury");
+
+        // then
+        assert!(res.is_err());
+        assert_eq!(
+            config.current_env(),
+            Some("preprod-test-wallet".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn issue_with_extra_signers__is_refused_by_the_mocked_ledger() {
+        // given
+        let client: TriremeLedgerClient<(), ()> =
+            ClientConfig::new_test("local", &PathBuf::from("data.toml"))
+                .to_client()
+                .await
+                .unwrap();
+        let tx = TxActions::v2().to_unbuilt_tx().unwrap();
+
+        // when
+        let res = client.issue_with_extra_signers(tx, &[]).await;
+
+        // then
+        assert!(matches!(res, Err(LedgerClientError::ConfigError(_))));
+    }
+
+    #[test]
+    fn resolve_address__accepts_saved_names_and_raw_addresses() {
+        // given
+        let treasury = "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu";
+        let oracle_feed =
+            "addr_test1wpe9mt7mkjmkkuqjmevzafm6mle9t0spprr9335q0e6p92cur7fvl";
+        let config = ClientConfig::new_test("local", &PathBuf::from("data.toml"))
+            .with_address("treasury", treasury)
+            .unwrap();
+
+        // when
+        let by_name = config.resolve_address("treasury").unwrap();
+        let by_address = config.resolve_address(oracle_feed).unwrap();
+        let unknown = config.resolve_address("oracle-feed");
+        let invalid = config.with_address("oracle-feed", "nope");
+
+        // then
+        assert_eq!(by_name, Address::from_bech32(treasury).unwrap());
+        assert_eq!(by_address, Address::from_bech32(oracle_feed).unwrap());
+        assert!(unknown.is_err());
+        assert!(invalid.is_err());
+    }
+}
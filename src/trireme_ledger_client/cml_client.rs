@@ -1,29 +1,61 @@
 use crate::{
     ledger_client::{
+        clock::{
+            Clock,
+            SystemClock,
+        },
+        retry::{
+            is_spent_inputs_error,
+            with_retry,
+            RetryPolicy,
+        },
         LedgerClient,
         LedgerClientError,
         LedgerClientResult,
     },
     output::{
+        DatumAttachment,
+        DatumKind,
         Output,
+        ReferenceScript,
         UnbuiltOutput,
     },
-    scripts::Validator,
+    protocol_params::ProtocolParameters,
+    scripts::{
+        plutus_validator::plutus_data::PlutusData as NauPlutusData,
+        ExecutionCost as ScriptExecutionCost,
+        Validator,
+    },
     transaction::{
+        era::EraFeature,
         TransactionVersion,
         TxId,
+        TxPreview,
     },
     trireme_ledger_client::cml_client::{
+        balancing::{
+            BalancingContext,
+            BalancingStrategy,
+            PreselectPureAda,
+        },
         issuance_helpers::{
             add_collateral,
+            add_metadata,
+            add_required_signers,
             build_tx_for_signing,
+            can_be_collateral,
+            cml_script_ref,
             cml_v1_script_from_nau_policy,
             cml_v1_script_from_nau_script,
             cml_v2_script_from_nau_policy,
             cml_v2_script_from_nau_script,
+            estimate_fee,
+            input_from_utxo,
             input_tx_hash,
             partial_script_witness,
+            required_signers,
             select_inputs_from_utxos,
+            set_total_collateral,
             sign_tx,
             specify_utxos_available_for_input_selection,
             utxo_to_nau_utxo,
@@ -32,7 +64,14 @@ use crate::{
         },
         network_settings::NetworkSettings,
         plutus_data_interop::PlutusDataInterop,
+        protocol_params_cache::{
+            EpochParameters,
+            ProtocolParamsCache,
+        },
+        tx_inspect::InspectedTx,
     },
+    values::Values,
+    PolicyId,
     UnbuiltTransaction,
 };
 use async_trait::async_trait;
@@ -55,25 +94,30 @@ use cardano_multiplatform_lib::{
         output_builder::SingleOutputBuilderResult,
         redeemer_builder::RedeemerWitnessKey,
         tx_builder::{
-            ChangeSelectionAlgo,
+            SignedTxBuilder,
             TransactionBuilder,
         },
-        witness_builder::{
-            PartialPlutusWitness,
-            PlutusScriptWitness,
-        },
     },
     crypto::{
+        Ed25519KeyHash,
         PrivateKey,
+        PublicKey,
         TransactionHash,
+        Vkeywitness,
     },
-    ledger::common::{
-        hash::hash_plutus_data,
-        value::{
-            BigNum,
-            Int,
-            Value as CMLValue,
+    ledger::{
+        common::{
+            hash::{
+                hash_plutus_data,
+                hash_transaction,
+            },
+            value::{
+                BigNum,
+                Int,
+                Value as CMLValue,
+            },
         },
+        shelley::witness::make_vkey_witness,
     },
     plutus::{
         ExUnits,
@@ -88,35 +132,106 @@ use cardano_multiplatform_lib::{
     Transaction as CMLTransaction,
     TransactionInput,
     TransactionOutput,
+    TransactionUnspentOutput,
 };
 use error::*;
+use futures::{
+    stream::{
+        self,
+        BoxStream,
+    },
+    StreamExt,
+    TryStreamExt,
+};
+use governance::{
+    DRepId,
+    GovernanceError,
+};
+use message_signing::{
+    sign_data,
+    DataSignature,
+};
+#[cfg(feature = "trireme")]
+use ogmios_client::OgmiosClient;
+#[cfg(feature = "trireme")]
+use ogmios_evaluation::OgmiosEvaluatedLedger;
 use pallas_addresses::{
     Address,
     Network as CMLNetwork,
 };
+use proposal::TxProposal;
 use std::{
     collections::HashMap,
     fmt::Debug,
     marker::PhantomData,
     ops::Deref,
-    time::UNIX_EPOCH,
+    sync::{
+        atomic::{
+            AtomicBool,
+            Ordering,
+        },
+        Mutex,
+    },
 };
 
+// Lovelace sent along with outputs that don't specify any
+const DEFAULT_OUTPUT_LOVELACE: u64 = 1155080;
+
+/// Change and fee balancing module
+pub mod balancing;
 /// Blockfrost Ledger module
+#[cfg(feature = "blockfrost")]
 pub mod blockfrost_ledger;
+/// CIP-36 Catalyst voting registration module
+pub mod catalyst;
+/// CIP-30 browser wallet bridge module
+#[cfg(feature = "trireme")]
+pub mod cip30_bridge;
+/// CIP-30 browser wallet Keys module, for running in the browser
+#[cfg(target_arch = "wasm32")]
+pub mod cip30_wallet;
 /// CML Ledger Client Error module
 pub mod error;
+/// Conway governance certificates and votes module
+pub mod governance;
+/// Hardware wallet Keys module
+pub mod hardware_wallet;
 mod issuance_helpers;
 /// CML Client Keys module
 pub mod key_manager;
+/// Kupo Ledger module
+#[cfg(feature = "trireme")]
+pub mod kupo_ledger;
+/// CIP-8 message signing module
+pub mod message_signing;
 /// CML Client Network Settings module
 pub mod network_settings;
+/// Ogmios script evaluation module
+#[cfg(feature = "trireme")]
+pub mod ogmios_evaluation;
 /// Ogmios + Scrolls Ledger module
+#[cfg(feature = "trireme")]
 pub mod ogmios_scrolls_ledger;
+/// pallas-txbuilder transaction building module
+#[cfg(feature = "pallas-builder")]
+pub mod pallas_builder;
 /// Plutus Data Interop module
 pub mod plutus_data_interop;
+/// Multi-party transaction proposal module
+pub mod proposal;
+/// Protocol parameters caching module
+pub mod protocol_params_cache;
+/// Remote signing service Keys module
+#[cfg(feature = "trireme")]
+pub mod remote_signer;
+/// Secret Phrase module
+pub mod secret_phrase;
+/// Transaction decoding module
+pub mod tx_inspect;
+/// Watch-only Keys module
+pub mod watch_only;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "trireme"))]
 mod tests;
 
 // TODO: Add minimum ADA https://github.com/MitchTurner/naumachia/issues/41
@@ -131,6 +246,10 @@ where
     ledger: L,
     keys: K,
     network_settings: NetworkSettings,
+    protocol_params: ProtocolParamsCache,
+    tx_builder_backend: TxBuilderBackend,
+    balancing: Box<dyn BalancingStrategy>,
+    clock: Box<dyn Clock>,
     _datum: PhantomData<Datum>,
     _redeemer: PhantomData<Redeemer>,
 }
@@ -142,6 +261,48 @@ pub trait Keys {
     async fn base_addr(&self) -> Result<BaseAddress>;
     /// Get the private key for signer
     async fn private_key(&self) -> Result<PrivateKey>;
+    /// Get the vkey witnesses authorizing `tx`
+    ///
+    /// Defaults to signing the body hash with [`Keys::private_key`]. Implementations that
+    /// can't expose their private key, e.g. hardware wallets, should override this.
+    async fn vkey_witnesses(&self, tx: &CMLTransaction) -> Result<Vec<Vkeywitness>> {
+        let priv_key = self.private_key().await?;
+        let tx_hash = hash_transaction(&tx.body());
+        let vkey_witness = make_vkey_witness(&tx_hash, &priv_key);
+        Ok(vec![vkey_witness])
+    }
+    /// Get the payment verification key for signer
+    ///
+    /// Defaults to deriving it from [`Keys::private_key`].
+    async fn payment_public_key(&self) -> Result<PublicKey> {
+        let priv_key = self.private_key().await?;
+        Ok(priv_key.to_public())
+    }
+    /// Sign `payload` for the base address like CIP-30 `signData`, so a dApp can check the
+    /// signer controls it
+    ///
+    /// Defaults to signing with [`Keys::private_key`].
+    async fn sign_data(&self, payload: &[u8]) -> Result<DataSignature> {
+        let priv_key = self.private_key().await?;
+        let address = self.base_addr().await?.to_address().to_bytes();
+        let signature = sign_data(&priv_key, &address, payload)
+            .map_err(|e| CMLLCError::KeyError(Box::new(e)))?;
+        Ok(signature)
+    }
+    /// Get the CIP-105 DRep key for signer
+    ///
+    /// Defaults to failing, for keys that don't derive one.
+    async fn drep_key(&self) -> Result<PrivateKey> {
+        Err(CMLLCError::KeyError(Box::new(GovernanceError::NoDRepKey)))
+    }
+    /// Get the vkey witness of the DRep key for the transaction with body hash `tx_hash`,
+    /// authorizing its DRep certificates and votes
+    ///
+    /// Defaults to signing with [`Keys::drep_key`].
+    async fn drep_witness(&self, tx_hash: &TransactionHash) -> Result<Vkeywitness> {
+        let drep_key = self.drep_key().await?;
+        Ok(make_vkey_witness(tx_hash, &drep_key))
+    }
 }
 
 fn addr_from_bech_32(addr: &str) -> Result<CMLAddress> {
@@ -157,6 +318,8 @@ pub struct UTxO {
     output_index: BigNum,
     amount: CMLValue,
     datum: Option<PlutusData>,
+    datum_hash: Option<Vec<u8>>,
+    reference_script: Option<ReferenceScript>,
 }
 
 impl UTxO {
@@ -172,9 +335,24 @@ impl UTxO {
             output_index,
             amount,
             datum,
+            datum_hash: None,
+            reference_script: None,
         }
     }
 
+    /// Record that the `UTxO` carries its datum by hash. Leave the datum as `None` if it
+    /// couldn't be resolved
+    pub fn with_datum_hash(mut self, datum_hash: Vec<u8>) -> Self {
+        self.datum_hash = Some(datum_hash);
+        self
+    }
+
+    /// Record the Plutus script stored on the `UTxO` for use by reference
+    pub fn with_reference_script(mut self, reference_script: ReferenceScript) -> Self {
+        self.reference_script = Some(reference_script);
+        self
+    }
+
     /// Get the transaction hash
     pub fn tx_hash(&self) -> &TransactionHash {
         &self.tx_hash
@@ -194,6 +372,27 @@ impl UTxO {
     pub fn datum(&self) -> &Option<PlutusData> {
         &self.datum
     }
+
+    /// Get the datum hash on the `UTxO`, if the datum is attached by hash
+    pub fn datum_hash(&self) -> Option<&[u8]> {
+        self.datum_hash.as_deref()
+    }
+
+    /// Get the Plutus script stored on the `UTxO`, if any
+    pub fn reference_script(&self) -> Option<&ReferenceScript> {
+        self.reference_script.as_ref()
+    }
+}
+
+/// Library that [`CMLLedgerCLient`] builds transactions with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TxBuilderBackend {
+    /// `cardano-multiplatform-lib`
+    #[default]
+    Cml,
+    /// `pallas-txbuilder`, for when CML's bugs or weight get in the way
+    #[cfg(feature = "pallas-builder")]
+    Pallas,
 }
 
 /// Cost of execution for a transaction
@@ -271,6 +470,63 @@ impl ExecutionCost {
     pub fn steps(&self) -> u64 {
         self.steps
     }
+
+    // One entry of an Ogmios `EvaluationResult`, e.g. `"spend:0": {"memory": 1, "steps": 2}`
+    pub(crate) fn from_ogmios_entry(
+        key: &str,
+        value: &serde_json::Value,
+    ) -> Option<(u64, ExecutionCost)> {
+        let (type_str, index) = key.split_once(':')?;
+        let index = index.parse::<u64>().ok()?;
+        let value_obj = value.as_object()?;
+        let memory = value_obj.get("memory")?.as_u64()?;
+        let steps = value_obj.get("steps")?.as_u64()?;
+        let ex_cost = match type_str {
+            "spend" => ExecutionCost::new_spend(memory, steps),
+            "mint" => ExecutionCost::new_mint(memory, steps),
+            "withdrawal" => ExecutionCost::new_withdrawal(memory, steps),
+            "certificate" => ExecutionCost::new_certificate(memory, steps),
+            _ => return None,
+        };
+        Some((index, ex_cost))
+    }
+}
+
+/// Number of UTxOs in each page of [`Ledger::get_utxo_page_for_addr`]
+pub const UTXO_PAGE_SIZE: usize = 100;
+
+// Lovelace kept on top of a transaction's outputs for fees, change and min ADA when paging
+// through the wallet
+const WALLET_FEE_HEADROOM: u64 = 5_000_000;
+
+// What the wallet has to pay for `tx`, its outputs and burns plus headroom, and what `tx`
+// brings in without the wallet, its other inputs and mints
+fn wallet_spend_estimate<Datum, Redeemer>(
+    tx: &UnbuiltTransaction<Datum, Redeemer>,
+) -> LedgerClientResult<(Values, Values)> {
+    let mut spent = Values::default();
+    spent.add_one_value(&PolicyId::Lovelace, WALLET_FEE_HEADROOM);
+    let mut received = Values::default();
+    for output in tx.unbuilt_outputs.iter() {
+        spent.add_values(output.values());
+    }
+    for (input, _, _) in tx.script_inputs.iter() {
+        received.add_values(input.values());
+    }
+    for input in tx.specific_wallet_inputs.iter() {
+        received.add_values(input.values());
+    }
+    for (amount, asset_name, _, policy) in tx.minting.iter() {
+        let id = policy.id().map_err(as_failed_to_issue_tx)?;
+        let token = PolicyId::native_token(&id, asset_name);
+        let values = if *amount < 0 {
+            &mut spent
+        } else {
+            &mut received
+        };
+        values.add_one_value(&token, amount.unsigned_abs());
+    }
+    Ok((spent, received))
 }
 
 /// Interface for providing a ledger to the [`CMLLedgerCLient`]
@@ -286,6 +542,21 @@ pub trait Ledger {
     ) -> Result<Vec<UTxO>>;
     /// Get all the UTxOs for an address
     async fn get_all_utxos_for_addr(&self, addr: &CMLAddress) -> Result<Vec<UTxO>>;
+    /// Get page `page`, counting from 0, of the UTxOs for an address
+    ///
+    /// Pages hold [`UTXO_PAGE_SIZE`] UTxOs, and a shorter page is the last. Defaults to every
+    /// UTxO on the first page, for ledgers that can't page.
+    async fn get_utxo_page_for_addr(
+        &self,
+        addr: &CMLAddress,
+        page: usize,
+    ) -> Result<Vec<UTxO>> {
+        if page == 0 {
+            self.get_all_utxos_for_addr(addr).await
+        } else {
+            Ok(Vec::new())
+        }
+    }
     /// Calculate the execution units for a transaction
     async fn calculate_ex_units(
         &self,
@@ -293,12 +564,36 @@ pub trait Ledger {
     ) -> Result<HashMap<u64, ExecutionCost>>;
     /// Submit a transaction
     async fn submit_transaction(&self, tx: &CMLTransaction) -> Result<String>;
+    /// Submit an already signed transaction from its CBOR
+    ///
+    /// Defaults to [`CMLLCError::Unsupported`], for ledgers that only submit CML transactions.
+    async fn submit_transaction_cbor(&self, _cbor: &[u8]) -> Result<String> {
+        Err(CMLLCError::Unsupported(
+            "submitting transaction CBOR".to_string(),
+        ))
+    }
+    /// Get the CBOR of a submitted transaction
+    async fn get_transaction_cbor(&self, tx_id: &str) -> Result<Vec<u8>>;
+    /// Whether the transaction `tx_id` is in a block
+    ///
+    /// Defaults to [`CMLLCError::Unsupported`], for ledgers that can't look it up.
+    async fn is_tx_confirmed(&self, _tx_id: &str) -> Result<bool> {
+        Err(CMLLCError::Unsupported(
+            "checking whether transactions are confirmed".to_string(),
+        ))
+    }
+    /// Get the protocol parameters of the current epoch
+    ///
+    /// Defaults to [`ProtocolParameters::default`], for ledgers that can't query them.
+    async fn epoch_parameters(&self) -> Result<EpochParameters> {
+        Ok(EpochParameters::new(ProtocolParameters::default(), None))
+    }
 }
 
 impl<L, K, D, R> CMLLedgerCLient<L, K, D, R>
 where
     L: Ledger,
-    K: Keys,
+    K: Keys + Sync,
     D: PlutusDataInterop,
     R: PlutusDataInterop,
 {
@@ -308,11 +603,339 @@ where
             ledger,
             keys,
             network_settings,
+            protocol_params: ProtocolParamsCache::default(),
+            tx_builder_backend: TxBuilderBackend::default(),
+            balancing: Box::new(PreselectPureAda::default()),
+            clock: Box::new(SystemClock),
+            _datum: Default::default(),
+            _redeemer: Default::default(),
+        }
+    }
+
+    /// Build transactions with `backend` instead of CML
+    pub fn with_tx_builder_backend(mut self, backend: TxBuilderBackend) -> Self {
+        self.tx_builder_backend = backend;
+        self
+    }
+
+    /// Balance transactions built with CML using `strategy`
+    pub fn with_balancing_strategy(
+        mut self,
+        strategy: impl BalancingStrategy + 'static,
+    ) -> Self {
+        self.balancing = Box::new(strategy);
+        self
+    }
+
+    /// Answer [`LedgerClient::current_time_secs`] from `clock` instead of the system time,
+    /// e.g. a [`ChainTipClock`](crate::ledger_client::clock::ChainTipClock)
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Evaluate redeemer budgets with Ogmios instead of the ledger, for exact execution
+    /// units while building
+    #[cfg(feature = "trireme")]
+    pub fn with_ogmios_evaluation(
+        self,
+        ogmios_client: OgmiosClient,
+    ) -> CMLLedgerCLient<OgmiosEvaluatedLedger<L>, K, D, R> {
+        CMLLedgerCLient {
+            ledger: OgmiosEvaluatedLedger::new(self.ledger, ogmios_client),
+            keys: self.keys,
+            network_settings: self.network_settings,
+            protocol_params: self.protocol_params,
+            tx_builder_backend: self.tx_builder_backend,
+            balancing: self.balancing,
+            clock: self.clock,
             _datum: Default::default(),
             _redeemer: Default::default(),
         }
     }
 
+    /// Use fetched protocol parameters for at most `ttl_secs`, even if their epoch hasn't
+    /// ended
+    pub fn with_protocol_params_ttl_secs(mut self, ttl_secs: i64) -> Self {
+        self.protocol_params = ProtocolParamsCache::new(ttl_secs);
+        self
+    }
+
+    /// Protocol parameters used to build transactions, fetched from the ledger unless
+    /// they're cached for the current epoch
+    pub async fn protocol_parameters(&self) -> Result<ProtocolParameters> {
+        let now = crate::runtime::unix_time_secs().map_err(CMLLCError::LedgerError)?;
+        match self.protocol_params.get(now) {
+            Some(params) => Ok(params),
+            None => self.fetch_protocol_parameters(now).await,
+        }
+    }
+
+    /// Fetch the protocol parameters from the ledger again, e.g. after a parameter
+    /// update that the cache doesn't know about yet
+    pub async fn refresh_protocol_parameters(&self) -> Result<ProtocolParameters> {
+        self.protocol_params.clear();
+        let now = crate::runtime::unix_time_secs().map_err(CMLLCError::LedgerError)?;
+        self.fetch_protocol_parameters(now).await
+    }
+
+    async fn fetch_protocol_parameters(&self, now: i64) -> Result<ProtocolParameters> {
+        let fetched = self.ledger.epoch_parameters().await?;
+        Ok(self.protocol_params.store(&fetched, now))
+    }
+
+    fn pallas_network(&self) -> CMLNetwork {
+        match self.network_settings.network() {
+            0 => CMLNetwork::Testnet,
+            1 => CMLNetwork::Mainnet,
+            other => CMLNetwork::Other(other),
+        }
+    }
+
+    /// Balance `tx` against the signer's UTxOs without signing or submitting it
+    ///
+    /// Useful for watch-only wallets, whose transactions are signed elsewhere.
+    pub async fn build_unsigned_tx<
+        Datum: PlutusDataInterop + Debug + Clone,
+        Redeemer: PlutusDataInterop,
+    >(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<CMLTransaction> {
+        match self.tx_builder_backend {
+            TxBuilderBackend::Cml => {
+                let signed_tx_builder = self.build_tx(&tx, &[]).await?;
+                Ok(signed_tx_builder.build_unchecked())
+            }
+            #[cfg(feature = "pallas-builder")]
+            TxBuilderBackend::Pallas => self.build_pallas_tx(&tx).await,
+        }
+    }
+
+    /// Issue `tx` signed by the signer and all of `extra_signers`, e.g. a policy admin key
+    ///
+    /// The extra signers are added as required signers, so scripts can check for their
+    /// signatures. Only supported by the CML backend.
+    pub async fn issue_with_extra_signers<
+        Datum: PlutusDataInterop + Debug + Clone,
+        Redeemer: PlutusDataInterop,
+    >(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+        extra_signers: &[&(dyn Keys + Sync)],
+    ) -> LedgerClientResult<TxId> {
+        let tx = self.build_signed_tx(&tx, extra_signers).await?;
+        self.submit_tx(&tx).await
+    }
+
+    /// Build `tx` into a [`TxProposal`] for the signer and `co_signers` to sign
+    ///
+    /// The co-signers are added as required signers, so their witnesses are accounted for
+    /// in the fee. The signer still has to [`TxProposal::sign`] it like everyone else.
+    /// Always builds with the CML backend.
+    pub async fn propose_tx<
+        Datum: PlutusDataInterop + Debug + Clone,
+        Redeemer: PlutusDataInterop,
+    >(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+        co_signers: &[Ed25519KeyHash],
+    ) -> LedgerClientResult<TxProposal> {
+        let signed_tx_builder = self.build_tx(&tx, co_signers).await?;
+        let my_key_hash = self
+            .keys
+            .payment_public_key()
+            .await
+            .map_err(as_failed_to_issue_tx)?
+            .hash();
+        let mut signers = vec![my_key_hash];
+        signers.extend(co_signers.iter().cloned());
+        Ok(TxProposal::new(
+            &signed_tx_builder.build_unchecked(),
+            &signers,
+        ))
+    }
+
+    /// Submit a [`TxProposal`] once all of its signers have signed it
+    pub async fn submit_proposal(
+        &self,
+        proposal: &TxProposal,
+    ) -> LedgerClientResult<TxId> {
+        let tx = proposal.signed_tx().map_err(as_failed_to_issue_tx)?;
+        self.submit_tx(&tx).await
+    }
+
+    async fn build_signed_tx<
+        Datum: PlutusDataInterop + Debug + Clone,
+        Redeemer: PlutusDataInterop,
+    >(
+        &self,
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
+        extra_signers: &[&(dyn Keys + Sync)],
+    ) -> LedgerClientResult<CMLTransaction> {
+        match self.tx_builder_backend {
+            TxBuilderBackend::Cml => {
+                let required_signers = required_signers(extra_signers).await?;
+                let mut signed_tx_builder = self.build_tx(tx, &required_signers).await?;
+                sign_tx(&mut signed_tx_builder, &self.keys, extra_signers).await
+            }
+            #[cfg(feature = "pallas-builder")]
+            TxBuilderBackend::Pallas => {
+                if !extra_signers.is_empty() {
+                    return Err(as_failed_to_issue_tx(
+                        pallas_builder::PallasBuilderError::UnsupportedExtraSigners,
+                    ))
+                }
+                let unsigned_tx = self.build_pallas_tx(tx).await?;
+                pallas_builder::sign_tx(unsigned_tx, &self.keys).await
+            }
+        }
+    }
+
+    #[cfg(feature = "pallas-builder")]
+    async fn build_pallas_tx<
+        Datum: PlutusDataInterop + Debug + Clone,
+        Redeemer: PlutusDataInterop,
+    >(
+        &self,
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<CMLTransaction> {
+        tx.check_network(self.pallas_network())
+            .map_err(LedgerClientError::WrongNetwork)?;
+        self.network_settings
+            .era()
+            .check_tx(tx)
+            .map_err(as_failed_to_issue_tx)?;
+        let my_address = self
+            .keys
+            .base_addr()
+            .await
+            .map_err(as_failed_to_issue_tx)?
+            .to_address();
+        let my_utxos = self.wallet_utxos_for_tx(&my_address, tx).await?;
+        let my_address = my_address
+            .to_bech32(None)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))
+            .map_err(as_failed_to_issue_tx)?;
+        let params = self
+            .protocol_parameters()
+            .await
+            .map_err(as_failed_to_issue_tx)?;
+        pallas_builder::build_tx(
+            &self.ledger,
+            tx,
+            &my_utxos,
+            &my_address,
+            &params,
+            &self.network_settings,
+        )
+        .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn build_tx<
+        Datum: PlutusDataInterop + Debug + Clone,
+        Redeemer: PlutusDataInterop,
+    >(
+        &self,
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
+        required_signers: &[Ed25519KeyHash],
+    ) -> LedgerClientResult<SignedTxBuilder> {
+        tx.check_network(self.pallas_network())
+            .map_err(LedgerClientError::WrongNetwork)?;
+        self.network_settings
+            .era()
+            .check_tx(tx)
+            .map_err(as_failed_to_issue_tx)?;
+        let my_address = self
+            .keys
+            .base_addr()
+            .await
+            .map_err(as_failed_to_issue_tx)?
+            .to_address();
+        let my_utxos = self.wallet_utxos_for_tx(&my_address, tx).await?;
+
+        match tx.script_version {
+            TransactionVersion::V1 => {
+                self.build_v1_tx(tx, my_utxos, my_address, required_signers)
+                    .await
+            }
+            TransactionVersion::V2 => {
+                self.build_v2_tx(tx, my_utxos, my_address, required_signers)
+                    .await
+            }
+        }
+    }
+
+    // Pages through the wallet only until it covers `tx` and holds a collateral UTxO, so
+    // input selection doesn't load every UTxO of a busy wallet
+    async fn wallet_utxos_for_tx<Datum, Redeemer>(
+        &self,
+        my_address: &CMLAddress,
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<Vec<UTxO>> {
+        let (needed, mut available) = wallet_spend_estimate(tx)?;
+        let mut has_collateral = false;
+        let mut utxos = Vec::new();
+        for page in 0.. {
+            let page_utxos = self
+                .ledger
+                .get_utxo_page_for_addr(my_address, page)
+                .await
+                .map_err(as_failed_to_issue_tx)?;
+            let last_page = page_utxos.len() < UTXO_PAGE_SIZE;
+            for utxo in page_utxos {
+                available.add_values(&Values::from(utxo.amount()));
+                has_collateral |= can_be_collateral(&utxo);
+                utxos.push(utxo);
+            }
+            if last_page || (has_collateral && available.contains(&needed)) {
+                break
+            }
+        }
+        Ok(utxos)
+    }
+
+    /// Get the payment verification key of the signer
+    pub async fn signer_payment_public_key(&self) -> Result<PublicKey> {
+        self.keys.payment_public_key().await
+    }
+
+    /// Get the DRep id of the signer
+    pub async fn signer_drep_id(&self) -> Result<DRepId> {
+        let drep_key = self.keys.drep_key().await?;
+        Ok(DRepId::from_public_key(&drep_key.to_public()))
+    }
+
+    /// Witness the DRep certificates and votes of the transaction with body hash `tx_hash`
+    ///
+    /// CML can't build Conway transactions yet, so the body is built and hashed elsewhere,
+    /// e.g. from [`governance::GovernanceCertificate::to_cbor`] and
+    /// [`governance::VotingProcedures::to_cbor`], and the signed transaction is submitted
+    /// with [`CMLLedgerCLient::submit_conway_tx`].
+    pub async fn drep_witness(&self, tx_hash: &TransactionHash) -> Result<Vkeywitness> {
+        self.network_settings
+            .era()
+            .check(EraFeature::GovernanceCertificates)?;
+        self.keys.drep_witness(tx_hash).await
+    }
+
+    /// Submit a signed Conway transaction built outside of CML, from its CBOR
+    pub async fn submit_conway_tx(&self, signed_tx_cbor: &[u8]) -> Result<TxId> {
+        self.network_settings
+            .era()
+            .check(EraFeature::GovernanceCertificates)?;
+        let tx_id = self.ledger.submit_transaction_cbor(signed_tx_cbor).await?;
+        tracing::info!(tx_id = tx_id.as_str(), "Submitted Conway transaction");
+        Ok(TxId::new(&tx_id))
+    }
+
+    /// Fetch a submitted transaction from the ledger and decode it
+    pub async fn inspect_tx(&self, tx_id: &TxId) -> Result<InspectedTx<D, R>> {
+        let cbor = self.ledger.get_transaction_cbor(&tx_id.as_str()).await?;
+        tx_inspect::inspect_tx(&cbor)
+    }
+
     async fn add_outputs_for_tx<
         Datum: PlutusDataInterop + Debug,
         Redeemer: PlutusDataInterop,
@@ -322,25 +945,38 @@ where
         tx: &UnbuiltTransaction<Datum, Redeemer>,
     ) -> LedgerClientResult<()> {
         for unbuilt_output in tx.unbuilt_outputs().iter() {
-            let cml_values: CMLValue = unbuilt_output
-                .values()
-                .to_owned()
-                .try_into()
-                .map_err(as_failed_to_issue_tx)?;
+            let mut values = unbuilt_output.values().to_owned();
+            if values.get(&PolicyId::Lovelace).is_none() {
+                // TODO: This is broken :((( I got an error message complaining about 1120600
+                //   and wanting 1155080. Please look into
+                values.add_one_value(&PolicyId::Lovelace, DEFAULT_OUTPUT_LOVELACE);
+            }
+            let cml_values =
+                CMLValue::try_from(&values).map_err(as_failed_to_issue_tx)?;
             let recipient = unbuilt_output.owner();
             let recp_addr = addr_from_bech_32(&recipient.to_string())
                 .map_err(as_failed_to_issue_tx)?;
             let mut output = TransactionOutput::new(&recp_addr, &cml_values);
-            let res = if let UnbuiltOutput::Validator { datum, .. } = unbuilt_output {
-                let data = datum.to_plutus_data();
-                let data_hash = hash_plutus_data(&data);
-                let cml_datum = CMLDatum::new_data_hash(&data_hash);
-                output.set_datum(&cml_datum);
-                let mut res = SingleOutputBuilderResult::new(&output);
-                res.set_communication_datum(&data);
-                res
-            } else {
-                SingleOutputBuilderResult::new(&output)
+            let res = match unbuilt_output {
+                UnbuiltOutput::Validator {
+                    datum,
+                    datum_attachment: DatumAttachment::Inline,
+                    ..
+                } if matches!(tx.script_version, TransactionVersion::V2) => {
+                    output.set_datum(&CMLDatum::new_data(&datum.to_plutus_data()));
+                    SingleOutputBuilderResult::new(&output)
+                }
+                // Plutus V1 scripts can only read datums from the witnesses
+                UnbuiltOutput::Validator { datum, .. } => {
+                    let data = datum.to_plutus_data();
+                    let data_hash = hash_plutus_data(&data);
+                    let cml_datum = CMLDatum::new_data_hash(&data_hash);
+                    output.set_datum(&cml_datum);
+                    let mut res = SingleOutputBuilderResult::new(&output);
+                    res.set_communication_datum(&data);
+                    res
+                }
+                UnbuiltOutput::Wallet { .. } => SingleOutputBuilderResult::new(&output),
             };
             tx_builder
                 .add_output(&res)
@@ -369,7 +1005,7 @@ where
     ) -> LedgerClientResult<InputBuilderResult> {
         let tx_hash = input_tx_hash(input).await?;
         let cml_script = cml_v1_script_from_nau_script(script).await?;
-        let partial_witness = partial_script_witness(&cml_script, redeemer).await;
+        let partial_witness = partial_script_witness(&cml_script, redeemer, false).await;
         let cml_script_address = self.cml_script_address(&cml_script).await;
         let required_signers = RequiredSigners::new();
 
@@ -402,10 +1038,13 @@ where
         input: &Output<Datum>,
         redeemer: &Redeemer,
         script: &(dyn Validator<Datum, Redeemer> + '_),
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
     ) -> LedgerClientResult<InputBuilderResult> {
         let tx_hash = input_tx_hash(input).await?;
         let cml_script = cml_v2_script_from_nau_script(script).await?;
-        let partial_witness = partial_script_witness(&cml_script, redeemer).await;
+        let by_reference = has_reference_script(tx, &cml_script);
+        let partial_witness =
+            partial_script_witness(&cml_script, redeemer, by_reference).await;
         let cml_script_address = self.cml_script_address(&cml_script).await;
         let required_signers = RequiredSigners::new();
 
@@ -418,15 +1057,24 @@ where
             .clone()
             .try_into()
             .map_err(as_failed_to_issue_tx)?;
-        let utxo_info = TransactionOutput::new(&cml_script_address, &value);
-        let input_builder = SingleInputBuilder::new(&script_input, &utxo_info);
+        let mut utxo_info = TransactionOutput::new(&cml_script_address, &value);
         let maybe_data: Option<Datum> = input.datum().to_owned().into();
         let data = maybe_data.ok_or(LedgerClientError::NoDatumOnScriptInput)?;
         let datum = data.to_plutus_data();
-        let cml_input = input_builder
-            .plutus_script(&partial_witness, &required_signers, &datum)
-            .map_err(|e| CMLLCError::JsError(e.to_string()))
-            .map_err(as_failed_to_issue_tx)?;
+        // Datums of hashed inputs go in the witnesses, inline ones are already on chain
+        let cml_input = if input.datum_hash().is_some() {
+            SingleInputBuilder::new(&script_input, &utxo_info).plutus_script(
+                &partial_witness,
+                &required_signers,
+                &datum,
+            )
+        } else {
+            utxo_info.set_datum(&CMLDatum::new_data(&datum));
+            SingleInputBuilder::new(&script_input, &utxo_info)
+                .plutus_script_inline_datum(&partial_witness, &required_signers)
+        }
+        .map_err(|e| CMLLCError::JsError(e.to_string()))
+        .map_err(as_failed_to_issue_tx)?;
         Ok(cml_input)
     }
 
@@ -459,9 +1107,10 @@ where
         input: &Output<Datum>,
         redeemer: &Redeemer,
         script: &(dyn Validator<Datum, Redeemer> + '_),
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
     ) -> LedgerClientResult<()> {
         let cml_input = self
-            .build_v2_cml_script_input(input, redeemer, script)
+            .build_v2_cml_script_input(input, redeemer, script, tx)
             .await?;
         tx_builder
             .add_input(&cml_input)
@@ -481,7 +1130,7 @@ where
         for (amount, asset_name, redeemer, policy) in tx.minting.iter() {
             let script = cml_v1_script_from_nau_policy(policy.deref()).await?;
             let mint_builder_res = self
-                .build_mint_res(*amount, asset_name, redeemer, script)
+                .build_mint_res(*amount, asset_name, redeemer, script, false)
                 .await?;
             tx_builder.add_mint(&mint_builder_res);
         }
@@ -498,8 +1147,9 @@ where
     ) -> LedgerClientResult<()> {
         for (amount, asset_name, redeemer, policy) in tx.minting.iter() {
             let script = cml_v2_script_from_nau_policy(policy.deref()).await?;
+            let by_reference = has_reference_script(tx, &script);
             let mint_builder_res = self
-                .build_mint_res(*amount, asset_name, redeemer, script)
+                .build_mint_res(*amount, asset_name, redeemer, script, by_reference)
                 .await?;
             tx_builder.add_mint(&mint_builder_res);
         }
@@ -508,26 +1158,26 @@ where
 
     async fn build_mint_res<Redeemer: PlutusDataInterop>(
         &self,
-        amount: u64,
-        asset_name: &Option<String>,
+        amount: i64,
+        asset_name: &Option<Vec<u8>>,
         redeemer: &Redeemer,
         script: PlutusScript,
+        by_reference: bool,
     ) -> LedgerClientResult<MintBuilderResult> {
-        let inner_key = if let Some(name) = asset_name {
-            name.as_bytes().to_vec()
-        } else {
-            Vec::new()
-        };
+        let inner_key = asset_name.clone().unwrap_or_default();
         let key = AssetName::new(inner_key)
             .map_err(|e| CMLLCError::JsError(e.to_string()))
             .map_err(as_failed_to_issue_tx)?;
-        let big_num = BigNum::from(amount);
-        let value = Int::new(&big_num);
+        let big_num = BigNum::from(amount.unsigned_abs());
+        let value = if amount < 0 {
+            Int::new_negative(&big_num)
+        } else {
+            Int::new(&big_num)
+        };
         let mint_assets = MintAssets::new_from_entry(&key, value);
         let mint_builder = SingleMintBuilder::new(&mint_assets);
-        let script_witness = PlutusScriptWitness::from_script(script);
-        let redeemer = redeemer.to_plutus_data();
-        let partial_witness = PartialPlutusWitness::new(&script_witness, &redeemer);
+        let partial_witness =
+            partial_script_witness(&script, redeemer, by_reference).await;
         let required_signers = RequiredSigners::new();
         let res = mint_builder.plutus_script(&partial_witness, &required_signers);
         Ok(res)
@@ -557,30 +1207,34 @@ where
         tx: &UnbuiltTransaction<Datum, Redeemer>,
     ) -> LedgerClientResult<()> {
         for (input, redeemer, script) in tx.script_inputs() {
-            self.add_v2_script_input(tx_builder, input, redeemer, script.deref())
+            self.add_v2_script_input(tx_builder, input, redeemer, script.deref(), tx)
                 .await?
         }
         Ok(())
     }
 
+    #[tracing::instrument(skip_all)]
+    // Sets the evaluated execution units and returns an upper bound on the fee
     async fn update_ex_units(
         &self,
         tx_builder: &mut TransactionBuilder,
-        my_address: &CMLAddress,
-    ) -> LedgerClientResult<()> {
-        let algo = ChangeSelectionAlgo::Default;
+        change_address: &CMLAddress,
+        params: &ProtocolParameters,
+    ) -> LedgerClientResult<u64> {
+        let algo = self.balancing.change_selection();
         let tx_redeemer_builder = tx_builder
-            .build_for_evaluation(algo, my_address)
+            .build_for_evaluation(algo, change_address)
             .map_err(|e| CMLLCError::JsError(e.to_string()))
             .map_err(as_failed_to_issue_tx)?;
         let transaction = tx_redeemer_builder.draft_tx();
-        println!("{}", &transaction.to_json().unwrap());
+        tracing::debug!(tx = ?transaction.to_json().ok(), "Evaluating draft transaction");
         let res = self
             .ledger
             .calculate_ex_units(&transaction)
             .await
             .map_err(|e| CMLLCError::JsError(e.to_string()))
             .map_err(as_failed_to_issue_tx)?;
+        let mut ex_units = Vec::new();
         for (index, spend) in res.iter() {
             let tag = match spend.execution_type {
                 ExecutionType::Spend => RedeemerTag::new_spend(),
@@ -588,83 +1242,210 @@ where
                 ExecutionType::Withdrawal => RedeemerTag::new_reward(),
                 ExecutionType::Certificate => RedeemerTag::new_cert(),
             };
+            tracing::debug!(
+                index,
+                execution_type = ?spend.execution_type,
+                memory = spend.memory(),
+                steps = spend.steps(),
+                "Evaluated script execution units"
+            );
             tx_builder.set_exunits(
                 &RedeemerWitnessKey::new(&tag, &BigNum::from(*index)),
                 &ExUnits::new(&spend.memory().into(), &spend.steps().into()),
             );
+            ex_units.push((spend.memory(), spend.steps()));
         }
-        Ok(())
+        let draft_size = transaction.to_bytes().len() as u64;
+        Ok(estimate_fee(params, draft_size, &ex_units))
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(fee = tracing::field::Empty, tx_id = tracing::field::Empty)
+    )]
     async fn submit_tx(&self, tx: &CMLTransaction) -> LedgerClientResult<TxId> {
+        let fee: u64 = tx.body().fee().into();
+        tracing::Span::current().record("fee", fee);
         let submit_res = self
             .ledger
             .submit_transaction(tx)
             .await
             .map_err(as_failed_to_issue_tx)?;
+        tracing::Span::current().record("tx_id", submit_res.as_str());
+        tracing::info!("Submitted transaction");
         Ok(TxId::new(&submit_res))
     }
 
-    async fn issue_v1_tx<
+    async fn build_v1_tx<
         Datum: PlutusDataInterop + Debug + Clone,
         Redeemer: PlutusDataInterop,
     >(
         &self,
-        tx: UnbuiltTransaction<Datum, Redeemer>,
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
         my_utxos: Vec<UTxO>,
         my_address: CMLAddress,
-        priv_key: PrivateKey,
-    ) -> LedgerClientResult<TxId> {
-        let mut tx_builder = vasil_v1_tx_builder()?;
-        self.add_v1_script_inputs(&mut tx_builder, &tx).await?;
-        self.add_tokens_for_v1_minting(&mut tx_builder, &tx).await?;
+        required_signers: &[Ed25519KeyHash],
+    ) -> LedgerClientResult<SignedTxBuilder> {
+        let params = self
+            .protocol_parameters()
+            .await
+            .map_err(as_failed_to_issue_tx)?;
+        let mut tx_builder = vasil_v1_tx_builder(&params)?;
+        add_required_signers(&mut tx_builder, required_signers);
+        self.add_v1_script_inputs(&mut tx_builder, tx).await?;
+        self.add_tokens_for_v1_minting(&mut tx_builder, tx).await?;
+        let available =
+            self.preselect_inputs(&mut tx_builder, tx, &my_address, &my_utxos)?;
         specify_utxos_available_for_input_selection(
             &mut tx_builder,
             &my_address,
-            &my_utxos,
+            &available,
         )
         .await?;
-        self.add_outputs_for_tx(&mut tx_builder, &tx).await?;
-        add_collateral(&mut tx_builder, &my_address, &my_utxos).await?;
-        select_inputs_from_utxos(&mut tx_builder).await?;
-        self.update_ex_units(&mut tx_builder, &my_address).await?;
-        let mut signed_tx_builder =
-            build_tx_for_signing(&mut tx_builder, &my_address).await?;
-        let tx = sign_tx(&mut signed_tx_builder, &priv_key).await?;
-        let tx_id = self.submit_tx(&tx).await?;
-        Ok(tx_id)
-    }
-
-    async fn issue_v2_tx<
+        self.add_outputs_for_tx(&mut tx_builder, tx).await?;
+        add_metadata(&mut tx_builder, &tx.metadata).await?;
+        let collateral_amount =
+            add_collateral(&mut tx_builder, &my_address, &my_utxos).await?;
+        select_inputs_from_utxos(&mut tx_builder, self.balancing.coin_selection())
+            .await?;
+        let change_address = self.balancing.change_address(&my_address);
+        let fee = self
+            .update_ex_units(&mut tx_builder, &change_address, &params)
+            .await?;
+        set_total_collateral(
+            &mut tx_builder,
+            &my_address,
+            collateral_amount,
+            fee,
+            &params,
+            self.network_settings.era(),
+        )?;
+        build_tx_for_signing(
+            &mut tx_builder,
+            &change_address,
+            self.balancing.change_selection(),
+        )
+        .await
+    }
+
+    async fn build_v2_tx<
         Datum: PlutusDataInterop + Debug + Clone,
         Redeemer: PlutusDataInterop,
     >(
         &self,
-        tx: UnbuiltTransaction<Datum, Redeemer>,
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
         my_utxos: Vec<UTxO>,
         my_address: CMLAddress,
-        priv_key: PrivateKey,
-    ) -> LedgerClientResult<TxId> {
-        let mut tx_builder = vasil_v2_tx_builder()?;
-        self.set_valid_range(&mut tx_builder, &tx).await?;
-        self.add_v2_script_inputs(&mut tx_builder, &tx).await?;
-        self.add_tokens_for_v2_minting(&mut tx_builder, &tx).await?;
+        required_signers: &[Ed25519KeyHash],
+    ) -> LedgerClientResult<SignedTxBuilder> {
+        let params = self
+            .protocol_parameters()
+            .await
+            .map_err(as_failed_to_issue_tx)?;
+        let mut tx_builder = vasil_v2_tx_builder(&params)?;
+        add_required_signers(&mut tx_builder, required_signers);
+        self.set_valid_range(&mut tx_builder, tx).await?;
+        self.add_reference_inputs(&mut tx_builder, tx).await?;
+        self.add_v2_script_inputs(&mut tx_builder, tx).await?;
+        self.add_tokens_for_v2_minting(&mut tx_builder, tx).await?;
+        let available =
+            self.preselect_inputs(&mut tx_builder, tx, &my_address, &my_utxos)?;
         specify_utxos_available_for_input_selection(
             &mut tx_builder,
             &my_address,
-            &my_utxos,
+            &available,
         )
         .await?;
-        self.add_specific_inputs(&mut tx_builder, &tx).await?;
-        self.add_outputs_for_tx(&mut tx_builder, &tx).await?;
-        add_collateral(&mut tx_builder, &my_address, &my_utxos).await?;
-        select_inputs_from_utxos(&mut tx_builder).await?;
-        self.update_ex_units(&mut tx_builder, &my_address).await?;
-        let mut signed_tx_builder =
-            build_tx_for_signing(&mut tx_builder, &my_address).await?;
-        let tx = sign_tx(&mut signed_tx_builder, &priv_key).await?;
-        let tx_id = self.submit_tx(&tx).await?;
-        Ok(tx_id)
+        self.add_specific_inputs(&mut tx_builder, tx).await?;
+        self.add_outputs_for_tx(&mut tx_builder, tx).await?;
+        add_metadata(&mut tx_builder, &tx.metadata).await?;
+        let collateral_amount =
+            add_collateral(&mut tx_builder, &my_address, &my_utxos).await?;
+        select_inputs_from_utxos(&mut tx_builder, self.balancing.coin_selection())
+            .await?;
+        let change_address = self.balancing.change_address(&my_address);
+        let fee = self
+            .update_ex_units(&mut tx_builder, &change_address, &params)
+            .await?;
+        set_total_collateral(
+            &mut tx_builder,
+            &my_address,
+            collateral_amount,
+            fee,
+            &params,
+            self.network_settings.era(),
+        )?;
+        build_tx_for_signing(
+            &mut tx_builder,
+            &change_address,
+            self.balancing.change_selection(),
+        )
+        .await
+    }
+
+    // Spend the wallet UTxOs the balancing strategy asks for, giving the rest for coin
+    // selection
+    fn preselect_inputs<'a, Datum, Redeemer>(
+        &self,
+        tx_builder: &mut TransactionBuilder,
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
+        my_address: &CMLAddress,
+        my_utxos: &'a [UTxO],
+    ) -> LedgerClientResult<Vec<&'a UTxO>> {
+        let lovelace =
+            |values: &Values| values.get(&PolicyId::Lovelace).unwrap_or_default();
+        let provided_lovelace = tx
+            .script_inputs()
+            .iter()
+            .map(|(input, _, _)| lovelace(input.values()))
+            .chain(
+                tx.specific_wallet_inputs
+                    .iter()
+                    .map(|input| lovelace(input.values())),
+            )
+            .sum();
+        let required_lovelace = tx
+            .unbuilt_outputs()
+            .iter()
+            .map(|output| {
+                output
+                    .values()
+                    .get(&PolicyId::Lovelace)
+                    .unwrap_or(DEFAULT_OUTPUT_LOVELACE)
+            })
+            .sum();
+        let context = BalancingContext::new(
+            provided_lovelace,
+            required_lovelace,
+            !tx.script_inputs().is_empty(),
+        );
+        // Specific wallet inputs are added on their own, so they can't be selected again
+        let candidates: Vec<&UTxO> = my_utxos
+            .iter()
+            .filter(|utxo| {
+                !tx.specific_wallet_inputs.iter().any(|input| {
+                    input.id().tx_hash() == utxo.tx_hash().to_bytes()
+                        && input.id().index() == u64::from(utxo.output_index())
+                })
+            })
+            .collect();
+        let preselected = self.balancing.preselect_inputs(&context, &candidates);
+        for utxo in preselected.iter() {
+            let input = input_from_utxo(my_address, utxo)?;
+            tx_builder
+                .add_input(&input)
+                .map_err(|e| CMLLCError::JsError(e.to_string()))
+                .map_err(as_failed_to_issue_tx)?;
+        }
+        let available = candidates
+            .into_iter()
+            .filter(|utxo| {
+                !preselected
+                    .iter()
+                    .any(|selected| std::ptr::eq(*selected, *utxo))
+            })
+            .collect();
+        Ok(available)
     }
 
     async fn set_valid_range<
@@ -730,6 +1511,84 @@ where
         }
         Ok(())
     }
+
+    async fn add_reference_inputs<
+        Datum: PlutusDataInterop,
+        Redeemer: PlutusDataInterop,
+    >(
+        &self,
+        tx_builder: &mut TransactionBuilder,
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<()> {
+        for reference_input in &tx.reference_inputs {
+            let transaction_id = input_tx_hash(reference_input).await?;
+            let index = reference_input.id().index().into();
+            let input = TransactionInput::new(&transaction_id, &index);
+            let address = addr_from_bech_32(&reference_input.owner().to_string())
+                .map_err(as_failed_to_issue_tx)?;
+            let amount = reference_input
+                .values()
+                .clone()
+                .try_into()
+                .map_err(as_failed_to_issue_tx)?;
+            let mut utxo_info = TransactionOutput::new(&address, &amount);
+            if let Some(reference_script) = reference_input.reference_script() {
+                utxo_info.set_script_ref(&cml_script_ref(reference_script)?);
+            }
+            // Scripts reading the input see its datum, so it has to match the chain's
+            let data = match reference_input.datum().value() {
+                Some(DatumKind::Typed(datum)) => Some(datum.to_plutus_data()),
+                Some(DatumKind::UnTyped(data)) => Some(data.to_owned().into()),
+                Some(DatumKind::None) | None => None,
+            };
+            if let Some(data) = data {
+                let cml_datum = match reference_input.datum_hash() {
+                    Some(_) => CMLDatum::new_data_hash(&hash_plutus_data(&data)),
+                    None => CMLDatum::new_data(&data),
+                };
+                utxo_info.set_datum(&cml_datum);
+            }
+            let utxo = TransactionUnspentOutput::new(&input, &utxo_info);
+            tx_builder.add_reference_input(&utxo);
+        }
+        Ok(())
+    }
+}
+
+// Whether a reference input of `tx` holds `cml_script`, so it doesn't need attaching
+fn has_reference_script<Datum, Redeemer>(
+    tx: &UnbuiltTransaction<Datum, Redeemer>,
+    cml_script: &PlutusScript,
+) -> bool {
+    tx.reference_script_input(&cml_script.hash().to_bytes())
+        .is_some()
+}
+
+// What `tx` does, read back from its CBOR
+fn preview_of<Datum, Redeemer>(
+    tx: &CMLTransaction,
+) -> LedgerClientResult<TxPreview<Datum>>
+where
+    Datum: PlutusDataInterop + Clone,
+    Redeemer: PlutusDataInterop,
+{
+    let inspected: InspectedTx<Datum, Redeemer> =
+        tx_inspect::inspect_tx(&tx.to_bytes()).map_err(as_failed_to_issue_tx)?;
+    let ex_units = inspected
+        .redeemers()
+        .iter()
+        .map(|redeemer| {
+            ScriptExecutionCost::new(redeemer.mem() as i64, redeemer.steps() as i64)
+        })
+        .collect();
+    let preview = TxPreview::new(
+        inspected.inputs().to_vec(),
+        inspected.outputs().to_vec(),
+        inspected.fee(),
+        inspected.mint().to_vec(),
+        ex_units,
+    );
+    Ok(preview)
 }
 
 #[async_trait]
@@ -804,45 +1663,149 @@ where
         Ok(utxos)
     }
 
+    // Fetches the next page of UTxOs once the last is consumed, converting each on the way
+    fn stream_outputs_at_address<'a>(
+        &'a self,
+        address: &'a Address,
+    ) -> BoxStream<'a, LedgerClientResult<Output<Datum>>>
+    where
+        Datum: Send + 'a,
+    {
+        let pages = async move {
+            let addr_string = address
+                .to_bech32()
+                .map_err(|e| LedgerClientError::BadAddress(Box::new(e)))?;
+            let cml_addr = addr_from_bech_32(&addr_string)
+                .map_err(as_failed_to_retrieve_by_address(address))?;
+            let pages = stream::try_unfold(Some(0), move |page| {
+                let cml_addr = cml_addr.clone();
+                async move {
+                    let page = match page {
+                        Some(page) => page,
+                        None => return Ok(None),
+                    };
+                    let utxos = self
+                        .ledger
+                        .get_utxo_page_for_addr(&cml_addr, page)
+                        .await
+                        .map_err(as_failed_to_retrieve_by_address(address))?;
+                    let next_page = (utxos.len() >= UTXO_PAGE_SIZE).then_some(page + 1);
+                    let outputs = utxos
+                        .into_iter()
+                        .map(move |utxo| utxo_to_nau_utxo(&utxo, address));
+                    Ok::<_, LedgerClientError>(Some((stream::iter(outputs), next_page)))
+                }
+            });
+            Ok::<_, LedgerClientError>(pages.try_flatten())
+        };
+        stream::once(pages).try_flatten().boxed()
+    }
+
+    // Checks the raw datums first, so only matching UTxOs are converted to `Datum`
+    async fn outputs_at_address_matching<P>(
+        &self,
+        address: &Address,
+        predicate: P,
+    ) -> LedgerClientResult<Vec<Output<Datum>>>
+    where
+        Datum: Clone + Into<NauPlutusData> + Send,
+        P: Fn(&NauPlutusData) -> bool + Send + Sync,
+    {
+        let addr_string = address.to_bech32().expect("Already Validated");
+        let cml_addr = addr_from_bech_32(&addr_string)
+            .map_err(as_failed_to_retrieve_by_address(address))?;
+        let bf_utxos = self
+            .ledger
+            .get_all_utxos_for_addr(&cml_addr)
+            .await
+            .map_err(as_failed_to_retrieve_by_address(address))?;
+
+        bf_utxos
+            .iter()
+            .filter(|utxo| {
+                utxo.datum()
+                    .as_ref()
+                    .map_or(false, |data| predicate(&data.clone().into()))
+            })
+            .map(|utxo| utxo_to_nau_utxo(utxo, address))
+            .collect()
+    }
+
     async fn issue(
         &self,
         tx: UnbuiltTransaction<Datum, Redeemer>,
     ) -> LedgerClientResult<TxId> {
-        let my_address = self
-            .keys
-            .base_addr()
-            .await
-            .map_err(as_failed_to_issue_tx)?
-            .to_address();
-        let priv_key = self
-            .keys
-            .private_key()
-            .await
-            .map_err(as_failed_to_issue_tx)?;
-
-        let my_utxos = self
-            .ledger
-            .get_all_utxos_for_addr(&my_address)
-            .await
-            .map_err(as_failed_to_issue_tx)?;
+        let tx = self.build_signed_tx(&tx, &[]).await?;
+        self.submit_tx(&tx).await
+    }
 
-        match tx.script_version {
-            TransactionVersion::V1 => {
-                self.issue_v1_tx(tx, my_utxos, my_address, priv_key).await
-            }
-            TransactionVersion::V2 => {
-                self.issue_v2_tx(tx, my_utxos, my_address, priv_key).await
+    async fn issue_with_retry(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+        policy: &RetryPolicy,
+    ) -> LedgerClientResult<TxId> {
+        let tx = &tx;
+        let signed_tx = &Mutex::new(self.build_signed_tx(tx, &[]).await?);
+        let submitted = &AtomicBool::new(false);
+        let inputs_spent = &AtomicBool::new(false);
+        with_retry(policy, || async move {
+            let mut attempt = signed_tx.lock().expect("Lock poisoned").clone();
+            let tx_id =
+                TxId::new(&hex::encode(hash_transaction(&attempt.body()).to_bytes()));
+            if submitted.swap(true, Ordering::SeqCst) {
+                // Resubmitting the same inputs can't pay twice, but a failed attempt may
+                // have landed anyway, which would make the resubmission fail
+                match self.is_confirmed(&tx_id).await {
+                    Ok(true) => return Ok(tx_id),
+                    // Only once it's known not to have landed can its inputs be replaced
+                    Ok(false) if inputs_spent.load(Ordering::SeqCst) => {
+                        attempt = self.build_signed_tx(tx, &[]).await?;
+                        *signed_tx.lock().expect("Lock poisoned") = attempt.clone();
+                    }
+                    _ => {}
+                }
             }
-        }
+            let res = self.submit_tx(&attempt).await;
+            let spent = matches!(&res, Err(error) if is_spent_inputs_error(error));
+            inputs_spent.store(spent, Ordering::SeqCst);
+            res
+        })
+        .await
+    }
+
+    async fn issue_with_preview(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<(TxId, Option<TxPreview<Datum>>)> {
+        let signed_tx = self.build_signed_tx(&tx, &[]).await?;
+        let tx_id = self.submit_tx(&signed_tx).await?;
+        // The tx is already on its way, so a preview that can't be built isn't an error
+        let preview = preview_of::<Datum, Redeemer>(&signed_tx).ok();
+        Ok((tx_id, preview))
+    }
+
+    async fn preview(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<TxPreview<Datum>> {
+        let unsigned_tx = self.build_unsigned_tx(tx).await?;
+        preview_of::<Datum, Redeemer>(&unsigned_tx)
+    }
+
+    async fn is_confirmed(&self, tx_id: &TxId) -> LedgerClientResult<bool> {
+        self.ledger
+            .is_tx_confirmed(&tx_id.as_str())
+            .await
+            .map_err(|e| match e {
+                CMLLCError::Unsupported(feature) => {
+                    LedgerClientError::Unsupported(feature)
+                }
+                e => LedgerClientError::FailedToRetrieveTx(tx_id.clone(), Box::new(e)),
+            })
     }
 
     async fn network(&self) -> LedgerClientResult<CMLNetwork> {
-        let network = match self.network_settings.network() {
-            0 => CMLNetwork::Testnet,
-            1 => CMLNetwork::Mainnet,
-            _ => CMLNetwork::Other(self.network_settings.network()),
-        };
-        Ok(network)
+        Ok(self.pallas_network())
     }
 
     async fn last_block_time_secs(&self) -> LedgerClientResult<i64> {
@@ -853,12 +1816,6 @@ where
     }
 
     async fn current_time_secs(&self) -> LedgerClientResult<i64> {
-        let now = std::time::SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|e| LedgerClientError::CurrentTime(Box::new(e)))?
-            .as_secs()
-            .try_into()
-            .expect("This should never be bigger than i64 in our lifetimes :)");
-        Ok(now)
+        self.clock.now_secs().await
     }
 }
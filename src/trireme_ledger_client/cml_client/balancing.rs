@@ -0,0 +1,197 @@
+use super::UTxO;
+use cardano_multiplatform_lib::{
+    address::Address as CMLAddress,
+    builders::tx_builder::{
+        ChangeSelectionAlgo,
+        CoinSelectionStrategyCIP2,
+    },
+};
+
+/// Lovelace that [`PreselectPureAda`] keeps spare for the fee and the change output
+pub const DEFAULT_BALANCING_BUFFER: u64 = 5_000_000;
+
+/// What a [`BalancingStrategy`] knows about the transaction before any wallet inputs are
+/// selected
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BalancingContext {
+    provided_lovelace: u64,
+    required_lovelace: u64,
+    spends_scripts: bool,
+}
+
+impl BalancingContext {
+    /// Constructor for a [`BalancingContext`] where the script and specific inputs hold
+    /// `provided_lovelace` and the outputs need `required_lovelace`
+    pub fn new(
+        provided_lovelace: u64,
+        required_lovelace: u64,
+        spends_scripts: bool,
+    ) -> Self {
+        BalancingContext {
+            provided_lovelace,
+            required_lovelace,
+            spends_scripts,
+        }
+    }
+
+    /// Getter for the Lovelace in the script and specific inputs
+    pub fn provided_lovelace(&self) -> u64 {
+        self.provided_lovelace
+    }
+
+    /// Getter for the Lovelace sent to the outputs
+    pub fn required_lovelace(&self) -> u64 {
+        self.required_lovelace
+    }
+
+    /// Getter for whether the transaction spends script outputs
+    pub fn spends_scripts(&self) -> bool {
+        self.spends_scripts
+    }
+}
+
+/// How the [`CMLLedgerCLient`](super::CMLLedgerCLient) balances transactions with CML:
+/// which wallet inputs are added, and how the change is returned
+///
+/// Only applies to the CML backend.
+pub trait BalancingStrategy: Send + Sync {
+    /// Wallet UTxOs to spend before CML's coin selection runs
+    ///
+    /// `wallet_utxos` doesn't include the transaction's specific wallet inputs, which are
+    /// already spent.
+    fn preselect_inputs<'a>(
+        &self,
+        context: &BalancingContext,
+        wallet_utxos: &[&'a UTxO],
+    ) -> Vec<&'a UTxO>;
+
+    /// Coin selection for whatever the inputs don't cover yet
+    ///
+    /// Defaults to largest first, which helps atomize the wallet.
+    fn coin_selection(&self) -> CoinSelectionStrategyCIP2 {
+        CoinSelectionStrategyCIP2::LargestFirstMultiAsset
+    }
+
+    /// How CML splits the change into outputs
+    fn change_selection(&self) -> ChangeSelectionAlgo {
+        ChangeSelectionAlgo::Default
+    }
+
+    /// Address the change goes to
+    ///
+    /// Defaults to the signer's address.
+    fn change_address(&self, signer_address: &CMLAddress) -> CMLAddress {
+        signer_address.clone()
+    }
+}
+
+/// Leave all input selection to CML
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CmlBalancing;
+
+impl BalancingStrategy for CmlBalancing {
+    fn preselect_inputs<'a>(
+        &self,
+        _context: &BalancingContext,
+        _wallet_utxos: &[&'a UTxO],
+    ) -> Vec<&'a UTxO> {
+        Vec::new()
+    }
+}
+
+/// Spend extra pure-ADA wallet UTxOs when the script inputs don't hold enough Lovelace for
+/// the outputs, fee and change
+///
+/// Otherwise CML can select inputs that cover the outputs but leave too little for a change
+/// output, and fail to balance.
+#[derive(Clone, Copy, Debug)]
+pub struct PreselectPureAda {
+    buffer: u64,
+}
+
+impl Default for PreselectPureAda {
+    fn default() -> Self {
+        PreselectPureAda {
+            buffer: DEFAULT_BALANCING_BUFFER,
+        }
+    }
+}
+
+impl PreselectPureAda {
+    /// Keep `buffer` Lovelace spare for the fee and the change output
+    pub fn with_buffer(mut self, buffer: u64) -> Self {
+        self.buffer = buffer;
+        self
+    }
+}
+
+impl BalancingStrategy for PreselectPureAda {
+    fn preselect_inputs<'a>(
+        &self,
+        context: &BalancingContext,
+        wallet_utxos: &[&'a UTxO],
+    ) -> Vec<&'a UTxO> {
+        if !context.spends_scripts() {
+            return Vec::new()
+        }
+        let target = context.required_lovelace().saturating_add(self.buffer);
+        let mut provided = context.provided_lovelace();
+        let mut pure_ada: Vec<_> = wallet_utxos
+            .iter()
+            .copied()
+            .filter(|utxo| utxo.amount().multiasset().is_none())
+            .collect();
+        pure_ada.sort_by_key(|utxo| std::cmp::Reverse(lovelace(utxo)));
+        let mut selected = Vec::new();
+        for utxo in pure_ada {
+            if provided >= target {
+                break
+            }
+            provided = provided.saturating_add(lovelace(utxo));
+            selected.push(utxo);
+        }
+        selected
+    }
+}
+
+fn lovelace(utxo: &UTxO) -> u64 {
+    utxo.amount().coin().into()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use cardano_multiplatform_lib::{
+        crypto::TransactionHash,
+        ledger::common::value::Value as CMLValue,
+    };
+
+    fn utxo(id: u8, lovelace: u64) -> UTxO {
+        let tx_hash = TransactionHash::from_bytes(vec![id; 32]).unwrap();
+        UTxO::new(tx_hash, 0.into(), CMLValue::new(&lovelace.into()), None)
+    }
+
+    #[test]
+    fn preselect_inputs__tops_up_small_script_inputs_with_largest_utxos() {
+        // given
+        let strategy = PreselectPureAda::default().with_buffer(1_000_000);
+        let utxos = vec![utxo(1, 2_000_000), utxo(2, 3_000_000), utxo(3, 500_000)];
+        let wallet_utxos: Vec<_> = utxos.iter().collect();
+        let small_script_input = BalancingContext::new(1_000_000, 4_000_000, true);
+        let large_script_input = BalancingContext::new(9_000_000, 4_000_000, true);
+        let no_scripts = BalancingContext::new(0, 4_000_000, false);
+
+        // when
+        let topped_up = strategy.preselect_inputs(&small_script_input, &wallet_utxos);
+        let covered = strategy.preselect_inputs(&large_script_input, &wallet_utxos);
+        let wallet_only = strategy.preselect_inputs(&no_scripts, &wallet_utxos);
+
+        // then
+        let topped_up: Vec<_> = topped_up.into_iter().map(lovelace).collect();
+        assert_eq!(topped_up, vec![3_000_000, 2_000_000]);
+        assert!(covered.is_empty());
+        assert!(wallet_only.is_empty());
+    }
+}
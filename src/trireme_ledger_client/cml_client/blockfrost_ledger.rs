@@ -1,21 +1,20 @@
 use super::error::*;
-use crate::trireme_ledger_client::cml_client::{
-    error::CMLLCError,
-    ExecutionCost,
-    Ledger,
-    UTxO,
-};
-use async_trait::async_trait;
-use blockfrost_http_client::{
-    error::Error,
-    models::{
-        ExecutionType,
-        UTxO as BFUTxO,
-        Value as BFValue,
+use crate::{
+    ledger_client::provider_error::ProviderError,
+    output::ReferenceScript,
+    protocol_params::ProtocolParameters,
+    transaction::TransactionVersion,
+    trireme_ledger_client::cml_client::{
+        error::CMLLCError,
+        protocol_params_cache::EpochParameters,
+        ExecutionCost,
+        Ledger,
+        UTxO,
+        UTXO_PAGE_SIZE,
     },
-    BlockFrostHttp,
-    BlockFrostHttpTrait,
 };
+use async_trait::async_trait;
+use blockfrost_http_client::models::Value as BFValue;
 use cardano_multiplatform_lib::{
     address::Address as CMLAddress,
     crypto::TransactionHash,
@@ -25,6 +24,7 @@ use cardano_multiplatform_lib::{
     },
     plutus::{
         encode_json_str_to_plutus_datum,
+        PlutusData,
         PlutusDatumSchema,
     },
     AssetName,
@@ -35,6 +35,7 @@ use cardano_multiplatform_lib::{
 };
 use futures::future;
 use serde::{
+    de::DeserializeOwned,
     Deserialize,
     Serialize,
 };
@@ -46,59 +47,201 @@ use thiserror::Error;
 
 /// A Ledger implementation that uses Blockfrost as a backend
 pub struct BlockFrostLedger {
-    client: BlockFrostHttp,
+    url: String,
+    key: String,
 }
 
 impl BlockFrostLedger {
     /// Constructor for BlockFrostLedger
     pub fn new(url: &str, key: &str) -> Self {
-        let client = BlockFrostHttp::new(url, key);
-        BlockFrostLedger { client }
+        BlockFrostLedger {
+            url: url.trim_end_matches('/').to_string(),
+            key: key.to_string(),
+        }
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = format!("{}{path}", self.url);
+        let request = reqwest::Client::new().get(url);
+        self.send(&format!("GET {path}"), request).await
+    }
+
+    async fn post_cbor<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: Vec<u8>,
+    ) -> Result<T> {
+        let url = format!("{}{path}", self.url);
+        let request = reqwest::Client::new()
+            .post(url)
+            .header("Content-Type", "application/cbor")
+            .body(body);
+        self.send(&format!("POST {path}"), request).await
+    }
+
+    // Requests are sent directly, rather than through the Blockfrost client, to keep the
+    // status and body of unsuccessful responses
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: &str,
+        builder: reqwest::RequestBuilder,
+    ) -> Result<T> {
+        let res = builder
+            .header("project_id", &self.key)
+            .send()
+            .await
+            .map_err(|e| ProviderError::new(BLOCKFROST, request).with_source(e))?;
+        if !res.status().is_success() {
+            let error = ProviderError::from_response(BLOCKFROST, request, res).await;
+            return Err(error.into())
+        }
+        let value = res
+            .json()
+            .await
+            .map_err(|e| ProviderError::new(BLOCKFROST, request).with_source(e))?;
+        Ok(value)
+    }
+
+    // Pages through the UTxOs at `address`, stopping once `limit` have been fetched.
+    // The Blockfrost client drops inline datums, so the endpoint is queried directly
+    async fn address_utxos(
+        &self,
+        address: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<BFAddressUTxO>> {
+        let mut utxos = Vec::new();
+        for page in 1.. {
+            let page = self.address_utxo_page(address, page).await?;
+            let last_page = page.len() < UTXO_PAGE_SIZE;
+            utxos.extend(page);
+            if let Some(limit) = limit {
+                if utxos.len() >= limit {
+                    utxos.truncate(limit);
+                    break
+                }
+            }
+            if last_page {
+                break
+            }
+        }
+        Ok(utxos)
+    }
+
+    // Blockfrost counts pages from 1
+    async fn address_utxo_page(
+        &self,
+        address: &str,
+        page: usize,
+    ) -> Result<Vec<BFAddressUTxO>> {
+        let path =
+            format!("/addresses/{address}/utxos?count={UTXO_PAGE_SIZE}&page={page}");
+        match self.get_json(&path).await {
+            Ok(page) => Ok(page),
+            // Blockfrost answers 404 for addresses that have never been used
+            Err(CMLLCError::Provider(e)) if e.status() == Some(404) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn bfutxos_to_utxos(&self, bf_utxos: &[BFAddressUTxO]) -> Result<Vec<UTxO>> {
+        future::join_all(
+            bf_utxos
+                .iter()
+                .map(|bf_utxo| async move { self.bfutxo_to_utxo(bf_utxo).await }),
+        )
+        .await
+        .into_iter()
+        .collect()
     }
 
     /// Convert a Blockfrost representation of a UTxO to a CML representation
-    // TODO: Handle V2 outputs (with inline datums)
-    async fn bfutxo_to_utxo(&self, bf_utxo: &BFUTxO) -> Result<UTxO> {
-        let tx_hash = TransactionHash::from_hex(bf_utxo.tx_hash())
+    async fn bfutxo_to_utxo(&self, bf_utxo: &BFAddressUTxO) -> Result<UTxO> {
+        let tx_hash = TransactionHash::from_hex(&bf_utxo.tx_hash)
             .map_err(|e| CMLLCError::JsError(e.to_string()))?;
-        let output_index = bf_utxo.output_index().into();
-        let amount = cmlvalue_from_bfvalues(bf_utxo.amount())?;
-        let datum = if let Some(data_hash) = bf_utxo.data_hash() {
-            let json_datum = self
-                .client
-                .datum(data_hash)
-                .await
-                .map_err(|e| CMLLCError::LedgerError(Box::new(e)))?;
-            if let Some(inner) = json_datum.as_object() {
-                if inner.get("error").is_none() {
-                    let ser = json_datum["json_value"].to_string();
-                    let plutus_data = encode_json_str_to_plutus_datum(
-                        &ser, // TODO: Make this safer!
-                        PlutusDatumSchema::DetailedSchema,
-                    )
+        let output_index = bf_utxo.output_index.into();
+        let amount = cmlvalue_from_units(
+            bf_utxo
+                .amount
+                .iter()
+                .map(|value| (value.unit.as_str(), value.quantity.as_str())),
+        )?;
+        let mut utxo = match &bf_utxo.inline_datum {
+            // Blockfrost reports the hash of inline datums too, so check for those first
+            Some(inline_datum) => {
+                let datum = PlutusData::from_bytes(hex::decode(inline_datum)?)
                     .map_err(|e| CMLLCError::JsError(e.to_string()))?;
-                    Some(plutus_data)
-                } else {
-                    None // TODO: Add debug msg
+                UTxO::new(tx_hash, output_index, amount, Some(datum))
+            }
+            None => {
+                let datum = match &bf_utxo.data_hash {
+                    Some(data_hash) => self.resolve_datum(data_hash).await?,
+                    None => None,
+                };
+                let mut utxo = UTxO::new(tx_hash, output_index, amount, datum);
+                if let Some(data_hash) = &bf_utxo.data_hash {
+                    let hash = hex::decode(data_hash)?;
+                    utxo = utxo.with_datum_hash(hash);
                 }
-            } else {
-                None // TODO: Add debug msg
+                utxo
             }
-        } else {
-            None
         };
-
-        let utxo = UTxO::new(tx_hash, output_index, amount, datum);
+        if let Some(script_hash) = &bf_utxo.reference_script_hash {
+            if let Some(reference_script) = self.reference_script(script_hash).await? {
+                utxo = utxo.with_reference_script(reference_script);
+            }
+        }
         Ok(utxo)
     }
+
+    async fn resolve_datum(&self, data_hash: &str) -> Result<Option<PlutusData>> {
+        let path = format!("/scripts/datum/{data_hash}");
+        let datum: BFDatum = match self.get_json(&path).await {
+            Ok(datum) => datum,
+            // Blockfrost only knows datums it has seen in a transaction
+            Err(CMLLCError::Provider(e)) if e.status() == Some(404) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let plutus_data = encode_json_str_to_plutus_datum(
+            &datum.json_value.to_string(),
+            PlutusDatumSchema::DetailedSchema,
+        )
+        .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        Ok(Some(plutus_data))
+    }
+
+    // Only Plutus scripts are kept, since native scripts can't be used by reference here
+    async fn reference_script(
+        &self,
+        script_hash: &str,
+    ) -> Result<Option<ReferenceScript>> {
+        let script: BFScript = self.get_json(&format!("/scripts/{script_hash}")).await?;
+        let version = match script.script_type.as_str() {
+            "plutusV1" => TransactionVersion::V1,
+            "plutusV2" => TransactionVersion::V2,
+            _ => return Ok(None),
+        };
+        let script_cbor: BFScriptCbor = self
+            .get_json(&format!("/scripts/{script_hash}/cbor"))
+            .await?;
+        let Some(cbor) = script_cbor.cbor else {
+            return Ok(None)
+        };
+        let reference_script =
+            ReferenceScript::new(hex::decode(script_hash)?, &cbor).with_version(version);
+        Ok(Some(reference_script))
+    }
 }
 
 /// Convert a list of Blockfrost values to a CML value
 pub fn cmlvalue_from_bfvalues(values: &[BFValue]) -> Result<CMLValue> {
+    cmlvalue_from_units(values.iter().map(|value| (value.unit(), value.quantity())))
+}
+
+fn cmlvalue_from_units<'a>(
+    units: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<CMLValue> {
     let mut cml_value = CMLValue::zero();
-    for value in values.iter() {
-        let unit = value.unit();
-        let quantity = value.quantity();
+    for (unit, quantity) in units {
         let add_value = match unit {
             "lovelace" => CMLValue::new(&BigNum::from_str(quantity).unwrap()),
             _ => {
@@ -131,12 +274,8 @@ pub fn cmlvalue_from_bfvalues(values: &[BFValue]) -> Result<CMLValue> {
 #[async_trait]
 impl Ledger for BlockFrostLedger {
     async fn last_block_time_secs(&self) -> Result<i64> {
-        let res = self
-            .client
-            .latest_block_info()
-            .await
-            .map_err(|e| CMLLCError::LedgerError(Box::new(e)))?;
-        Ok(res.time() as i64)
+        let block: BFBlock = self.get_json("/blocks/latest").await?;
+        Ok(block.time)
     }
 
     async fn get_utxos_for_addr(
@@ -147,104 +286,206 @@ impl Ledger for BlockFrostLedger {
         let addr_string = addr
             .to_bech32(None)
             .map_err(|e| CMLLCError::JsError(e.to_string()))?;
-        let bf_utxos_res = self.client.utxos(&addr_string, Some(count)).await;
-        let bf_utxos = match bf_utxos_res {
-            Ok(bf_utxos) => Ok(bf_utxos),
-            Err(e) => match e {
-                Error::HttpError { status_code, .. } => {
-                    if status_code == 404 {
-                        Ok(Vec::new())
-                    } else {
-                        Err(CMLLCError::LedgerError(Box::new(e)))
-                    }
-                }
-                _ => Err(CMLLCError::LedgerError(Box::new(e))),
-            },
-        }?;
-        let utxos = future::join_all(
-            bf_utxos
-                .iter()
-                .map(|bf_utxo| async move { self.bfutxo_to_utxo(bf_utxo).await }),
-        )
-        .await
-        .into_iter()
-        .collect::<Result<Vec<_>>>()?;
-        Ok(utxos)
+        let bf_utxos = self.address_utxos(&addr_string, Some(count)).await?;
+        self.bfutxos_to_utxos(&bf_utxos).await
     }
 
     async fn get_all_utxos_for_addr(&self, addr: &CMLAddress) -> Result<Vec<UTxO>> {
         let addr_string = addr
             .to_bech32(None)
             .map_err(|e| CMLLCError::JsError(e.to_string()))?;
-        let bf_utxos_res = self.client.utxos(&addr_string, None).await;
-        let bf_utxos = match bf_utxos_res {
-            Ok(bf_utxos) => Ok(bf_utxos),
-            Err(e) => match e {
-                Error::HttpError { status_code, .. } => {
-                    if status_code == 404 {
-                        Ok(Vec::new())
-                    } else {
-                        Err(CMLLCError::LedgerError(Box::new(e)))
-                    }
-                }
-                _ => Err(CMLLCError::LedgerError(Box::new(e))),
-            },
-        }?;
-        let utxos = future::join_all(
-            bf_utxos
-                .iter()
-                .map(|bf_utxo| async move { self.bfutxo_to_utxo(bf_utxo).await }),
-        )
-        .await
-        .into_iter()
-        .collect::<Result<Vec<_>>>()?;
-        Ok(utxos)
+        let bf_utxos = self.address_utxos(&addr_string, None).await?;
+        self.bfutxos_to_utxos(&bf_utxos).await
+    }
+
+    async fn get_utxo_page_for_addr(
+        &self,
+        addr: &CMLAddress,
+        page: usize,
+    ) -> Result<Vec<UTxO>> {
+        let addr_string = addr
+            .to_bech32(None)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        let bf_utxos = self.address_utxo_page(&addr_string, page + 1).await?;
+        self.bfutxos_to_utxos(&bf_utxos).await
     }
 
     async fn calculate_ex_units(
         &self,
         tx: &CMLTransaction,
     ) -> Result<HashMap<u64, ExecutionCost>> {
-        let bytes = tx.to_bytes();
-        let res = self
-            .client
-            .execution_units(&bytes)
-            .await
-            .map_err(|e| CMLLCError::LedgerError(Box::new(e)))?;
-        let bf_spends = res
-            .get_execution_costs()
-            .map_err(|e| CMLLCError::LedgerError(Box::new(e)))?;
-        let spends = bf_spends
-            .iter()
-            .map(|(index, bf_spend)| (*index, spend_from_bf_spend(bf_spend)))
-            .collect();
-        // dbg!(spends);
-        // todo!();
-        Ok(spends)
+        // The evaluation endpoint takes the CBOR hex encoded
+        let body = hex::encode(tx.to_bytes()).into_bytes();
+        let res: serde_json::Value = self.post_cbor(EVALUATE_PATH, body).await?;
+        let results = res
+            .get("result")
+            .and_then(|result| result.get("EvaluationResult"))
+            .and_then(|results| results.as_object());
+        match results {
+            Some(results) => Ok(results
+                .iter()
+                .filter_map(|(k, v)| ExecutionCost::from_ogmios_entry(k, v))
+                .collect()),
+            // Script failures come back with a successful status, so keep what was said
+            None => {
+                let request = format!("POST {EVALUATE_PATH}");
+                let error =
+                    ProviderError::new(BLOCKFROST, &request).with_body(&res.to_string());
+                Err(error.into())
+            }
+        }
     }
 
     async fn submit_transaction(&self, tx: &CMLTransaction) -> Result<String> {
-        println!("{}", &tx.to_json().unwrap());
-        let res = self
-            .client
-            .submit_tx(&tx.to_bytes())
+        tracing::debug!(tx = ?tx.to_json().ok(), "Submitting transaction to BlockFrost");
+        self.submit_transaction_cbor(&tx.to_bytes()).await
+    }
+
+    async fn submit_transaction_cbor(&self, cbor: &[u8]) -> Result<String> {
+        let tx_id: String = self.post_cbor("/tx/submit", cbor.to_vec()).await?;
+        Ok(tx_id)
+    }
+
+    async fn get_transaction_cbor(&self, tx_id: &str) -> Result<Vec<u8>> {
+        let tx_cbor: BFTxCbor = self.get_json(&format!("/txs/{tx_id}/cbor")).await?;
+        let cbor = hex::decode(tx_cbor.cbor)?;
+        Ok(cbor)
+    }
+
+    // Blockfrost only serves transactions once they are in a block
+    async fn is_tx_confirmed(&self, tx_id: &str) -> Result<bool> {
+        match self
+            .get_json::<serde_json::Value>(&format!("/txs/{tx_id}"))
             .await
-            .map_err(|e| CMLLCError::LedgerError(Box::new(e)))?;
-        Ok(res.tx_id().to_string())
+        {
+            Ok(_) => Ok(true),
+            Err(CMLLCError::Provider(e)) if e.status() == Some(404) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn epoch_parameters(&self) -> Result<EpochParameters> {
+        let (epoch, bf_params) = future::try_join(
+            self.get_json::<BFEpoch>("/epochs/latest"),
+            self.get_json::<BFEpochParameters>("/epochs/latest/parameters"),
+        )
+        .await?;
+        let params = bf_params.to_protocol_parameters()?;
+        Ok(EpochParameters::new(params, Some(epoch.end_time)))
     }
 }
 
-fn spend_from_bf_spend(
-    bf_spend: &blockfrost_http_client::models::ExecutionCostsWithType,
-) -> ExecutionCost {
-    let memory = bf_spend.memory();
-    let steps = bf_spend.steps();
-    match bf_spend.execution_type() {
-        ExecutionType::Spend => ExecutionCost::new_spend(memory, steps),
-        ExecutionType::Mint => ExecutionCost::new_mint(memory, steps),
+const BLOCKFROST: &str = "Blockfrost";
+const EVALUATE_PATH: &str = "/utils/txs/evaluate";
+
+#[derive(Deserialize)]
+struct BFBlock {
+    time: i64,
+}
+
+#[derive(Deserialize)]
+struct BFDatum {
+    json_value: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct BFAddressUTxO {
+    tx_hash: String,
+    output_index: u64,
+    amount: Vec<BFAmount>,
+    data_hash: Option<String>,
+    inline_datum: Option<String>,
+    reference_script_hash: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BFScript {
+    #[serde(rename = "type")]
+    script_type: String,
+}
+
+#[derive(Deserialize)]
+struct BFScriptCbor {
+    cbor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BFAmount {
+    unit: String,
+    quantity: String,
+}
+
+#[derive(Deserialize)]
+struct BFTxCbor {
+    cbor: String,
+}
+
+#[derive(Deserialize)]
+struct BFEpoch {
+    end_time: i64,
+}
+
+// Blockfrost gives prices as decimals, which are turned back into ratios over this
+const PRICE_DENOMINATOR: u64 = 10_000_000;
+
+#[derive(Deserialize)]
+struct BFEpochParameters {
+    min_fee_a: u64,
+    min_fee_b: u64,
+    max_tx_size: u32,
+    key_deposit: String,
+    pool_deposit: String,
+    price_mem: Option<f64>,
+    price_step: Option<f64>,
+    max_val_size: Option<String>,
+    collateral_percent: Option<u32>,
+    max_collateral_inputs: Option<u32>,
+    coins_per_utxo_size: Option<String>,
+}
+
+impl BFEpochParameters {
+    fn to_protocol_parameters(&self) -> Result<ProtocolParameters> {
+        let defaults = ProtocolParameters::default();
+        let max_value_size = match &self.max_val_size {
+            Some(size) => parse_quantity(size)?,
+            None => defaults.max_value_size(),
+        };
+        let coins_per_utxo_byte = match &self.coins_per_utxo_size {
+            Some(coins) => parse_quantity(coins)?,
+            None => defaults.coins_per_utxo_byte(),
+        };
+        let price_mem = self.price_mem.map_or(defaults.price_mem(), price_ratio);
+        let price_step = self.price_step.map_or(defaults.price_step(), price_ratio);
+        let params = defaults
+            .with_coins_per_utxo_byte(coins_per_utxo_byte)
+            .with_min_fee(self.min_fee_a, self.min_fee_b)
+            .with_deposits(
+                parse_quantity(&self.key_deposit)?,
+                parse_quantity(&self.pool_deposit)?,
+            )
+            .with_max_sizes(self.max_tx_size, max_value_size)
+            .with_ex_unit_prices(price_mem, price_step)
+            .with_collateral(
+                self.collateral_percent
+                    .unwrap_or(defaults.collateral_percentage()),
+                self.max_collateral_inputs
+                    .unwrap_or(defaults.max_collateral_inputs()),
+            );
+        Ok(params)
     }
 }
 
+fn parse_quantity<T: FromStr>(quantity: &str) -> Result<T> {
+    quantity.parse().map_err(|_| {
+        CMLLCError::Deserialize(format!("Invalid protocol parameter: {quantity}"))
+    })
+}
+
+fn price_ratio(price: f64) -> (u64, u64) {
+    let numerator = (price * PRICE_DENOMINATOR as f64).round() as u64;
+    (numerator, PRICE_DENOMINATOR)
+}
+
 /// API key for Blockfrost
 #[derive(Serialize, Deserialize)]
 pub struct BlockfrostApiKey {
@@ -278,3 +519,53 @@ pub enum BlockfrostLedgerError {
     #[error("No config directory for raw phrase file: {0:?}")]
     NoConfigDirectory(String),
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use tokio::{
+        io::{
+            AsyncReadExt,
+            AsyncWriteExt,
+        },
+        net::TcpListener,
+    };
+
+    // Answers a single request with `status` and `body`
+    async fn serve_once(listener: TcpListener, status: &str, body: &str) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0; 4096];
+        stream.read(&mut buf).await.unwrap();
+        let response = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn submit_transaction_cbor__keeps_the_status_and_body_of_rejections() {
+        // given
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let ledger = BlockFrostLedger::new(&url, "key");
+        let body =
+            r#"{"status_code":400,"error":"Bad Request","message":"BadInputsUTxO"}"#;
+
+        // when
+        let (res, _) = tokio::join!(
+            ledger.submit_transaction_cbor(&[0x84]),
+            serve_once(listener, "400 Bad Request", body)
+        );
+
+        // then
+        let Err(CMLLCError::Provider(error)) = res else {
+            panic!("expected a provider error, got {res:?}")
+        };
+        assert_eq!(error.request(), "POST /tx/submit");
+        assert_eq!(error.status(), Some(400));
+        assert_eq!(error.body(), Some(body));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::transaction::metadata::{
+    MetadataError,
+    Metadatum,
+    TxMetadata,
+};
+use cardano_multiplatform_lib::crypto::{
+    PrivateKey,
+    PublicKey,
+};
+use pallas_addresses::Address;
+use pallas_crypto::hash::Hasher;
+use thiserror::Error;
+
+/// CIP-36 metadata label of the registration
+pub const REGISTRATION_LABEL: u64 = 61284;
+/// CIP-36 metadata label of the stake key signature over the registration
+pub const SIGNATURE_LABEL: u64 = 61285;
+/// Voting purpose of Catalyst
+pub const CATALYST_VOTING_PURPOSE: u64 = 0;
+
+// Keys of the registration map
+const DELEGATIONS_KEY: u64 = 1;
+const STAKE_KEY_KEY: u64 = 2;
+const PAYMENT_ADDRESS_KEY: u64 = 3;
+const NONCE_KEY: u64 = 4;
+const VOTING_PURPOSE_KEY: u64 = 5;
+const SIGNATURE_KEY: u64 = 1;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CatalystError {
+    #[error("Registration doesn't delegate to any voting key")]
+    NoDelegations,
+    #[error("Signing key isn't the stake key of the registration")]
+    WrongStakeKey,
+    #[error("Metadata error: {0}")]
+    Metadata(#[from] MetadataError),
+}
+
+/// Specialized result type for Catalyst registrations
+pub type CatalystResult<T, E = CatalystError> = Result<T, E>;
+
+/// CIP-36 registration of the voting power of a stake key, to one or more voting keys
+///
+/// ```ignore
+/// let metadata = CatalystRegistration::new(&stake_key.to_public(), &reward_address, slot)
+///     .with_delegation(&voting_key, 1)
+///     .sign(&stake_key)?;
+/// let actions = TxActions::v2().with_metadata(metadata);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CatalystRegistration {
+    delegations: Vec<(Vec<u8>, u32)>,
+    stake_public_key: Vec<u8>,
+    payment_address: Vec<u8>,
+    nonce: u64,
+    voting_purpose: u64,
+}
+
+impl CatalystRegistration {
+    /// Constructor for a [`CatalystRegistration`] of the stake key `stake_public_key`,
+    /// paying rewards to `payment_address`
+    ///
+    /// `nonce` must grow with each registration of the stake key, and is usually the
+    /// current slot.
+    pub fn new(
+        stake_public_key: &PublicKey,
+        payment_address: &Address,
+        nonce: u64,
+    ) -> Self {
+        CatalystRegistration {
+            delegations: Vec::new(),
+            stake_public_key: stake_public_key.as_bytes(),
+            payment_address: payment_address.to_vec(),
+            nonce,
+            voting_purpose: CATALYST_VOTING_PURPOSE,
+        }
+    }
+
+    /// Delegate `weight` parts of the voting power to `voting_key`
+    pub fn with_delegation(mut self, voting_key: &PublicKey, weight: u32) -> Self {
+        self.delegations.push((voting_key.as_bytes(), weight));
+        self
+    }
+
+    /// Register for a voting purpose other than Catalyst
+    pub fn with_voting_purpose(mut self, voting_purpose: u64) -> Self {
+        self.voting_purpose = voting_purpose;
+        self
+    }
+
+    /// Getter for the voting keys and their weights
+    pub fn delegations(&self) -> &[(Vec<u8>, u32)] {
+        &self.delegations
+    }
+
+    /// Getter for the nonce
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// Registration metadatum, to go under [`REGISTRATION_LABEL`]
+    pub fn registration(&self) -> Metadatum {
+        let delegations = self
+            .delegations
+            .iter()
+            .map(|(voting_key, weight)| {
+                Metadatum::List(vec![
+                    voting_key.clone().into(),
+                    u64::from(*weight).into(),
+                ])
+            })
+            .collect();
+        Metadatum::Map(vec![
+            (DELEGATIONS_KEY.into(), Metadatum::List(delegations)),
+            (STAKE_KEY_KEY.into(), self.stake_public_key.clone().into()),
+            (
+                PAYMENT_ADDRESS_KEY.into(),
+                self.payment_address.clone().into(),
+            ),
+            (NONCE_KEY.into(), self.nonce.into()),
+            (VOTING_PURPOSE_KEY.into(), self.voting_purpose.into()),
+        ])
+    }
+
+    /// Sign the registration with the stake key, giving the metadata for both labels
+    pub fn sign(&self, stake_key: &PrivateKey) -> CatalystResult<TxMetadata> {
+        if self.delegations.is_empty() {
+            return Err(CatalystError::NoDelegations)
+        }
+        if stake_key.to_public().as_bytes() != self.stake_public_key {
+            return Err(CatalystError::WrongStakeKey)
+        }
+        let registration =
+            TxMetadata::default().with_entry(REGISTRATION_LABEL, self.registration());
+        let registration_hash = Hasher::<256>::hash(&registration.to_cbor()?);
+        let signature = stake_key.sign(registration_hash.as_ref()).to_bytes();
+        let witness = Metadatum::Map(vec![(SIGNATURE_KEY.into(), signature.into())]);
+        Ok(registration.with_entry(SIGNATURE_LABEL, witness))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use cardano_multiplatform_lib::crypto::Ed25519Signature;
+
+    // Header of testnet reward addresses with a key hash credential
+    const REWARD_ADDRESS_HEADER: u8 = 0xe0;
+
+    fn reward_address(stake_key: &PrivateKey) -> Address {
+        let key_hash = stake_key.to_public().hash().to_bytes();
+        Address::from_bytes(&[vec![REWARD_ADDRESS_HEADER], key_hash].concat()).unwrap()
+    }
+
+    #[test]
+    fn sign__signs_registration_with_stake_key() {
+        // given
+        let stake_key = PrivateKey::generate_ed25519().unwrap();
+        let voting_key = PrivateKey::generate_ed25519().unwrap().to_public();
+        let address = reward_address(&stake_key);
+        let registration =
+            CatalystRegistration::new(&stake_key.to_public(), &address, 42)
+                .with_delegation(&voting_key, 1);
+
+        // when
+        let metadata = registration.sign(&stake_key).unwrap();
+
+        // then
+        let signed = TxMetadata::default()
+            .with_entry(REGISTRATION_LABEL, registration.registration());
+        let hash = Hasher::<256>::hash(&signed.to_cbor().unwrap());
+        let Some(Metadatum::Map(witness)) = metadata.get(SIGNATURE_LABEL) else {
+            panic!("missing signature metadatum")
+        };
+        let Metadatum::Bytes(signature) = &witness[0].1 else {
+            panic!("signature isn't bytes")
+        };
+        let signature = Ed25519Signature::from_bytes(signature.clone()).unwrap();
+        assert!(stake_key.to_public().verify(hash.as_ref(), &signature));
+        assert_eq!(
+            metadata.get(REGISTRATION_LABEL),
+            Some(&registration.registration())
+        );
+    }
+
+    #[test]
+    fn sign__rejects_other_keys_and_empty_registrations() {
+        // given
+        let stake_key = PrivateKey::generate_ed25519().unwrap();
+        let other_key = PrivateKey::generate_ed25519().unwrap();
+        let address = reward_address(&stake_key);
+        let registration = CatalystRegistration::new(&stake_key.to_public(), &address, 1);
+
+        // when
+        let empty = registration.sign(&stake_key);
+        let wrong_key = registration
+            .with_delegation(&other_key.to_public(), 1)
+            .sign(&other_key);
+
+        // then
+        assert_eq!(empty, Err(CatalystError::NoDelegations));
+        assert_eq!(wrong_key, Err(CatalystError::WrongStakeKey));
+    }
+}
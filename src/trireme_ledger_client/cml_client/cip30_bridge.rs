@@ -0,0 +1,406 @@
+use super::{
+    error::*,
+    Keys,
+};
+use async_trait::async_trait;
+use cardano_multiplatform_lib::{
+    address::{
+        Address as CMLAddress,
+        BaseAddress,
+    },
+    crypto::{
+        PrivateKey,
+        Vkeywitness,
+    },
+    Transaction as CMLTransaction,
+    TransactionWitnessSet,
+};
+use rand::Rng;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    collections::{
+        HashMap,
+        VecDeque,
+    },
+    net::SocketAddr,
+    sync::{
+        Arc,
+        Mutex,
+    },
+    time::Duration,
+};
+use thiserror::Error;
+use tokio::{
+    io::{
+        AsyncReadExt,
+        AsyncWriteExt,
+    },
+    net::{
+        TcpListener,
+        TcpStream,
+    },
+    sync::oneshot,
+    task::JoinHandle,
+};
+
+const BRIDGE_PAGE: &str = include_str!("cip30_bridge.html");
+const LONG_POLL: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const MAX_REQUEST_SIZE: usize = 1 << 20;
+const TOKEN_HEADER: &str = "x-bridge-token";
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum Cip30BridgeError {
+    #[error("Couldn't start bridge: {0}")]
+    Start(std::io::Error),
+    #[error("Wallet refused the request: {0}")]
+    Wallet(String),
+    #[error("Timed out waiting for the browser wallet")]
+    Timeout,
+    #[error("Bridge shut down before the wallet responded")]
+    Closed,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum WalletRequest {
+    Address { id: u64 },
+    Sign { id: u64, tx: String },
+}
+
+#[derive(Deserialize)]
+struct WalletResponse {
+    id: u64,
+    result: Option<String>,
+    error: Option<String>,
+}
+
+struct BridgeState {
+    // Only the page opened from the bridge's URL knows it
+    token: String,
+    origin: String,
+    queue: Mutex<VecDeque<WalletRequest>>,
+    waiting: Mutex<HashMap<u64, oneshot::Sender<WalletResponse>>>,
+}
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Local HTTP bridge to a CIP-30 browser wallet (Eternl, Lace, etc.)
+///
+/// Open the bridge's URL in a browser with the wallet extension installed and connect.
+/// Requests from Naumachia are then forwarded to the wallet, which asks the user to
+/// approve them. The URL carries a random token for the session, and the bridge only
+/// talks to pages from its own origin that present it.
+pub struct Cip30Bridge {
+    addr: SocketAddr,
+    state: Arc<BridgeState>,
+    timeout: Duration,
+    server: JoinHandle<()>,
+}
+
+impl Cip30Bridge {
+    /// Start serving the bridge on `addr`, e.g. `127.0.0.1:8090`
+    pub async fn start(addr: SocketAddr) -> Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| CMLLCError::KeyError(Box::new(Cip30BridgeError::Start(e))))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| CMLLCError::KeyError(Box::new(Cip30BridgeError::Start(e))))?;
+        let token = hex::encode(rand::thread_rng().gen::<[u8; 32]>());
+        let state = Arc::new(BridgeState {
+            token,
+            origin: format!("http://{addr}"),
+            queue: Default::default(),
+            waiting: Default::default(),
+        });
+        let server_state = state.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle_connection(stream, server_state.clone()));
+            }
+        });
+        Ok(Cip30Bridge {
+            addr,
+            state,
+            timeout: Duration::from_secs(300),
+            server,
+        })
+    }
+
+    /// How long to wait for the user to approve a request in their wallet
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// URL to open in the browser, including the session's token
+    pub fn url(&self) -> String {
+        format!("{}/?token={}", self.state.origin, self.state.token)
+    }
+
+    async fn request(
+        &self,
+        request: impl FnOnce(u64) -> WalletRequest,
+    ) -> Result<String> {
+        let id = rand::thread_rng().gen();
+        let (sender, receiver) = oneshot::channel();
+        self.state.waiting.lock().unwrap().insert(id, sender);
+        self.state.queue.lock().unwrap().push_back(request(id));
+
+        let response = tokio::time::timeout(self.timeout, receiver).await;
+        self.state.waiting.lock().unwrap().remove(&id);
+        let response = response
+            .map_err(|_| Cip30BridgeError::Timeout)
+            .and_then(|res| res.map_err(|_| Cip30BridgeError::Closed))
+            .map_err(|e| CMLLCError::KeyError(Box::new(e)))?;
+        match (response.result, response.error) {
+            (Some(result), None) => Ok(result),
+            (_, error) => Err(CMLLCError::KeyError(Box::new(Cip30BridgeError::Wallet(
+                error.unwrap_or_default(),
+            )))),
+        }
+    }
+}
+
+impl Drop for Cip30Bridge {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+#[async_trait]
+impl Keys for Cip30Bridge {
+    async fn base_addr(&self) -> Result<BaseAddress> {
+        let address_hex = self.request(|id| WalletRequest::Address { id }).await?;
+        let bytes = hex::decode(address_hex)?;
+        let address = CMLAddress::from_bytes(bytes)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        BaseAddress::from_address(&address).ok_or(CMLLCError::InvalidBaseAddr)
+    }
+
+    async fn private_key(&self) -> Result<PrivateKey> {
+        Err(CMLLCError::KeyError(Box::new(Cip30BridgeError::Wallet(
+            "Browser wallets don't expose private keys".to_string(),
+        ))))
+    }
+
+    async fn vkey_witnesses(&self, tx: &CMLTransaction) -> Result<Vec<Vkeywitness>> {
+        let tx = hex::encode(tx.to_bytes());
+        let witness_set_hex = self.request(|id| WalletRequest::Sign { id, tx }).await?;
+        let witness_set =
+            TransactionWitnessSet::from_bytes(hex::decode(witness_set_hex)?)
+                .map_err(|e| CMLLCError::Deserialize(e.to_string()))?;
+        let vkeys = witness_set.vkeys();
+        let witnesses = vkeys
+            .map(|vkeys| (0..vkeys.len()).map(|i| vkeys.get(i)).collect())
+            .unwrap_or_default();
+        Ok(witnesses)
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, state: Arc<BridgeState>) {
+    let response = match read_request(&mut stream).await {
+        Some(request) => route(&request, &state).await,
+        None => http_response("400 Bad Request", "text/plain", ""),
+    };
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+// Browsers send the origin of cross-origin requests, so any other page is turned away
+fn is_authorized(request: &HttpRequest, state: &BridgeState) -> bool {
+    let same_origin = request
+        .header("origin")
+        .map_or(true, |origin| origin == state.origin);
+    let has_token = request.header(TOKEN_HEADER) == Some(state.token.as_str());
+    same_origin && has_token
+}
+
+async fn route(request: &HttpRequest, state: &BridgeState) -> String {
+    let path = request.path.split('?').next().unwrap_or_default();
+    if path != "/" && !is_authorized(request, state) {
+        return http_response("401 Unauthorized", "text/plain", "")
+    }
+    match (request.method.as_str(), path) {
+        ("GET", "/") => http_response("200 OK", "text/html", BRIDGE_PAGE),
+        ("GET", "/request") => {
+            let mut waited = Duration::ZERO;
+            while waited < LONG_POLL {
+                let next = state.queue.lock().unwrap().pop_front();
+                if let Some(request) = next {
+                    let json = serde_json::to_string(&request).unwrap_or_default();
+                    return http_response("200 OK", "application/json", &json)
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+                waited += POLL_INTERVAL;
+            }
+            http_response("204 No Content", "text/plain", "")
+        }
+        ("POST", "/response") => {
+            match serde_json::from_slice::<WalletResponse>(&request.body) {
+                Ok(response) => {
+                    if let Some(sender) =
+                        state.waiting.lock().unwrap().remove(&response.id)
+                    {
+                        let _ = sender.send(response);
+                    }
+                    http_response("200 OK", "text/plain", "")
+                }
+                Err(_) => http_response("400 Bad Request", "text/plain", ""),
+            }
+        }
+        _ => http_response("404 Not Found", "text/plain", ""),
+    }
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<HttpRequest> {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 4096];
+    let header_end = loop {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 || buf.len() > MAX_REQUEST_SIZE {
+            return None
+        }
+        buf.extend_from_slice(&chunk[..read]);
+        if let Some(pos) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+            break pos + 4
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_REQUEST_SIZE {
+        return None
+    }
+    let mut body = buf[header_end..].to_vec();
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    Some(HttpRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+fn http_response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    async fn answer_next_request(bridge: &Cip30Bridge, result: &str) {
+        let client = reqwest::Client::new();
+        let request: serde_json::Value = client
+            .get(format!("{}/request", bridge.state.origin))
+            .header(TOKEN_HEADER, &bridge.state.token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let response = serde_json::json!({ "id": request["id"], "result": result });
+        client
+            .post(format!("{}/response", bridge.state.origin))
+            .header(TOKEN_HEADER, &bridge.state.token)
+            .body(response.to_string())
+            .send()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn base_addr__forwards_wallet_change_address() {
+        // given
+        let bridge = Cip30Bridge::start("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let bech32 = "addr_test1qrksjmprvgcedgdt6rhg40590vr6exdzdc2hm5wc6pyl9ymkyskmqs55usm57gflrumk9kd63f3ty6r0l2tdfwfm28qs0rurdr";
+        let address = CMLAddress::from_bech32(bech32).unwrap();
+        let address_hex = hex::encode(address.to_bytes());
+
+        // when
+        let (base_addr, _) = tokio::join!(
+            bridge.base_addr(),
+            answer_next_request(&bridge, &address_hex)
+        );
+
+        // then
+        let actual = base_addr.unwrap().to_address().to_bech32(None).unwrap();
+        assert_eq!(actual, bech32);
+    }
+
+    #[tokio::test]
+    async fn route__rejects_requests_without_the_token_or_from_other_origins() {
+        // given
+        let bridge = Cip30Bridge::start("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let client = reqwest::Client::new();
+        let response_url = format!("{}/response", bridge.state.origin);
+        let body = serde_json::json!({ "id": 0, "result": "00" }).to_string();
+
+        // when
+        let without_token = client
+            .post(&response_url)
+            .body(body.clone())
+            .send()
+            .await
+            .unwrap();
+        let other_origin = client
+            .post(&response_url)
+            .header(TOKEN_HEADER, &bridge.state.token)
+            .header("Origin", "http://evil.example")
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+
+        // then
+        assert_eq!(without_token.status(), 401);
+        assert_eq!(other_origin.status(), 401);
+        assert!(bridge.url().contains(&bridge.state.token));
+    }
+}
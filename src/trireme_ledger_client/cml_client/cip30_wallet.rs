@@ -0,0 +1,159 @@
+use super::{
+    error::*,
+    message_signing::DataSignature,
+    Keys,
+};
+use async_trait::async_trait;
+use cardano_multiplatform_lib::{
+    address::{
+        Address as CMLAddress,
+        BaseAddress,
+    },
+    crypto::{
+        PrivateKey,
+        PublicKey,
+        Vkeywitness,
+    },
+    Transaction as CMLTransaction,
+    TransactionWitnessSet,
+};
+use js_sys::{
+    Array,
+    Function,
+    Promise,
+    Reflect,
+};
+use send_wrapper::SendWrapper;
+use thiserror::Error;
+use wasm_bindgen::{
+    JsCast,
+    JsValue,
+};
+use wasm_bindgen_futures::JsFuture;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum Cip30WalletError {
+    #[error("Wallet API call `{method}` failed: {message}")]
+    Api { method: String, message: String },
+    #[error("Browser wallets don't expose private keys")]
+    PrivateKeyUnavailable,
+    #[error("Browser wallets don't expose public keys")]
+    PublicKeyUnavailable,
+}
+
+/// [`Keys`] implementation signing with a CIP-30 browser wallet (Eternl, Lace, etc.)
+///
+/// Only available when compiled to `wasm32`, inside the browser. Wraps the API object
+/// returned by `window.cardano.<wallet>.enable()`. CIP-30 wallets can't query arbitrary
+/// addresses, so pair these keys with a [`Ledger`](super::Ledger) like BlockFrost.
+pub struct Cip30WalletKeys {
+    // JS values aren't `Send`, but wasm32 in the browser only has the one thread
+    api: SendWrapper<JsValue>,
+}
+
+impl Cip30WalletKeys {
+    /// Constructor for the [`Cip30WalletKeys`] struct from an enabled wallet API object
+    pub fn new(api: JsValue) -> Self {
+        Cip30WalletKeys {
+            api: SendWrapper::new(api),
+        }
+    }
+
+    // Start calling `method` on the wallet API. Kept apart from awaiting the call so no
+    // JS value is held across an await point.
+    fn call(&self, method: &str, args: &[JsValue]) -> Result<SendWrapper<JsFuture>> {
+        let api_error = |e: JsValue| api_error(method, e);
+        let function: Function = Reflect::get(&self.api, &JsValue::from_str(method))
+            .map_err(api_error)?
+            .dyn_into()
+            .map_err(api_error)?;
+        let args: Array = args.iter().collect();
+        let promise: Promise = function
+            .apply(&self.api, &args)
+            .map_err(api_error)?
+            .dyn_into()
+            .map_err(api_error)?;
+        Ok(SendWrapper::new(JsFuture::from(promise)))
+    }
+
+    // Await `call` and read the hex string it resolves to
+    async fn hex_result(method: &str, call: SendWrapper<JsFuture>) -> Result<Vec<u8>> {
+        let value = call.await.map_err(|e| api_error(method, e))?;
+        let hex_string = value.as_string().ok_or_else(|| {
+            api_error(method, JsValue::from_str("expected a hex string"))
+        })?;
+        Ok(hex::decode(hex_string)?)
+    }
+
+    // Await a `signData` call and read the hex `signature` and `key` it resolves to
+    async fn data_signature_result(
+        call: SendWrapper<JsFuture>,
+    ) -> Result<(String, String)> {
+        let value = call.await.map_err(|e| api_error("signData", e))?;
+        let field = |name: &str| {
+            Reflect::get(&value, &JsValue::from_str(name))
+                .ok()
+                .and_then(|field| field.as_string())
+                .ok_or_else(|| {
+                    api_error("signData", JsValue::from_str(&format!("missing {name}")))
+                })
+        };
+        Ok((field("signature")?, field("key")?))
+    }
+}
+
+fn api_error(method: &str, error: JsValue) -> CMLLCError {
+    CMLLCError::KeyError(Box::new(Cip30WalletError::Api {
+        method: method.to_string(),
+        message: format!("{error:?}"),
+    }))
+}
+
+#[async_trait]
+impl Keys for Cip30WalletKeys {
+    async fn base_addr(&self) -> Result<BaseAddress> {
+        let call = self.call("getChangeAddress", &[])?;
+        let bytes = Self::hex_result("getChangeAddress", call).await?;
+        let address = CMLAddress::from_bytes(bytes)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        BaseAddress::from_address(&address).ok_or(CMLLCError::InvalidBaseAddr)
+    }
+
+    async fn private_key(&self) -> Result<PrivateKey> {
+        Err(CMLLCError::KeyError(Box::new(
+            Cip30WalletError::PrivateKeyUnavailable,
+        )))
+    }
+
+    async fn vkey_witnesses(&self, tx: &CMLTransaction) -> Result<Vec<Vkeywitness>> {
+        let tx = JsValue::from_str(&hex::encode(tx.to_bytes()));
+        let call = self.call("signTx", &[tx, JsValue::TRUE])?;
+        let bytes = Self::hex_result("signTx", call).await?;
+        let witness_set = TransactionWitnessSet::from_bytes(bytes)
+            .map_err(|e| CMLLCError::Deserialize(e.to_string()))?;
+        let vkeys = witness_set.vkeys();
+        let witnesses = vkeys
+            .map(|vkeys| (0..vkeys.len()).map(|i| vkeys.get(i)).collect())
+            .unwrap_or_default();
+        Ok(witnesses)
+    }
+
+    async fn payment_public_key(&self) -> Result<PublicKey> {
+        Err(CMLLCError::KeyError(Box::new(
+            Cip30WalletError::PublicKeyUnavailable,
+        )))
+    }
+
+    async fn sign_data(&self, payload: &[u8]) -> Result<DataSignature> {
+        let address = self.base_addr().await?.to_address().to_bytes();
+        let args = [
+            JsValue::from_str(&hex::encode(address)),
+            JsValue::from_str(&hex::encode(payload)),
+        ];
+        let call = self.call("signData", &args)?;
+        let (signature, key) = Self::data_signature_result(call).await?;
+        DataSignature::from_hex(&signature, &key)
+            .map_err(|e| CMLLCError::KeyError(Box::new(e)))
+    }
+}
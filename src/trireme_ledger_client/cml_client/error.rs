@@ -1,4 +1,11 @@
-use crate::ledger_client::LedgerClientError;
+use crate::{
+    ledger_client::{
+        provider_error::ProviderError,
+        LedgerClientError,
+    },
+    transaction::era::EraError,
+    values::ValuesError,
+};
 use pallas_addresses::Address;
 use thiserror::Error;
 
@@ -9,18 +16,22 @@ pub enum CMLLCError {
     JsError(String),
     #[error("Address Error: {0:?}")]
     Address(#[from] pallas_addresses::Error),
+    #[cfg(feature = "trireme")]
     #[error("Scrolls Client: {0:?}")]
     ScrollsClient(#[from] scrolls_client::error::Error),
+    #[cfg(feature = "trireme")]
     #[error("Ogmios Client: {0:?}")]
     OgmiosClient(#[from] ogmios_client::Error),
     #[error("Ogmios Response: {0:?}")]
     OgmiosResponse(String),
+    #[error("Provider Error: {0}")]
+    Provider(#[from] ProviderError),
     #[error("Not a valid BaseAddress")]
     InvalidBaseAddr,
     #[error("Error from ledger implementation: {0:?}")]
-    LedgerError(Box<dyn std::error::Error + Send + Sync>),
+    LedgerError(#[source] Box<dyn std::error::Error + Send + Sync>),
     #[error("Error in key manager implementation: {0:?}")]
-    KeyError(Box<dyn std::error::Error + Send + Sync>),
+    KeyError(#[source] Box<dyn std::error::Error + Send + Sync>),
     #[error("Unbuilt output does not have sufficient ADA")]
     InsufficientADA,
     #[error("Error while deserializing: {0:?}")]
@@ -30,6 +41,12 @@ pub enum CMLLCError {
     Hex(#[from] hex::FromHexError),
     #[error("Invalid Policy Id: {0:?}")]
     InvalidPolicyId(String),
+    #[error("Era Error: {0}")]
+    Era(#[from] EraError),
+    #[error("Values Error: {0}")]
+    Values(#[from] ValuesError),
+    #[error("Not supported by this ledger: {0}")]
+    Unsupported(String),
 }
 
 /// Convenience function for wrapping a `CMLLCError` in a [`LedgerClientError`] `FailedToRetrieveOutputsAt` variant
@@ -0,0 +1,514 @@
+use bech32::{
+    FromBase32,
+    ToBase32,
+    Variant,
+};
+use cardano_multiplatform_lib::crypto::PublicKey;
+use minicbor::{
+    encode::Error as EncodeError,
+    Encoder,
+};
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    fmt,
+};
+use thiserror::Error;
+
+// CIP-105 prefix of DRep ids, the bech32 of the DRep key hash
+const DREP_HRP: &str = "drep";
+const KEY_HASH_LEN: usize = 28;
+
+// Conway certificate tags, from the ledger CDDL
+const VOTE_DELEG_CERT: u8 = 9;
+const REG_DREP_CERT: u8 = 16;
+const UNREG_DREP_CERT: u8 = 17;
+const UPDATE_DREP_CERT: u8 = 18;
+
+type EncodeResult = Result<(), EncodeError<Infallible>>;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum GovernanceError {
+    #[error("Failed to encode governance CBOR: {0}")]
+    Encoding(String),
+    #[error("Invalid DRep id: {0}")]
+    InvalidDRepId(String),
+    #[error("Keys don't provide a DRep key")]
+    NoDRepKey,
+}
+
+/// Specialized result type for governance certificates and votes
+pub type GovernanceResult<T, E = GovernanceError> = Result<T, E>;
+
+/// Id of a DRep registered with a key, the hash of its CIP-105 DRep key
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DRepId {
+    key_hash: Vec<u8>,
+}
+
+impl DRepId {
+    /// Id of the DRep with verification key `public_key`
+    pub fn from_public_key(public_key: &PublicKey) -> Self {
+        DRepId {
+            key_hash: public_key.hash().to_bytes(),
+        }
+    }
+
+    /// Parse a bech32 `drep1...` id
+    pub fn from_bech32(id: &str) -> GovernanceResult<Self> {
+        let invalid = GovernanceError::InvalidDRepId;
+        let (hrp, data, _) = bech32::decode(id).map_err(|e| invalid(e.to_string()))?;
+        if hrp != DREP_HRP {
+            return Err(invalid(format!(
+                "expected prefix {DREP_HRP:?}, got {hrp:?}"
+            )))
+        }
+        let key_hash =
+            Vec::<u8>::from_base32(&data).map_err(|e| invalid(e.to_string()))?;
+        if key_hash.len() != KEY_HASH_LEN {
+            return Err(invalid(format!(
+                "expected a {KEY_HASH_LEN} byte key hash, got {} bytes",
+                key_hash.len()
+            )))
+        }
+        Ok(DRepId { key_hash })
+    }
+
+    /// Getter for the DRep key hash
+    pub fn key_hash(&self) -> &[u8] {
+        &self.key_hash
+    }
+
+    /// DRep credential for certificates
+    pub fn credential(&self) -> Credential {
+        Credential::Key(self.key_hash.clone())
+    }
+
+    /// Voter for voting procedures
+    pub fn voter(&self) -> Voter {
+        Voter::DRepKey(self.key_hash.clone())
+    }
+}
+
+impl fmt::Display for DRepId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id = bech32::encode(DREP_HRP, self.key_hash.to_base32(), Variant::Bech32)
+            .map_err(|_| fmt::Error)?;
+        write!(f, "{id}")
+    }
+}
+
+/// Key or script hash credential, of a DRep or of a stake address
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Credential {
+    /// Hash of a verification key
+    Key(Vec<u8>),
+    /// Hash of a script
+    Script(Vec<u8>),
+}
+
+impl Credential {
+    fn encode(&self, e: &mut Encoder<Vec<u8>>) -> EncodeResult {
+        match self {
+            Credential::Key(hash) => e.array(2)?.u8(0)?.bytes(hash)?,
+            Credential::Script(hash) => e.array(2)?.u8(1)?.bytes(hash)?,
+        };
+        Ok(())
+    }
+}
+
+/// Who a stake credential delegates its votes to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DRep {
+    /// DRep registered with a key
+    Key(Vec<u8>),
+    /// DRep registered with a script
+    Script(Vec<u8>),
+    /// Count the stake as abstaining from every action
+    AlwaysAbstain,
+    /// Count the stake as voting no confidence in the constitutional committee
+    AlwaysNoConfidence,
+}
+
+impl From<&DRepId> for DRep {
+    fn from(id: &DRepId) -> Self {
+        DRep::Key(id.key_hash.clone())
+    }
+}
+
+impl DRep {
+    fn encode(&self, e: &mut Encoder<Vec<u8>>) -> EncodeResult {
+        match self {
+            DRep::Key(hash) => e.array(2)?.u8(0)?.bytes(hash)?,
+            DRep::Script(hash) => e.array(2)?.u8(1)?.bytes(hash)?,
+            DRep::AlwaysAbstain => e.array(1)?.u8(2)?,
+            DRep::AlwaysNoConfidence => e.array(1)?.u8(3)?,
+        };
+        Ok(())
+    }
+}
+
+/// URL and Blake2b-256 hash of off-chain metadata, e.g. a DRep profile or a vote rationale
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Anchor {
+    url: String,
+    data_hash: Vec<u8>,
+}
+
+impl Anchor {
+    /// Constructor for an [`Anchor`] to the document at `url` hashing to `data_hash`
+    pub fn new(url: &str, data_hash: Vec<u8>) -> Self {
+        Anchor {
+            url: url.to_string(),
+            data_hash,
+        }
+    }
+
+    /// Getter for the URL
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Getter for the hash of the document
+    pub fn data_hash(&self) -> &[u8] {
+        &self.data_hash
+    }
+
+    fn encode_optional(
+        anchor: &Option<Anchor>,
+        e: &mut Encoder<Vec<u8>>,
+    ) -> EncodeResult {
+        match anchor {
+            Some(anchor) => {
+                e.array(2)?.str(&anchor.url)?.bytes(&anchor.data_hash)?;
+            }
+            None => {
+                e.null()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Conway certificates for registering DReps and delegating votes to them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GovernanceCertificate {
+    /// Register `drep`, locking `deposit` lovelace
+    RegDRep {
+        /// DRep credential
+        drep: Credential,
+        /// Deposit, which must match the current protocol parameters
+        deposit: u64,
+        /// Optional DRep metadata
+        anchor: Option<Anchor>,
+    },
+    /// Retire `drep`, returning its `refund`
+    UnregDRep {
+        /// DRep credential
+        drep: Credential,
+        /// Refund, which must match the deposit paid on registration
+        refund: u64,
+    },
+    /// Replace the metadata of `drep`
+    UpdateDRep {
+        /// DRep credential
+        drep: Credential,
+        /// New DRep metadata
+        anchor: Option<Anchor>,
+    },
+    /// Delegate the votes of `stake` to `drep`
+    VoteDelegation {
+        /// Stake credential of the delegator
+        stake: Credential,
+        /// DRep receiving the votes
+        drep: DRep,
+    },
+}
+
+impl GovernanceCertificate {
+    /// Encode the certificate as in a Conway transaction body
+    pub fn to_cbor(&self) -> GovernanceResult<Vec<u8>> {
+        encode(|e| self.encode(e))
+    }
+
+    fn encode(&self, e: &mut Encoder<Vec<u8>>) -> EncodeResult {
+        match self {
+            GovernanceCertificate::RegDRep {
+                drep,
+                deposit,
+                anchor,
+            } => {
+                e.array(4)?.u8(REG_DREP_CERT)?;
+                drep.encode(e)?;
+                e.u64(*deposit)?;
+                Anchor::encode_optional(anchor, e)
+            }
+            GovernanceCertificate::UnregDRep { drep, refund } => {
+                e.array(3)?.u8(UNREG_DREP_CERT)?;
+                drep.encode(e)?;
+                e.u64(*refund)?;
+                Ok(())
+            }
+            GovernanceCertificate::UpdateDRep { drep, anchor } => {
+                e.array(3)?.u8(UPDATE_DREP_CERT)?;
+                drep.encode(e)?;
+                Anchor::encode_optional(anchor, e)
+            }
+            GovernanceCertificate::VoteDelegation { stake, drep } => {
+                e.array(3)?.u8(VOTE_DELEG_CERT)?;
+                stake.encode(e)?;
+                drep.encode(e)
+            }
+        }
+    }
+}
+
+/// Choice on a governance action
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Vote {
+    No = 0,
+    Yes = 1,
+    Abstain = 2,
+}
+
+/// Body casting votes, which must witness the transaction with the key or script of its
+/// credential
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Voter {
+    /// Constitutional committee hot key hash
+    CommitteeKey(Vec<u8>),
+    /// Constitutional committee hot script hash
+    CommitteeScript(Vec<u8>),
+    /// DRep key hash
+    DRepKey(Vec<u8>),
+    /// DRep script hash
+    DRepScript(Vec<u8>),
+    /// Stake pool key hash
+    StakePool(Vec<u8>),
+}
+
+impl Voter {
+    fn encode(&self, e: &mut Encoder<Vec<u8>>) -> EncodeResult {
+        let (tag, hash) = match self {
+            Voter::CommitteeKey(hash) => (0, hash),
+            Voter::CommitteeScript(hash) => (1, hash),
+            Voter::DRepKey(hash) => (2, hash),
+            Voter::DRepScript(hash) => (3, hash),
+            Voter::StakePool(hash) => (4, hash),
+        };
+        e.array(2)?.u8(tag)?.bytes(hash)?;
+        Ok(())
+    }
+}
+
+/// Governance action, identified by the transaction proposing it and its index there
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GovActionId {
+    tx_hash: Vec<u8>,
+    index: u16,
+}
+
+impl GovActionId {
+    /// Constructor for the [`GovActionId`] of proposal `index` of transaction `tx_hash`
+    pub fn new(tx_hash: Vec<u8>, index: u16) -> Self {
+        GovActionId { tx_hash, index }
+    }
+
+    /// Getter for the hash of the proposing transaction
+    pub fn tx_hash(&self) -> &[u8] {
+        &self.tx_hash
+    }
+
+    /// Getter for the index of the proposal in its transaction
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+}
+
+/// Vote on one action, with an optional rationale
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VotingProcedure {
+    vote: Vote,
+    anchor: Option<Anchor>,
+}
+
+impl VotingProcedure {
+    /// Constructor for a [`VotingProcedure`] without rationale
+    pub fn new(vote: Vote) -> Self {
+        VotingProcedure { vote, anchor: None }
+    }
+
+    /// Link the rationale for the vote
+    pub fn with_anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = Some(anchor);
+        self
+    }
+
+    /// Getter for the vote
+    pub fn vote(&self) -> Vote {
+        self.vote
+    }
+
+    /// Getter for the rationale
+    pub fn anchor(&self) -> Option<&Anchor> {
+        self.anchor.as_ref()
+    }
+}
+
+/// Votes of a transaction, by voter and action
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VotingProcedures {
+    votes: BTreeMap<Voter, BTreeMap<GovActionId, VotingProcedure>>,
+}
+
+impl VotingProcedures {
+    /// Add the vote of `voter` on `action`, replacing any earlier vote on it
+    pub fn with_vote(
+        mut self,
+        voter: Voter,
+        action: GovActionId,
+        procedure: VotingProcedure,
+    ) -> Self {
+        self.votes
+            .entry(voter)
+            .or_default()
+            .insert(action, procedure);
+        self
+    }
+
+    /// Voters, each of which must witness the transaction
+    pub fn voters(&self) -> impl Iterator<Item = &Voter> {
+        self.votes.keys()
+    }
+
+    /// Whether there are no votes
+    pub fn is_empty(&self) -> bool {
+        self.votes.is_empty()
+    }
+
+    /// Encode the votes as in a Conway transaction body
+    pub fn to_cbor(&self) -> GovernanceResult<Vec<u8>> {
+        encode(|e| {
+            e.map(self.votes.len() as u64)?;
+            for (voter, actions) in &self.votes {
+                voter.encode(e)?;
+                e.map(actions.len() as u64)?;
+                for (action, procedure) in actions {
+                    e.array(2)?.bytes(&action.tx_hash)?.u16(action.index)?;
+                    e.array(2)?.u8(procedure.vote as u8)?;
+                    Anchor::encode_optional(&procedure.anchor, e)?;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+fn encode(
+    write: impl FnOnce(&mut Encoder<Vec<u8>>) -> EncodeResult,
+) -> GovernanceResult<Vec<u8>> {
+    let mut encoder = Encoder::new(Vec::new());
+    write(&mut encoder).map_err(|e| GovernanceError::Encoding(e.to_string()))?;
+    Ok(encoder.into_writer())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use cardano_multiplatform_lib::crypto::PrivateKey;
+
+    #[test]
+    fn drep_id__roundtrips_through_bech32() {
+        // given
+        let key = PrivateKey::generate_ed25519().unwrap();
+        let id = DRepId::from_public_key(&key.to_public());
+
+        // when
+        let bech32 = id.to_string();
+        let parsed = DRepId::from_bech32(&bech32).unwrap();
+
+        // then
+        assert!(bech32.starts_with("drep1"));
+        assert_eq!(parsed, id);
+        assert!(matches!(
+            DRepId::from_bech32(
+                "stake1uxpdrerp9wrxunfh6ukyv5267j70fzxgw0fr3z8zeac5vyqhf9jhy"
+            ),
+            Err(GovernanceError::InvalidDRepId(_))
+        ));
+    }
+
+    #[test]
+    fn drep_id__matches_the_cip_105_test_vector() {
+        // given
+        let drep_vk = hex::decode(
+            "f74d7ac30513ac1825715fd0196769761fca6e7f69de33d04ef09a0c417a752b",
+        )
+        .unwrap();
+        let public_key = PublicKey::from_bytes(&drep_vk).unwrap();
+
+        // when
+        let id = DRepId::from_public_key(&public_key);
+
+        // then
+        assert_eq!(
+            hex::encode(id.key_hash()),
+            "a5b45515a3ff8cb7c02ce351834da324eb6dfc41b5779cb5e6b832aa"
+        );
+        assert_eq!(
+            id.to_string(),
+            "drep15k6929drl7xt0spvudgcxndryn4kmlzpk4meed0xhqe25nle07s"
+        );
+    }
+
+    #[test]
+    fn to_cbor__encodes_certificates_and_votes_per_conway_cddl() {
+        // given
+        let hash = vec![0xaa; KEY_HASH_LEN];
+        let registration = GovernanceCertificate::RegDRep {
+            drep: Credential::Key(hash.clone()),
+            deposit: 500,
+            anchor: None,
+        };
+        let delegation = GovernanceCertificate::VoteDelegation {
+            stake: Credential::Key(hash.clone()),
+            drep: DRep::AlwaysAbstain,
+        };
+        let votes = VotingProcedures::default().with_vote(
+            Voter::DRepKey(hash.clone()),
+            GovActionId::new(vec![0xbb; 32], 1),
+            VotingProcedure::new(Vote::Yes),
+        );
+
+        // when
+        let registration = registration.to_cbor().unwrap();
+        let delegation = delegation.to_cbor().unwrap();
+        let votes = votes.to_cbor().unwrap();
+
+        // then
+        let key_credential = [&[0x82, 0x00, 0x58, 0x1c][..], &hash].concat();
+        assert_eq!(
+            registration,
+            [
+                &[0x84, 0x10][..],
+                &key_credential,
+                &[0x19, 0x01, 0xf4, 0xf6]
+            ]
+            .concat()
+        );
+        assert_eq!(
+            delegation,
+            [&[0x83, 0x09][..], &key_credential, &[0x81, 0x02]].concat()
+        );
+        let voter = [&[0x82, 0x02, 0x58, 0x1c][..], &hash].concat();
+        let action = [&[0x82, 0x58, 0x20][..], &[0xbb; 32], &[0x01]].concat();
+        assert_eq!(
+            votes,
+            [&[0xa1][..], &voter, &[0xa1], &action, &[0x82, 0x01, 0xf6]].concat()
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use super::{
+    error::*,
+    Keys,
+};
+use async_trait::async_trait;
+use cardano_multiplatform_lib::{
+    address::{
+        BaseAddress,
+        StakeCredential,
+    },
+    crypto::{
+        Bip32PublicKey,
+        PrivateKey,
+        PublicKey,
+        Vkeywitness,
+    },
+    ledger::common::value::Int,
+    Transaction as CMLTransaction,
+    TransactionBody,
+};
+use thiserror::Error;
+
+/// Ledger hardware wallet device module
+#[cfg(feature = "ledger")]
+pub mod ledger;
+/// Trezor hardware wallet device module
+pub mod trezor;
+
+/// Interface for a hardware signing device
+///
+/// Private keys never leave the device; it only hands out public keys and signatures.
+#[async_trait]
+pub trait HardwareDevice {
+    /// Get the extended public key at the BIP32 `path`
+    async fn extended_public_key(&self, path: &[u32]) -> Result<Bip32PublicKey>;
+    /// Have the device sign `tx` with the key at each of the `signing_paths`
+    async fn sign_tx(
+        &self,
+        tx: &CMLTransaction,
+        signing_paths: &[Vec<u32>],
+    ) -> Result<Vec<Vkeywitness>>;
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum HardwareWalletError {
+    #[error("Private keys can't be exported from a hardware wallet")]
+    PrivateKeyUnavailable,
+    #[error("Device error: {0:?}")]
+    Device(String),
+    #[error("Not supported by the device: {0:?}")]
+    Unsupported(String),
+}
+
+/// [`Keys`] implementation backed by a [`HardwareDevice`]
+///
+/// Uses the standard CIP-1852 account at index `account`, with the payment key at
+/// `0/address_index` and the stake key at `2/0`.
+pub struct HardwareWalletKeys<D: HardwareDevice> {
+    device: D,
+    account: u32,
+    address_index: u32,
+    network: u8,
+}
+
+impl<D: HardwareDevice> HardwareWalletKeys<D> {
+    /// Constructor for the [`HardwareWalletKeys`] struct
+    pub fn new(device: D, account: u32, network: u8) -> Self {
+        HardwareWalletKeys {
+            device,
+            account,
+            address_index: 0,
+            network,
+        }
+    }
+
+    /// Use a different payment key index within the account
+    pub fn with_address_index(mut self, address_index: u32) -> Self {
+        self.address_index = address_index;
+        self
+    }
+
+    fn account_path(&self) -> Vec<u32> {
+        vec![harden(1852), harden(1815), harden(self.account)]
+    }
+
+    fn payment_key_path(&self) -> Vec<u32> {
+        let mut path = self.account_path();
+        path.extend([0, self.address_index]);
+        path
+    }
+}
+
+#[async_trait]
+impl<D: HardwareDevice + Send + Sync> Keys for HardwareWalletKeys<D> {
+    async fn base_addr(&self) -> Result<BaseAddress> {
+        let account_key = self
+            .device
+            .extended_public_key(&self.account_path())
+            .await?;
+        base_addr_from_account_key(&account_key, self.address_index, self.network)
+    }
+
+    async fn private_key(&self) -> Result<PrivateKey> {
+        Err(CMLLCError::KeyError(Box::new(
+            HardwareWalletError::PrivateKeyUnavailable,
+        )))
+    }
+
+    async fn vkey_witnesses(&self, tx: &CMLTransaction) -> Result<Vec<Vkeywitness>> {
+        self.device.sign_tx(tx, &[self.payment_key_path()]).await
+    }
+
+    async fn payment_public_key(&self) -> Result<PublicKey> {
+        let account_key = self
+            .device
+            .extended_public_key(&self.account_path())
+            .await?;
+        let pub_key = derive_soft(&account_key, 0, self.address_index)?;
+        Ok(pub_key.to_raw_key())
+    }
+}
+
+/// Base address of the payment key at `address_index` and the first stake key of an
+/// account
+pub(crate) fn base_addr_from_account_key(
+    account_key: &Bip32PublicKey,
+    address_index: u32,
+    network: u8,
+) -> Result<BaseAddress> {
+    let pub_key = derive_soft(account_key, 0, address_index)?;
+    let stake_key = derive_soft(account_key, 2, 0)?;
+    let pub_key_creds = StakeCredential::from_keyhash(&pub_key.to_raw_key().hash());
+    let stake_key_creds = StakeCredential::from_keyhash(&stake_key.to_raw_key().hash());
+    Ok(BaseAddress::new(network, &pub_key_creds, &stake_key_creds))
+}
+
+pub(crate) fn derive_soft(
+    account_key: &Bip32PublicKey,
+    role: u32,
+    index: u32,
+) -> Result<Bip32PublicKey> {
+    account_key
+        .derive(role)
+        .and_then(|key| key.derive(index))
+        .map_err(|e| CMLLCError::JsError(e.to_string()))
+}
+
+pub(crate) fn harden(index: u32) -> u32 {
+    index | 0x80_00_00_00
+}
+
+// Devices walk the user through every body field, so we only send the ones we know
+fn check_supported(body: &TransactionBody) -> Result<()> {
+    let unsupported_fields = [
+        ("certificates", body.certs().is_some()),
+        ("withdrawals", body.withdrawals().is_some()),
+        ("auxiliary data", body.auxiliary_data_hash().is_some()),
+        ("collateral return", body.collateral_return().is_some()),
+        ("total collateral", body.total_collateral().is_some()),
+        ("reference inputs", body.reference_inputs().is_some()),
+        ("network id", body.network_id().is_some()),
+    ];
+    for (name, present) in unsupported_fields {
+        if present {
+            return Err(unsupported(name))
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn int_to_i64(int: &Int) -> i64 {
+    match (int.as_positive(), int.as_negative()) {
+        (Some(positive), _) => u64::from(positive) as i64,
+        (_, Some(negative)) => -(u64::from(negative) as i64),
+        _ => 0,
+    }
+}
+
+fn device_error<E: ToString>(error: E) -> CMLLCError {
+    CMLLCError::KeyError(Box::new(HardwareWalletError::Device(error.to_string())))
+}
+
+fn unsupported(feature: &str) -> CMLLCError {
+    CMLLCError::KeyError(Box::new(HardwareWalletError::Unsupported(
+        feature.to_string(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::trireme_ledger_client::cml_client::secret_phrase::{
+        account_key_to_payment_key,
+        private_key_to_base_address,
+        private_key_to_base_address_at,
+        secret_phrase_to_account_key,
+        DerivationPath,
+    };
+    use bip39::{
+        Language,
+        Mnemonic,
+    };
+    use cardano_multiplatform_lib::{
+        crypto::{
+            Bip32PrivateKey,
+            TransactionHash,
+        },
+        ledger::{
+            common::{
+                hash::hash_transaction,
+                value::BigNum,
+            },
+            shelley::witness::make_vkey_witness,
+        },
+        TransactionInput,
+        TransactionInputs,
+        TransactionOutputs,
+        TransactionWitnessSet,
+    };
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon \
+        abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+        abandon abandon abandon abandon abandon abandon art";
+
+    struct SoftwareDevice {
+        root_key: Bip32PrivateKey,
+    }
+
+    impl SoftwareDevice {
+        fn new(phrase: &str) -> Self {
+            let mnemonic = Mnemonic::from_phrase(phrase, Language::English).unwrap();
+            let root_key = Bip32PrivateKey::from_bip39_entropy(mnemonic.entropy(), &[]);
+            SoftwareDevice { root_key }
+        }
+
+        fn key_at(&self, path: &[u32]) -> Bip32PrivateKey {
+            path.iter()
+                .fold(self.root_key.clone(), |key, index| key.derive(*index))
+        }
+    }
+
+    #[async_trait]
+    impl HardwareDevice for SoftwareDevice {
+        async fn extended_public_key(&self, path: &[u32]) -> Result<Bip32PublicKey> {
+            Ok(self.key_at(path).to_public())
+        }
+
+        async fn sign_tx(
+            &self,
+            tx: &CMLTransaction,
+            signing_paths: &[Vec<u32>],
+        ) -> Result<Vec<Vkeywitness>> {
+            let tx_hash = hash_transaction(&tx.body());
+            let witnesses = signing_paths
+                .iter()
+                .map(|path| make_vkey_witness(&tx_hash, &self.key_at(path).to_raw_key()))
+                .collect();
+            Ok(witnesses)
+        }
+    }
+
+    fn unsigned_tx() -> CMLTransaction {
+        let mut inputs = TransactionInputs::new();
+        let input_hash = TransactionHash::from_bytes(vec![7; 32]).unwrap();
+        inputs.add(&TransactionInput::new(&input_hash, &BigNum::from(0)));
+        let body = TransactionBody::new(
+            &inputs,
+            &TransactionOutputs::new(),
+            &BigNum::from(170_000),
+            None,
+        );
+        CMLTransaction::new(&body, &TransactionWitnessSet::new(), None)
+    }
+
+    #[tokio::test]
+    async fn base_addr__matches_secret_phrase_derivation() {
+        // given
+        let network = 0;
+        let keys = HardwareWalletKeys::new(SoftwareDevice::new(PHRASE), 0, network);
+
+        // when
+        let base_addr = keys.base_addr().await.unwrap();
+
+        // then
+        let account_key = secret_phrase_to_account_key(PHRASE).unwrap();
+        let expected = private_key_to_base_address(&account_key, network);
+        assert_eq!(
+            base_addr.to_address().to_bytes(),
+            expected.to_address().to_bytes()
+        );
+    }
+
+    #[tokio::test]
+    async fn vkey_witnesses__are_signed_by_the_payment_key() {
+        // given
+        let keys = HardwareWalletKeys::new(SoftwareDevice::new(PHRASE), 0, 0);
+        let tx = unsigned_tx();
+
+        // when
+        let witnesses = keys.vkey_witnesses(&tx).await.unwrap();
+
+        // then
+        let payment_key = keys.payment_public_key().await.unwrap();
+        let tx_hash = hash_transaction(&tx.body()).to_bytes();
+        assert_eq!(witnesses.len(), 1);
+        let witness = &witnesses[0];
+        assert_eq!(
+            witness.vkey().public_key().to_bytes(),
+            payment_key.to_bytes()
+        );
+        assert!(payment_key.verify(&tx_hash, &witness.signature()));
+    }
+
+    #[tokio::test]
+    async fn payment_public_key__uses_the_configured_address_index() {
+        // given
+        let network = 0;
+        let keys = HardwareWalletKeys::new(SoftwareDevice::new(PHRASE), 0, network)
+            .with_address_index(3);
+
+        // when
+        let payment_key = keys.payment_public_key().await.unwrap();
+        let base_addr = keys.base_addr().await.unwrap();
+
+        // then
+        let account_key = secret_phrase_to_account_key(PHRASE).unwrap();
+        let path = DerivationPath::new(0).with_address_index(3);
+        let expected_key = account_key_to_payment_key(&account_key, &path).to_public();
+        let expected_addr = private_key_to_base_address_at(&account_key, network, &path);
+        assert_eq!(payment_key.to_bytes(), expected_key.to_bytes());
+        assert_eq!(
+            base_addr.to_address().to_bytes(),
+            expected_addr.to_address().to_bytes()
+        );
+    }
+
+    #[tokio::test]
+    async fn private_key__is_never_exposed() {
+        // given
+        let keys = HardwareWalletKeys::new(SoftwareDevice::new(PHRASE), 0, 0);
+
+        // when
+        let res = keys.private_key().await;
+
+        // then
+        assert!(res.is_err());
+    }
+}
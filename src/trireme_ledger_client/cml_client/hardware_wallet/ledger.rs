@@ -0,0 +1,363 @@
+use super::{
+    check_supported,
+    device_error,
+    int_to_i64,
+    unsupported,
+    HardwareDevice,
+};
+use crate::trireme_ledger_client::cml_client::error::*;
+use async_trait::async_trait;
+use cardano_multiplatform_lib::{
+    crypto::{
+        Bip32PublicKey,
+        Ed25519Signature,
+        Vkey,
+        Vkeywitness,
+    },
+    ledger::common::hash::hash_transaction,
+    Transaction as CMLTransaction,
+    TransactionBody,
+    TransactionInput,
+    TransactionOutput,
+};
+use ledger_transport::APDUCommand;
+use ledger_transport_hid::{
+    hidapi::HidApi,
+    TransportNativeHID,
+};
+
+// Constants of the Cardano Ledger app's APDU protocol.
+// https://github.com/LedgerHQ/app-cardano/tree/develop/doc
+const CLA: u8 = 0xD7;
+const INS_GET_EXT_PUBLIC_KEY: u8 = 0x10;
+const INS_SIGN_TX: u8 = 0x21;
+const SW_OK: u16 = 0x9000;
+
+const STAGE_INIT: u8 = 0x01;
+const STAGE_INPUTS: u8 = 0x02;
+const STAGE_OUTPUTS: u8 = 0x03;
+const STAGE_FEE: u8 = 0x04;
+const STAGE_TTL: u8 = 0x05;
+const STAGE_VALIDITY_INTERVAL_START: u8 = 0x09;
+const STAGE_CONFIRM: u8 = 0x0a;
+const STAGE_MINT: u8 = 0x0b;
+const STAGE_SCRIPT_DATA_HASH: u8 = 0x0c;
+const STAGE_COLLATERAL_INPUTS: u8 = 0x0d;
+const STAGE_REQUIRED_SIGNERS: u8 = 0x0e;
+const STAGE_WITNESSES: u8 = 0x0f;
+
+const P2_BASIC_DATA: u8 = 0x30;
+const P2_ASSET_GROUP: u8 = 0x31;
+const P2_TOKEN: u8 = 0x32;
+const P2_CONFIRM: u8 = 0x33;
+const P2_DATUM: u8 = 0x34;
+
+const YES: u8 = 0x01;
+const NO: u8 = 0x02;
+const SIGNING_MODE_PLUTUS_TRANSACTION: u8 = 0x07;
+const OUTPUT_FORMAT_ARRAY_LEGACY: u8 = 0x00;
+const OUTPUT_FORMAT_MAP_BABBAGE: u8 = 0x01;
+const DESTINATION_THIRD_PARTY: u8 = 0x01;
+const DATUM_HASH: u8 = 0x00;
+const DATUM_INLINE: u8 = 0x01;
+const REQUIRED_SIGNER_HASH: u8 = 0x00;
+
+// Inline datums larger than this have to be streamed in chunks, which isn't supported yet
+const MAX_INLINE_DATUM_CHUNK: usize = 240;
+
+/// [`HardwareDevice`] implementation for Ledger devices running the Cardano app
+///
+/// Only the transaction features Naumachia builds are supported: inputs, outputs,
+/// minting, validity range, Plutus script data and collateral.
+pub struct LedgerDevice {
+    transport: TransportNativeHID,
+    network_id: u8,
+    protocol_magic: u32,
+}
+
+impl LedgerDevice {
+    /// Connect to the first Ledger device found over USB
+    pub fn connect(network_id: u8, protocol_magic: u32) -> Result<Self> {
+        let api = HidApi::new().map_err(device_error)?;
+        let transport = TransportNativeHID::new(&api).map_err(device_error)?;
+        Ok(LedgerDevice {
+            transport,
+            network_id,
+            protocol_magic,
+        })
+    }
+
+    fn exchange(&self, ins: u8, p1: u8, p2: u8, data: Vec<u8>) -> Result<Vec<u8>> {
+        let command = APDUCommand {
+            cla: CLA,
+            ins,
+            p1,
+            p2,
+            data,
+        };
+        let answer = self.transport.exchange(&command).map_err(device_error)?;
+        if answer.retcode() != SW_OK {
+            return Err(device_error(format!(
+                "Device returned status {:#06x}",
+                answer.retcode()
+            )))
+        }
+        Ok(answer.data().to_vec())
+    }
+
+    fn sign_tx_stage(&self, p1: u8, p2: u8, data: Vec<u8>) -> Result<Vec<u8>> {
+        self.exchange(INS_SIGN_TX, p1, p2, data)
+    }
+
+    fn send_init(&self, body: &TransactionBody, witness_count: usize) -> Result<()> {
+        let mut data = vec![self.network_id];
+        data.extend(self.protocol_magic.to_be_bytes());
+        data.push(flag(body.ttl().is_some()));
+        data.push(NO); // auxiliary data
+        data.push(flag(body.validity_start_interval().is_some()));
+        data.push(flag(body.mint().is_some()));
+        data.push(flag(body.script_data_hash().is_some()));
+        data.push(flag(body.collateral().is_some()));
+        data.push(flag(body.required_signers().is_some()));
+        data.push(NO); // network id
+        data.push(NO); // collateral return
+        data.push(NO); // total collateral
+        data.push(NO); // reference inputs
+        data.push(SIGNING_MODE_PLUTUS_TRANSACTION);
+        let counts = [
+            body.inputs().len(),
+            body.outputs().len(),
+            0, // certificates
+            0, // withdrawals
+            body.collateral().map(|inputs| inputs.len()).unwrap_or(0),
+            body.required_signers().map(|keys| keys.len()).unwrap_or(0),
+            0, // reference inputs
+            witness_count,
+        ];
+        for count in counts {
+            data.extend((count as u32).to_be_bytes());
+        }
+        self.sign_tx_stage(STAGE_INIT, 0, data)?;
+        Ok(())
+    }
+
+    fn send_output(&self, output: &TransactionOutput) -> Result<()> {
+        let datum = output.datum();
+        let inline_datum = datum.as_ref().and_then(|datum| datum.as_inline_data());
+        let format = if inline_datum.is_some() {
+            OUTPUT_FORMAT_MAP_BABBAGE
+        } else {
+            OUTPUT_FORMAT_ARRAY_LEGACY
+        };
+        let amount = output.amount();
+        let multiasset = amount.multiasset();
+        let address = output.address().to_bytes();
+
+        let mut data = vec![format, DESTINATION_THIRD_PARTY];
+        data.extend((address.len() as u32).to_be_bytes());
+        data.extend(address);
+        data.extend(u64::from(amount.coin()).to_be_bytes());
+        let group_count = multiasset.as_ref().map(|ma| ma.len()).unwrap_or(0);
+        data.extend((group_count as u32).to_be_bytes());
+        data.push(flag(datum.is_some()));
+        data.push(NO); // reference script
+        self.sign_tx_stage(STAGE_OUTPUTS, P2_BASIC_DATA, data)?;
+
+        if let Some(multiasset) = multiasset {
+            let policies = multiasset.keys();
+            for i in 0..policies.len() {
+                let policy = policies.get(i);
+                let assets = multiasset
+                    .get(&policy)
+                    .ok_or_else(|| unsupported("empty asset group"))?;
+                let names = assets.keys();
+                let mut group = policy.to_bytes();
+                group.extend((names.len() as u32).to_be_bytes());
+                self.sign_tx_stage(STAGE_OUTPUTS, P2_ASSET_GROUP, group)?;
+                for j in 0..names.len() {
+                    let name = names.get(j);
+                    let quantity = assets.get(&name).map(u64::from).unwrap_or(0);
+                    let mut token = serialize_asset_name(&name.name());
+                    token.extend(quantity.to_be_bytes());
+                    self.sign_tx_stage(STAGE_OUTPUTS, P2_TOKEN, token)?;
+                }
+            }
+        }
+
+        if let Some(datum) = datum {
+            let mut data = Vec::new();
+            if let Some(hash) = datum.as_data_hash() {
+                data.push(DATUM_HASH);
+                data.extend(hash.to_bytes());
+            } else if let Some(inline) = inline_datum {
+                let bytes = inline.to_bytes();
+                if bytes.len() > MAX_INLINE_DATUM_CHUNK {
+                    return Err(unsupported("inline datums over 240 bytes"))
+                }
+                data.push(DATUM_INLINE);
+                data.extend((bytes.len() as u32).to_be_bytes());
+                data.extend((bytes.len() as u32).to_be_bytes());
+                data.extend(bytes);
+            }
+            self.sign_tx_stage(STAGE_OUTPUTS, P2_DATUM, data)?;
+        }
+
+        self.sign_tx_stage(STAGE_OUTPUTS, P2_CONFIRM, Vec::new())?;
+        Ok(())
+    }
+
+    fn send_mint(&self, body: &TransactionBody) -> Result<()> {
+        if let Some(mint) = body.mint() {
+            let policies = mint.keys();
+            let count = policies.len() as u32;
+            self.sign_tx_stage(STAGE_MINT, P2_BASIC_DATA, count.to_be_bytes().to_vec())?;
+            for i in 0..policies.len() {
+                let policy = policies.get(i);
+                let assets =
+                    mint.get(&policy).ok_or_else(|| unsupported("empty mint"))?;
+                let names = assets.keys();
+                let mut group = policy.to_bytes();
+                group.extend((names.len() as u32).to_be_bytes());
+                self.sign_tx_stage(STAGE_MINT, P2_ASSET_GROUP, group)?;
+                for j in 0..names.len() {
+                    let name = names.get(j);
+                    let amount = assets
+                        .get(&name)
+                        .map(|amount| int_to_i64(&amount))
+                        .unwrap_or(0);
+                    let mut token = serialize_asset_name(&name.name());
+                    token.extend(amount.to_be_bytes());
+                    self.sign_tx_stage(STAGE_MINT, P2_TOKEN, token)?;
+                }
+            }
+            self.sign_tx_stage(STAGE_MINT, P2_CONFIRM, Vec::new())?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl HardwareDevice for LedgerDevice {
+    async fn extended_public_key(&self, path: &[u32]) -> Result<Bip32PublicKey> {
+        let response =
+            self.exchange(INS_GET_EXT_PUBLIC_KEY, 0, 0, serialize_path(path))?;
+        // 32 byte public key followed by 32 byte chain code
+        let bytes = response
+            .get(..64)
+            .ok_or_else(|| device_error("Extended public key response too short"))?;
+        Bip32PublicKey::from_bytes(bytes).map_err(|e| CMLLCError::JsError(e.to_string()))
+    }
+
+    async fn sign_tx(
+        &self,
+        tx: &CMLTransaction,
+        signing_paths: &[Vec<u32>],
+    ) -> Result<Vec<Vkeywitness>> {
+        let body = tx.body();
+        check_supported(&body)?;
+        // The device only answers witness requests once signing has started
+        let mut pub_keys = Vec::new();
+        for path in signing_paths {
+            pub_keys.push(self.extended_public_key(path).await?.to_raw_key());
+        }
+
+        self.send_init(&body, signing_paths.len())?;
+        let inputs = body.inputs();
+        for i in 0..inputs.len() {
+            self.sign_tx_stage(STAGE_INPUTS, 0, serialize_input(&inputs.get(i)))?;
+        }
+        let outputs = body.outputs();
+        for i in 0..outputs.len() {
+            self.send_output(&outputs.get(i))?;
+        }
+        let fee = u64::from(body.fee()).to_be_bytes().to_vec();
+        self.sign_tx_stage(STAGE_FEE, 0, fee)?;
+        if let Some(ttl) = body.ttl() {
+            self.sign_tx_stage(STAGE_TTL, 0, u64::from(ttl).to_be_bytes().to_vec())?;
+        }
+        if let Some(start) = body.validity_start_interval() {
+            let start = u64::from(start).to_be_bytes().to_vec();
+            self.sign_tx_stage(STAGE_VALIDITY_INTERVAL_START, 0, start)?;
+        }
+        self.send_mint(&body)?;
+        if let Some(hash) = body.script_data_hash() {
+            self.sign_tx_stage(STAGE_SCRIPT_DATA_HASH, 0, hash.to_bytes())?;
+        }
+        if let Some(collateral) = body.collateral() {
+            for i in 0..collateral.len() {
+                let input = serialize_input(&collateral.get(i));
+                self.sign_tx_stage(STAGE_COLLATERAL_INPUTS, 0, input)?;
+            }
+        }
+        if let Some(signers) = body.required_signers() {
+            for i in 0..signers.len() {
+                let mut signer = vec![REQUIRED_SIGNER_HASH];
+                signer.extend(signers.get(i).to_bytes());
+                self.sign_tx_stage(STAGE_REQUIRED_SIGNERS, 0, signer)?;
+            }
+        }
+
+        // The device hashes the body it was walked through; make sure it's the one we built
+        let device_tx_hash = self.sign_tx_stage(STAGE_CONFIRM, 0, Vec::new())?;
+        if device_tx_hash != hash_transaction(&body).to_bytes() {
+            return Err(device_error("Device signed a different transaction body"))
+        }
+
+        let mut witnesses = Vec::new();
+        for (path, pub_key) in signing_paths.iter().zip(pub_keys) {
+            let response =
+                self.sign_tx_stage(STAGE_WITNESSES, 0, serialize_path(path))?;
+            let signature = Ed25519Signature::from_bytes(response)
+                .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+            witnesses.push(Vkeywitness::new(&Vkey::new(&pub_key), &signature));
+        }
+        Ok(witnesses)
+    }
+}
+
+fn flag(included: bool) -> u8 {
+    if included {
+        YES
+    } else {
+        NO
+    }
+}
+
+fn serialize_path(path: &[u32]) -> Vec<u8> {
+    let mut data = vec![path.len() as u8];
+    for index in path {
+        data.extend(index.to_be_bytes());
+    }
+    data
+}
+
+fn serialize_input(input: &TransactionInput) -> Vec<u8> {
+    let mut data = input.transaction_id().to_bytes();
+    data.extend((u64::from(input.index()) as u32).to_be_bytes());
+    data
+}
+
+fn serialize_asset_name(name: &[u8]) -> Vec<u8> {
+    let mut data = (name.len() as u32).to_be_bytes().to_vec();
+    data.extend(name);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn serialize_path__prefixes_length_and_uses_big_endian() {
+        let path = [0x8000_073c, 0x8000_0717, 0x8000_0000, 0, 1];
+
+        let data = serialize_path(&path);
+
+        assert_eq!(data[0], 5);
+        assert_eq!(&data[1..5], &[0x80, 0x00, 0x07, 0x3c]);
+        assert_eq!(&data[17..21], &[0, 0, 0, 1]);
+        assert_eq!(data.len(), 21);
+    }
+}
@@ -0,0 +1,579 @@
+use super::{
+    check_supported,
+    device_error,
+    int_to_i64,
+    unsupported,
+    HardwareDevice,
+};
+use crate::trireme_ledger_client::cml_client::error::*;
+use async_trait::async_trait;
+use cardano_multiplatform_lib::{
+    crypto::{
+        Bip32PublicKey,
+        Ed25519Signature,
+        PublicKey,
+        Vkey,
+        Vkeywitness,
+    },
+    ledger::common::hash::hash_transaction,
+    MultiAsset,
+    Transaction as CMLTransaction,
+    TransactionBody,
+    TransactionInput,
+    TransactionOutput,
+};
+use serde::Deserialize;
+
+// Message type ids from Trezor's `messages.proto`
+const FAILURE: u16 = 3;
+const BUTTON_REQUEST: u16 = 26;
+const BUTTON_ACK: u16 = 27;
+const CARDANO_GET_PUBLIC_KEY: u16 = 305;
+const CARDANO_PUBLIC_KEY: u16 = 306;
+const CARDANO_TX_ITEM_ACK: u16 = 313;
+const CARDANO_TX_WITNESS_REQUEST: u16 = 315;
+const CARDANO_TX_WITNESS_RESPONSE: u16 = 316;
+const CARDANO_TX_HOST_ACK: u16 = 317;
+const CARDANO_TX_BODY_HASH: u16 = 318;
+const CARDANO_SIGN_TX_FINISHED: u16 = 319;
+const CARDANO_SIGN_TX_INIT: u16 = 320;
+const CARDANO_TX_INPUT: u16 = 321;
+const CARDANO_TX_OUTPUT: u16 = 322;
+const CARDANO_ASSET_GROUP: u16 = 323;
+const CARDANO_TOKEN: u16 = 324;
+const CARDANO_TX_MINT: u16 = 332;
+const CARDANO_TX_COLLATERAL_INPUT: u16 = 333;
+const CARDANO_TX_REQUIRED_SIGNER: u16 = 334;
+const CARDANO_TX_INLINE_DATUM_CHUNK: u16 = 335;
+
+const SIGNING_MODE_PLUTUS_TRANSACTION: u64 = 3;
+// Matches the derivation Naumachia uses for secret phrases
+const DERIVATION_TYPE_ICARUS: u64 = 1;
+const OUTPUT_FORMAT_ARRAY_LEGACY: u64 = 0;
+const OUTPUT_FORMAT_MAP_BABBAGE: u64 = 1;
+const MAX_INLINE_DATUM_CHUNK: usize = 1024;
+
+/// Default address of a locally running Trezor Bridge
+pub const DEFAULT_BRIDGE_URL: &str = "http://127.0.0.1:21325";
+
+#[derive(Deserialize)]
+struct BridgeDevice {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct BridgeSession {
+    session: String,
+}
+
+/// [`HardwareDevice`] implementation for Trezor devices, talking through Trezor Bridge
+///
+/// Only the transaction features Naumachia builds are supported: inputs, outputs,
+/// minting, validity range, Plutus script data and collateral.
+pub struct TrezorDevice {
+    client: reqwest::Client,
+    bridge_url: String,
+    session: String,
+    network_id: u8,
+    protocol_magic: u32,
+}
+
+impl TrezorDevice {
+    /// Connect to the first Trezor device found by the local Trezor Bridge
+    pub async fn connect(network_id: u8, protocol_magic: u32) -> Result<Self> {
+        Self::connect_to_bridge(DEFAULT_BRIDGE_URL, network_id, protocol_magic).await
+    }
+
+    /// Connect to the first Trezor device found by the Trezor Bridge at `bridge_url`
+    pub async fn connect_to_bridge(
+        bridge_url: &str,
+        network_id: u8,
+        protocol_magic: u32,
+    ) -> Result<Self> {
+        let client = reqwest::Client::new();
+        let devices: Vec<BridgeDevice> =
+            bridge_post(&client, bridge_url, "enumerate", String::new())
+                .await?
+                .json()
+                .await
+                .map_err(device_error)?;
+        let device = devices
+            .first()
+            .ok_or_else(|| device_error("No Trezor device found"))?;
+        let path = format!("acquire/{}/null", device.path);
+        let BridgeSession { session } =
+            bridge_post(&client, bridge_url, &path, String::new())
+                .await?
+                .json()
+                .await
+                .map_err(device_error)?;
+        Ok(TrezorDevice {
+            client,
+            bridge_url: bridge_url.to_string(),
+            session,
+            network_id,
+            protocol_magic,
+        })
+    }
+
+    async fn call(&self, message_type: u16, message: Vec<u8>) -> Result<(u16, Vec<u8>)> {
+        let mut request = (message_type, message);
+        loop {
+            let (message_type, message) = request;
+            let mut body = message_type.to_be_bytes().to_vec();
+            body.extend((message.len() as u32).to_be_bytes());
+            body.extend(message);
+            let path = format!("call/{}", self.session);
+            let response =
+                bridge_post(&self.client, &self.bridge_url, &path, hex::encode(body))
+                    .await?
+                    .text()
+                    .await
+                    .map_err(device_error)?;
+            let bytes = hex::decode(response.trim()).map_err(device_error)?;
+            if bytes.len() < 6 {
+                return Err(device_error("Malformed response from Trezor Bridge"))
+            }
+            let response_type = u16::from_be_bytes([bytes[0], bytes[1]]);
+            let payload = bytes[6..].to_vec();
+            match response_type {
+                BUTTON_REQUEST => request = (BUTTON_ACK, Vec::new()),
+                FAILURE => {
+                    let reason = read_fields(&payload)?
+                        .into_iter()
+                        .find_map(|(field, value)| match (field, value) {
+                            (2, Field::Bytes(bytes)) => {
+                                Some(String::from_utf8_lossy(&bytes).to_string())
+                            }
+                            _ => None,
+                        })
+                        .unwrap_or_else(|| "Unknown failure".to_string());
+                    return Err(device_error(reason))
+                }
+                _ => return Ok((response_type, payload)),
+            }
+        }
+    }
+
+    async fn call_expecting(
+        &self,
+        message_type: u16,
+        message: Vec<u8>,
+        expected: u16,
+    ) -> Result<Vec<u8>> {
+        let (response_type, payload) = self.call(message_type, message).await?;
+        if response_type != expected {
+            return Err(device_error(format!(
+                "Expected message {expected} from device, got {response_type}"
+            )))
+        }
+        Ok(payload)
+    }
+
+    async fn send_item(&self, message_type: u16, message: Vec<u8>) -> Result<()> {
+        self.call_expecting(message_type, message, CARDANO_TX_ITEM_ACK)
+            .await?;
+        Ok(())
+    }
+
+    async fn send_init(
+        &self,
+        body: &TransactionBody,
+        witness_count: usize,
+    ) -> Result<()> {
+        let mut init = Proto::default()
+            .uint(1, SIGNING_MODE_PLUTUS_TRANSACTION)
+            .uint(2, self.protocol_magic as u64)
+            .uint(3, self.network_id as u64)
+            .uint(4, body.inputs().len() as u64)
+            .uint(5, body.outputs().len() as u64)
+            .uint(6, u64::from(body.fee()))
+            .uint(8, 0) // certificates
+            .uint(9, 0) // withdrawals
+            .uint(10, 0) // auxiliary data
+            .uint(12, witness_count as u64)
+            .uint(13, body.mint().map(|mint| mint.len()).unwrap_or(0) as u64)
+            .uint(14, DERIVATION_TYPE_ICARUS)
+            .uint(15, 0) // network id
+            .uint(
+                17,
+                body.collateral().map(|inputs| inputs.len()).unwrap_or(0) as u64,
+            )
+            .uint(
+                18,
+                body.required_signers().map(|keys| keys.len()).unwrap_or(0) as u64,
+            )
+            .uint(19, 0) // collateral return
+            .uint(21, 0); // reference inputs
+        if let Some(ttl) = body.ttl() {
+            init = init.uint(7, u64::from(ttl));
+        }
+        if let Some(start) = body.validity_start_interval() {
+            init = init.uint(11, u64::from(start));
+        }
+        if let Some(hash) = body.script_data_hash() {
+            init = init.bytes(16, &hash.to_bytes());
+        }
+        self.send_item(CARDANO_SIGN_TX_INIT, init.build()).await
+    }
+
+    async fn send_output(&self, output: &TransactionOutput) -> Result<()> {
+        let datum = output.datum();
+        let inline_datum = datum
+            .as_ref()
+            .and_then(|datum| datum.as_inline_data())
+            .map(|data| data.to_bytes());
+        let amount = output.amount();
+        let multiasset = amount.multiasset();
+        let mut message = Proto::default()
+            .string(1, &output.address().to_bech32(None).map_err(js_error)?)
+            .uint(3, u64::from(amount.coin()))
+            .uint(
+                4,
+                multiasset.as_ref().map(|ma| ma.len()).unwrap_or(0) as u64,
+            );
+        if let Some(hash) = datum.as_ref().and_then(|datum| datum.as_data_hash()) {
+            message = message.bytes(5, &hash.to_bytes());
+        }
+        message = match &inline_datum {
+            Some(bytes) => message
+                .uint(6, OUTPUT_FORMAT_MAP_BABBAGE)
+                .uint(7, bytes.len() as u64),
+            None => message.uint(6, OUTPUT_FORMAT_ARRAY_LEGACY),
+        };
+        self.send_item(CARDANO_TX_OUTPUT, message.build()).await?;
+
+        if let Some(multiasset) = multiasset {
+            self.send_output_assets(&multiasset).await?;
+        }
+        if let Some(bytes) = inline_datum {
+            for chunk in bytes.chunks(MAX_INLINE_DATUM_CHUNK) {
+                let message = Proto::default().bytes(1, chunk).build();
+                self.send_item(CARDANO_TX_INLINE_DATUM_CHUNK, message)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_output_assets(&self, multiasset: &MultiAsset) -> Result<()> {
+        let policies = multiasset.keys();
+        for i in 0..policies.len() {
+            let policy = policies.get(i);
+            let assets = multiasset
+                .get(&policy)
+                .ok_or_else(|| unsupported("empty asset group"))?;
+            let names = assets.keys();
+            let group = Proto::default()
+                .bytes(1, &policy.to_bytes())
+                .uint(2, names.len() as u64)
+                .build();
+            self.send_item(CARDANO_ASSET_GROUP, group).await?;
+            for j in 0..names.len() {
+                let name = names.get(j);
+                let quantity = assets.get(&name).map(u64::from).unwrap_or(0);
+                let token = Proto::default()
+                    .bytes(1, &name.name())
+                    .uint(2, quantity)
+                    .build();
+                self.send_item(CARDANO_TOKEN, token).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_mint(&self, body: &TransactionBody) -> Result<()> {
+        if let Some(mint) = body.mint() {
+            let policies = mint.keys();
+            let message = Proto::default().uint(1, policies.len() as u64).build();
+            self.send_item(CARDANO_TX_MINT, message).await?;
+            for i in 0..policies.len() {
+                let policy = policies.get(i);
+                let assets =
+                    mint.get(&policy).ok_or_else(|| unsupported("empty mint"))?;
+                let names = assets.keys();
+                let group = Proto::default()
+                    .bytes(1, &policy.to_bytes())
+                    .uint(2, names.len() as u64)
+                    .build();
+                self.send_item(CARDANO_ASSET_GROUP, group).await?;
+                for j in 0..names.len() {
+                    let name = names.get(j);
+                    let amount = assets
+                        .get(&name)
+                        .map(|amount| int_to_i64(&amount))
+                        .unwrap_or(0);
+                    let token = Proto::default()
+                        .bytes(1, &name.name())
+                        .sint(3, amount)
+                        .build();
+                    self.send_item(CARDANO_TOKEN, token).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl HardwareDevice for TrezorDevice {
+    async fn extended_public_key(&self, path: &[u32]) -> Result<Bip32PublicKey> {
+        let message = Proto::default()
+            .repeated_uints(1, path)
+            .uint(2, 0) // show_display
+            .uint(3, DERIVATION_TYPE_ICARUS)
+            .build();
+        let response = self
+            .call_expecting(CARDANO_GET_PUBLIC_KEY, message, CARDANO_PUBLIC_KEY)
+            .await?;
+        let xpub = read_fields(&response)?
+            .into_iter()
+            .find_map(|(field, value)| match (field, value) {
+                (1, Field::Bytes(bytes)) => Some(bytes),
+                _ => None,
+            })
+            .ok_or_else(|| device_error("Public key missing from response"))?;
+        // Hex string of the 32 byte public key followed by the 32 byte chain code
+        let bytes = hex::decode(xpub).map_err(device_error)?;
+        Bip32PublicKey::from_bytes(&bytes).map_err(js_error)
+    }
+
+    async fn sign_tx(
+        &self,
+        tx: &CMLTransaction,
+        signing_paths: &[Vec<u32>],
+    ) -> Result<Vec<Vkeywitness>> {
+        let body = tx.body();
+        check_supported(&body)?;
+
+        self.send_init(&body, signing_paths.len()).await?;
+        let inputs = body.inputs();
+        for i in 0..inputs.len() {
+            self.send_item(CARDANO_TX_INPUT, serialize_input(&inputs.get(i)))
+                .await?;
+        }
+        let outputs = body.outputs();
+        for i in 0..outputs.len() {
+            self.send_output(&outputs.get(i)).await?;
+        }
+        self.send_mint(&body).await?;
+        if let Some(collateral) = body.collateral() {
+            for i in 0..collateral.len() {
+                let input = serialize_input(&collateral.get(i));
+                self.send_item(CARDANO_TX_COLLATERAL_INPUT, input).await?;
+            }
+        }
+        if let Some(signers) = body.required_signers() {
+            for i in 0..signers.len() {
+                let signer = Proto::default()
+                    .bytes(1, &signers.get(i).to_bytes())
+                    .build();
+                self.send_item(CARDANO_TX_REQUIRED_SIGNER, signer).await?;
+            }
+        }
+
+        let mut witnesses = Vec::new();
+        for path in signing_paths {
+            let request = Proto::default().repeated_uints(1, path).build();
+            let response = self
+                .call_expecting(
+                    CARDANO_TX_WITNESS_REQUEST,
+                    request,
+                    CARDANO_TX_WITNESS_RESPONSE,
+                )
+                .await?;
+            witnesses.push(witness_from_response(&response)?);
+        }
+
+        // The device hashes the body it was walked through; make sure it's the one we built
+        let response = self
+            .call_expecting(CARDANO_TX_HOST_ACK, Vec::new(), CARDANO_TX_BODY_HASH)
+            .await?;
+        let device_tx_hash =
+            read_fields(&response)?
+                .into_iter()
+                .find_map(|(field, value)| match (field, value) {
+                    (1, Field::Bytes(bytes)) => Some(bytes),
+                    _ => None,
+                });
+        if device_tx_hash != Some(hash_transaction(&body).to_bytes()) {
+            return Err(device_error("Device signed a different transaction body"))
+        }
+        self.call_expecting(CARDANO_TX_HOST_ACK, Vec::new(), CARDANO_SIGN_TX_FINISHED)
+            .await?;
+        Ok(witnesses)
+    }
+}
+
+async fn bridge_post(
+    client: &reqwest::Client,
+    bridge_url: &str,
+    path: &str,
+    body: String,
+) -> Result<reqwest::Response> {
+    let response = client
+        .post(format!("{bridge_url}/{path}"))
+        // Trezor Bridge only answers requests from known origins
+        .header("Origin", "https://python.trezor.io")
+        .body(body)
+        .send()
+        .await
+        .map_err(device_error)?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(device_error(format!(
+            "Trezor Bridge responded {status}: {text}"
+        )))
+    }
+    Ok(response)
+}
+
+fn witness_from_response(response: &[u8]) -> Result<Vkeywitness> {
+    let mut pub_key = None;
+    let mut signature = None;
+    for (field, value) in read_fields(response)? {
+        match (field, value) {
+            (2, Field::Bytes(bytes)) => pub_key = Some(bytes),
+            (3, Field::Bytes(bytes)) => signature = Some(bytes),
+            _ => {}
+        }
+    }
+    let pub_key = pub_key.ok_or_else(|| device_error("Witness missing public key"))?;
+    let signature = signature.ok_or_else(|| device_error("Witness missing signature"))?;
+    let pub_key = PublicKey::from_bytes(&pub_key).map_err(js_error)?;
+    let signature = Ed25519Signature::from_bytes(signature).map_err(js_error)?;
+    Ok(Vkeywitness::new(&Vkey::new(&pub_key), &signature))
+}
+
+fn serialize_input(input: &TransactionInput) -> Vec<u8> {
+    Proto::default()
+        .bytes(1, &input.transaction_id().to_bytes())
+        .uint(2, u64::from(input.index()))
+        .build()
+}
+
+fn js_error<E: ToString>(error: E) -> CMLLCError {
+    CMLLCError::JsError(error.to_string())
+}
+
+/// Minimal protobuf writer for the handful of messages we send
+#[derive(Default)]
+struct Proto {
+    buf: Vec<u8>,
+}
+
+impl Proto {
+    fn uint(mut self, field: u32, value: u64) -> Self {
+        write_varint(&mut self.buf, (field as u64) << 3);
+        write_varint(&mut self.buf, value);
+        self
+    }
+
+    fn sint(self, field: u32, value: i64) -> Self {
+        let zigzag = ((value << 1) ^ (value >> 63)) as u64;
+        self.uint(field, zigzag)
+    }
+
+    fn bytes(mut self, field: u32, value: &[u8]) -> Self {
+        write_varint(&mut self.buf, ((field as u64) << 3) | 2);
+        write_varint(&mut self.buf, value.len() as u64);
+        self.buf.extend(value);
+        self
+    }
+
+    fn string(self, field: u32, value: &str) -> Self {
+        self.bytes(field, value.as_bytes())
+    }
+
+    fn repeated_uints(self, field: u32, values: &[u32]) -> Self {
+        values
+            .iter()
+            .fold(self, |proto, value| proto.uint(field, *value as u64))
+    }
+
+    fn build(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+#[derive(Debug, PartialEq)]
+enum Field {
+    Varint(u64),
+    Bytes(Vec<u8>),
+}
+
+fn read_fields(mut bytes: &[u8]) -> Result<Vec<(u32, Field)>> {
+    let mut fields = Vec::new();
+    while !bytes.is_empty() {
+        let key = read_varint(&mut bytes)?;
+        let field = (key >> 3) as u32;
+        let value = match key & 0x7 {
+            0 => Field::Varint(read_varint(&mut bytes)?),
+            2 => {
+                let len = read_varint(&mut bytes)? as usize;
+                if bytes.len() < len {
+                    return Err(device_error("Truncated protobuf message"))
+                }
+                let (value, rest) = bytes.split_at(len);
+                bytes = rest;
+                Field::Bytes(value.to_vec())
+            }
+            wire_type => {
+                return Err(device_error(format!(
+                    "Unexpected protobuf wire type {wire_type}"
+                )))
+            }
+        };
+        fields.push((field, value));
+    }
+    Ok(fields)
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64> {
+    let slice: &[u8] = bytes;
+    let mut value = 0;
+    for (i, byte) in slice.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            *bytes = &slice[i + 1..];
+            return Ok(value)
+        }
+    }
+    Err(device_error("Malformed protobuf varint"))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn proto__roundtrips_through_reader() {
+        let message = Proto::default()
+            .uint(1, 300)
+            .bytes(2, &[1, 2, 3])
+            .sint(3, -2)
+            .build();
+
+        let fields = read_fields(&message).unwrap();
+
+        assert_eq!(
+            fields,
+            vec![
+                (1, Field::Varint(300)),
+                (2, Field::Bytes(vec![1, 2, 3])),
+                (3, Field::Varint(3)),
+            ]
+        );
+    }
+}
@@ -4,14 +4,27 @@ use crate::{
         LedgerClientError,
         LedgerClientResult,
     },
-    output::Output,
+    output::{
+        Output,
+        ReferenceScript,
+    },
+    protocol_params::ProtocolParameters,
     scripts::{
         MintingPolicy,
         Validator,
     },
+    transaction::{
+        era::{
+            Era,
+            EraFeature,
+        },
+        metadata::TxMetadata,
+        TransactionVersion,
+    },
     trireme_ledger_client::cml_client::{
         error::CMLLCError::JsError,
         plutus_data_interop::PlutusDataInterop,
+        Keys,
         UTxO,
     },
     values::Values,
@@ -38,20 +51,20 @@ use cardano_multiplatform_lib::{
         },
     },
     crypto::{
-        PrivateKey,
+        Ed25519KeyHash,
         ScriptHash,
         TransactionHash,
     },
     ledger::{
         alonzo::fees::LinearFee,
-        common::{
-            hash::hash_transaction,
-            value::{
-                Int,
-                Value as CMLValue,
-            },
+        common::value::{
+            Int,
+            Value as CMLValue,
         },
-        shelley::witness::make_vkey_witness,
+    },
+    metadata::{
+        AuxiliaryData,
+        GeneralTransactionMetadata,
     },
     plutus::{
         CostModel,
@@ -65,49 +78,70 @@ use cardano_multiplatform_lib::{
     AssetName,
     Assets,
     MultiAsset,
+    Script,
+    ScriptRef,
     Transaction as CMLTransaction,
     TransactionInput,
     TransactionOutput,
     UnitInterval,
 };
 use pallas_addresses::Address;
-use std::collections::BTreeMap;
 
-// TODO: I think some of these values might be dynamic, in which case we should query them
-//   rather than hard-coding them
-pub fn vasil_v1_tx_builder() -> LedgerClientResult<TransactionBuilder> {
-    let coefficient = 44.into();
-    let constant = 155381.into();
+/// Plutus V1 cost model since Vasil
+pub(crate) const VASIL_V1_COST_MODEL: &[i64] = &[
+    205665, 812, 1, 1, 1000, 571, 0, 1, 1000, 24177, 4, 1, 1000, 32, 117366, 10475, 4,
+    23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 100, 100,
+    23000, 100, 19537, 32, 175354, 32, 46417, 4, 221973, 511, 0, 1, 89141, 32, 497525,
+    14068, 4, 2, 196500, 453240, 220, 0, 1, 1, 1000, 28662, 4, 2, 245000, 216773, 62, 1,
+    1060367, 12586, 1, 208512, 421, 1, 187000, 1000, 52998, 1, 80436, 32, 43249, 32,
+    1000, 32, 80556, 1, 57667, 4, 1000, 10, 197145, 156, 1, 197145, 156, 1, 204924, 473,
+    1, 208896, 511, 1, 52467, 32, 64832, 32, 65493, 32, 22558, 32, 16563, 32, 76511, 32,
+    196500, 453240, 220, 0, 1, 1, 69522, 11687, 0, 1, 60091, 32, 196500, 453240, 220, 0,
+    1, 1, 196500, 453240, 220, 0, 1, 1, 806990, 30482, 4, 1927926, 82523, 4, 265318, 0,
+    4, 0, 85931, 32, 205665, 812, 1, 1, 41182, 32, 212342, 32, 31220, 32, 32696, 32,
+    43357, 32, 32247, 32, 38314, 32, 9462713, 1021, 10,
+];
+
+/// Plutus V2 cost model since Vasil
+pub(crate) const VASIL_V2_COST_MODEL: &[i64] = &[
+    205665, 812, 1, 1, 1000, 571, 0, 1, 1000, 24177, 4, 1, 1000, 32, 117366, 10475, 4,
+    23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 100, 100,
+    23000, 100, 19537, 32, 175354, 32, 46417, 4, 221973, 511, 0, 1, 89141, 32, 497525,
+    14068, 4, 2, 196500, 453240, 220, 0, 1, 1, 1000, 28662, 4, 2, 245000, 216773, 62, 1,
+    1060367, 12586, 1, 208512, 421, 1, 187000, 1000, 52998, 1, 80436, 32, 43249, 32,
+    1000, 32, 80556, 1, 57667, 4, 1000, 10, 197145, 156, 1, 197145, 156, 1, 204924, 473,
+    1, 208896, 511, 1, 52467, 32, 64832, 32, 65493, 32, 22558, 32, 16563, 32, 76511, 32,
+    196500, 453240, 220, 0, 1, 1, 69522, 11687, 0, 1, 60091, 32, 196500, 453240, 220, 0,
+    1, 1, 196500, 453240, 220, 0, 1, 1, 1159724, 392670, 0, 2, 806990, 30482, 4, 1927926,
+    82523, 4, 265318, 0, 4, 0, 85931, 32, 205665, 812, 1, 1, 41182, 32, 212342, 32,
+    31220, 32, 32696, 32, 43357, 32, 32247, 32, 38314, 32, 35892428, 10, 57996947, 18975,
+    10, 38887044, 32947, 10,
+];
+
+// TODO: Cost models are dynamic too, and should come with the rest of the parameters
+pub fn vasil_v1_tx_builder(
+    params: &ProtocolParameters,
+) -> LedgerClientResult<TransactionBuilder> {
+    let coefficient = params.min_fee_a().into();
+    let constant = params.min_fee_b().into();
     let linear_fee = LinearFee::new(&coefficient, &constant);
 
-    let pool_deposit = 500000000.into();
-    let key_deposit = 2000000.into();
-
-    let coins_per_utxo_byte = 4310.into();
-    let mem_num = 577.into();
-    let mem_den = 10000.into();
-    let mem_price = UnitInterval::new(&mem_num, &mem_den);
-    let step_num = 721.into();
-    let step_den = 10000000.into();
-    let step_price = UnitInterval::new(&step_num, &step_den);
-    let ex_unit_prices = ExUnitPrices::new(&mem_price, &step_price);
-    let vasil_v1_cost_models = vec![
-        205665, 812, 1, 1, 1000, 571, 0, 1, 1000, 24177, 4, 1, 1000, 32, 117366, 10475,
-        4, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 100,
-        100, 23000, 100, 19537, 32, 175354, 32, 46417, 4, 221973, 511, 0, 1, 89141, 32,
-        497525, 14068, 4, 2, 196500, 453240, 220, 0, 1, 1, 1000, 28662, 4, 2, 245000,
-        216773, 62, 1, 1060367, 12586, 1, 208512, 421, 1, 187000, 1000, 52998, 1, 80436,
-        32, 43249, 32, 1000, 32, 80556, 1, 57667, 4, 1000, 10, 197145, 156, 1, 197145,
-        156, 1, 204924, 473, 1, 208896, 511, 1, 52467, 32, 64832, 32, 65493, 32, 22558,
-        32, 16563, 32, 76511, 32, 196500, 453240, 220, 0, 1, 1, 69522, 11687, 0, 1,
-        60091, 32, 196500, 453240, 220, 0, 1, 1, 196500, 453240, 220, 0, 1, 1, 806990,
-        30482, 4, 1927926, 82523, 4, 265318, 0, 4, 0, 85931, 32, 205665, 812, 1, 1,
-        41182, 32, 212342, 32, 31220, 32, 32696, 32, 43357, 32, 32247, 32, 38314, 32,
-        9462713, 1021, 10,
-    ];
+    let pool_deposit = params.pool_deposit().into();
+    let key_deposit = params.key_deposit().into();
+
+    let coins_per_utxo_byte = params.coins_per_utxo_byte().into();
+    let ex_unit_prices = ex_unit_prices(params);
     let cm = CostModel::new(
         &Language::new_plutus_v1(),
-        &vasil_v1_cost_models.iter().map(|&i| Int::from(i)).collect(),
+        &VASIL_V1_COST_MODEL
+            .iter()
+            .map(|&i| Int::from_str(&i.to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                LedgerClientError::ConfigError(format!(
+                    "Cost models misconfigured: {e:?}"
+                ))
+            })?,
     );
     let mut cost_models = Costmdls::new();
     cost_models.insert(&cm);
@@ -116,12 +150,12 @@ pub fn vasil_v1_tx_builder() -> LedgerClientResult<TransactionBuilder> {
         .fee_algo(&linear_fee)
         .pool_deposit(&pool_deposit)
         .key_deposit(&key_deposit)
-        .max_value_size(5000)
-        .max_tx_size(16384)
+        .max_value_size(params.max_value_size())
+        .max_tx_size(params.max_tx_size())
         .coins_per_utxo_byte(&coins_per_utxo_byte)
         .ex_unit_prices(&ex_unit_prices)
-        .collateral_percentage(150)
-        .max_collateral_inputs(3)
+        .collateral_percentage(params.collateral_percentage())
+        .max_collateral_inputs(params.max_collateral_inputs())
         .costmdls(&cost_models)
         .build()
         .map_err(|e| CMLLCError::JsError(e.to_string()))
@@ -129,41 +163,22 @@ pub fn vasil_v1_tx_builder() -> LedgerClientResult<TransactionBuilder> {
     Ok(TransactionBuilder::new(&tx_builder_cfg))
 }
 
-// TODO: I think some of these values might be dynamic, in which case we should query them
-//   rather than hard-coding them
-pub fn vasil_v2_tx_builder() -> LedgerClientResult<TransactionBuilder> {
-    let coefficient = 44.into();
-    let constant = 155381.into();
+// TODO: Cost models are dynamic too, and should come with the rest of the parameters
+pub fn vasil_v2_tx_builder(
+    params: &ProtocolParameters,
+) -> LedgerClientResult<TransactionBuilder> {
+    let coefficient = params.min_fee_a().into();
+    let constant = params.min_fee_b().into();
     let linear_fee = LinearFee::new(&coefficient, &constant);
 
-    let pool_deposit = 500000000.into();
-    let key_deposit = 2000000.into();
-
-    let coins_per_utxo_byte = 4310.into();
-    let mem_num = 577.into();
-    let mem_den = 10000.into();
-    let mem_price = UnitInterval::new(&mem_num, &mem_den);
-    let step_num = 721.into();
-    let step_den = 10000000.into();
-    let step_price = UnitInterval::new(&step_num, &step_den);
-    let ex_unit_prices = ExUnitPrices::new(&mem_price, &step_price);
-    let vasil_v2_cost_models: Vec<i64> = vec![
-        205665, 812, 1, 1, 1000, 571, 0, 1, 1000, 24177, 4, 1, 1000, 32, 117366, 10475,
-        4, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 100,
-        100, 23000, 100, 19537, 32, 175354, 32, 46417, 4, 221973, 511, 0, 1, 89141, 32,
-        497525, 14068, 4, 2, 196500, 453240, 220, 0, 1, 1, 1000, 28662, 4, 2, 245000,
-        216773, 62, 1, 1060367, 12586, 1, 208512, 421, 1, 187000, 1000, 52998, 1, 80436,
-        32, 43249, 32, 1000, 32, 80556, 1, 57667, 4, 1000, 10, 197145, 156, 1, 197145,
-        156, 1, 204924, 473, 1, 208896, 511, 1, 52467, 32, 64832, 32, 65493, 32, 22558,
-        32, 16563, 32, 76511, 32, 196500, 453240, 220, 0, 1, 1, 69522, 11687, 0, 1,
-        60091, 32, 196500, 453240, 220, 0, 1, 1, 196500, 453240, 220, 0, 1, 1, 1159724,
-        392670, 0, 2, 806990, 30482, 4, 1927926, 82523, 4, 265318, 0, 4, 0, 85931, 32,
-        205665, 812, 1, 1, 41182, 32, 212342, 32, 31220, 32, 32696, 32, 43357, 32, 32247,
-        32, 38314, 32, 35892428, 10, 57996947, 18975, 10, 38887044, 32947, 10,
-    ];
+    let pool_deposit = params.pool_deposit().into();
+    let key_deposit = params.key_deposit().into();
+
+    let coins_per_utxo_byte = params.coins_per_utxo_byte().into();
+    let ex_unit_prices = ex_unit_prices(params);
     let cm = CostModel::new(
         &Language::new_plutus_v2(),
-        &vasil_v2_cost_models
+        &VASIL_V2_COST_MODEL
             .iter()
             .map(|&i| Int::from_str(&i.to_string()))
             .collect::<Result<Vec<_>, _>>()
@@ -180,12 +195,12 @@ pub fn vasil_v2_tx_builder() -> LedgerClientResult<TransactionBuilder> {
         .fee_algo(&linear_fee)
         .pool_deposit(&pool_deposit)
         .key_deposit(&key_deposit)
-        .max_value_size(5000)
-        .max_tx_size(16384)
+        .max_value_size(params.max_value_size())
+        .max_tx_size(params.max_tx_size())
         .coins_per_utxo_byte(&coins_per_utxo_byte)
         .ex_unit_prices(&ex_unit_prices)
-        .collateral_percentage(150)
-        .max_collateral_inputs(3)
+        .collateral_percentage(params.collateral_percentage())
+        .max_collateral_inputs(params.max_collateral_inputs())
         .costmdls(&cost_models)
         .build()
         .map_err(|e| CMLLCError::JsError(e.to_string()))
@@ -193,6 +208,14 @@ pub fn vasil_v2_tx_builder() -> LedgerClientResult<TransactionBuilder> {
     Ok(TransactionBuilder::new(&tx_builder_cfg))
 }
 
+fn ex_unit_prices(params: &ProtocolParameters) -> ExUnitPrices {
+    let (mem_num, mem_den) = params.price_mem();
+    let mem_price = UnitInterval::new(&mem_num.into(), &mem_den.into());
+    let (step_num, step_den) = params.price_step();
+    let step_price = UnitInterval::new(&step_num.into(), &step_den.into());
+    ExUnitPrices::new(&mem_price, &step_price)
+}
+
 pub(crate) fn input_from_utxo(
     my_address: &CMLAddress,
     utxo: &UTxO,
@@ -214,47 +237,26 @@ pub(crate) fn input_from_utxo(
     Ok(res)
 }
 
-impl TryFrom<Values> for CMLValue {
+/// Native tokens without an asset name get the empty asset name
+impl TryFrom<&Values> for CMLValue {
     type Error = CMLLCError;
 
-    fn try_from(vals: Values) -> Result<Self> {
-        // TODO: This is broken :((( I got an error message complaining about 1120600 and wanting
-        //   1155080. Please look into
-        // let mut ada = 1120600u64; // TODO: This is kinda buried. Maybe ref the CML value
-        let mut ada = 1155080u64; // TODO: This is kinda buried. Maybe ref the CML value
-        let mut nau_assets: BTreeMap<String, BTreeMap<Option<String>, u64>> =
-            BTreeMap::new();
-        for (policy_id, amount) in vals.as_iter() {
-            match policy_id {
-                PolicyId::Lovelace => ada = *amount,
-                PolicyId::NativeToken(id, asset_name) => {
-                    if let Some(mut inner) = nau_assets.remove(id) {
-                        inner.insert(asset_name.to_owned(), *amount);
-                        nau_assets.insert(id.to_owned(), inner);
-                    } else {
-                        let mut inner = BTreeMap::new();
-                        inner.insert(asset_name.to_owned(), *amount);
-                        nau_assets.insert(id.to_owned(), inner);
-                    }
-                }
-            }
-        }
+    fn try_from(vals: &Values) -> Result<Self> {
+        let (ada, nau_assets) = vals.grouped_by_policy()?;
         let coin = ada.into();
         let mut cml_value = CMLValue::new(&coin);
+        if nau_assets.is_empty() {
+            return Ok(cml_value)
+        }
         let mut multi_asset = MultiAsset::new();
-        for (id, assets) in nau_assets.iter() {
+        for (id, assets) in nau_assets.into_iter() {
             let mut cml_assets = Assets::new();
-            for (name, amount) in assets.iter() {
-                let key = if let Some(inner) = name {
-                    AssetName::new(inner.as_bytes().to_owned())
-                } else {
-                    AssetName::new(Vec::new())
-                }
-                .unwrap(); // TODO
-                let value = (*amount).into();
-                cml_assets.insert(&key, &value);
+            for (name, amount) in assets.into_iter() {
+                let key = AssetName::new(name).map_err(|e| JsError(e.to_string()))?;
+                cml_assets.insert(&key, &amount.into());
             }
-            let policy_id = ScriptHash::from_hex(id).unwrap(); // TODO
+            let policy_id = ScriptHash::from_hex(id)
+                .map_err(|_| CMLLCError::InvalidPolicyId(id.to_string()))?;
             multi_asset.insert(&policy_id, &cml_assets);
         }
         cml_value.set_multiasset(&multi_asset);
@@ -262,6 +264,42 @@ impl TryFrom<Values> for CMLValue {
     }
 }
 
+impl TryFrom<Values> for CMLValue {
+    type Error = CMLLCError;
+
+    fn try_from(vals: Values) -> Result<Self> {
+        CMLValue::try_from(&vals)
+    }
+}
+
+impl From<&CMLValue> for Values {
+    fn from(cml_value: &CMLValue) -> Self {
+        let mut values = Values::default();
+        let ada = cml_value.coin().into();
+        values.add_one_value(&PolicyId::Lovelace, ada);
+        if let Some(multiasset) = cml_value.multiasset() {
+            let ids = multiasset.keys();
+            for i in 0..ids.len() {
+                let id = ids.get(i);
+                if let Some(assets) = multiasset.get(&id) {
+                    let assets_names = assets.keys();
+                    for j in 0..assets_names.len() {
+                        let asset = assets_names.get(j);
+                        if let Some(amt) = assets.get(&asset) {
+                            let policy_id = PolicyId::native_token(
+                                &id.to_string(),
+                                &Some(asset.name()),
+                            );
+                            values.add_one_value(&policy_id, amt.into());
+                        }
+                    }
+                }
+            }
+        }
+        values
+    }
+}
+
 pub(crate) fn utxo_to_nau_utxo<Datum: PlutusDataInterop>(
     utxo: &UTxO,
     owner: &Address,
@@ -269,57 +307,38 @@ pub(crate) fn utxo_to_nau_utxo<Datum: PlutusDataInterop>(
     let tx_hash = hex::decode(utxo.tx_hash().to_string())
         .map_err(|e| LedgerClientError::BadTxId(Box::new(e)))?;
     let index = utxo.output_index().into();
-    let values = as_nau_values(utxo.amount())?;
+    let values = Values::from(utxo.amount());
 
-    // TODO: Add debug msg in the case that this can't convert from PlutusData?
-    let output = if let Some(datum) = utxo
-        .datum()
-        .to_owned()
-        .and_then(|data| Datum::from_plutus_data(&data).ok())
-    {
-        Output::new_validator(tx_hash, index, owner.to_owned(), values, datum)
-    } else {
-        Output::new_wallet(tx_hash, index, owner.to_owned(), values)
+    let output = match utxo.datum() {
+        Some(data) => match Datum::from_plutus_data(data) {
+            Ok(datum) => {
+                Output::new_validator(tx_hash, index, owner.to_owned(), values, datum)
+            }
+            Err(_) => Output::new_untyped_validator(
+                tx_hash,
+                index,
+                owner.to_owned(),
+                values,
+                data.clone().into(),
+            ),
+        },
+        None => Output::new_wallet(tx_hash, index, owner.to_owned(), values),
+    };
+    let output = match utxo.datum_hash() {
+        Some(hash) => output.with_datum_hash(hash.to_vec()),
+        None => output,
+    };
+    let output = match utxo.reference_script() {
+        Some(reference_script) => output.with_reference_script(reference_script.clone()),
+        None => output,
     };
     Ok(output)
 }
 
-fn as_nau_values(cml_value: &CMLValue) -> LedgerClientResult<Values> {
-    let mut values = Values::default();
-    let ada = cml_value.coin().into();
-    values.add_one_value(&PolicyId::Lovelace, ada);
-    if let Some(multiasset) = cml_value.multiasset() {
-        let ids = multiasset.keys();
-        let len = multiasset.len();
-        for i in 0..len {
-            let id = ids.get(i);
-            if let Some(assets) = multiasset.get(&id) {
-                let assets_names = assets.keys();
-                let len = assets_names.len();
-                for i in 0..len {
-                    let asset = assets_names.get(i);
-                    if let Some(amt) = assets.get(&asset) {
-                        let asset_bytes = asset.name();
-                        let asset_text = std::str::from_utf8(&asset_bytes)
-                            .map_err(as_failed_to_issue_tx)?;
-                        let policy_id = PolicyId::native_token(
-                            &id.to_string(),
-                            &Some(asset_text.to_string()),
-                        );
-                        values.add_one_value(&policy_id, amt.into());
-                    }
-                }
-            }
-        }
-    }
-
-    Ok(values)
-}
-
 pub(crate) async fn specify_utxos_available_for_input_selection(
     tx_builder: &mut TransactionBuilder,
     my_address: &CMLAddress,
-    my_utxos: &[UTxO],
+    my_utxos: &[&UTxO],
 ) -> LedgerClientResult<()> {
     for utxo in my_utxos.iter() {
         let input = input_from_utxo(my_address, utxo)?;
@@ -328,29 +347,126 @@ pub(crate) async fn specify_utxos_available_for_input_selection(
     Ok(())
 }
 
+// Collateral inputs are picked before the fee is known, so they need to hold enough for
+// the collateral of most transactions: fees up to 3.33 ADA at a 150% collateral percentage
+const MIN_COLLATERAL_INPUT: u64 = 5_000_000;
+// Bytes the signed transaction can have on top of the evaluated draft: its signatures,
+// collateral return and total collateral
+const FEE_ESTIMATE_HEADROOM_BYTES: u64 = 1_024;
+
+/// Pick a collateral input and return how much it holds
 pub(crate) async fn add_collateral(
     tx_builder: &mut TransactionBuilder,
     my_address: &CMLAddress,
     my_utxos: &Vec<UTxO>,
-) -> LedgerClientResult<()> {
-    const MIN_COLLATERAL_AMT: u64 = 5_000_000;
-
-    let collateral_utxo =
-        select_collateral_utxo(my_address, my_utxos, MIN_COLLATERAL_AMT)?;
+) -> LedgerClientResult<u64> {
+    let (collateral_utxo, collateral_amount) =
+        select_collateral_utxo(my_address, my_utxos, MIN_COLLATERAL_INPUT)?;
 
     tx_builder
         .add_collateral(&collateral_utxo)
         .map_err(|e| CMLLCError::JsError(e.to_string()))
         .map_err(as_failed_to_issue_tx)?;
+    Ok(collateral_amount)
+}
+
+/// Put up the collateral the ledger asks for a transaction paying `fee`, and take the
+/// rest of the collateral input back in the collateral return
+pub(crate) fn set_total_collateral(
+    tx_builder: &mut TransactionBuilder,
+    my_address: &CMLAddress,
+    collateral_amount: u64,
+    fee: u64,
+    params: &ProtocolParameters,
+    era: Era,
+) -> LedgerClientResult<()> {
+    let total_collateral = required_collateral(fee, params);
+    if total_collateral > collateral_amount {
+        return Err(LedgerClientError::NoBigEnoughCollateralUTxO)
+    }
+    if !era.supports(EraFeature::CollateralReturn) {
+        return Ok(())
+    }
+    if let Some(returned) =
+        collateral_return_amount(collateral_amount, total_collateral, params)
+    {
+        let collateral_return =
+            TransactionOutput::new(my_address, &CMLValue::new(&returned.into()));
+        tx_builder.set_collateral_return(&collateral_return);
+        tx_builder.set_total_collateral(&total_collateral.into());
+    }
+    Ok(())
+}
+
+/// Upper bound on the fee of a transaction whose evaluated draft is `draft_size` bytes
+pub(crate) fn estimate_fee(
+    params: &ProtocolParameters,
+    draft_size: u64,
+    ex_units: &[(u64, u64)],
+) -> u64 {
+    linear_fee(params, draft_size + FEE_ESTIMATE_HEADROOM_BYTES, ex_units)
+}
+
+/// Fee of a transaction of `tx_size` bytes running scripts with `ex_units`
+pub(crate) fn linear_fee(
+    params: &ProtocolParameters,
+    tx_size: u64,
+    ex_units: &[(u64, u64)],
+) -> u64 {
+    let size_fee = params.min_fee_a() * tx_size + params.min_fee_b();
+    let (mem_total, steps_total) = ex_units
+        .iter()
+        .fold((0, 0), |(mem, steps), (m, s)| (mem + m, steps + s));
+    size_fee
+        + price(mem_total, params.price_mem())
+        + price(steps_total, params.price_step())
+}
+
+fn price(units: u64, (numerator, denominator): (u64, u64)) -> u64 {
+    let total = units as u128 * numerator as u128;
+    total.div_ceil(denominator as u128) as u64
+}
+
+/// Collateral the ledger requires for a transaction paying `fee`
+pub(crate) fn required_collateral(fee: u64, params: &ProtocolParameters) -> u64 {
+    let total = fee as u128 * params.collateral_percentage() as u128;
+    total.div_ceil(100) as u64
+}
+
+// Without a return output the whole collateral input is lost, so only skip it when what's
+// left over can't pay for an output of its own
+fn collateral_return_amount(
+    collateral_amount: u64,
+    total_collateral: u64,
+    params: &ProtocolParameters,
+) -> Option<u64> {
+    let returned = collateral_amount.checked_sub(total_collateral)?;
+    let mut return_value = Values::default();
+    return_value.add_one_value(&PolicyId::Lovelace, returned);
+    (returned >= return_value.min_ada_required(params)).then_some(returned)
+}
+
+pub(crate) async fn add_metadata(
+    tx_builder: &mut TransactionBuilder,
+    metadata: &TxMetadata,
+) -> LedgerClientResult<()> {
+    if metadata.is_empty() {
+        return Ok(())
+    }
+    let cbor = metadata.to_cbor().map_err(as_failed_to_issue_tx)?;
+    let general = GeneralTransactionMetadata::from_bytes(cbor)
+        .map_err(|e| CMLLCError::Deserialize(e.to_string()))
+        .map_err(as_failed_to_issue_tx)?;
+    let mut auxiliary_data = AuxiliaryData::new();
+    auxiliary_data.set_metadata(&general);
+    tx_builder.add_auxiliary_data(&auxiliary_data);
     Ok(())
 }
 
 pub(crate) async fn select_inputs_from_utxos(
     tx_builder: &mut TransactionBuilder,
+    strategy: CoinSelectionStrategyCIP2,
 ) -> LedgerClientResult<()> {
-    // Hardcode for now. I'm choosing this strat because it helps atomize my wallet a
-    // little more which makes testing a bit safer 🦺
-    let strategy = CoinSelectionStrategyCIP2::LargestFirstMultiAsset;
     tx_builder
         .select_utxos(strategy)
         .map_err(|e| CMLLCError::JsError(e.to_string()))
@@ -358,12 +474,18 @@ pub(crate) async fn select_inputs_from_utxos(
     Ok(())
 }
 
+// Whether `select_collateral_utxo` could pick `utxo`
+pub(crate) fn can_be_collateral(utxo: &UTxO) -> bool {
+    utxo.amount().multiasset().is_none()
+        && u64::from(utxo.amount().coin()) > MIN_COLLATERAL_INPUT
+}
+
 // TODO: This could be less naive (e.g. include multiple UTxOs, etc)
 pub(crate) fn select_collateral_utxo(
     my_cml_address: &CMLAddress,
     my_utxos: &Vec<UTxO>,
     min_amount: u64,
-) -> LedgerClientResult<InputBuilderResult> {
+) -> LedgerClientResult<(InputBuilderResult, u64)> {
     let mut smallest_utxo_meets_qual = None;
     let mut smallest_amount = u64::MAX;
     for utxo in my_utxos {
@@ -392,7 +514,7 @@ pub(crate) fn select_collateral_utxo(
             .payment_key()
             .map_err(|e| JsError(e.to_string()))
             .map_err(as_failed_to_issue_tx)?;
-        Some(res)
+        Some((res, smallest_amount))
     } else {
         None
     };
@@ -401,25 +523,59 @@ pub(crate) fn select_collateral_utxo(
 
 pub(crate) async fn build_tx_for_signing(
     tx_builder: &mut TransactionBuilder,
-    my_address: &CMLAddress,
+    change_address: &CMLAddress,
+    algo: ChangeSelectionAlgo,
 ) -> LedgerClientResult<SignedTxBuilder> {
-    let algo = ChangeSelectionAlgo::Default;
     let signed_tx_builder = tx_builder
-        .build(algo, my_address)
+        .build(algo, change_address)
         .map_err(|e| CMLLCError::JsError(e.to_string()))
         .map_err(as_failed_to_issue_tx)?;
     Ok(signed_tx_builder)
 }
 
-pub(crate) async fn sign_tx(
+pub(crate) async fn required_signers(
+    signers: &[&(dyn Keys + Sync)],
+) -> LedgerClientResult<Vec<Ed25519KeyHash>> {
+    let mut key_hashes = Vec::new();
+    for signer in signers {
+        let public_key = signer
+            .payment_public_key()
+            .await
+            .map_err(as_failed_to_issue_tx)?;
+        key_hashes.push(public_key.hash());
+    }
+    Ok(key_hashes)
+}
+
+pub(crate) fn add_required_signers(
+    tx_builder: &mut TransactionBuilder,
+    required_signers: &[Ed25519KeyHash],
+) {
+    for key_hash in required_signers {
+        tx_builder.add_required_signer(key_hash);
+    }
+}
+
+pub(crate) async fn sign_tx<K: Keys + Sync>(
     signed_tx_builder: &mut SignedTxBuilder,
-    priv_key: &PrivateKey,
+    keys: &K,
+    extra_signers: &[&(dyn Keys + Sync)],
 ) -> LedgerClientResult<CMLTransaction> {
     let unchecked_tx = signed_tx_builder.build_unchecked();
-    let tx_body = unchecked_tx.body();
-    let tx_hash = hash_transaction(&tx_body);
-    let vkey_witness = make_vkey_witness(&tx_hash, priv_key);
-    signed_tx_builder.add_vkey(&vkey_witness);
+    let mut vkey_witnesses = keys
+        .vkey_witnesses(&unchecked_tx)
+        .await
+        .map_err(as_failed_to_issue_tx)?;
+    for signer in extra_signers {
+        let extra_witnesses = signer
+            .vkey_witnesses(&unchecked_tx)
+            .await
+            .map_err(as_failed_to_issue_tx)?;
+        vkey_witnesses.extend(extra_witnesses);
+    }
+    for vkey_witness in vkey_witnesses.iter() {
+        signed_tx_builder.add_vkey(vkey_witness);
+    }
     let tx = signed_tx_builder
         .build_checked()
         .map_err(|e| CMLLCError::JsError(e.to_string()))
@@ -487,7 +643,141 @@ pub(crate) async fn cml_v2_script_from_nau_policy<Redeemer>(
 pub(crate) async fn partial_script_witness<Redeemer: PlutusDataInterop>(
     cml_script: &PlutusScript,
     redeemer: &Redeemer,
+    by_reference: bool,
 ) -> PartialPlutusWitness {
-    let script_witness = PlutusScriptWitness::from_script(cml_script.clone());
+    let script_witness = if by_reference {
+        PlutusScriptWitness::from_ref(cml_script.hash())
+    } else {
+        PlutusScriptWitness::from_script(cml_script.clone())
+    };
     PartialPlutusWitness::new(&script_witness, &redeemer.to_plutus_data())
 }
+
+pub(crate) fn cml_script_ref(
+    reference_script: &ReferenceScript,
+) -> LedgerClientResult<ScriptRef> {
+    let script_bytes =
+        hex::decode(reference_script.script_hex()).map_err(as_failed_to_issue_tx)?;
+    let script =
+        match reference_script.version() {
+            TransactionVersion::V1 => PlutusV1Script::from_bytes(script_bytes)
+                .map(|v1| Script::new_plutus_v1(&v1)),
+            TransactionVersion::V2 => PlutusV2Script::from_bytes(script_bytes)
+                .map(|v2| Script::new_plutus_v2(&v2)),
+        }
+        .map_err(|e| CMLLCError::Deserialize(e.to_string()))
+        .map_err(as_failed_to_issue_tx)?;
+    Ok(ScriptRef::new(&script))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::values::ValuesError;
+
+    #[test]
+    fn cml_value__round_trips_values() {
+        // given
+        let policy = "bdaa99eb158414dea0a91d6c727e2268574b23efe6e08ab3b841abe8";
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, 2_000_000);
+        values.add_one_value(&PolicyId::native_token(policy, &Some(vec![0xff])), 3);
+        values.add_one_value(
+            &PolicyId::native_token(policy, &Some(b"Token".to_vec())),
+            u64::MAX,
+        );
+
+        // when
+        let cml_value = CMLValue::try_from(&values).unwrap();
+        let round_tripped = Values::from(&cml_value);
+
+        // then
+        assert_eq!(round_tripped, values);
+    }
+
+    #[test]
+    fn cml_value__rejects_invalid_policy_ids() {
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::native_token("nope", &None), 1);
+
+        let res = CMLValue::try_from(&values);
+
+        assert!(matches!(res, Err(CMLLCError::InvalidPolicyId(_))));
+    }
+
+    #[test]
+    fn cml_value__rejects_unnamed_and_empty_named_tokens_that_overflow_together() {
+        // given
+        let policy = "bdaa99eb158414dea0a91d6c727e2268574b23efe6e08ab3b841abe8";
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::native_token(policy, &None), u64::MAX);
+        values.add_one_value(&PolicyId::native_token(policy, &Some(vec![])), 1);
+
+        // when
+        let res = CMLValue::try_from(&values);
+
+        // then
+        assert!(matches!(
+            res,
+            Err(CMLLCError::Values(ValuesError::Overflow(_)))
+        ));
+    }
+
+    #[test]
+    fn utxo_to_nau_utxo__keeps_reference_scripts_and_their_version() {
+        // given
+        let script = PlutusV1Script::new(vec![0x4d, 0x01, 0x00, 0x00]);
+        let script_hash = PlutusScript::from_v1(&script).hash().to_bytes();
+        let reference_script =
+            ReferenceScript::new(script_hash.clone(), &hex::encode(script.to_bytes()))
+                .with_version(TransactionVersion::V1);
+        let tx_hash = TransactionHash::from_bytes(vec![1; 32]).unwrap();
+        let utxo = UTxO::new(tx_hash, 0.into(), CMLValue::new(&2_000_000.into()), None)
+            .with_reference_script(reference_script.clone());
+        let owner = Address::from_bech32(
+            "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu",
+        )
+        .unwrap();
+
+        // when
+        let output = utxo_to_nau_utxo::<()>(&utxo, &owner).unwrap();
+        let script_ref = cml_script_ref(output.reference_script().unwrap()).unwrap();
+
+        // then
+        assert_eq!(output.reference_script(), Some(&reference_script));
+        let v1 = script_ref.script().as_plutus_v1().unwrap();
+        assert_eq!(PlutusScript::from_v1(&v1).hash().to_bytes(), script_hash);
+    }
+
+    #[test]
+    fn collateral_return_amount__returns_whatever_can_pay_for_an_output() {
+        // given
+        let params = ProtocolParameters::default();
+
+        // when
+        let total = 300_000;
+        let large_utxo = collateral_return_amount(100_000_000, total, &params);
+        let dust_left = collateral_return_amount(total + 100_000, total, &params);
+        let exact = collateral_return_amount(total, total, &params);
+
+        // then
+        assert_eq!(large_utxo, Some(99_700_000));
+        assert_eq!(dust_left, None);
+        assert_eq!(exact, None);
+    }
+
+    #[test]
+    fn required_collateral__is_the_fee_times_the_collateral_percentage() {
+        // given
+        let params = ProtocolParameters::default();
+
+        // when
+        let collateral = required_collateral(200_001, &params);
+
+        // then
+        assert_eq!(params.collateral_percentage(), 150);
+        assert_eq!(collateral, 300_002);
+    }
+}
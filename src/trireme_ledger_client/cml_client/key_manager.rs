@@ -1,5 +1,11 @@
 use super::{
     error::*,
+    secret_phrase::{
+        account_key_to_drep_key,
+        account_key_to_payment_key,
+        private_key_to_base_address_at,
+        DerivationPath,
+    },
     Keys,
 };
 use async_trait::async_trait;
@@ -8,10 +14,7 @@ use bip39::{
     Mnemonic,
 };
 use cardano_multiplatform_lib::{
-    address::{
-        BaseAddress,
-        StakeCredential,
-    },
+    address::BaseAddress,
     crypto::{
         Bip32PrivateKey,
         PrivateKey,
@@ -27,6 +30,7 @@ use thiserror::Error;
 pub struct KeyManager {
     config_path: String,
     network: u8,
+    derivation_path: DerivationPath,
 }
 
 impl KeyManager {
@@ -35,8 +39,15 @@ impl KeyManager {
         KeyManager {
             config_path,
             network,
+            derivation_path: DerivationPath::default(),
         }
     }
+
+    /// Derive keys at `derivation_path` instead of the first address of the first account
+    pub fn with_derivation_path(mut self, derivation_path: DerivationPath) -> Self {
+        self.derivation_path = derivation_path;
+        self
+    }
 }
 
 #[allow(missing_docs)]
@@ -55,9 +66,14 @@ impl Keys for KeyManager {
 
     async fn private_key(&self) -> Result<PrivateKey> {
         let account_key = self.get_account_key()?;
-        let priv_key = account_key.derive(0).derive(0).to_raw_key();
+        let priv_key = account_key_to_payment_key(&account_key, &self.derivation_path);
         Ok(priv_key)
     }
+
+    async fn drep_key(&self) -> Result<PrivateKey> {
+        let account_key = self.get_account_key()?;
+        Ok(account_key_to_drep_key(&account_key))
+    }
 }
 
 impl KeyManager {
@@ -72,18 +88,17 @@ impl KeyManager {
         let account_key = root_key
             .derive(harden(1852))
             .derive(harden(1815))
-            .derive(harden(0));
+            .derive(harden(self.derivation_path.account()));
         Ok(account_key)
     }
 
     fn get_base_address(&self) -> Result<BaseAddress> {
         let account_key = self.get_account_key()?;
-        let pub_key = account_key.derive(0).derive(0).to_public();
-        let stake_key = account_key.derive(2).derive(0).to_public();
-        let pub_key_creds = StakeCredential::from_keyhash(&pub_key.to_raw_key().hash());
-        let stake_key_creds =
-            StakeCredential::from_keyhash(&stake_key.to_raw_key().hash());
-        let base_addr = BaseAddress::new(self.network, &pub_key_creds, &stake_key_creds);
+        let base_addr = private_key_to_base_address_at(
+            &account_key,
+            self.network,
+            &self.derivation_path,
+        );
         Ok(base_addr)
     }
 }
@@ -0,0 +1,355 @@
+use crate::{
+    ledger_client::provider_error::ProviderError,
+    output::ReferenceScript,
+    transaction::TransactionVersion,
+    trireme_ledger_client::cml_client::{
+        error::{
+            CMLLCError,
+            Result,
+        },
+        network_settings::NetworkSettings,
+        ogmios_scrolls_ledger::{
+            evaluate_tx,
+            submit_tx,
+        },
+        ExecutionCost,
+        Ledger,
+        UTxO,
+    },
+};
+use async_trait::async_trait;
+use cardano_multiplatform_lib::{
+    address::Address as CMLAddress,
+    crypto::TransactionHash,
+    ledger::common::value::Value as CMLValue,
+    plutus::PlutusData,
+    AssetName,
+    Assets,
+    MultiAsset,
+    PolicyID,
+    Transaction as CMLTransaction,
+};
+use futures::future;
+use ogmios_client::OgmiosClient;
+use serde::{
+    de::DeserializeOwned,
+    Deserialize,
+};
+use std::collections::HashMap;
+
+const KUPO: &str = "Kupo";
+const POLICY_ID_HEX_LEN: usize = 56;
+
+/// Implementation of the [`Ledger`] trait that reads the chain from Kupo, and evaluates and
+/// submits transactions with Ogmios
+pub struct KupoLedger {
+    url: String,
+    ogmios_client: OgmiosClient,
+    network_settings: NetworkSettings,
+}
+
+impl KupoLedger {
+    /// Constructor for the [`KupoLedger`], for the Kupo instance at `url`
+    pub fn new(
+        url: &str,
+        ogmios_client: OgmiosClient,
+        network_settings: NetworkSettings,
+    ) -> Self {
+        KupoLedger {
+            url: url.trim_end_matches('/').to_string(),
+            ogmios_client,
+            network_settings,
+        }
+    }
+
+    /// Get the unspent outputs matching a Kupo `pattern`, e.g. an address, a policy id
+    /// or `{policy_id}.{asset_name}`
+    pub async fn get_utxos_matching(&self, pattern: &str) -> Result<Vec<UTxO>> {
+        let matches: Vec<KupoMatch> = self
+            .get_json(&format!("/matches/{pattern}?unspent"))
+            .await?;
+        future::join_all(matches.iter().map(|kupo_match| self.to_utxo(kupo_match)))
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    async fn to_utxo(&self, kupo_match: &KupoMatch) -> Result<UTxO> {
+        let tx_hash = TransactionHash::from_hex(&kupo_match.transaction_id)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        let amount = cml_value_from_kupo_value(&kupo_match.value)?;
+        let datum = match &kupo_match.datum_hash {
+            Some(datum_hash) => self.resolve_datum(datum_hash).await?,
+            None => None,
+        };
+        let mut utxo = UTxO::new(tx_hash, kupo_match.output_index.into(), amount, datum);
+        if let (Some(datum_hash), Some(DatumType::Hash)) =
+            (&kupo_match.datum_hash, &kupo_match.datum_type)
+        {
+            utxo = utxo.with_datum_hash(hex::decode(datum_hash)?);
+        }
+        if let Some(script_hash) = &kupo_match.script_hash {
+            if let Some(reference_script) = self.reference_script(script_hash).await? {
+                utxo = utxo.with_reference_script(reference_script);
+            }
+        }
+        Ok(utxo)
+    }
+
+    // Only Plutus scripts are kept, since native scripts can't be used by reference here
+    async fn reference_script(
+        &self,
+        script_hash: &str,
+    ) -> Result<Option<ReferenceScript>> {
+        let script: Option<KupoScript> =
+            self.get_json(&format!("/scripts/{script_hash}")).await?;
+        let Some(script) = script else {
+            return Ok(None)
+        };
+        let version = match script.language.as_str() {
+            "plutus:v1" => TransactionVersion::V1,
+            "plutus:v2" => TransactionVersion::V2,
+            _ => return Ok(None),
+        };
+        let reference_script =
+            ReferenceScript::new(hex::decode(script_hash)?, &script.script)
+                .with_version(version);
+        Ok(Some(reference_script))
+    }
+
+    // Kupo only keeps datums it has seen on chain, so others stay unresolved
+    async fn resolve_datum(&self, datum_hash: &str) -> Result<Option<PlutusData>> {
+        let datum: Option<KupoDatum> =
+            self.get_json(&format!("/datums/{datum_hash}")).await?;
+        let Some(datum) = datum else { return Ok(None) };
+        let bytes = hex::decode(datum.datum)?;
+        Ok(PlutusData::from_bytes(bytes).ok())
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let request = format!("GET {}", path.split('?').next().unwrap_or(path));
+        let url = format!("{}{path}", self.url);
+        let res = reqwest::Client::new()
+            .get(url)
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .map_err(|e| ProviderError::new(KUPO, &request).with_source(e))?;
+        if !res.status().is_success() {
+            let error = ProviderError::from_response(KUPO, &request, res).await;
+            return Err(error.into())
+        }
+        let value = res
+            .json()
+            .await
+            .map_err(|e| ProviderError::new(KUPO, &request).with_source(e))?;
+        Ok(value)
+    }
+}
+
+#[async_trait]
+impl Ledger for KupoLedger {
+    async fn last_block_time_secs(&self) -> Result<i64> {
+        let health: KupoHealth = self.get_json("/health").await?;
+        let slot = health.most_recent_checkpoint.ok_or_else(|| {
+            ProviderError::new(KUPO, "GET /health").with_body("No checkpoint yet")
+        })?;
+        Ok(self.network_settings.posix_from_slot(slot))
+    }
+
+    async fn get_utxos_for_addr(
+        &self,
+        addr: &CMLAddress,
+        count: usize,
+    ) -> Result<Vec<UTxO>> {
+        let outputs = self
+            .get_all_utxos_for_addr(addr)
+            .await?
+            .into_iter()
+            .take(count)
+            .collect();
+        Ok(outputs)
+    }
+
+    async fn get_all_utxos_for_addr(&self, addr: &CMLAddress) -> Result<Vec<UTxO>> {
+        let address = addr
+            .to_bech32(None)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        self.get_utxos_matching(&address).await
+    }
+
+    async fn calculate_ex_units(
+        &self,
+        tx: &CMLTransaction,
+    ) -> Result<HashMap<u64, ExecutionCost>> {
+        evaluate_tx(&self.ogmios_client, tx).await
+    }
+
+    async fn submit_transaction(&self, tx: &CMLTransaction) -> Result<String> {
+        submit_tx(&self.ogmios_client, &tx.to_bytes()).await
+    }
+
+    async fn submit_transaction_cbor(&self, cbor: &[u8]) -> Result<String> {
+        submit_tx(&self.ogmios_client, cbor).await
+    }
+
+    async fn get_transaction_cbor(&self, _tx_id: &str) -> Result<Vec<u8>> {
+        Err(CMLLCError::OgmiosResponse(
+            "Looking up transactions by id isn't supported by Kupo/Ogmios".to_string(),
+        ))
+    }
+
+    // Kupo keeps spent outputs too, so any output of the transaction means it's on chain
+    async fn is_tx_confirmed(&self, tx_id: &str) -> Result<bool> {
+        let matches: Vec<KupoMatch> =
+            self.get_json(&format!("/matches/*@{tx_id}")).await?;
+        Ok(!matches.is_empty())
+    }
+}
+
+#[derive(Deserialize)]
+struct KupoMatch {
+    transaction_id: String,
+    output_index: u64,
+    value: KupoValue,
+    datum_hash: Option<String>,
+    datum_type: Option<DatumType>,
+    script_hash: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DatumType {
+    Hash,
+    Inline,
+}
+
+#[derive(Deserialize)]
+struct KupoValue {
+    coins: u64,
+    #[serde(default)]
+    assets: HashMap<String, u64>,
+}
+
+#[derive(Deserialize)]
+struct KupoScript {
+    language: String,
+    script: String,
+}
+
+#[derive(Deserialize)]
+struct KupoDatum {
+    datum: String,
+}
+
+#[derive(Deserialize)]
+struct KupoHealth {
+    most_recent_checkpoint: Option<u64>,
+}
+
+// Assets are keyed by `{policy_id}.{asset_name}`, or just the policy id for empty names
+fn cml_value_from_kupo_value(value: &KupoValue) -> Result<CMLValue> {
+    let mut cml_value = CMLValue::new(&value.coins.into());
+    for (unit, quantity) in value.assets.iter() {
+        let (policy_id_hex, asset_name_hex) =
+            unit.split_once('.').unwrap_or((unit.as_str(), ""));
+        if policy_id_hex.len() != POLICY_ID_HEX_LEN {
+            return Err(CMLLCError::InvalidPolicyId(unit.to_string()))
+        }
+        let policy_id = PolicyID::from_hex(policy_id_hex)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        let asset_name = AssetName::new(hex::decode(asset_name_hex)?)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        let mut assets = Assets::new();
+        assets.insert(&asset_name, &(*quantity).into());
+        let mut multi_assets = MultiAsset::new();
+        multi_assets.insert(&policy_id, &assets);
+        cml_value = cml_value
+            .checked_add(&CMLValue::new_from_assets(&multi_assets))
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+    }
+    Ok(cml_value)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::{
+        trireme_ledger_client::Network,
+        values::Values,
+        PolicyId,
+    };
+    use tokio::{
+        io::{
+            AsyncReadExt,
+            AsyncWriteExt,
+        },
+        net::TcpListener,
+    };
+
+    // Answers a single request with `body` and returns the raw request
+    async fn serve_once(listener: TcpListener, body: String) -> String {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0; 4096];
+        let read = stream.read(&mut buf).await.unwrap();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&buf[..read]).to_string()
+    }
+
+    fn ledger_at(url: &str) -> KupoLedger {
+        let ogmios_client =
+            OgmiosClient::new("127.0.0.1".to_string(), "1337".to_string());
+        KupoLedger::new(url, ogmios_client, Network::Preprod.try_into().unwrap())
+    }
+
+    #[test]
+    fn cml_value_from_kupo_value__reads_coins_and_assets() {
+        // given
+        let policy = "bdaa99eb158414dea0a91d6c727e2268574b23efe6e08ab3b841abe8";
+        let json = format!(
+            r#"{{ "coins": 2000000, "assets": {{ "{policy}.546f6b656e": 3, "{policy}": 4 }} }}"#
+        );
+        let value: KupoValue = serde_json::from_str(&json).unwrap();
+
+        // when
+        let cml_value = cml_value_from_kupo_value(&value).unwrap();
+
+        // then
+        let mut expected = Values::default();
+        expected.add_one_value(&PolicyId::Lovelace, 2_000_000);
+        expected
+            .add_one_value(&PolicyId::native_token(policy, &Some(b"Token".to_vec())), 3);
+        expected.add_one_value(&PolicyId::native_token(policy, &Some(vec![])), 4);
+        assert_eq!(Values::from(&cml_value), expected);
+    }
+
+    #[tokio::test]
+    async fn is_tx_confirmed__looks_for_any_output_of_the_tx() {
+        // given
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ledger = ledger_at(&format!("http://{}", listener.local_addr().unwrap()));
+        let tx_id = "a".repeat(64);
+        let body = serde_json::json!([{
+            "transaction_id": tx_id,
+            "output_index": 0,
+            "value": { "coins": 2000000 },
+            "datum_hash": null,
+            "spent_at": { "slot_no": 10 },
+        }])
+        .to_string();
+
+        // when
+        let (confirmed, request) =
+            tokio::join!(ledger.is_tx_confirmed(&tx_id), serve_once(listener, body));
+
+        // then
+        assert!(confirmed.unwrap());
+        assert!(request.starts_with(&format!("GET /matches/*@{tx_id}")));
+    }
+}
@@ -0,0 +1,390 @@
+use cardano_multiplatform_lib::crypto::{
+    Ed25519Signature,
+    PrivateKey,
+    PublicKey,
+};
+use minicbor::{
+    data::Type,
+    Decoder,
+    Encoder,
+};
+use pallas_addresses::{
+    Address,
+    ShelleyPaymentPart,
+    StakePayload,
+};
+use thiserror::Error;
+
+// COSE header labels and values, from RFC 8152
+const ALG_LABEL: i64 = 1;
+const EDDSA: i64 = -8;
+const KTY_LABEL: i64 = 1;
+const OKP: i64 = 1;
+const KEY_ALG_LABEL: i64 = 3;
+const CRV_LABEL: i64 = -1;
+const ED25519: i64 = 6;
+const X_LABEL: i64 = -2;
+const COSE_SIGN1_TAG: u64 = 18;
+const SIGNATURE1_CONTEXT: &str = "Signature1";
+// CIP-8 header with the address the payload is signed for
+const ADDRESS_LABEL: &str = "address";
+const HASHED_LABEL: &str = "hashed";
+
+#[allow(missing_docs)]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum MessageSigningError {
+    #[error("Failed to encode COSE structure: {0}")]
+    Encoding(String),
+    #[error("Malformed COSE structure: {0}")]
+    Malformed(String),
+    #[error("Unsupported COSE algorithm or key: {0}")]
+    Unsupported(String),
+    #[error("Signature doesn't match the payload and key")]
+    BadSignature,
+    #[error("Key doesn't control address {0}")]
+    WrongKey(String),
+}
+
+/// Specialized result type for CIP-8 message signing
+pub type MessageSigningResult<T, E = MessageSigningError> = Result<T, E>;
+
+/// COSE_Sign1 signature and COSE_Key of a payload, as returned by CIP-30 `signData`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataSignature {
+    signature: Vec<u8>,
+    key: Vec<u8>,
+}
+
+impl DataSignature {
+    /// Constructor for a [`DataSignature`] from the CBOR of the COSE_Sign1 and COSE_Key
+    pub fn new(signature: Vec<u8>, key: Vec<u8>) -> Self {
+        DataSignature { signature, key }
+    }
+
+    /// Constructor for a [`DataSignature`] from the hex fields CIP-30 wallets return
+    pub fn from_hex(signature: &str, key: &str) -> MessageSigningResult<Self> {
+        let decode = |hex_str: &str| {
+            hex::decode(hex_str)
+                .map_err(|e| MessageSigningError::Malformed(e.to_string()))
+        };
+        Ok(DataSignature::new(decode(signature)?, decode(key)?))
+    }
+
+    /// Getter for the CBOR of the COSE_Sign1
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Getter for the CBOR of the COSE_Key
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// Check the signature and that its key controls the signing address
+    ///
+    /// The key must hash to the payment credential of a Shelley address or the stake
+    /// credential of a reward address. Payloads a wallet hashed before signing come back as
+    /// that hash.
+    pub fn verify(&self) -> MessageSigningResult<VerifiedMessage> {
+        let sign1 = CoseSign1::decode(&self.signature)?;
+        let public_key = decode_cose_key(&self.key)?;
+        let signature = Ed25519Signature::from_bytes(sign1.signature.clone())
+            .map_err(|e| MessageSigningError::Malformed(e.to_string()))?;
+        let sig_structure = sig_structure(&sign1.protected, &sign1.payload)?;
+        if !public_key.verify(&sig_structure, &signature) {
+            return Err(MessageSigningError::BadSignature)
+        }
+        let address = Address::from_bytes(&sign1.address)
+            .map_err(|e| MessageSigningError::Malformed(e.to_string()))?;
+        let key_hash = public_key.hash().to_bytes();
+        if key_credential(&address).as_deref() != Some(key_hash.as_slice()) {
+            return Err(MessageSigningError::WrongKey(hex::encode(&sign1.address)))
+        }
+        Ok(VerifiedMessage {
+            address,
+            payload: sign1.payload,
+        })
+    }
+}
+
+/// Payload whose signature was checked by [`DataSignature::verify`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifiedMessage {
+    address: Address,
+    payload: Vec<u8>,
+}
+
+impl VerifiedMessage {
+    /// Getter for the address the payload was signed for
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Getter for the signed payload
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+}
+
+/// Sign `payload` for the address with bytes `address` the way CIP-30 `signData` does
+///
+/// `private_key` should be the payment key of a Shelley address, or the stake key of a
+/// reward address.
+pub fn sign_data(
+    private_key: &PrivateKey,
+    address: &[u8],
+    payload: &[u8],
+) -> MessageSigningResult<DataSignature> {
+    let protected = encode(|e| {
+        e.map(2)?
+            .i64(ALG_LABEL)?
+            .i64(EDDSA)?
+            .str(ADDRESS_LABEL)?
+            .bytes(address)?;
+        Ok(())
+    })?;
+    let signature = private_key
+        .sign(&sig_structure(&protected, payload)?)
+        .to_bytes();
+    let sign1 = encode(|e| {
+        e.array(4)?
+            .bytes(&protected)?
+            .map(1)?
+            .str(HASHED_LABEL)?
+            .bool(false)?
+            .bytes(payload)?
+            .bytes(&signature)?;
+        Ok(())
+    })?;
+    let key = encode(|e| {
+        e.map(4)?
+            .i64(KTY_LABEL)?
+            .i64(OKP)?
+            .i64(KEY_ALG_LABEL)?
+            .i64(EDDSA)?
+            .i64(CRV_LABEL)?
+            .i64(ED25519)?
+            .i64(X_LABEL)?
+            .bytes(&private_key.to_public().as_bytes())?;
+        Ok(())
+    })?;
+    Ok(DataSignature::new(sign1, key))
+}
+
+struct CoseSign1 {
+    protected: Vec<u8>,
+    address: Vec<u8>,
+    payload: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+impl CoseSign1 {
+    fn decode(bytes: &[u8]) -> MessageSigningResult<Self> {
+        let mut decoder = Decoder::new(bytes);
+        if decoder.datatype().map_err(malformed)? == Type::Tag {
+            let tag = decoder.tag().map_err(malformed)?;
+            if u64::from(tag) != COSE_SIGN1_TAG {
+                return Err(MessageSigningError::Malformed(format!(
+                    "expected COSE_Sign1 tag, found {tag:?}"
+                )))
+            }
+        }
+        if decoder.array().map_err(malformed)? != Some(4) {
+            return Err(MessageSigningError::Malformed(
+                "COSE_Sign1 must have 4 items".to_string(),
+            ))
+        }
+        let protected = decoder.bytes().map_err(malformed)?.to_vec();
+        decoder.skip().map_err(malformed)?;
+        let payload = match decoder.datatype().map_err(malformed)? {
+            Type::Null => {
+                return Err(MessageSigningError::Unsupported(
+                    "detached payloads".to_string(),
+                ))
+            }
+            _ => decoder.bytes().map_err(malformed)?.to_vec(),
+        };
+        let signature = decoder.bytes().map_err(malformed)?.to_vec();
+        let address = decode_protected(&protected)?;
+        Ok(CoseSign1 {
+            protected,
+            address,
+            payload,
+            signature,
+        })
+    }
+}
+
+// Check the algorithm and pull out the address of the protected headers
+fn decode_protected(protected: &[u8]) -> MessageSigningResult<Vec<u8>> {
+    let mut decoder = Decoder::new(protected);
+    let entries = decoder.map().map_err(malformed)?.ok_or_else(|| {
+        MessageSigningError::Malformed("indefinite headers".to_string())
+    })?;
+    let mut alg = None;
+    let mut address = None;
+    for _ in 0..entries {
+        match decoder.datatype().map_err(malformed)? {
+            Type::String => {
+                let label = decoder.str().map_err(malformed)?;
+                if label == ADDRESS_LABEL {
+                    address = Some(decoder.bytes().map_err(malformed)?.to_vec());
+                } else {
+                    decoder.skip().map_err(malformed)?;
+                }
+            }
+            _ => {
+                let label = decoder.i64().map_err(malformed)?;
+                if label == ALG_LABEL {
+                    alg = Some(decoder.i64().map_err(malformed)?);
+                } else {
+                    decoder.skip().map_err(malformed)?;
+                }
+            }
+        }
+    }
+    if alg != Some(EDDSA) {
+        return Err(MessageSigningError::Unsupported(format!(
+            "algorithm {alg:?}"
+        )))
+    }
+    address.ok_or_else(|| {
+        MessageSigningError::Malformed("no address in protected headers".to_string())
+    })
+}
+
+fn decode_cose_key(key: &[u8]) -> MessageSigningResult<PublicKey> {
+    let mut decoder = Decoder::new(key);
+    let entries = decoder
+        .map()
+        .map_err(malformed)?
+        .ok_or_else(|| MessageSigningError::Malformed("indefinite key".to_string()))?;
+    let (mut kty, mut crv, mut x) = (None, None, None);
+    for _ in 0..entries {
+        let label = match decoder.datatype().map_err(malformed)? {
+            Type::String => {
+                decoder.str().map_err(malformed)?;
+                None
+            }
+            _ => Some(decoder.i64().map_err(malformed)?),
+        };
+        match label {
+            Some(KTY_LABEL) => kty = Some(decoder.i64().map_err(malformed)?),
+            Some(CRV_LABEL) => crv = Some(decoder.i64().map_err(malformed)?),
+            Some(X_LABEL) => x = Some(decoder.bytes().map_err(malformed)?.to_vec()),
+            _ => decoder.skip().map_err(malformed)?,
+        }
+    }
+    if kty != Some(OKP) || crv != Some(ED25519) {
+        return Err(MessageSigningError::Unsupported(format!(
+            "key type {kty:?} on curve {crv:?}"
+        )))
+    }
+    let x =
+        x.ok_or_else(|| MessageSigningError::Malformed("no public key".to_string()))?;
+    PublicKey::from_bytes(&x).map_err(|e| MessageSigningError::Malformed(e.to_string()))
+}
+
+// The bytes actually signed: `["Signature1", protected, external_aad, payload]`
+fn sig_structure(protected: &[u8], payload: &[u8]) -> MessageSigningResult<Vec<u8>> {
+    encode(|e| {
+        e.array(4)?
+            .str(SIGNATURE1_CONTEXT)?
+            .bytes(protected)?
+            .bytes(&[])?
+            .bytes(payload)?;
+        Ok(())
+    })
+}
+
+// Key hash the signing key must match for `address`
+fn key_credential(address: &Address) -> Option<Vec<u8>> {
+    match address {
+        Address::Shelley(shelley) => match shelley.payment() {
+            ShelleyPaymentPart::Key(hash) => Some(hash.to_vec()),
+            ShelleyPaymentPart::Script(_) => None,
+        },
+        Address::Stake(stake) => match stake.payload() {
+            StakePayload::Stake(hash) => Some(hash.to_vec()),
+            StakePayload::Script(_) => None,
+        },
+        Address::Byron(_) => None,
+    }
+}
+
+fn encode(
+    write: impl FnOnce(
+        &mut Encoder<Vec<u8>>,
+    ) -> Result<(), minicbor::encode::Error<std::convert::Infallible>>,
+) -> MessageSigningResult<Vec<u8>> {
+    let mut encoder = Encoder::new(Vec::new());
+    write(&mut encoder).map_err(|e| MessageSigningError::Encoding(e.to_string()))?;
+    Ok(encoder.into_writer())
+}
+
+fn malformed(error: minicbor::decode::Error) -> MessageSigningError {
+    MessageSigningError::Malformed(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use cardano_multiplatform_lib::address::{
+        BaseAddress,
+        StakeCredential,
+    };
+
+    fn key_and_address() -> (PrivateKey, Vec<u8>) {
+        let payment_key = PrivateKey::generate_ed25519().unwrap();
+        let stake_key = PrivateKey::generate_ed25519().unwrap();
+        let address = BaseAddress::new(
+            0,
+            &StakeCredential::from_keyhash(&payment_key.to_public().hash()),
+            &StakeCredential::from_keyhash(&stake_key.to_public().hash()),
+        )
+        .to_address()
+        .to_bytes();
+        (payment_key, address)
+    }
+
+    #[test]
+    fn verify__accepts_signature_from_address_key() {
+        // given
+        let (key, address) = key_and_address();
+        let signature = sign_data(&key, &address, b"login nonce 42").unwrap();
+
+        // when
+        let verified = signature.verify().unwrap();
+
+        // then
+        assert_eq!(verified.payload(), b"login nonce 42");
+        assert_eq!(verified.address().to_vec(), address);
+    }
+
+    #[test]
+    fn verify__rejects_tampering_and_keys_of_other_addresses() {
+        // given
+        let (key, address) = key_and_address();
+        let (other_key, _) = key_and_address();
+        let signature = sign_data(&key, &address, b"login nonce 42").unwrap();
+        let mut tampered = signature.signature().to_vec();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+
+        // when
+        let bad_signature =
+            DataSignature::new(tampered, signature.key().to_vec()).verify();
+        let wrong_key = sign_data(&other_key, &address, b"login nonce 42")
+            .unwrap()
+            .verify();
+
+        // then
+        assert_eq!(bad_signature, Err(MessageSigningError::BadSignature));
+        assert_eq!(
+            wrong_key,
+            Err(MessageSigningError::WrongKey(hex::encode(&address)))
+        );
+    }
+}
@@ -1,101 +1,201 @@
-use crate::trireme_ledger_client::Network;
+use crate::{
+    slot_config::{
+        SlotConfig,
+        SlotConfigError,
+    },
+    transaction::era::Era,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::fmt;
+
+/// The networks supported by Trireme
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(tag = "type")]
+pub enum Network {
+    /// "Preprod" Cardano test network
+    Preprod,
+    /// "Preview" Cardano test network
+    Preview,
+    /// Cardano Mainnet
+    Mainnet,
+    /// Any other network, e.g. a local devnet
+    Custom {
+        /// Protocol magic identifying the network
+        protocol_magic: u32,
+        /// Network id used in addresses
+        network_id: u8,
+        /// Length of a slot in seconds
+        slot_length: i64,
+        /// POSIX time in seconds of `starting_slot_number`
+        starting_slot_time: i64,
+        /// Slot from which `slot_length` applies
+        starting_slot_number: u64,
+    },
+}
+
+impl Network {
+    /// Protocol magic identifying the network
+    pub fn protocol_magic(&self) -> u32 {
+        match self {
+            Network::Mainnet => 764824073,
+            Network::Preprod => 1,
+            Network::Preview => 2,
+            Network::Custom { protocol_magic, .. } => *protocol_magic,
+        }
+    }
+
+    /// How slots map to POSIX time on the network
+    ///
+    /// Fails for custom networks whose slot settings are out of range.
+    pub fn slot_config(&self) -> Result<SlotConfig, SlotConfigError> {
+        match self {
+            Network::Mainnet => Ok(SlotConfig::MAINNET),
+            Network::Preprod => Ok(SlotConfig::PREPROD),
+            Network::Preview => Ok(SlotConfig::PREVIEW),
+            Network::Custom {
+                slot_length,
+                starting_slot_time,
+                starting_slot_number,
+                ..
+            } => SlotConfig::from_secs(
+                *starting_slot_time,
+                *starting_slot_number,
+                *slot_length,
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Network::Preprod => write!(f, "Preprod"),
+            Network::Preview => write!(f, "Preview"),
+            Network::Mainnet => write!(f, "Mainnet"),
+            Network::Custom { protocol_magic, .. } => {
+                write!(f, "Custom (magic {protocol_magic})")
+            }
+        }
+    }
+}
+
+impl From<Network> for u8 {
+    fn from(network: Network) -> Self {
+        match network {
+            Network::Mainnet => 1,
+            Network::Preprod | Network::Preview => 0,
+            Network::Custom { network_id, .. } => network_id,
+        }
+    }
+}
 
 /// Network Settings
 #[derive(Debug, Clone, Copy)]
 pub struct NetworkSettings {
     network: u8,
-    slot_length: i64,
-    starting_slot_time: i64,
-    starting_slot_number: u64,
+    slot_config: SlotConfig,
+    era: Era,
 }
 
 impl NetworkSettings {
-    /// Constructor for the [`NetworkSettings`] struct
+    /// Constructor for the [`NetworkSettings`] struct, with the slot config in seconds
+    ///
+    /// Fails unless `slot_length` is positive and the times fit in milliseconds.
     pub fn new(
         network: u8,
         slot_length: i64,
         starting_slot_time: i64,
         starting_slot_number: u64,
-    ) -> Self {
+    ) -> Result<Self, SlotConfigError> {
+        let slot_config =
+            SlotConfig::from_secs(starting_slot_time, starting_slot_number, slot_length)?;
+        Ok(NetworkSettings::from_slot_config(network, slot_config))
+    }
+
+    /// Constructor for the [`NetworkSettings`] of `network` with `slot_config`
+    pub fn from_slot_config(network: u8, slot_config: SlotConfig) -> Self {
         NetworkSettings {
             network,
-            slot_length,
-            starting_slot_time,
-            starting_slot_number,
+            slot_config,
+            era: Era::default(),
         }
     }
 
+    /// Build transactions for `era`, e.g. for a devnet that hasn't hard forked yet
+    pub fn with_era(mut self, era: Era) -> Self {
+        self.era = era;
+        self
+    }
+
     /// Getter for the network
     pub fn network(&self) -> u8 {
         self.network
     }
 
-    /// Getter for the slot length
+    /// Getter for the slot config
+    pub fn slot_config(&self) -> SlotConfig {
+        self.slot_config
+    }
+
+    /// Getter for the era transactions are built for
+    pub fn era(&self) -> Era {
+        self.era
+    }
+
+    /// Getter for the slot length in seconds
     pub fn slot_length(&self) -> i64 {
-        self.slot_length
+        self.slot_config.slot_length() as i64 / MILLIS_PER_SEC
     }
 
-    /// Getter for the starting slot time
+    /// Getter for the starting slot time in seconds
     pub fn starting_slot_time(&self) -> i64 {
-        self.starting_slot_time
+        self.slot_config.zero_time().div_euclid(MILLIS_PER_SEC)
     }
 
     /// Getter for the starting slot number
     pub fn starting_slot_number(&self) -> u64 {
-        self.starting_slot_number
+        self.slot_config.zero_slot()
     }
 
-    /// Converts a POSIX timestamp to a slot number
+    /// Converts a POSIX timestamp in seconds to a slot number
     pub fn slot_from_posix(&self, posix: i64) -> Option<u64> {
-        let time_s = posix.checked_sub(self.starting_slot_time())?;
-        let abs_slot = (time_s / self.slot_length()) as u64 + self.starting_slot_number();
-        Some(abs_slot)
+        self.slot_config.slot_from_posix_secs(posix)
     }
 
-    /// Converts a slot number to a POSIX timestamp
+    /// Converts a slot number to a POSIX timestamp in seconds
     pub fn posix_from_slot(&self, slot: u64) -> i64 {
-        let time_s = (slot - self.starting_slot_number()) as i64 * self.slot_length();
-        time_s + self.starting_slot_time()
+        self.slot_config.posix_secs_from_slot(slot)
     }
 }
 
+const MILLIS_PER_SEC: i64 = 1000;
 const MAINNET_NETWORK: u8 = 1;
-const MAINNET_SLOT_LENGTH: i64 = 1;
-const MAINNET_STARTING_SLOT_TIME: i64 = 1596059091;
-const MAINNET_STARTING_SLOT_NUMBER: u64 = 4492800;
-
 const PRE_PROD_NETWORK: u8 = 0;
-const PRE_PROD_SLOT_LENGTH: i64 = 1;
-const PRE_PROD_STARTING_SLOT_TIME: i64 = 1655769600;
-const PRE_PROD_STARTING_SLOT_NUMBER: u64 = 86400;
-
 const PREVIEW_NETWORK: u8 = 0;
-const PREVIEW_SLOT_LENGTH: i64 = 1;
-const PREVIEW_STARTING_SLOT_TIME: i64 = 1666656000;
-const PREVIEW_STARTING_SLOT_NUMBER: u64 = 0;
 
-impl From<Network> for NetworkSettings {
-    fn from(network: Network) -> Self {
-        match network {
-            Network::Preprod => NetworkSettings::new(
-                PRE_PROD_NETWORK,
-                PRE_PROD_SLOT_LENGTH,
-                PRE_PROD_STARTING_SLOT_TIME,
-                PRE_PROD_STARTING_SLOT_NUMBER,
-            ),
-            Network::Mainnet => NetworkSettings::new(
-                MAINNET_NETWORK,
-                MAINNET_SLOT_LENGTH,
-                MAINNET_STARTING_SLOT_TIME,
-                MAINNET_STARTING_SLOT_NUMBER,
-            ),
-            Network::Preview => NetworkSettings::new(
-                PREVIEW_NETWORK,
-                PREVIEW_SLOT_LENGTH,
-                PREVIEW_STARTING_SLOT_TIME,
-                PREVIEW_STARTING_SLOT_NUMBER,
-            ),
-        }
+impl TryFrom<Network> for NetworkSettings {
+    type Error = SlotConfigError;
+
+    fn try_from(network: Network) -> Result<Self, Self::Error> {
+        let slot_config = network.slot_config()?;
+        let network_settings = match network {
+            Network::Preprod => {
+                NetworkSettings::from_slot_config(PRE_PROD_NETWORK, slot_config)
+            }
+            Network::Mainnet => {
+                NetworkSettings::from_slot_config(MAINNET_NETWORK, slot_config)
+            }
+            Network::Preview => {
+                NetworkSettings::from_slot_config(PREVIEW_NETWORK, slot_config)
+            }
+            Network::Custom { network_id, .. } => {
+                NetworkSettings::from_slot_config(network_id, slot_config)
+            }
+        };
+        Ok(network_settings)
     }
 }
 
@@ -105,11 +205,35 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn try_from__rejects_custom_networks_without_positive_slot_lengths() {
+        // given
+        let custom = |slot_length| Network::Custom {
+            protocol_magic: 42,
+            network_id: 0,
+            slot_length,
+            starting_slot_time: 0,
+            starting_slot_number: 0,
+        };
+
+        // when
+        let zero = NetworkSettings::try_from(custom(0));
+        let negative = NetworkSettings::try_from(custom(-1));
+
+        // then
+        assert_eq!(zero.unwrap_err(), SlotConfigError::InvalidSlotLength(0));
+        assert_eq!(
+            negative.unwrap_err(),
+            SlotConfigError::InvalidSlotLength(-1)
+        );
+        assert!(NetworkSettings::try_from(custom(1)).is_ok());
+    }
+
     #[test]
     fn slot_from_posix__mainnet() {
         // given
         let network = Network::Mainnet;
-        let network_settings = NetworkSettings::from(network);
+        let network_settings = NetworkSettings::try_from(network).unwrap();
 
         // when
         let posix = 1693686614;
@@ -124,7 +248,7 @@ mod tests {
     fn slot_from_posix__preprod() {
         // given
         let network = Network::Preprod;
-        let network_settings = NetworkSettings::from(network);
+        let network_settings = NetworkSettings::try_from(network).unwrap();
 
         // when
         let posix = 1693686777;
@@ -139,7 +263,7 @@ mod tests {
     fn slot_from_posix__preview() {
         // given
         let network = Network::Preview;
-        let network_settings = NetworkSettings::from(network);
+        let network_settings = NetworkSettings::try_from(network).unwrap();
 
         // when
         let posix = 1693686823;
@@ -154,7 +278,7 @@ mod tests {
     fn posix_from_slot__mainnet() {
         // given
         let network = Network::Mainnet;
-        let network_settings = NetworkSettings::from(network);
+        let network_settings = NetworkSettings::try_from(network).unwrap();
 
         // when
         let posix = 102120323;
@@ -169,7 +293,7 @@ mod tests {
     fn posix_from_slot__preprod() {
         // given
         let network = Network::Preprod;
-        let network_settings = NetworkSettings::from(network);
+        let network_settings = NetworkSettings::try_from(network).unwrap();
 
         // when
         let posix = 38003577;
@@ -184,7 +308,7 @@ mod tests {
     fn posix_from_slot__preview() {
         // given
         let network = Network::Preview;
-        let network_settings = NetworkSettings::from(network);
+        let network_settings = NetworkSettings::try_from(network).unwrap();
 
         // when
         let posix = 27030823;
@@ -0,0 +1,95 @@
+use crate::trireme_ledger_client::cml_client::{
+    error::Result,
+    ogmios_scrolls_ledger::evaluate_tx,
+    protocol_params_cache::EpochParameters,
+    ExecutionCost,
+    Ledger,
+    UTxO,
+};
+use async_trait::async_trait;
+use cardano_multiplatform_lib::{
+    address::Address as CMLAddress,
+    Transaction as CMLTransaction,
+};
+use ogmios_client::OgmiosClient;
+use std::collections::HashMap;
+
+/// [`Ledger`] that evaluates scripts with Ogmios `EvaluateTx`, and reads and submits with
+/// the wrapped ledger
+///
+/// Ogmios runs the scripts against the node's ledger state, so redeemer budgets are exact
+/// instead of estimated. That avoids both over-padded fees and "budget exceeded" failures
+/// when submitting.
+pub struct OgmiosEvaluatedLedger<L: Ledger> {
+    inner: L,
+    ogmios_client: OgmiosClient,
+}
+
+impl<L: Ledger> OgmiosEvaluatedLedger<L> {
+    /// Constructor for the [`OgmiosEvaluatedLedger`], evaluating with `ogmios_client`
+    pub fn new(inner: L, ogmios_client: OgmiosClient) -> Self {
+        OgmiosEvaluatedLedger {
+            inner,
+            ogmios_client,
+        }
+    }
+
+    /// Getter for the wrapped ledger
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<L: Ledger + Send + Sync> Ledger for OgmiosEvaluatedLedger<L> {
+    async fn last_block_time_secs(&self) -> Result<i64> {
+        self.inner.last_block_time_secs().await
+    }
+
+    async fn get_utxos_for_addr(
+        &self,
+        addr: &CMLAddress,
+        count: usize,
+    ) -> Result<Vec<UTxO>> {
+        self.inner.get_utxos_for_addr(addr, count).await
+    }
+
+    async fn get_all_utxos_for_addr(&self, addr: &CMLAddress) -> Result<Vec<UTxO>> {
+        self.inner.get_all_utxos_for_addr(addr).await
+    }
+
+    async fn get_utxo_page_for_addr(
+        &self,
+        addr: &CMLAddress,
+        page: usize,
+    ) -> Result<Vec<UTxO>> {
+        self.inner.get_utxo_page_for_addr(addr, page).await
+    }
+
+    async fn calculate_ex_units(
+        &self,
+        tx: &CMLTransaction,
+    ) -> Result<HashMap<u64, ExecutionCost>> {
+        evaluate_tx(&self.ogmios_client, tx).await
+    }
+
+    async fn submit_transaction(&self, tx: &CMLTransaction) -> Result<String> {
+        self.inner.submit_transaction(tx).await
+    }
+
+    async fn submit_transaction_cbor(&self, cbor: &[u8]) -> Result<String> {
+        self.inner.submit_transaction_cbor(cbor).await
+    }
+
+    async fn get_transaction_cbor(&self, tx_id: &str) -> Result<Vec<u8>> {
+        self.inner.get_transaction_cbor(tx_id).await
+    }
+
+    async fn is_tx_confirmed(&self, tx_id: &str) -> Result<bool> {
+        self.inner.is_tx_confirmed(tx_id).await
+    }
+
+    async fn epoch_parameters(&self) -> Result<EpochParameters> {
+        self.inner.epoch_parameters().await
+    }
+}
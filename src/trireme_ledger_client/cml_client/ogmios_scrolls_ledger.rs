@@ -1,12 +1,15 @@
-use crate::trireme_ledger_client::cml_client::{
-    error::{
-        CMLLCError,
-        Result,
+use crate::{
+    ledger_client::provider_error::ProviderError,
+    trireme_ledger_client::cml_client::{
+        error::{
+            CMLLCError,
+            Result,
+        },
+        network_settings::NetworkSettings,
+        ExecutionCost,
+        Ledger,
+        UTxO,
     },
-    network_settings::NetworkSettings,
-    ExecutionCost,
-    Ledger,
-    UTxO,
 };
 use async_trait::async_trait;
 use cardano_multiplatform_lib::{
@@ -34,8 +37,13 @@ use scrolls_client::{
     UTxO as ScrollsClientUTxO,
     UTxOsByAddress,
 };
+use serde::Deserialize;
+use serde_json::json;
 use std::collections::HashMap;
 
+const OGMIOS: &str = "Ogmios";
+const SCROLLS: &str = "Scrolls";
+
 fn utxo_from_scrolls_utxo(utxo: &ScrollsClientUTxO) -> Result<UTxO> {
     let tx_hash = TransactionHash::from_hex(utxo.tx_hash())
         .map_err(|e| CMLLCError::JsError(e.to_string()))?;
@@ -49,6 +57,7 @@ fn utxo_from_scrolls_utxo(utxo: &ScrollsClientUTxO) -> Result<UTxO> {
         None
     };
 
+    // TODO: Scrolls' `UTxOsByAddress` doesn't index reference scripts, so they are left off
     Ok(UTxO::new(tx_hash, output_index, amount, datum))
 }
 
@@ -96,6 +105,7 @@ pub struct OgmiosScrollsLedger {
     scrolls_client: ScrollsClient,
     ogmios_client: OgmiosClient,
     network_settings: NetworkSettings,
+    ogmios_url: Option<String>,
     // TODO: WS Client for Ogmios data
 }
 
@@ -110,9 +120,17 @@ impl OgmiosScrollsLedger {
             scrolls_client,
             ogmios_client,
             network_settings,
+            ogmios_url: None,
         }
     }
 
+    /// Check whether transactions are confirmed with the HTTP interface of Ogmios at `url`,
+    /// e.g. `http://localhost:1337`
+    pub fn with_ogmios_url(mut self, url: &str) -> Self {
+        self.ogmios_url = Some(url.trim_end_matches('/').to_string());
+        self
+    }
+
     /// Get the UTxOs for an address
     pub async fn get_utxos(&self, addr: &CMLAddress) -> Result<Vec<UTxO>> {
         let address_str = addr
@@ -121,7 +139,11 @@ impl OgmiosScrollsLedger {
         let address = Address::from_bech32(&address_str)?;
         self.scrolls_client
             .get_utxos_for_address(&address)
-            .await?
+            .await
+            .map_err(|e| {
+                let request = format!("UTxOsByAddress {address_str}");
+                ProviderError::new(SCROLLS, &request).with_source(e)
+            })?
             .iter()
             .map(utxo_from_scrolls_utxo)
             .collect()
@@ -131,7 +153,12 @@ impl OgmiosScrollsLedger {
 #[async_trait]
 impl Ledger for OgmiosScrollsLedger {
     async fn last_block_time_secs(&self) -> Result<i64> {
-        let slot = self.scrolls_client.get_last_block_info().await?.slot;
+        let slot = self
+            .scrolls_client
+            .get_last_block_info()
+            .await
+            .map_err(|e| ProviderError::new(SCROLLS, "LastBlockInfo").with_source(e))?
+            .slot;
         Ok(self.network_settings.posix_from_slot(slot))
     }
 
@@ -157,29 +184,112 @@ impl Ledger for OgmiosScrollsLedger {
         &self,
         tx: &CMLTransaction,
     ) -> Result<HashMap<u64, ExecutionCost>> {
-        let bytes = tx.to_bytes();
-        let res = self.ogmios_client.evaluate_tx(&bytes, vec![]).await?;
-        check_for_error(&res)?;
-        parse_evaluation_results(&res)
+        evaluate_tx(&self.ogmios_client, tx).await
     }
 
     async fn submit_transaction(&self, tx: &CMLTransaction) -> Result<String> {
-        let bytes = tx.to_bytes();
-        let res = self.ogmios_client.submit_tx(&bytes).await?;
-        let tx_hash = res
-            .result()
-            .ok_or(CMLLCError::OgmiosResponse(
-                "No transaction hash in response".to_string(),
-            ))?
-            .tx_id()
-            .to_string();
-        Ok(tx_hash)
+        submit_tx(&self.ogmios_client, &tx.to_bytes()).await
     }
+
+    async fn submit_transaction_cbor(&self, cbor: &[u8]) -> Result<String> {
+        submit_tx(&self.ogmios_client, cbor).await
+    }
+
+    async fn get_transaction_cbor(&self, _tx_id: &str) -> Result<Vec<u8>> {
+        Err(CMLLCError::OgmiosResponse(
+            "Looking up transactions by id isn't supported by Ogmios/Scrolls".to_string(),
+        ))
+    }
+
+    // Ogmios only knows the current UTxO set, so this looks for the first output of the
+    // transaction. It reads as unconfirmed again once that output is spent.
+    async fn is_tx_confirmed(&self, tx_id: &str) -> Result<bool> {
+        let Some(url) = &self.ogmios_url else {
+            return Err(CMLLCError::Unsupported(
+                "checking whether transactions are confirmed without an Ogmios URL"
+                    .to_string(),
+            ))
+        };
+        let request = "Query utxo";
+        let query = json!({
+            "type": "jsonwsp/request",
+            "version": "1.0",
+            "servicename": "ogmios",
+            "methodname": "Query",
+            "args": { "query": { "utxo": [{ "txId": tx_id, "index": 0 }] } },
+        });
+        let res = reqwest::Client::new()
+            .post(url)
+            .json(&query)
+            .send()
+            .await
+            .map_err(|e| ProviderError::new(OGMIOS, request).with_source(e))?;
+        if !res.status().is_success() {
+            let error = ProviderError::from_response(OGMIOS, request, res).await;
+            return Err(error.into())
+        }
+        let response: OgmiosQueryResponse = res
+            .json()
+            .await
+            .map_err(|e| ProviderError::new(OGMIOS, request).with_source(e))?;
+        match response.result {
+            serde_json::Value::Array(utxos) => Ok(!utxos.is_empty()),
+            other => {
+                let error =
+                    ProviderError::new(OGMIOS, request).with_body(&other.to_string());
+                Err(error.into())
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct OgmiosQueryResponse {
+    #[serde(default)]
+    result: serde_json::Value,
+}
+
+/// Evaluate the scripts of `tx` with Ogmios
+pub(crate) async fn evaluate_tx(
+    ogmios_client: &OgmiosClient,
+    tx: &CMLTransaction,
+) -> Result<HashMap<u64, ExecutionCost>> {
+    let bytes = tx.to_bytes();
+    let res = ogmios_client
+        .evaluate_tx(&bytes, vec![])
+        .await
+        .map_err(|e| ProviderError::new(OGMIOS, "EvaluateTx").with_source(e))?;
+    check_for_error(&res)?;
+    parse_evaluation_results(&res)
+}
+
+/// Submit `tx` with Ogmios, giving its id
+pub(crate) async fn submit_tx(
+    ogmios_client: &OgmiosClient,
+    tx_bytes: &[u8],
+) -> Result<String> {
+    let res = ogmios_client
+        .submit_tx(tx_bytes)
+        .await
+        .map_err(|e| ProviderError::new(OGMIOS, "SubmitTx").with_source(e))?;
+    if let Some(fault) = res.fault() {
+        let error = ProviderError::new(OGMIOS, "SubmitTx").with_body(&fault.to_string());
+        return Err(error.into())
+    }
+    let tx_hash = res
+        .result()
+        .ok_or(CMLLCError::OgmiosResponse(
+            "No transaction hash in response".to_string(),
+        ))?
+        .tx_id()
+        .to_string();
+    Ok(tx_hash)
 }
 
 fn check_for_error(res: &OgmiosResponse<EvaluationResult>) -> Result<()> {
     if let Some(err) = res.fault() {
-        Err(CMLLCError::OgmiosResponse(err.to_string()))
+        let error = ProviderError::new(OGMIOS, "EvaluateTx").with_body(&err.to_string());
+        Err(error.into())
     } else {
         Ok(())
     }
@@ -204,24 +314,7 @@ fn parse_evaluation_results(
             "Evaluation result is not a JSON object".to_string(),
         ))?
         .iter()
-        .filter_map(|(k, v)| parse_pair(k, v))
+        .filter_map(|(k, v)| ExecutionCost::from_ogmios_entry(k, v))
         .collect();
     Ok(map)
 }
-
-fn parse_pair(key: &str, value: &serde_json::Value) -> Option<(u64, ExecutionCost)> {
-    let split_key = key.split(':').collect::<Vec<_>>();
-    let index = split_key.get(1).and_then(|s| s.parse::<u64>().ok())?;
-    let type_str = split_key.first()?;
-    let value_obj = value.as_object()?;
-    let memory = value_obj.get("memory")?.as_u64()?;
-    let steps = value_obj.get("steps")?.as_u64()?;
-    let ex_cost = match *type_str {
-        "spend" => Some(ExecutionCost::new_spend(memory, steps)),
-        "mint" => Some(ExecutionCost::new_mint(memory, steps)),
-        "withdrawal" => Some(ExecutionCost::new_withdrawal(memory, steps)),
-        "certificate" => Some(ExecutionCost::new_certificate(memory, steps)),
-        _ => None,
-    }?;
-    Some((index, ex_cost))
-}
@@ -0,0 +1,777 @@
+use super::{
+    error::*,
+    issuance_helpers::{
+        can_be_collateral,
+        linear_fee,
+        required_collateral,
+        VASIL_V1_COST_MODEL,
+        VASIL_V2_COST_MODEL,
+    },
+    network_settings::NetworkSettings,
+    plutus_data_interop::PlutusDataInterop,
+    ExecutionType,
+    Keys,
+    Ledger,
+    UTxO,
+    DEFAULT_OUTPUT_LOVELACE,
+};
+use crate::{
+    ledger_client::{
+        LedgerClientError,
+        LedgerClientResult,
+    },
+    output::{
+        DatumAttachment,
+        Output,
+        UnbuiltOutput,
+    },
+    protocol_params::ProtocolParameters,
+    transaction::TransactionVersion,
+    values::Values,
+    PolicyId,
+    UnbuiltTransaction,
+};
+use cardano_multiplatform_lib::{
+    crypto::Vkeywitnesses,
+    Transaction as CMLTransaction,
+};
+use minicbor::Decoder;
+use pallas_crypto::hash::Hasher;
+use pallas_txbuilder::{
+    BuildBabbage,
+    ExUnits,
+    Input,
+    Output as StagedOutput,
+    ScriptKind,
+    StagingTransaction,
+};
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+    HashMap,
+};
+use thiserror::Error;
+use txbuilder_addresses::Address as StagedAddress;
+
+// Bytes added to the body for the signer's witness, which isn't there when the fee is set
+const VKEY_WITNESS_SIZE: u64 = 128;
+// Budget given to each redeemer of the draft that the ledger evaluates
+const DRAFT_EX_UNITS: (u64, u64) = (14_000_000, 10_000_000_000);
+const MAX_FEE_ITERATIONS: usize = 5;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum PallasBuilderError {
+    #[error("pallas-txbuilder failed: {0}")]
+    TxBuilder(String),
+    #[error("Metadata isn't supported when building with pallas-txbuilder")]
+    UnsupportedMetadata,
+    #[error("Extra signers aren't supported when building with pallas-txbuilder")]
+    UnsupportedExtraSigners,
+    #[error("Wallet doesn't have enough funds, missing: {0}")]
+    InsufficientFunds(Values),
+    #[error("Invalid {0}: {1}")]
+    Invalid(&'static str, String),
+    #[error("Fee didn't settle after {0} attempts")]
+    FeeDidNotSettle(usize),
+}
+
+// Transaction hash and output index
+type OutputRef = ([u8; 32], u64);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum RedeemerKey {
+    Spend(OutputRef),
+    Mint([u8; 28]),
+}
+
+struct ScriptSpend {
+    input: OutputRef,
+    values: Values,
+    // Only for inputs with datum hashes, inline datums are already on chain
+    witness_datum: Option<Vec<u8>>,
+    redeemer: Vec<u8>,
+    // `None` when a reference input carries the script
+    script: Option<Vec<u8>>,
+}
+
+struct ScriptMint {
+    policy: [u8; 28],
+    asset_name: Vec<u8>,
+    // Negative for burning
+    amount: i64,
+    redeemer: Vec<u8>,
+    // `None` when a reference input carries the script
+    script: Option<Vec<u8>>,
+}
+
+struct PlannedOutput {
+    address: String,
+    values: Values,
+    datum: Option<Vec<u8>>,
+    inline_datum: bool,
+}
+
+/// Everything about the transaction that doesn't depend on its fee
+struct Plan {
+    script_kind: ScriptKind,
+    cost_model: &'static [i64],
+    script_spends: Vec<ScriptSpend>,
+    mints: Vec<ScriptMint>,
+    wallet_inputs: Vec<(OutputRef, Values)>,
+    reference_inputs: Vec<OutputRef>,
+    outputs: Vec<PlannedOutput>,
+    collateral: Option<OutputRef>,
+    valid_from: Option<u64>,
+    invalid_from: Option<u64>,
+}
+
+/// Balance `tx` against `my_utxos` with pallas-txbuilder instead of CML, giving the
+/// unsigned transaction
+///
+/// Scripts are evaluated by the `ledger` on a draft before the fee is settled, and those
+/// deployed on a reference input aren't witnessed again. Change goes back to `my_address`.
+/// Metadata isn't supported yet and fails with [`PallasBuilderError::UnsupportedMetadata`].
+pub(crate) async fn build_tx<L, Datum, Redeemer>(
+    ledger: &L,
+    tx: &UnbuiltTransaction<Datum, Redeemer>,
+    my_utxos: &[UTxO],
+    my_address: &str,
+    params: &ProtocolParameters,
+    network_settings: &NetworkSettings,
+) -> LedgerClientResult<CMLTransaction>
+where
+    L: Ledger,
+    Datum: PlutusDataInterop + Clone,
+    Redeemer: PlutusDataInterop,
+{
+    if !tx.metadata.is_empty() {
+        return Err(as_failed_to_issue_tx(
+            PallasBuilderError::UnsupportedMetadata,
+        ))
+    }
+    let mut plan = Plan::new(tx, network_settings)?;
+    let available = available_utxos(my_utxos, &plan)?;
+    let mut collateral_amount = None;
+    if plan.has_redeemers() {
+        let (collateral, amount) = select_collateral(my_utxos)?;
+        plan.collateral = Some(collateral);
+        collateral_amount = Some(amount);
+    }
+
+    let mut ex_units = HashMap::new();
+    let mut fee = params.min_fee_b();
+    if plan.has_redeemers() {
+        let draft = plan.build(&available, fee, &ex_units, params, my_address)?;
+        ex_units = evaluate(ledger, &draft).await?;
+    }
+    for _ in 0..MAX_FEE_ITERATIONS {
+        let built = plan.build(&available, fee, &ex_units, params, my_address)?;
+        let required = min_fee(params, built.bytes.len() as u64, &built.ex_units);
+        if required <= fee {
+            // There's no collateral return, so the whole collateral input is put up
+            if collateral_amount
+                .is_some_and(|amount| amount < required_collateral(fee, params))
+            {
+                return Err(LedgerClientError::NoBigEnoughCollateralUTxO)
+            }
+            return CMLTransaction::from_bytes(built.bytes)
+                .map_err(|e| CMLLCError::Deserialize(e.to_string()))
+                .map_err(as_failed_to_issue_tx)
+        }
+        fee = required;
+    }
+    Err(as_failed_to_issue_tx(PallasBuilderError::FeeDidNotSettle(
+        MAX_FEE_ITERATIONS,
+    )))
+}
+
+/// Add the signer's witnesses to a transaction built by [`build_tx`]
+pub(crate) async fn sign_tx<K: Keys + Sync>(
+    tx: CMLTransaction,
+    keys: &K,
+) -> LedgerClientResult<CMLTransaction> {
+    let vkey_witnesses = keys
+        .vkey_witnesses(&tx)
+        .await
+        .map_err(as_failed_to_issue_tx)?;
+    let mut witness_set = tx.witness_set();
+    let mut vkeys = witness_set.vkeys().unwrap_or_else(Vkeywitnesses::new);
+    for vkey_witness in vkey_witnesses.iter() {
+        vkeys.add(vkey_witness);
+    }
+    witness_set.set_vkeys(&vkeys);
+    Ok(CMLTransaction::new(
+        &tx.body(),
+        &witness_set,
+        tx.auxiliary_data(),
+    ))
+}
+
+struct Built {
+    bytes: Vec<u8>,
+    inputs: Vec<OutputRef>,
+    policies: Vec<[u8; 28]>,
+    ex_units: Vec<(u64, u64)>,
+}
+
+impl Plan {
+    fn new<Datum: PlutusDataInterop + Clone, Redeemer: PlutusDataInterop>(
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
+        network_settings: &NetworkSettings,
+    ) -> LedgerClientResult<Self> {
+        let (script_kind, cost_model, language_tag) = match tx.script_version {
+            TransactionVersion::V1 => (ScriptKind::PlutusV1, VASIL_V1_COST_MODEL, 1),
+            TransactionVersion::V2 => (ScriptKind::PlutusV2, VASIL_V2_COST_MODEL, 2),
+        };
+        let witnessed = |script: Vec<u8>| {
+            let hash = Hasher::<224>::hash_tagged(&script, language_tag);
+            tx.reference_script_input(hash.as_ref())
+                .is_none()
+                .then_some(script)
+        };
+        let script_spends = tx
+            .script_inputs()
+            .iter()
+            .map(|(input, redeemer, script)| {
+                let maybe_datum: Option<Datum> = input.datum().to_owned().into();
+                let datum = maybe_datum.ok_or(LedgerClientError::NoDatumOnScriptInput)?;
+                let script_hex = script.script_hex().map_err(as_failed_to_issue_tx)?;
+                Ok(ScriptSpend {
+                    input: output_ref(input)?,
+                    values: input.values().clone(),
+                    witness_datum: input
+                        .datum_hash()
+                        .map(|_| datum.to_plutus_data().to_bytes()),
+                    redeemer: redeemer.to_plutus_data().to_bytes(),
+                    script: witnessed(unwrap_script(&script_hex)?),
+                })
+            })
+            .collect::<LedgerClientResult<_>>()?;
+        let mints = tx
+            .minting
+            .iter()
+            .map(|(amount, asset_name, redeemer, policy)| {
+                let id = policy.id().map_err(as_failed_to_issue_tx)?;
+                let script_hex = policy.script_hex().map_err(as_failed_to_issue_tx)?;
+                Ok(ScriptMint {
+                    policy: decode_array("policy id", &id)?,
+                    asset_name: asset_name.clone().unwrap_or_default(),
+                    amount: *amount,
+                    redeemer: redeemer.to_plutus_data().to_bytes(),
+                    script: witnessed(unwrap_script(&script_hex)?),
+                })
+            })
+            .collect::<LedgerClientResult<_>>()?;
+        let wallet_inputs = tx
+            .specific_wallet_inputs
+            .iter()
+            .map(|input| Ok((output_ref(input)?, input.values().clone())))
+            .collect::<LedgerClientResult<_>>()?;
+        let reference_inputs = tx
+            .reference_inputs
+            .iter()
+            .map(output_ref)
+            .collect::<LedgerClientResult<_>>()?;
+        // Plutus V1 scripts can only read datums from the witnesses
+        let inline_allowed = matches!(tx.script_version, TransactionVersion::V2);
+        let outputs = tx
+            .unbuilt_outputs()
+            .iter()
+            .map(|output| planned_output(output, inline_allowed))
+            .collect();
+        let (lower, upper) = tx.valid_range;
+        let slot = |posix: Option<i64>, bound: &str| {
+            posix
+                .map(|posix| {
+                    network_settings.slot_from_posix(posix).ok_or(
+                        LedgerClientError::ValidityRange(format!(
+                        "Invalid {bound} bounds; Must be after starting slot time: {:?}",
+                        network_settings.starting_slot_time()
+                    )),
+                    )
+                })
+                .transpose()
+        };
+        Ok(Plan {
+            script_kind,
+            cost_model,
+            script_spends,
+            mints,
+            wallet_inputs,
+            reference_inputs,
+            outputs,
+            collateral: None,
+            valid_from: slot(lower, "lower")?,
+            invalid_from: slot(upper, "upper")?,
+        })
+    }
+
+    fn has_redeemers(&self) -> bool {
+        !self.script_spends.is_empty() || !self.mints.is_empty()
+    }
+
+    // Select wallet inputs to cover the outputs and `fee`, then stage and build
+    fn build(
+        &self,
+        available: &[(OutputRef, Values)],
+        fee: u64,
+        ex_units: &HashMap<RedeemerKey, (u64, u64)>,
+        params: &ProtocolParameters,
+        my_address: &str,
+    ) -> LedgerClientResult<Built> {
+        let mut spent: Values = self
+            .script_spends
+            .iter()
+            .map(|spend| spend.values.clone())
+            .chain(self.wallet_inputs.iter().map(|(_, values)| values.clone()))
+            .sum();
+        for mint in &self.mints {
+            let policy = PolicyId::native_token(
+                &hex::encode(mint.policy),
+                &Some(mint.asset_name.clone()),
+            );
+            if let Ok(minted) = u64::try_from(mint.amount) {
+                spent.add_one_value(&policy, minted);
+            }
+        }
+        let mut required: Values = self
+            .outputs
+            .iter()
+            .map(|output| output.values.clone())
+            .sum();
+        for mint in self.mints.iter().filter(|mint| mint.amount < 0) {
+            let policy = PolicyId::native_token(
+                &hex::encode(mint.policy),
+                &Some(mint.asset_name.clone()),
+            );
+            required.add_one_value(&policy, mint.amount.unsigned_abs());
+        }
+        required.add_one_value(&PolicyId::Lovelace, fee);
+
+        let mut inputs: Vec<OutputRef> = self
+            .script_spends
+            .iter()
+            .map(|spend| spend.input)
+            .chain(self.wallet_inputs.iter().map(|(input, _)| *input))
+            .collect();
+        let mut candidates = available.iter();
+        let change = loop {
+            let diff = spent.diff(&required);
+            let change = diff.surplus().clone();
+            let change_lovelace = change.get(&PolicyId::Lovelace).unwrap_or_default();
+            let change_is_enough =
+                change.is_empty() || change_lovelace >= change.min_ada_required(params);
+            if diff.is_covered() && change_is_enough {
+                break change
+            }
+            match candidates.next() {
+                Some((input, values)) => {
+                    inputs.push(*input);
+                    spent.add_values(values);
+                }
+                None => {
+                    let mut missing = diff.shortfall().clone();
+                    if diff.is_covered() {
+                        let min_ada = change.min_ada_required(params);
+                        missing.add_one_value(
+                            &PolicyId::Lovelace,
+                            min_ada - change_lovelace,
+                        );
+                    }
+                    return Err(as_failed_to_issue_tx(
+                        PallasBuilderError::InsufficientFunds(missing),
+                    ))
+                }
+            }
+        };
+
+        let mut staged = StagingTransaction::new().fee(fee);
+        for input in &inputs {
+            staged = staged.input(staged_input(input));
+        }
+        for reference_input in &self.reference_inputs {
+            staged = staged.reference_input(staged_input(reference_input));
+        }
+        for output in &self.outputs {
+            staged = staged.output(staged_output(output)?);
+            if let (Some(datum), false) = (&output.datum, output.inline_datum) {
+                staged = staged.datum(datum.clone());
+            }
+        }
+        if !change.is_empty() {
+            let change_output = PlannedOutput {
+                address: my_address.to_string(),
+                values: change,
+                datum: None,
+                inline_datum: false,
+            };
+            staged = staged.output(staged_output(&change_output)?);
+        }
+
+        let mut used_ex_units = Vec::new();
+        let mut redeemer_ex_units = |key: RedeemerKey| {
+            let (mem, steps) = ex_units.get(&key).copied().unwrap_or(DRAFT_EX_UNITS);
+            used_ex_units.push((mem, steps));
+            Some(ExUnits { mem, steps })
+        };
+        for spend in &self.script_spends {
+            let units = redeemer_ex_units(RedeemerKey::Spend(spend.input));
+            staged = staged.add_spend_redeemer(
+                staged_input(&spend.input),
+                spend.redeemer.clone(),
+                units,
+            );
+            if let Some(datum) = &spend.witness_datum {
+                staged = staged.datum(datum.clone());
+            }
+            if let Some(script) = &spend.script {
+                staged = staged.script(self.script_kind, script.clone());
+            }
+        }
+        let mut policies = BTreeSet::new();
+        for mint in &self.mints {
+            staged = staged
+                .mint_asset(mint.policy.into(), mint.asset_name.clone(), mint.amount)
+                .map_err(tx_builder_error)?;
+            // A policy has one redeemer, however many of its tokens are minted
+            if policies.insert(mint.policy) {
+                let units = redeemer_ex_units(RedeemerKey::Mint(mint.policy));
+                staged = staged.add_mint_redeemer(
+                    mint.policy.into(),
+                    mint.redeemer.clone(),
+                    units,
+                );
+                if let Some(script) = &mint.script {
+                    staged = staged.script(self.script_kind, script.clone());
+                }
+            }
+        }
+        if self.has_redeemers() {
+            staged = staged.language_view(self.script_kind, self.cost_model.to_vec());
+        }
+        if let Some(collateral) = &self.collateral {
+            staged = staged.collateral_input(staged_input(collateral));
+        }
+        if let Some(slot) = self.valid_from {
+            staged = staged.valid_from_slot(slot);
+        }
+        if let Some(slot) = self.invalid_from {
+            staged = staged.invalid_from_slot(slot);
+        }
+
+        let built = staged.build_babbage_raw().map_err(tx_builder_error)?;
+        // The ledger orders inputs and policies by their bytes, and so do redeemer indices
+        inputs.sort();
+        Ok(Built {
+            bytes: built.tx_bytes.to_vec(),
+            inputs,
+            policies: policies.into_iter().collect(),
+            ex_units: used_ex_units,
+        })
+    }
+}
+
+async fn evaluate<L: Ledger>(
+    ledger: &L,
+    draft: &Built,
+) -> LedgerClientResult<HashMap<RedeemerKey, (u64, u64)>> {
+    let draft_tx = CMLTransaction::from_bytes(draft.bytes.clone())
+        .map_err(|e| CMLLCError::Deserialize(e.to_string()))
+        .map_err(as_failed_to_issue_tx)?;
+    let costs = ledger
+        .calculate_ex_units(&draft_tx)
+        .await
+        .map_err(as_failed_to_issue_tx)?;
+    let mut ex_units = HashMap::new();
+    for (index, cost) in costs.iter() {
+        let index = *index as usize;
+        let key = match cost.execution_type {
+            ExecutionType::Spend => {
+                draft.inputs.get(index).map(|i| RedeemerKey::Spend(*i))
+            }
+            ExecutionType::Mint => {
+                draft.policies.get(index).map(|p| RedeemerKey::Mint(*p))
+            }
+            ExecutionType::Certificate | ExecutionType::Withdrawal => None,
+        };
+        if let Some(key) = key {
+            ex_units.insert(key, (cost.memory(), cost.steps()));
+        }
+    }
+    Ok(ex_units)
+}
+
+fn min_fee(params: &ProtocolParameters, tx_size: u64, ex_units: &[(u64, u64)]) -> u64 {
+    linear_fee(params, tx_size + VKEY_WITNESS_SIZE, ex_units)
+}
+
+// Wallet UTxOs that aren't spent already, largest first
+fn available_utxos(
+    my_utxos: &[UTxO],
+    plan: &Plan,
+) -> LedgerClientResult<Vec<(OutputRef, Values)>> {
+    let spent: BTreeSet<OutputRef> =
+        plan.wallet_inputs.iter().map(|(input, _)| *input).collect();
+    let mut available = Vec::new();
+    for utxo in my_utxos {
+        let input = utxo_ref(utxo)?;
+        if !spent.contains(&input) {
+            available.push((input, Values::from(utxo.amount())));
+        }
+    }
+    available.sort_by_key(|(_, values)| {
+        std::cmp::Reverse(values.get(&PolicyId::Lovelace).unwrap_or_default())
+    });
+    Ok(available)
+}
+
+// TODO: This could be less naive (e.g. include multiple UTxOs, etc)
+fn select_collateral(my_utxos: &[UTxO]) -> LedgerClientResult<(OutputRef, u64)> {
+    let smallest = my_utxos
+        .iter()
+        .filter(|utxo| can_be_collateral(utxo))
+        .min_by_key(|utxo| u64::from(utxo.amount().coin()))
+        .ok_or(LedgerClientError::NoBigEnoughCollateralUTxO)?;
+    Ok((utxo_ref(smallest)?, smallest.amount().coin().into()))
+}
+
+fn planned_output<Datum: PlutusDataInterop>(
+    output: &UnbuiltOutput<Datum>,
+    inline_allowed: bool,
+) -> PlannedOutput {
+    let mut values = output.values().to_owned();
+    if values.get(&PolicyId::Lovelace).is_none() {
+        values.add_one_value(&PolicyId::Lovelace, DEFAULT_OUTPUT_LOVELACE);
+    }
+    PlannedOutput {
+        address: output.owner().to_string(),
+        values,
+        datum: output
+            .datum()
+            .map(|datum| datum.to_plutus_data().to_bytes()),
+        inline_datum: inline_allowed
+            && output.datum_attachment() == Some(DatumAttachment::Inline),
+    }
+}
+
+fn staged_input(input: &OutputRef) -> Input {
+    let (tx_hash, index) = *input;
+    Input::new(tx_hash.into(), index)
+}
+
+fn staged_output(output: &PlannedOutput) -> LedgerClientResult<StagedOutput> {
+    let address = StagedAddress::from_bech32(&output.address).map_err(|e| {
+        as_failed_to_issue_tx(PallasBuilderError::Invalid("address", e.to_string()))
+    })?;
+    let lovelace = output.values.get(&PolicyId::Lovelace).unwrap_or_default();
+    let mut assets = BTreeMap::new();
+    for (policy, amount) in output.values.as_iter() {
+        if let PolicyId::NativeToken(id, asset_name) = policy {
+            let policy: [u8; 28] = decode_array("policy id", id)?;
+            assets.insert((policy, asset_name.clone().unwrap_or_default()), *amount);
+        }
+    }
+    let mut staged = StagedOutput::new(address, lovelace);
+    for ((policy, asset_name), amount) in assets {
+        staged = staged
+            .add_asset(policy.into(), asset_name, amount)
+            .map_err(tx_builder_error)?;
+    }
+    match &output.datum {
+        Some(datum) if output.inline_datum => {
+            staged = staged.set_inline_datum(datum.clone());
+        }
+        Some(datum) => {
+            let datum_hash: [u8; 32] = *Hasher::<256>::hash(datum);
+            staged = staged.set_datum_hash(datum_hash.into());
+        }
+        None => {}
+    }
+    Ok(staged)
+}
+
+fn output_ref<Datum>(output: &Output<Datum>) -> LedgerClientResult<OutputRef> {
+    let tx_hash = output.id().tx_hash().try_into().map_err(|_| {
+        as_failed_to_issue_tx(PallasBuilderError::Invalid(
+            "transaction hash",
+            hex::encode(output.id().tx_hash()),
+        ))
+    })?;
+    Ok((tx_hash, output.id().index()))
+}
+
+fn utxo_ref(utxo: &UTxO) -> LedgerClientResult<OutputRef> {
+    let tx_hash = utxo.tx_hash().to_bytes();
+    let tx_hash = tx_hash.as_slice().try_into().map_err(|_| {
+        as_failed_to_issue_tx(PallasBuilderError::Invalid(
+            "transaction hash",
+            hex::encode(&tx_hash),
+        ))
+    })?;
+    Ok((tx_hash, utxo.output_index().into()))
+}
+
+fn decode_array<const N: usize>(
+    what: &'static str,
+    hex: &str,
+) -> LedgerClientResult<[u8; N]> {
+    let invalid =
+        || as_failed_to_issue_tx(PallasBuilderError::Invalid(what, hex.to_string()));
+    let bytes = hex::decode(hex).map_err(|_| invalid())?;
+    bytes.try_into().map_err(|_| invalid())
+}
+
+// Scripts are kept CBOR wrapped, as in their envelopes, but witnessed without the wrapper
+fn unwrap_script(script_hex: &str) -> LedgerClientResult<Vec<u8>> {
+    let cbor = hex::decode(script_hex).map_err(as_failed_to_issue_tx)?;
+    let script = Decoder::new(&cbor)
+        .bytes()
+        .map_err(|e| CMLLCError::Deserialize(e.to_string()))
+        .map_err(as_failed_to_issue_tx)?;
+    Ok(script.to_vec())
+}
+
+fn tx_builder_error(error: pallas_txbuilder::TxBuilderError) -> LedgerClientError {
+    as_failed_to_issue_tx(PallasBuilderError::TxBuilder(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::trireme_ledger_client::cml_client::{
+        network_settings::Network,
+        ExecutionCost,
+    };
+    use async_trait::async_trait;
+    use cardano_multiplatform_lib::{
+        address::Address as CMLAddress,
+        crypto::TransactionHash,
+        ledger::common::value::Value as CMLValue,
+    };
+
+    const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+    const BOB: &str = "addr_test1qzvrhz9v6lwcr26a52y8mmk2nzq37lky68359keq3dgth4lkzpnnjv8vf98m20lhqdzl60mcftq7r2lc4xtcsv0w6xjstag0ua";
+
+    struct NoScriptsLedger;
+
+    #[async_trait]
+    impl Ledger for NoScriptsLedger {
+        async fn last_block_time_secs(&self) -> Result<i64> {
+            unimplemented!()
+        }
+
+        async fn get_utxos_for_addr(
+            &self,
+            _addr: &CMLAddress,
+            _count: usize,
+        ) -> Result<Vec<UTxO>> {
+            unimplemented!()
+        }
+
+        async fn get_all_utxos_for_addr(&self, _addr: &CMLAddress) -> Result<Vec<UTxO>> {
+            unimplemented!()
+        }
+
+        async fn calculate_ex_units(
+            &self,
+            _tx: &CMLTransaction,
+        ) -> Result<HashMap<u64, ExecutionCost>> {
+            unimplemented!()
+        }
+
+        async fn submit_transaction(&self, _tx: &CMLTransaction) -> Result<String> {
+            unimplemented!()
+        }
+
+        async fn get_transaction_cbor(&self, _tx_id: &str) -> Result<Vec<u8>> {
+            unimplemented!()
+        }
+    }
+
+    fn utxo(id: u8, lovelace: u64) -> UTxO {
+        let tx_hash = TransactionHash::from_bytes(vec![id; 32]).unwrap();
+        UTxO::new(tx_hash, 0.into(), CMLValue::new(&lovelace.into()), None)
+    }
+
+    fn payment_to_bob(lovelace: u64) -> UnbuiltTransaction<(), ()> {
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, lovelace);
+        let bob = pallas_addresses::Address::from_bech32(BOB).unwrap();
+        UnbuiltTransaction {
+            script_version: TransactionVersion::V2,
+            script_inputs: vec![],
+            unbuilt_outputs: vec![UnbuiltOutput::new_wallet(bob, values)],
+            minting: vec![],
+            specific_wallet_inputs: vec![],
+            reference_inputs: vec![],
+            valid_range: (None, None),
+            metadata: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn build_tx__pays_fee_and_returns_change() {
+        // given
+        let tx = payment_to_bob(3_000_000);
+        let my_utxos = vec![utxo(1, 2_000_000), utxo(2, 10_000_000)];
+        let params = ProtocolParameters::default();
+
+        // when
+        let built = build_tx(
+            &NoScriptsLedger,
+            &tx,
+            &my_utxos,
+            ALICE,
+            &params,
+            &NetworkSettings::try_from(Network::Preprod).unwrap(),
+        )
+        .await
+        .unwrap();
+
+        // then
+        let body = built.body();
+        let fee: u64 = body.fee().into();
+        let size = built.to_bytes().len() as u64;
+        assert!(fee >= min_fee(&params, size, &[]));
+        assert_eq!(body.inputs().len(), 1);
+        let outputs = body.outputs();
+        let paid: u64 = (0..outputs.len())
+            .map(|i| u64::from(outputs.get(i).amount().coin()))
+            .sum();
+        assert_eq!(paid + fee, 10_000_000);
+    }
+
+    #[tokio::test]
+    async fn build_tx__keeps_reference_inputs() {
+        // given
+        let mut tx = payment_to_bob(3_000_000);
+        let alice = pallas_addresses::Address::from_bech32(ALICE).unwrap();
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, 2_000_000);
+        tx.reference_inputs = vec![Output::new_wallet(vec![9; 32], 1, alice, values)];
+        let my_utxos = vec![utxo(1, 10_000_000)];
+        let params = ProtocolParameters::default();
+
+        // when
+        let built = build_tx(
+            &NoScriptsLedger,
+            &tx,
+            &my_utxos,
+            ALICE,
+            &params,
+            &NetworkSettings::try_from(Network::Preprod).unwrap(),
+        )
+        .await
+        .unwrap();
+
+        // then
+        let reference_inputs = built.body().reference_inputs().unwrap();
+        assert_eq!(reference_inputs.len(), 1);
+        let reference_input = reference_inputs.get(0);
+        assert_eq!(reference_input.transaction_id().to_bytes(), vec![9; 32]);
+        assert_eq!(u64::from(reference_input.index()), 1);
+    }
+}
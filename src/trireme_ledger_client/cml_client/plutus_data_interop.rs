@@ -106,26 +106,17 @@ impl From<CMLConstrPlutusData> for Constr<PlutusData> {
 
 impl From<BigInt> for CMLBigInt {
     fn from(big_int: BigInt) -> Self {
-        match big_int {
-            BigInt::Int { neg, val } => {
-                let mut string = match neg {
-                    true => "-".to_string(),
-                    false => "".to_string(),
-                };
-                string.push_str(&val.to_string());
-                CMLBigInt::from_str(&string).expect("Can this fail?") // TODO: unwrap
-            }
-            BigInt::BigUInt(_) => todo!(),
-            BigInt::BigNInt(_) => todo!(),
-        }
+        CMLBigInt::from_str(&big_int.to_string())
+            .expect("BigInt always displays as a decimal integer")
     }
 }
 
 impl From<CMLBigInt> for BigInt {
     fn from(value: CMLBigInt) -> Self {
-        let string = value.to_str();
-        let num = string.parse::<i64>().unwrap(); // TODO: unwrap
-        num.into()
+        value
+            .to_str()
+            .parse()
+            .expect("CML BigInt always displays as a decimal integer")
     }
 }
 
@@ -185,7 +176,18 @@ mod tests {
         let nau_big_int = BigInt::from(original);
         let cml_big_int = CMLBigInt::from(nau_big_int);
         let new_nau_big_int = BigInt::from(cml_big_int);
-        let new: i64 = new_nau_big_int.into();
+        let new = i64::try_from(new_nau_big_int).unwrap();
         assert_eq!(original, new);
     }
+
+    #[test]
+    fn roundtrip_big_int_beyond_u64() {
+        for original in [i128::MAX, i128::MIN, -(1 << 64) - 1, 1 << 64] {
+            let nau_big_int = BigInt::from(original);
+            let cml_big_int = CMLBigInt::from(nau_big_int.clone());
+            let new_nau_big_int = BigInt::from(cml_big_int);
+            assert_eq!(new_nau_big_int, nau_big_int);
+            assert_eq!(i128::try_from(&new_nau_big_int).unwrap(), original);
+        }
+    }
 }
@@ -0,0 +1,282 @@
+use super::{
+    error::*,
+    Keys,
+};
+use cardano_multiplatform_lib::{
+    crypto::{
+        Ed25519KeyHash,
+        Vkeywitness,
+        Vkeywitnesses,
+    },
+    ledger::common::hash::hash_transaction,
+    Transaction as CMLTransaction,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum ProposalError {
+    #[error("Proposal is still missing signatures from: {0:?}")]
+    MissingSignatures(Vec<String>),
+    #[error("Key {0} isn't one of the proposal's signers")]
+    UnexpectedSigner(String),
+}
+
+/// Transaction built by one party and passed between the others to co-sign, e.g. for an
+/// OTC swap where two wallets spend into a single transaction
+///
+/// Holds the balanced transaction, including the signatures collected so far, and the
+/// payment key hashes of everyone who has to sign it. Serializes to JSON so it can be sent
+/// between parties however they like.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxProposal {
+    tx: String,
+    signers: Vec<String>,
+}
+
+impl TxProposal {
+    /// Constructor for a [`TxProposal`] of `tx`, to be signed by the `signers` key hashes
+    pub fn new(tx: &CMLTransaction, signers: &[Ed25519KeyHash]) -> Self {
+        TxProposal {
+            tx: hex::encode(tx.to_bytes()),
+            signers: signers
+                .iter()
+                .map(|key_hash| hex::encode(key_hash.to_bytes()))
+                .collect(),
+        }
+    }
+
+    /// Read a proposal received from another party
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| CMLLCError::Deserialize(e.to_string()))
+    }
+
+    /// Serialize the proposal to send it to another party
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Proposals always serialize")
+    }
+
+    /// The transaction, with the signatures collected so far
+    pub fn tx(&self) -> Result<CMLTransaction> {
+        CMLTransaction::from_bytes(hex::decode(&self.tx)?)
+            .map_err(|e| CMLLCError::Deserialize(e.to_string()))
+    }
+
+    /// Hex payment key hashes of everyone who has to sign
+    pub fn signers(&self) -> &[String] {
+        &self.signers
+    }
+
+    /// Hex payment key hashes of the signers that haven't signed yet
+    pub fn missing_signers(&self) -> Result<Vec<String>> {
+        let signed = signed_key_hashes(&self.tx()?);
+        let missing = self
+            .signers
+            .iter()
+            .filter(|signer| !signed.contains(signer))
+            .cloned()
+            .collect();
+        Ok(missing)
+    }
+
+    /// Whether all signers have signed
+    pub fn is_fully_signed(&self) -> Result<bool> {
+        Ok(self.missing_signers()?.is_empty())
+    }
+
+    /// Add the signatures of `keys`, which must be one of the signers
+    ///
+    /// Signing again doesn't add more witnesses, and witnesses that don't sign the
+    /// transaction are dropped.
+    pub async fn sign<K: Keys + Sync>(&mut self, keys: &K) -> Result<()> {
+        let key_hash = hex::encode(keys.payment_public_key().await?.hash().to_bytes());
+        if !self.signers.contains(&key_hash) {
+            let error = ProposalError::UnexpectedSigner(key_hash);
+            return Err(CMLLCError::KeyError(Box::new(error)))
+        }
+        let tx = self.tx()?;
+        let new_witnesses = keys.vkey_witnesses(&tx).await?;
+        let mut witness_set = tx.witness_set();
+        let mut vkeys = Vkeywitnesses::new();
+        let mut signed = Vec::new();
+        for witness in valid_witnesses(&tx).into_iter().chain(new_witnesses) {
+            let vkey = witness.vkey().public_key().as_bytes();
+            if !signed.contains(&vkey) {
+                signed.push(vkey);
+                vkeys.add(&witness);
+            }
+        }
+        witness_set.set_vkeys(&vkeys);
+        let signed_tx =
+            CMLTransaction::new(&tx.body(), &witness_set, tx.auxiliary_data());
+        self.tx = hex::encode(signed_tx.to_bytes());
+        Ok(())
+    }
+
+    /// The transaction, once everyone has signed it
+    pub fn signed_tx(&self) -> Result<CMLTransaction> {
+        let missing = self.missing_signers()?;
+        if !missing.is_empty() {
+            let error = ProposalError::MissingSignatures(missing);
+            return Err(CMLLCError::KeyError(Box::new(error)))
+        }
+        self.tx()
+    }
+}
+
+// Witnesses whose signature checks out against the body of `tx`
+fn valid_witnesses(tx: &CMLTransaction) -> Vec<Vkeywitness> {
+    let Some(vkeys) = tx.witness_set().vkeys() else {
+        return Vec::new()
+    };
+    let tx_hash = hash_transaction(&tx.body()).to_bytes();
+    (0..vkeys.len())
+        .map(|i| vkeys.get(i))
+        .filter(|witness| {
+            witness
+                .vkey()
+                .public_key()
+                .verify(&tx_hash, &witness.signature())
+        })
+        .collect()
+}
+
+fn signed_key_hashes(tx: &CMLTransaction) -> Vec<String> {
+    let mut signed: Vec<String> = valid_witnesses(tx)
+        .iter()
+        .map(|witness| hex::encode(witness.vkey().public_key().hash().to_bytes()))
+        .collect();
+    signed.sort();
+    signed.dedup();
+    signed
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use async_trait::async_trait;
+    use cardano_multiplatform_lib::{
+        address::{
+            BaseAddress,
+            StakeCredential,
+        },
+        crypto::{
+            PrivateKey,
+            TransactionHash,
+        },
+        ledger::common::value::BigNum,
+        TransactionBody,
+        TransactionInput,
+        TransactionInputs,
+        TransactionOutputs,
+        TransactionWitnessSet,
+    };
+
+    struct InMemoryKeys(Vec<u8>);
+
+    impl InMemoryKeys {
+        fn generate() -> Self {
+            InMemoryKeys(PrivateKey::generate_ed25519().unwrap().as_bytes())
+        }
+
+        fn key_hash(&self) -> Ed25519KeyHash {
+            PrivateKey::from_normal_bytes(&self.0)
+                .unwrap()
+                .to_public()
+                .hash()
+        }
+    }
+
+    #[async_trait]
+    impl Keys for InMemoryKeys {
+        async fn base_addr(&self) -> Result<BaseAddress> {
+            let credential = StakeCredential::from_keyhash(&self.key_hash());
+            Ok(BaseAddress::new(0, &credential, &credential))
+        }
+
+        async fn private_key(&self) -> Result<PrivateKey> {
+            PrivateKey::from_normal_bytes(&self.0)
+                .map_err(|e| CMLLCError::JsError(e.to_string()))
+        }
+    }
+
+    fn unsigned_tx() -> CMLTransaction {
+        let mut inputs = TransactionInputs::new();
+        let input_hash = TransactionHash::from_bytes(vec![7; 32]).unwrap();
+        inputs.add(&TransactionInput::new(&input_hash, &BigNum::from(0)));
+        let body = TransactionBody::new(
+            &inputs,
+            &TransactionOutputs::new(),
+            &BigNum::from(170_000),
+            None,
+        );
+        CMLTransaction::new(&body, &TransactionWitnessSet::new(), None)
+    }
+
+    #[tokio::test]
+    async fn sign__collects_signatures_from_each_party_until_fully_signed() {
+        // given
+        let alice = InMemoryKeys::generate();
+        let bob = InMemoryKeys::generate();
+        let eve = InMemoryKeys::generate();
+        let proposal =
+            TxProposal::new(&unsigned_tx(), &[alice.key_hash(), bob.key_hash()]);
+
+        // when
+        let mut received_by_alice = TxProposal::from_json(&proposal.to_json()).unwrap();
+        received_by_alice.sign(&alice).await.unwrap();
+        let half_signed = received_by_alice.signed_tx();
+        let mut received_by_bob =
+            TxProposal::from_json(&received_by_alice.to_json()).unwrap();
+        let eve_signing = received_by_bob.sign(&eve).await;
+        received_by_bob.sign(&bob).await.unwrap();
+
+        // then
+        assert!(half_signed.is_err());
+        assert!(eve_signing.is_err());
+        assert!(received_by_bob.is_fully_signed().unwrap());
+        let signed_tx = received_by_bob.signed_tx().unwrap();
+        assert_eq!(signed_tx.witness_set().vkeys().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn sign__ignores_repeated_and_invalid_signatures() {
+        // given
+        let alice = InMemoryKeys::generate();
+        let bob = InMemoryKeys::generate();
+        let tx = unsigned_tx();
+        let other_body = TransactionBody::new(
+            &tx.body().inputs(),
+            &TransactionOutputs::new(),
+            &BigNum::from(1),
+            None,
+        );
+        let other_tx =
+            CMLTransaction::new(&other_body, &TransactionWitnessSet::new(), None);
+        let mut forged = Vkeywitnesses::new();
+        for witness in bob.vkey_witnesses(&other_tx).await.unwrap() {
+            forged.add(&witness);
+        }
+        let mut witness_set = TransactionWitnessSet::new();
+        witness_set.set_vkeys(&forged);
+        let forged_tx = CMLTransaction::new(&tx.body(), &witness_set, None);
+        let mut proposal =
+            TxProposal::new(&forged_tx, &[alice.key_hash(), bob.key_hash()]);
+
+        // when
+        proposal.sign(&alice).await.unwrap();
+        proposal.sign(&alice).await.unwrap();
+
+        // then
+        let missing = proposal.missing_signers().unwrap();
+        assert_eq!(missing, vec![hex::encode(bob.key_hash().to_bytes())]);
+        let vkeys = proposal.tx().unwrap().witness_set().vkeys().unwrap();
+        assert_eq!(vkeys.len(), 1);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::protocol_params::ProtocolParameters;
+use std::sync::Mutex;
+
+/// How long fetched protocol parameters are used for, unless the epoch ends first
+pub const DEFAULT_PROTOCOL_PARAMS_TTL_SECS: i64 = 60 * 60;
+
+/// Protocol parameters of the current epoch, as fetched from a
+/// [`Ledger`](super::Ledger)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EpochParameters {
+    params: ProtocolParameters,
+    epoch_end_secs: Option<i64>,
+}
+
+impl EpochParameters {
+    /// Constructor for [`EpochParameters`] that may change at `epoch_end_secs`, or whenever
+    /// the ledger can't tell
+    pub fn new(params: ProtocolParameters, epoch_end_secs: Option<i64>) -> Self {
+        EpochParameters {
+            params,
+            epoch_end_secs,
+        }
+    }
+
+    /// Getter for the protocol parameters
+    pub fn params(&self) -> &ProtocolParameters {
+        &self.params
+    }
+
+    /// Getter for the POSIX time in seconds when the epoch ends
+    pub fn epoch_end_secs(&self) -> Option<i64> {
+        self.epoch_end_secs
+    }
+}
+
+/// Fetched protocol parameters, kept until the end of their epoch or for a TTL,
+/// whichever comes first
+#[derive(Debug)]
+pub(crate) struct ProtocolParamsCache {
+    ttl_secs: i64,
+    cached: Mutex<Option<(ProtocolParameters, i64)>>,
+}
+
+impl Default for ProtocolParamsCache {
+    fn default() -> Self {
+        ProtocolParamsCache::new(DEFAULT_PROTOCOL_PARAMS_TTL_SECS)
+    }
+}
+
+impl ProtocolParamsCache {
+    pub fn new(ttl_secs: i64) -> Self {
+        ProtocolParamsCache {
+            ttl_secs,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Cached parameters, if they haven't expired by `now_secs`
+    pub fn get(&self, now_secs: i64) -> Option<ProtocolParameters> {
+        let cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        cached
+            .as_ref()
+            .filter(|(_, expires_at)| now_secs < *expires_at)
+            .map(|(params, _)| *params)
+    }
+
+    /// Keep `fetched` until its epoch ends or the TTL passes from `now_secs`
+    pub fn store(&self, fetched: &EpochParameters, now_secs: i64) -> ProtocolParameters {
+        let ttl_expiry = now_secs.saturating_add(self.ttl_secs);
+        let expires_at = fetched
+            .epoch_end_secs()
+            .map_or(ttl_expiry, |epoch_end| epoch_end.min(ttl_expiry));
+        let params = *fetched.params();
+        *self.cached.lock().unwrap_or_else(|e| e.into_inner()) =
+            Some((params, expires_at));
+        params
+    }
+
+    /// Forget the cached parameters, so the next use fetches them again
+    pub fn clear(&self) {
+        *self.cached.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn get__expires_at_epoch_end_or_ttl_whichever_is_first() {
+        // given
+        let cache = ProtocolParamsCache::new(100);
+        let params = ProtocolParameters::default().with_coins_per_utxo_byte(1);
+        let ending_soon = EpochParameters::new(params, Some(1_050));
+        let ending_later = EpochParameters::new(params, Some(5_000));
+
+        // when
+        cache.store(&ending_soon, 1_000);
+        let before_epoch_end = cache.get(1_049);
+        let after_epoch_end = cache.get(1_050);
+        cache.store(&ending_later, 1_000);
+        let before_ttl = cache.get(1_099);
+        let after_ttl = cache.get(1_100);
+        cache.clear();
+        let after_clear = cache.get(1_000);
+
+        // then
+        assert_eq!(before_epoch_end, Some(params));
+        assert_eq!(after_epoch_end, None);
+        assert_eq!(before_ttl, Some(params));
+        assert_eq!(after_ttl, None);
+        assert_eq!(after_clear, None);
+    }
+}
@@ -0,0 +1,216 @@
+use super::{
+    error::*,
+    Keys,
+};
+use async_trait::async_trait;
+use cardano_multiplatform_lib::{
+    address::{
+        Address as CMLAddress,
+        BaseAddress,
+    },
+    crypto::{
+        PrivateKey,
+        PublicKey,
+        Vkeywitness,
+    },
+    ledger::common::hash::hash_transaction,
+    Transaction as CMLTransaction,
+};
+use secrecy::{
+    ExposeSecret,
+    Secret,
+};
+use serde::{
+    de::DeserializeOwned,
+    Deserialize,
+    Serialize,
+};
+use std::time::Duration;
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum RemoteSignerError {
+    #[error("Private keys never leave the remote signer")]
+    PrivateKeyUnavailable,
+    #[error("Request to remote signer failed: {0}")]
+    Request(reqwest::Error),
+}
+
+#[derive(Deserialize)]
+struct AddressResponse {
+    address: String,
+}
+
+#[derive(Deserialize)]
+struct PublicKeyResponse {
+    public_key: String,
+}
+
+#[derive(Serialize)]
+struct SignRequest {
+    tx_hash: String,
+    tx: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    witnesses: Vec<String>,
+}
+
+/// [`Keys`] implementation that delegates to a remote signing service over HTTP
+///
+/// Keeps private keys off the application host, e.g. behind a KMS or HSM. The service
+/// must implement:
+///
+/// - `GET /address` returning `{"address": "<bech32 base address>"}`
+/// - `GET /public-key` returning `{"public_key": "<hex payment vkey>"}`
+/// - `POST /sign` taking `{"tx_hash": "<hex>", "tx": "<hex cbor>"}` and returning
+///   `{"witnesses": ["<hex cbor vkeywitness>", ...]}`
+///
+/// The full transaction is sent so the service can apply its own signing policy.
+pub struct RemoteSignerKeys {
+    url: String,
+    client: reqwest::Client,
+    auth_token: Option<Secret<String>>,
+}
+
+impl RemoteSignerKeys {
+    /// Constructor for the [`RemoteSignerKeys`] struct
+    pub fn new(url: &str) -> Self {
+        RemoteSignerKeys {
+            url: url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+            auth_token: None,
+        }
+    }
+
+    /// Send `token` as a bearer token with every request
+    pub fn with_auth_token(mut self, token: Secret<String>) -> Self {
+        self.auth_token = Some(token);
+        self
+    }
+
+    /// Give up on requests that take longer than `timeout`
+    ///
+    /// Fails if the HTTP client can't be built, e.g. when no TLS backend is available.
+    pub fn with_timeout(mut self, timeout: Duration) -> Result<Self> {
+        self.client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(remote_error)?;
+        Ok(self)
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T> {
+        let request = match &self.auth_token {
+            Some(token) => request.bearer_auth(token.expose_secret()),
+            None => request,
+        };
+        request
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .map_err(remote_error)?
+            .json()
+            .await
+            .map_err(remote_error)
+    }
+}
+
+fn remote_error(error: reqwest::Error) -> CMLLCError {
+    CMLLCError::KeyError(Box::new(RemoteSignerError::Request(error)))
+}
+
+#[async_trait]
+impl Keys for RemoteSignerKeys {
+    async fn base_addr(&self) -> Result<BaseAddress> {
+        let request = self.client.get(format!("{}/address", self.url));
+        let res: AddressResponse = self.send(request).await?;
+        let address = CMLAddress::from_bech32(&res.address)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        BaseAddress::from_address(&address).ok_or(CMLLCError::InvalidBaseAddr)
+    }
+
+    async fn private_key(&self) -> Result<PrivateKey> {
+        Err(CMLLCError::KeyError(Box::new(
+            RemoteSignerError::PrivateKeyUnavailable,
+        )))
+    }
+
+    async fn vkey_witnesses(&self, tx: &CMLTransaction) -> Result<Vec<Vkeywitness>> {
+        let body = SignRequest {
+            tx_hash: hex::encode(hash_transaction(&tx.body()).to_bytes()),
+            tx: hex::encode(tx.to_bytes()),
+        };
+        let request = self.client.post(format!("{}/sign", self.url)).json(&body);
+        let res: SignResponse = self.send(request).await?;
+        res.witnesses
+            .iter()
+            .map(|witness| {
+                Vkeywitness::from_bytes(hex::decode(witness)?)
+                    .map_err(|e| CMLLCError::Deserialize(e.to_string()))
+            })
+            .collect()
+    }
+
+    async fn payment_public_key(&self) -> Result<PublicKey> {
+        let request = self.client.get(format!("{}/public-key", self.url));
+        let res: PublicKeyResponse = self.send(request).await?;
+        PublicKey::from_bytes(&hex::decode(res.public_key)?)
+            .map_err(|e| CMLLCError::Deserialize(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use tokio::{
+        io::{
+            AsyncReadExt,
+            AsyncWriteExt,
+        },
+        net::TcpListener,
+    };
+
+    // Answers a single request with `body` and returns the raw request
+    async fn serve_once(listener: TcpListener, body: String) -> String {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0; 4096];
+        let read = stream.read(&mut buf).await.unwrap();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&buf[..read]).to_string()
+    }
+
+    #[tokio::test]
+    async fn base_addr__uses_signer_address_with_auth_token() {
+        // given
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let bech32 = "addr_test1qrksjmprvgcedgdt6rhg40590vr6exdzdc2hm5wc6pyl9ymkyskmqs55usm57gflrumk9kd63f3ty6r0l2tdfwfm28qs0rurdr";
+        let body = serde_json::json!({ "address": bech32 }).to_string();
+        let keys =
+            RemoteSignerKeys::new(&url).with_auth_token(Secret::new("t0ken".into()));
+
+        // when
+        let (base_addr, request) =
+            tokio::join!(keys.base_addr(), serve_once(listener, body));
+
+        // then
+        let actual = base_addr.unwrap().to_address().to_bech32(None).unwrap();
+        assert_eq!(actual, bech32);
+        assert!(request.starts_with("GET /address"));
+        assert!(request
+            .to_lowercase()
+            .contains("authorization: bearer t0ken"));
+    }
+}
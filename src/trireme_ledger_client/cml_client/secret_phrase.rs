@@ -0,0 +1,288 @@
+use super::error::{
+    CMLLCError,
+    Result as CMLLCResult,
+};
+use bip39::{
+    Language,
+    Mnemonic,
+    MnemonicType,
+};
+use cardano_multiplatform_lib::{
+    address::{
+        BaseAddress,
+        StakeCredential,
+    },
+    crypto::{
+        Bip32PrivateKey,
+        PrivateKey,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    fmt,
+    str::FromStr,
+};
+use thiserror::Error;
+
+const PURPOSE: u32 = 1852;
+const COIN_TYPE: u32 = 1815;
+const PAYMENT_ROLE: u32 = 0;
+const STAKE_ROLE: u32 = 2;
+const DREP_ROLE: u32 = 3;
+
+/// CIP-1852 derivation path of the signing key, `m/1852'/1815'/account'/0/address_index`
+///
+/// The stake key is always the account's first, `m/1852'/1815'/account'/2/0`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DerivationPath {
+    account: u32,
+    address_index: u32,
+}
+
+impl DerivationPath {
+    /// Path to the first address of `account`
+    pub fn new(account: u32) -> Self {
+        DerivationPath {
+            account,
+            ..Default::default()
+        }
+    }
+
+    /// Use a different payment key index within the account
+    pub fn with_address_index(mut self, address_index: u32) -> Self {
+        self.address_index = address_index;
+        self
+    }
+
+    /// Account index
+    pub fn account(&self) -> u32 {
+        self.account
+    }
+
+    /// Payment key index
+    pub fn address_index(&self) -> u32 {
+        self.address_index
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum SecretPhraseError {
+    #[error("Invalid secret phrase: {0}")]
+    Bip39(String),
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum DerivationPathError {
+    #[error("Expected a path like m/1852'/1815'/0'/0/0, got {0:?}")]
+    Malformed(String),
+    #[error("Only CIP-1852 paths (m/1852'/1815'/...) are supported, got {0:?}")]
+    Unsupported(String),
+}
+
+impl FromStr for DerivationPath {
+    type Err = DerivationPathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || DerivationPathError::Malformed(s.to_string());
+        let mut segments = s.trim().split('/');
+        if segments.next() != Some("m") {
+            return Err(malformed())
+        }
+        let indices = segments
+            .map(|segment| match segment.strip_suffix('\'') {
+                Some(index) => index.parse::<u32>().map(harden),
+                None => segment.parse::<u32>(),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| malformed())?;
+        match indices.as_slice() {
+            [purpose, coin, account, PAYMENT_ROLE, address_index]
+                if *purpose == harden(PURPOSE)
+                    && *coin == harden(COIN_TYPE)
+                    && *account >= harden(0) =>
+            {
+                Ok(DerivationPath {
+                    account: *account ^ harden(0),
+                    address_index: *address_index,
+                })
+            }
+            [_, _, _, _, _] => Err(DerivationPathError::Unsupported(s.to_string())),
+            _ => Err(malformed()),
+        }
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "m/{PURPOSE}'/{COIN_TYPE}'/{}'/{PAYMENT_ROLE}/{}",
+            self.account, self.address_index
+        )
+    }
+}
+
+impl TryFrom<String> for DerivationPath {
+    type Error = DerivationPathError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<DerivationPath> for String {
+    fn from(path: DerivationPath) -> Self {
+        path.to_string()
+    }
+}
+
+/// Get Private Key from Secret Phrase
+pub fn secret_phrase_to_account_key(phrase: &str) -> CMLLCResult<Bip32PrivateKey> {
+    secret_phrase_to_account_key_at(phrase, 0)
+}
+
+/// Generate a new random 24 word secret phrase
+pub fn generate_secret_phrase() -> String {
+    Mnemonic::new(MnemonicType::Words24, Language::English)
+        .phrase()
+        .to_string()
+}
+
+/// Check that `phrase` is a valid English BIP39 secret phrase, including its checksum
+pub fn validate_secret_phrase(phrase: &str) -> CMLLCResult<()> {
+    Mnemonic::validate(phrase, Language::English)
+        .map_err(|e| SecretPhraseError::Bip39(e.to_string()))
+        .map_err(|e| CMLLCError::KeyError(Box::new(e)))
+}
+
+/// Get the Private Key for `account` from Secret Phrase
+pub fn secret_phrase_to_account_key_at(
+    phrase: &str,
+    account: u32,
+) -> CMLLCResult<Bip32PrivateKey> {
+    let mnemonic = Mnemonic::from_phrase(phrase, Language::English)
+        .map_err(|e| SecretPhraseError::Bip39(e.to_string()))
+        .map_err(|e| CMLLCError::KeyError(Box::new(e)))?;
+    let entropy = mnemonic.entropy();
+    let root_key = Bip32PrivateKey::from_bip39_entropy(entropy, &[]);
+
+    let account_key = root_key
+        .derive(harden(PURPOSE))
+        .derive(harden(COIN_TYPE))
+        .derive(harden(account));
+
+    Ok(account_key)
+}
+
+/// Get Base Address from Private Key
+pub fn private_key_to_base_address(
+    account_key: &Bip32PrivateKey,
+    network: u8,
+) -> BaseAddress {
+    private_key_to_base_address_at(account_key, network, &DerivationPath::default())
+}
+
+/// Get the Base Address at `path` from the account's Private Key
+pub fn private_key_to_base_address_at(
+    account_key: &Bip32PrivateKey,
+    network: u8,
+    path: &DerivationPath,
+) -> BaseAddress {
+    let pub_key = account_key
+        .derive(PAYMENT_ROLE)
+        .derive(path.address_index)
+        .to_public();
+    let stake_key = account_key.derive(STAKE_ROLE).derive(0).to_public();
+    let pub_key_creds = StakeCredential::from_keyhash(&pub_key.to_raw_key().hash());
+    let stake_key_creds = StakeCredential::from_keyhash(&stake_key.to_raw_key().hash());
+    BaseAddress::new(network, &pub_key_creds, &stake_key_creds)
+}
+
+/// Get the payment Private Key at `path` from the account's Private Key
+pub fn account_key_to_payment_key(
+    account_key: &Bip32PrivateKey,
+    path: &DerivationPath,
+) -> PrivateKey {
+    account_key
+        .derive(PAYMENT_ROLE)
+        .derive(path.address_index)
+        .to_raw_key()
+}
+
+/// Get the CIP-105 DRep Private Key, `m/1852'/1815'/account'/3/0`, from the account's
+/// Private Key
+pub fn account_key_to_drep_key(account_key: &Bip32PrivateKey) -> PrivateKey {
+    account_key.derive(DREP_ROLE).derive(0).to_raw_key()
+}
+
+fn harden(index: u32) -> u32 {
+    index | 0x80_00_00_00
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn generate_secret_phrase__is_valid_24_word_phrase() {
+        let phrase = generate_secret_phrase();
+
+        assert_eq!(phrase.split_whitespace().count(), 24);
+        assert!(validate_secret_phrase(&phrase).is_ok());
+    }
+
+    #[test]
+    fn validate_secret_phrase__rejects_bad_checksum() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon \
+            abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+            abandon abandon abandon abandon abandon abandon abandon";
+
+        assert!(validate_secret_phrase(phrase).is_err());
+    }
+
+    #[test]
+    fn derivation_path__roundtrips_through_string() {
+        let path = DerivationPath::new(3).with_address_index(7);
+
+        let parsed: DerivationPath = path.to_string().parse().unwrap();
+
+        assert_eq!(path.to_string(), "m/1852'/1815'/3'/0/7");
+        assert_eq!(parsed, path);
+    }
+
+    #[test]
+    fn derivation_path__rejects_non_cip1852_paths() {
+        let res = "m/44'/1815'/0'/0/0".parse::<DerivationPath>();
+
+        assert!(matches!(res, Err(DerivationPathError::Unsupported(_))));
+    }
+
+    #[test]
+    fn account_key_to_drep_key__uses_drep_role_of_account() {
+        let phrase = generate_secret_phrase();
+        let account_key = secret_phrase_to_account_key(&phrase).unwrap();
+
+        let drep_key = account_key_to_drep_key(&account_key);
+
+        let expected = account_key.derive(3).derive(0).to_raw_key();
+        let payment_key =
+            account_key_to_payment_key(&account_key, &DerivationPath::default());
+        assert_eq!(drep_key.as_bytes(), expected.as_bytes());
+        assert_ne!(drep_key.as_bytes(), payment_key.as_bytes());
+    }
+
+    #[test]
+    fn derivation_path__rejects_unhardened_account() {
+        let res = "m/1852'/1815'/0/0/0".parse::<DerivationPath>();
+
+        assert!(res.is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use super::*;
 use crate::{
+    ledger_client::provider_error::ProviderError,
     trireme_ledger_client::{
         cml_client::{
             blockfrost_ledger::BlockFrostLedger,
@@ -13,8 +14,21 @@ use blockfrost_http_client::{
     load_key_from_file,
     PREPROD_NETWORK_URL,
 };
-use cardano_multiplatform_lib::address::BaseAddress;
-use std::time::Duration;
+use cardano_multiplatform_lib::{
+    address::BaseAddress,
+    crypto::TransactionHash,
+    ledger::common::value::Value as CMLValue,
+};
+use std::{
+    sync::{
+        atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+        Mutex,
+    },
+    time::Duration,
+};
 use test_helpers::{
     always_succeeds_script_address,
     claim_always_succeeds_datum_tx,
@@ -26,6 +40,267 @@ use tokio::time::sleep;
 
 mod test_helpers;
 
+const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+
+// Serves `utxo_count` UTxOs of 10 ADA each, counting the pages asked for and keeping
+// whatever is submitted
+struct PagedLedger {
+    utxo_count: usize,
+    pages_fetched: AtomicUsize,
+    submitted: Mutex<Vec<Vec<u8>>>,
+    loses_first_submission: bool,
+    spent_by_others: Mutex<Vec<u64>>,
+}
+
+impl PagedLedger {
+    fn new(utxo_count: usize) -> Self {
+        PagedLedger {
+            utxo_count,
+            pages_fetched: AtomicUsize::new(0),
+            submitted: Mutex::new(Vec::new()),
+            loses_first_submission: false,
+            spent_by_others: Mutex::new(Vec::new()),
+        }
+    }
+
+    // The inputs of the first submission get spent by another transaction just before it
+    // reaches the ledger, so it's rejected
+    fn losing_first_submission(utxo_count: usize) -> Self {
+        PagedLedger {
+            loses_first_submission: true,
+            ..PagedLedger::new(utxo_count)
+        }
+    }
+
+    fn pages_fetched(&self) -> usize {
+        self.pages_fetched.load(Ordering::SeqCst)
+    }
+
+    fn submitted(&self) -> Vec<CMLTransaction> {
+        self.submitted
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|bytes| CMLTransaction::from_bytes(bytes.clone()).unwrap())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Ledger for PagedLedger {
+    async fn last_block_time_secs(&self) -> Result<i64> {
+        unimplemented!()
+    }
+
+    async fn get_utxos_for_addr(
+        &self,
+        _addr: &CMLAddress,
+        _count: usize,
+    ) -> Result<Vec<UTxO>> {
+        unimplemented!()
+    }
+
+    async fn get_all_utxos_for_addr(&self, _addr: &CMLAddress) -> Result<Vec<UTxO>> {
+        unimplemented!()
+    }
+
+    async fn get_utxo_page_for_addr(
+        &self,
+        _addr: &CMLAddress,
+        page: usize,
+    ) -> Result<Vec<UTxO>> {
+        self.pages_fetched.fetch_add(1, Ordering::SeqCst);
+        let start = (page * UTXO_PAGE_SIZE).min(self.utxo_count);
+        let end = ((page + 1) * UTXO_PAGE_SIZE).min(self.utxo_count);
+        let spent_by_others = self.spent_by_others.lock().unwrap().clone();
+        let utxos = (start..end)
+            .filter(|index| !spent_by_others.contains(&(*index as u64)))
+            .map(|index| {
+                let tx_hash = TransactionHash::from_bytes(vec![1; 32]).unwrap();
+                let amount = CMLValue::new(&10_000_000u64.into());
+                UTxO::new(tx_hash, (index as u64).into(), amount, None)
+            })
+            .collect();
+        Ok(utxos)
+    }
+
+    async fn calculate_ex_units(
+        &self,
+        _tx: &CMLTransaction,
+    ) -> Result<HashMap<u64, ExecutionCost>> {
+        Ok(HashMap::new())
+    }
+
+    async fn submit_transaction(&self, tx: &CMLTransaction) -> Result<String> {
+        let mut submitted = self.submitted.lock().unwrap();
+        submitted.push(tx.to_bytes());
+        if self.loses_first_submission && submitted.len() == 1 {
+            let inputs = tx.body().inputs();
+            let mut spent_by_others = self.spent_by_others.lock().unwrap();
+            for i in 0..inputs.len() {
+                spent_by_others.push(inputs.get(i).index().into());
+            }
+            let error = ProviderError::new("Blockfrost", "POST /tx/submit")
+                .with_status(400)
+                .with_body("BadInputsUTxO (fromList [TxIn ...])");
+            return Err(CMLLCError::Provider(error))
+        }
+        Ok(hash_transaction(&tx.body()).to_hex())
+    }
+
+    async fn get_transaction_cbor(&self, _tx_id: &str) -> Result<Vec<u8>> {
+        unimplemented!()
+    }
+
+    async fn is_tx_confirmed(&self, _tx_id: &str) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+fn paged_client(utxo_count: usize) -> CMLLedgerCLient<PagedLedger, KeyManager, (), ()> {
+    let keys = KeyManager::new(CONFIG_PATH.to_string(), Network::Preprod.into());
+    CMLLedgerCLient::new(
+        PagedLedger::new(utxo_count),
+        keys,
+        Network::Preprod.try_into().unwrap(),
+    )
+}
+
+struct InMemoryKeys(Vec<u8>);
+
+impl InMemoryKeys {
+    fn generate() -> Self {
+        InMemoryKeys(PrivateKey::generate_ed25519().unwrap().as_bytes())
+    }
+
+    fn key_hash(&self) -> Ed25519KeyHash {
+        PrivateKey::from_normal_bytes(&self.0)
+            .unwrap()
+            .to_public()
+            .hash()
+    }
+}
+
+#[async_trait]
+impl Keys for InMemoryKeys {
+    async fn base_addr(&self) -> Result<BaseAddress> {
+        let credential = StakeCredential::from_keyhash(&self.key_hash());
+        Ok(BaseAddress::new(0, &credential, &credential))
+    }
+
+    async fn private_key(&self) -> Result<PrivateKey> {
+        PrivateKey::from_normal_bytes(&self.0)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))
+    }
+}
+
+#[tokio::test]
+async fn stream_outputs_at_address__fetches_pages_as_they_are_consumed() {
+    // given
+    let client = paged_client(UTXO_PAGE_SIZE * 2 + 1);
+    let alice = Address::from_bech32(ALICE).unwrap();
+
+    // when
+    let first = client
+        .find_output_at_address(&alice, |_| true)
+        .await
+        .unwrap();
+    let pages_for_first = client.ledger.pages_fetched();
+    let all: Vec<Output<()>> = client
+        .stream_outputs_at_address(&alice)
+        .try_collect()
+        .await
+        .unwrap();
+
+    // then
+    assert!(first.is_some());
+    assert_eq!(pages_for_first, 1);
+    assert_eq!(all.len(), UTXO_PAGE_SIZE * 2 + 1);
+    assert_eq!(client.ledger.pages_fetched(), 1 + 3);
+}
+
+#[tokio::test]
+async fn wallet_utxos_for_tx__stops_paging_once_the_tx_is_covered() {
+    // given
+    let client = paged_client(UTXO_PAGE_SIZE * 3);
+    let my_address = addr_from_bech_32(ALICE).unwrap();
+    let tx = transfer_tx(Address::from_bech32(ALICE).unwrap(), 20_000_000);
+
+    // when
+    let utxos = client.wallet_utxos_for_tx(&my_address, &tx).await.unwrap();
+
+    // then
+    assert_eq!(utxos.len(), UTXO_PAGE_SIZE);
+    assert_eq!(client.ledger.pages_fetched(), 1);
+}
+
+#[tokio::test]
+async fn issue_with_extra_signers__requires_and_collects_each_signature() {
+    // given
+    let me = InMemoryKeys::generate();
+    let my_key_hash = me.key_hash();
+    let admin = InMemoryKeys::generate();
+    let client: CMLLedgerCLient<_, _, (), ()> = CMLLedgerCLient::new(
+        PagedLedger::new(3),
+        me,
+        Network::Preprod.try_into().unwrap(),
+    );
+    let tx = transfer_tx(Address::from_bech32(ALICE).unwrap(), 6_000_000);
+
+    // when
+    client
+        .issue_with_extra_signers(tx, &[&admin])
+        .await
+        .unwrap();
+
+    // then
+    let submitted = client.ledger.submitted();
+    assert_eq!(submitted.len(), 1);
+    let required_signers = submitted[0].body().required_signers().unwrap();
+    assert_eq!(required_signers.len(), 1);
+    assert_eq!(required_signers.get(0), admin.key_hash());
+    let vkeys = submitted[0].witness_set().vkeys().unwrap();
+    assert_eq!(vkeys.len(), 2);
+    let signers: Vec<_> = (0..vkeys.len())
+        .map(|i| vkeys.get(i).vkey().public_key().hash())
+        .collect();
+    assert!(signers.contains(&my_key_hash));
+    assert!(signers.contains(&admin.key_hash()));
+}
+
+#[tokio::test]
+async fn issue_with_retry__selects_new_inputs_once_the_first_ones_are_spent() {
+    // given
+    let client: CMLLedgerCLient<_, _, (), ()> = CMLLedgerCLient::new(
+        PagedLedger::losing_first_submission(5),
+        InMemoryKeys::generate(),
+        Network::Preprod.try_into().unwrap(),
+    );
+    let tx = transfer_tx(Address::from_bech32(ALICE).unwrap(), 6_000_000);
+    let policy = RetryPolicy::default().with_backoff(Duration::ZERO, Duration::ZERO);
+
+    // when
+    let tx_id = client.issue_with_retry(tx, &policy).await.unwrap();
+
+    // then
+    let submitted = client.ledger.submitted();
+    assert_eq!(submitted.len(), 2);
+    let input_indices = |tx: &CMLTransaction| -> Vec<u64> {
+        let inputs = tx.body().inputs();
+        (0..inputs.len())
+            .map(|i| inputs.get(i).index().into())
+            .collect()
+    };
+    let first_inputs = input_indices(&submitted[0]);
+    let retried_inputs = input_indices(&submitted[1]);
+    assert!(!retried_inputs.is_empty());
+    assert!(retried_inputs
+        .iter()
+        .all(|index| !first_inputs.contains(index)));
+    let retried_id = hash_transaction(&submitted[1].body()).to_hex();
+    assert_eq!(tx_id.as_str(), retried_id);
+}
+
 // Must include a TOML file at your project root with the field:
 //   project_id = <INSERT API KEY HERE>
 const CONFIG_PATH: &str = ".blockfrost.toml";
@@ -39,7 +314,7 @@ async fn get_test_client<Datum: PlutusDataInterop, Redeemer: PlutusDataInterop>(
     let keys = KeyManager::new(CONFIG_PATH.to_string(), Network::Preprod.into());
     let base_addr = keys.base_addr().await.unwrap();
     (
-        CMLLedgerCLient::new(ledger, keys, Network::Preprod.into()),
+        CMLLedgerCLient::new(ledger, keys, Network::Preprod.try_into().unwrap()),
         base_addr,
     )
 }
@@ -41,7 +41,9 @@ pub fn transfer_tx(recipient: Address, amount: u64) -> UnbuiltTransaction<(), ()
         unbuilt_outputs: vec![output],
         minting: Default::default(),
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     }
 }
 
@@ -56,7 +58,9 @@ pub fn lock_at_always_succeeds_tx(amount: u64) -> UnbuiltTransaction<(), ()> {
         unbuilt_outputs: vec![output],
         minting: Default::default(),
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     }
 }
 
@@ -104,7 +108,9 @@ pub fn claim_always_succeeds_datum_tx(
         unbuilt_outputs: vec![],
         minting: Default::default(),
         specific_wallet_inputs: vec![],
+        reference_inputs: vec![],
         valid_range: (None, None),
+        metadata: Default::default(),
     }
 }
 
@@ -0,0 +1,402 @@
+use super::{
+    error::*,
+    hardware_wallet::int_to_i64,
+    plutus_data_interop::PlutusDataInterop,
+};
+use crate::{
+    output::{
+        DatumKind,
+        Output,
+        OutputId,
+    },
+    scripts::plutus_validator::plutus_data::PlutusData,
+    transaction::TxId,
+    values::Values,
+    PolicyId,
+};
+use cardano_multiplatform_lib::{
+    ledger::common::hash::{
+        hash_plutus_data,
+        hash_transaction,
+    },
+    plutus::{
+        PlutusData as CMLPlutusData,
+        RedeemerTagKind,
+    },
+    Transaction as CMLTransaction,
+    TransactionInputs,
+    TransactionOutput,
+};
+use pallas_addresses::Address;
+
+/// What a redeemer is for
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RedeemerPurpose {
+    /// Spending the script input at the index
+    Spend,
+    /// Minting with the policy at the index
+    Mint,
+    /// Certificate at the index
+    Cert,
+    /// Withdrawal at the index
+    Reward,
+}
+
+/// Redeemer of a decoded transaction
+#[derive(Clone, PartialEq, Debug)]
+pub struct InspectedRedeemer<Redeemer> {
+    purpose: RedeemerPurpose,
+    index: u64,
+    data: DatumKind<Redeemer>,
+    mem: u64,
+    steps: u64,
+}
+
+impl<Redeemer> InspectedRedeemer<Redeemer> {
+    /// What the redeemer is for
+    pub fn purpose(&self) -> RedeemerPurpose {
+        self.purpose
+    }
+
+    /// Index of the input, policy, etc. the redeemer is for
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Redeemer data, typed if it decodes as `Redeemer`
+    pub fn data(&self) -> &DatumKind<Redeemer> {
+        &self.data
+    }
+
+    /// Memory budget of the redeemer
+    pub fn mem(&self) -> u64 {
+        self.mem
+    }
+
+    /// CPU steps budget of the redeemer
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+}
+
+/// Transaction decoded into Naumachia domain types, for debugging
+#[derive(Clone, PartialEq, Debug)]
+pub struct InspectedTx<Datum, Redeemer> {
+    tx_id: TxId,
+    inputs: Vec<OutputId>,
+    reference_inputs: Vec<OutputId>,
+    collateral: Vec<OutputId>,
+    outputs: Vec<Output<Datum>>,
+    fee: u64,
+    mint: Vec<(PolicyId, i64)>,
+    valid_from_slot: Option<u64>,
+    valid_to_slot: Option<u64>,
+    required_signers: Vec<String>,
+    signers: Vec<String>,
+    redeemers: Vec<InspectedRedeemer<Redeemer>>,
+    witness_datums: Vec<PlutusData>,
+}
+
+impl<Datum, Redeemer> InspectedTx<Datum, Redeemer> {
+    /// Id of the transaction
+    pub fn tx_id(&self) -> &TxId {
+        &self.tx_id
+    }
+
+    /// Outputs spent by the transaction
+    pub fn inputs(&self) -> &[OutputId] {
+        &self.inputs
+    }
+
+    /// Outputs referenced but not spent by the transaction
+    pub fn reference_inputs(&self) -> &[OutputId] {
+        &self.reference_inputs
+    }
+
+    /// Outputs put up as collateral
+    pub fn collateral(&self) -> &[OutputId] {
+        &self.collateral
+    }
+
+    /// Outputs created by the transaction, with datums typed where they decode as `Datum`
+    pub fn outputs(&self) -> &[Output<Datum>] {
+        &self.outputs
+    }
+
+    /// Fee in Lovelace
+    pub fn fee(&self) -> u64 {
+        self.fee
+    }
+
+    /// Minted (positive) and burned (negative) tokens
+    pub fn mint(&self) -> &[(PolicyId, i64)] {
+        &self.mint
+    }
+
+    /// First slot the transaction is valid in
+    pub fn valid_from_slot(&self) -> Option<u64> {
+        self.valid_from_slot
+    }
+
+    /// Slot from which the transaction is no longer valid
+    pub fn valid_to_slot(&self) -> Option<u64> {
+        self.valid_to_slot
+    }
+
+    /// Hex encoded key hashes that must sign the transaction
+    pub fn required_signers(&self) -> &[String] {
+        &self.required_signers
+    }
+
+    /// Hex encoded key hashes that have signed the transaction
+    pub fn signers(&self) -> &[String] {
+        &self.signers
+    }
+
+    /// Redeemers in the witness set
+    pub fn redeemers(&self) -> &[InspectedRedeemer<Redeemer>] {
+        &self.redeemers
+    }
+
+    /// Datums in the witness set
+    pub fn witness_datums(&self) -> &[PlutusData] {
+        &self.witness_datums
+    }
+}
+
+/// Decode a CBOR encoded transaction
+///
+/// Datums and redeemers are decoded as `Datum` and `Redeemer` where possible and kept as
+/// untyped [`PlutusData`] otherwise.
+pub fn inspect_tx<Datum: PlutusDataInterop, Redeemer: PlutusDataInterop>(
+    cbor: &[u8],
+) -> Result<InspectedTx<Datum, Redeemer>> {
+    let tx = CMLTransaction::from_bytes(cbor.to_vec())
+        .map_err(|e| CMLLCError::Deserialize(e.to_string()))?;
+    let body = tx.body();
+    let witness_set = tx.witness_set();
+    let tx_hash = hash_transaction(&body).to_bytes();
+
+    let cml_witness_datums: Vec<CMLPlutusData> = witness_set
+        .plutus_data()
+        .map(|list| (0..list.len()).map(|i| list.get(i)).collect())
+        .unwrap_or_default();
+
+    let body_outputs = body.outputs();
+    let outputs = (0..body_outputs.len())
+        .map(|i| {
+            inspect_output(
+                &tx_hash,
+                i as u64,
+                &body_outputs.get(i),
+                &cml_witness_datums,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut mint = Vec::new();
+    if let Some(cml_mint) = body.mint() {
+        let policies = cml_mint.keys();
+        for i in 0..policies.len() {
+            let policy = policies.get(i);
+            if let Some(assets) = cml_mint.get(&policy) {
+                let names = assets.keys();
+                for j in 0..names.len() {
+                    let name = names.get(j);
+                    let amount = assets
+                        .get(&name)
+                        .map(|amount| int_to_i64(&amount))
+                        .unwrap_or(0);
+                    let policy_id = PolicyId::native_token(
+                        &hex::encode(policy.to_bytes()),
+                        &Some(name.name()),
+                    );
+                    mint.push((policy_id, amount));
+                }
+            }
+        }
+    }
+
+    let required_signers = body
+        .required_signers()
+        .map(|keys| {
+            (0..keys.len())
+                .map(|i| hex::encode(keys.get(i).to_bytes()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let signers = witness_set
+        .vkeys()
+        .map(|vkeys| {
+            (0..vkeys.len())
+                .map(|i| hex::encode(vkeys.get(i).vkey().public_key().hash().to_bytes()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let redeemers = witness_set
+        .redeemers()
+        .map(|redeemers| {
+            (0..redeemers.len())
+                .map(|i| {
+                    let redeemer = redeemers.get(i);
+                    let purpose = match redeemer.tag().kind() {
+                        RedeemerTagKind::Spend => RedeemerPurpose::Spend,
+                        RedeemerTagKind::Mint => RedeemerPurpose::Mint,
+                        RedeemerTagKind::Cert => RedeemerPurpose::Cert,
+                        RedeemerTagKind::Reward => RedeemerPurpose::Reward,
+                    };
+                    InspectedRedeemer {
+                        purpose,
+                        index: redeemer.index().into(),
+                        data: decode_data(&redeemer.data()),
+                        mem: redeemer.ex_units().mem().into(),
+                        steps: redeemer.ex_units().steps().into(),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let inspected = InspectedTx {
+        tx_id: TxId::new(&hex::encode(&tx_hash)),
+        inputs: output_ids(&body.inputs()),
+        reference_inputs: body
+            .reference_inputs()
+            .map(|inputs| output_ids(&inputs))
+            .unwrap_or_default(),
+        collateral: body
+            .collateral()
+            .map(|inputs| output_ids(&inputs))
+            .unwrap_or_default(),
+        outputs,
+        fee: body.fee().into(),
+        mint,
+        valid_from_slot: body.validity_start_interval().map(Into::into),
+        valid_to_slot: body.ttl().map(Into::into),
+        required_signers,
+        signers,
+        redeemers,
+        witness_datums: cml_witness_datums.into_iter().map(Into::into).collect(),
+    };
+    Ok(inspected)
+}
+
+fn inspect_output<Datum: PlutusDataInterop>(
+    tx_hash: &[u8],
+    index: u64,
+    output: &TransactionOutput,
+    witness_datums: &[CMLPlutusData],
+) -> Result<Output<Datum>> {
+    let owner = Address::from_bytes(&output.address().to_bytes())?;
+    let values = Values::from(&output.amount());
+    let tx_hash = tx_hash.to_vec();
+    let datum = output.datum();
+    let datum_hash = datum
+        .as_ref()
+        .and_then(|datum| datum.as_data_hash())
+        .map(|hash| hash.to_bytes());
+    let data = match (&datum_hash, datum.and_then(|datum| datum.as_inline_data())) {
+        (_, Some(inline)) => Some(inline),
+        (Some(hash), None) => witness_datums
+            .iter()
+            .find(|data| &hash_plutus_data(data).to_bytes() == hash)
+            .cloned(),
+        (None, None) => None,
+    };
+    let output = match data.map(|data| decode_data::<Datum>(&data)) {
+        Some(DatumKind::Typed(datum)) => {
+            Output::new_validator(tx_hash, index, owner, values, datum)
+        }
+        Some(DatumKind::UnTyped(data)) => {
+            Output::new_untyped_validator(tx_hash, index, owner, values, data)
+        }
+        Some(DatumKind::None) | None => Output::new_wallet(tx_hash, index, owner, values),
+    };
+    let output = match datum_hash {
+        Some(hash) => output.with_datum_hash(hash),
+        None => output,
+    };
+    Ok(output)
+}
+
+fn decode_data<T: PlutusDataInterop>(data: &CMLPlutusData) -> DatumKind<T> {
+    match T::from_plutus_data(data) {
+        Ok(typed) => DatumKind::Typed(typed),
+        Err(_) => DatumKind::UnTyped(data.clone().into()),
+    }
+}
+
+fn output_ids(inputs: &TransactionInputs) -> Vec<OutputId> {
+    (0..inputs.len())
+        .map(|i| {
+            let input = inputs.get(i);
+            OutputId::new(input.transaction_id().to_bytes(), input.index().into())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use cardano_multiplatform_lib::{
+        address::Address as CMLAddress,
+        crypto::TransactionHash,
+        ledger::common::value::{
+            BigNum,
+            Value as CMLValue,
+        },
+        plutus::PlutusList,
+        Datum as CMLDatum,
+        TransactionBody,
+        TransactionInput,
+        TransactionOutputs,
+        TransactionWitnessSet,
+    };
+
+    const ADDRESS: &str = "addr_test1qrksjmprvgcedgdt6rhg40590vr6exdzdc2hm5wc6pyl9ymkyskmqs55usm57gflrumk9kd63f3ty6r0l2tdfwfm28qs0rurdr";
+
+    fn output_with_datum_hash(data: &CMLPlutusData) -> TransactionOutput {
+        let address = CMLAddress::from_bech32(ADDRESS).unwrap();
+        let value = CMLValue::new(&BigNum::from(2_000_000));
+        let mut output = TransactionOutput::new(&address, &value);
+        output.set_datum(&CMLDatum::new_data_hash(&hash_plutus_data(data)));
+        output
+    }
+
+    #[test]
+    fn inspect_tx__decodes_datums_into_registered_type() {
+        // given
+        let typed_datum = 42_i64.to_plutus_data();
+        let untyped_datum: CMLPlutusData = PlutusData::from(vec![1_u8, 2, 3]).into();
+
+        let mut inputs = TransactionInputs::new();
+        let input_hash = TransactionHash::from_bytes(vec![7; 32]).unwrap();
+        inputs.add(&TransactionInput::new(&input_hash, &BigNum::from(1)));
+        let mut outputs = TransactionOutputs::new();
+        outputs.add(&output_with_datum_hash(&typed_datum));
+        outputs.add(&output_with_datum_hash(&untyped_datum));
+        let body = TransactionBody::new(&inputs, &outputs, &BigNum::from(170_000), None);
+        let mut datums = PlutusList::new();
+        datums.add(&typed_datum);
+        datums.add(&untyped_datum);
+        let mut witness_set = TransactionWitnessSet::new();
+        witness_set.set_plutus_data(&datums);
+        let tx = CMLTransaction::new(&body, &witness_set, None);
+
+        // when
+        let inspected = inspect_tx::<i64, ()>(&tx.to_bytes()).unwrap();
+
+        // then
+        assert_eq!(inspected.fee(), 170_000);
+        assert_eq!(inspected.inputs(), &[OutputId::new(vec![7; 32], 1)]);
+        assert_eq!(inspected.outputs()[0].typed_datum(), Some(42));
+        assert_eq!(
+            inspected.outputs()[1].datum().value(),
+            Some(&DatumKind::UnTyped(PlutusData::from(vec![1_u8, 2, 3])))
+        );
+        assert_eq!(inspected.witness_datums().len(), 2);
+    }
+}
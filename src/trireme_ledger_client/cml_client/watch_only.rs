@@ -0,0 +1,158 @@
+use super::{
+    error::*,
+    hardware_wallet::{
+        base_addr_from_account_key,
+        derive_soft,
+    },
+    Keys,
+};
+use async_trait::async_trait;
+use cardano_multiplatform_lib::{
+    address::{
+        Address as CMLAddress,
+        BaseAddress,
+    },
+    crypto::{
+        Bip32PublicKey,
+        PrivateKey,
+        PublicKey,
+        Vkeywitness,
+    },
+    Transaction as CMLTransaction,
+};
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum WatchOnlyError {
+    #[error("Watch-only wallets can't sign transactions")]
+    CannotSign,
+    #[error("Only the key hash is known for a watch-only address")]
+    NoPublicKey,
+    #[error("Invalid account extended public key: {0:?}")]
+    InvalidXpub(String),
+}
+
+/// [`Keys`] implementation without any secrets, for monitoring wallets
+///
+/// Can query balances and build unsigned transactions, but signing always fails.
+pub enum WatchOnlyKeys {
+    /// A fixed base address
+    Address(BaseAddress),
+    /// A CIP-1852 account, using its first payment and stake keys
+    AccountXpub {
+        /// Extended public key of the account
+        account_key: Bip32PublicKey,
+        /// Network id used in the address
+        network: u8,
+    },
+}
+
+impl WatchOnlyKeys {
+    /// Watch the bech32 encoded base address
+    pub fn from_address(address: &str) -> Result<Self> {
+        let address = CMLAddress::from_bech32(address)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        let base_addr =
+            BaseAddress::from_address(&address).ok_or(CMLLCError::InvalidBaseAddr)?;
+        Ok(WatchOnlyKeys::Address(base_addr))
+    }
+
+    /// Watch the account with the bech32 (`acct_xvk...`) or hex encoded extended public key
+    pub fn from_account_xpub(account_xpub: &str, network: u8) -> Result<Self> {
+        let account_key = Bip32PublicKey::from_bech32(account_xpub)
+            .or_else(|_| {
+                let bytes = hex::decode(account_xpub).map_err(|e| e.to_string())?;
+                Bip32PublicKey::from_bytes(&bytes).map_err(|e| e.to_string())
+            })
+            .map_err(|e| {
+                CMLLCError::KeyError(Box::new(WatchOnlyError::InvalidXpub(e.to_string())))
+            })?;
+        Ok(WatchOnlyKeys::AccountXpub {
+            account_key,
+            network,
+        })
+    }
+}
+
+#[async_trait]
+impl Keys for WatchOnlyKeys {
+    async fn base_addr(&self) -> Result<BaseAddress> {
+        match self {
+            WatchOnlyKeys::Address(base_addr) => Ok(base_addr.clone()),
+            WatchOnlyKeys::AccountXpub {
+                account_key,
+                network,
+            } => base_addr_from_account_key(account_key, 0, *network),
+        }
+    }
+
+    async fn private_key(&self) -> Result<PrivateKey> {
+        Err(CMLLCError::KeyError(Box::new(WatchOnlyError::CannotSign)))
+    }
+
+    async fn vkey_witnesses(&self, _tx: &CMLTransaction) -> Result<Vec<Vkeywitness>> {
+        Err(CMLLCError::KeyError(Box::new(WatchOnlyError::CannotSign)))
+    }
+
+    async fn payment_public_key(&self) -> Result<PublicKey> {
+        match self {
+            WatchOnlyKeys::Address(_) => {
+                Err(CMLLCError::KeyError(Box::new(WatchOnlyError::NoPublicKey)))
+            }
+            WatchOnlyKeys::AccountXpub { account_key, .. } => {
+                Ok(derive_soft(account_key, 0, 0)?.to_raw_key())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::trireme_ledger_client::cml_client::secret_phrase::{
+        private_key_to_base_address,
+        secret_phrase_to_account_key,
+    };
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon \
+        abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+        abandon abandon abandon abandon abandon abandon art";
+
+    #[tokio::test]
+    async fn base_addr__from_account_xpub_matches_secret_phrase() {
+        // given
+        let account_key = secret_phrase_to_account_key(PHRASE).unwrap();
+        let xpub = hex::encode(account_key.to_public().as_bytes());
+        let keys = WatchOnlyKeys::from_account_xpub(&xpub, 0).unwrap();
+
+        // when
+        let base_addr = keys.base_addr().await.unwrap();
+
+        // then
+        let expected = private_key_to_base_address(&account_key, 0);
+        assert_eq!(
+            base_addr.to_address().to_bytes(),
+            expected.to_address().to_bytes()
+        );
+    }
+
+    #[tokio::test]
+    async fn private_key__is_unavailable_for_address() {
+        // given
+        let account_key = secret_phrase_to_account_key(PHRASE).unwrap();
+        let address = private_key_to_base_address(&account_key, 0)
+            .to_address()
+            .to_bech32(None)
+            .unwrap();
+        let keys = WatchOnlyKeys::from_address(&address).unwrap();
+
+        // when
+        let res = keys.private_key().await;
+
+        // then
+        assert!(res.is_err());
+    }
+}
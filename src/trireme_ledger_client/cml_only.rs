@@ -0,0 +1,7 @@
+//! The CML client on its own, used in place of the full Trireme module when Naumachia is
+//! built without the `trireme` feature, e.g. for `wasm32-unknown-unknown`
+
+/// CML CLient module
+pub mod cml_client;
+
+pub use cml_client::network_settings::Network;
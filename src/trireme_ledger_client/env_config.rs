@@ -0,0 +1,372 @@
+//! Environment variable overrides for the Trireme config
+//!
+//! Every setting is resolved in this order, first match wins:
+//!
+//! 1. The environment variable, if set and non-empty
+//! 2. The value in the config files under the Trireme config dir
+//! 3. The built-in default
+//!
+//! | Variable                          | Overrides                                         |
+//! |-----------------------------------|---------------------------------------------------|
+//! | `TRIREME_CONFIG_DIR`              | Config dir, instead of `~/.trireme`               |
+//! | `TRIREME_ENV`                     | Active environment name                           |
+//! | `TRIREME_NETWORK`                 | Network: `preprod`, `preview` or `mainnet`        |
+//! | `TRIREME_BLOCKFROST_API_KEY`      | BlockFrost API key, read instead of the key file  |
+//! | `TRIREME_BLOCKFROST_API_KEY_FILE` | Path to the BlockFrost API key file               |
+//! | `TRIREME_OGMIOS_IP`               | Ogmios IP                                         |
+//! | `TRIREME_OGMIOS_PORT`             | Ogmios port                                       |
+//! | `TRIREME_SCROLLS_IP`              | Scrolls IP                                        |
+//! | `TRIREME_SCROLLS_PORT`            | Scrolls port                                      |
+//! | `TRIREME_PHRASE_FILE`             | Path to the (raw or password-protected) phrase    |
+//! | `TRIREME_KEYSTORE_FILE`           | Path to the encrypted keystore                    |
+//! | `TRIREME_KEYSTORE_PASSPHRASE`     | Keystore passphrase, instead of prompting         |
+//! | `TRIREME_DERIVATION_PATH`         | Derivation path, e.g. `m/1852'/1815'/0'/0/0`      |
+//! | `TRIREME_REMOTE_SIGNER_URL`       | Remote signer URL                                 |
+//! | `TRIREME_REMOTE_SIGNER_TOKEN`     | Bearer token sent to the remote signer            |
+//! | `TRIREME_ALLOW_MAINNET`           | `true` allows issuing transactions on mainnet     |
+//!
+//! If there is no config file at all, a config is built from the variables alone. That
+//! needs `TRIREME_NETWORK`, a provider (`TRIREME_BLOCKFROST_API_KEY[_FILE]` or
+//! `TRIREME_OGMIOS_IP`) and a key (`TRIREME_KEYSTORE_FILE` or `TRIREME_PHRASE_FILE`), so
+//! containers and CI don't have to write anything into `$HOME`.
+
+use crate::{
+    error::{
+        Error,
+        Result,
+    },
+    trireme_ledger_client::{
+        secret_phrase::DerivationPath,
+        ClientConfig,
+        ClientVariant,
+        KeySource,
+        LedgerSource,
+        Network,
+    },
+};
+use std::{
+    path::PathBuf,
+    str::FromStr,
+};
+
+/// Replaces the `~/.trireme` config dir
+pub const CONFIG_DIR_VAR: &str = "TRIREME_CONFIG_DIR";
+/// Replaces the active environment
+pub const ENV_VAR: &str = "TRIREME_ENV";
+/// Replaces the network of the active environment
+pub const NETWORK_VAR: &str = "TRIREME_NETWORK";
+/// BlockFrost API key, used instead of the key file
+pub const BLOCKFROST_API_KEY_VAR: &str = "TRIREME_BLOCKFROST_API_KEY";
+/// Replaces the BlockFrost API key file path
+pub const BLOCKFROST_API_KEY_FILE_VAR: &str = "TRIREME_BLOCKFROST_API_KEY_FILE";
+/// Replaces the Ogmios IP
+pub const OGMIOS_IP_VAR: &str = "TRIREME_OGMIOS_IP";
+/// Replaces the Ogmios port
+pub const OGMIOS_PORT_VAR: &str = "TRIREME_OGMIOS_PORT";
+/// Replaces the Scrolls IP
+pub const SCROLLS_IP_VAR: &str = "TRIREME_SCROLLS_IP";
+/// Replaces the Scrolls port
+pub const SCROLLS_PORT_VAR: &str = "TRIREME_SCROLLS_PORT";
+/// Replaces the secret phrase file path
+pub const PHRASE_FILE_VAR: &str = "TRIREME_PHRASE_FILE";
+/// Replaces the keystore file path
+pub const KEYSTORE_FILE_VAR: &str = "TRIREME_KEYSTORE_FILE";
+/// Keystore passphrase, used instead of prompting on the terminal
+pub const KEYSTORE_PASSPHRASE_VAR: &str = "TRIREME_KEYSTORE_PASSPHRASE";
+/// Replaces the derivation path of secret phrase keys
+pub const DERIVATION_PATH_VAR: &str = "TRIREME_DERIVATION_PATH";
+/// Replaces the remote signer URL
+pub const REMOTE_SIGNER_URL_VAR: &str = "TRIREME_REMOTE_SIGNER_URL";
+/// Bearer token for the remote signer, never stored in the config file
+pub const REMOTE_SIGNER_TOKEN_VAR: &str = "TRIREME_REMOTE_SIGNER_TOKEN";
+/// Replaces whether transactions may be issued on mainnet
+pub const ALLOW_MAINNET_VAR: &str = "TRIREME_ALLOW_MAINNET";
+
+const DEFAULT_SCROLLS_PORT: &str = "6379";
+const DEFAULT_OGMIOS_PORT: &str = "1337";
+
+/// Read a variable from the process environment, treating empty values as unset
+pub fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+impl FromStr for Network {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "preprod" => Ok(Network::Preprod),
+            "preview" => Ok(Network::Preview),
+            "mainnet" => Ok(Network::Mainnet),
+            _ => Err(Error::Trireme(format!("Unknown network: {s:?}"))),
+        }
+    }
+}
+
+/// Apply the overrides found by `lookup` to `config`
+pub fn apply_overrides(
+    mut config: ClientConfig,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<ClientConfig> {
+    if let ClientVariant::CML(inner) = &mut config.variant {
+        if let Some(network) = lookup(NETWORK_VAR) {
+            inner.network = network.parse()?;
+        }
+        if let Some(allow_mainnet) = lookup(ALLOW_MAINNET_VAR) {
+            inner.allow_mainnet = allow_mainnet.parse().map_err(|_| {
+                Error::Trireme(format!(
+                    "{ALLOW_MAINNET_VAR} must be `true` or `false`, got {allow_mainnet:?}"
+                ))
+            })?;
+        }
+        override_ledger_source(&mut inner.ledger_source, &lookup);
+        override_key_source(&mut inner.key_source, &lookup)?;
+    }
+    Ok(config)
+}
+
+/// Build a config purely from the variables found by `lookup`
+///
+/// Returns `None` if the network, provider or key isn't specified.
+pub fn config_from_vars(
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Option<ClientConfig>> {
+    let network = match lookup(NETWORK_VAR) {
+        Some(network) => network.parse::<Network>()?,
+        None => return Ok(None),
+    };
+    let ledger_source = if lookup(BLOCKFROST_API_KEY_VAR).is_some()
+        || lookup(BLOCKFROST_API_KEY_FILE_VAR).is_some()
+    {
+        LedgerSource::BlockFrost {
+            api_key_file: PathBuf::new(),
+        }
+    } else if let Some(ogmios_ip) = lookup(OGMIOS_IP_VAR) {
+        LedgerSource::OgmiosAndScrolls {
+            scrolls_ip: ogmios_ip.clone(),
+            scrolls_port: DEFAULT_SCROLLS_PORT.to_string(),
+            ogmios_ip,
+            ogmios_port: DEFAULT_OGMIOS_PORT.to_string(),
+        }
+    } else {
+        return Ok(None)
+    };
+    let key_source = if lookup(KEYSTORE_FILE_VAR).is_some() {
+        KeySource::EncryptedKeystore {
+            keystore_file: PathBuf::new(),
+            derivation_path: DerivationPath::default(),
+        }
+    } else if lookup(PHRASE_FILE_VAR).is_some() {
+        KeySource::RawSecretPhrase {
+            phrase_file: PathBuf::new(),
+            derivation_path: DerivationPath::default(),
+        }
+    } else {
+        return Ok(None)
+    };
+    let name = lookup(ENV_VAR).unwrap_or_else(|| "env".to_string());
+    let config = ClientConfig::new_cml(&name, ledger_source, key_source, network);
+    apply_overrides(config, lookup).map(Some)
+}
+
+fn override_ledger_source(
+    ledger_source: &mut LedgerSource,
+    lookup: &impl Fn(&str) -> Option<String>,
+) {
+    match ledger_source {
+        LedgerSource::BlockFrost { api_key_file } => {
+            override_value(api_key_file, lookup(BLOCKFROST_API_KEY_FILE_VAR));
+        }
+        LedgerSource::OgmiosAndScrolls {
+            scrolls_ip,
+            scrolls_port,
+            ogmios_ip,
+            ogmios_port,
+        } => {
+            override_value(scrolls_ip, lookup(SCROLLS_IP_VAR));
+            override_value(scrolls_port, lookup(SCROLLS_PORT_VAR));
+            override_value(ogmios_ip, lookup(OGMIOS_IP_VAR));
+            override_value(ogmios_port, lookup(OGMIOS_PORT_VAR));
+        }
+    }
+}
+
+fn override_key_source(
+    key_source: &mut KeySource,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<()> {
+    let derivation_path = lookup(DERIVATION_PATH_VAR)
+        .map(|path| path.parse::<DerivationPath>())
+        .transpose()
+        .map_err(|e| Error::Trireme(e.to_string()))?;
+    match key_source {
+        KeySource::RawSecretPhrase {
+            phrase_file,
+            derivation_path: path,
+        }
+        | KeySource::TerminalPasswordUpfrontSecretPhrase {
+            phrase_file,
+            derivation_path: path,
+            ..
+        } => {
+            override_value(phrase_file, lookup(PHRASE_FILE_VAR));
+            override_value(path, derivation_path);
+        }
+        KeySource::EncryptedKeystore {
+            keystore_file,
+            derivation_path: path,
+        } => {
+            override_value(keystore_file, lookup(KEYSTORE_FILE_VAR));
+            override_value(path, derivation_path);
+        }
+        KeySource::OsKeychain {
+            derivation_path: path,
+            ..
+        } => {
+            override_value(path, derivation_path);
+        }
+        KeySource::RemoteSigner { url } => {
+            override_value(url, lookup(REMOTE_SIGNER_URL_VAR));
+        }
+        KeySource::LedgerHardwareWallet { .. }
+        | KeySource::TrezorHardwareWallet { .. }
+        | KeySource::Cip30BrowserWallet { .. }
+        | KeySource::WatchOnlyAddress { .. }
+        | KeySource::WatchOnlyXpub { .. } => {}
+    }
+    Ok(())
+}
+
+fn override_value<T, V: Into<T>>(field: &mut T, value: Option<V>) {
+    if let Some(value) = value {
+        *field = value.into();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::trireme_ledger_client::CMLClientConfig;
+    use std::collections::HashMap;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    fn cml_inner(config: ClientConfig) -> CMLClientConfig {
+        match config.variant() {
+            ClientVariant::CML(inner) => inner,
+            ClientVariant::Test(_) => panic!("expected CML config"),
+        }
+    }
+
+    #[test]
+    fn apply_overrides__env_vars_take_precedence_over_file_values() {
+        // given
+        let config = ClientConfig::new_cml(
+            "preprod-test-wallet",
+            LedgerSource::BlockFrost {
+                api_key_file: PathBuf::from("/home/me/.trireme/blockfrost.toml"),
+            },
+            KeySource::EncryptedKeystore {
+                keystore_file: PathBuf::from("/home/me/.trireme/keystore.toml"),
+                derivation_path: DerivationPath::default(),
+            },
+            Network::Preprod,
+        );
+        let vars = lookup(&[
+            (NETWORK_VAR, "mainnet"),
+            (BLOCKFROST_API_KEY_FILE_VAR, "/run/secrets/blockfrost.toml"),
+            (KEYSTORE_FILE_VAR, "/run/secrets/keystore.toml"),
+            (DERIVATION_PATH_VAR, "m/1852'/1815'/2'/0/0"),
+            (ALLOW_MAINNET_VAR, "true"),
+        ]);
+
+        // when
+        let inner = cml_inner(apply_overrides(config, vars).unwrap());
+
+        // then
+        assert_eq!(inner.network.to_string(), "Mainnet");
+        assert!(inner.allow_mainnet);
+        assert!(matches!(
+            inner.ledger_source,
+            LedgerSource::BlockFrost { api_key_file }
+                if api_key_file == PathBuf::from("/run/secrets/blockfrost.toml")
+        ));
+        assert!(matches!(
+            inner.key_source,
+            KeySource::EncryptedKeystore { keystore_file, derivation_path }
+                if keystore_file == PathBuf::from("/run/secrets/keystore.toml")
+                    && derivation_path.account() == 2
+        ));
+    }
+
+    #[test]
+    fn apply_overrides__keeps_file_values_without_env_vars() {
+        // given
+        let config = ClientConfig::new_cml(
+            "preprod-test-wallet",
+            LedgerSource::OgmiosAndScrolls {
+                scrolls_ip: "10.0.0.1".to_string(),
+                scrolls_port: "6379".to_string(),
+                ogmios_ip: "10.0.0.2".to_string(),
+                ogmios_port: "1337".to_string(),
+            },
+            KeySource::TrezorHardwareWallet {
+                account: 0,
+                address_index: 0,
+            },
+            Network::Preprod,
+        );
+
+        // when
+        let inner = cml_inner(apply_overrides(config, lookup(&[])).unwrap());
+
+        // then
+        assert_eq!(inner.network.to_string(), "Preprod");
+        assert!(matches!(
+            inner.ledger_source,
+            LedgerSource::OgmiosAndScrolls { ogmios_ip, .. } if ogmios_ip == "10.0.0.2"
+        ));
+    }
+
+    #[test]
+    fn config_from_vars__builds_config_without_files() {
+        // given
+        let vars = lookup(&[
+            (NETWORK_VAR, "preprod"),
+            (BLOCKFROST_API_KEY_VAR, "preprodAbc123"),
+            (PHRASE_FILE_VAR, "/run/secrets/phrase.toml"),
+        ]);
+
+        // when
+        let config = config_from_vars(vars).unwrap().unwrap();
+
+        // then
+        assert_eq!(
+            config.summary(),
+            "Preprod via BlockFrost with raw secret phrase"
+        );
+    }
+
+    #[test]
+    fn config_from_vars__is_none_without_key() {
+        // given
+        let vars = lookup(&[
+            (NETWORK_VAR, "preprod"),
+            (BLOCKFROST_API_KEY_VAR, "preprodAbc123"),
+        ]);
+
+        // when
+        let config = config_from_vars(vars).unwrap();
+
+        // then
+        assert!(config.is_none());
+    }
+}
@@ -0,0 +1,310 @@
+use crate::{
+    error::{
+        Error,
+        Result,
+    },
+    trireme_ledger_client::{
+        cml_client::{
+            error::{
+                CMLLCError,
+                Result as CMLResult,
+            },
+            Keys,
+        },
+        secret_phrase::{
+            account_key_to_drep_key,
+            account_key_to_payment_key,
+            private_key_to_base_address_at,
+            secret_phrase_to_account_key_at,
+            validate_secret_phrase,
+            DerivationPath,
+        },
+        write_toml_struct_to_file,
+    },
+};
+use async_trait::async_trait;
+use cardano_multiplatform_lib::{
+    address::BaseAddress,
+    crypto::PrivateKey,
+};
+use chacha20poly1305::{
+    aead::Aead,
+    ChaCha20Poly1305,
+    KeyInit,
+    Nonce,
+};
+use dialoguer::Password as InputPassword;
+use rand::Rng;
+use secrecy::{
+    ExposeSecret,
+    Secret,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::path::PathBuf;
+use thiserror::Error;
+use tokio::{
+    fs,
+    sync::OnceCell,
+    task,
+};
+
+const KEYSTORE_VERSION: u8 = 1;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum KeystoreError {
+    #[error("Unsupported keystore version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("Wrong passphrase or corrupted keystore")]
+    Decryption,
+    #[error("Failed to derive key from passphrase: {0}")]
+    KeyDerivation(String),
+}
+
+/// Secret phrase encrypted at rest with a key derived from a passphrase
+///
+/// The key is derived with Argon2id and the phrase sealed with ChaCha20-Poly1305, so a
+/// wrong passphrase is detected instead of silently producing garbage.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EncryptedKeystore {
+    version: u8,
+    salt: Vec<u8>,
+    nonce: [u8; 12],
+    ciphertext: Vec<u8>,
+}
+
+impl EncryptedKeystore {
+    /// Encrypt `phrase` with `passphrase`, using a fresh random salt and nonce
+    pub fn encrypt(phrase: &str, passphrase: &Secret<String>) -> Result<Self> {
+        let salt = rand::thread_rng().gen::<[u8; 32]>().to_vec();
+        let nonce = rand::thread_rng().gen::<[u8; 12]>();
+        let key =
+            derive_key(passphrase, &salt).map_err(|e| Error::Trireme(e.to_string()))?;
+        let cipher = ChaCha20Poly1305::new(key.expose_secret().into());
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), phrase.as_bytes())
+            .map_err(|_| Error::Trireme("Failed to encrypt secret phrase".to_string()))?;
+        Ok(EncryptedKeystore {
+            version: KEYSTORE_VERSION,
+            salt,
+            nonce,
+            ciphertext,
+        })
+    }
+
+    /// Decrypt the secret phrase with `passphrase`
+    pub fn decrypt(&self, passphrase: &Secret<String>) -> CMLResult<Secret<String>> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(keystore_error(KeystoreError::UnsupportedVersion(
+                self.version,
+            )))
+        }
+        let key = derive_key(passphrase, &self.salt).map_err(keystore_error)?;
+        let cipher = ChaCha20Poly1305::new(key.expose_secret().into());
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&self.nonce), self.ciphertext.as_slice())
+            .map_err(|_| keystore_error(KeystoreError::Decryption))?;
+        let phrase = String::from_utf8(plaintext)
+            .map_err(|_| keystore_error(KeystoreError::Decryption))?;
+        Ok(Secret::new(phrase))
+    }
+}
+
+/// Validate `phrase` and store it at `keystore_path`, encrypted with `passphrase`
+pub async fn import_secret_phrase(
+    phrase: &str,
+    passphrase: &Secret<String>,
+    keystore_path: &PathBuf,
+) -> Result<()> {
+    validate_secret_phrase(phrase).map_err(|e| Error::Trireme(e.to_string()))?;
+    let keystore = EncryptedKeystore::encrypt(phrase, passphrase)?;
+    write_toml_struct_to_file(keystore_path, &keystore).await
+}
+
+fn derive_key(
+    passphrase: &Secret<String>,
+    salt: &[u8],
+) -> std::result::Result<Secret<[u8; 32]>, KeystoreError> {
+    // OWASP recommended Argon2id parameters
+    let config = argon2::Config {
+        variant: argon2::Variant::Argon2id,
+        mem_cost: 19456,
+        time_cost: 2,
+        lanes: 1,
+        hash_length: 32,
+        ..Default::default()
+    };
+    let hashed = argon2::hash_raw(passphrase.expose_secret().as_bytes(), salt, &config)
+        .map_err(|e| KeystoreError::KeyDerivation(e.to_string()))?;
+    let key = hashed[..32].try_into().expect("hash_length is 32");
+    Ok(Secret::new(key))
+}
+
+fn keystore_error(error: KeystoreError) -> CMLLCError {
+    CMLLCError::KeyError(Box::new(error))
+}
+
+/// [`Keys`] implementation reading the secret phrase from an [`EncryptedKeystore`] file
+///
+/// The phrase is decrypted the first time a key is needed and kept for the lifetime of the
+/// keys, zeroized when they're dropped. Argon2id is deliberately slow, so it runs on the
+/// blocking thread pool.
+pub struct KeystoreKeys {
+    keystore_path: PathBuf,
+    passphrase: Secret<String>,
+    phrase: OnceCell<Secret<String>>,
+    network: u8,
+    derivation_path: DerivationPath,
+}
+
+impl KeystoreKeys {
+    /// Constructor for the [`KeystoreKeys`] struct
+    pub fn new(keystore_path: PathBuf, passphrase: Secret<String>, network: u8) -> Self {
+        KeystoreKeys {
+            keystore_path,
+            passphrase,
+            phrase: OnceCell::new(),
+            network,
+            derivation_path: DerivationPath::default(),
+        }
+    }
+
+    /// Prompt for the passphrase on the terminal and construct the keys
+    pub fn prompt(keystore_path: PathBuf, network: u8) -> Result<Self> {
+        let passphrase = InputPassword::new()
+            .with_prompt("Enter keystore passphrase")
+            .interact()
+            .map_err(|e| Error::Trireme(e.to_string()))?;
+        Ok(Self::new(keystore_path, Secret::new(passphrase), network))
+    }
+
+    /// Derive keys at `derivation_path` instead of the first address of the first account
+    pub fn with_derivation_path(mut self, derivation_path: DerivationPath) -> Self {
+        self.derivation_path = derivation_path;
+        self
+    }
+
+    async fn read_phrase(&self) -> CMLResult<&Secret<String>> {
+        self.phrase
+            .get_or_try_init(|| async {
+                let text = fs::read_to_string(&self.keystore_path)
+                    .await
+                    .map_err(|e| CMLLCError::KeyError(Box::new(e)))?;
+                let keystore: EncryptedKeystore = toml::from_str(&text)
+                    .map_err(|e| CMLLCError::KeyError(Box::new(e)))?;
+                let passphrase = Secret::new(self.passphrase.expose_secret().clone());
+                task::spawn_blocking(move || keystore.decrypt(&passphrase))
+                    .await
+                    .map_err(|e| CMLLCError::KeyError(Box::new(e)))?
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl Keys for KeystoreKeys {
+    async fn base_addr(&self) -> CMLResult<BaseAddress> {
+        let phrase = self.read_phrase().await?;
+        let account_key = secret_phrase_to_account_key_at(
+            phrase.expose_secret(),
+            self.derivation_path.account(),
+        )?;
+        let base_addr = private_key_to_base_address_at(
+            &account_key,
+            self.network,
+            &self.derivation_path,
+        );
+        Ok(base_addr)
+    }
+
+    async fn private_key(&self) -> CMLResult<PrivateKey> {
+        let phrase = self.read_phrase().await?;
+        let account_key = secret_phrase_to_account_key_at(
+            phrase.expose_secret(),
+            self.derivation_path.account(),
+        )?;
+        let priv_key = account_key_to_payment_key(&account_key, &self.derivation_path);
+        Ok(priv_key)
+    }
+
+    async fn drep_key(&self) -> CMLResult<PrivateKey> {
+        let phrase = self.read_phrase().await?;
+        let account_key = secret_phrase_to_account_key_at(
+            phrase.expose_secret(),
+            self.derivation_path.account(),
+        )?;
+        Ok(account_key_to_drep_key(&account_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon \
+        abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+        abandon abandon abandon abandon abandon abandon art";
+
+    #[tokio::test]
+    async fn roundtrip_phrase() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("keystore.toml");
+        let passphrase = Secret::new("correct horse battery staple".to_string());
+
+        let keystore = EncryptedKeystore::encrypt(PHRASE, &passphrase).unwrap();
+        write_toml_struct_to_file(&file_path, &keystore)
+            .await
+            .unwrap();
+
+        let keys = KeystoreKeys::new(file_path, passphrase, 0);
+        let phrase = keys.read_phrase().await.unwrap();
+
+        assert_eq!(phrase.expose_secret(), PHRASE);
+    }
+
+    #[tokio::test]
+    async fn read_phrase_decrypts_only_once() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("keystore.toml");
+        let passphrase = Secret::new("correct horse battery staple".to_string());
+        let keystore = EncryptedKeystore::encrypt(PHRASE, &passphrase).unwrap();
+        write_toml_struct_to_file(&file_path, &keystore)
+            .await
+            .unwrap();
+        let keys = KeystoreKeys::new(file_path.clone(), passphrase, 0);
+
+        keys.read_phrase().await.unwrap();
+        std::fs::remove_file(&file_path).unwrap();
+        let phrase = keys.read_phrase().await.unwrap();
+
+        assert_eq!(phrase.expose_secret(), PHRASE);
+    }
+
+    #[tokio::test]
+    async fn import_rejects_invalid_phrase() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("keystore.toml");
+        let passphrase = Secret::new("correct horse battery staple".to_string());
+
+        let res =
+            import_secret_phrase("not a secret phrase", &passphrase, &file_path).await;
+
+        assert!(res.is_err());
+        assert!(!file_path.exists());
+    }
+
+    #[tokio::test]
+    async fn decryption_fails_with_wrong_passphrase() {
+        let passphrase = Secret::new("correct horse battery staple".to_string());
+        let wrong = Secret::new("incorrect horse battery staple".to_string());
+
+        let keystore = EncryptedKeystore::encrypt(PHRASE, &passphrase).unwrap();
+        let res = keystore.decrypt(&wrong);
+
+        assert!(res.is_err());
+    }
+}
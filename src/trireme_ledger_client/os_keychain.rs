@@ -0,0 +1,130 @@
+use crate::{
+    error::{
+        Error,
+        Result,
+    },
+    trireme_ledger_client::{
+        cml_client::{
+            error::{
+                CMLLCError,
+                Result as CMLResult,
+            },
+            Keys,
+        },
+        secret_phrase::{
+            account_key_to_drep_key,
+            account_key_to_payment_key,
+            private_key_to_base_address_at,
+            secret_phrase_to_account_key_at,
+            validate_secret_phrase,
+            DerivationPath,
+        },
+    },
+};
+use async_trait::async_trait;
+use cardano_multiplatform_lib::{
+    address::BaseAddress,
+    crypto::PrivateKey,
+};
+use keyring::Entry;
+use secrecy::{
+    ExposeSecret,
+    Secret,
+};
+
+/// Keychain service name Trireme stores secret phrases under
+pub const DEFAULT_KEYCHAIN_SERVICE: &str = "trireme";
+
+/// Validate `phrase` and store it in the OS keychain as `service`/`account`
+///
+/// Uses the macOS Keychain, the Secret Service on Linux or the Windows Credential
+/// Manager. Overwrites any phrase already stored for the entry.
+pub fn store_secret_phrase_in_keychain(
+    phrase: &str,
+    service: &str,
+    account: &str,
+) -> Result<()> {
+    validate_secret_phrase(phrase).map_err(|e| Error::Trireme(e.to_string()))?;
+    Entry::new(service, account)
+        .and_then(|entry| entry.set_password(phrase))
+        .map_err(|e| Error::Trireme(e.to_string()))
+}
+
+/// Remove the secret phrase stored as `service`/`account` from the OS keychain
+pub fn delete_secret_phrase_from_keychain(service: &str, account: &str) -> Result<()> {
+    Entry::new(service, account)
+        .and_then(|entry| entry.delete_password())
+        .map_err(|e| Error::Trireme(e.to_string()))
+}
+
+/// [`Keys`] implementation reading the secret phrase from the OS keychain
+///
+/// The phrase is fetched from the keychain every time a key is needed, so it is never
+/// kept in memory longer than a single operation.
+pub struct KeychainKeys {
+    service: String,
+    account: String,
+    network: u8,
+    derivation_path: DerivationPath,
+}
+
+impl KeychainKeys {
+    /// Constructor for the [`KeychainKeys`] struct
+    pub fn new(service: &str, account: &str, network: u8) -> Self {
+        KeychainKeys {
+            service: service.to_string(),
+            account: account.to_string(),
+            network,
+            derivation_path: DerivationPath::default(),
+        }
+    }
+
+    /// Derive keys at `derivation_path` instead of the first address of the first account
+    pub fn with_derivation_path(mut self, derivation_path: DerivationPath) -> Self {
+        self.derivation_path = derivation_path;
+        self
+    }
+
+    fn read_phrase(&self) -> CMLResult<Secret<String>> {
+        let phrase = Entry::new(&self.service, &self.account)
+            .and_then(|entry| entry.get_password())
+            .map_err(|e| CMLLCError::KeyError(Box::new(e)))?;
+        Ok(Secret::new(phrase))
+    }
+}
+
+#[async_trait]
+impl Keys for KeychainKeys {
+    async fn base_addr(&self) -> CMLResult<BaseAddress> {
+        let phrase = self.read_phrase()?;
+        let account_key = secret_phrase_to_account_key_at(
+            phrase.expose_secret(),
+            self.derivation_path.account(),
+        )?;
+        let base_addr = private_key_to_base_address_at(
+            &account_key,
+            self.network,
+            &self.derivation_path,
+        );
+        Ok(base_addr)
+    }
+
+    async fn private_key(&self) -> CMLResult<PrivateKey> {
+        let phrase = self.read_phrase()?;
+        let account_key = secret_phrase_to_account_key_at(
+            phrase.expose_secret(),
+            self.derivation_path.account(),
+        )?;
+        let priv_key = account_key_to_payment_key(&account_key, &self.derivation_path);
+        Ok(priv_key)
+    }
+
+    async fn drep_key(&self) -> CMLResult<PrivateKey> {
+        let phrase = self.read_phrase()?;
+        let account_key = secret_phrase_to_account_key_at(
+            phrase.expose_secret(),
+            self.derivation_path.account(),
+        )?;
+        Ok(account_key_to_drep_key(&account_key))
+    }
+}
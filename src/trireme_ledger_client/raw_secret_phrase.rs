@@ -7,8 +7,11 @@ use crate::trireme_ledger_client::{
         Keys,
     },
     secret_phrase::{
-        private_key_to_base_address,
-        secret_phrase_to_account_key,
+        account_key_to_drep_key,
+        account_key_to_payment_key,
+        private_key_to_base_address_at,
+        secret_phrase_to_account_key_at,
+        DerivationPath,
     },
 };
 use async_trait::async_trait;
@@ -34,6 +37,7 @@ use tokio::fs;
 pub struct RawSecretPhraseKeys {
     phrase_file_path: PathBuf,
     network: u8,
+    derivation_path: DerivationPath,
 }
 
 impl RawSecretPhraseKeys {
@@ -42,8 +46,15 @@ impl RawSecretPhraseKeys {
         RawSecretPhraseKeys {
             phrase_file_path,
             network,
+            derivation_path: DerivationPath::default(),
         }
     }
+
+    /// Derive keys at `derivation_path` instead of the first address of the first account
+    pub fn with_derivation_path(mut self, derivation_path: DerivationPath) -> Self {
+        self.derivation_path = derivation_path;
+        self
+    }
 }
 
 #[allow(missing_docs)]
@@ -59,14 +70,19 @@ impl RawSecretPhraseKeys {
     /// Get the account key from the secret phrase
     async fn get_account_key(&self) -> CMLLCResult<Bip32PrivateKey> {
         let phrase: String = read_secret_phrase(&self.phrase_file_path).await?.into();
-        let account_key = secret_phrase_to_account_key(&phrase)?;
+        let account_key =
+            secret_phrase_to_account_key_at(&phrase, self.derivation_path.account())?;
         Ok(account_key)
     }
 
     /// Get the base address from the secret phrase
     async fn get_base_address(&self) -> CMLLCResult<BaseAddress> {
         let account_key = self.get_account_key().await?;
-        let base_addr = private_key_to_base_address(&account_key, self.network);
+        let base_addr = private_key_to_base_address_at(
+            &account_key,
+            self.network,
+            &self.derivation_path,
+        );
         Ok(base_addr)
     }
 }
@@ -80,9 +96,14 @@ impl Keys for RawSecretPhraseKeys {
 
     async fn private_key(&self) -> CMLLCResult<PrivateKey> {
         let account_key = self.get_account_key().await?;
-        let priv_key = account_key.derive(0).derive(0).to_raw_key();
+        let priv_key = account_key_to_payment_key(&account_key, &self.derivation_path);
         Ok(priv_key)
     }
+
+    async fn drep_key(&self) -> CMLLCResult<PrivateKey> {
+        let account_key = self.get_account_key().await?;
+        Ok(account_key_to_drep_key(&account_key))
+    }
 }
 
 /// Type for holding the secret phrase
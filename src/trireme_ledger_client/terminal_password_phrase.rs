@@ -6,8 +6,11 @@ use crate::{
     trireme_ledger_client::{
         cml_client::Keys,
         secret_phrase::{
-            private_key_to_base_address,
-            secret_phrase_to_account_key,
+            account_key_to_drep_key,
+            account_key_to_payment_key,
+            private_key_to_base_address_at,
+            secret_phrase_to_account_key_at,
+            DerivationPath,
         },
     },
 };
@@ -46,6 +49,7 @@ pub struct PasswordProtectedPhraseKeys<P: Password> {
     phrase_file_path: PathBuf,
     encryption_nonce: [u8; 12],
     network: u8,
+    derivation_path: DerivationPath,
 }
 
 impl<P: Password> PasswordProtectedPhraseKeys<P> {
@@ -61,9 +65,16 @@ impl<P: Password> PasswordProtectedPhraseKeys<P> {
             phrase_file_path,
             encryption_nonce,
             network: network_index,
+            derivation_path: DerivationPath::default(),
         }
     }
 
+    /// Derive keys at `derivation_path` instead of the first address of the first account
+    pub fn with_derivation_path(mut self, derivation_path: DerivationPath) -> Self {
+        self.derivation_path = derivation_path;
+        self
+    }
+
     /// Decrypt and read the secret phrase
     async fn read_phrase(&self) -> CMLResult<String> {
         let text = fs::read_to_string(&self.phrase_file_path)
@@ -83,17 +94,30 @@ impl<P: Password> PasswordProtectedPhraseKeys<P> {
 impl<P: Password + Send + Sync> Keys for PasswordProtectedPhraseKeys<P> {
     async fn base_addr(&self) -> CMLResult<BaseAddress> {
         let phrase = self.read_phrase().await?;
-        let account_key = secret_phrase_to_account_key(&phrase)?;
-        let base_addr = private_key_to_base_address(&account_key, self.network);
+        let account_key =
+            secret_phrase_to_account_key_at(&phrase, self.derivation_path.account())?;
+        let base_addr = private_key_to_base_address_at(
+            &account_key,
+            self.network,
+            &self.derivation_path,
+        );
         Ok(base_addr)
     }
 
     async fn private_key(&self) -> CMLResult<PrivateKey> {
         let phrase = self.read_phrase().await?;
-        let account_key = secret_phrase_to_account_key(&phrase)?;
-        let priv_key = account_key.derive(0).derive(0).to_raw_key();
+        let account_key =
+            secret_phrase_to_account_key_at(&phrase, self.derivation_path.account())?;
+        let priv_key = account_key_to_payment_key(&account_key, &self.derivation_path);
         Ok(priv_key)
     }
+
+    async fn drep_key(&self) -> CMLResult<PrivateKey> {
+        let phrase = self.read_phrase().await?;
+        let account_key =
+            secret_phrase_to_account_key_at(&phrase, self.derivation_path.account())?;
+        Ok(account_key_to_drep_key(&account_key))
+    }
 }
 
 /// Type for holding password
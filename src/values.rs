@@ -1,19 +1,74 @@
 use crate::{
-    error::{
-        Error,
-        Result,
-    },
+    error::ErrorClass,
     output::Output,
+    protocol_params::ProtocolParameters,
     PolicyId,
 };
+use pallas_primitives::babbage::{
+    AssetName,
+    Coin,
+    PolicyId as PallasPolicyId,
+    Value as PallasValue,
+};
 use serde::{
     Deserialize,
     Serialize,
 };
 use std::{
     cmp::Ordering,
-    collections::HashMap,
+    collections::{
+        BTreeMap,
+        HashMap,
+    },
+    fmt,
+    iter::Sum,
+    ops::Add,
 };
+use thiserror::Error;
+
+// Bytes every UTxO entry is charged for on top of its serialized output
+const UTXO_ENTRY_OVERHEAD: u64 = 160;
+// Serialized size of a base address: 1 header byte and two 28 byte credentials
+const BASE_ADDRESS_SIZE: u64 = 57;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ValuesError {
+    #[error("Adding to the amount of {0:?} overflows")]
+    Overflow(PolicyId),
+    #[error("Insufficient amount of {policy:?}: have {available}, need {required}")]
+    Underflow {
+        policy: PolicyId,
+        available: u64,
+        required: u64,
+    },
+    #[error("Invalid policy id: {0:?}")]
+    InvalidPolicyId(String),
+}
+
+impl ValuesError {
+    /// Stable code identifying the failure
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValuesError::Overflow(_) => "values.overflow",
+            ValuesError::Underflow { .. } => "values.underflow",
+            ValuesError::InvalidPolicyId(_) => "values.invalid_policy_id",
+        }
+    }
+
+    /// Broad class of the failure
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            ValuesError::Underflow { .. } => ErrorClass::InsufficientFunds,
+            ValuesError::Overflow(_) | ValuesError::InvalidPolicyId(_) => {
+                ErrorClass::InvalidInput
+            }
+        }
+    }
+}
+
+/// Specialized result type for [`Values`] arithmetic
+pub type ValuesResult<T, E = ValuesError> = std::result::Result<T, E>;
 
 /// Domain representation of value on the Cardano blockchain
 #[serde_with::serde_as]
@@ -33,50 +88,99 @@ impl Values {
     }
 
     /// Try to remove the `other` `Values` from `self`
-    pub fn try_subtract(&self, other: &Values) -> Result<Option<Values>> {
-        let mut remainders = Vec::new();
-        let mut mine_cloned = self.values.clone();
-        let mut there_is_a_difference = false;
-        if !other.is_empty() {
-            for (policy, amt) in other.as_iter() {
-                if let Some(available) = mine_cloned.remove(policy) {
-                    match amt.cmp(&available) {
-                        Ordering::Less => {
-                            let remaining = available - amt;
-                            remainders.push((policy.clone(), remaining));
-                            there_is_a_difference = true;
-                        }
-                        Ordering::Greater => {
-                            return Err(Error::InsufficientAmountOf(policy.to_owned()))
-                        }
-                        _ => {}
-                    }
-                } else {
-                    return Err(Error::InsufficientAmountOf(policy.to_owned()));
+    ///
+    /// Fails if `self` has less of any policy than `other`. Policies that end up at zero are
+    /// dropped, so subtracting equal `Values` leaves an empty `Values`.
+    pub fn try_subtract(&self, other: &Values) -> ValuesResult<Values> {
+        let mut values = self.values.clone();
+        for (policy, required) in other.as_iter() {
+            let available = values.get(policy).copied().unwrap_or_default();
+            let remaining = available.checked_sub(*required).ok_or_else(|| {
+                ValuesError::Underflow {
+                    policy: policy.clone(),
+                    available,
+                    required: *required,
                 }
+            })?;
+            if remaining == 0 {
+                values.remove(policy);
+            } else {
+                values.insert(policy.clone(), remaining);
             }
-        } else {
-            there_is_a_difference = true; // We just keep what we started with
         }
+        Ok(Values { values })
+    }
+
+    /// Try to add the `other` `Values` to `self`, failing if any amount overflows
+    pub fn try_add(&self, other: &Values) -> ValuesResult<Values> {
+        let mut values = self.values.clone();
+        for (policy, amount) in other.as_iter() {
+            let total = values
+                .get(policy)
+                .copied()
+                .unwrap_or_default()
+                .checked_add(*amount)
+                .ok_or_else(|| ValuesError::Overflow(policy.clone()))?;
+            values.insert(policy.clone(), total);
+        }
+        Ok(Values { values })
+    }
 
-        let other_remainders: Vec<_> = mine_cloned.into_iter().collect();
-        remainders.extend(other_remainders);
+    /// Whether `self` has at least as much of every policy as `other`
+    pub fn contains(&self, other: &Values) -> bool {
+        other
+            .as_iter()
+            .all(|(policy, amount)| self.get(policy).unwrap_or_default() >= *amount)
+    }
 
-        let values = remainders.into_iter().collect();
-        if there_is_a_difference {
-            let difference = Values { values };
-            Ok(Some(difference))
-        } else {
-            Ok(None)
+    /// Compare `self` to `other` policy by policy
+    pub fn diff(&self, other: &Values) -> ValuesDiff {
+        let mut surplus = Values::default();
+        let mut shortfall = Values::default();
+        for (policy, mine) in self.as_iter() {
+            let theirs = other.get(policy).unwrap_or_default();
+            if *mine > theirs {
+                surplus.add_one_value(policy, mine - theirs);
+            }
+        }
+        for (policy, theirs) in other.as_iter() {
+            let mine = self.get(policy).unwrap_or_default();
+            if *theirs > mine {
+                shortfall.add_one_value(policy, theirs - mine);
+            }
         }
+        ValuesDiff { surplus, shortfall }
     }
 
     /// Add one value to the `self`
+    ///
+    /// Panics if the total overflows. Use [`Values::try_add_one_value`] for amounts that
+    /// aren't known to be in range.
     pub fn add_one_value(&mut self, policy: &PolicyId, amount: u64) {
         add_to_map(&mut self.values, policy.clone(), amount)
     }
 
+    /// Add one value to the `self`, failing if the total overflows
+    pub fn try_add_one_value(
+        &mut self,
+        policy: &PolicyId,
+        amount: u64,
+    ) -> ValuesResult<()> {
+        let total = self
+            .values
+            .get(policy)
+            .copied()
+            .unwrap_or_default()
+            .checked_add(amount)
+            .ok_or_else(|| ValuesError::Overflow(policy.clone()))?;
+        self.values.insert(policy.clone(), total);
+        Ok(())
+    }
+
     /// Add a `Values` to the `self`
+    ///
+    /// Panics if any total overflows. Use [`Values::try_add`] for amounts that aren't known
+    /// to be in range.
     pub fn add_values(&mut self, values: &Values) {
         for (policy, amt) in values.as_iter() {
             self.add_one_value(policy, *amt)
@@ -90,7 +194,9 @@ impl Values {
 
     /// Convert the `Values` to a `Vec` of [`PolicyId`]s and amounts
     pub fn vec(&self) -> Vec<(PolicyId, u64)> {
-        self.values.clone().into_iter().collect()
+        self.as_iter()
+            .map(|(policy, amount)| (policy.clone(), *amount))
+            .collect()
     }
 
     /// Get the amount for a given [`PolicyId`]
@@ -103,6 +209,79 @@ impl Values {
         self.values.remove(policy)
     }
 
+    /// Lovelace an output holding these native tokens needs to meet the min-UTxO rule
+    ///
+    /// Uses the Babbage formula, `(160 + output size) * coinsPerUTxOByte`, for an output to
+    /// a base address without a datum. Any Lovelace already in `self` is ignored. Use
+    /// [`UnbuiltOutput::min_ada_required`](crate::output::UnbuiltOutput::min_ada_required)
+    /// for outputs with a datum or to other kinds of address.
+    pub fn min_ada_required(&self, protocol_params: &ProtocolParameters) -> u64 {
+        self.min_ada_for_output(protocol_params, BASE_ADDRESS_SIZE, &[])
+    }
+
+    /// Like [`Values::min_ada_required`], for an output whose address is `address_size` bytes
+    /// and which has extra fields, like a datum, of the given serialized sizes
+    pub(crate) fn min_ada_for_output(
+        &self,
+        protocol_params: &ProtocolParameters,
+        address_size: u64,
+        extra_field_sizes: &[u64],
+    ) -> u64 {
+        let mut policies: HashMap<String, Vec<(usize, u64)>> = HashMap::new();
+        for (policy, amount) in self.as_iter() {
+            if let PolicyId::NativeToken(id, asset_name) = policy {
+                let name_len = asset_name.as_ref().map(|name| name.len()).unwrap_or(0);
+                policies
+                    .entry(id.clone())
+                    .or_default()
+                    .push((name_len, *amount));
+            }
+        }
+        let assets_size = if policies.is_empty() {
+            None
+        } else {
+            let size = policies.iter().fold(
+                cbor_head_size(policies.len() as u64),
+                |acc, (id, assets)| {
+                    let assets_size = assets.iter().fold(
+                        cbor_head_size(assets.len() as u64),
+                        |acc, (name_len, amount)| {
+                            acc + cbor_bytes_size(*name_len as u64)
+                                + cbor_head_size(*amount)
+                        },
+                    );
+                    acc + cbor_bytes_size(id.len() as u64 / 2) + assets_size
+                },
+            );
+            Some(size)
+        };
+        let required_for = |coin: u64| {
+            let value_size = match assets_size {
+                Some(assets_size) => 1 + cbor_head_size(coin) + assets_size,
+                None => cbor_head_size(coin),
+            };
+            let fields_size = cbor_bytes_size(address_size) + value_size;
+            // Outputs without a datum or script serialize as `[address, value]`, others as
+            // a map with a one byte key per field
+            let output_size = if extra_field_sizes.is_empty() {
+                1 + fields_size
+            } else {
+                let field_count = 2 + extra_field_sizes.len() as u64;
+                1 + field_count + fields_size + extra_field_sizes.iter().sum::<u64>()
+            };
+            (UTXO_ENTRY_OVERHEAD + output_size) * protocol_params.coins_per_utxo_byte()
+        };
+        // The required Lovelace is part of the output, so grow it until its own size settles
+        let mut coin = 0;
+        loop {
+            let required = required_for(coin);
+            if cbor_head_size(required) == cbor_head_size(coin) {
+                return required
+            }
+            coin = required;
+        }
+    }
+
     /// Get the number of [`PolicyId`]s in the `Values`
     pub fn len(&self) -> usize {
         self.values.len()
@@ -114,14 +293,462 @@ impl Values {
     }
 }
 
+/// Partial order by containment: `a >= b` when `a` has at least as much of every policy as
+/// `b`. `Values` holding more of one policy and less of another aren't comparable, and
+/// neither are `Values` only differing in policies one of them holds none of, since they
+/// aren't equal either.
+impl PartialOrd for Values {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self.contains(other), other.contains(self)) {
+            (true, true) => (self == other).then_some(Ordering::Equal),
+            (true, false) => Some(Ordering::Greater),
+            (false, true) => Some(Ordering::Less),
+            (false, false) => None,
+        }
+    }
+}
+
+/// Result of [`Values::diff`]
+#[derive(Clone, PartialEq, Debug, Eq, Default)]
+pub struct ValuesDiff {
+    surplus: Values,
+    shortfall: Values,
+}
+
+impl ValuesDiff {
+    /// What the first `Values` has beyond the second
+    pub fn surplus(&self) -> &Values {
+        &self.surplus
+    }
+
+    /// What the first `Values` is missing to cover the second
+    pub fn shortfall(&self) -> &Values {
+        &self.shortfall
+    }
+
+    /// Whether the first `Values` covers the second
+    pub fn is_covered(&self) -> bool {
+        self.shortfall.is_empty()
+    }
+}
+
+/// Summarizes the values as e.g. `2.5 ADA, <policy id>.<asset name>: 1`. Asset names are
+/// shown as text when they are printable UTF-8 and as hex otherwise.
+impl fmt::Display for Values {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut assets: Vec<_> = self
+            .as_iter()
+            .filter_map(|(policy, amount)| match policy {
+                PolicyId::Lovelace => None,
+                PolicyId::NativeToken(id, _) => Some((id, policy, amount)),
+            })
+            .collect();
+        assets.sort_by_key(|(id, policy, _)| (id.to_owned(), policy.asset_name()));
+
+        let lovelace = self.get(&PolicyId::Lovelace);
+        if lovelace.is_some() || assets.is_empty() {
+            write_ada(f, lovelace.unwrap_or_default())?;
+            if !assets.is_empty() {
+                write!(f, ", ")?;
+            }
+        }
+        for (i, (id, policy, amount)) in assets.into_iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{id}")?;
+            if let Some(name) = policy.asset_name() {
+                match policy.asset_name_utf8() {
+                    Some(text) if !text.chars().any(char::is_control) => {
+                        write!(f, ".{text}")?
+                    }
+                    _ => write!(f, ".{}", hex::encode(name))?,
+                }
+            }
+            write!(f, ": {amount}")?;
+        }
+        Ok(())
+    }
+}
+
+fn write_ada(f: &mut fmt::Formatter<'_>, lovelace: u64) -> fmt::Result {
+    let whole = lovelace / 1_000_000;
+    let fraction = lovelace % 1_000_000;
+    if fraction == 0 {
+        write!(f, "{whole} ADA")
+    } else {
+        let fraction = format!("{fraction:06}");
+        write!(f, "{whole}.{} ADA", fraction.trim_end_matches('0'))
+    }
+}
+
+/// Panics if any amount overflows, use [`Values::try_add`] to handle that
+impl Add for Values {
+    type Output = Values;
+
+    fn add(mut self, rhs: Values) -> Self::Output {
+        self.add_values(&rhs);
+        self
+    }
+}
+
+impl<'a> Add<&'a Values> for Values {
+    type Output = Values;
+
+    fn add(mut self, rhs: &'a Values) -> Self::Output {
+        self.add_values(rhs);
+        self
+    }
+}
+
+impl Sum for Values {
+    fn sum<I: Iterator<Item = Values>>(iter: I) -> Self {
+        iter.fold(Values::default(), Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Values> for Values {
+    fn sum<I: Iterator<Item = &'a Values>>(iter: I) -> Self {
+        iter.fold(Values::default(), Add::add)
+    }
+}
+
+impl From<&PallasValue> for Values {
+    fn from(value: &PallasValue) -> Self {
+        let mut values = Values::default();
+        match value {
+            PallasValue::Coin(lovelace) => {
+                values.add_one_value(&PolicyId::Lovelace, *lovelace);
+            }
+            PallasValue::Multiasset(lovelace, multiasset) => {
+                values.add_one_value(&PolicyId::Lovelace, *lovelace);
+                for (policy, assets) in multiasset.iter() {
+                    for (name, amount) in assets.iter() {
+                        let policy_id = PolicyId::native_token(
+                            &policy.to_string(),
+                            &Some(name.to_vec()),
+                        );
+                        values.add_one_value(&policy_id, *amount);
+                    }
+                }
+            }
+        }
+        values
+    }
+}
+
+impl Values {
+    /// Lovelace, and native token amounts grouped by policy and asset name
+    ///
+    /// A missing asset name is the empty one on chain, so tokens stored under both are
+    /// summed rather than one replacing the other.
+    pub(crate) fn grouped_by_policy(
+        &self,
+    ) -> ValuesResult<(u64, BTreeMap<&str, BTreeMap<Vec<u8>, u64>>)> {
+        let mut lovelace = 0;
+        let mut grouped: BTreeMap<&str, BTreeMap<Vec<u8>, u64>> = BTreeMap::new();
+        for (policy, amount) in self.as_iter() {
+            match policy {
+                PolicyId::Lovelace => lovelace = *amount,
+                PolicyId::NativeToken(id, asset_name) => {
+                    let total = grouped
+                        .entry(id)
+                        .or_default()
+                        .entry(asset_name.clone().unwrap_or_default())
+                        .or_default();
+                    *total = total
+                        .checked_add(*amount)
+                        .ok_or_else(|| ValuesError::Overflow(policy.clone()))?;
+                }
+            }
+        }
+        Ok((lovelace, grouped))
+    }
+}
+
+/// Native tokens without an asset name get the empty asset name
+impl TryFrom<&Values> for PallasValue {
+    type Error = ValuesError;
+
+    fn try_from(values: &Values) -> ValuesResult<Self> {
+        let (lovelace, grouped) = values.grouped_by_policy()?;
+        if grouped.is_empty() {
+            return Ok(PallasValue::Coin(lovelace))
+        }
+        let multiasset = grouped
+            .into_iter()
+            .map(|(id, assets)| {
+                let policy: PallasPolicyId = id
+                    .parse()
+                    .map_err(|_| ValuesError::InvalidPolicyId(id.to_string()))?;
+                let assets: Vec<(AssetName, Coin)> = assets
+                    .into_iter()
+                    .map(|(name, amount)| (name.into(), amount))
+                    .collect();
+                Ok((policy, assets.into()))
+            })
+            .collect::<ValuesResult<Vec<_>>>()?;
+        Ok(PallasValue::Multiasset(lovelace, multiasset.into()))
+    }
+}
+
+// Size of a CBOR head carrying `n`, as an unsigned int or a length
+pub(crate) fn cbor_head_size(n: u64) -> u64 {
+    match n {
+        0..=23 => 1,
+        24..=0xff => 2,
+        0x100..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+pub(crate) fn cbor_bytes_size(len: u64) -> u64 {
+    cbor_head_size(len) + len
+}
+
 pub(crate) fn add_to_map(
     h_map: &mut HashMap<PolicyId, u64>,
     policy: PolicyId,
     amount: u64,
 ) {
-    let mut new_total = amount;
-    if let Some(total) = h_map.get(&policy) {
-        new_total += total;
+    let total = h_map.get(&policy).copied().unwrap_or_default();
+    let new_total = total
+        .checked_add(amount)
+        .unwrap_or_else(|| panic!("Adding {amount} to {total} of {policy:?} overflows"));
+    h_map.insert(policy, new_total);
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    fn values(entries: &[(PolicyId, u64)]) -> Values {
+        let mut values = Values::default();
+        for (policy, amount) in entries {
+            values.add_one_value(policy, *amount);
+        }
+        values
+    }
+
+    #[test]
+    fn try_subtract__drops_policies_that_reach_zero() {
+        // given
+        let token = PolicyId::native_token("abc", &None);
+        let mine = values(&[(PolicyId::Lovelace, 10), (token.clone(), 5)]);
+        let other = values(&[(PolicyId::Lovelace, 4), (token, 5)]);
+
+        // when
+        let difference = mine.try_subtract(&other).unwrap();
+
+        // then
+        assert_eq!(difference, values(&[(PolicyId::Lovelace, 6)]));
+    }
+
+    #[test]
+    fn try_subtract__fails_with_underflow() {
+        // given
+        let token = PolicyId::native_token("abc", &None);
+        let mine = values(&[(PolicyId::Lovelace, 10)]);
+        let other = values(&[(token.clone(), 1)]);
+
+        // when
+        let error = mine.try_subtract(&other).unwrap_err();
+
+        // then
+        assert_eq!(
+            error,
+            ValuesError::Underflow {
+                policy: token,
+                available: 0,
+                required: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn try_add__fails_with_overflow() {
+        let mine = values(&[(PolicyId::Lovelace, u64::MAX)]);
+        let other = values(&[(PolicyId::Lovelace, 1)]);
+
+        let error = mine.try_add(&other).unwrap_err();
+
+        assert_eq!(error, ValuesError::Overflow(PolicyId::Lovelace));
+    }
+
+    #[test]
+    fn try_add_one_value__fails_with_overflow_and_leaves_the_total() {
+        let mut mine = values(&[(PolicyId::Lovelace, u64::MAX)]);
+
+        let error = mine.try_add_one_value(&PolicyId::Lovelace, 1).unwrap_err();
+
+        assert_eq!(error, ValuesError::Overflow(PolicyId::Lovelace));
+        assert_eq!(mine.get(&PolicyId::Lovelace), Some(u64::MAX));
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows")]
+    fn add_one_value__panics_on_overflow() {
+        let mut mine = values(&[(PolicyId::Lovelace, u64::MAX)]);
+
+        mine.add_one_value(&PolicyId::Lovelace, 1);
+    }
+
+    #[test]
+    fn min_ada_required__ada_only() {
+        let values = values(&[(PolicyId::Lovelace, 10)]);
+
+        let min_ada = values.min_ada_required(&ProtocolParameters::default());
+
+        assert_eq!(min_ada, 969_750);
+    }
+
+    #[test]
+    fn min_ada_required__grows_with_native_tokens() {
+        // given
+        let policy = "8a1cfae21368b8bebbbed9800fec304e95cce39a2a57dc35e2e3ebaa";
+        let one_token =
+            values(&[(PolicyId::native_token(policy, &Some(b"Nft".to_vec())), 1)]);
+        let mut two_tokens = one_token.clone();
+        two_tokens.add_one_value(
+            &PolicyId::native_token(policy, &Some(b"OtherNft".to_vec())),
+            1,
+        );
+        let params = ProtocolParameters::default();
+
+        // when
+        let one_token_min = one_token.min_ada_required(&params);
+        let two_tokens_min = two_tokens.min_ada_required(&params);
+
+        // then
+        // 1 + 59 + (1 + 5 + 1 + 30 + 1 + 4 + 1) = 103 bytes of output
+        assert_eq!(one_token_min, (160 + 103) * 4310);
+        assert!(two_tokens_min > one_token_min);
+    }
+
+    #[test]
+    fn display__shows_ada_in_decimal_and_assets_by_name() {
+        let policy = "8a1cfae21368b8bebbbed9800fec304e95cce39a2a57dc35e2e3ebaa";
+        let values = values(&[
+            (PolicyId::Lovelace, 2_500_000),
+            (PolicyId::native_token(policy, &Some(b"Nft".to_vec())), 1),
+            (PolicyId::native_token(policy, &Some(vec![0xff])), 7),
+        ]);
+
+        let displayed = values.to_string();
+
+        assert_eq!(
+            displayed,
+            format!("2.5 ADA, {policy}.Nft: 1, {policy}.ff: 7")
+        );
+    }
+
+    #[test]
+    fn contains__requires_every_policy() {
+        let token = PolicyId::native_token("abc", &None);
+        let output = values(&[(PolicyId::Lovelace, 10), (token.clone(), 1)]);
+        let required = values(&[(PolicyId::Lovelace, 5), (token.clone(), 1)]);
+        let too_much = values(&[(PolicyId::Lovelace, 5), (token, 2)]);
+
+        assert!(output.contains(&required));
+        assert!(output >= required);
+        assert!(!output.contains(&too_much));
+        assert_eq!(output.partial_cmp(&too_much), None);
+    }
+
+    #[test]
+    fn partial_cmp__is_consistent_with_eq_for_zero_amounts() {
+        let token = PolicyId::native_token("abc", &None);
+        let ada = values(&[(PolicyId::Lovelace, 10)]);
+        let ada_and_no_token = values(&[(PolicyId::Lovelace, 10), (token, 0)]);
+
+        assert_ne!(ada, ada_and_no_token);
+        assert_eq!(ada.partial_cmp(&ada_and_no_token), None);
+        assert_eq!(ada.partial_cmp(&ada), Some(Ordering::Equal));
+    }
+
+    #[test]
+    fn diff__splits_surplus_and_shortfall() {
+        // given
+        let token = PolicyId::native_token("abc", &None);
+        let mine = values(&[(PolicyId::Lovelace, 10)]);
+        let required = values(&[(PolicyId::Lovelace, 4), (token.clone(), 2)]);
+
+        // when
+        let diff = mine.diff(&required);
+
+        // then
+        assert_eq!(diff.surplus(), &values(&[(PolicyId::Lovelace, 6)]));
+        assert_eq!(diff.shortfall(), &values(&[(token, 2)]));
+        assert!(!diff.is_covered());
+    }
+
+    #[test]
+    fn sum__adds_every_values() {
+        let token = PolicyId::native_token("abc", &None);
+        let all = vec![
+            values(&[(PolicyId::Lovelace, 1)]),
+            values(&[(PolicyId::Lovelace, 2), (token.clone(), 3)]),
+        ];
+
+        let total: Values = all.iter().sum();
+
+        assert_eq!(total, values(&[(PolicyId::Lovelace, 3), (token, 3)]));
+    }
+
+    #[test]
+    fn pallas_value__round_trips() {
+        // given
+        let policy = "bdaa99eb158414dea0a91d6c727e2268574b23efe6e08ab3b841abe8";
+        let values = values(&[
+            (PolicyId::Lovelace, 2_000_000),
+            (PolicyId::native_token(policy, &Some(vec![0xff, 0x01])), 7),
+            (
+                PolicyId::native_token(policy, &Some(b"Token".to_vec())),
+                u64::MAX,
+            ),
+        ]);
+
+        // when
+        let pallas_value = PallasValue::try_from(&values).unwrap();
+        let round_tripped = Values::from(&pallas_value);
+
+        // then
+        assert_eq!(round_tripped, values);
+    }
+
+    #[test]
+    fn pallas_value__sums_unnamed_and_empty_named_tokens() {
+        // given
+        let policy = "bdaa99eb158414dea0a91d6c727e2268574b23efe6e08ab3b841abe8";
+        let tokens = values(&[
+            (PolicyId::Lovelace, 2_000_000),
+            (PolicyId::native_token(policy, &None), 2),
+            (PolicyId::native_token(policy, &Some(vec![])), 3),
+        ]);
+
+        // when
+        let pallas_value = PallasValue::try_from(&tokens).unwrap();
+
+        // then
+        let expected = values(&[
+            (PolicyId::Lovelace, 2_000_000),
+            (PolicyId::native_token(policy, &Some(vec![])), 5),
+        ]);
+        assert_eq!(Values::from(&pallas_value), expected);
+    }
+
+    #[test]
+    fn pallas_value__rejects_invalid_policy_ids() {
+        let values = values(&[(PolicyId::native_token("nope", &None), 1)]);
+
+        let res = PallasValue::try_from(&values);
+
+        assert_eq!(
+            res.err(),
+            Some(ValuesError::InvalidPolicyId("nope".to_string()))
+        );
     }
-    h_map.insert(policy.clone(), new_total);
 }
@@ -14,6 +14,7 @@ use naumachia::{
             SCLogicResult,
         },
         SCLogic,
+        SCLogicWithOutput,
     },
     policy_id::PolicyId,
     scripts::{
@@ -87,6 +88,22 @@ impl SCLogic for AlwaysMintsSmartContract {
     }
 }
 
+#[async_trait]
+impl SCLogicWithOutput for AlwaysMintsSmartContract {
+    type EndpointOutput = PolicyId;
+
+    async fn handle_endpoint_with_output<
+        Record: LedgerClient<Self::Datums, Self::Redeemers>,
+    >(
+        endpoint: Self::Endpoints,
+        txo_record: &Record,
+    ) -> SCLogicResult<(TxActions<(), ()>, PolicyId)> {
+        let actions = Self::handle_endpoint(endpoint, txo_record).await?;
+        let policy = PolicyId::native_token(&hex::encode(MINT_POLICY_ID), &None);
+        Ok((actions, policy))
+    }
+}
+
 fn mint(amount: u64, _recipient: Address) -> SCLogicResult<TxActions<(), ()>> {
     let policy = Box::new(AlwaysMintsPolicy);
     let utx = TxActions::v1().with_mint(amount, None, (), policy);
@@ -114,3 +131,22 @@ async fn can_mint_from_always_true_minting_policy() {
     .unwrap();
     assert_eq!(expected, actual)
 }
+
+#[tokio::test]
+async fn hit_endpoint_with_output_returns_minted_policy() {
+    let me = Address::from_bech32("addr_test1qpuy2q9xel76qxdw8r29skldzc876cdgg9cugfg7mwh0zvpg3292mxuf3kq7nysjumlxjrlsfn9tp85r0l54l29x3qcs7nvyfm").unwrap();
+    let backend = TestLedgerClientBuilder::new(&me).build_in_memory();
+    let amount = 69;
+    let call = Endpoint::Mint { amount };
+    let contract = SmartContract::new(AlwaysMintsSmartContract, backend);
+
+    let (_, policy) = contract.hit_endpoint_with_output(call).await.unwrap();
+
+    let actual = <TestLedgerClient<(), (), InMemoryStorage<()>> as LedgerClient<
+        (),
+        (),
+    >>::balance_at_address(contract.ledger_client(), &me, &policy)
+    .await
+    .unwrap();
+    assert_eq!(amount, actual)
+}
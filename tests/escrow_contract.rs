@@ -0,0 +1,473 @@
+use async_trait::async_trait;
+use naumachia::{
+    error::Error,
+    ledger_client::{
+        test_ledger_client::{
+            in_memory_storage::InMemoryStorage,
+            TestLedgerClient,
+            TestLedgerClientBuilder,
+        },
+        LedgerClient,
+        LedgerClientError,
+    },
+    logic::{
+        error::{
+            as_endpoint_err,
+            as_lookup_err,
+            SCLogicError,
+            SCLogicResult,
+        },
+        SCLogic,
+    },
+    output::{
+        Output,
+        OutputId,
+    },
+    policy_id::PolicyId,
+    scripts::{
+        context::{
+            pub_key_hash_from_address_if_available,
+            CtxDatum,
+            CtxValue,
+            TxContext,
+        },
+        plutus_validator::plutus_data::{
+            Constr,
+            PlutusData,
+        },
+        ExecutionCost,
+        ScriptError,
+        ScriptResult,
+        Validator,
+    },
+    smart_contract::{
+        SmartContract,
+        SmartContractTrait,
+    },
+    transaction::TxActions,
+    values::Values,
+};
+use pallas_addresses::{
+    Address,
+    Network,
+};
+
+const ESCROW_SCRIPT: &str =
+    "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu";
+
+const PAYER: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+const RECEIVER: &str = "addr_test1qzvrhz9v6lwcr26a52y8mmk2nzq37lky68359keq3dgth4lkzpnnjv8vf98m20lhqdzl60mcftq7r2lc4xtcsv0w6xjstag0ua";
+const ARBITER: &str = "addr_test1qpuy2q9xel76qxdw8r29skldzc876cdgg9cugfg7mwh0zvpg3292mxuf3kq7nysjumlxjrlsfn9tp85r0l54l29x3qcs7nvyfm";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct EscrowDatum {
+    payer: Address,
+    receiver: Address,
+    arbiter: Address,
+}
+
+impl From<EscrowDatum> for PlutusData {
+    fn from(datum: EscrowDatum) -> Self {
+        let fields = [datum.payer, datum.receiver, datum.arbiter]
+            .iter()
+            .map(|address| PlutusData::BoundedBytes(address.to_vec()))
+            .collect();
+        PlutusData::Constr(Constr { constr: 0, fields })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum EscrowRedeemer {
+    /// Pay `amount` to the receiver, keeping the rest in escrow
+    Release { amount: u64 },
+    /// Give everything back to the payer
+    Refund,
+}
+
+/// Lets the payer or the arbiter release funds to the receiver, and only the arbiter refund
+struct EscrowValidatorScript;
+
+fn lovelace(value: &CtxValue) -> u64 {
+    value
+        .inner
+        .get("")
+        .and_then(|assets| assets.get(&Vec::new()))
+        .copied()
+        .unwrap_or(0)
+}
+
+fn paid_to(ctx: &TxContext, address: &Address) -> u64 {
+    ctx.outputs
+        .iter()
+        .filter(|output| &output.address == address)
+        .map(|output| lovelace(&output.value))
+        .sum()
+}
+
+fn signed_by(ctx: &TxContext, address: &Address) -> bool {
+    pub_key_hash_from_address_if_available(address).as_ref() == Some(&ctx.signer)
+}
+
+impl Validator<EscrowDatum, EscrowRedeemer> for EscrowValidatorScript {
+    fn execute(
+        &self,
+        datum: EscrowDatum,
+        redeemer: EscrowRedeemer,
+        ctx: TxContext,
+    ) -> ScriptResult<ExecutionCost> {
+        let script = Address::from_bech32(ESCROW_SCRIPT).unwrap();
+        // Only one escrow is spent at a time, so everything from the script is this one's
+        let held: u64 = ctx
+            .inputs
+            .iter()
+            .filter(|input| input.address == script)
+            .map(|input| lovelace(&input.value))
+            .sum();
+        match redeemer {
+            EscrowRedeemer::Release { amount } => {
+                if !signed_by(&ctx, &datum.payer) && !signed_by(&ctx, &datum.arbiter) {
+                    return Err(ScriptError::FailedToExecute(
+                        "Only the payer or the arbiter can release".to_string(),
+                    ))
+                }
+                if paid_to(&ctx, &datum.receiver) < amount {
+                    return Err(ScriptError::FailedToExecute(
+                        "Receiver isn't paid the released amount".to_string(),
+                    ))
+                }
+                let remaining = held.saturating_sub(amount);
+                let expected: PlutusData = datum.clone().into();
+                let kept = ctx.outputs.iter().any(|output| {
+                    output.address == script
+                        && lovelace(&output.value) >= remaining
+                        && matches!(&output.datum, CtxDatum::InlineDatum(data) if data == &expected)
+                });
+                if remaining > 0 && !kept {
+                    return Err(ScriptError::FailedToExecute(
+                        "Unreleased funds must stay in escrow".to_string(),
+                    ))
+                }
+            }
+            EscrowRedeemer::Refund => {
+                if !signed_by(&ctx, &datum.arbiter) {
+                    return Err(ScriptError::FailedToExecute(
+                        "Only the arbiter can refund".to_string(),
+                    ))
+                }
+                if paid_to(&ctx, &datum.payer) < held {
+                    return Err(ScriptError::FailedToExecute(
+                        "Payer isn't refunded".to_string(),
+                    ))
+                }
+            }
+        }
+        Ok(ExecutionCost::default())
+    }
+
+    fn address(&self, _network: Network) -> ScriptResult<Address> {
+        Ok(Address::from_bech32(ESCROW_SCRIPT).unwrap())
+    }
+
+    fn script_hex(&self) -> ScriptResult<String> {
+        todo!()
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct EscrowContract;
+
+#[derive(Debug)]
+enum Endpoint {
+    Escrow {
+        amount: u64,
+        receiver: Address,
+        arbiter: Address,
+    },
+    Release {
+        output_id: OutputId,
+        amount: u64,
+    },
+    Refund {
+        output_id: OutputId,
+    },
+}
+
+#[derive(Debug)]
+enum Lookup {
+    ForReceiver(Address),
+}
+
+#[derive(Debug)]
+enum LookupResponse {
+    Escrows(Vec<Output<EscrowDatum>>),
+}
+
+#[async_trait]
+impl SCLogic for EscrowContract {
+    type Endpoints = Endpoint;
+    type Lookups = Lookup;
+    type LookupResponses = LookupResponse;
+    type Datums = EscrowDatum;
+    type Redeemers = EscrowRedeemer;
+
+    async fn handle_endpoint<Record: LedgerClient<Self::Datums, Self::Redeemers>>(
+        endpoint: Self::Endpoints,
+        txo_record: &Record,
+    ) -> SCLogicResult<TxActions<EscrowDatum, EscrowRedeemer>> {
+        match endpoint {
+            Endpoint::Escrow {
+                amount,
+                receiver,
+                arbiter,
+            } => {
+                let payer = txo_record.signer_base_address().await?;
+                let datum = EscrowDatum {
+                    payer,
+                    receiver,
+                    arbiter,
+                };
+                let mut values = Values::default();
+                values.add_one_value(&PolicyId::Lovelace, amount);
+                let address = Address::from_bech32(ESCROW_SCRIPT).unwrap();
+                Ok(TxActions::v2().with_script_init_inline_datum(datum, values, address))
+            }
+            Endpoint::Release { output_id, amount } => {
+                let (output, datum) = find_escrow(txo_record, &output_id).await?;
+                let held = output.values().get(&PolicyId::Lovelace).unwrap_or(0);
+                if amount > held {
+                    return Err(SCLogicError::Endpoint(
+                        format!("Can't release {amount}, escrow only holds {held}")
+                            .into(),
+                    ))
+                }
+                let redeemer = EscrowRedeemer::Release { amount };
+                let mut actions = TxActions::v2()
+                    .with_script_redeem(output, redeemer, Box::new(EscrowValidatorScript))
+                    .with_transfer(amount, datum.receiver.clone(), PolicyId::Lovelace);
+                if held > amount {
+                    let mut values = Values::default();
+                    values.add_one_value(&PolicyId::Lovelace, held - amount);
+                    let address = Address::from_bech32(ESCROW_SCRIPT).unwrap();
+                    actions =
+                        actions.with_script_init_inline_datum(datum, values, address);
+                }
+                Ok(actions)
+            }
+            Endpoint::Refund { output_id } => {
+                let (output, datum) = find_escrow(txo_record, &output_id).await?;
+                let held = output.values().get(&PolicyId::Lovelace).unwrap_or(0);
+                let actions = TxActions::v2()
+                    .with_script_redeem(
+                        output,
+                        EscrowRedeemer::Refund,
+                        Box::new(EscrowValidatorScript),
+                    )
+                    .with_transfer(held, datum.payer, PolicyId::Lovelace);
+                Ok(actions)
+            }
+        }
+    }
+
+    async fn lookup<Record: LedgerClient<Self::Datums, Self::Redeemers>>(
+        lookup: Self::Lookups,
+        txo_record: &Record,
+    ) -> SCLogicResult<Self::LookupResponses> {
+        match lookup {
+            Lookup::ForReceiver(receiver) => {
+                let address = Address::from_bech32(ESCROW_SCRIPT).unwrap();
+                let escrows = txo_record
+                    .all_outputs_at_address(&address)
+                    .await
+                    .map_err(as_lookup_err)?
+                    .into_iter()
+                    .filter(|output| {
+                        matches!(output.typed_datum(), Some(datum) if datum.receiver == receiver)
+                    })
+                    .collect();
+                Ok(LookupResponse::Escrows(escrows))
+            }
+        }
+    }
+}
+
+async fn find_escrow<Record: LedgerClient<EscrowDatum, EscrowRedeemer>>(
+    txo_record: &Record,
+    output_id: &OutputId,
+) -> SCLogicResult<(Output<EscrowDatum>, EscrowDatum)> {
+    let address = Address::from_bech32(ESCROW_SCRIPT).unwrap();
+    let output = txo_record
+        .all_outputs_at_address(&address)
+        .await?
+        .into_iter()
+        .find(|output| output.id() == output_id)
+        .ok_or(SCLogicError::Endpoint(
+            format!("No escrow with id {output_id:?}").into(),
+        ))?;
+    let datum = output
+        .typed_datum()
+        .ok_or(ScriptError::DatumDeserialization(format!("{output_id:?}")))
+        .map_err(as_endpoint_err)?;
+    Ok((output, datum))
+}
+
+type EscrowLedger =
+    TestLedgerClient<EscrowDatum, EscrowRedeemer, InMemoryStorage<EscrowDatum>>;
+
+fn contract_for(
+    ledger_client: &EscrowLedger,
+    signer: &str,
+) -> SmartContract<EscrowContract, EscrowLedger> {
+    let signer = Address::from_bech32(signer).unwrap();
+    SmartContract::new(EscrowContract, ledger_client.with_signer(&signer))
+}
+
+async fn escrowed(amount: u64) -> (EscrowLedger, OutputId) {
+    let payer = Address::from_bech32(PAYER).unwrap();
+    let arbiter = Address::from_bech32(ARBITER).unwrap();
+    let ledger_client = TestLedgerClientBuilder::new(&payer)
+        .fund_lovelace(&payer, 100_000_000)
+        .fund_lovelace(&arbiter, 10_000_000)
+        .build_in_memory();
+    let receiver = Address::from_bech32(RECEIVER).unwrap();
+    contract_for(&ledger_client, PAYER)
+        .hit_endpoint(Endpoint::Escrow {
+            amount,
+            receiver: receiver.clone(),
+            arbiter,
+        })
+        .await
+        .unwrap();
+    let output_id = escrow_for(&ledger_client, &receiver)
+        .await
+        .first()
+        .unwrap()
+        .id()
+        .clone();
+    (ledger_client, output_id)
+}
+
+async fn escrow_for(
+    ledger_client: &EscrowLedger,
+    receiver: &Address,
+) -> Vec<Output<EscrowDatum>> {
+    let LookupResponse::Escrows(escrows) = contract_for(ledger_client, PAYER)
+        .lookup(Lookup::ForReceiver(receiver.clone()))
+        .await
+        .unwrap();
+    escrows
+}
+
+async fn balance(ledger_client: &EscrowLedger, address: &str) -> u64 {
+    let address = Address::from_bech32(address).unwrap();
+    ledger_client
+        .balance_at_address(&address, &PolicyId::Lovelace)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn escrow_locks_funds_for_the_receiver() {
+    let (ledger_client, _) = escrowed(30_000_000).await;
+
+    let receiver = Address::from_bech32(RECEIVER).unwrap();
+    let escrows = escrow_for(&ledger_client, &receiver).await;
+    assert_eq!(escrows.len(), 1);
+    assert_eq!(
+        escrows[0].values().get(&PolicyId::Lovelace),
+        Some(30_000_000)
+    );
+    assert_eq!(balance(&ledger_client, PAYER).await, 70_000_000);
+}
+
+#[tokio::test]
+async fn arbiter_can_release_part_and_keep_the_rest_in_escrow() {
+    let (ledger_client, output_id) = escrowed(30_000_000).await;
+
+    contract_for(&ledger_client, ARBITER)
+        .hit_endpoint(Endpoint::Release {
+            output_id,
+            amount: 10_000_000,
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(balance(&ledger_client, RECEIVER).await, 10_000_000);
+    let receiver = Address::from_bech32(RECEIVER).unwrap();
+    let escrows = escrow_for(&ledger_client, &receiver).await;
+    assert_eq!(escrows.len(), 1);
+    assert_eq!(
+        escrows[0].values().get(&PolicyId::Lovelace),
+        Some(20_000_000)
+    );
+}
+
+#[tokio::test]
+async fn payer_can_release_the_rest_after_a_partial_release() {
+    let (ledger_client, output_id) = escrowed(30_000_000).await;
+    contract_for(&ledger_client, ARBITER)
+        .hit_endpoint(Endpoint::Release {
+            output_id,
+            amount: 10_000_000,
+        })
+        .await
+        .unwrap();
+    let receiver = Address::from_bech32(RECEIVER).unwrap();
+    let output_id = escrow_for(&ledger_client, &receiver).await[0].id().clone();
+
+    contract_for(&ledger_client, PAYER)
+        .hit_endpoint(Endpoint::Release {
+            output_id,
+            amount: 20_000_000,
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(balance(&ledger_client, RECEIVER).await, 30_000_000);
+    assert!(escrow_for(&ledger_client, &receiver).await.is_empty());
+}
+
+#[tokio::test]
+async fn receiver_cannot_release_to_themselves() {
+    let (ledger_client, output_id) = escrowed(30_000_000).await;
+
+    let err = contract_for(&ledger_client, RECEIVER)
+        .hit_endpoint(Endpoint::Release {
+            output_id,
+            amount: 30_000_000,
+        })
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err,
+        Error::LedgerClient(LedgerClientError::FailedToIssueTx(_))
+    ));
+    let receiver = Address::from_bech32(RECEIVER).unwrap();
+    assert_eq!(escrow_for(&ledger_client, &receiver).await.len(), 1);
+}
+
+#[tokio::test]
+async fn arbiter_can_refund_the_payer() {
+    let (ledger_client, output_id) = escrowed(30_000_000).await;
+
+    contract_for(&ledger_client, ARBITER)
+        .hit_endpoint(Endpoint::Refund { output_id })
+        .await
+        .unwrap();
+
+    assert_eq!(balance(&ledger_client, PAYER).await, 100_000_000);
+    let receiver = Address::from_bech32(RECEIVER).unwrap();
+    assert!(escrow_for(&ledger_client, &receiver).await.is_empty());
+}
+
+#[tokio::test]
+async fn payer_cannot_refund_themselves() {
+    let (ledger_client, output_id) = escrowed(30_000_000).await;
+
+    let res = contract_for(&ledger_client, PAYER)
+        .hit_endpoint(Endpoint::Refund { output_id })
+        .await;
+
+    assert!(res.is_err());
+    assert_eq!(balance(&ledger_client, PAYER).await, 70_000_000);
+}
@@ -0,0 +1,186 @@
+use async_trait::async_trait;
+use naumachia::{
+    ledger_client::{
+        test_ledger_client::TestLedgerClientBuilder,
+        LedgerClient,
+    },
+    logic::{
+        error::SCLogicResult,
+        SCLogic,
+        SCLogicWithConfig,
+        SCLogicWithOutput,
+    },
+    policy_id::PolicyId,
+    smart_contract::{
+        SmartContract,
+        SmartContractTrait,
+    },
+    transaction::TxActions,
+};
+use pallas_addresses::Address;
+
+const ME: &str = "addr_test1qpuy2q9xel76qxdw8r29skldzc876cdgg9cugfg7mwh0zvpg3292mxuf3kq7nysjumlxjrlsfn9tp85r0l54l29x3qcs7nvyfm";
+const ALICE: &str = "addr_test1qzvrhz9v6lwcr26a52y8mmk2nzq37lky68359keq3dgth4lkzpnnjv8vf98m20lhqdzl60mcftq7r2lc4xtcsv0w6xjstag0ua";
+
+struct FeeConfig {
+    treasury: Address,
+    fee: u64,
+}
+
+struct FeeLogic {
+    config: FeeConfig,
+}
+
+#[derive(Debug)]
+enum Endpoint {
+    PayFee,
+}
+
+#[async_trait]
+impl SCLogic for FeeLogic {
+    type Endpoints = Endpoint;
+    type Lookups = ();
+    type LookupResponses = ();
+    type Datums = ();
+    type Redeemers = ();
+
+    async fn handle_instance_endpoint<
+        Record: LedgerClient<Self::Datums, Self::Redeemers>,
+    >(
+        &self,
+        endpoint: Self::Endpoints,
+        txo_record: &Record,
+    ) -> SCLogicResult<TxActions<(), ()>> {
+        Self::handle_endpoint_with_config(endpoint, self.config(), txo_record).await
+    }
+
+    async fn lookup<Record: LedgerClient<Self::Datums, Self::Redeemers>>(
+        _endpoint: Self::Lookups,
+        _txo_record: &Record,
+    ) -> SCLogicResult<Self::LookupResponses> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SCLogicWithConfig for FeeLogic {
+    type Config = FeeConfig;
+
+    fn config(&self) -> &FeeConfig {
+        &self.config
+    }
+
+    async fn handle_endpoint_with_config<
+        Record: LedgerClient<Self::Datums, Self::Redeemers>,
+    >(
+        endpoint: Self::Endpoints,
+        config: &FeeConfig,
+        _txo_record: &Record,
+    ) -> SCLogicResult<TxActions<(), ()>> {
+        match endpoint {
+            Endpoint::PayFee => Ok(TxActions::v2().with_transfer(
+                config.fee,
+                config.treasury.clone(),
+                PolicyId::Lovelace,
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl SCLogicWithOutput for FeeLogic {
+    type EndpointOutput = u64;
+
+    async fn handle_instance_endpoint_with_output<
+        Record: LedgerClient<Self::Datums, Self::Redeemers>,
+    >(
+        &self,
+        endpoint: Self::Endpoints,
+        txo_record: &Record,
+    ) -> SCLogicResult<(TxActions<(), ()>, u64)> {
+        let actions = self.handle_instance_endpoint(endpoint, txo_record).await?;
+        Ok((actions, self.config.fee))
+    }
+}
+
+#[tokio::test]
+async fn hit_endpoint_with_config_uses_the_instance_configuration() {
+    let me = Address::from_bech32(ME).unwrap();
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let ledger_client = TestLedgerClientBuilder::new(&me)
+        .fund_lovelace(&me, 10_000_000)
+        .build_in_memory();
+    let logic = FeeLogic {
+        config: FeeConfig {
+            treasury: alice.clone(),
+            fee: 3_000_000,
+        },
+    };
+    let contract = SmartContract::new(logic, ledger_client);
+
+    contract
+        .hit_endpoint_with_config(Endpoint::PayFee)
+        .await
+        .unwrap();
+
+    let treasury_balance = contract
+        .ledger_client()
+        .balance_at_address(&alice, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(treasury_balance, 3_000_000);
+}
+
+#[tokio::test]
+async fn hit_endpoint_uses_the_instance_configuration() {
+    let me = Address::from_bech32(ME).unwrap();
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let ledger_client = TestLedgerClientBuilder::new(&me)
+        .fund_lovelace(&me, 10_000_000)
+        .build_in_memory();
+    let logic = FeeLogic {
+        config: FeeConfig {
+            treasury: alice.clone(),
+            fee: 2_000_000,
+        },
+    };
+    let contract = SmartContract::new(logic, ledger_client);
+
+    contract.hit_endpoint(Endpoint::PayFee).await.unwrap();
+
+    let treasury_balance = contract
+        .ledger_client()
+        .balance_at_address(&alice, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(treasury_balance, 2_000_000);
+}
+
+#[tokio::test]
+async fn hit_endpoint_with_output_uses_the_instance_configuration() {
+    let me = Address::from_bech32(ME).unwrap();
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let ledger_client = TestLedgerClientBuilder::new(&me)
+        .fund_lovelace(&me, 10_000_000)
+        .build_in_memory();
+    let logic = FeeLogic {
+        config: FeeConfig {
+            treasury: alice.clone(),
+            fee: 4_000_000,
+        },
+    };
+    let contract = SmartContract::new(logic, ledger_client);
+
+    let (_, fee) = contract
+        .hit_endpoint_with_output(Endpoint::PayFee)
+        .await
+        .unwrap();
+
+    let treasury_balance = contract
+        .ledger_client()
+        .balance_at_address(&alice, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(fee, 4_000_000);
+    assert_eq!(treasury_balance, 4_000_000);
+}
@@ -0,0 +1,124 @@
+use async_trait::async_trait;
+use naumachia::{
+    ledger_client::{
+        test_ledger_client::TestLedgerClientBuilder,
+        LedgerClient,
+    },
+    logic::{
+        error::{
+            as_lookup_err,
+            SCLogicError,
+            SCLogicResult,
+        },
+        SCLogic,
+        SCLogicWithSteps,
+    },
+    policy_id::PolicyId,
+    smart_contract::SmartContract,
+    transaction::{
+        TxActions,
+        TxId,
+    },
+    values::Values,
+};
+use pallas_addresses::Address;
+
+const SCRIPT: &str = "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu";
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct TwoStepContract;
+
+#[derive(Debug)]
+enum Endpoint {
+    DeployThenInit,
+}
+
+fn lock(datum: i64) -> TxActions<i64, ()> {
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, 2_000_000);
+    let address = Address::from_bech32(SCRIPT).unwrap();
+    TxActions::v2().with_script_init(datum, values, address)
+}
+
+#[async_trait]
+impl SCLogic for TwoStepContract {
+    type Endpoints = Endpoint;
+    type Lookups = ();
+    type LookupResponses = ();
+    type Datums = i64;
+    type Redeemers = ();
+
+    async fn handle_endpoint<Record: LedgerClient<Self::Datums, Self::Redeemers>>(
+        _endpoint: Self::Endpoints,
+        _txo_record: &Record,
+    ) -> SCLogicResult<TxActions<i64, ()>> {
+        Ok(lock(1))
+    }
+
+    async fn lookup<Record: LedgerClient<Self::Datums, Self::Redeemers>>(
+        _endpoint: Self::Lookups,
+        _txo_record: &Record,
+    ) -> SCLogicResult<Self::LookupResponses> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SCLogicWithSteps for TwoStepContract {
+    async fn handle_endpoint_step<Record: LedgerClient<Self::Datums, Self::Redeemers>>(
+        _endpoint: &Self::Endpoints,
+        step: usize,
+        previous: &[TxId],
+        txo_record: &Record,
+    ) -> SCLogicResult<Option<TxActions<i64, ()>>> {
+        match step {
+            0 => Ok(Some(lock(1))),
+            1 => {
+                // The first step's output has to be there before the second is built
+                let address = Address::from_bech32(SCRIPT).unwrap();
+                let outputs = txo_record
+                    .all_outputs_at_address(&address)
+                    .await
+                    .map_err(as_lookup_err)?;
+                let deployed = outputs.iter().any(|output| {
+                    hex::encode(output.id().tx_hash()) == previous[0].as_str()
+                });
+                if !deployed {
+                    return Err(SCLogicError::Endpoint(
+                        "First step's output is missing".into(),
+                    ))
+                }
+                Ok(Some(lock(2)))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[tokio::test]
+async fn hit_endpoint_in_steps_submits_each_step_in_order() {
+    let me = Address::from_bech32("addr_test1qpuy2q9xel76qxdw8r29skldzc876cdgg9cugfg7mwh0zvpg3292mxuf3kq7nysjumlxjrlsfn9tp85r0l54l29x3qcs7nvyfm").unwrap();
+    let ledger_client = TestLedgerClientBuilder::new(&me)
+        .fund_lovelace(&me, 10_000_000)
+        .build_in_memory();
+    let contract = SmartContract::new(TwoStepContract, ledger_client);
+
+    let tx_ids = contract
+        .hit_endpoint_in_steps(Endpoint::DeployThenInit)
+        .await
+        .unwrap();
+
+    assert_eq!(tx_ids.len(), 2);
+    assert_ne!(tx_ids[0], tx_ids[1]);
+    let script = Address::from_bech32(SCRIPT).unwrap();
+    let mut datums: Vec<_> = contract
+        .ledger_client()
+        .all_outputs_at_address(&script)
+        .await
+        .unwrap()
+        .iter()
+        .filter_map(|output| output.typed_datum())
+        .collect();
+    datums.sort();
+    assert_eq!(datums, vec![1, 2]);
+}
@@ -1,5 +1,9 @@
 use async_trait::async_trait;
 use naumachia::{
+    error::{
+        Error,
+        Result,
+    },
     ledger_client::{
         test_ledger_client::TestLedgerClientBuilder,
         LedgerClient,
@@ -13,9 +17,16 @@ use naumachia::{
         SmartContract,
         SmartContractTrait,
     },
-    transaction::TxActions,
+    transaction::{
+        TxActions,
+        TxId,
+    },
 };
 use pallas_addresses::Address;
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 struct TransferADASmartContract;
@@ -105,3 +116,154 @@ async fn can_transfer_and_keep_remainder() {
         .unwrap();
     assert_eq!(expected_extra_amount, actual_extra_amount);
 }
+
+const ME: &str = "addr_test1qpuy2q9xel76qxdw8r29skldzc876cdgg9cugfg7mwh0zvpg3292mxuf3kq7nysjumlxjrlsfn9tp85r0l54l29x3qcs7nvyfm";
+const ALICE: &str = "addr_test1qzvrhz9v6lwcr26a52y8mmk2nzq37lky68359keq3dgth4lkzpnnjv8vf98m20lhqdzl60mcftq7r2lc4xtcsv0w6xjstag0ua";
+
+static HOOK_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn count_endpoint(_: &Endpoint) -> Result<()> {
+    HOOK_CALLS.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+fn count_tx_actions(tx_actions: &TxActions<(), ()>) -> Result<()> {
+    assert_eq!(tx_actions.actions.len(), 1);
+    HOOK_CALLS.fetch_add(10, Ordering::SeqCst);
+    Ok(())
+}
+
+fn count_tx_id(_: &TxId) {
+    HOOK_CALLS.fetch_add(100, Ordering::SeqCst);
+}
+
+fn reject_large_transfers(endpoint: &Endpoint) -> Result<()> {
+    match endpoint {
+        Endpoint::Transfer { amount, .. } if *amount > 100 => {
+            Err(Error::RejectedByHook(format!("{amount} is over the limit")))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[tokio::test]
+async fn hooks_run_around_endpoint() {
+    let me = Address::from_bech32(ME).unwrap();
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let ledger_client = TestLedgerClientBuilder::new(&me)
+        .start_output(&me)
+        .with_value(PolicyId::Lovelace, 666)
+        .finish_output()
+        .build_in_memory();
+
+    let contract = SmartContract::new(TransferADASmartContract, ledger_client)
+        .with_before_endpoint(count_endpoint)
+        .with_before_submit(count_tx_actions)
+        .with_after_submit(count_tx_id);
+
+    let call = Endpoint::Transfer {
+        amount: 590,
+        recipient: alice,
+    };
+    contract.hit_endpoint(call).await.unwrap();
+
+    assert_eq!(HOOK_CALLS.load(Ordering::SeqCst), 111);
+}
+
+#[tokio::test]
+async fn rejecting_hook_prevents_submission() {
+    let me = Address::from_bech32(ME).unwrap();
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let ledger_client = TestLedgerClientBuilder::new(&me)
+        .start_output(&me)
+        .with_value(PolicyId::Lovelace, 666)
+        .finish_output()
+        .build_in_memory();
+
+    let contract = SmartContract::new(TransferADASmartContract, ledger_client)
+        .with_before_endpoint(reject_large_transfers);
+
+    let call = Endpoint::Transfer {
+        amount: 590,
+        recipient: alice.clone(),
+    };
+    let res = contract.hit_endpoint(call).await;
+
+    assert!(matches!(res, Err(Error::RejectedByHook(_))));
+    let alice_balance = contract
+        .ledger_client()
+        .balance_at_address(&alice, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(alice_balance, 0);
+}
+
+#[tokio::test]
+async fn preview_endpoint_builds_without_submitting() {
+    let me = Address::from_bech32(ME).unwrap();
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let ledger_client = TestLedgerClientBuilder::new(&me)
+        .start_output(&me)
+        .with_value(PolicyId::Lovelace, 666)
+        .finish_output()
+        .build_in_memory();
+
+    let contract = SmartContract::new(TransferADASmartContract, ledger_client);
+
+    let call = Endpoint::Transfer {
+        amount: 590,
+        recipient: alice.clone(),
+    };
+    let preview = contract.preview_endpoint(call).await.unwrap();
+
+    assert_eq!(preview.inputs().len(), 1);
+    assert_eq!(preview.fee(), 0);
+    let to_alice = preview
+        .outputs()
+        .iter()
+        .find(|output| output.owner() == alice)
+        .unwrap();
+    assert_eq!(to_alice.values().get(&PolicyId::Lovelace), Some(590));
+    let alice_balance = contract
+        .ledger_client()
+        .balance_at_address(&alice, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(alice_balance, 0);
+}
+
+#[tokio::test]
+async fn hit_endpoints_atomically_submits_one_transaction() {
+    let me = Address::from_bech32(ME).unwrap();
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let ledger_client = TestLedgerClientBuilder::new(&me)
+        .start_output(&me)
+        .with_value(PolicyId::Lovelace, 666)
+        .finish_output()
+        .build_in_memory();
+
+    let contract = SmartContract::new(TransferADASmartContract, ledger_client);
+
+    let calls = vec![
+        Endpoint::Transfer {
+            amount: 100,
+            recipient: alice.clone(),
+        },
+        Endpoint::Transfer {
+            amount: 200,
+            recipient: alice.clone(),
+        },
+    ];
+    contract.hit_endpoints_atomically(calls).await.unwrap();
+
+    let alice_outputs = contract
+        .ledger_client()
+        .all_outputs_at_address(&alice)
+        .await
+        .unwrap();
+    assert_eq!(alice_outputs.len(), 1);
+    assert_eq!(
+        alice_outputs[0].values().get(&PolicyId::Lovelace),
+        Some(300)
+    );
+}
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use naumachia::trireme_ledger_client::{
+    add_address_to_file,
+    get_current_client_config_from_file,
+    remove_address_from_file,
+    resolve_address_from_file,
+};
+
+pub(crate) async fn addresses_impl() -> Result<()> {
+    match get_current_client_config_from_file().await? {
+        Some(config) if !config.addresses().is_empty() => {
+            for (name, address) in config.addresses() {
+                println!("{name}: {address}");
+            }
+        }
+        _ => println!("No saved addresses"),
+    }
+    Ok(())
+}
+
+pub(crate) async fn add_address_impl(name: &str, address: &str) -> Result<()> {
+    add_address_to_file(name, address).await?;
+    println!("Saved {name}");
+    Ok(())
+}
+
+pub(crate) async fn remove_address_impl(name: &str) -> Result<()> {
+    remove_address_from_file(name).await?;
+    println!("Removed {name}");
+    Ok(())
+}
+
+pub(crate) async fn resolve_address_impl(name_or_address: &str) -> Result<()> {
+    let address = resolve_address_from_file(name_or_address).await?;
+    println!("{}", address.to_bech32()?);
+    Ok(())
+}
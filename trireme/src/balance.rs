@@ -50,7 +50,13 @@ pub(crate) async fn balance_impl() -> Result<()> {
                         ada_balance = lovelace_to_ada(amt as f64);
                     }
                     PolicyId::NativeToken(policy_id, asset_name) => {
-                        let asset = (policy_id, asset_name.unwrap_or(String::new()), amt);
+                        let name = asset_name
+                            .map(|name| {
+                                String::from_utf8(name)
+                                    .unwrap_or_else(|e| hex::encode(e.into_bytes()))
+                            })
+                            .unwrap_or_default();
+                        let asset = (policy_id, name, amt);
                         native_assets.push(asset);
                     }
                 }
@@ -6,6 +6,12 @@ use dialoguer::{
     Select,
 };
 use hex;
+#[cfg(feature = "keychain")]
+use naumachia::trireme_ledger_client::os_keychain::{
+    delete_secret_phrase_from_keychain,
+    store_secret_phrase_in_keychain,
+    DEFAULT_KEYCHAIN_SERVICE,
+};
 use naumachia::{
     ledger_client::{
         test_ledger_client::local_persisted_storage::LocalPersistedStorage,
@@ -14,17 +20,21 @@ use naumachia::{
     scripts::context::pub_key_hash_from_address_if_available,
     trireme_ledger_client::{
         cml_client::blockfrost_ledger::BlockfrostApiKey,
+        get_client_config_from_file,
         get_current_client_config_from_file,
         get_trireme_config_from_file,
         get_trireme_ledger_client_from_file,
+        keystore::import_secret_phrase,
         path_to_client_config_file,
         path_to_trireme_config_dir,
         path_to_trireme_config_file,
         read_toml_struct_from_file,
-        terminal_password_phrase::{
-            encrypt_phrase,
-            normalize_password,
+        secret_phrase::{
+            generate_secret_phrase,
+            validate_secret_phrase,
+            DerivationPath,
         },
+        switch_env_in_file,
         write_toml_struct_to_file,
         ClientConfig,
         ClientVariant,
@@ -36,7 +46,7 @@ use naumachia::{
     },
     Address,
 };
-use rand::Rng;
+use secrecy::Secret;
 use std::{
     path::PathBuf,
     str::FromStr,
@@ -46,6 +56,11 @@ use tokio::fs;
 #[derive(Clone, Copy)]
 pub enum EnvironmentType {
     Real,
+    RealWithLedgerDevice,
+    RealWithTrezorDevice,
+    RealWithBrowserWallet,
+    RealWatchOnly,
+    RealWithRemoteSigner,
     Mocked,
 }
 
@@ -53,6 +68,19 @@ impl ToString for EnvironmentType {
     fn to_string(&self) -> String {
         match self {
             EnvironmentType::Real => "Real Chain".to_string(),
+            EnvironmentType::RealWithLedgerDevice => {
+                "Real Chain (Ledger hardware wallet)".to_string()
+            }
+            EnvironmentType::RealWithTrezorDevice => {
+                "Real Chain (Trezor hardware wallet)".to_string()
+            }
+            EnvironmentType::RealWithBrowserWallet => {
+                "Real Chain (CIP-30 browser wallet)".to_string()
+            }
+            EnvironmentType::RealWatchOnly => "Real Chain (watch-only)".to_string(),
+            EnvironmentType::RealWithRemoteSigner => {
+                "Real Chain (remote signer)".to_string()
+            }
             EnvironmentType::Mocked => "Local Mocked".to_string(),
         }
     }
@@ -77,6 +105,27 @@ pub async fn new_env_impl() -> Result<()> {
 
     match get_env_type()? {
         EnvironmentType::Real => setup_password_protected_blockfrost_env(&name).await?,
+        EnvironmentType::RealWithLedgerDevice => {
+            setup_hardware_wallet_env(&name, |account, address_index| {
+                KeySource::LedgerHardwareWallet {
+                    account,
+                    address_index,
+                }
+            })
+            .await?
+        }
+        EnvironmentType::RealWithTrezorDevice => {
+            setup_hardware_wallet_env(&name, |account, address_index| {
+                KeySource::TrezorHardwareWallet {
+                    account,
+                    address_index,
+                }
+            })
+            .await?
+        }
+        EnvironmentType::RealWithBrowserWallet => setup_browser_wallet_env(&name).await?,
+        EnvironmentType::RealWatchOnly => setup_watch_only_env(&name).await?,
+        EnvironmentType::RealWithRemoteSigner => setup_remote_signer_env(&name).await?,
         EnvironmentType::Mocked => setup_local_mocked_env(&name).await?,
     }
 
@@ -90,7 +139,15 @@ pub async fn new_env_impl() -> Result<()> {
 }
 
 fn get_env_type() -> Result<EnvironmentType> {
-    let items = vec![EnvironmentType::Mocked, EnvironmentType::Real];
+    let items = vec![
+        EnvironmentType::Mocked,
+        EnvironmentType::Real,
+        EnvironmentType::RealWithLedgerDevice,
+        EnvironmentType::RealWithTrezorDevice,
+        EnvironmentType::RealWithBrowserWallet,
+        EnvironmentType::RealWatchOnly,
+        EnvironmentType::RealWithRemoteSigner,
+    ];
     let item_index = Select::new()
         .with_prompt("What kind of environment?")
         .items(&items)
@@ -105,45 +162,188 @@ fn get_env_type() -> Result<EnvironmentType> {
 async fn setup_password_protected_blockfrost_env(name: &str) -> Result<()> {
     let ledger_source = get_ledger_source(name).await?;
 
-    let secret_phrase: String = Input::new()
-        .with_prompt("⚠️  Insert testnet secret phrase ⚠️  ")
-        .interact_text()?;
+    let secret_phrase = get_secret_phrase()?;
+    let key_source = store_secret_phrase(&secret_phrase, name).await?;
+
+    // TODO: Do a prompt or derive network from api key
+    let network = Network::Preprod;
+
+    let client_config = ClientConfig::new_cml(name, ledger_source, key_source, network);
+    let file_path = path_to_client_config_file(name)?;
+    write_toml_struct_to_file(&file_path, &client_config).await?;
+    Ok(())
+}
 
-    let password = get_password_with_prompt("Enter password")?;
-    let mut confirmed_password = get_password_with_prompt("Confirm password")?;
+#[cfg(feature = "keychain")]
+async fn store_secret_phrase(secret_phrase: &str, name: &str) -> Result<KeySource> {
+    let items = vec!["Encrypted keystore file", "OS keychain"];
+    let index = Select::new()
+        .with_prompt("Where should the secret phrase be stored?")
+        .items(&items)
+        .interact()?;
+    if index == 0 {
+        store_secret_phrase_in_keystore(secret_phrase, name).await
+    } else {
+        store_secret_phrase_in_keychain(secret_phrase, DEFAULT_KEYCHAIN_SERVICE, name)?;
+        let derivation_path = get_derivation_path()?;
+        Ok(KeySource::OsKeychain {
+            service: DEFAULT_KEYCHAIN_SERVICE.to_string(),
+            account: name.to_string(),
+            derivation_path,
+        })
+    }
+}
+
+#[cfg(not(feature = "keychain"))]
+async fn store_secret_phrase(secret_phrase: &str, name: &str) -> Result<KeySource> {
+    store_secret_phrase_in_keystore(secret_phrase, name).await
+}
+
+async fn store_secret_phrase_in_keystore(
+    secret_phrase: &str,
+    name: &str,
+) -> Result<KeySource> {
+    let password = get_password_with_prompt("Enter keystore passphrase")?;
+    let mut confirmed_password = get_password_with_prompt("Confirm passphrase")?;
 
     while password != confirmed_password {
         println!("Try again");
-        confirmed_password = get_password_with_prompt("Confirm password")?;
+        confirmed_password = get_password_with_prompt("Confirm passphrase")?;
+    }
+
+    let keystore_path = write_encrypted_keystore(secret_phrase, name, password).await?;
+    let derivation_path = get_derivation_path()?;
+    Ok(KeySource::EncryptedKeystore {
+        keystore_file: keystore_path,
+        derivation_path,
+    })
+}
+
+fn get_secret_phrase() -> Result<String> {
+    let items = vec![
+        "Generate a new secret phrase",
+        "Import an existing secret phrase",
+    ];
+    let index = Select::new()
+        .with_prompt("Which secret phrase?")
+        .items(&items)
+        .interact()?;
+    if index == 0 {
+        let secret_phrase = generate_secret_phrase();
+        println!();
+        println!("📝 Write down your new secret phrase and keep it somewhere safe:");
+        println!();
+        println!("{secret_phrase}");
+        println!();
+        Ok(secret_phrase)
+    } else {
+        let secret_phrase: String = Input::new()
+            .with_prompt("⚠️  Insert testnet secret phrase ⚠️  ")
+            .validate_with(|input: &String| -> std::result::Result<(), String> {
+                validate_secret_phrase(input).map_err(|e| e.to_string())
+            })
+            .interact_text()?;
+        Ok(secret_phrase)
     }
+}
+
+fn get_derivation_path() -> Result<DerivationPath> {
+    let derivation_path: String = Input::new()
+        .with_prompt("Derivation path of your signing key")
+        .default(DerivationPath::default().to_string())
+        .validate_with(|input: &String| -> std::result::Result<(), String> {
+            input
+                .parse::<DerivationPath>()
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })
+        .interact_text()?;
+    Ok(derivation_path.parse()?)
+}
+
+async fn setup_hardware_wallet_env(
+    name: &str,
+    key_source: impl Fn(u32, u32) -> KeySource,
+) -> Result<()> {
+    let ledger_source = get_ledger_source(name).await?;
 
-    // TODO: Is `rand` good enough for salt?
-    let salt = rand::thread_rng().gen::<[u8; 32]>();
+    let account: u32 = Input::new()
+        .with_prompt("Which account on the device?")
+        .default(0)
+        .interact_text()?;
+    let address_index: u32 = Input::new()
+        .with_prompt("Which address index in the account?")
+        .default(0)
+        .interact_text()?;
+    // TODO: Do a prompt or derive network from api key
+    let network = Network::Preprod;
 
-    let normalized_password = normalize_password(&password, &salt)?;
+    let key_source = key_source(account, address_index);
+    let client_config = ClientConfig::new_cml(name, ledger_source, key_source, network);
+    let file_path = path_to_client_config_file(name)?;
+    write_toml_struct_to_file(&file_path, &client_config).await?;
+    Ok(())
+}
 
-    let encryption_nonce = rand::thread_rng().gen::<[u8; 12]>();
+async fn setup_browser_wallet_env(name: &str) -> Result<()> {
+    let ledger_source = get_ledger_source(name).await?;
 
-    let secret_phrase_path = write_secret_phrase_with_password(
-        &secret_phrase,
-        name,
-        &normalized_password,
-        &encryption_nonce,
-    )
-    .await?;
+    let port: u16 = Input::new()
+        .with_prompt("Local port for the wallet bridge page")
+        .default(8090)
+        .interact_text()?;
     // TODO: Do a prompt or derive network from api key
     let network = Network::Preprod;
 
-    write_cml_client_config_with_password_protection(
-        &name,
-        &name,
-        ledger_source,
-        secret_phrase_path,
-        salt.to_vec(),
-        encryption_nonce,
-        network,
-    )
-    .await?;
+    let key_source = KeySource::Cip30BrowserWallet { port };
+    let client_config = ClientConfig::new_cml(name, ledger_source, key_source, network);
+    let file_path = path_to_client_config_file(name)?;
+    write_toml_struct_to_file(&file_path, &client_config).await?;
+    Ok(())
+}
+
+async fn setup_watch_only_env(name: &str) -> Result<()> {
+    let ledger_source = get_ledger_source(name).await?;
+
+    let items = vec!["Base address", "Account extended public key"];
+    let index = Select::new()
+        .with_prompt("What do you want to watch?")
+        .items(&items)
+        .interact()?;
+    let key_source = if index == 0 {
+        let address: String = Input::new()
+            .with_prompt("Bech32 base address")
+            .interact_text()?;
+        KeySource::WatchOnlyAddress { address }
+    } else {
+        let account_xpub: String = Input::new()
+            .with_prompt("Account extended public key (acct_xvk... or hex)")
+            .interact_text()?;
+        KeySource::WatchOnlyXpub { account_xpub }
+    };
+    // TODO: Do a prompt or derive network from api key
+    let network = Network::Preprod;
+
+    let client_config = ClientConfig::new_cml(name, ledger_source, key_source, network);
+    let file_path = path_to_client_config_file(name)?;
+    write_toml_struct_to_file(&file_path, &client_config).await?;
+    Ok(())
+}
+
+async fn setup_remote_signer_env(name: &str) -> Result<()> {
+    let ledger_source = get_ledger_source(name).await?;
+
+    let url: String = Input::new()
+        .with_prompt("Remote signer URL")
+        .interact_text()?;
+    println!("Set TRIREME_REMOTE_SIGNER_TOKEN if the signer requires a bearer token");
+    // TODO: Do a prompt or derive network from api key
+    let network = Network::Preprod;
+
+    let key_source = KeySource::RemoteSigner { url };
+    let client_config = ClientConfig::new_cml(name, ledger_source, key_source, network);
+    let file_path = path_to_client_config_file(name)?;
+    write_toml_struct_to_file(&file_path, &client_config).await?;
     Ok(())
 }
 
@@ -260,22 +460,49 @@ async fn setup_local_mocked_env(name: &str) -> Result<()> {
     Ok(())
 }
 
-pub async fn switch_env_impl() -> Result<()> {
-    match get_trireme_config_from_file().await? {
-        Some(mut config) => {
+pub async fn switch_env_impl(name: Option<String>) -> Result<()> {
+    let name = match name {
+        Some(name) => name,
+        None => {
+            let config = get_trireme_config_from_file()
+                .await?
+                .ok_or_else(|| Error::CLI("Environment doesn't exist".to_string()))?;
             let items = config.envs();
             let index = Select::new()
                 .with_prompt("To which environment?")
                 .items(&items)
                 .interact()?;
-            let name = items.get(index).expect("should always be a valid index");
-            config.switch_env(&name)?;
-            write_trireme_config(&config).await?;
-            println!("Switched environment to: {}", &name);
-            Ok(())
+            items
+                .get(index)
+                .expect("should always be a valid index")
+                .to_owned()
         }
-        None => Err(Error::CLI("Environment doesn't exist".to_string()).into()),
+    };
+    switch_env_in_file(&name).await?;
+    println!("Switched environment to: {}", &name);
+    Ok(())
+}
+
+pub async fn envs_impl() -> Result<()> {
+    match get_trireme_config_from_file().await? {
+        Some(config) => {
+            let current = config.current_env();
+            for name in config.envs() {
+                let marker = if current.as_ref() == Some(&name) {
+                    "*"
+                } else {
+                    " "
+                };
+                let summary = get_client_config_from_file(&name)
+                    .await?
+                    .map(|client_config| client_config.summary())
+                    .unwrap_or_else(|| "missing config".to_string());
+                println!("{marker} {name} ({summary})");
+            }
+        }
+        None => println!("No environment set"),
     }
+    Ok(())
 }
 
 pub async fn remove_env_impl() -> Result<()> {
@@ -291,6 +518,8 @@ pub async fn remove_env_impl() -> Result<()> {
                 .with_prompt("Type in name of env to confirm")
                 .interact_text()?;
             if &confirmation_name == name {
+                #[cfg(feature = "keychain")]
+                delete_keychain_entry(name).await?;
                 config.remove_env(&name)?;
                 write_trireme_config(&config).await?;
                 delete_directory(&name).await?;
@@ -312,6 +541,9 @@ pub async fn env_impl() -> Result<()> {
     {
         println!("Current Environment:");
         println!("{}", env);
+        if let Some(client_config) = get_client_config_from_file(&env).await? {
+            println!("{}", client_config.summary());
+        }
     } else {
         println!("No environment set");
     }
@@ -323,7 +555,9 @@ fn print_safety_warning() {
     println!("⚠️  Only use keys you are willing to loose funds from, preferably ");
     println!("⚠️  only with funds on testnet!");
     println!("⚠️  Trireme only works on Testnet currently!");
-    println!("⚠️  Keys will be stored in plaintext files on your computer!");
+    println!(
+        "⚠️  Secret phrases are stored on your computer, encrypted with your passphrase!"
+    );
     println!();
 }
 
@@ -446,19 +680,17 @@ pub async fn advance_blocks(count: i64) -> Result<()> {
     Ok(())
 }
 
-const RAW_PHRASE_FILE: &str = "secret_phrase.toml";
+const KEYSTORE_FILE: &str = "keystore.toml";
 
-async fn write_secret_phrase_with_password(
+async fn write_encrypted_keystore(
     phrase: &str,
     sub_dir: &str,
-    password: &[u8; 32],
-    encryption_nonce: &[u8; 12],
+    passphrase: String,
 ) -> Result<PathBuf> {
     let mut file_path = path_to_trireme_config_dir()?;
     file_path.push(sub_dir);
-    file_path.push(RAW_PHRASE_FILE);
-    let phrase_struct = encrypt_phrase(phrase, password, encryption_nonce);
-    write_toml_struct_to_file(&file_path, &phrase_struct).await?;
+    file_path.push(KEYSTORE_FILE);
+    import_secret_phrase(phrase, &Secret::new(passphrase), &file_path).await?;
     Ok(file_path)
 }
 
@@ -479,23 +711,19 @@ async fn write_trireme_config(trireme_config: &TriremeConfig) -> Result<()> {
     Ok(())
 }
 
-async fn write_cml_client_config_with_password_protection(
-    name: &str,
-    sub_dir: &str,
-    ledger_source: LedgerSource,
-    phrase_file: PathBuf,
-    password_salt: Vec<u8>,
-    encrpytion_nonce: [u8; 12],
-    network: Network,
-) -> Result<()> {
-    let key_source = KeySource::TerminalPasswordUpfrontSecretPhrase {
-        phrase_file,
-        password_salt,
-        encrpytion_nonce,
-    };
-    let client_config = ClientConfig::new_cml(name, ledger_source, key_source, network);
-    let file_path = path_to_client_config_file(sub_dir)?;
-    write_toml_struct_to_file(&file_path, &client_config).await?;
+#[cfg(feature = "keychain")]
+async fn delete_keychain_entry(name: &str) -> Result<()> {
+    if let Some(ClientVariant::CML(inner)) = get_client_config_from_file(name)
+        .await?
+        .map(|config| config.variant())
+    {
+        if let KeySource::OsKeychain {
+            service, account, ..
+        } = inner.key_source()
+        {
+            delete_secret_phrase_from_keychain(service, account)?;
+        }
+    }
     Ok(())
 }
 
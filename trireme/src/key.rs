@@ -0,0 +1,39 @@
+use anyhow::Result;
+use naumachia::{
+    ledger_client::LedgerClient,
+    trireme_ledger_client::{
+        get_trireme_ledger_client_from_file,
+        secret_phrase::generate_secret_phrase,
+        TriremeLedgerClient,
+    },
+};
+use serde_json::json;
+
+// CBOR header of a 32 byte bytestring, as used by cardano-cli key files
+const VKEY_CBOR_PREFIX: &str = "5820";
+
+pub(crate) async fn generate_key_impl() -> Result<()> {
+    let secret_phrase = generate_secret_phrase();
+    println!("📝 Write down your new secret phrase and keep it somewhere safe:");
+    println!();
+    println!("{secret_phrase}");
+    println!();
+    println!("Use `trireme new-env` to import it into an environment.");
+    Ok(())
+}
+
+pub(crate) async fn export_key_impl() -> Result<()> {
+    let ledger_client: TriremeLedgerClient<(), ()> =
+        get_trireme_ledger_client_from_file().await?;
+    let public_key = ledger_client.signer_payment_public_key().await?;
+    let address = ledger_client.signer_base_address().await?;
+    let envelope = json!({
+        "type": "PaymentVerificationKeyShelley_ed25519",
+        "description": "Payment Verification Key",
+        "cborHex": format!("{VKEY_CBOR_PREFIX}{}", hex::encode(public_key.as_bytes())),
+    });
+    println!("{}", serde_json::to_string_pretty(&envelope)?);
+    println!();
+    println!("Address: {}", address.to_bech32()?);
+    Ok(())
+}
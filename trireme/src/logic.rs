@@ -10,7 +10,6 @@ use naumachia::{
     },
     policy_id::PolicyId,
     transaction::TxActions,
-    values::Values,
 };
 
 #[derive(Debug, Eq, PartialEq)]
@@ -76,12 +75,11 @@ async fn impl_total_balance<LC: LedgerClient<(), ()>>(
         .signer_base_address()
         .await
         .map_err(as_lookup_err)?;
-    let outputs = ledger_client
-        .all_outputs_at_address(&address)
+    let total_value = ledger_client
+        .balances_at_address(&address)
         .await
-        .map_err(as_lookup_err)?;
-
-    let total_value = Values::from_outputs(&outputs).vec();
+        .map_err(as_lookup_err)?
+        .vec();
     let response = TriremeResponses::TotalBalance(total_value);
     Ok(response)
 }
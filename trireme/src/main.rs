@@ -1,4 +1,10 @@
 use crate::{
+    address_book::{
+        add_address_impl,
+        addresses_impl,
+        remove_address_impl,
+        resolve_address_impl,
+    },
     balance::{
         ada_balance_impl,
         balance_impl,
@@ -8,6 +14,7 @@ use crate::{
         advance_blocks,
         current_time_impl,
         env_impl,
+        envs_impl,
         get_address_impl,
         get_pubkey_hash_impl,
         last_block_time_impl,
@@ -16,19 +23,27 @@ use crate::{
         switch_env_impl,
         switch_signer_impl,
     },
+    key::{
+        export_key_impl,
+        generate_key_impl,
+    },
     logic::{
         TriremeLogic,
         TriremeLookups,
         TriremeResponses,
     },
+    tx::inspect_tx_impl,
 };
 use anyhow::Result;
 use clap::Parser;
 use thiserror::Error;
 
+mod address_book;
 mod balance;
 mod environment;
+mod key;
 mod logic;
+mod tx;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -43,8 +58,13 @@ enum ActionParams {
     Env,
     /// Create a new environment 🚣
     NewEnv,
+    /// List all environments 🗺
+    Envs,
     /// Switch Environments ⛵
-    SwitchEnv,
+    SwitchEnv {
+        /// Name of the environment to switch to. Prompts if omitted
+        name: Option<String>,
+    },
     /// Remove Env 🌀
     RemoveEnv,
     /// Get ADA Balance ₳
@@ -65,6 +85,47 @@ enum ActionParams {
     LastBlockTime,
     /// Advance time and block height by count 🧱
     AdvanceBlocks { count: u16 },
+    /// Key management 🔑
+    Key {
+        #[clap(subcommand)]
+        action: KeyParams,
+    },
+    /// Transaction tools 🔍
+    Tx {
+        #[clap(subcommand)]
+        action: TxParams,
+    },
+    /// Named addresses of the current environment 📒
+    AddressBook {
+        #[clap(subcommand)]
+        action: AddressBookParams,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum KeyParams {
+    /// Generate a new secret phrase
+    Generate,
+    /// Export the signer's payment verification key and address
+    Export,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum TxParams {
+    /// Decode a transaction from its hex encoded CBOR or its id
+    Inspect { tx: String },
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum AddressBookParams {
+    /// List saved addresses
+    List,
+    /// Save a bech32 address under a name, e.g. `treasury`
+    Add { name: String, address: String },
+    /// Remove a saved address
+    Remove { name: String },
+    /// Print the address for a saved name or a raw address
+    Resolve { name: String },
 }
 
 #[derive(Debug, Error)]
@@ -81,7 +142,8 @@ async fn main() -> Result<()> {
     match args.action {
         ActionParams::Env => env_impl().await?,
         ActionParams::NewEnv => new_env_impl().await?,
-        ActionParams::SwitchEnv => switch_env_impl().await?,
+        ActionParams::Envs => envs_impl().await?,
+        ActionParams::SwitchEnv { name } => switch_env_impl(name).await?,
         ActionParams::RemoveEnv => remove_env_impl().await?,
         ActionParams::AdaBalance => ada_balance_impl().await?,
         ActionParams::Balance => balance_impl().await?,
@@ -92,6 +154,21 @@ async fn main() -> Result<()> {
         ActionParams::Time => current_time_impl().await?,
         ActionParams::LastBlockTime => last_block_time_impl().await?,
         ActionParams::AdvanceBlocks { count } => advance_blocks(count as i64).await?,
+        ActionParams::Key { action } => match action {
+            KeyParams::Generate => generate_key_impl().await?,
+            KeyParams::Export => export_key_impl().await?,
+        },
+        ActionParams::Tx { action } => match action {
+            TxParams::Inspect { tx } => inspect_tx_impl(&tx).await?,
+        },
+        ActionParams::AddressBook { action } => match action {
+            AddressBookParams::List => addresses_impl().await?,
+            AddressBookParams::Add { name, address } => {
+                add_address_impl(&name, &address).await?
+            }
+            AddressBookParams::Remove { name } => remove_address_impl(&name).await?,
+            AddressBookParams::Resolve { name } => resolve_address_impl(&name).await?,
+        },
     }
     Ok(())
 }
@@ -0,0 +1,27 @@
+use anyhow::Result;
+use naumachia::{
+    transaction::TxId,
+    trireme_ledger_client::{
+        cml_client::tx_inspect::{
+            inspect_tx,
+            InspectedTx,
+        },
+        get_trireme_ledger_client_from_file,
+        TriremeLedgerClient,
+    },
+};
+
+const TX_ID_HEX_LEN: usize = 64;
+
+pub(crate) async fn inspect_tx_impl(tx: &str) -> Result<()> {
+    let inspected: InspectedTx<(), ()> = if tx.len() == TX_ID_HEX_LEN {
+        let ledger_client: TriremeLedgerClient<(), ()> =
+            get_trireme_ledger_client_from_file().await?;
+        ledger_client.inspect_tx(&TxId::new(tx)).await?
+    } else {
+        let cbor = hex::decode(tx.trim())?;
+        inspect_tx(&cbor)?
+    };
+    println!("{inspected:#?}");
+    Ok(())
+}